@@ -0,0 +1,45 @@
+//! The throughput of a blackboard under bursts of events, its worker applying them one by one or in batches.
+//!
+//! `cargo bench --bench batching`
+
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use bacht::blackboard::{Blackboard, BlackboardTrait};
+use bacht::blackboard::store::{Store, StoreTrait};
+use bacht::blackboard::task_queue::TaskQueue;
+use bacht::blackboard::worker::{Worker, WorkerOptions};
+use bacht::model::token::TokenId;
+
+const AGENTS: usize = 64;
+const OPERATIONS: usize = 2_000;
+
+// Run AGENTS agents sending OPERATIONS primitives each as fast as answered, a mix of tells, asks and gets
+async fn run(batch_size: usize) -> Duration {
+    let options = WorkerOptions { batch_size, ..WorkerOptions::default() };
+    let blackboard: Blackboard<TaskQueue, Worker, Store> = Blackboard::new_with_options(Store::new(), options);
+    let start = Instant::now();
+    let mut agents = JoinSet::new();
+    for agent in 0..AGENTS {
+        let blackboard = blackboard.clone();
+        agents.spawn(async move {
+            for operation in 0..OPERATIONS {
+                let token = TokenId::from(format!("token{}", (agent + operation) % 16));
+                let _ = match operation % 3 {
+                    0 => blackboard.tell(token).await,
+                    1 => blackboard.ask(token).await,
+                    _ => blackboard.get(token).await,
+                };
+            }
+        });
+    }
+    agents.join_all().await;
+    start.elapsed()
+}
+
+#[tokio::main]
+async fn main() {
+    for batch_size in [1, 8, 32, 128] {
+        let elapsed = run(batch_size).await.as_secs_f64();
+        println!("batch {:<4} {:>10.0} events/s", batch_size, (AGENTS * OPERATIONS) as f64 / elapsed);
+    }
+}
@@ -0,0 +1,56 @@
+//! The throughput of the frames through a byte stream, with a buffer allocated for each frame or reused from frame to
+//! frame, as the connections of the transport do.
+//!
+//! Only the pooling of the buffers is measured: in both cases the decoded frame owns a copy of its tokens.
+//!
+//! `cargo bench --bench frames`
+
+use std::time::{Duration, Instant};
+use bytes::BytesMut;
+use tokio::io::duplex;
+use bacht::communication::compression::Compression;
+use bacht::communication::frame::{read_frame, read_frame_into, write_frame_with, write_frame_into, Frame};
+use bacht::model::action::Action;
+
+const FRAMES: u64 = 200_000;
+
+// Write and read back FRAMES copies of the frame, through fresh buffers or reused ones
+async fn run(frame: &Frame, reuse: bool) -> Duration {
+    let (mut client, mut server) = duplex(64 * 1024);
+    let start = Instant::now();
+    let writer = async {
+        let mut buffer = Vec::new();
+        for id in 0..FRAMES {
+            match reuse {
+                true => write_frame_into(&mut client, id, frame, Compression::None, &mut buffer).await.unwrap(),
+                false => write_frame_with(&mut client, id, frame, Compression::None).await.unwrap(),
+            }
+        }
+    };
+    let reader = async {
+        let mut buffer = BytesMut::new();
+        for _ in 0..FRAMES {
+            match reuse {
+                true => read_frame_into(&mut server, &mut buffer).await.unwrap().unwrap(),
+                false => read_frame(&mut server).await.unwrap().unwrap(),
+            };
+        }
+    };
+    tokio::join!(writer, reader);
+    start.elapsed()
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let frames = [
+        ("request", Frame::Request(Action::Tell("temperature(21)".into()))),
+        ("batch", Frame::Batch((0..64).map(|i| Action::Tell(format!("token{}", i).into())).collect())),
+    ];
+    for (name, frame) in frames {
+        let size = frame.encode().len() as f64;
+        for (label, reuse) in [("allocated", false), ("reused", true)] {
+            let elapsed = run(&frame, reuse).await.as_secs_f64();
+            println!("{:<8} {:<10} {:>10.0} frames/s {:>8.1} MB/s", name, label, FRAMES as f64 / elapsed, FRAMES as f64 * size / elapsed / 1e6);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+// Generate the gRPC service of proto/bacht.proto, with the grpc feature only
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/bacht.proto");
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().expect("protoc is bundled for this platform"));
+        tonic_prost_build::compile_protos("proto/bacht.proto").expect("proto/bacht.proto should compile");
+    }
+}
@@ -0,0 +1,254 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+use bacht::config::Config;
+use bacht::log::Level;
+use clap::{ArgAction, Parser, Subcommand};
+use crate::bench::BenchConfig;
+
+/// What the CLI does once started
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum Command {
+    /// Read the agents typed, the default
+    Repl,
+    /// Run the agents of a script, then print the final store
+    Run { script: PathBuf },
+    /// Parse the agents of a script without running them
+    Check { script: PathBuf },
+    /// Enter again the lines recorded with :record, on a fresh blackboard
+    Replay { recording: PathBuf },
+    /// Run an agent, printing its transitions as a JSON conformance trace
+    Export { agent: String },
+    /// Check that the interpreter takes the transitions of a conformance trace, e.g. exported by the reference
+    /// implementation in Scala
+    Conform {
+        // Apart from the option --trace
+        #[arg(id = "trace_file", value_name = "TRACE")]
+        trace: PathBuf,
+    },
+    /// Serve the blackboard to the remote agents and clients, or a REPL session to each telnet or netcat client with
+    /// --interactive
+    Serve,
+    /// Measure the blackboard under a synthetic workload
+    Bench,
+    /// Load the blackboard with producers and consumers at steady rates, then print the throughput it sustained
+    Loadgen,
+    /// Monitor the store, queue, peers and changes of the blackboard, serving it unless --connect is given
+    Dashboard,
+    /// Serve a web page running the agents typed, on the port 8080 by default
+    Playground,
+    /// Walk through canned examples of the language, with explanations
+    Demo,
+}
+
+/// @summary - The command line of the CLI, its subcommand and the options configuring it, accepted before and after
+/// the subcommand
+#[derive(Debug, Clone, PartialEq, Parser)]
+#[command(name = "bach_cli", version, about = "Run the agents of the BachT language on a blackboard, in-process or remote")]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The settings of the blackboard served, BACHT_CONFIG or bacht.toml by default
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Run the agents on a remote blackboard node instead
+    #[arg(long, global = true, value_name = "HOST:PORT")]
+    pub connect: Option<String>,
+    /// The key presented to the node connected to, so that it accepts :store, :clear and the other commands managing
+    /// its store, and required from the administrators of the blackboard served; node.admin_key by default
+    #[arg(long, global = true, value_name = "KEY")]
+    pub admin_key: Option<String>,
+    /// The port served, 2138 by default
+    #[arg(long, global = true)]
+    pub port: Option<u16>,
+    /// The address served instead of the IPv4 loopback, e.g. ::
+    #[arg(long, global = true, value_name = "ADDRESS")]
+    pub bind: Option<IpAddr>,
+    /// Fill the store with the tokens of a file, one `token [count]` per line
+    #[arg(long, global = true, value_name = "FILE")]
+    pub store: Option<PathBuf>,
+    /// Pick the branches of the agents reproducibly
+    #[arg(long, global = true, value_name = "N")]
+    pub seed: Option<u64>,
+    /// The agents of the benchmark
+    #[arg(long, global = true, value_name = "N", default_value_t = BenchConfig::default().agents)]
+    agents: usize,
+    /// The distinct tokens of the benchmark and of loadgen
+    #[arg(long, global = true, value_name = "N", default_value_t = BenchConfig::default().tokens)]
+    tokens: usize,
+    /// The primitives run by each agent of the benchmark
+    #[arg(long, global = true, value_name = "N", default_value_t = BenchConfig::default().operations)]
+    operations: usize,
+    /// The weights of the primitives of the benchmark, tell=4,ask=2,get=3,nask=1 by default
+    #[arg(long, global = true, value_name = "WEIGHTS", value_parser = parse_mix)]
+    mix: Option<[u32; 4]>,
+    /// The producers of loadgen with the tokens each one tells per second
+    #[arg(long, global = true, value_name = "N@RATE", default_value = "4@100", value_parser = parse_population)]
+    pub producers: (usize, f64),
+    /// The consumers of loadgen with the tokens each one gets per second
+    #[arg(long, global = true, value_name = "N@RATE", default_value = "4@100", value_parser = parse_population)]
+    pub consumers: (usize, f64),
+    /// How long loadgen runs, in seconds
+    #[arg(long, global = true, value_name = "SECONDS", default_value_t = 10)]
+    pub duration: u64,
+    /// The prompt, where {tokens}, {occurrences} and {queued} are replaced by the counters of the blackboard, e.g.
+    /// "[{tokens} tokens|{queued} queued]> "
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    pub prompt: Option<String>,
+    /// Serve the REPL sessions instead of the blackboard protocol, on the port 2139 by default
+    #[arg(long, global = true)]
+    pub interactive: bool,
+    /// Only parse the agents of the REPL or of run, printing them with each operator parenthesized and as a tree,
+    /// without running them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Also accept the primitives of Linda in the agents: out, rd and in as tell, ask and get, inp and rdp as the get
+    /// and ask that never block
+    #[arg(long, global = true)]
+    pub linda: bool,
+    /// Print each primitive attempted by the agents
+    #[arg(long, global = true)]
+    pub trace: bool,
+    /// Print the time taken by each agent, with its transitions and blocked attempts
+    #[arg(long, global = true)]
+    pub timing: bool,
+    /// Never color the output
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    /// Only print the errors of the blackboard, not the addresses listened on
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also print its connections and the activity of its worker, with -vv each event handled and each primitive
+    /// attempted
+    #[arg(short, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// How much detail of the blackboard reaches the terminal, set from -q and -v or from the configuration
+    #[arg(skip = Level::Info)]
+    pub log_level: Level,
+}
+
+impl Args {
+
+    /// @returns - The subcommand given, the REPL by default
+    pub fn command(&self) -> Command {
+        self.command.clone().unwrap_or(Command::Repl)
+    }
+
+    /// @returns - The workload of the benchmark, seeded with --seed
+    pub fn bench(&self) -> BenchConfig {
+        BenchConfig {
+            agents: self.agents,
+            tokens: self.tokens,
+            operations: self.operations,
+            mix: self.mix.unwrap_or(BenchConfig::default().mix),
+            seed: self.seed.unwrap_or_default(),
+        }
+    }
+
+    /// @summary - Complete the options with the settings of a configuration file: the port and the address served, the
+    /// admin key and the level, the options given taking precedence
+    ///
+    /// @returns - The options, or the reason why a setting is invalid
+    pub fn with_config(mut self, config: &Config) -> Result<Self, String> {
+        self.port = self.port.or(config.parse_value("listen.port").map_err(|e| e.to_string())?);
+        self.bind = self.bind.or(config.parse_value("listen.bind").map_err(|e| e.to_string())?);
+        self.admin_key = self.admin_key.or(config.get("node.admin_key").map(String::from));
+        self.log_level = match (self.quiet, self.verbose) {
+            (true, _) => Level::Error,
+            // No option sets the default level
+            (false, 0) => config.parse_value("log.level").map_err(|e| e.to_string())?.unwrap_or(Level::Info),
+            (false, 1) => Level::Debug,
+            (false, _) => Level::Trace,
+        };
+        Ok(self)
+    }
+}
+
+// The weights of the primitives, e.g. tell=4,ask=2
+fn parse_mix(value: &str) -> Result<[u32; 4], String> {
+    BenchConfig::default().with_mix(value).map(|bench| bench.mix)
+}
+
+// The agents of a population and the primitives per second of each one, e.g. 4@100
+fn parse_population(value: &str) -> Result<(usize, f64), String> {
+    let (agents, rate) = value.split_once('@').ok_or("expected agents@rate")?;
+    let agents = agents.parse().map_err(|_| format!("invalid number of agents {}", agents))?;
+    match rate.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate >= 0.0 => Ok((agents, rate)),
+        _ => Err(format!("invalid rate {}", rate)),
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+
+    fn parse(args: &str) -> Result<Args, clap::Error> {
+        Args::try_parse_from(std::iter::once("bach_cli").chain(args.split_whitespace()))
+    }
+
+    fn level(args: &str) -> Level {
+        parse(args).unwrap().with_config(&Config::parse("").unwrap()).unwrap().log_level
+    }
+
+    #[test]
+    fn args_should_parse_the_subcommands_with_their_options_anywhere() {
+        assert_eq!(parse("").unwrap().command(), Command::Repl);
+        let args = parse("repl --trace --timing").unwrap();
+        assert_eq!((args.command(), args.trace, args.timing), (Command::Repl, true, true));
+        let args = parse("--no-color run agents.bacht --seed 7 --store tokens.txt").unwrap();
+        assert_eq!(args.command(), Command::Run { script: "agents.bacht".into() });
+        assert_eq!((args.seed, args.store, args.no_color, args.trace), (Some(7), Some("tokens.txt".into()), true, false));
+        assert!(parse("run agents.bacht --dry-run").unwrap().dry_run);
+        assert!(parse("--linda check agents.bacht").unwrap().linda);
+        let args = parse("serve --port 4000 --bind ::").unwrap();
+        assert_eq!((args.command(), args.port, args.bind), (Command::Serve, Some(4000), Some("::".parse().unwrap())));
+        assert!(parse("serve --interactive").unwrap().interactive);
+        assert_eq!((level(""), level("-q"), level("serve -v"), level("serve -vv")), (Level::Info, Level::Error, Level::Debug, Level::Trace));
+        assert_eq!(parse("check agents.bacht").unwrap().command(), Command::Check { script: "agents.bacht".into() });
+        assert_eq!(parse("replay session.tsv").unwrap().command(), Command::Replay { recording: "session.tsv".into() });
+        assert_eq!(parse("export tell(a);get(a) --seed 2").unwrap().command(), Command::Export { agent: "tell(a);get(a)".into() });
+        assert_eq!(parse("conform trace.json").unwrap().command(), Command::Conform { trace: "trace.json".into() });
+        let args = parse("bench --agents 2 --mix get=1 --seed 3").unwrap();
+        assert_eq!(args.command(), Command::Bench);
+        assert_eq!(args.bench(), BenchConfig { agents: 2, mix: [0, 0, 1, 0], seed: 3, ..BenchConfig::default() });
+        assert_eq!(parse("bench").unwrap().bench(), BenchConfig::default());
+        assert_eq!(parse("dashboard --connect localhost:2138").unwrap().connect, Some("localhost:2138".to_string()));
+        assert_eq!(parse("--connect localhost:2138 --admin-key secret").unwrap().admin_key, Some("secret".to_string()));
+        let args = parse("loadgen --producers 2@50 --consumers 3@12.5 --duration 30").unwrap();
+        assert_eq!((args.command(), args.producers, args.consumers, args.duration), (Command::Loadgen, (2, 50.0), (3, 12.5), 30));
+        assert_eq!(parse("loadgen").unwrap().producers, (4, 100.0));
+    }
+
+    #[test]
+    fn args_should_take_the_settings_not_given_from_the_config() {
+        let config = Config::parse("[listen]\nport = 4000\nbind = \"::\"\n[log]\nlevel = \"debug\"\n[node]\nadmin_key = \"secret\"").unwrap();
+        let args = parse("serve --config node.toml --port 5000").unwrap();
+        assert_eq!(args.config, Some("node.toml".into()));
+        let args = args.with_config(&config).unwrap();
+        assert_eq!((args.port, args.bind, args.log_level), (Some(5000), Some("::".parse().unwrap()), Level::Debug));
+        assert_eq!(parse("-q").unwrap().with_config(&config).unwrap().log_level, Level::Error);
+        assert_eq!(args.admin_key, Some("secret".to_string()));
+        assert_eq!(parse("--admin-key other").unwrap().with_config(&config).unwrap().admin_key, Some("other".to_string()));
+        assert_eq!(parse("").unwrap().with_config(&config.with("listen.port", "x")).unwrap_err(), "invalid value x of listen.port");
+    }
+
+    #[test]
+    fn args_should_reject_the_invalid_command_lines() {
+        let kind = |args| parse(args).unwrap_err().kind();
+        assert_eq!(kind("--port"), ErrorKind::InvalidValue);
+        assert_eq!(kind("serve --port 99999"), ErrorKind::ValueValidation);
+        assert_eq!(kind("--verbose"), ErrorKind::UnknownArgument);
+        assert_eq!(kind("run"), ErrorKind::MissingRequiredArgument);
+        assert_eq!(kind("conform"), ErrorKind::MissingRequiredArgument);
+        assert_eq!(kind("run a b"), ErrorKind::UnknownArgument);
+        assert_eq!(kind("-q -v"), ErrorKind::ArgumentConflict);
+        assert_eq!(kind("--help"), ErrorKind::DisplayHelp);
+        assert!(parse("loadgen --producers 4").unwrap_err().to_string().contains("expected agents@rate"));
+        assert!(parse("loadgen --consumers 4@-1").unwrap_err().to_string().contains("invalid rate -1"));
+        assert!(parse("bench --mix run=1").unwrap_err().to_string().contains("Unknown primitive run"));
+    }
+}
@@ -0,0 +1,74 @@
+use bacht::blackboard::BlackboardTrait;
+use bacht::language::blackboard_interface::{BlackboardInterfaceTrait, LocalBlackboardInterface, RemoteBlackboardInterface};
+use bacht::error::Error;
+use bacht::model::admin::{AdminCommand, AdminReply};
+use bacht::model::token::TokenId;
+
+/// @summary - The blackboard the REPL runs its agents on: the in-process one, or a remote node
+pub enum Backend<B: BlackboardTrait> {
+    Local(LocalBlackboardInterface<B>),
+    Remote(Box<RemoteBlackboardInterface>),
+}
+
+impl<B: BlackboardTrait> Backend<B> {
+
+    /// @summary - Manage the blackboard, e.g. dump its store
+    ///
+    /// @note - A remote node only accepts the commands of a client holding its admin key
+    pub async fn admin(&self, command: AdminCommand) -> Result<AdminReply, Error> {
+        match self {
+            Backend::Local(local) => local.blackboard().admin(command).await.map_err(Error::from),
+            Backend::Remote(remote) => remote.client().admin(command).await.map_err(Error::from),
+        }
+    }
+
+    /// @returns - Where the agents are run, for the user
+    pub fn describe(&self) -> String {
+        match self {
+            Backend::Local(_) => "the local blackboard".to_string(),
+            Backend::Remote(remote) => remote.client().addr().to_string(),
+        }
+    }
+}
+
+impl<B: BlackboardTrait + Sync> BlackboardInterfaceTrait for Backend<B> {
+
+    fn new() -> Self {
+        Backend::Local(LocalBlackboardInterface::new())
+    }
+
+    async fn tell(&self, coord_data: &str) -> Result<bool, Error> {
+        match self {
+            Backend::Local(local) => local.tell(coord_data).await,
+            Backend::Remote(remote) => remote.tell(coord_data).await,
+        }
+    }
+
+    async fn ask(&self, coord_data: &str) -> Result<bool, Error> {
+        match self {
+            Backend::Local(local) => local.ask(coord_data).await,
+            Backend::Remote(remote) => remote.ask(coord_data).await,
+        }
+    }
+
+    async fn get(&self, coord_data: &str) -> Result<bool, Error> {
+        match self {
+            Backend::Local(local) => local.get(coord_data).await,
+            Backend::Remote(remote) => remote.get(coord_data).await,
+        }
+    }
+
+    async fn nask(&self, coord_data: &str) -> Result<bool, Error> {
+        match self {
+            Backend::Local(local) => local.nask(coord_data).await,
+            Backend::Remote(remote) => remote.nask(coord_data).await,
+        }
+    }
+
+    async fn tokens(&self) -> Result<Vec<TokenId>, Error> {
+        match self {
+            Backend::Local(local) => local.tokens().await,
+            Backend::Remote(remote) => remote.tokens().await,
+        }
+    }
+}
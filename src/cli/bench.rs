@@ -0,0 +1,185 @@
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use tokio::task::JoinSet;
+use bacht::model::action::Action;
+use bacht::model::token::TokenId;
+
+/// @summary - The synthetic workload of a benchmark: agents running concurrently a random mix of primitives
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchConfig {
+    /// The number of agents, each run on its own task
+    pub agents: usize,
+    /// The number of distinct tokens, picked uniformly by the primitives
+    pub tokens: usize,
+    /// The number of primitives run by each agent
+    pub operations: usize,
+    /// The weights of tell, ask, get and nask in the mix
+    pub mix: [u32; 4],
+    /// The seed of the workload, the same seed giving the same primitives
+    pub seed: u64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self { agents: 8, tokens: 16, operations: 1000, mix: [4, 2, 3, 1], seed: 0 }
+    }
+}
+
+impl BenchConfig {
+
+    /// @summary - Set the weights of the primitives from a list such as `tell=4,ask=2,get=3,nask=1`
+    ///
+    /// @note - The primitives absent from the list are not run
+    pub fn with_mix(mut self, mix: &str) -> Result<Self, String> {
+        let mut weights = [0; 4];
+        for weight in mix.split(',') {
+            let (primitive, value) = weight.split_once('=').ok_or(format!("Invalid weight {}, expected primitive=weight", weight))?;
+            let index = ["tell", "ask", "get", "nask"].iter().position(|name| *name == primitive)
+                .ok_or(format!("Unknown primitive {}", primitive))?;
+            weights[index] = value.parse().map_err(|_| format!("Invalid weight {} of {}", value, primitive))?;
+        }
+        if weights.iter().all(|weight| *weight == 0) {
+            return Err("The mix should run at least one primitive".to_string());
+        }
+        self.mix = weights;
+        Ok(self)
+    }
+
+    fn pick(&self, rng: &mut StdRng) -> Action {
+        let token = TokenId::from(format!("token{}", rng.random_range(0..self.tokens.max(1))));
+        let mut pick = rng.random_range(0..self.mix.iter().sum::<u32>());
+        let index = self.mix.iter().position(|weight| {
+            if pick < *weight {
+                return true;
+            }
+            pick -= weight;
+            false
+        }).unwrap_or(0);
+        match index {
+            0 => Action::Tell(token),
+            1 => Action::Ask(token),
+            2 => Action::Get(token),
+            _ => Action::Nask(token),
+        }
+    }
+}
+
+/// @summary - The measures of a benchmark
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub agents: usize,
+    pub elapsed: Duration,
+    /// The latency of each primitive answered, sorted
+    pub latencies: Vec<Duration>,
+    /// The primitives executed, the others being answered false, e.g. a get of an absent token
+    pub executed: usize,
+    /// The primitives that failed, e.g. on a lost connection
+    pub errors: usize,
+}
+
+impl BenchReport {
+
+    /// @param percentile - Between 0 and 100
+    ///
+    /// @returns - The latency under which this percentage of the primitives were answered
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    /// @returns - The number of primitives answered per second
+    pub fn throughput(&self) -> f64 {
+        self.latencies.len() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |latency: Duration| latency.as_secs_f64() * 1000.0;
+        writeln!(f, "{} primitives by {} agents in {:.3} s: {:.0} primitives/s",
+            self.latencies.len(), self.agents, self.elapsed.as_secs_f64(), self.throughput())?;
+        writeln!(f, "{} executed, {} answered false, {} failed",
+            self.executed, self.latencies.len() - self.executed, self.errors)?;
+        write!(f, "latency p50 {:.3} ms, p90 {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+            ms(self.percentile(50.0)), ms(self.percentile(90.0)), ms(self.percentile(99.0)), ms(self.percentile(100.0)))
+    }
+}
+
+/// @summary - Run the workload of a benchmark, measuring the latency of each primitive
+///
+/// @param send - Sends a primitive to the blackboard measured, e.g. the in-process one or a remote node
+pub async fn bench<F, Fut, E>(config: &BenchConfig, send: F) -> BenchReport
+where F: Fn(Action) -> Fut + Clone + Send + 'static, Fut: Future<Output = Result<bool, E>> + Send, E: Send + 'static {
+    let started = Instant::now();
+    let mut agents = JoinSet::new();
+    for agent in 0..config.agents {
+        let config = config.clone();
+        let send = send.clone();
+        agents.spawn(async move {
+            let mut rng = StdRng::seed_from_u64(config.seed.wrapping_add(agent as u64));
+            let mut measures = Vec::with_capacity(config.operations);
+            for _ in 0..config.operations {
+                let action = config.pick(&mut rng);
+                let sent = Instant::now();
+                let result = send(action).await;
+                measures.push((sent.elapsed(), result.ok()));
+            }
+            measures
+        });
+    }
+    let mut report = BenchReport { agents: config.agents, elapsed: Duration::ZERO, latencies: Vec::new(), executed: 0, errors: 0 };
+    while let Some(measures) = agents.join_next().await {
+        for (latency, result) in measures.unwrap_or_default() {
+            match result {
+                Some(executed) => {
+                    report.latencies.push(latency);
+                    report.executed += executed as usize;
+                },
+                None => report.errors += 1,
+            }
+        }
+    }
+    report.elapsed = started.elapsed();
+    report.latencies.sort();
+    report
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use bacht::blackboard::{BlackboardTrait, create_blackboard};
+    use bacht::model::event::Event;
+
+    #[tokio::test]
+    async fn bench_should_measure_every_primitive_of_the_workload() {
+        let blackboard = Arc::new(create_blackboard());
+        let config = BenchConfig { agents: 3, tokens: 4, operations: 50, seed: 7, ..BenchConfig::default() }.with_mix("tell=1,get=1").unwrap();
+        let report = bench(&config, move |action| {
+            let blackboard = blackboard.clone();
+            async move { blackboard.send_event(Event::new(action)).await }
+        }).await;
+
+        assert_eq!((report.latencies.len(), report.errors), (150, 0));
+        assert!(report.executed > 0 && report.executed < 150, "Only the gets of absent tokens should be answered false");
+        assert!(report.percentile(50.0) <= report.percentile(99.0));
+        assert_eq!(report.percentile(100.0), *report.latencies.last().unwrap());
+    }
+
+    #[test]
+    fn bench_config_should_reject_the_invalid_mixes() {
+        assert_eq!(BenchConfig::default().with_mix("ask=1,nask=3").unwrap().mix, [0, 1, 0, 3]);
+        assert_eq!(BenchConfig::default().with_mix("put=1").unwrap_err(), "Unknown primitive put");
+        assert_eq!(BenchConfig::default().with_mix("tell").unwrap_err(), "Invalid weight tell, expected primitive=weight");
+        assert_eq!(BenchConfig::default().with_mix("tell=0").unwrap_err(), "The mix should run at least one primitive");
+    }
+}
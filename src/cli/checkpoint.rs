@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+/// @summary - Write the tokens of a store as a JSON object, each token with its number of occurrences
+pub fn to_json(tokens: &[(Box<str>, u32)]) -> String {
+    let tokens: BTreeMap<&str, u32> = tokens.iter().map(|(token, count)| (&**token, *count)).collect();
+    let mut json = serde_json::to_string_pretty(&tokens).expect("A map of strings is always serializable");
+    json.push('\n');
+    json
+}
+
+/// @summary - Read the tokens written by to_json
+///
+/// @returns - The tokens with their number of occurrences, or where the JSON is invalid
+pub fn from_json(json: &str) -> Result<BTreeMap<String, u32>, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoint_should_read_back_the_tokens_it_wrote() {
+        let tokens: Vec<(Box<str>, u32)> = vec![("a".into(), 2), ("b\"c".into(), 1)];
+        let json = to_json(&tokens);
+        assert_eq!(json, "{\n  \"a\": 2,\n  \"b\\\"c\": 1\n}\n");
+        assert_eq!(from_json(&json).unwrap(), BTreeMap::from([("a".to_string(), 2), ("b\"c".to_string(), 1)]));
+        assert_eq!(from_json(&to_json(&[])).unwrap(), BTreeMap::new());
+        assert_eq!(from_json(" {\"x\":3} ").unwrap(), BTreeMap::from([("x".to_string(), 3)]));
+    }
+
+    #[test]
+    fn checkpoint_should_reject_the_invalid_json() {
+        let rejected = |json: &str| from_json(json).unwrap_err();
+        assert!(rejected("[]").starts_with("invalid type: sequence, expected a map"), "{}", rejected("[]"));
+        assert!(rejected("{\"a\": -1}").starts_with("invalid value: integer `-1`, expected u32"), "{}", rejected("{\"a\": -1}"));
+        assert!(rejected("{\"a\": 1").starts_with("EOF while parsing an object"), "{}", rejected("{\"a\": 1"));
+        assert!(rejected("{\"a\": 1} {}").starts_with("trailing characters"), "{}", rejected("{\"a\": 1} {}"));
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+use crossterm::event::{Event as TerminalEvent, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
+use crossterm::{cursor, execute};
+use futures::StreamExt;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, Paragraph};
+use ratatui::{Frame, Terminal};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::UnboundedReceiver;
+use bacht::blackboard::BlackboardTrait;
+use bacht::model::admin::{AdminCommand, AdminReply, PeerInfo};
+use bacht::model::change::StoreChange;
+use crate::backend::Backend;
+
+// How often the counters and the peers are fetched, the store following its changes as they come
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// The events kept for the log pane, the oldest being dropped first
+const LOG_CAPACITY: usize = 500;
+
+// The lines scrolled by PageUp and PageDown
+const PAGE: usize = 10;
+
+// The pattern of the subscription to a remote store, a variable alone matching every token
+const EVERY_TOKEN: &str = "X";
+
+/// @summary - The monitoring console of a blackboard: its store, its queue and counters, its peers and a log of the
+/// changes of its store
+pub struct Dashboard {
+    // What is monitored, for the title
+    source: String,
+    started: Instant,
+    tokens: BTreeMap<Box<str>, u32>,
+    counters: Vec<(String, u64)>,
+    peers: Vec<PeerInfo>,
+    log: VecDeque<String>,
+    // The lines of the log scrolled back from the newest one, 0 following the new lines
+    scroll: usize,
+    // Why the store is not followed, e.g. a remote node refusing the subscription of a client without key
+    error: Option<String>,
+}
+
+/// @summary - What a key pressed does to the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Quit,
+    Continue,
+}
+
+impl Dashboard {
+
+    /// @param source - What is monitored, e.g. the address of the node
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            started: Instant::now(),
+            tokens: BTreeMap::new(),
+            counters: Vec::new(),
+            peers: Vec::new(),
+            log: VecDeque::new(),
+            scroll: 0,
+            error: None,
+        }
+    }
+
+    /// @summary - Add a line to the log, stamped with the time elapsed since the start of the dashboard
+    pub fn log(&mut self, line: &str) {
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(format!("[{:>9.3} s] {}", self.started.elapsed().as_secs_f64(), line));
+        // The lines scrolled back stay in place as the new ones come
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.log.len() - 1);
+        }
+    }
+
+    /// @summary - Apply and log a change of the store received from its subscription
+    ///
+    /// @param count - The number of occurrences of the token after the change, 0 once it is absent
+    pub fn change(&mut self, token: &str, count: u32) {
+        match count {
+            0 => self.tokens.remove(token),
+            _ => self.tokens.insert(token.into(), count),
+        };
+        self.log(&format!("{} = {}", token, count));
+    }
+
+    /// @summary - Read the whole store once, before following its changes or after missing some of them
+    ///
+    /// @note - A node refusing the snapshot to a client without the admin key leaves the tokens changed since the
+    /// subscription only
+    pub async fn seed<B: BlackboardTrait + Sync>(&mut self, backend: &Backend<B>) {
+        match backend.admin(AdminCommand::Snapshot).await {
+            Ok(AdminReply::Snapshot(tokens)) => self.tokens = tokens.into_iter().collect(),
+            Ok(reply) => self.log(&format!("Unexpected reply to the snapshot {:?}", reply)),
+            Err(e) => self.log(&format!("The store is shown from its changes only: {}", e)),
+        }
+    }
+
+    /// @summary - Fetch the counters and the peers of the blackboard, the ones refused being left empty
+    pub async fn poll<B: BlackboardTrait + Sync>(&mut self, backend: &Backend<B>) {
+        if let Ok(AdminReply::Stats(counters)) = backend.admin(AdminCommand::Stats).await {
+            self.counters = counters;
+        }
+        if let Ok(AdminReply::Peers(peers)) = backend.admin(AdminCommand::Peers).await {
+            self.peers = peers;
+        }
+    }
+
+    /// @summary - Quit with q, Esc or Ctrl-C, scroll the log with the arrows, PageUp and PageDown, Home and End
+    pub fn key(&mut self, key: KeyEvent) -> KeyAction {
+        let oldest = self.log.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return KeyAction::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return KeyAction::Quit,
+            KeyCode::Up => self.scroll = (self.scroll + 1).min(oldest),
+            KeyCode::Down => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageUp => self.scroll = (self.scroll + PAGE).min(oldest),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(PAGE),
+            KeyCode::Home => self.scroll = oldest,
+            KeyCode::End => self.scroll = 0,
+            _ => {},
+        }
+        KeyAction::Continue
+    }
+
+    /// @summary - Draw the panes on a frame, the store, the counters and the peers side by side above the log
+    pub fn draw(&self, frame: &mut Frame) {
+        let [title, top, events, status] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1), Constraint::Fill(1), Constraint::Length(1)])
+            .areas(frame.area());
+        let [store, counters, peers] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(top);
+        frame.render_widget(Paragraph::new(format!(" BachT dashboard of {}", self.source)), title);
+
+        let occurrences: u32 = self.tokens.values().sum();
+        let tokens = self.tokens.iter().map(|(token, count)| format!("{} {}", token, count));
+        frame.render_widget(List::new(tokens).block(pane(&format!("Store: {} tokens, {} occurrences", self.tokens.len(), occurrences))), store);
+        let counters_lines = self.counters.iter().map(|(name, value)| format!("{} {}", name, value));
+        frame.render_widget(List::new(counters_lines).block(pane("Queue and counters")), counters);
+        let peers_lines = self.peers.iter().map(|peer| format!("{} {} {}", peer.name, peer.addr, if peer.alive { "alive" } else { "down" }));
+        frame.render_widget(List::new(peers_lines).block(pane("Peers")), peers);
+
+        frame.render_widget(List::new(self.visible_log(events)).block(pane("Events")), events);
+        let status_line = match &self.error {
+            Some(e) => format!(" Error: {}", e),
+            None => " q to quit, ↑ ↓ PgUp PgDn to scroll the events".to_string(),
+        };
+        frame.render_widget(Paragraph::new(Line::from(status_line)), status);
+    }
+
+    // The lines of the log shown in its pane, the last one being the newest unless scrolled back
+    fn visible_log(&self, area: Rect) -> Vec<&str> {
+        let rows = area.height.saturating_sub(2) as usize;
+        let end = self.log.len() - self.scroll.min(self.log.len());
+        self.log.range(end.saturating_sub(rows)..end).map(String::as_str).collect()
+    }
+}
+
+// A bordered pane under its title
+fn pane(title: &str) -> Block<'_> {
+    Block::bordered().title(format!(" {} ", title))
+}
+
+/// @summary - The changes of the store followed by the dashboard
+enum Changes {
+    // The subscription stream of the in-process store
+    Local(broadcast::Receiver<StoreChange>),
+    // The Subscribe stream of a remote node, ended with its connection or once it lagged
+    Remote(UnboundedReceiver<(Box<str>, u32)>),
+    // Waiting to subscribe again to the remote node
+    Unsubscribed,
+}
+
+// What was received from the changes of the store
+enum Received {
+    Change(Box<str>, u32),
+    // Some changes were missed, the store has to be read again
+    Missed(u64),
+    Ended,
+}
+
+impl Changes {
+
+    async fn next(&mut self) -> Received {
+        match self {
+            Changes::Local(changes) => match changes.recv().await {
+                Ok(change) => Received::Change((*change.token).into(), change.count),
+                Err(RecvError::Lagged(missed)) => Received::Missed(missed),
+                Err(RecvError::Closed) => Received::Ended,
+            },
+            Changes::Remote(changes) => changes.recv().await.map_or(Received::Ended, |(token, count)| Received::Change(token, count)),
+            Changes::Unsubscribed => std::future::pending().await,
+        }
+    }
+}
+
+// Subscribe to the changes of every token of a remote node, then read its store
+async fn subscribe<B: BlackboardTrait + Sync>(dashboard: &mut Dashboard, backend: &Backend<B>) -> Changes {
+    let Backend::Remote(remote) = backend else {
+        return Changes::Unsubscribed;
+    };
+    match remote.client().subscribe(EVERY_TOKEN).await {
+        Ok(changes) => {
+            dashboard.error = None;
+            // Subscribed first, the changes made while the store is read are not missed
+            dashboard.seed(backend).await;
+            Changes::Remote(changes)
+        },
+        Err(e) => {
+            dashboard.error = Some(format!("subscribing to the store: {}", e));
+            Changes::Unsubscribed
+        },
+    }
+}
+
+/// @summary - Show the dashboard of a blackboard on the alternate screen of the terminal, in raw mode, until q, Esc
+/// or Ctrl-C is pressed
+///
+/// @param changes - The subscription stream of the in-process store, none for a remote node whose store is
+/// subscribed to with its Subscribe stream
+pub async fn run<B: BlackboardTrait + Sync>(backend: Backend<B>, changes: Option<broadcast::Receiver<StoreChange>>) -> std::io::Result<()> {
+    let mut dashboard = Dashboard::new(&backend.describe());
+    let mut changes = match changes {
+        Some(changes) => {
+            dashboard.seed(&backend).await;
+            Changes::Local(changes)
+        },
+        None => subscribe(&mut dashboard, &backend).await,
+    };
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    // The alternate screen, without cursor, is left as it was found even if drawing failed
+    let result = match execute!(stdout, EnterAlternateScreen, cursor::Hide) {
+        Ok(()) => show(&mut dashboard, &backend, &mut changes).await,
+        Err(e) => Err(e),
+    };
+    let restored = execute!(stdout, cursor::Show, LeaveAlternateScreen).and(disable_raw_mode());
+    result.and(restored)
+}
+
+// Draw the dashboard at each change of the store, each poll of its counters and each key pressed
+async fn show<B: BlackboardTrait + Sync>(dashboard: &mut Dashboard, backend: &Backend<B>, changes: &mut Changes) -> std::io::Result<()> {
+    let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+    let mut keys = EventStream::new();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            key = keys.next() => match key {
+                Some(Ok(TerminalEvent::Key(key))) if key.kind != KeyEventKind::Release => {
+                    if dashboard.key(key) == KeyAction::Quit {
+                        return Ok(());
+                    }
+                },
+                Some(Ok(_)) => {},
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            },
+            _ = poll.tick() => {
+                dashboard.poll(backend).await;
+                if matches!(changes, Changes::Unsubscribed) {
+                    *changes = subscribe(dashboard, backend).await;
+                }
+            },
+            received = changes.next() => match received {
+                Received::Change(token, count) => dashboard.change(&token, count),
+                Received::Missed(missed) => {
+                    dashboard.log(&format!("{} changes of the store missed, reading it again", missed));
+                    dashboard.seed(backend).await;
+                },
+                Received::Ended => {
+                    dashboard.log("The changes of the store ended, subscribing again");
+                    *changes = subscribe(dashboard, backend).await;
+                },
+            },
+        }
+        terminal.draw(|frame| dashboard.draw(frame))?;
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use bacht::blackboard::create_blackboard;
+    use bacht::language::blackboard_interface::{BlackboardInterfaceTrait, LocalBlackboardInterface};
+
+    // The rows of the screen drawn, without their trailing spaces
+    fn screen(dashboard: &Dashboard, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string()).collect()
+    }
+
+    // The messages of the events shown, without their time
+    fn events(screen: &[String]) -> Vec<&str> {
+        screen.iter().filter_map(|row| row.split("] ").nth(1)).map(|event| event.trim_end_matches([' ', '│'])).collect()
+    }
+
+    #[tokio::test]
+    async fn dashboard_should_show_the_store_and_log_its_changes() {
+        let blackboard = create_blackboard();
+        let backend = Backend::Local(LocalBlackboardInterface::new_with(blackboard.clone()));
+        let mut changes = Changes::Local(blackboard.subscribe());
+        let mut dashboard = Dashboard::new(&backend.describe());
+        backend.tell("a").await.unwrap();
+        dashboard.seed(&backend).await;
+        backend.tell("b").await.unwrap();
+        backend.get("a").await.unwrap();
+        // The change of a, made before the snapshot, is received as well
+        for _ in 0..3 {
+            let Received::Change(token, count) = changes.next().await else { panic!("A change should be received") };
+            dashboard.change(&token, count);
+        }
+
+        let screen = screen(&dashboard, 120, 12);
+        assert_eq!(screen[0], " BachT dashboard of the local blackboard");
+        assert!(screen[1].starts_with("┌ Store: 1 tokens, 1 occurrences ─"), "{:?}", screen);
+        assert!(screen[2].starts_with("│b 1"), "{:?}", screen);
+        assert_eq!(events(&screen), ["a = 1", "b = 1", "a = 0"]);
+        assert_eq!(screen[11], " q to quit, ↑ ↓ PgUp PgDn to scroll the events");
+    }
+
+    #[test]
+    fn dashboard_should_scroll_the_events_and_quit_with_its_keys() {
+        let mut dashboard = Dashboard::new("test");
+        (0..10).for_each(|i| dashboard.log(&format!("event {}", i)));
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        // 12 rows leave 3 events shown
+        assert_eq!(events(&screen(&dashboard, 120, 12)), ["event 7", "event 8", "event 9"]);
+        assert_eq!(dashboard.key(press(KeyCode::Up)), KeyAction::Continue);
+        assert_eq!(events(&screen(&dashboard, 120, 12)), ["event 6", "event 7", "event 8"]);
+        dashboard.log("event 10");
+        assert_eq!(events(&screen(&dashboard, 120, 12)), ["event 6", "event 7", "event 8"], "The events scrolled back should stay in place");
+        dashboard.key(press(KeyCode::PageUp));
+        assert_eq!(events(&screen(&dashboard, 120, 12)), ["event 0"]);
+        dashboard.key(press(KeyCode::End));
+        assert_eq!(events(&screen(&dashboard, 120, 12)), ["event 8", "event 9", "event 10"]);
+        assert_eq!(dashboard.key(press(KeyCode::Char('q'))), KeyAction::Quit);
+        assert_eq!(dashboard.key(press(KeyCode::Esc)), KeyAction::Quit);
+        assert_eq!(dashboard.key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), KeyAction::Quit);
+    }
+}
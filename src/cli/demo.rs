@@ -0,0 +1,116 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use bacht::blackboard::BlackboardTrait;
+use crate::repl::{PROMPT, Repl, Step};
+
+/// @summary - A canned example of the language: a title, then the lines typed, each with its explanation
+pub struct Lesson {
+    pub title: &'static str,
+    pub steps: &'static [(&'static str, &'static str)],
+}
+
+/// The examples walked through by `bach_cli demo`, each on an empty store
+pub const LESSONS: [Lesson; 3] = [
+    Lesson {
+        title: "Producer and consumer",
+        steps: &[
+            ("A producer tells two items on the blackboard, one after the other with the sequence operator ;.",
+                "tell(item);tell(item)"),
+            ("A consumer gets three items: it gets the two items produced, then it is stuck waiting for a third one.",
+                "get(item);get(item);get(item)"),
+            ("With the parallel operator ||, the consumer waits for the producer: the get is blocked until the tell.",
+                "get(item)||tell(item)"),
+            ("A recursive consumer is defined by name, it gets the items as long as there are some.",
+                ":def consumer = get(item);consumer"),
+            ("Three items are produced, and the consumer gets them all before waiting for the next one.",
+                "tell(item);tell(item);tell(item);consumer"),
+            ("The store is empty again.", ":store"),
+        ],
+    },
+    Lesson {
+        title: "Dining philosophers",
+        steps: &[
+            ("Two forks lie on the table, each a token of the blackboard.", "tell(fork1);tell(fork2)"),
+            ("The branches are picked from a seed, so that the next example is reproducible.", ":seed 3"),
+            ("Each philosopher gets both forks to eat, then puts them back. As they pick the forks in opposite orders, \
+                each may hold one fork while waiting for the other: they are stuck in a deadlock.",
+                "(get(fork1);get(fork2);tell(eating1);get(eating1);tell(fork1);tell(fork2))||(get(fork2);get(fork1);tell(eating2);get(eating2);tell(fork2);tell(fork1))"),
+            ("No fork is left on the table: each philosopher holds one forever.", ":store"),
+            ("The forks are put back on the table.", ":clear"),
+            ("", "tell(fork1);tell(fork2)"),
+            ("With the same seed, the philosophers picking the forks in the same order never deadlock.", ":seed 3"),
+            ("",
+                "(get(fork1);get(fork2);tell(eating1);get(eating1);tell(fork1);tell(fork2))||(get(fork1);get(fork2);tell(eating2);get(eating2);tell(fork2);tell(fork1))"),
+        ],
+    },
+    Lesson {
+        title: "Mutual exclusion",
+        steps: &[
+            ("A single lock token guards the critical section.", "tell(lock)"),
+            ("Each agent gets the lock before entering the critical section, and tells it back when leaving it: \
+                the two agents are never in the critical section together.",
+                "(get(lock);tell(critical);get(critical);tell(lock))||(get(lock);tell(critical);get(critical);tell(lock))"),
+            ("nask checks that no agent is left in the critical section, and ask that the lock is free again.",
+                "nask(critical);ask(lock)"),
+            ("The choice operator + runs one of its branches: an agent enters only if the lock is free, and gives up \
+                otherwise.",
+                "(get(lock);tell(critical);get(critical);tell(lock))+nask(lock)"),
+        ],
+    },
+];
+
+/// @summary - Walk through the lessons, printing each line typed with its explanation and its outcome
+///
+/// @param pause - The input waited for between the lessons, e.g. the terminal, none to run them straight through
+///
+/// @note - The store is cleared before each lesson
+pub async fn run_demo<B, R, W>(repl: &mut Repl<B>, lessons: &[Lesson], mut pause: Option<R>, mut output: W) -> std::io::Result<()>
+where B: BlackboardTrait + Sync, R: AsyncBufRead + Unpin, W: AsyncWrite + Unpin {
+    for (number, lesson) in lessons.iter().enumerate() {
+        repl.step(":clear").await;
+        output.write_all(format!("=== {}. {} ===\n", number + 1, lesson.title).as_bytes()).await?;
+        for (explanation, line) in lesson.steps {
+            if !explanation.is_empty() {
+                output.write_all(format!("\n{}\n", explanation).as_bytes()).await?;
+            }
+            let outcome = match repl.step(line).await {
+                Step::Print(outcome) => outcome,
+                Step::Quit => String::new(),
+            };
+            output.write_all(format!("{}{}\n{}\n", PROMPT, line, outcome).as_bytes()).await?;
+        }
+        output.write_all(b"\n").await?;
+        if let Some(input) = pause.as_mut().filter(|_| number + 1 < lessons.len()) {
+            output.write_all(b"Press Enter for the next example, or Ctrl-D to stop\n").await?;
+            output.flush().await?;
+            if input.read_line(&mut String::new()).await? == 0 {
+                break;
+            }
+        }
+    }
+    output.flush().await
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bacht::blackboard::create_blackboard;
+
+    #[tokio::test]
+    async fn demo_should_run_every_lesson_with_the_outcomes_explained() {
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        run_demo(&mut repl, &LESSONS, Some("\n\n".as_bytes()), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.lines().filter(|line| ["Success", "Failure: the agent is stuck"].contains(line)).collect();
+        assert_eq!(outcomes, ["Success", "Failure: the agent is stuck", "Success", "Failure: the agent is stuck", "Success",
+            "Failure: the agent is stuck", "Success", "Success", "Success", "Success", "Success", "Success"]);
+        assert!(output.contains("=== 2. Dining philosophers ===\n"));
+        assert!(output.contains("bacht> :store\nThe store is empty\n"));
+        assert_eq!(output.matches("Press Enter for the next example").count(), 2);
+        assert!(!output.contains("Error"), "Every line of the lessons should run: {}", output);
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+use rustyline::completion::Completer as RustylineCompleter;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+
+// The number of lines kept in the history, the oldest ones are forgotten first
+const MAX_HISTORY: usize = 1000;
+
+const HISTORY_FILE: &str = ".bacht_history";
+
+/// @returns - The history file given by BACHT_HISTORY, or `.bacht_history` in the home directory
+pub fn history_path() -> Option<PathBuf> {
+    std::env::var_os("BACHT_HISTORY").map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE)))
+}
+
+const PRIMITIVES: [&str; 4] = ["tell(", "ask(", "get(", "nask("];
+
+const OPERATORS: [&str; 3] = [";", "||", "+"];
+
+/// @summary - The Completer proposes the words completing the one before the cursor, depending on where it is typed:
+/// the meta-commands after a colon, the tokens of the store in a primitive, the primitives or the operators elsewhere
+#[derive(Debug, Clone, Default)]
+pub struct Completer {
+    commands: Vec<String>,
+    tokens: Vec<String>,
+}
+
+impl Completer {
+
+    /// @param commands - The meta-commands, without their colon
+    pub fn new(commands: &[&str]) -> Self {
+        Self { commands: commands.iter().map(|command| format!(":{}", command)).collect(), tokens: Vec::new() }
+    }
+
+    /// @summary - Replace the tokens proposed, e.g. with the ones of the store before each line
+    pub fn set_tokens(&mut self, tokens: Vec<String>) {
+        self.tokens = tokens;
+    }
+
+    /// @param before - The line before the cursor
+    ///
+    /// @returns - The byte position of the first character of the word completed, and its completions sorted
+    pub fn complete(&self, before: &str) -> (usize, Vec<String>) {
+        // The words completed are ASCII, their characters are one byte long
+        let start = before.len() - before.chars().rev().take_while(char::is_ascii_alphanumeric).count();
+        let (preceding, word) = before.split_at(start);
+        let (start, candidates): (usize, Vec<&str>) = match preceding {
+            ":" => (0, self.commands.iter().map(String::as_str).collect()),
+            _ if PRIMITIVES.iter().any(|primitive| preceding.ends_with(primitive)) => {
+                (start, self.tokens.iter().map(String::as_str).collect())
+            },
+            _ if preceding.ends_with(')') && word.is_empty() => (start, OPERATORS.to_vec()),
+            _ => (start, PRIMITIVES.to_vec()),
+        };
+        let typed = &before[start..];
+        let mut completions: Vec<String> = candidates.into_iter().filter(|candidate| candidate.starts_with(typed)).map(String::from).collect();
+        completions.sort();
+        completions.dedup();
+        (start, completions)
+    }
+}
+
+impl RustylineCompleter for Completer {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(Completer::complete(self, &line[..pos]))
+    }
+}
+
+impl Hinter for Completer {
+    type Hint = String;
+}
+
+impl Highlighter for Completer {}
+
+impl Validator for Completer {}
+
+impl Helper for Completer {}
+
+/// @summary - The LineEditor reads the lines typed in a terminal, with the arrow keys to edit them and browse the
+/// history, Ctrl-R to search it and Tab to complete the words.
+///
+/// When the standard input is not a terminal (e.g. a piped script), the lines are read as they are.
+pub struct LineEditor {
+    editor: Editor<Completer, FileHistory>,
+    history: Option<PathBuf>,
+}
+
+impl LineEditor {
+
+    /// @param history - The history file of the previous sessions, created on the first line typed if it does not
+    /// exist, and trimmed to its last MAX_HISTORY lines. None keeps the history in memory only
+    pub fn new(history: Option<PathBuf>) -> rustyline::Result<Self> {
+        let config = Config::builder()
+            .max_history_size(MAX_HISTORY)?
+            .history_ignore_dups(true)?
+            .history_ignore_space(true)
+            // Tab completes the common prefix, then lists the completions left, as a shell does
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(config)?;
+        editor.set_helper(Some(Completer::default()));
+        if let Some(path) = &history {
+            // A missing or unreadable file starts an empty history
+            let _ = editor.load_history(path);
+        }
+        Ok(Self { editor, history })
+    }
+
+    /// @summary - Complete the words with this completer when Tab is pressed
+    pub fn with_completer(mut self, completer: Completer) -> Self {
+        self.editor.set_helper(Some(completer));
+        self
+    }
+
+    pub fn completer_mut(&mut self) -> &mut Completer {
+        self.editor.helper_mut().expect("The completer is set on creation")
+    }
+
+    /// @summary - Read the next line, after printing the prompt. Ctrl-C drops the line being typed
+    ///
+    /// @returns - The line, or None once the input is closed (Ctrl-D)
+    pub fn read_line(&mut self, prompt: &str) -> std::io::Result<Option<String>> {
+        loop {
+            match self.editor.readline(prompt) {
+                Ok(line) => {
+                    self.remember(&line);
+                    return Ok(Some(line));
+                },
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return Ok(None),
+                Err(ReadlineError::Io(e)) => return Err(e),
+                Err(e) => return Err(std::io::Error::other(e)),
+            }
+        }
+    }
+
+    /// @summary - Remember a typed line, unless it is empty or the same as the previous one
+    fn remember(&mut self, line: &str) {
+        // The history is a convenience, failing to persist it must not stop the REPL
+        if let (Ok(true), Some(path)) = (self.editor.add_history_entry(line), &self.history) {
+            let _ = self.editor.append_history(path);
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completer_should_complete_the_word_before_the_cursor() {
+        let mut completer = Completer::new(&["store", "step", "quit"]);
+        completer.set_tokens(vec!["apple".into(), "apricot".into(), "banana".into()]);
+        assert_eq!(completer.complete(":st"), (0, vec![":step".into(), ":store".into()]));
+        assert_eq!(completer.complete(":q"), (0, vec![":quit".into()]));
+        assert_eq!(completer.complete("tell(a);ge"), (8, vec!["get(".into()]));
+        assert_eq!(completer.complete("tell(a);get(a"), (12, vec!["apple".into(), "apricot".into()]));
+        assert_eq!(completer.complete("tell(a)").1, ["+", ";", "||"]);
+        assert_eq!(completer.complete("(a").1, ["ask("], "A token should only be completed in a primitive");
+    }
+
+    #[test]
+    fn history_should_be_persisted_across_sessions_and_trimmed() {
+        let path = std::env::temp_dir().join(format!("bacht-history-{}", rand::random::<u64>()));
+        let mut editor = LineEditor::new(Some(path.clone())).unwrap();
+        ["tell(a)", "get(a)", "get(a)", "", "  "].iter().for_each(|line| editor.remember(line));
+        (0..MAX_HISTORY).for_each(|i| editor.remember(&format!("tell(t{})", i)));
+
+        let reloaded = LineEditor::new(Some(path.clone())).unwrap();
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        std::fs::remove_file(path).unwrap();
+        let history: Vec<&String> = reloaded.editor.history().iter().collect();
+        assert_eq!((history.len(), history[0].as_str()), (MAX_HISTORY, "tell(t0)"), "The oldest lines should be forgotten");
+        assert!(lines <= MAX_HISTORY + 1, "The file should be trimmed, besides its header: {} lines", lines);
+    }
+}
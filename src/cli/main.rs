@@ -0,0 +1,421 @@
+mod args;
+mod backend;
+mod bench;
+mod checkpoint;
+mod dashboard;
+mod demo;
+mod editor;
+mod playground;
+mod recording;
+mod repl;
+mod session;
+mod style;
+mod watch;
+
+use std::io::IsTerminal;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use bacht::blackboard::{BlackboardTrait, create_blackboard};
+use bacht::config::Config;
+use bacht::communication::listeners::{Listeners, ListenersHandle};
+use bacht::communication::socket_client::{ReconnectPolicy, SocketClient, SocketClientTrait};
+use bacht::communication::socket_listener::{SocketListener, SocketListenerTrait};
+use tokio::io::BufReader;
+use bacht::model::event::Event;
+use bacht::language::blackboard_interface::{LocalBlackboardInterface, RemoteBlackboardInterface};
+use bacht::language::conformance::{Conformance, ConformanceTrace};
+use bacht::loadgen::LoadGenerator;
+use bacht::model::admin::{AdminCommand, AdminReply};
+use clap::Parser;
+use crate::args::{Args, Command};
+use crate::backend::Backend;
+use crate::bench::bench;
+use crate::editor::{Completer, LineEditor};
+use crate::recording::Record;
+use crate::repl::{COMMANDS, Input, Repl, Step};
+use crate::session::serve_sessions;
+use crate::style::Style;
+
+// The port of the playground, apart from the one of the blackboard nodes
+const PLAYGROUND_PORT: u16 = 8080;
+
+// The port of the REPL sessions of serve --interactive, next to the one of the blackboard protocol
+const INTERACTIVE_PORT: u16 = 2139;
+
+#[tokio::main]
+async fn main() {
+    // Exits with the usage on --help, or on an invalid command line
+    let args = Args::parse();
+    let config = match Config::find(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error reading the configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let args = match args.with_config(&config) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error reading the configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
+    // The messages of the library go on the standard error, the standard output being the one of the REPL
+    let _ = bacht::log::StreamLogger::new(std::io::stderr()).with_level(args.log_level).install();
+    let command = args.command();
+    let blackboard = create_blackboard();
+    // Fill the store before the first agent, e.g. with the tokens of a scenario
+    if let Some(path) = &args.store {
+        if let Err(e) = load_store(&blackboard, path).await {
+            eprintln!("Error loading the store {}: {}", path.display(), e);
+            std::process::exit(2);
+        }
+    }
+    // Presented to the remote nodes, which only accept the commands managing their store from their administrators,
+    // and required from the administrators of the served blackboard
+    let admin_key = args.admin_key.clone();
+    // Serve a REPL session to each telnet or netcat client, every session running its agents on the in-process blackboard
+    if command == Command::Serve && args.interactive {
+        let address = args.bind.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let style = Style::new(!args.no_color);
+        let session = || configure(Repl::new_with(blackboard.clone()), &args, style);
+        if let Err(e) = serve_sessions(address, args.port.unwrap_or(INTERACTIVE_PORT), session).await {
+            eprintln!("Error serving the sessions: {}", e);
+            std::process::exit(2);
+        }
+        return;
+    }
+    if command == Command::Serve {
+        serve(blackboard, &args, admin_key.as_deref()).await;
+        return;
+    }
+    // Measure the in-process blackboard, or the remote node of --connect
+    if command == Command::Bench {
+        let report = match &args.connect {
+            Some(addr) => match SocketClient::connect(addr, ReconnectPolicy::default()).await {
+                Ok(client) => {
+                    let client = Arc::new(client);
+                    bench(&args.bench(), move |action| {
+                        let client = client.clone();
+                        async move { client.send(action).await }
+                    }).await
+                },
+                Err(e) => {
+                    eprintln!("Error connecting to {}: {}", addr, e);
+                    std::process::exit(2);
+                }
+            },
+            None => {
+                let blackboard = Arc::new(blackboard);
+                bench(&args.bench(), move |action| {
+                    let blackboard = blackboard.clone();
+                    async move { blackboard.send_event(Event::new(action)).await }
+                }).await
+            },
+        };
+        println!("{}", report);
+        return;
+    }
+    // Load the in-process blackboard, or the remote node of --connect, with producers and consumers
+    if command == Command::Loadgen {
+        let generator = LoadGenerator::new(Duration::from_secs(args.duration)).with_seed(args.bench().seed)
+            .with_producers(args.producers.0, args.producers.1, args.bench().tokens)
+            .with_consumers(args.consumers.0, args.consumers.1, args.bench().tokens);
+        let report = match &args.connect {
+            Some(addr) => match SocketClient::connect(addr, ReconnectPolicy::default()).await {
+                Ok(client) => {
+                    let client = Arc::new(client);
+                    generator.run(move |action| {
+                        let client = client.clone();
+                        async move { client.send(action).await }
+                    }).await
+                },
+                Err(e) => {
+                    eprintln!("Error connecting to {}: {}", addr, e);
+                    std::process::exit(2);
+                }
+            },
+            None => {
+                let blackboard = Arc::new(blackboard);
+                generator.run(move |action| {
+                    let blackboard = blackboard.clone();
+                    async move { blackboard.send_event(Event::new(action)).await }
+                }).await
+            },
+        };
+        println!("{}", report);
+        return;
+    }
+    // Monitor the remote node of --connect, or the in-process blackboard while serving it
+    if command == Command::Dashboard {
+        if let Err(e) = monitor(blackboard, &args, admin_key.as_deref()).await {
+            eprintln!("Error drawing the dashboard: {}", e);
+            std::process::exit(2);
+        }
+        return;
+    }
+    // Export the transitions of an agent run from the store of --store, in the format of the conformance traces
+    if let Command::Export { agent } = &command {
+        let store = match blackboard.admin(AdminCommand::Snapshot).await {
+            Ok(AdminReply::Snapshot(tokens)) => tokens.into_iter().map(|(token, occurrences)| (token.into(), occurrences)).collect(),
+            _ => Vec::new(),
+        };
+        match ConformanceTrace::record(agent, &store, args.seed.unwrap_or_else(rand::random)).await {
+            Ok(trace) => print!("{}", trace.to_json()),
+            Err(e) => {
+                eprintln!("Error running the agent: {}", e);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+    // Take the transitions of a trace, e.g. exported by the reference implementation, failing if one of them is not taken
+    if let Command::Conform { trace: path } = &command {
+        let trace = match ConformanceTrace::from_json(&read_script(path)) {
+            Ok(trace) => trace,
+            Err(e) => {
+                eprintln!("Error reading the trace {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        };
+        match trace.check().await {
+            Ok(Conformance::Agrees) => println!("Agrees with the trace on its {} transitions", trace.transitions.len()),
+            Ok(Conformance::Diverges { step, reason }) => {
+                println!("Diverges from the trace at the transition {}: {}", step, reason);
+                std::process::exit(1);
+            },
+            Err(e) => {
+                eprintln!("Error running the agent: {}", e);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+    // Run the agents typed by the user on an in-process blackboard
+    let mut repl = Repl::new_with(blackboard);
+    if let Some(key) = &admin_key {
+        repl = repl.with_admin_key(key);
+    }
+    // Run the agents on a remote node instead
+    if let Some(addr) = &args.connect {
+        if let Err(e) = repl.connect(addr).await {
+            eprintln!("Error connecting to {}: {}", addr, e);
+            std::process::exit(2);
+        }
+    }
+    // Color the output of a terminal, unless --no-color is given
+    repl = configure(repl, &args, if args.no_color { Style::new(false) } else { Style::detect() });
+    match &command {
+        // Run a script non-interactively, failing if one of its agents does not terminate
+        Command::Run { script: path } => {
+            let script = read_script(path);
+            exit_with(repl.run_script(&script, tokio::io::stdout()).await);
+        },
+        // Only parse the agents of a script, failing if one of them does not parse
+        Command::Check { script: path } => {
+            let script = read_script(path);
+            exit_with(repl.check_script(&script, tokio::io::stdout()).await);
+        },
+        // Run the agents typed in a web page, e.g. to teach the language
+        Command::Playground => {
+            let address = args.bind.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+            if let Err(e) = playground::serve(repl, address, args.port.unwrap_or(PLAYGROUND_PORT)).await {
+                eprintln!("Error serving the playground: {}", e);
+                std::process::exit(2);
+            }
+            return;
+        },
+        // Enter again the lines of a recorded session, failing if one of the outcomes differs
+        Command::Replay { recording: path } => {
+            let records: Result<Vec<Record>, String> = read_script(path).lines().enumerate()
+                .map(|(number, line)| Record::decode(line).map_err(|e| format!("line {}: {}", number + 1, e)))
+                .collect();
+            match records {
+                Ok(records) => exit_with(recording::replay(&mut repl, &records, tokio::io::stdout()).await),
+                Err(e) => {
+                    eprintln!("Error reading the recording {}: {}", path.display(), e);
+                    std::process::exit(2);
+                }
+            }
+        },
+        // Walk through the examples, waiting for Enter between them on a terminal
+        Command::Demo => {
+            let pause = std::io::stdin().is_terminal().then(|| BufReader::new(tokio::io::stdin()));
+            if let Err(e) = demo::run_demo(&mut repl, &demo::LESSONS, pause, tokio::io::stdout()).await {
+                eprintln!("Error writing the demo: {}", e);
+                std::process::exit(2);
+            }
+            return;
+        },
+        Command::Repl | Command::Serve | Command::Bench | Command::Loadgen | Command::Dashboard | Command::Export { .. } | Command::Conform { .. } => {},
+    }
+    if !std::io::stdin().is_terminal() {
+        if let Err(e) = repl.run(BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await {
+            eprintln!("Error reading the agents: {}", e);
+        }
+        return;
+    }
+    // The lines typed are remembered across the sessions, in BACHT_HISTORY or ~/.bacht_history
+    let editor = match LineEditor::new(editor::history_path()) {
+        Ok(editor) => editor.with_completer(Completer::new(&COMMANDS)),
+        Err(e) => {
+            eprintln!("Error opening the terminal: {}", e);
+            std::process::exit(2);
+        },
+    };
+    if let Err(e) = interact(&mut repl, editor).await {
+        eprintln!("Error reading the agents: {}", e);
+    }
+}
+
+/// @summary - Apply the options of the command line to a REPL, once connected, as connecting resets the seed
+fn configure<B: BlackboardTrait + Sync>(mut repl: Repl<B>, args: &Args, style: Style) -> Repl<B> {
+    repl = repl.with_style(style);
+    if let Some(seed) = args.seed {
+        repl = repl.with_seed(seed);
+    }
+    // Print each primitive attempted by the agents, as :trace on
+    if args.trace {
+        repl = repl.with_trace();
+    }
+    // Show the counters of the blackboard in the prompt, as :prompt
+    if let Some(template) = &args.prompt {
+        repl = repl.with_prompt(template);
+    }
+    // Print the time taken by each agent, as :timing on
+    if args.timing {
+        repl = repl.with_timing();
+    }
+    // Only parse and explain the agents, as :dryrun on
+    if args.dry_run {
+        repl = repl.with_dry_run();
+    }
+    // Accept out, rd, in, inp and rdp
+    if args.linda {
+        repl = repl.with_linda();
+    }
+    repl
+}
+
+fn read_script(path: &Path) -> String {
+    match std::fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", path.display(), e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// @summary - Exit with 0 if every agent of a script succeeded, 1 if one of them failed and 2 if the output failed
+fn exit_with(result: std::io::Result<bool>) -> ! {
+    match result {
+        Ok(true) => std::process::exit(0),
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Error writing the results: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// @summary - Tell the tokens of a file, one `token [count]` per line, the empty lines and the ones starting with # being skipped
+async fn load_store<B: BlackboardTrait>(blackboard: &B, path: &Path) -> Result<(), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    for (number, line) in content.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let (token, count) = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => continue,
+            (Some(comment), _, _) if comment.starts_with('#') => continue,
+            (Some(token), None, _) => (token, 1),
+            (Some(token), Some(count), None) => match count.parse::<u32>() {
+                Ok(count) => (token, count),
+                Err(_) => return Err(format!("line {}: invalid count {}", number + 1, count)),
+            },
+            _ => return Err(format!("line {}: expected a token and its count", number + 1)),
+        };
+        for _ in 0..count {
+            blackboard.tell(token.into()).await.map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// @summary - Serve the in-process blackboard on a port until the process is stopped, without REPL
+async fn serve<B: BlackboardTrait + Sync + Send + 'static>(blackboard: B, args: &Args, admin_key: Option<&str>) {
+    let mut handle = listen(blackboard, args, admin_key);
+    if let Err(e) = handle.wait().await {
+        eprintln!("Error starting listener: {}", e);
+        std::process::exit(2);
+    }
+}
+
+/// @summary - Start serving the in-process blackboard on the port and address of the arguments
+///
+/// @returns - The listener, served until the handle is dropped
+fn listen<B: BlackboardTrait + Sync + Send + 'static>(blackboard: B, args: &Args, admin_key: Option<&str>) -> ListenersHandle {
+    let mut listener = SocketListener::new(blackboard, args.port);
+    if let Some(address) = args.bind {
+        listener = listener.with_address(address).with_dual_stack(address == IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+    }
+    if let Some(key) = admin_key {
+        listener = listener.with_admin_key(key);
+    }
+    Listeners::new().with_listener(listener).spawn()
+}
+
+/// @summary - Show the dashboard of the remote node of --connect, or of the in-process blackboard while serving it
+async fn monitor<B: BlackboardTrait + Sync + Send + 'static>(blackboard: B, args: &Args, admin_key: Option<&str>) -> std::io::Result<()> {
+    let Some(addr) = &args.connect else {
+        let changes = blackboard.subscribe();
+        let _served = listen(blackboard.clone(), args, admin_key);
+        return dashboard::run(Backend::Local(LocalBlackboardInterface::new_with(blackboard)), Some(changes)).await;
+    };
+    let client = match SocketClient::connect(addr, ReconnectPolicy::default()).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error connecting to {}: {}", addr, e);
+            std::process::exit(2);
+        }
+    };
+    let client = match admin_key {
+        Some(key) => client.with_admin_key(key),
+        None => client,
+    };
+    dashboard::run(Backend::<B>::Remote(Box::new(RemoteBlackboardInterface::new_with(client))), None).await
+}
+
+/// @summary - Read the lines typed in the terminal with the line editor, until Ctrl-D or `:quit`
+async fn interact<B: BlackboardTrait + Sync>(repl: &mut Repl<B>, mut editor: LineEditor) -> std::io::Result<()> {
+    // The changes of the tokens watched are printed as they come, even while a line is typed
+    if let Some(mut notifications) = repl.take_notifications() {
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                print!("\r\x1b[K{}\r\n", notification);
+            }
+        });
+    }
+    let mut input = Input::default();
+    loop {
+        // The tokens completed are the ones of the store when the line is started
+        editor.completer_mut().set_tokens(repl.tokens().await);
+        // The editor blocks on the terminal, away from the runtime running the agents
+        let prompt = repl.prompt(&input).await;
+        let (returned, line) = tokio::task::spawn_blocking(move || {
+            let line = editor.read_line(&prompt);
+            (editor, line)
+        }).await?;
+        editor = returned;
+        let Some(line) = line? else { return Ok(()) };
+        let Some(line) = input.push(&line) else { continue };
+        if line.is_empty() && !repl.is_stepping() {
+            continue;
+        }
+        match repl.step(&line).await {
+            Step::Print(outcome) => println!("{}", outcome),
+            Step::Quit => return Ok(()),
+        }
+    }
+}
@@ -1,6 +0,0 @@
-#[derive(Debug)]
-pub enum CLIError {
-    ParseError(String),
-    UnknownPrimitive(String),
-    CommuncationError(String),
-}
\ No newline at end of file
@@ -0,0 +1,143 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::time::timeout;
+use bacht::blackboard::BlackboardTrait;
+use bacht::log;
+use bacht::log::Level;
+use crate::checkpoint;
+use crate::repl::{Repl, Step};
+
+// The page typing the agents and showing the store, served on /
+const PAGE: &str = include_str!("playground.html");
+
+// The agents typed are small, anything bigger is not a request of the page
+const MAX_REQUEST_LENGTH: usize = 65536;
+
+// A visitor sending its request slower than that is dropped, the other visitors waiting for the REPL meanwhile
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// @summary - Serve the web page of the playground, where the agents typed are run by the REPL and the store is shown
+/// as it changes
+///
+/// `GET /` is the page, `POST /step` runs the line of its body as typed in the REPL, answering its outcome, and
+/// `GET /store` answers the store as a JSON object, each token with its number of occurrences.
+///
+/// @note - The visitors share the REPL, its store and its step mode
+pub async fn serve<B: BlackboardTrait + Sync + Send + 'static>(repl: Repl<B>, address: IpAddr, port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind(SocketAddr::new(address, port)).await
+        .map_err(|e| format!("Failed to bind playground port: {}", e))?;
+    log!(Level::Info, "Playground on http://{}", SocketAddr::new(address, port));
+    let repl = Arc::new(Mutex::new(repl));
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                log!(Level::Error, "Error accepting visitor: {}", e);
+                continue;
+            }
+        };
+        let repl = repl.clone();
+        tokio::spawn(async move {
+            if let Err(e) = answer(stream, repl).await {
+                log!(Level::Error, "Error answering visitor: {}", e);
+            }
+        });
+    }
+}
+
+async fn answer<B: BlackboardTrait + Sync>(mut stream: TcpStream, repl: Arc<Mutex<Repl<B>>>) -> std::io::Result<()> {
+    let Ok(request) = timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await else { return Ok(()) };
+    let (request_line, body) = request?;
+    let (status, content_type, body) = respond(&mut *repl.lock().await, &request_line, &body).await;
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+// The request line and the body, whose length is given by the Content-Length header
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<(String, String)> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 4096];
+    let header_end = loop {
+        if let Some(end) = request.windows(4).position(|end| end == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 || request.len() > MAX_REQUEST_LENGTH {
+            break request.len();
+        }
+        request.extend_from_slice(&buffer[..read]);
+    };
+    let header = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let length = header.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_REQUEST_LENGTH);
+    while request.len() < header_end + length {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let body = String::from_utf8_lossy(&request[header_end..(header_end + length).min(request.len())]).to_string();
+    Ok((header.lines().next().unwrap_or_default().to_string(), body))
+}
+
+/// @summary - Answer a request of the page
+///
+/// @param request_line - The first line of the HTTP request, e.g. `POST /step HTTP/1.1`
+///
+/// @returns - The status line, the content type and the body of the response
+async fn respond<B: BlackboardTrait + Sync>(repl: &mut Repl<B>, request_line: &str, body: &str) -> (&'static str, &'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/")) => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+        (Some("GET"), Some("/store")) => match repl.store().await {
+            Ok(tokens) => ("200 OK", "application/json", checkpoint::to_json(&tokens)),
+            Err(e) => ("500 Internal Server Error", "text/plain; charset=utf-8", e),
+        },
+        // The page cannot leave the shared REPL
+        (Some("POST"), Some("/step")) if matches!(body.trim(), ":quit" | ":q") => {
+            ("200 OK", "text/plain; charset=utf-8", "The playground cannot be left".to_string())
+        },
+        (Some("POST"), Some("/step")) => match repl.step(body.trim()).await {
+            Step::Print(outcome) => ("200 OK", "text/plain; charset=utf-8", outcome),
+            Step::Quit => ("200 OK", "text/plain; charset=utf-8", String::new()),
+        },
+        (Some("GET" | "POST"), _) => ("404 Not Found", "text/plain; charset=utf-8", String::new()),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", String::new()),
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bacht::blackboard::create_blackboard;
+
+    #[tokio::test]
+    async fn playground_should_run_the_lines_posted_and_show_the_store() {
+        let mut repl = Repl::new_with(create_blackboard());
+        assert_eq!(respond(&mut repl, "GET / HTTP/1.1", "").await.2, PAGE);
+        assert_eq!(respond(&mut repl, "POST /step HTTP/1.1", "tell(a);tell(b)\n").await.2, "Success");
+        assert_eq!(respond(&mut repl, "POST /step HTTP/1.1", ":step get(a);get(b)").await.2,
+            "Stepping get(a);get(b), press Enter to perform each transition");
+        assert_eq!(respond(&mut repl, "POST /step HTTP/1.1", "").await.2, "get(a) => get(b)");
+        assert_eq!(respond(&mut repl, "GET /store HTTP/1.1", "").await, ("200 OK", "application/json", "{\n  \"b\": 1\n}\n".to_string()));
+        assert_eq!(respond(&mut repl, "POST /step HTTP/1.1", ":quit").await.2, "The playground cannot be left");
+        assert_eq!(respond(&mut repl, "GET /agents HTTP/1.1", "").await.0, "404 Not Found");
+        assert_eq!(respond(&mut repl, "DELETE /store HTTP/1.1", "").await.0, "405 Method Not Allowed");
+    }
+}
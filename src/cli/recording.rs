@@ -0,0 +1,133 @@
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use bacht::blackboard::BlackboardTrait;
+use crate::repl::{PROMPT, Repl, Step};
+use crate::style::Color;
+
+/// @summary - A line entered in a recorded session, with its outcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// When the line was entered, in milliseconds since the start of the recording
+    pub at: f64,
+    pub line: String,
+    /// The outcome printed, without colors
+    pub outcome: String,
+}
+
+impl Record {
+
+    /// @returns - The record on one line: its time, its line and its outcome separated by tabs, the outcome escaped
+    pub fn encode(&self) -> String {
+        format!("{:.3}\t{}\t{}", self.at, self.line, escape(&self.outcome))
+    }
+
+    /// @summary - Read a record written by encode
+    pub fn decode(encoded: &str) -> Result<Self, String> {
+        let mut fields = encoded.splitn(3, '\t');
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(at), Some(line), Some(outcome)) => Ok(Self {
+                at: at.parse().map_err(|_| format!("invalid time {}", at))?,
+                line: line.to_string(),
+                outcome: unescape(outcome),
+            }),
+            _ => Err("expected a time, a line and an outcome separated by tabs".to_string()),
+        }
+    }
+
+    // The lines compared when replayed, the indented ones varying between runs, e.g. the times of :timing
+    fn significant(outcome: &str) -> Vec<&str> {
+        outcome.lines().filter(|line| !line.starts_with("  ")).collect()
+    }
+}
+
+fn escape(outcome: &str) -> String {
+    outcome.replace('\\', "\\\\").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape(escaped: &str) -> String {
+    let mut outcome = String::new();
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => outcome.push('\n'),
+                Some('t') => outcome.push('\t'),
+                Some(other) => outcome.push(other),
+                None => outcome.push('\\'),
+            },
+            (c, false) => outcome.push(c),
+        }
+    }
+    outcome
+}
+
+/// @returns - The text without the ANSI escape sequences coloring it
+pub fn plain(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // The color sequences end with m
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// @summary - Enter again the lines of a recorded session, printing each of them with its outcome, and the recorded
+/// outcome when it differs
+///
+/// @param repl - On a fresh blackboard
+///
+/// @returns - true if every outcome is the recorded one, the indented lines of the outcomes aside
+pub async fn replay<B, W>(repl: &mut Repl<B>, records: &[Record], mut output: W) -> std::io::Result<bool>
+where B: BlackboardTrait + Sync, W: AsyncWrite + Unpin {
+    let mut same = true;
+    for record in records {
+        let outcome = match repl.step(&record.line).await {
+            Step::Print(outcome) => outcome,
+            Step::Quit => break,
+        };
+        output.write_all(format!("{}{}\n{}\n", PROMPT, record.line, outcome).as_bytes()).await?;
+        if Record::significant(&plain(&outcome)) != Record::significant(&record.outcome) {
+            same = false;
+            let recorded = format!("Recorded at {:.3} ms: {}", record.at, record.outcome.replace('\n', "\n  "));
+            output.write_all(format!("{}\n", repl.style().paint(Color::Yellow, &recorded)).as_bytes()).await?;
+        }
+    }
+    output.flush().await?;
+    Ok(same)
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bacht::blackboard::create_blackboard;
+
+    #[test]
+    fn record_should_be_read_back_as_written() {
+        let record = Record { at: 12.5, line: "get(a)".to_string(), outcome: "a\t1\nb \\ 2".to_string() };
+        assert_eq!(record.encode(), "12.500\tget(a)\ta\\t1\\nb \\\\ 2");
+        assert_eq!(Record::decode(&record.encode()).unwrap(), record);
+        assert_eq!(Record::decode("12\tget(a)").unwrap_err(), "expected a time, a line and an outcome separated by tabs");
+        assert_eq!(plain("\x1b[32mSuccess\x1b[0m"), "Success");
+    }
+
+    #[tokio::test]
+    async fn replay_should_report_the_outcomes_differing_from_the_recorded_ones() {
+        let records: Vec<Record> = ["0\t:seed 4\tSeeded with 4", "1.5\ttell(a)\tSuccess", "3\tget(a);get(a)\tSuccess\\n  1.0 ms"].iter()
+            .map(|encoded| Record::decode(encoded).unwrap())
+            .collect();
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        assert!(!replay(&mut repl, &records, &mut output).await.unwrap());
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "bacht> :seed 4\nSeeded with 4\nbacht> tell(a)\nSuccess\nbacht> get(a);get(a)\nFailure: the agent is stuck\n\
+            Recorded at 3.000 ms: Success\n    1.0 ms\n");
+    }
+}
@@ -0,0 +1,1414 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use bacht::blackboard::BlackboardTrait;
+use bacht::communication::socket_client::{ReconnectPolicy, SocketClient};
+use bacht::model::admin::{AdminCommand, AdminReply};
+use bacht::language::model::data::Expr;
+use bacht::error::{Error, ParseErrorKind, TransportError};
+use bacht::language::parser::{Dialect, parse_definition, parse_with, strip_comments};
+use bacht::language::simulator::{Simulator, SimulatorTrait, TraceEntry};
+use bacht::language::blackboard_interface::{BlackboardInterfaceTrait, LocalBlackboardInterface, RemoteBlackboardInterface};
+use crate::backend::Backend;
+use crate::checkpoint;
+use crate::recording::{Record, plain};
+use crate::style::{Color, Style};
+use crate::watch::{watch_local, watch_remote};
+
+pub const PROMPT: &str = "bacht> ";
+
+// Shown while the agent typed is incomplete
+const CONTINUATION_PROMPT: &str = "  ...> ";
+
+/// The prompt of `:prompt summary`, showing the leftovers of the previous agents
+pub const SUMMARY_PROMPT: &str = "[{tokens} tokens|{queued} queued]> ";
+
+/// The meta-commands, completed by the line editor
+pub const COMMANDS: [&str; 25] = ["history", "undo", "record", "store", "clear", "tell", "get", "count", "watch", "unwatch", "save", "load", "def", "defs", "undef", "seed", "step", "trace", "timing", "dryrun", "autoprint", "prompt", "connect", "help", "quit"];
+
+const HELP: &str = "\
+Type an agent to run it on the blackboard, e.g. `tell(a);(get(a)+ask(b))`, or a command.
+An agent the parser expects more of, e.g. ending with an operator or with unclosed parentheses, continues on the
+next line.
+Comments, from // to the end of the line or between /* and */, are skipped.
+*A replicates the agent A, a copy starting at each step it can, e.g. *(get(job);tell(done)).
+P -> A guards the agent A of a choice by the primitive P, e.g. ask(a) -> tell(b) + ask(c) -> tell(d) waits for a or c.
+skip is the empty agent, terminating in one step without executing a primitive, e.g. ask(x);skip + tell(y).
+  :store    list the tokens of the store with their number of occurrences
+  :clear    remove every token of the store
+  :tell T N add N occurrences of the token T to the store, 1 by default, :get T N removes them
+  :count T  show the number of occurrences of the token T
+  :watch T  print a line each time the number of occurrences of the token T changes, :unwatch T stops,
+            :watch alone lists the tokens watched; the watches stop when connecting to another blackboard
+  :save F   save the store to the JSON file F
+  :load F   replace the store with the tokens of the JSON file F
+  :def N = A define the agent A, which the next agents can call by the name N, e.g. :def producer = tell(item);producer,
+            or with parameters, e.g. :def producer(x) = tell(x);producer(x) called as producer(item); the colon
+            of def is optional
+  :defs     list the agents defined, :undef N removes the definition of N
+  :seed N   pick the branches of the next agents reproducibly, from the seed N
+  :step A   run the agent A one transition each time Enter is pressed, :step alone stops
+  :trace on|off
+            print each primitive attempted by the next agents, with its result and time
+  :prompt T set the prompt to the template T, where {tokens}, {occurrences} and {queued} are replaced by the
+            counters of the blackboard, :prompt summary shows the tokens and the queue, :prompt default restores it
+  :timing on|off
+            print the time taken by the next agents, with their transitions and blocked attempts
+  :dryrun on|off
+            only parse the next agents, printing them with each operator parenthesized and as a tree, without running
+            them
+  :autoprint on|summary|off
+            print the store after each agent and transition, or only the tokens it changed with summary
+  :connect host:port
+            run the next agents on a remote blackboard node, :connect alone comes back to the local blackboard; the
+            node only accepts :store, :clear, :save and :load from a REPL started with its key, bach_cli --admin-key K
+  :undo     reverse the changes of the store made by the last agent, except the tokens it told and others got since
+  :record F record the lines entered next with their outcome in the file F, to replay them with bach_cli replay F,
+            :record alone stops; the branches are seeded when the recording starts, so that the replay picks the same
+  :history  list the agents run, !n runs again the agent n of the list and !! the last one
+  :help     show this help
+  :quit     leave the REPL";
+
+fn occurrences(count: u32, token: &str) -> String {
+    format!("{} occurrence{} of {}", count, if count == 1 { "" } else { "s" }, token)
+}
+
+/// What the REPL prints of the store after each agent and transition, see `:autoprint`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Autoprint {
+    Off,
+    /// The whole store, unusable for large stores
+    Store,
+    /// The tokens whose number of occurrences changed, with their change
+    Summary,
+}
+
+/// What the REPL does after a line
+pub enum Step {
+    /// Print the outcome of the line, then read the next one
+    Print(String),
+    Quit,
+}
+
+/// @summary - The Input gathers the lines of an agent typed across several lines.
+///
+/// An agent is incomplete while the parser expects more of it, e.g. on unclosed parentheses or comments, or after an
+/// operator, e.g. `tell(a);`
+#[derive(Default)]
+pub struct Input {
+    pending: String,
+}
+
+impl Input {
+
+    /// @returns - The prompt for the next line, a continuation prompt while the agent is incomplete
+    pub fn prompt(&self) -> &'static str {
+        if self.pending.is_empty() { PROMPT } else { CONTINUATION_PROMPT }
+    }
+
+    /// @summary - Add a line to the agent being typed
+    ///
+    /// @returns - The agent or the meta-command once complete, None while more lines are expected
+    pub fn push(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        if self.pending.is_empty() && line.starts_with([':', '!']) {
+            return Some(line.to_string());
+        }
+        // The lines are joined as they are, the agents have no whitespace, but a comment ends with its line
+        if self.pending.contains("//") || self.pending.contains("/*") {
+            self.pending.push('\n');
+        }
+        self.pending.push_str(line);
+        if Self::is_incomplete(&self.pending) {
+            return None;
+        }
+        let statement = std::mem::take(&mut self.pending);
+        // Comments alone are skipped
+        (statement.is_empty() || !strip_comments(&statement).trim().is_empty()).then_some(statement)
+    }
+
+    // The parser tells whether the lines typed so far stop before the end of an agent, in the widest dialect
+    fn is_incomplete(statement: &str) -> bool {
+        if strip_comments(statement).trim().is_empty() {
+            return false;
+        }
+        let parsed = match definition(statement) {
+            Some(_) => parse_definition(statement, Dialect::Linda).map(|_| ()),
+            None => parse_with(statement, Dialect::Linda).map(|_| ()),
+        };
+        parsed.is_err_and(|e| e.kind == ParseErrorKind::Incomplete)
+    }
+}
+
+// A definition typed without the colon of :def is the command itself
+fn definition(line: &str) -> Option<&str> {
+    line.starts_with("def ").then_some(line)
+}
+
+/// @returns - The agent with each of its operators parenthesized, then its tree, showing how the agent was parsed
+fn explain(agent: &Expr) -> String {
+    fn joined(operator: &str, agents: &[Expr]) -> String {
+        agents.iter().map(grouped).collect::<Vec<_>>().join(operator)
+    }
+    fn grouped(agent: &Expr) -> String {
+        match agent {
+            Expr::BachtAstAgent(operator, agents) => format!("({})", joined(operator, agents)),
+            agent => agent.to_string(),
+        }
+    }
+    fn tree(agent: &Expr, prefix: &str, lines: &mut Vec<String>) {
+        let Expr::BachtAstAgent(operator, agents) = agent else { return };
+        let name = match *operator {
+            ";" => "sequence",
+            "||" => "parallel",
+            _ => "choice",
+        };
+        lines.last_mut().unwrap().push_str(&format!("{} {}", operator, name));
+        for (i, operand) in agents.iter().enumerate() {
+            let last = i + 1 == agents.len();
+            lines.push(format!("{}{}", prefix, if last { "└── " } else { "├── " }));
+            match operand {
+                Expr::BachtAstAgent(..) => tree(operand, &format!("{}{}", prefix, if last { "    " } else { "│   " }), lines),
+                operand => lines.last_mut().unwrap().push_str(&operand.to_string()),
+            }
+        }
+    }
+    let full = match agent {
+        Expr::BachtAstAgent(operator, agents) => joined(operator, agents),
+        agent => agent.to_string(),
+    };
+    let mut lines = vec![full, String::new()];
+    match agent {
+        Expr::BachtAstAgent(..) => tree(agent, "", &mut lines),
+        agent => lines[1] = agent.to_string(),
+    }
+    lines.join("\n")
+}
+
+/// @summary - The Repl reads BachT agents line by line, runs each of them on the blackboard and prints its outcome.
+///
+/// The agents are run by the Simulator on the blackboard of the core, on the tokio runtime, i.e. the same code path
+/// as the programs submitted to a blackboard node.
+///
+/// The lines starting with a colon are meta-commands inspecting or resetting the blackboard, see `:help`.
+///
+/// The agents may also be run on a remote node, through the socket client: the same store is then shared with the
+/// agents of other machines.
+pub struct Repl<B: BlackboardTrait + Sync> {
+    // The in-process blackboard, kept while connected to a remote node
+    blackboard: B,
+    simulator: Simulator<Backend<B>>,
+    // Presented to the remote nodes, for the meta-commands managing their store
+    admin_key: Option<String>,
+    style: Style,
+    // The remaining agent of the step mode
+    stepping: Option<String>,
+    // The primitives attempted since the last outcome printed
+    traced: Arc<Mutex<Vec<TraceEntry>>>,
+    tracing: bool,
+    timing: bool,
+    // The template of the prompt, the default prompt when None
+    prompt: Option<String>,
+    // The tasks watching the tokens, sending their lines to the notifications
+    watches: HashMap<String, JoinHandle<()>>,
+    notify: UnboundedSender<String>,
+    notifications: Option<UnboundedReceiver<String>>,
+    // The agents run in the session, recalled by `!!` and `!n`
+    history: Vec<String>,
+    // The net changes of the store made by each agent, the most recent last, reversed by `:undo`
+    deltas: Vec<Vec<(String, i64)>>,
+    autoprint: Autoprint,
+    // The file the lines entered are recorded in, with the start of the recording
+    recording: Option<(std::fs::File, Instant)>,
+    // The agents are only parsed and explained, see `:dryrun`
+    dry_run: bool,
+    // The grammar of the agents typed
+    dialect: Dialect,
+}
+
+impl<B: BlackboardTrait + Sync> Drop for Repl<B> {
+    fn drop(&mut self) {
+        self.watches.values().for_each(JoinHandle::abort);
+    }
+}
+
+impl<B: BlackboardTrait + Sync> Repl<B> {
+
+    /// @param blackboard - The blackboard the agents are run on, its store is kept between the lines
+    pub fn new_with(blackboard: B) -> Self {
+        let (notify, notifications) = mpsc::unbounded_channel();
+        let repl = Self {
+            simulator: Simulator::new_with(Backend::Local(LocalBlackboardInterface::new_with(blackboard.clone()))),
+            blackboard,
+            admin_key: None,
+            style: Style::default(),
+            stepping: None,
+            traced: Arc::default(),
+            tracing: false,
+            timing: false,
+            prompt: None,
+            watches: HashMap::new(),
+            notify,
+            notifications: Some(notifications),
+            history: Vec::new(),
+            deltas: Vec::new(),
+            autoprint: Autoprint::Off,
+            recording: None,
+            dry_run: false,
+            dialect: Dialect::default(),
+        };
+        repl.install_tracer();
+        repl
+    }
+
+    /// @summary - Color the outcomes and the store listings, e.g. `Style::detect()` on a terminal
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// @summary - Print each primitive attempted by the agents before their outcome, as `:trace on`
+    pub fn with_trace(mut self) -> Self {
+        self.tracing = true;
+        self.install_tracer();
+        self
+    }
+
+    /// @summary - Print the time taken by each agent with its number of transitions and blocked attempts, as `:timing on`
+    pub fn with_timing(mut self) -> Self {
+        self.timing = true;
+        self.install_tracer();
+        self
+    }
+
+    /// @summary - Parse the agents and print how they were understood without running them, as `:dryrun on`
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// @summary - Accept the primitives of Linda in the agents and the definitions, `out`, `rd`, `in`, `inp` and `rdp`
+    pub fn with_linda(mut self) -> Self {
+        self.dialect = Dialect::Linda;
+        self.simulator.set_dialect(Dialect::Linda);
+        self
+    }
+
+    /// @summary - Show the counters of the blackboard in the prompt, as `:prompt`
+    ///
+    /// @param template - The prompt, where `{tokens}`, `{occurrences}` and `{queued}` are replaced by the counters
+    pub fn with_prompt(mut self, template: &str) -> Self {
+        self.prompt = Some(template.to_string());
+        self
+    }
+
+    /// @summary - Pick the branches of the agents reproducibly, as `:seed`
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.simulator.seed(seed);
+        self
+    }
+
+    /// @summary - Present this key to the remote nodes, so that `:store` and `:clear` are accepted there
+    pub fn with_admin_key(mut self, key: &str) -> Self {
+        self.admin_key = Some(key.to_string());
+        self
+    }
+
+    /// @summary - Run the next agents on a remote blackboard node
+    ///
+    /// @param addr - The address of the node, host:port or unix:<path>
+    ///
+    /// @note - The seed of the branches is reset
+    pub async fn connect(&mut self, addr: &str) -> Result<(), TransportError> {
+        let client = self.open(addr).await?;
+        self.switch(Backend::Remote(Box::new(RemoteBlackboardInterface::new_with(client))));
+        Ok(())
+    }
+
+    async fn open(&self, addr: &str) -> Result<SocketClient, TransportError> {
+        // Give up quickly on an unreachable node, the user may retry
+        let policy = ReconnectPolicy { max_attempts: Some(3), ..ReconnectPolicy::default() };
+        let client = SocketClient::connect(addr, policy).await?;
+        Ok(match &self.admin_key {
+            Some(key) => client.with_admin_key(key),
+            None => client,
+        })
+    }
+
+    /// @summary - Run the next agents on the in-process blackboard again
+    pub fn disconnect(&mut self) {
+        self.switch(Backend::Local(LocalBlackboardInterface::new_with(self.blackboard.clone())));
+    }
+
+    // Run the next agents on another blackboard, with the same definitions and tracing
+    fn switch(&mut self, backend: Backend<B>) {
+        let simulator = Simulator::new_with(backend);
+        simulator.set_dialect(self.dialect);
+        for (name, body) in self.simulator.definitions() {
+            // Already parsed once
+            simulator.define(&name, &body).expect("A definition should stay valid");
+        }
+        self.simulator = simulator;
+        self.install_tracer();
+        self.watches.drain().for_each(|(_, watch)| watch.abort());
+        self.deltas.clear();
+    }
+
+    // The primitives attempted are always recorded, the changes of the store being undone from them
+    fn install_tracer(&self) {
+        self.traced.lock().unwrap().clear();
+        let traced = self.traced.clone();
+        self.simulator.set_tracer(Some(Box::new(move |entry: &TraceEntry| traced.lock().unwrap().push(entry.clone()))));
+    }
+
+    /// @summary - Surround an outcome with the primitives attempted since the start of its agent while tracing, and
+    /// with the time it took while timing
+    ///
+    /// @note - The changes of the store made by these primitives are recorded for `:undo`, and printed after the outcome
+    /// depending on `:autoprint`
+    async fn report(&mut self, started: Instant, outcome: String) -> String {
+        let traced: Vec<TraceEntry> = self.traced.lock().unwrap().drain(..).collect();
+        let delta = self.record(&traced);
+        let mut lines = Vec::new();
+        if self.tracing {
+            lines.extend(traced.iter().map(|entry| {
+                let elapsed = entry.at.saturating_duration_since(started).as_secs_f64() * 1000.0;
+                let result = if entry.result {
+                    self.style.paint(Color::Green, "executed")
+                } else {
+                    self.style.paint(Color::Yellow, "blocked")
+                };
+                format!("  [{:>9.3} ms] {}({}) {}", elapsed, entry.primitive, entry.token, result)
+            }));
+        }
+        lines.push(outcome);
+        if self.timing {
+            let transitions = traced.iter().filter(|entry| entry.result).count();
+            let blocked = traced.len() - transitions;
+            lines.push(format!("  {:.3} ms, {} transition{}, {} blocked attempt{}", started.elapsed().as_secs_f64() * 1000.0,
+                transitions, if transitions == 1 { "" } else { "s" }, blocked, if blocked == 1 { "" } else { "s" }));
+        }
+        match self.autoprint {
+            Autoprint::Off => {},
+            Autoprint::Store => lines.push(match self.store().await {
+                Ok(tokens) if tokens.is_empty() => "The store is empty".to_string(),
+                Ok(tokens) => self.style.store(&tokens),
+                Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+            }),
+            Autoprint::Summary if delta.is_empty() => lines.push("No token changed".to_string()),
+            Autoprint::Summary => lines.push(format!("Changed {}", delta.iter()
+                .map(|(token, change)| format!("{} {:+}", self.style.paint(Color::Cyan, token), change))
+                .collect::<Vec<_>>()
+                .join(", "))),
+        }
+        lines.join("\n")
+    }
+
+    // The tokens told and got by the primitives executed, each token with its net change
+    fn record(&mut self, traced: &[TraceEntry]) -> Vec<(String, i64)> {
+        let mut delta: Vec<(String, i64)> = Vec::new();
+        for entry in traced.iter().filter(|entry| entry.result) {
+            let change = match entry.primitive.as_str() {
+                "tell" => 1,
+                "get" => -1,
+                _ => continue,
+            };
+            match delta.iter_mut().find(|(token, _)| *token == entry.token) {
+                Some((_, count)) => *count += change,
+                None => delta.push((entry.token.clone(), change)),
+            }
+        }
+        delta.retain(|(_, count)| *count != 0);
+        if !delta.is_empty() {
+            self.deltas.push(delta.clone());
+        }
+        delta
+    }
+
+    /// @summary - Reverse the changes of the store made by the last agent, getting the tokens it told and telling
+    /// back the tokens it got
+    ///
+    /// @returns - The primitives run to reverse them, or the reason why nothing is undone
+    ///
+    /// @note - The tokens told but got since by other agents cannot be got back, they are reported as not reversed
+    async fn undo(&mut self) -> Result<String, String> {
+        let delta = self.deltas.pop().ok_or("Nothing to undo")?;
+        let blackboard = self.simulator.blackboard();
+        let mut undone = Vec::new();
+        let mut missing = Vec::new();
+        for (token, change) in delta {
+            let (primitive, times) = if change > 0 { ("get", change) } else { ("tell", -change) };
+            let mut reversed = 0;
+            for _ in 0..times {
+                let executed = match primitive {
+                    "get" => blackboard.get(&token).await,
+                    _ => blackboard.tell(&token).await,
+                }.map_err(|e| format!("Error: {}", e))?;
+                reversed += executed as i64;
+            }
+            if reversed > 0 {
+                undone.push(if reversed == 1 { format!("{}({})", primitive, token) } else { format!("{}({}) x{}", primitive, token, reversed) });
+            }
+            if reversed < times {
+                missing.push(format!("{} x{}", token, times - reversed));
+            }
+        }
+        let undone = if undone.is_empty() { "Nothing undone".to_string() } else { format!("Undone with {}", undone.join(", ")) };
+        match missing.is_empty() {
+            true => Ok(undone),
+            false => Err(format!("{}, not reversed as got since: {}", undone, missing.join(", "))),
+        }
+    }
+
+    /// @summary - Parse an agent and run it until it terminates or gets stuck
+    ///
+    /// @returns - true if the agent terminated, false if it got stuck, Error::Parse if it does not parse, or
+    /// Error::Cancelled if Ctrl-C stopped it, e.g. `*tell(a)` before the step limit of the simulator
+    pub async fn eval(&self, line: &str) -> Result<bool, Error> {
+        let agent = parse_with(line, self.dialect)?;
+        tokio::select! {
+            result = self.simulator.bacht_exec_all(agent) => result,
+            _ = tokio::signal::ctrl_c() => Err(Error::Cancelled),
+        }
+    }
+
+    /// @summary - Apply a meta-command, the line without its colon
+    async fn command(&mut self, command: &str) -> Step {
+        // The body of a definition follows its name and an equal sign
+        if let Some(definition) = command.strip_prefix("def ") {
+            let outcome = match definition.split_once('=') {
+                Some((name, body)) => match self.define(name.trim(), body.trim()) {
+                    Ok(()) => format!("Defined {}", name.trim()),
+                    Err(Error::Parse(e)) => self.style.paint(Color::Red, &format!("Parse error: {}", e.diagnostic())),
+                    Err(e @ Error::InvalidName(_)) => self.style.paint(Color::Red, &format!("Parse error: {}", e)),
+                    Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+                },
+                None => self.style.paint(Color::Red, "Expected :def name = agent"),
+            };
+            return Step::Print(outcome);
+        }
+        // The template of the prompt may contain spaces
+        if let Some(template) = command.strip_prefix("prompt ") {
+            self.prompt = match template {
+                "default" => None,
+                "summary" => Some(SUMMARY_PROMPT.to_string()),
+                // The line being trimmed, the space separating the prompt from the input is added back
+                template => Some(format!("{} ", template)),
+            };
+            return Step::Print("Prompt changed".to_string());
+        }
+        let mut words = command.split_whitespace();
+        let outcome = match (words.next(), words.next(), words.next()) {
+            (Some("quit" | "q"), None, _) => return Step::Quit,
+            (Some("help" | "h"), None, _) => HELP.to_string(),
+            (Some("store"), None, _) => match self.simulator.blackboard().admin(AdminCommand::Snapshot).await {
+                Ok(AdminReply::Snapshot(tokens)) if tokens.is_empty() => "The store is empty".to_string(),
+                Ok(AdminReply::Snapshot(tokens)) => self.style.store(&tokens),
+                Ok(reply) => self.style.paint(Color::Red, &format!("Error: unexpected reply {:?}", reply)),
+                Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+            },
+            (Some("watch"), None, _) => match self.watches.keys().collect::<Vec<_>>() {
+                watched if watched.is_empty() => "No token is watched".to_string(),
+                mut watched => {
+                    watched.sort();
+                    format!("Watching {}", watched.iter().map(|token| token.as_str()).collect::<Vec<_>>().join(", "))
+                },
+            },
+            (Some("watch"), Some(token), None) => match self.watch(token).await {
+                Ok(count) => format!("Watching {}, {} occurrences", token, count),
+                Err(e) => self.style.paint(Color::Red, &format!("Error watching {}: {}", token, e)),
+            },
+            (Some("unwatch"), Some(token), None) => match self.watches.remove(token) {
+                Some(watch) => {
+                    watch.abort();
+                    format!("Stopped watching {}", token)
+                },
+                None => self.style.paint(Color::Red, &format!("{} is not watched", token)),
+            },
+            (Some("history"), None, _) if self.history.is_empty() => "No agent was run yet".to_string(),
+            (Some("history"), None, _) => self.history.iter().enumerate()
+                .map(|(index, agent)| format!("{:>4}  {}", index + 1, agent))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            (Some("defs"), None, _) => match self.simulator.definitions() {
+                definitions if definitions.is_empty() => "No agent is defined".to_string(),
+                definitions => definitions.iter().map(|(name, body)| format!("{} = {}", name, body)).collect::<Vec<_>>().join("\n"),
+            },
+            (Some("undef"), Some(name), None) => match self.simulator.undefine(name) {
+                true => format!("Undefined {}", name),
+                false => self.style.paint(Color::Red, &format!("{} is not defined", name)),
+            },
+            (Some(primitive @ ("tell" | "get")), Some(token), times) => match times.map_or(Ok(1), str::parse::<u32>) {
+                Ok(times) => match self.repeat(primitive, token, times).await {
+                    Ok(count) => occurrences(count, token),
+                    Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+                },
+                Err(_) => self.style.paint(Color::Red, &format!("Invalid number of occurrences {}", times.unwrap_or_default())),
+            },
+            (Some("count"), Some(token), None) => match self.count(token).await {
+                Ok(count) => occurrences(count, token),
+                Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+            },
+            (Some("record"), None, _) => match self.recording.take() {
+                Some(_) => "Recording stopped".to_string(),
+                None => "Not recording, type :record followed by a file".to_string(),
+            },
+            (Some("record"), Some(path), None) => match self.start_recording(path) {
+                Ok(seed) => format!("Recording to {}, seeded with {}", path, seed),
+                Err(e) => self.style.paint(Color::Red, &format!("Error recording to {}: {}", path, e)),
+            },
+            (Some("undo"), None, _) => match self.undo().await {
+                Ok(undone) => undone,
+                Err(e) => self.style.paint(Color::Red, &e),
+            },
+            (Some("clear"), None, _) => match self.simulator.blackboard().admin(AdminCommand::Clear).await {
+                Ok(_) => {
+                    self.deltas.clear();
+                    "The store is cleared".to_string()
+                },
+                Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+            },
+            (Some("save"), Some(path), None) => match self.save(path).await {
+                Ok(saved) => format!("Saved {} tokens to {}", saved, path),
+                Err(e) => self.style.paint(Color::Red, &format!("Error saving the store to {}: {}", path, e)),
+            },
+            (Some("load"), Some(path), None) => match self.load(path).await {
+                Ok(loaded) => {
+                    self.deltas.clear();
+                    format!("Loaded {} tokens from {}", loaded, path)
+                },
+                Err(e) => self.style.paint(Color::Red, &format!("Error loading the store from {}: {}", path, e)),
+            },
+            (Some("seed"), Some(seed), None) => match seed.parse::<u64>() {
+                Ok(seed) => {
+                    self.simulator.seed(seed);
+                    format!("Seeded with {}", seed)
+                },
+                Err(_) => self.style.paint(Color::Red, &format!("Invalid seed {}, expected an unsigned integer", seed)),
+            },
+            (Some("step"), None, _) => match self.stepping.take() {
+                Some(agent) => format!("Step mode left, {} remained", agent),
+                None => "Not stepping, type :step followed by an agent".to_string(),
+            },
+            (Some("step"), Some(agent), None) => match parse_with(agent, self.dialect) {
+                Ok(_) => {
+                    self.stepping = Some(agent.to_string());
+                    format!("Stepping {}, press Enter to perform each transition", agent)
+                },
+                Err(e) => self.style.paint(Color::Red, &format!("Parse error: {}", e.diagnostic())),
+            },
+            (Some("trace"), Some(mode @ ("on" | "off")), None) => {
+                self.tracing = mode == "on";
+                self.install_tracer();
+                format!("Tracing {}", mode)
+            },
+            (Some("autoprint"), Some(mode @ ("on" | "summary" | "off")), None) => {
+                self.autoprint = match mode {
+                    "on" => Autoprint::Store,
+                    "summary" => Autoprint::Summary,
+                    _ => Autoprint::Off,
+                };
+                format!("Autoprint {}", mode)
+            },
+            (Some("dryrun"), Some(mode @ ("on" | "off")), None) => {
+                self.dry_run = mode == "on";
+                format!("Dry run {}", mode)
+            },
+            (Some("timing"), Some(mode @ ("on" | "off")), None) => {
+                self.timing = mode == "on";
+                self.install_tracer();
+                format!("Timing {}", mode)
+            },
+            (Some("connect"), None, _) => {
+                self.disconnect();
+                "Running the agents on the local blackboard".to_string()
+            },
+            (Some("connect"), Some(addr), None) => match self.connect(addr).await {
+                Ok(()) => format!("Running the agents on {}", self.simulator.blackboard().describe()),
+                Err(e) => self.style.paint(Color::Red, &format!("Error connecting to {}: {}", addr, e)),
+            },
+            _ => self.style.paint(Color::Red, &format!("Unknown command :{}, type :help for the commands", command)),
+        };
+        Step::Print(outcome)
+    }
+
+    /// @summary - Print a line each time the number of occurrences of a token changes, whoever changes it
+    ///
+    /// @returns - The number of occurrences of the token when the watch starts
+    ///
+    /// @note - The lines are sent to the notifications, see take_notifications
+    async fn watch(&mut self, token: &str) -> Result<u32, String> {
+        let count = self.count(token).await?;
+        let watch = match self.simulator.blackboard() {
+            Backend::Local(_) => tokio::spawn(watch_local(self.blackboard.subscribe(), token.to_string(), self.notify.clone())),
+            // A connection of its own, not to delay the agents
+            Backend::Remote(remote) => {
+                let client = self.open(remote.client().addr()).await.map_err(|e| e.to_string())?;
+                let changes = client.subscribe(token).await.map_err(|e| e.to_string())?;
+                tokio::spawn(watch_remote(client, changes, token.to_string(), self.notify.clone()))
+            },
+        };
+        if let Some(previous) = self.watches.insert(token.to_string(), watch) {
+            previous.abort();
+        }
+        Ok(count)
+    }
+
+    /// @summary - Take the lines of the watches, to print them as they come, e.g. while the user is typing
+    ///
+    /// @returns - None if they were already taken
+    pub fn take_notifications(&mut self) -> Option<UnboundedReceiver<String>> {
+        self.notifications.take()
+    }
+
+    /// @summary - The lines of the watches received so far, unless they were taken
+    async fn notified(&mut self) -> Vec<String> {
+        let Some(notifications) = &mut self.notifications else { return Vec::new() };
+        // Let the watches receive the changes of the last line
+        tokio::task::yield_now().await;
+        std::iter::from_fn(|| notifications.try_recv().ok()).collect()
+    }
+
+    /// @returns - The tokens of the store with their number of occurrences, sorted by token, or why the store cannot
+    /// be dumped, e.g. on a remote node without the admin key
+    pub async fn store(&self) -> Result<Vec<(Box<str>, u32)>, String> {
+        match self.simulator.blackboard().admin(AdminCommand::Snapshot).await {
+            Ok(AdminReply::Snapshot(tokens)) => Ok(tokens),
+            Ok(reply) => Err(format!("unexpected reply {:?}", reply)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// @returns - The number of occurrences of a token in the store, or why the store cannot be dumped
+    async fn count(&self, token: &str) -> Result<u32, String> {
+        let tokens = self.store().await?;
+        Ok(tokens.iter().find(|(present, _)| **present == *token).map_or(0, |(_, count)| *count))
+    }
+
+    /// @summary - Tell or get a token several times, bypassing the grammar of the agents, e.g. to set up a scenario
+    ///
+    /// @returns - The number of occurrences of the token in the store once done
+    ///
+    /// @note - The gets stop once the token is absent, and these changes of the store are not undone by `:undo`
+    async fn repeat(&self, primitive: &str, token: &str, times: u32) -> Result<u32, String> {
+        let blackboard = self.simulator.blackboard();
+        for _ in 0..times {
+            let executed = match primitive {
+                "tell" => blackboard.tell(token).await,
+                _ => blackboard.get(token).await,
+            }.map_err(|e| e.to_string())?;
+            if !executed {
+                break;
+            }
+        }
+        self.count(token).await
+    }
+
+    /// @summary - Define an agent for the rest of the session, which the next agents may call by its name
+    ///
+    /// @note - The definitions are kept when connecting to another blackboard
+    pub fn define(&self, name: &str, body: &str) -> Result<(), Error> {
+        self.simulator.define(name, body)
+    }
+
+    /// @summary - Checkpoint the store in a JSON file, each token with its number of occurrences
+    ///
+    /// @returns - The number of distinct tokens saved
+    async fn save(&self, path: &str) -> Result<usize, String> {
+        let tokens = self.store().await?;
+        std::fs::write(path, checkpoint::to_json(&tokens)).map_err(|e| e.to_string())?;
+        Ok(tokens.len())
+    }
+
+    /// @summary - Replace the store with the tokens of a JSON file written by `:save`
+    ///
+    /// @returns - The number of distinct tokens loaded
+    async fn load(&self, path: &str) -> Result<usize, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let tokens = checkpoint::from_json(&json)?;
+        let blackboard = self.simulator.blackboard();
+        blackboard.admin(AdminCommand::Clear).await.map_err(|e| e.to_string())?;
+        for (token, count) in &tokens {
+            for _ in 0..*count {
+                blackboard.tell(token).await.map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(tokens.len())
+    }
+
+    /// @returns - The tokens present in the store, none if it cannot be dumped, e.g. on a remote node without the admin key
+    pub async fn tokens(&self) -> Vec<String> {
+        match self.simulator.blackboard().admin(AdminCommand::Snapshot).await {
+            Ok(AdminReply::Snapshot(tokens)) => tokens.into_iter().map(|(token, _)| token.into()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// @returns - The prompt for the next line of the input, with the counters of the blackboard when it is a template
+    ///
+    /// @note - A counter that cannot be fetched, e.g. on a remote node without the admin key, is shown as `?`
+    pub async fn prompt(&self, input: &Input) -> String {
+        let Some(template) = self.prompt.as_ref().filter(|_| input.prompt() == PROMPT) else {
+            return input.prompt().to_string();
+        };
+        let counters = match self.simulator.blackboard().admin(AdminCommand::Stats).await {
+            Ok(AdminReply::Stats(counters)) => counters,
+            _ => Vec::new(),
+        };
+        ["tokens", "occurrences", "queued"].iter().fold(template.clone(), |prompt, name| {
+            let value = counters.iter().find(|(counter, _)| counter == name).map_or("?".to_string(), |(_, value)| value.to_string());
+            prompt.replace(&format!("{{{}}}", name), &value)
+        })
+    }
+
+    /// @returns - true while in step mode, where an empty line performs a transition
+    pub fn is_stepping(&self) -> bool {
+        self.stepping.is_some()
+    }
+
+    /// @summary - Perform one transition of the agent of the step mode
+    ///
+    /// @returns - The primitive executed and the remaining agent, the step mode is left once the agent terminated
+    async fn transition(&mut self) -> String {
+        let Some(source) = self.stepping.take() else { return String::new() };
+        let agent = match parse_with(&source, self.dialect) {
+            Ok(agent) => agent,
+            Err(e) => return self.style.paint(Color::Red, &format!("Parse error: {}", e.diagnostic())),
+        };
+        match self.simulator.step(agent).await {
+            Ok(Some(transition)) => {
+                let (primitive, token) = &transition.executed;
+                let executed = match token.is_empty() {
+                    // The empty agent executes no primitive
+                    true => self.style.paint(Color::Green, primitive),
+                    false => self.style.paint(Color::Green, &format!("{}({})", primitive, token)),
+                };
+                if transition.continuation == Expr::BachtAstEmptyAgent() {
+                    return format!("{} => the agent terminated", executed);
+                }
+                let continuation = transition.continuation.to_string();
+                self.stepping = Some(continuation.clone());
+                format!("{} => {}", executed, continuation)
+            },
+            Ok(None) => {
+                let stuck = self.style.paint(Color::Yellow, &format!("The agent is stuck: {}", source));
+                self.stepping = Some(source);
+                stuck
+            },
+            Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+        }
+    }
+
+    /// @summary - Start recording the lines entered with their outcome, seeding the branches with a random seed
+    ///
+    /// @returns - The seed, recorded first so that the replay picks the same branches
+    fn start_recording(&mut self, path: &str) -> std::io::Result<u64> {
+        let mut file = std::fs::File::create(path)?;
+        let seed = rand::random::<u64>();
+        self.simulator.seed(seed);
+        let record = Record { at: 0.0, line: format!(":seed {}", seed), outcome: format!("Seeded with {}", seed) };
+        writeln!(file, "{}", record.encode())?;
+        self.recording = Some((file, Instant::now()));
+        Ok(seed)
+    }
+
+    /// @summary - Run an agent, or apply a meta-command
+    ///
+    /// @note - In step mode, an empty line performs the next transition of the stepped agent. While recording, the line
+    /// and its outcome are written to the recording, except the `:record` commands
+    pub async fn step(&mut self, line: &str) -> Step {
+        let started = Instant::now();
+        let step = self.execute(line).await;
+        if let (Some((file, start)), Step::Print(outcome), false) = (self.recording.as_mut(), &step, line.starts_with(":record")) {
+            let record = Record {
+                at: started.saturating_duration_since(*start).as_secs_f64() * 1000.0,
+                line: line.to_string(),
+                outcome: plain(outcome),
+            };
+            if let Err(e) = writeln!(file, "{}", record.encode()) {
+                self.recording = None;
+                return Step::Print(format!("{}\n{}", outcome, self.style.paint(Color::Red, &format!("Recording stopped: {}", e))));
+            }
+        }
+        step
+    }
+
+    async fn execute(&mut self, line: &str) -> Step {
+        let started = Instant::now();
+        if line.is_empty() {
+            let outcome = self.transition().await;
+            return Step::Print(self.report(started, outcome).await);
+        }
+        if let Some(command) = line.strip_prefix(':').or_else(|| definition(line)) {
+            return self.command(command).await;
+        }
+        // The recalled agent is printed before its outcome
+        if let Some(recalled) = line.strip_prefix('!') {
+            let agent = match self.recall(recalled) {
+                Ok(agent) => agent,
+                Err(e) => return Step::Print(self.style.paint(Color::Red, &e)),
+            };
+            if self.dry_run {
+                return Step::Print(format!("{}\n{}", agent, self.dry(&agent).unwrap_or_else(|e| e)));
+            }
+            let outcome = self.describe(&self.eval(&agent).await);
+            self.history.push(agent.clone());
+            return Step::Print(format!("{}\n{}", agent, self.report(started, outcome).await));
+        }
+        if self.dry_run {
+            return Step::Print(self.dry(line).unwrap_or_else(|e| e));
+        }
+        let outcome = self.describe(&self.eval(line).await);
+        self.history.push(line.to_string());
+        Step::Print(self.report(started, outcome).await)
+    }
+
+    /// @returns - How an agent was parsed, see explain, or the parse error
+    fn dry(&self, line: &str) -> Result<String, String> {
+        match parse_with(line, self.dialect) {
+            Ok(agent) => Ok(explain(&agent)),
+            Err(e) => Err(self.style.paint(Color::Red, &format!("Parse error: {}", e.diagnostic()))),
+        }
+    }
+
+    /// @param recalled - `!` for the last agent run, or the number of an agent in `:history`
+    fn recall(&self, recalled: &str) -> Result<String, String> {
+        let index = match recalled {
+            "!" => self.history.len().checked_sub(1).ok_or("No agent was run yet")?,
+            number => match number.parse::<usize>() {
+                Ok(number) if (1..=self.history.len()).contains(&number) => number - 1,
+                _ => return Err(format!("No agent !{} in the history, see :history", number)),
+            },
+        };
+        Ok(self.history[index].clone())
+    }
+
+    fn describe(&self, result: &Result<bool, Error>) -> String {
+        match result {
+            Ok(true) => self.style.paint(Color::Green, "Success"),
+            Ok(false) => self.style.paint(Color::Yellow, "Failure: the agent is stuck"),
+            Err(Error::Parse(e)) => self.style.paint(Color::Red, &format!("Parse error: {}", e.diagnostic())),
+            Err(e @ Error::InvalidName(_)) => self.style.paint(Color::Red, &format!("Parse error: {}", e)),
+            Err(e) => self.style.paint(Color::Red, &format!("Error: {}", e)),
+        }
+    }
+
+    /// @summary - Run the statements of a script one after the other, without prompt, then print the final store
+    ///
+    /// @param script - The agents and meta-commands, one per line, an incomplete agent continuing on the next lines
+    ///
+    /// @returns - true if every agent terminated, false if one got stuck or did not parse
+    ///
+    /// @note - Each statement is printed with its outcome and the number of its first line
+    pub async fn run_script<W: AsyncWrite + Unpin>(&mut self, script: &str, mut output: W) -> std::io::Result<bool> {
+        let mut input = Input::default();
+        let mut succeeded = true;
+        let mut first_line = 1;
+        for (number, line) in script.lines().enumerate() {
+            if input.prompt() == PROMPT {
+                first_line = number + 1;
+            }
+            let Some(statement) = input.push(line) else { continue };
+            if statement.is_empty() {
+                continue;
+            }
+            let outcome = match statement.strip_prefix(':').or_else(|| definition(&statement)) {
+                Some(command) => match self.command(command).await {
+                    Step::Print(outcome) => outcome,
+                    Step::Quit => break,
+                },
+                None if self.dry_run => match self.dry(&statement) {
+                    Ok(explanation) => explanation,
+                    Err(e) => {
+                        succeeded = false;
+                        e
+                    },
+                },
+                None => {
+                    let started = Instant::now();
+                    let result = self.eval(&statement).await;
+                    succeeded &= matches!(result, Ok(true));
+                    let outcome = self.describe(&result);
+                    self.report(started, outcome).await
+                },
+            };
+            output.write_all(format!("{}: {} => {}\n", first_line, statement, outcome).as_bytes()).await?;
+        }
+        if input.prompt() != PROMPT {
+            let incomplete = self.style.paint(Color::Red, "Incomplete agent at the end of the script");
+            output.write_all(format!("{}: {}\n", first_line, incomplete).as_bytes()).await?;
+            succeeded = false;
+        }
+        if let Step::Print(store) = self.command("store").await {
+            output.write_all(format!("Final store:\n{}\n", store).as_bytes()).await?;
+        }
+        output.flush().await?;
+        Ok(succeeded)
+    }
+
+    /// @summary - Parse the agents of a script without running them, e.g. to validate it before running it on a node
+    ///
+    /// @returns - true if every agent parsed, the meta-commands being skipped
+    pub async fn check_script<W: AsyncWrite + Unpin>(&self, script: &str, mut output: W) -> std::io::Result<bool> {
+        let mut input = Input::default();
+        let mut succeeded = true;
+        let mut first_line = 1;
+        for (number, line) in script.lines().enumerate() {
+            if input.prompt() == PROMPT {
+                first_line = number + 1;
+            }
+            let Some(statement) = input.push(line) else { continue };
+            if statement.is_empty() || statement.starts_with(':') {
+                continue;
+            }
+            let parsed = match definition(&statement) {
+                Some(_) => parse_definition(&statement, self.dialect).map(|_| ()),
+                None => parse_with(&statement, self.dialect).map(|_| ()),
+            };
+            let outcome = match parsed {
+                Ok(_) => self.style.paint(Color::Green, "OK"),
+                Err(e) => {
+                    succeeded = false;
+                    self.style.paint(Color::Red, &format!("Parse error: {}", e.diagnostic()))
+                },
+            };
+            output.write_all(format!("{}: {} => {}\n", first_line, statement, outcome).as_bytes()).await?;
+        }
+        if input.prompt() != PROMPT {
+            let incomplete = self.style.paint(Color::Red, "Incomplete agent at the end of the script");
+            output.write_all(format!("{}: {}\n", first_line, incomplete).as_bytes()).await?;
+            succeeded = false;
+        }
+        output.flush().await?;
+        Ok(succeeded)
+    }
+
+    /// @summary - Evaluate the lines of the input until it is closed or `:quit` is typed, printing a prompt before each of them
+    ///
+    /// @note - The empty lines are skipped, an incomplete agent is read on the next lines
+    pub async fn run<R, W>(&mut self, input: R, mut output: W) -> std::io::Result<()>
+    where R: AsyncBufRead + Unpin, W: AsyncWrite + Unpin {
+        let mut lines = input.lines();
+        let mut input = Input::default();
+        loop {
+            output.write_all(self.prompt(&input).await.as_bytes()).await?;
+            output.flush().await?;
+            let Some(line) = lines.next_line().await? else { break };
+            let Some(line) = input.push(&line) else { continue };
+            if line.is_empty() && !self.is_stepping() {
+                continue;
+            }
+            match self.step(&line).await {
+                Step::Print(outcome) => output.write_all(format!("{}\n", outcome).as_bytes()).await?,
+                Step::Quit => return output.flush().await,
+            }
+            for notification in self.notified().await {
+                output.write_all(format!("{}\n", self.style.paint(Color::Cyan, &notification)).as_bytes()).await?;
+            }
+        }
+        output.write_all(b"\n").await?;
+        output.flush().await
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bacht::language::parser::parse;
+    use bacht::blackboard::create_blackboard;
+    use bacht::communication::socket_listener::{SocketListener, SocketListenerTrait};
+
+    #[tokio::test]
+    async fn repl_should_run_each_line_on_the_same_blackboard() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run("tell(a);tell(b)\n\nget(a)\nget(a)\ntell(a)??\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes.len(), 4, "The empty line should be skipped: {}", output);
+        assert_eq!(outcomes[..3], ["Success", "Success", "Failure: the agent is stuck"]);
+        assert!(outcomes[3].starts_with("Parse error"));
+        assert!(blackboard.ask("b".into()).await.unwrap());
+        assert!(blackboard.nask("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn repl_should_read_incomplete_agents_on_the_next_lines() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run("tell(a);\n(get(a)+\n\n ask(b)\n);tell(c)\n(tell(d)))\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(CONTINUATION_PROMPT).count(), 4, "The empty line should be skipped too: {}", output);
+        let outcomes: Vec<&str> = output.split(['>']).map(str::trim).filter(|outcome| !outcome.is_empty() && *outcome != "bacht" && *outcome != "...").collect();
+        assert_eq!(outcomes.len(), 2, "{}", output);
+        assert!(outcomes[0].starts_with("Success"), "{}", output);
+        assert!(outcomes[1].starts_with("Parse error"), "An extra parenthesis is not an incomplete agent");
+        assert!(blackboard.ask("c".into()).await.unwrap());
+    }
+
+    #[test]
+    fn input_should_wait_for_balanced_parentheses_and_trailing_operators() {
+        let mut input = Input::default();
+        assert_eq!(input.push(":store"), Some(":store".into()));
+        assert_eq!(input.push("tell(a)||"), None);
+        assert_eq!(input.prompt(), CONTINUATION_PROMPT);
+        assert_eq!(input.push(":store"), Some("tell(a)||:store".into()), "A colon within an agent is not a command");
+        assert_eq!(input.prompt(), PROMPT);
+        assert_eq!(input.push("(tell(a"), None);
+        assert_eq!(input.push(")"), None);
+        assert_eq!(input.push(")"), Some("(tell(a))".into()));
+        assert_eq!(input.push("// a comment alone"), None);
+        assert_eq!(input.prompt(), PROMPT);
+        assert_eq!(input.push("tell(a); // (first"), None);
+        assert_eq!(input.push("/* then"), None);
+        assert_eq!(input.push("*/ tell(b)"), Some("tell(a); // (first\n/* then\n*/ tell(b)".into()));
+        assert_eq!(input.push("def p(x) ="), None);
+        assert_eq!(input.push("ask(x) ->"), None);
+        assert_eq!(input.push("tell(t(x,"), None);
+        assert_eq!(input.push("y))"), Some("def p(x) =ask(x) ->tell(t(x,y))".into()));
+        assert_eq!(input.push("tell(a))"), Some("tell(a))".into()), "An invalid agent is not completed by more lines");
+    }
+
+    #[tokio::test]
+    async fn repl_should_run_a_script_and_print_the_final_store() {
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        assert!(repl.run_script("tell(a);\ntell(b)\n\n:seed 1\nget(b)\n", &mut output).await.unwrap());
+        assert_eq!(String::from_utf8(output).unwrap(), "1: tell(a);tell(b) => Success\n4: :seed 1 => Seeded with 1\n5: get(b) => Success\nFinal store:\na  1\n");
+
+        let mut output = Vec::new();
+        assert!(!repl.run_script("get(b)\ntell(c)\ntell(d)||\n", &mut output).await.unwrap());
+        assert_eq!(String::from_utf8(output).unwrap(), "1: get(b) => Failure: the agent is stuck\n2: tell(c) => Success\n\
+            3: Incomplete agent at the end of the script\nFinal store:\na  1\nc  1\n");
+    }
+
+    #[tokio::test]
+    async fn repl_should_run_the_agents_on_the_remote_blackboard_it_is_connected_to() {
+        let remote = create_blackboard();
+        let port = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let listener = SocketListener::new(remote.clone(), Some(port)).with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+
+        let local = create_blackboard();
+        let mut repl = Repl::new_with(local.clone()).with_admin_key("secret");
+        let mut output = Vec::new();
+        let script = format!(":connect 127.0.0.1:{}\ntell(a);tell(b)\n:store\n:connect\ntell(c)\n", port);
+        assert!(repl.run_script(&script, &mut output).await.unwrap());
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(&format!("Running the agents on 127.0.0.1:{}", port)), "{}", output);
+        assert!(output.contains("3: :store => a  1\nb  1\n"), "The store of the remote should be listed: {}", output);
+        assert!(remote.ask("b".into()).await.unwrap());
+        assert!(remote.nask("c".into()).await.unwrap());
+        assert!(local.ask("c".into()).await.unwrap(), "The agents should run locally again once disconnected");
+        assert!(local.nask("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn repl_should_print_the_changes_of_the_tokens_watched_on_a_remote_blackboard() {
+        let remote = create_blackboard();
+        let port = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let listener = SocketListener::new(remote.clone(), Some(port)).with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+        let addr = format!("127.0.0.1:{}", port);
+
+        let mut anonymous = Repl::new_with(create_blackboard());
+        anonymous.connect(&addr).await.unwrap();
+        assert!(anonymous.watch("a").await.is_err(), "The node should only stream its changes to a client presenting a key");
+
+        let mut repl = Repl::new_with(create_blackboard()).with_admin_key("secret");
+        repl.connect(&addr).await.unwrap();
+        let mut lines = repl.take_notifications().unwrap();
+        assert_eq!(repl.watch("a").await, Ok(0));
+        remote.tell("b".into()).await.unwrap();
+        remote.tell("a".into()).await.unwrap();
+        remote.get("a".into()).await.unwrap();
+        assert_eq!((lines.recv().await.unwrap(), lines.recv().await.unwrap()), ("a = 1".to_string(), "a = 0".to_string()));
+    }
+
+    #[tokio::test]
+    async fn repl_should_perform_one_transition_per_enter_in_step_mode() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run(":step tell(a);get(b);tell(c)\n\n\ntell(b)\n\n:store\n\n\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["Stepping tell(a);get(b);tell(c), press Enter to perform each transition", "tell(a) => get(b);tell(c)",
+            "The agent is stuck: get(b);tell(c)", "Success", "get(b) => tell(c)", "a  1", "tell(c) => the agent terminated"]);
+        assert!(!repl.is_stepping());
+        assert!(blackboard.ask("c".into()).await.unwrap());
+    }
+
+    #[test]
+    fn agents_should_be_printed_with_the_parentheses_their_structure_requires() {
+        for agent in ["tell(a)", "tell(a);get(b);ask(c)", "(tell(a)+get(b))||ask(c)", "tell(a)+get(b)||nask(c);ask(d)", "tell(a);(get(b)||ask(c))"] {
+            assert_eq!(parse(agent).unwrap().to_string(), agent);
+        }
+        // The compositions by the same operator are flattened
+        assert_eq!(parse("(tell(a);get(b));ask(c)").unwrap().to_string(), "tell(a);get(b);ask(c)");
+        assert_eq!(parse("(tell(a)||get(b))||(ask(c)||nask(d))").unwrap().to_string(), "tell(a)||get(b)||ask(c)||nask(d)");
+    }
+
+    #[test]
+    fn explain_should_parenthesize_every_operator_and_draw_the_tree() {
+        assert_eq!(explain(&parse("tell(a);get(b)||ask(c)+nask(d)").unwrap()),
+            "((tell(a);get(b))||ask(c))+nask(d)\n+ choice\n├── || parallel\n│   ├── ; sequence\n│   │   ├── tell(a)\n│   │   └── get(b)\n│   └── ask(c)\n└── nask(d)");
+        assert_eq!(explain(&parse("tell(a)").unwrap()), "tell(a)\ntell(a)");
+    }
+
+    #[tokio::test]
+    async fn repl_should_apply_meta_commands_before_parsing() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run(":store\ntell(b);tell(a);tell(a)\n:store\n:seed x\n:seed 7\n:clear\n:store\n:nope\n:quit\ntell(c)\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["The store is empty", "Success", "a  2\nb  1", "Invalid seed x, expected an unsigned integer", "Seeded with 7",
+            "The store is cleared", "The store is empty", "Unknown command :nope, type :help for the commands"]);
+        assert!(blackboard.nask("c".into()).await.unwrap(), "The lines after :quit should not be run");
+    }
+
+    #[tokio::test]
+    async fn repl_should_print_the_primitives_attempted_while_tracing() {
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        repl.run(":trace on\ntell(a);get(b)\n:trace off\ntell(c)\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes[0], "Tracing on");
+        let traced: Vec<&str> = outcomes[1].lines().collect();
+        assert_eq!(traced.len(), 3, "{}", outcomes[1]);
+        assert!(traced[0].starts_with('[') && traced[0].ends_with("ms] tell(a) executed"), "{}", traced[0]);
+        assert!(traced[1].ends_with("ms] get(b) blocked"), "{}", traced[1]);
+        assert_eq!(traced[2], "Failure: the agent is stuck");
+        assert_eq!(outcomes[2..], ["Tracing off", "Success"]);
+    }
+
+    #[tokio::test]
+    async fn repl_should_check_a_script_without_running_it() {
+        let blackboard = create_blackboard();
+        let repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        assert!(!repl.check_script("tell(a);\nget(a)\n:store\ntell(b))\nask(", &mut output).await.unwrap());
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "1: tell(a);get(a) => OK");
+        assert_eq!(lines[1], "4: tell(b)) => Parse error: unexpected `)` at 1:8, expected an operator or the end of the agent");
+        assert_eq!(lines[2..4], ["tell(b))", "       ^"], "The diagnostic should point at the extra parenthesis");
+        assert_eq!(lines[4], "5: Incomplete agent at the end of the script");
+        assert!(blackboard.nask("a".into()).await.unwrap(), "The agents should not be run");
+    }
+
+    #[tokio::test]
+    async fn repl_should_time_the_agents_while_timing() {
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        repl.run(":timing on\ntell(a);ask(a);get(b)\n:timing off\ntell(c)\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        let timed: Vec<&str> = outcomes[1].lines().collect();
+        assert_eq!(timed[0], "Failure: the agent is stuck");
+        assert!(timed[1].trim().ends_with(" ms, 2 transitions, 1 blocked attempt"), "{}", timed[1]);
+        assert_eq!(outcomes[2..], ["Timing off", "Success"]);
+    }
+
+    #[tokio::test]
+    async fn repl_should_save_and_load_the_store() {
+        let path = std::env::temp_dir().join(format!("bacht-store-{}.json", rand::random::<u64>()));
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        let script = format!("tell(a);tell(a);tell(b)\n:save {0}\n:clear\ntell(c)\n:load {0}\n:store\n:load missing.json\n", path.display());
+        repl.run(script.as_bytes(), &mut output).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes[1], format!("Saved 2 tokens to {}", path.display()));
+        assert_eq!(outcomes[4], format!("Loaded 2 tokens from {}", path.display()));
+        assert_eq!(outcomes[5], "a  2\nb  1", "The tokens told since the save should be gone");
+        assert!(outcomes[6].starts_with("Error loading the store from missing.json"), "{}", outcomes[6]);
+    }
+
+    #[tokio::test]
+    async fn repl_should_show_the_counters_of_the_blackboard_in_the_prompt() {
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        repl.run(":prompt summary\ntell(a);tell(b);\ntell(b)\n:prompt {occurrences}/{missing}>\n:prompt default\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "bacht> Prompt changed\n[0 tokens|0 queued]>   ...> Success\n[2 tokens|0 queued]> Prompt changed\n3/{missing}> Prompt changed\nbacht> \n");
+    }
+
+    #[tokio::test]
+    async fn repl_should_run_the_agents_calling_the_definitions() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        let script = ":def consumer = get(item);consumer+nask(item)\n:def producer=tell(item);tell(item)\n:defs\nproducer;consumer\n:undef producer\nproducer\n:def loop = loop\nloop\n:def Bad = tell(a)\n:defs\n";
+        repl.run(script.as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes[..6], ["Defined consumer", "Defined producer", "consumer = get(item);consumer+nask(item)\nproducer = tell(item);tell(item)",
+            "Success", "Undefined producer", "Error: unknown agent producer"]);
+        assert_eq!(outcomes[6..8], ["Defined loop", "Error: loop calls itself before any primitive"]);
+        assert!(outcomes[8].starts_with("Parse error: Invalid name Bad"), "{}", outcomes[8]);
+        assert_eq!(outcomes[9], "consumer = get(item);consumer+nask(item)\nloop = loop");
+        assert!(blackboard.nask("item".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn repl_should_run_the_definitions_with_parameters() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        let script = "def producer(x, n) = tell(t(x, n));tell(x)\n:def consumer(x) = get(x);consumer(x)+nask(x)\nproducer(a, one);consumer(a)\nproducer(a)\n";
+        assert!(!repl.run_script(script, &mut output).await.unwrap());
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[..4], ["1: def producer(x, n) = tell(t(x, n));tell(x) => Defined producer(x, n)", "2: :def consumer(x) = get(x);consumer(x)+nask(x) => Defined consumer(x)",
+            "3: producer(a, one);consumer(a) => Success", "4: producer(a) => Error: producer expects 2 arguments, 1 given"]);
+        assert!(blackboard.ask("t(a,one)".into()).await.unwrap() && blackboard.nask("a".into()).await.unwrap());
+        let mut output = Vec::new();
+        assert!(!repl.check_script("def p(x) = tell(x)\ndef p(x, x) = tell(x)\n", &mut output).await.unwrap());
+        assert!(String::from_utf8(output).unwrap().lines().nth(1).unwrap().starts_with("2: def p(x, x) = tell(x) => Parse error"));
+    }
+
+    #[tokio::test]
+    async fn repl_should_print_the_changes_of_the_tokens_watched() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run(":watch a\n:watch b\n:unwatch b\n:watch\ntell(a);tell(b);get(a);tell(a)\n:unwatch c\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["Watching a, 0 occurrences", "Watching b, 0 occurrences", "Stopped watching b", "Watching a",
+            "Success\na = 1\na = 0\na = 1", "c is not watched"]);
+    }
+
+    #[tokio::test]
+    async fn repl_should_run_again_the_agents_recalled_from_the_history() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run("!!\n:history\ntell(a)\nget(a);get(a)\n!1\n!!\n!9\n:history\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["No agent was run yet", "No agent was run yet", "Success", "Failure: the agent is stuck", "tell(a)\nSuccess",
+            "tell(a)\nSuccess", "No agent !9 in the history, see :history",
+            "1  tell(a)\n   2  get(a);get(a)\n   3  tell(a)\n   4  tell(a)"]);
+        assert!(blackboard.ask("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn repl_should_undo_the_changes_of_the_store_made_by_the_last_agents() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run(":undo\ntell(a);tell(a);tell(b)\nget(a);tell(c);ask(c)\n:undo\n:store\nget(b)\n:undo\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["Nothing to undo", "Success", "Success", "Undone with tell(a), get(c)", "a  2\nb  1", "Success",
+            "Undone with tell(b)"]);
+
+        repl.step("get(a)").await;
+        repl.step("ask(b)").await;
+        let Step::Print(undone) = repl.step(":undo").await else { panic!("Undo should not quit") };
+        assert_eq!(undone, "Undone with tell(a)");
+        blackboard.get("b".into()).await.unwrap();
+        let Step::Print(undone) = repl.step(":undo").await else { panic!("Undo should not quit") };
+        assert_eq!(undone, "Undone with get(a) x2, not reversed as got since: b x1");
+    }
+
+    #[tokio::test]
+    async fn repl_should_tell_get_and_count_the_tokens_directly() {
+        let blackboard = create_blackboard();
+        let mut repl = Repl::new_with(blackboard.clone());
+        let mut output = Vec::new();
+        repl.run(":tell a 5\n:get a 2\n:tell b\n:count a\n:get b 3\n:count c\n:tell a many\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["5 occurrences of a", "3 occurrences of a", "1 occurrence of b", "3 occurrences of a",
+            "0 occurrences of b", "0 occurrences of c", "Invalid number of occurrences many"]);
+        assert!(blackboard.ask("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn repl_should_print_the_store_or_its_changes_after_each_agent_with_autoprint() {
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        repl.run(":autoprint on\ntell(a);tell(b)\n:autoprint summary\ntell(a);get(b)\nask(a)\n:step tell(c);get(c)\n\n:autoprint off\nget(a)\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["Autoprint on", "Success\na  1\nb  1", "Autoprint summary", "Success\nChanged a +1, b -1",
+            "Success\nNo token changed", "Stepping tell(c);get(c), press Enter to perform each transition",
+            "tell(c) => get(c)\nChanged c +1", "Autoprint off", "Success"]);
+    }
+
+    #[tokio::test]
+    async fn repl_should_record_the_lines_entered_with_their_outcome() {
+        let path = std::env::temp_dir().join(format!("bacht_record_{}.tsv", std::process::id()));
+        let mut repl = Repl::new_with(create_blackboard());
+        let mut output = Vec::new();
+        let input = format!("tell(z)\n:record {}\ntell(a)\nget(a);get(a)\n:store\n:record\nask(a)\n", path.display());
+        repl.run(input.as_bytes(), &mut output).await.unwrap();
+
+        let records: Vec<Record> = std::fs::read_to_string(&path).unwrap().lines().map(|line| Record::decode(line).unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<(&str, &str)> = records.iter().map(|record| (record.line.as_str(), record.outcome.as_str())).collect();
+        assert_eq!(lines[1..], [("tell(a)", "Success"), ("get(a);get(a)", "Failure: the agent is stuck"), (":store", "z  1")]);
+        assert!(lines[0].0.starts_with(":seed ") && records.windows(2).all(|pair| pair[0].at <= pair[1].at));
+    }
+
+    #[tokio::test]
+    async fn repl_should_only_explain_the_agents_in_dry_run() {
+        let mut repl = Repl::new_with(create_blackboard()).with_dry_run();
+        let mut output = Vec::new();
+        repl.run("tell(a);tell(b)+get(c)\ntell(a\n)\n:dryrun off\ntell(a)\n".as_bytes(), &mut output).await.unwrap();
+
+        let output = String::from_utf8(output).unwrap().replace(CONTINUATION_PROMPT, PROMPT);
+        let outcomes: Vec<&str> = output.split(PROMPT).map(str::trim).filter(|outcome| !outcome.is_empty()).collect();
+        assert_eq!(outcomes, ["(tell(a);tell(b))+get(c)\n+ choice\n├── ; sequence\n│   ├── tell(a)\n│   └── tell(b)\n└── get(c)",
+            "tell(a)\ntell(a)", "Dry run off", "Success"]);
+        assert_eq!(repl.store().await.unwrap(), [("a".into(), 1)], "Only the agent run after the dry run should have told a token");
+    }
+
+    #[tokio::test]
+    async fn repl_should_run_the_linda_primitives_once_enabled() {
+        assert!(matches!(Repl::new_with(create_blackboard()).eval("out(a)").await, Err(Error::Parse(_))));
+
+        let mut repl = Repl::new_with(create_blackboard()).with_linda();
+        repl.define("drain", "inp(a);rdp(a)").unwrap();
+        assert!(repl.eval("out(a);out(b);rd(a);in(b);drain").await.unwrap());
+        assert_eq!(repl.store().await.unwrap(), []);
+        repl.disconnect();
+        assert!(repl.eval("drain").await.unwrap(), "The definitions should stay in the Linda dialect");
+    }
+}
@@ -0,0 +1,95 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use bacht::blackboard::BlackboardTrait;
+use bacht::log;
+use bacht::log::Level;
+use crate::repl::Repl;
+
+/// @summary - Serve a REPL session to each client connecting with a line-based terminal, e.g. telnet or netcat, the
+/// agents of all the sessions sharing the same blackboard
+///
+/// @param session - Creates the REPL of a session, on the shared blackboard
+///
+/// @note - The sessions run until their client disconnects or types `:quit`, their definitions, history and watches
+/// being their own
+pub async fn serve_sessions<B, F>(address: IpAddr, port: u16, session: F) -> Result<(), String>
+where B: BlackboardTrait + Sync + Send + 'static, F: Fn() -> Repl<B> {
+    let listener = TcpListener::bind(SocketAddr::new(address, port)).await
+        .map_err(|e| format!("Failed to bind interactive port: {}", e))?;
+    log!(Level::Info, "Serving the REPL sessions on {}", SocketAddr::new(address, port));
+    let sessions = Arc::new(AtomicUsize::new(0));
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                log!(Level::Error, "Error accepting session: {}", e);
+                continue;
+            }
+        };
+        let repl = session();
+        let sessions = sessions.clone();
+        tokio::spawn(async move {
+            let open = sessions.fetch_add(1, Ordering::SeqCst) + 1;
+            log!(Level::Debug, "Session of {} opened, {} open", peer, open);
+            if let Err(e) = run_session(stream, repl, open).await {
+                log!(Level::Error, "Error in the session of {}: {}", peer, e);
+            }
+            let open = sessions.fetch_sub(1, Ordering::SeqCst) - 1;
+            log!(Level::Debug, "Session of {} closed, {} open", peer, open);
+        });
+    }
+}
+
+async fn run_session<B: BlackboardTrait + Sync>(stream: TcpStream, mut repl: Repl<B>, open: usize) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let others = match open - 1 {
+        1 => "1 other session".to_string(),
+        others => format!("{} other sessions", others),
+    };
+    writer.write_all(format!("Connected to the shared blackboard with {}, type :help for the commands\n", others).as_bytes()).await?;
+    repl.run(BufReader::new(reader), writer).await
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+    use tokio::time::sleep;
+    use bacht::blackboard::create_blackboard;
+    use crate::repl::PROMPT;
+
+    async fn outcome(lines: &mut tokio::io::Lines<BufReader<TcpStream>>) -> String {
+        lines.next_line().await.unwrap().unwrap().trim_start_matches(PROMPT).to_string()
+    }
+
+    #[tokio::test]
+    async fn sessions_should_share_the_blackboard() {
+        let blackboard = create_blackboard();
+        tokio::spawn(serve_sessions(IpAddr::V4(Ipv4Addr::LOCALHOST), 2176, move || Repl::new_with(blackboard.clone())));
+        sleep(Duration::from_millis(100)).await;
+
+        let mut first = BufReader::new(TcpStream::connect("127.0.0.1:2176").await.unwrap()).lines();
+        assert_eq!(outcome(&mut first).await, "Connected to the shared blackboard with 0 other sessions, type :help for the commands");
+        let mut second = BufReader::new(TcpStream::connect("127.0.0.1:2176").await.unwrap()).lines();
+        assert_eq!(outcome(&mut second).await, "Connected to the shared blackboard with 1 other session, type :help for the commands");
+
+        first.get_mut().get_mut().write_all(b"tell(a)\r\n").await.unwrap();
+        assert_eq!(outcome(&mut first).await, "Success");
+        second.get_mut().get_mut().write_all(b"get(a)\n").await.unwrap();
+        assert_eq!(outcome(&mut second).await, "Success");
+        second.get_mut().get_mut().write_all(b":quit\n").await.unwrap();
+        let mut rest = String::new();
+        second.into_inner().read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, PROMPT, "The session should end after :quit");
+        first.get_mut().get_mut().write_all(b"ask(a)\n").await.unwrap();
+        assert_eq!(outcome(&mut first).await, "Failure: the agent is stuck");
+    }
+}
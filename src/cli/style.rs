@@ -0,0 +1,76 @@
+/// The colors of the terminal output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    /// An agent that terminated
+    Green,
+    /// An agent that got stuck
+    Yellow,
+    /// An error, e.g. an agent that does not parse
+    Red,
+    /// The tokens of the store
+    Cyan,
+}
+
+impl Color {
+    fn code(&self) -> &'static str {
+        match self {
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Red => "31",
+            Color::Cyan => "36",
+        }
+    }
+}
+
+/// @summary - The Style colors the output of the REPL with ANSI escape sequences, unless it is plain
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Style {
+    colored: bool,
+}
+
+impl Style {
+
+    pub fn new(colored: bool) -> Self {
+        Self { colored }
+    }
+
+    /// @returns - The colors to use on the standard output: none when it is not a terminal, or when NO_COLOR is set
+    pub fn detect() -> Self {
+        use std::io::IsTerminal;
+        Self::new(std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none())
+    }
+
+    pub fn paint(&self, color: Color, text: &str) -> String {
+        if self.colored {
+            format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// @summary - Lay out the tokens of a store in two aligned columns, the token and its number of occurrences
+    pub fn store(&self, tokens: &[(Box<str>, u32)]) -> String {
+        let width = tokens.iter().map(|(token, _)| token.chars().count()).max().unwrap_or(0);
+        let count_width = tokens.iter().map(|(_, count)| count.to_string().len()).max().unwrap_or(0);
+        tokens.iter()
+            .map(|(token, count)| format!("{}  {:>count_width$}", self.paint(Color::Cyan, &format!("{:<width$}", token)), count))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_should_align_the_store_in_columns() {
+        let tokens = vec![("a".into(), 12), ("token".into(), 3)];
+        assert_eq!(Style::new(false).store(&tokens), "a      12\ntoken   3");
+        assert_eq!(Style::new(true).store(&tokens[..1]), "\x1b[36ma\x1b[0m  12");
+        assert_eq!(Style::new(true).paint(Color::Red, "Error"), "\x1b[31mError\x1b[0m");
+    }
+}
@@ -0,0 +1,42 @@
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use bacht::communication::socket_client::SocketClient;
+use bacht::model::change::StoreChange;
+
+/// @summary - Send a line each time the number of occurrences of a token changes in the in-process store
+///
+/// @note - Ends once the store or the receiver of the lines is dropped
+pub async fn watch_local(mut changes: broadcast::Receiver<StoreChange>, token: String, lines: UnboundedSender<String>) {
+    loop {
+        match changes.recv().await {
+            Ok(change) if *change.token == *token => {
+                if lines.send(format!("{} = {}", token, change.count)).is_err() {
+                    return;
+                }
+            },
+            Ok(_) => {},
+            Err(RecvError::Lagged(missed)) => {
+                if lines.send(format!("{}: {} changes of the store missed", token, missed)).is_err() {
+                    return;
+                }
+            },
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// @summary - Send a line each time the number of occurrences of a token changes in the store of a remote node
+///
+/// @param client - The client subscribed, kept for the subscription to last
+/// @param changes - The changes of the token, subscribed with its own pattern
+///
+/// @note - The changes end with the connection, or once the subscription missed some: the watch then stops
+pub async fn watch_remote(client: SocketClient, mut changes: UnboundedReceiver<(Box<str>, u32)>, token: String, lines: UnboundedSender<String>) {
+    while let Some((changed, count)) = changes.recv().await {
+        // The pattern matches other tokens too if the token holds a variable
+        if *changed == *token && lines.send(format!("{} = {}", token, count)).is_err() {
+            return;
+        }
+    }
+    let _ = lines.send(format!("{}: the watch of {} stopped", token, client.addr()));
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+/// @summary - The misbehaviours of a worker, as the probability of each one to hit a task it dequeues, e.g. to test
+/// that the clients of a blackboard handle the failed events and retry them.
+///
+/// The worker is killed, losing the task without answering it, and restarted after `restart_after`; else the task is
+/// failed with StoreError::Injected, the store being unchanged; else it is delayed up to `max_delay` before being
+/// applied. The same seed hits the same tasks, in the order the worker dequeues them.
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use bacht::blackboard::{Blackboard, BlackboardTrait};
+/// use bacht::blackboard::chaos::Chaos;
+/// use bacht::blackboard::store::{Store, StoreTrait};
+/// use bacht::blackboard::task_queue::TaskQueue;
+/// use bacht::blackboard::worker::{DroppedResultPolicy, Worker};
+/// use bacht::error::StoreError;
+///
+/// let chaos = Chaos { error: 1.0, seed: 42, ..Chaos::default() };
+/// let blackboard: Blackboard<TaskQueue, Worker, Store> = Blackboard::new_with_chaos(Store::new(), DroppedResultPolicy::default(), chaos);
+/// assert_eq!(blackboard.tell("token".into()).await, Err(StoreError::Injected));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Chaos {
+    pub kill: f64,
+    pub restart_after: Duration,
+    pub error: f64,
+    pub delay: f64,
+    pub max_delay: Duration,
+    pub seed: u64,
+}
+
+// The misbehaviour hitting a task
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Mischief {
+    Kill(Duration),
+    Fail,
+    Delay(Duration),
+}
+
+// The misbehaviours of a worker, and the seeded generator deciding which tasks they hit.
+// A SplitMix64 rather than rand, which the blackboard alone does not depend on
+pub(crate) struct Schedule {
+    chaos: Chaos,
+    state: u64,
+}
+
+impl Schedule {
+
+    pub(crate) fn new(chaos: Chaos) -> Self {
+        Self { chaos, state: chaos.seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A number uniformly drawn in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn hits(&mut self, probability: f64) -> bool {
+        self.uniform() < probability.clamp(0.0, 1.0)
+    }
+
+    pub(crate) fn roll(&mut self) -> Option<Mischief> {
+        let chaos = self.chaos;
+        if self.hits(chaos.kill) {
+            Some(Mischief::Kill(chaos.restart_after))
+        } else if self.hits(chaos.error) {
+            Some(Mischief::Fail)
+        } else if self.hits(chaos.delay) {
+            Some(Mischief::Delay(chaos.max_delay.mul_f64(self.uniform())))
+        } else {
+            None
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_should_hit_the_same_tasks_with_the_same_seed() {
+        let chaos = Chaos { kill: 0.1, restart_after: Duration::from_secs(1), error: 0.3, delay: 0.5, max_delay: Duration::from_secs(1), seed: 7 };
+        let rolls = |chaos| { let mut schedule = Schedule::new(chaos); (0..100).map(|_| schedule.roll()).collect::<Vec<_>>() };
+        assert_eq!(rolls(chaos), rolls(chaos));
+        assert_ne!(rolls(chaos), rolls(Chaos { seed: 8, ..chaos }));
+        assert!(rolls(chaos).iter().any(|mischief| matches!(mischief, Some(Mischief::Kill(_)))));
+        assert!(rolls(chaos).iter().all(|mischief| !matches!(mischief, Some(Mischief::Delay(delay)) if *delay > Duration::from_secs(1))));
+
+        assert!(rolls(Chaos::default()).iter().all(Option::is_none), "No misbehaviour by default");
+        assert!(rolls(Chaos { error: 1.0, ..Chaos::default() }).iter().all(|mischief| *mischief == Some(Mischief::Fail)));
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use crate::model::event::Event;
+
+// The dead letters kept, the oldest ones being dropped beyond
+const DEAD_LETTERS_CAPACITY: usize = 1024;
+
+/// An event applied by the worker while nobody waited for its result anymore, e.g. the request of a timed out client
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadLetter {
+    pub event: Event,
+    /// The result the sender would have received
+    pub result: bool,
+}
+
+/// @summary - The DeadLetters keep the events whose result was not received, so that an operator can inspect them.
+/// Its clones share the same letters.
+#[derive(Debug, Clone, Default)]
+pub struct DeadLetters {
+    letters: Arc<Mutex<VecDeque<DeadLetter>>>,
+}
+
+impl DeadLetters {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// @summary - Keep a dead letter, dropping the oldest one if the capacity is reached
+    pub fn push(&self, letter: DeadLetter) {
+        let mut letters = self.letters.lock().unwrap();
+        if letters.len() == DEAD_LETTERS_CAPACITY {
+            letters.pop_front();
+        }
+        letters.push_back(letter);
+    }
+
+    /// @returns - The dead letters kept, the oldest first, removing them
+    pub fn drain(&self) -> Vec<DeadLetter> {
+        self.letters.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::action::Action;
+
+    #[test]
+    fn dead_letters_should_keep_the_latest_letters_up_to_their_capacity() {
+        let dead_letters = DeadLetters::new();
+        let shared = dead_letters.clone();
+        for i in 0..DEAD_LETTERS_CAPACITY + 2 {
+            dead_letters.push(DeadLetter { event: Event::new(Action::Tell(format!("{}", i).into())), result: true });
+        }
+        assert_eq!(shared.len(), DEAD_LETTERS_CAPACITY);
+        let letters = shared.drain();
+        assert_eq!(letters[0].event.action, Action::Tell("2".into()));
+        assert!(dead_letters.is_empty());
+    }
+}
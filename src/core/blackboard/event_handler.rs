@@ -1,6 +1,9 @@
 use mockall::automock;
 use crate::model::{event::Event, action::Action::{Tell, Ask, Get, Nask}};
+use crate::model::task::Task;
 use crate::blackboard::store::StoreTrait;
+use crate::log;
+use crate::log::Level;
 
 #[automock]
 pub trait EventHandlerTrait {
@@ -14,6 +17,13 @@ pub trait EventHandlerTrait {
     /// 
     /// **@returns** - return the response to the action
     fn handle_event<S: StoreTrait + 'static>(&self, store: &S, e: &Event) -> bool;
+
+    /// **@summary** - It handles the events of the tasks in order, applying them on the store at once
+    ///
+    /// **@param** tasks: &[Task] - The tasks dequeued together by the worker
+    ///
+    /// **@returns** - return the response to the action of each task
+    fn handle_events<S: StoreTrait + 'static>(&self, store: &S, tasks: &[Task]) -> Vec<bool>;
 }
 
 pub struct EventHandler;
@@ -24,27 +34,36 @@ impl EventHandlerTrait for EventHandler {
     }
 
     fn handle_event<S: StoreTrait>(&self, store: &S, e: &Event) -> bool {
-        match e {
+        let result = match e {
             Event {action: Tell(token), .. } => {
-                store.tell(token.clone())
+                store.tell(*token)
             },
             Event {action: Ask(token), .. } => {
-                store.ask(token)
+                store.ask(*token)
             },
             Event {action: Nask(token), .. } => {
-                store.nask(token)
+                store.nask(*token)
             },
             Event {action: Get(token), .. } => {
-                store.get(token.clone())
+                store.get(*token)
             }
+        };
+        log!(Level::Trace, e.span() => "Handled {:?}: {}", e.action, if result { "executed" } else { "refused" });
+        result
+    }
+
+    fn handle_events<S: StoreTrait>(&self, store: &S, tasks: &[Task]) -> Vec<bool> {
+        let results = store.apply_batch(tasks.iter().map(|task| task.event.action.clone()).collect());
+        for (Task { event: e, .. }, result) in tasks.iter().zip(&results) {
+            log!(Level::Trace, e.span() => "Handled {:?}: {}", e.action, if *result { "executed" } else { "refused" });
         }
+        results
     }
 }
 
 /// ===============
 /// |    TESTS    |
 /// ===============
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,4 +104,12 @@ mod tests {
         let event = Event::new(Nask("token".into()));
         assert!(EventHandler::new().handle_event(&mock_store, &event));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn event_handler_should_handle_events_at_once() {
+        let mut mock_store = MockStoreTrait::default();
+        mock_store.expect_apply_batch().times(1).returning(|actions| actions.iter().map(|action| matches!(action, Tell(_))).collect());
+        let tasks = [Task::new(Event::new(Tell("token".into()))).0, Task::new(Event::new(Ask("token".into()))).0];
+        assert_eq!(EventHandler::new().handle_events(&mock_store, &tasks), [true, false]);
+    }
+}
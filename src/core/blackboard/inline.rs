@@ -0,0 +1,152 @@
+use std::time::Instant;
+use tokio::sync::broadcast;
+use crate::blackboard::{BlackboardTrait, admin_reply};
+use crate::blackboard::event_handler::{EventHandler, EventHandlerTrait};
+use crate::blackboard::store::{Store, StoreTrait};
+use crate::blackboard::task_queue::{TaskQueue, TaskQueueTrait};
+use crate::blackboard::worker::{DroppedResultPolicy, process};
+use crate::error::{QueueError, StoreError};
+use crate::metrics;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::change::StoreChange;
+use crate::model::event::Event;
+use crate::model::health::Health;
+use crate::model::token::TokenId;
+
+/// @summary - The InlineBlackboard is a blackboard without a worker task: the task sending an event drives the queue
+/// itself, applying the events pending up to its own before answering. Nothing is spawned, so that it runs on a
+/// current-thread runtime, e.g. embedded in a host that cannot afford the threads of a multi-thread runtime, or that
+/// must not pay the wake up of a worker on each primitive:
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use bacht::BlackboardTrait;
+/// use bacht::blackboard::inline::InlineBlackboard;
+///
+/// let blackboard: InlineBlackboard = InlineBlackboard::new();
+/// assert!(blackboard.tell("token".into()).await.unwrap());
+/// assert!(blackboard.get("token".into()).await.unwrap());
+/// # }
+/// ```
+///
+/// @note - The events are applied in the order they were queued, whichever task drives the queue
+pub struct InlineBlackboard<S: StoreTrait = Store> {
+    task_queue: TaskQueue,
+    store: S,
+    policy: DroppedResultPolicy,
+}
+
+impl<S: StoreTrait + Sync + Send + 'static> InlineBlackboard<S> {
+
+    /// @summary - Instance a blackboard on an existing store, e.g. a DynStore chosen from the configuration
+    pub fn new_with_store(store: S) -> Self {
+        Self::new_with_policy(store, DroppedResultPolicy::default())
+    }
+
+    /// @summary - Instance a blackboard on an existing store, applying the policy to the results nobody waits for
+    /// anymore
+    pub fn new_with_policy(store: S, policy: DroppedResultPolicy) -> Self {
+        Self { task_queue: TaskQueue::new(), store, policy }
+    }
+
+    /// @summary - Apply the events pending in the queue, as the worker of a Blackboard would
+    ///
+    /// @returns - The number of events applied
+    pub fn run_pending(&self) -> usize {
+        let handler = EventHandler::new();
+        let mut applied = 0;
+        while let Some(task) = self.task_queue.get_task() {
+            process(&self.store, &handler, &self.policy, task);
+            applied += 1;
+        }
+        applied
+    }
+}
+
+impl<S: StoreTrait + Sync + Send + 'static> BlackboardTrait for InlineBlackboard<S> {
+
+    fn new() -> Self {
+        Self::new_with_store(S::new())
+    }
+
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        let (action, queued) = (event.action.clone(), Instant::now());
+        let rx = self.task_queue.add_event_to_queue(event);
+        self.run_pending();
+        let result_channel = rx.await;
+        metrics::observe_latency(&action, queued.elapsed());
+        result_channel.unwrap_or(Err(StoreError::Queue(QueueError::Channel)))
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        admin_reply(&self.store, &self.task_queue, command)
+    }
+
+    /// @note - The worker is always alive, being the tasks sending the events
+    fn health(&self) -> Health {
+        Health {
+            worker_alive: true,
+            queue_depth: self.task_queue.depth() as u64,
+            store_size: self.store.size() as u64,
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.store.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            task_queue: self.task_queue.clone(),
+            store: self.store.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_blackboard_should_answer_on_a_current_thread_runtime_without_spawning() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let blackboard: InlineBlackboard = InlineBlackboard::new();
+        let shared = blackboard.clone();
+        runtime.block_on(async {
+            assert!(blackboard.tell("token".into()).await.unwrap());
+            assert!(shared.ask("token".into()).await.unwrap());
+            assert!(!blackboard.nask("token".into()).await.unwrap());
+            assert!(shared.get("token".into()).await.unwrap());
+            assert!(!blackboard.get("token".into()).await.unwrap());
+        });
+        assert_eq!(runtime.metrics().num_alive_tasks(), 0, "Nothing should be spawned");
+        assert_eq!(blackboard.health(), Health { worker_alive: true, queue_depth: 0, store_size: 0 });
+
+        // The events queued by another task are applied by the next one driving the queue
+        let pending = blackboard.task_queue.add_event_to_queue(Event::new(Action::Tell("queued".into())));
+        assert_eq!(blackboard.run_pending(), 1);
+        assert_eq!(pending.blocking_recv().unwrap(), Ok(true));
+    }
+}
@@ -0,0 +1,129 @@
+use std::future::Future;
+use tokio::sync::broadcast::error::RecvError;
+use crate::blackboard::BlackboardTrait;
+use crate::error::{QueueError, StoreError};
+use crate::model::token::TokenId;
+
+/// @summary - The primitives of Linda on any blackboard, easing the migration of the course material and the examples
+/// written for Linda: `out` is `tell`, `inp` and `rdp` are the non-blocking `get` and `ask`, while `in` and `rd`
+/// wait for the token to be told.
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use bacht::BlackboardTrait;
+/// use bacht::blackboard::create_blackboard;
+/// use bacht::blackboard::linda::Linda;
+///
+/// let blackboard = create_blackboard();
+/// assert!(!blackboard.inp("token".into()).await.unwrap());
+/// blackboard.out("token".into()).await.unwrap();
+/// blackboard.r#in("token".into()).await.unwrap();
+/// # }
+/// ```
+///
+/// @note - The agents written with these primitives are parsed with `parser::Dialect::Linda`
+pub trait Linda: BlackboardTrait {
+
+    /// @summary - Put a token on the blackboard, as tell
+    fn out(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
+
+    /// @summary - Wait until a token is on the blackboard, without taking it
+    ///
+    /// @returns - A promise resolved once the token was seen, or an error if the blackboard stopped
+    fn rd(&self, coord_data: TokenId) -> impl Future<Output = Result<(), StoreError>> + Send;
+
+    /// @summary - Wait until a token is on the blackboard, then take it
+    ///
+    /// @returns - A promise resolved once the token was taken, or an error if the blackboard stopped
+    fn r#in(&self, coord_data: TokenId) -> impl Future<Output = Result<(), StoreError>> + Send;
+
+    /// @summary - Take a token if it is on the blackboard, as get
+    ///
+    /// @returns - A promise of false if the token was absent, without waiting for it
+    fn inp(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
+
+    /// @summary - Check whether a token is on the blackboard, as ask
+    ///
+    /// @returns - A promise of false if the token was absent, without waiting for it
+    fn rdp(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
+}
+
+impl<B: BlackboardTrait + Sync> Linda for B {
+
+    async fn out(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.tell(coord_data).await
+    }
+
+    async fn rd(&self, coord_data: TokenId) -> Result<(), StoreError> {
+        wait_for(self, coord_data, false).await
+    }
+
+    async fn r#in(&self, coord_data: TokenId) -> Result<(), StoreError> {
+        wait_for(self, coord_data, true).await
+    }
+
+    async fn inp(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.get(coord_data).await
+    }
+
+    async fn rdp(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.ask(coord_data).await
+    }
+}
+
+// Retry the ask or the get of a token each time it is told, the subscription preceding the attempt so that no tell
+// is missed in between
+async fn wait_for<B: BlackboardTrait + Sync>(blackboard: &B, coord_data: TokenId, take: bool) -> Result<(), StoreError> {
+    let mut changes = blackboard.subscribe();
+    loop {
+        let found = match take {
+            true => blackboard.get(coord_data).await?,
+            false => blackboard.ask(coord_data).await?,
+        };
+        if found {
+            return Ok(());
+        }
+        loop {
+            match changes.recv().await {
+                Ok(change) if change.token == coord_data && change.count > 0 => break,
+                Ok(_) => continue,
+                // The changes lost may have told the token
+                Err(RecvError::Lagged(_)) => break,
+                Err(RecvError::Closed) => return Err(StoreError::Queue(QueueError::Channel)),
+            }
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::create_blackboard;
+
+    #[tokio::test]
+    async fn linda_should_wait_in_in_and_rd_until_the_token_is_told() {
+        let blackboard = create_blackboard();
+        assert!(!blackboard.rdp("token".into()).await.unwrap());
+        let reader = tokio::spawn({
+            let blackboard = blackboard.clone();
+            async move { blackboard.rd("token".into()).await }
+        });
+        let taker = tokio::spawn({
+            let blackboard = blackboard.clone();
+            async move { blackboard.r#in("token".into()).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!reader.is_finished() && !taker.is_finished());
+
+        assert!(blackboard.out("token".into()).await.unwrap());
+        taker.await.unwrap().unwrap();
+        assert!(!blackboard.inp("token".into()).await.unwrap(), "in should have taken the token");
+        assert!(blackboard.out("token".into()).await.unwrap());
+        reader.await.unwrap().unwrap();
+        assert!(blackboard.inp("token".into()).await.unwrap());
+    }
+}
@@ -1,18 +1,36 @@
+pub mod chaos;
+pub mod dead_letters;
 pub mod event_handler;
+pub mod inline;
+pub mod linda;
 pub mod task_queue;
 pub mod store;
+#[cfg(feature = "sled")]
+pub mod sled_store;
 pub mod worker;
+pub mod sync;
 
 use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use mockall::automock;
+use chaos::Chaos;
 use task_queue::{TaskQueue, TaskQueueTrait};
-use worker::{Worker, WorkerTrait};
+use worker::{DroppedResultPolicy, Worker, WorkerOptions, WorkerTrait};
 use store::{Store, StoreTrait};
 use super::model::event::Event;
 use event_handler::{EventHandler, EventHandlerTrait};
 use super::model::action::Action;
-use super::model::task::TaskError;
+use super::model::admin::{AdminCommand, AdminReply};
+use super::model::change::StoreChange;
+use super::model::health::Health;
+use tokio::sync::broadcast;
+use super::error::{QueueError, StoreError, TableFull};
+use super::metrics;
+use super::model::token::TokenId;
+
+// How often drain checks the depth of the queue
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 #[automock]
 pub trait BlackboardTrait {
@@ -27,8 +45,8 @@ pub trait BlackboardTrait {
     ///
     /// @returns - A promise of the result of the event
     /// 
-    /// @note - The synchronous version of this function is send_event_sync
-    fn send_event(&self, event: Event) -> impl Future<Output = Result<bool, TaskError>>;
+    /// @note - The synchronous versions of the operations are the ones of sync::SyncBlackboard
+    fn send_event(&self, event: Event) -> impl Future<Output = Result<bool, StoreError>> + Send;
     
     /// @summary - Allow to interact directly with the blackboard without sending an event
     /// 
@@ -36,29 +54,53 @@ pub trait BlackboardTrait {
     /// 
     /// @returns - A promise of the result of the operation
     /// 
-    /// @note - The synchronous version of this function is tell_sync
-    fn tell(&self, coord_data: Box<str>) -> impl Future<Output = Result<bool, TaskError>>;
+    /// @note - The synchronous version of this function is SyncBlackboard::tell
+    fn tell(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
     
     /// @summary - Allow to interact directly with the blackboard without sending an event
     /// 
     /// @param coord_data - The coordinate data to check the blackboard
     /// 
     /// @returns - A promise of the result of the operation
-    fn ask(&self, coord_data: Box<str>) -> impl Future<Output = Result<bool, TaskError>>;
+    fn ask(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
     
     /// @summary - Allow to interact directly with the blackboard without sending an event
     /// 
     /// @param coord_data - The coordinate data to get from the blackboard
     /// 
     /// @returns - A promise of the result of the operation
-    fn get(&self, coord_data: Box<str>) -> impl Future<Output = Result<bool, TaskError>>;
+    fn get(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
     
     /// @summary - Allow to interact directly with the blackboard without sending an event
     /// 
     /// @param coord_data - The coordinate data to check the blackboard
     /// 
     /// @returns - A promise of the result of the operation
-    fn nask(&self, coord_data: Box<str>) -> impl Future<Output = Result<bool, TaskError>>;
+    fn nask(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, StoreError>> + Send;
+
+    /// @summary - Manage the blackboard, on behalf of an administrator
+    ///
+    /// @param command - The command to apply
+    ///
+    /// @returns - A promise of the answer to the command
+    ///
+    /// @note - The layers of a node (replication, federation...) forward the commands to the local blackboard,
+    /// except the ones they can answer better (e.g. the federation knows the peers)
+    fn admin(&self, command: AdminCommand) -> impl Future<Output = Result<AdminReply, StoreError>> + Send;
+
+    /// @summary - Check the health of the blackboard, without going through the task queue
+    ///
+    /// @returns - The liveness of the worker, the depth of the queue and the size of the store
+    ///
+    /// @note - The layers of a node forward the check to the local blackboard
+    fn health(&self) -> Health;
+
+    /// @summary - Subscribe to the changes of the store, whoever applies them (agents, clients or peers)
+    ///
+    /// @returns - The changes applied from now on, a subscriber lagging behind loses the oldest ones
+    ///
+    /// @note - The layers of a node forward the subscription to the local blackboard
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange>;
     
     /// @summary - Allow to clone the blackboard
     /// 
@@ -68,7 +110,6 @@ pub trait BlackboardTrait {
 
 /// The blackboard allow interaction with the store
 /// It can be cloned in order to share the access to the same share space
-
 pub struct Blackboard<Q, W, S> 
 where 
     Q: TaskQueueTrait,
@@ -83,51 +124,61 @@ where
 impl<Q, W, S> BlackboardTrait for Blackboard<Q, W, S>
 where
     Q: TaskQueueTrait + Sync + Send + 'static,
-    W: WorkerTrait,
+    W: WorkerTrait + Sync + Send,
     S: StoreTrait + Sync + Send + 'static,
 {
     fn new() -> Self {
-
-        let store = S::new();
-        let task_queue = Q::new();
-        let handler = EventHandler::new();
-
-        Blackboard {
-            task_queue: task_queue.clone(),
-            worker: Arc::new(W::new(store.clone(), task_queue.clone(), handler)),
-            store: store.clone(),
-        }
+        Self::new_with_store(S::new())
     }
 
 
-    async fn send_event(&self, event: Event) -> Result<bool, TaskError> {
+    #[allow(clippy::unnecessary_lazy_evaluations)]
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        let (action, queued) = (event.action.clone(), Instant::now());
         let rx = self.task_queue.add_event_to_queue(event);
         let result_channel = rx.await;
+        metrics::observe_latency(&action, queued.elapsed());
         result_channel.unwrap_or_else(|_| {
-            Err(TaskError::ChannelError)
+            Err(StoreError::Queue(QueueError::Channel))
         })
     }
     
-    async fn tell(&self, coord_data: Box<str>) -> Result<bool, TaskError> {
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
         let event = Event::new(Action::Tell(coord_data));
         self.send_event(event).await
     }
     
-    async fn ask(&self, coord_data: Box<str>) -> Result<bool, TaskError> {
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
         let event = Event::new(Action::Ask(coord_data));
         self.send_event(event).await
     }
     
-    async fn get(&self, coord_data: Box<str>) -> Result<bool, TaskError> {
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
         let event = Event::new(Action::Get(coord_data));
         self.send_event(event).await
     }
     
-    async fn nask(&self, coord_data: Box<str>) -> Result<bool, TaskError> {
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
         let event = Event::new(Action::Nask(coord_data));
         self.send_event(event).await
     }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        admin_reply(&self.store, &self.task_queue, command)
+    }
+
+    fn health(&self) -> Health {
+        Health {
+            worker_alive: self.worker.is_alive(),
+            queue_depth: self.task_queue.depth() as u64,
+            store_size: self.store.size() as u64,
+        }
+    }
     
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.store.subscribe()
+    }
+
     fn clone(&self) -> Self {
         let store = self.store.clone();
         let task_queue = self.task_queue.clone();
@@ -141,6 +192,74 @@ where
     }
 }
 
+impl<Q, W, S> Blackboard<Q, W, S>
+where
+    Q: TaskQueueTrait + Sync + Send + 'static,
+    W: WorkerTrait + Sync + Send,
+    S: StoreTrait + Sync + Send + 'static,
+{
+    /// @summary - Instance a blackboard on an existing store, e.g. a DynStore chosen from the configuration
+    pub fn new_with_store(store: S) -> Self {
+        Self::new_with_policy(store, DroppedResultPolicy::default())
+    }
+
+    /// @summary - Instance a blackboard on an existing store, its worker applying the policy to the results nobody
+    /// waits for anymore
+    pub fn new_with_policy(store: S, policy: DroppedResultPolicy) -> Self {
+        Self::new_with_options(store, WorkerOptions { policy, ..WorkerOptions::default() })
+    }
+
+    /// @summary - Instance a blackboard whose worker misbehaves on the tasks hit by the chaos, e.g. to test that an
+    /// application retries the events failed or lost
+    ///
+    /// @note - Meant for the tests, the InlineBlackboard having no worker to misbehave
+    pub fn new_with_chaos(store: S, policy: DroppedResultPolicy, chaos: Chaos) -> Self {
+        Self::new_with_options(store, WorkerOptions { policy, chaos: Some(chaos), ..WorkerOptions::default() })
+    }
+
+    /// @summary - Instance a blackboard on an existing store, its worker tuned by the options, e.g. applying the events
+    /// queued in batches of `batch_size` under a bursty load
+    pub fn new_with_options(store: S, options: WorkerOptions) -> Self {
+        let task_queue = Q::new();
+        let handler = EventHandler::new();
+
+        Blackboard {
+            task_queue: task_queue.clone(),
+            worker: Arc::new(W::new_with_options(store.clone(), task_queue.clone(), handler, options)),
+            store,
+        }
+    }
+}
+
+/// @summary - Apply an admin command on the store of a standalone blackboard
+///
+/// @returns - The reply, or StoreError::TableFull if the tokens of an import can't be interned
+fn admin_reply(store: &impl StoreTrait, task_queue: &impl TaskQueueTrait, command: AdminCommand) -> Result<AdminReply, StoreError> {
+    Ok(match command {
+        AdminCommand::Clear => {
+            store.clear_store();
+            AdminReply::Done
+        },
+        AdminCommand::Stats => {
+            let snapshot = store.snapshot();
+            AdminReply::Stats(vec![
+                ("occurrences".to_string(), snapshot.iter().map(|(_, occurrences)| *occurrences as u64).sum()),
+                ("queued".to_string(), task_queue.depth() as u64),
+                ("tokens".to_string(), snapshot.len() as u64),
+            ])
+        },
+        AdminCommand::Snapshot => AdminReply::Snapshot(store.snapshot()),
+        // A standalone blackboard has no peer
+        AdminCommand::Peers => AdminReply::Peers(Vec::new()),
+        AdminCommand::Import(tokens) => {
+            // Every token is interned before the store changes
+            let tokens = tokens.iter().map(|(token, occurrences)| Ok((TokenId::try_intern(token)?, *occurrences))).collect::<Result<_, TableFull>>()?;
+            store.import(tokens);
+            AdminReply::Done
+        },
+    })
+}
+
 /// @summary - Instance a new blackboard with default concrete types
 /// 
 /// @returns - The blackboard instance
@@ -148,13 +267,27 @@ pub fn create_blackboard() -> Blackboard<TaskQueue, Worker, Store> {
     Blackboard::<TaskQueue, Worker, Store>::new()
 }
 
+/// @summary - Wait until the worker applied the events queued, e.g. before stopping a node
+///
+/// @param timeout - How long to wait for the queue to empty
+///
+/// @returns - true if the queue emptied, false if events were still waiting at the timeout
+pub async fn drain<B: BlackboardTrait>(blackboard: &B, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while blackboard.health().queue_depth > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+    true
+}
+
 /// ===============
 /// |    TESTS    |
 /// ===============
-
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
     use tokio::task;
     use tokio::time::timeout;
     use super::*;
@@ -169,8 +302,8 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
         
-        let (tx1, rx1) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
-        let (tx2, rx2) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx1, rx1) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
+        let (tx2, rx2) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
         
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx1});
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx2});
@@ -212,7 +345,7 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
 
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx});
 
@@ -225,13 +358,13 @@ mod tests {
         let event = Event::new(Action::Ask("token".into()));
         let pending_result = bb.send_event(event);
 
-        let send_result = tx.send(Err(TaskError::UnspecifiedError));
+        let send_result = tx.send(Err(StoreError::Queue(QueueError::Unspecified)));
         assert!(send_result.is_ok());
 
         let result = pending_result.await;
         match result {
             Ok(res) => panic!("Expected an error, but got: {}", res),
-            Err(err) => assert!(matches!(err, TaskError::UnspecifiedError), "Expected an unspecified error"),
+            Err(err) => assert!(matches!(err, StoreError::Queue(QueueError::Unspecified)), "Expected an unspecified error"),
         }
     }
 
@@ -241,7 +374,7 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
 
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx});
 
@@ -259,7 +392,7 @@ mod tests {
         let result = pending_result.await;
         match result {
             Ok(res) => panic!("Expected an error, but got: {}", res),
-            Err(err) => assert!(matches!(err, TaskError::ChannelError), "Expected a channel error"),
+            Err(err) => assert!(matches!(err, StoreError::Queue(QueueError::Channel)), "Expected a channel error"),
         }
     }
     
@@ -269,7 +402,7 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
 
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx});
 
@@ -297,7 +430,7 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
 
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx});
 
@@ -325,7 +458,7 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
 
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx});
 
@@ -353,7 +486,7 @@ mod tests {
         let mut mock_task_queue = MockTaskQueueTrait::default();
         let mock_worker = Arc::new(MockWorkerTrait::default());
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, TaskError>>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, StoreError>>();
 
         mock_task_queue.expect_add_event_to_queue().times(1).return_once(move |_| {rx});
 
@@ -420,10 +553,37 @@ mod tests {
         let cloned_bb = bb.clone();
         let task_tell = task::spawn(async move {
             let event = Event::new(Action::Tell("token".into()));
-            let result = cloned_bb.send_event(event).await;
+            let _ = cloned_bb.send_event(event).await;
         });
         
         assert!(timeout(Duration::from_secs(5), task_tell).await.is_ok());
         assert!(timeout(Duration::from_secs(5), task_ask).await.is_ok());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn blackboard_should_run_on_a_store_given_at_runtime() {
+        let store = store::DynStore::new_with(Store::new());
+        let blackboard: Blackboard<TaskQueue, Worker, store::DynStore> = Blackboard::new_with_store(store.clone());
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        assert_eq!(store.snapshot(), [("token".into(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn drain_should_wait_until_the_queue_is_empty() {
+        let bb = create_blackboard();
+        let pending: Vec<_> = (0..100).map(|_| bb.task_queue.add_event_to_queue(Event::new(Action::Tell("token".into())))).collect();
+        assert!(drain(&bb, Duration::from_secs(5)).await);
+        for result in pending {
+            assert_eq!(result.await.unwrap(), Ok(true));
+        }
+
+        // A worker that never runs leaves the queue as it is
+        let mut mock_worker = MockWorkerTrait::default();
+        mock_worker.expect_is_alive().returning(|| true);
+        let mut mock_store = MockStoreTrait::default();
+        mock_store.expect_size().returning(|| 0);
+        let stuck = Blackboard { task_queue: TaskQueue::new(), worker: Arc::new(mock_worker), store: mock_store };
+        let _pending = stuck.task_queue.add_event_to_queue(Event::new(Action::Tell("token".into())));
+        assert!(!drain(&stuck, Duration::from_millis(50)).await);
+    }
+}
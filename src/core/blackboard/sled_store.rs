@@ -0,0 +1,197 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use crate::error::ConfigError;
+use crate::model::action::Action;
+use crate::model::change::StoreChange;
+use crate::model::token::TokenId;
+use crate::blackboard::store::StoreTrait;
+use crate::log;
+use crate::log::Level;
+
+// The changes kept for a subscriber that lags behind, the older ones are dropped
+const CHANGES_CAPACITY: usize = 1024;
+
+/// **@summary** - The SledStore keeps the occurrences of the tokens in a sled database, so that they outlive the node.
+///
+/// Each token is a key of the database, its number of occurrences the big-endian u32 of its value, a token without
+/// occurrence having no key.
+///
+/// **@note** - sled flushes the database to the disk in the background, every 500 ms by default: the last changes before
+/// a crash may be lost, not the ones before a clean shutdown
+pub struct SledStore {
+    db: sled::Db,
+    // Serializes the changes, so that a batch is applied at once and the subscribers receive the changes in order
+    lock: Arc<Mutex<()>>,
+    changes: broadcast::Sender<StoreChange>,
+}
+
+impl SledStore {
+
+    /// **@summary** - Open the database of a directory, created if it does not exist, with the tokens it kept
+    pub fn open(path: &Path) -> Result<Self, ConfigError> {
+        let db = sled::open(path).map_err(|e| ConfigError::Io(format!("Failed to open the store {}: {}", path.display(), e)))?;
+        Ok(Self::new_with_db(db))
+    }
+
+    fn new_with_db(db: sled::Db) -> Self {
+        Self {
+            db,
+            lock: Arc::new(Mutex::new(())),
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+        }
+    }
+
+    fn count(&self, token: &str) -> u32 {
+        match self.db.get(token) {
+            Ok(Some(count)) => count.as_ref().try_into().map_or(0, u32::from_be_bytes),
+            Ok(None) => 0,
+            Err(e) => {
+                log!(Level::Error, "Error reading {} from the store: {}", token, e);
+                0
+            },
+        }
+    }
+
+    // Write the number of occurrences of a token, then notify it, under the lock
+    fn set(&self, token: TokenId, count: u32) -> bool {
+        let written = match count {
+            0 => self.db.remove(&*token).map(|_| ()),
+            _ => self.db.insert(&*token, &count.to_be_bytes()).map(|_| ()),
+        };
+        if let Err(e) = written {
+            log!(Level::Error, "Error writing {} to the store: {}", token, e);
+            return false;
+        }
+        // No subscriber is not an error
+        let _ = self.changes.send(StoreChange { token, count });
+        true
+    }
+
+    fn tell_locked(&self, token: TokenId) -> bool {
+        let count = self.count(&token).saturating_add(1);
+        self.set(token, count)
+    }
+
+    fn get_locked(&self, token: TokenId) -> bool {
+        match self.count(&token) {
+            0 => false,
+            count => self.set(token, count - 1),
+        }
+    }
+}
+
+impl StoreTrait for SledStore {
+
+    /// **@summary** - A store in a temporary database, removed once the store is dropped
+    fn new() -> Self {
+        Self::new_with_db(sled::Config::new().temporary(true).open().expect("A temporary database should open"))
+    }
+
+    fn tell(&self, token: TokenId) -> bool {
+        let _lock = self.lock.lock().unwrap();
+        self.tell_locked(token)
+    }
+
+    fn ask(&self, token: TokenId) -> bool {
+        self.count(&token) > 0
+    }
+
+    fn get(&self, token: TokenId) -> bool {
+        let _lock = self.lock.lock().unwrap();
+        self.get_locked(token)
+    }
+
+    fn nask(&self, token: TokenId) -> bool {
+        self.count(&token) == 0
+    }
+
+    fn apply_batch(&self, actions: Vec<Action>) -> Vec<bool> {
+        let _lock = self.lock.lock().unwrap();
+        actions.into_iter().map(|action| match action {
+            Action::Tell(token) => self.tell_locked(token),
+            Action::Ask(token) => self.count(&token) > 0,
+            Action::Get(token) => self.get_locked(token),
+            Action::Nask(token) => self.count(&token) == 0,
+        }).collect()
+    }
+
+    fn import(&self, tokens: Vec<(TokenId, u32)>) {
+        let _lock = self.lock.lock().unwrap();
+        for (token, occurrences) in tokens.into_iter().filter(|(_, occurrences)| *occurrences > 0) {
+            self.set(token, self.count(&token).saturating_add(occurrences));
+        }
+    }
+
+    fn clear_store(&self) {
+        let _lock = self.lock.lock().unwrap();
+        for (token, _) in self.snapshot() {
+            self.set(TokenId::from(token), 0);
+        }
+    }
+
+    fn snapshot(&self) -> Vec<(Box<str>, u32)> {
+        // The keys of sled are sorted, as the tokens of a snapshot
+        self.db.iter().filter_map(Result::ok)
+            .filter_map(|(token, count)| Some((std::str::from_utf8(&token).ok()?.into(), u32::from_be_bytes(count.as_ref().try_into().ok()?))))
+            .collect()
+    }
+
+    fn size(&self) -> usize {
+        self.db.len()
+    }
+
+    fn print_store(&self) {
+        let tokens: Vec<String> = self.snapshot().iter().map(|(token, count)| format!("{}({})", token, count)).collect();
+        log!(Level::Debug, "Store: {}", tokens.join(", "));
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.changes.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+            lock: Arc::clone(&self.lock),
+            changes: self.changes.clone(),
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::store::from_config;
+    use crate::config::Config;
+
+    #[test]
+    fn sled_store_should_keep_the_tokens_across_openings() {
+        let path = std::env::temp_dir().join(format!("bacht-sled-{}", rand::random::<u64>()));
+        {
+            let store = SledStore::open(&path).unwrap();
+            let mut changes = store.subscribe();
+            assert_eq!(store.apply_batch(vec![Action::Tell("b".into()), Action::Tell("a".into()), Action::Tell("a".into()), Action::Get("b".into())]),
+                [true, true, true, true]);
+            assert!(!store.get("b".into()));
+            assert_eq!(changes.try_recv().unwrap(), StoreChange { token: "b".into(), count: 1 });
+            store.db.flush().unwrap();
+        }
+        let config = Config::new().with("store.backend", "sled").with("store.path", path.to_str().unwrap());
+        // The flusher thread of sled releases the lock of the directory shortly after the database is dropped
+        let store = (0..100).find_map(|_| from_config(&config).inspect_err(|_| std::thread::sleep(std::time::Duration::from_millis(10))).ok())
+            .expect("The database should be opened again");
+        assert_eq!(store.snapshot(), [("a".into(), 2)]);
+        assert!(store.ask("a".into()) && store.nask("b".into()));
+        assert_eq!(store.size(), 1);
+        store.import(vec![("a".into(), 1), ("c".into(), 2)]);
+        assert_eq!(store.snapshot(), [("a".into(), 3), ("c".into(), 2)]);
+        store.clear_store();
+        assert_eq!(store.snapshot(), []);
+        drop(store);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -1,14 +1,30 @@
 use std::collections::HashMap;
 use mockall::automock;
 use std::sync::{Arc, Mutex};
-
+use tokio::sync::broadcast;
+use crate::config::Config;
+use crate::error::ConfigError;
+use crate::model::action::Action;
+use crate::model::token::TokenId;
+use crate::model::change::StoreChange;
+use crate::log;
+use crate::log::Level;
+
+// The changes kept for a subscriber that lags behind, the older ones are dropped
+const CHANGES_CAPACITY: usize = 1024;
+
+/// The directory of the sled store when store.path is not set, in the working directory
+#[cfg(feature = "sled")]
+pub const DEFAULT_SLED_PATH: &str = "bacht.sled";
+
+/// **@note** - The trait is object safe, so that a store can be chosen at runtime as a DynStore
 #[automock]
 pub trait StoreTrait {
     
     /// **@summary** - The constructor of the Store
     /// 
     /// **@returns** - The Store instance
-    fn new() -> Self;
+    fn new() -> Self where Self: Sized;
 
     /// **@summary** - It adds one occurrence of the token to the store
     ///
@@ -18,34 +34,169 @@ pub trait StoreTrait {
     ///
     /// Nbr of occurrences of the token is encoded using u32. So it ignores incrementation if it reaches the u32's max value.
     /// See [reference](https://doc.rust-lang.org/std/collections/hash_map/enum.Entry.html).
-    fn tell(&self, token: Box<str>) -> bool;
+    fn tell(&self, token: TokenId) -> bool;
 
     /// **@summary** - It checks if the token is in the store
     ///
     /// **@param** token: &str - The token to check in the store
     ///
     /// **@returns** - true if the token is in the store, false otherwise
-    fn ask(&self, token: &str) -> bool;
+    fn ask(&self, token: TokenId) -> bool;
 
     /// **@summary** - It checks if the token is in the store and removes one occurrence of it
     ///
     /// **@param** token: &str - The token to check in the store
     ///
     /// **@returns** - true if the token is in the store, false otherwise
-    fn get(&self, token: Box<str>) -> bool;
+    fn get(&self, token: TokenId) -> bool;
 
     /// **@summary** - It checks if the token is absent from the store
     ///
     /// **@param** token: &str - The token to check in the store
     ///
     /// **@returns** - true if the token is absent from the store, false if it is present
-    fn nask(&self, token: &str) -> bool;
+    fn nask(&self, token: TokenId) -> bool;
+
+    /// **@summary** - It applies the actions in order, e.g. the batch of tasks dequeued at once by the worker
+    ///
+    /// **@returns** - The result of each action, as if applied one by one
+    ///
+    /// **@note** - The Store applies them under one lock, the default applies them one by one
+    fn apply_batch(&self, actions: Vec<Action>) -> Vec<bool> {
+        actions.into_iter().map(|action| match action {
+            Action::Tell(token) => self.tell(token),
+            Action::Ask(token) => self.ask(token),
+            Action::Get(token) => self.get(token),
+            Action::Nask(token) => self.nask(token),
+        }).collect()
+    }
+
+    /// **@summary** - It adds the occurrences of the tokens, e.g. of a snapshot imported at once
+    ///
+    /// **@param** tokens - The tokens with the number of occurrences to add, counted as by tell
+    ///
+    /// **@note** - The Store adds them under one lock, notifying each token once, the default tells them one by one
+    fn import(&self, tokens: Vec<(TokenId, u32)>) {
+        for (token, occurrences) in tokens {
+            for _ in 0..occurrences {
+                self.tell(token);
+            }
+        }
+    }
 
     /// **@summary** - It clears the store
     fn clear_store(&self);
+
+    /// **@summary** - It lists the tokens present in the store
+    ///
+    /// **@returns** - The tokens with their number of occurrences, sorted by token
+    fn snapshot(&self) -> Vec<(Box<str>, u32)>;
+
+    /// **@summary** - It counts the tokens present in the store
+    ///
+    /// **@returns** - The number of distinct tokens with at least one occurrence
+    fn size(&self) -> usize;
+
+    /// **@summary** - It logs the tokens of the store and their occurrences, at Level::Debug
     fn print_store(&self);
+
+    /// **@summary** - It subscribes to the changes of the tokens, e.g. to watch a token
+    ///
+    /// **@returns** - The changes applied from now on, a subscriber lagging behind loses the oldest ones
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange>;
     
-    fn clone(&self) -> Self;
+    fn clone(&self) -> Self where Self: Sized;
+}
+
+/// **@summary** - A store chosen at runtime, e.g. from the configuration of a node, see from_config
+///
+/// Its clones share the same store, as the clones of a Store.
+pub struct DynStore(Arc<dyn StoreTrait + Send + Sync>);
+
+impl DynStore {
+
+    /// **@summary** - Use any store, e.g. one implemented by an application
+    pub fn new_with(store: impl StoreTrait + Send + Sync + 'static) -> Self {
+        Self(Arc::new(store))
+    }
+}
+
+impl From<Arc<dyn StoreTrait + Send + Sync>> for DynStore {
+    fn from(store: Arc<dyn StoreTrait + Send + Sync>) -> Self {
+        Self(store)
+    }
+}
+
+impl StoreTrait for DynStore {
+
+    /// **@summary** - A new in-memory Store
+    fn new() -> Self {
+        Self::new_with(Store::new())
+    }
+
+    fn tell(&self, token: TokenId) -> bool {
+        self.0.tell(token)
+    }
+
+    fn ask(&self, token: TokenId) -> bool {
+        self.0.ask(token)
+    }
+
+    fn get(&self, token: TokenId) -> bool {
+        self.0.get(token)
+    }
+
+    fn nask(&self, token: TokenId) -> bool {
+        self.0.nask(token)
+    }
+
+    fn apply_batch(&self, actions: Vec<Action>) -> Vec<bool> {
+        self.0.apply_batch(actions)
+    }
+
+    fn import(&self, tokens: Vec<(TokenId, u32)>) {
+        self.0.import(tokens)
+    }
+
+    fn clear_store(&self) {
+        self.0.clear_store()
+    }
+
+    fn snapshot(&self) -> Vec<(Box<str>, u32)> {
+        self.0.snapshot()
+    }
+
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    fn print_store(&self) {
+        self.0.print_store()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.0.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// **@summary** - The store of the backend set by `store.backend`: `memory`, the default, or `sled`, persisted in the
+/// directory of `store.path` (`bacht.sled` by default) with the sled feature
+///
+/// **@returns** - The store, or ConfigError::Unavailable for a backend not built in
+pub fn from_config(config: &Config) -> Result<DynStore, ConfigError> {
+    match config.get("store.backend").unwrap_or("memory") {
+        "memory" => Ok(DynStore::new_with(Store::new())),
+        #[cfg(feature = "sled")]
+        "sled" => {
+            let path = std::path::Path::new(config.get("store.path").unwrap_or(DEFAULT_SLED_PATH));
+            Ok(DynStore::new_with(super::sled_store::SledStore::open(path)?))
+        },
+        backend => Err(ConfigError::Unavailable { key: "store.backend".to_string(), value: backend.to_string() }),
+    }
 }
 
 
@@ -53,70 +204,89 @@ pub trait StoreTrait {
 ///
 /// Using HashMap, see [reference](https://doc.rust-lang.org/std/collections/struct.HashMap.html).
 pub struct Store {
-    the_store: Arc<Mutex<HashMap<Box<str>, u32>>>
+    the_store: Arc<Mutex<HashMap<TokenId, u32>>>,
+    changes: broadcast::Sender<StoreChange>,
 }
 
 
 impl StoreTrait for Store {
 
     fn new() -> Store {
-        Store {
-            the_store: Arc::from(Mutex::new(HashMap::new()))
-        }
+        Self::new_with_data(HashMap::new())
     }
 
-    fn tell(&self, token: Box<str>) -> bool {
-        self.the_store.lock().unwrap().entry(token).and_modify(|nbr_occurrence| {
-            *nbr_occurrence = Self::safe_inc(*nbr_occurrence);
-        }).or_insert(1);
-        true
+    fn tell(&self, token: TokenId) -> bool {
+        self.tell_locked(&mut self.the_store.lock().unwrap(), token)
     }
 
-    fn ask(&self, token: &str) -> bool {
-        let unlock_store = self.the_store.lock().unwrap();
-        if !unlock_store.contains_key(token) {
-            false
-        } else {
-            unlock_store.get(token).unwrap() > &0
-        }
+    fn ask(&self, token: TokenId) -> bool {
+        Self::ask_locked(&self.the_store.lock().unwrap(), token)
     }
 
-    fn get(&self, token: Box<str>) -> bool {
-        let mut res = false;
+    fn get(&self, token: TokenId) -> bool {
+        self.get_locked(&mut self.the_store.lock().unwrap(), token)
+    }
 
-        self.the_store.lock().unwrap().entry(token).and_modify(|nbr_occurrence| {
-            if *nbr_occurrence > 0 {
-                *nbr_occurrence -= 1;
-                res = true;
-            }
-        });
-        res
+    fn nask(&self, token: TokenId) -> bool {
+        !Self::ask_locked(&self.the_store.lock().unwrap(), token)
     }
 
-    fn nask(&self, token: &str) -> bool {
-        let unlock_store = self.the_store.lock().unwrap();
-        if !unlock_store.contains_key(token) {
-            true
-        } else {
-            unlock_store.get(token).unwrap() <= &0
+    fn apply_batch(&self, actions: Vec<Action>) -> Vec<bool> {
+        let mut store = self.the_store.lock().unwrap();
+        actions.into_iter().map(|action| match action {
+            Action::Tell(token) => self.tell_locked(&mut store, token),
+            Action::Ask(token) => Self::ask_locked(&store, token),
+            Action::Get(token) => self.get_locked(&mut store, token),
+            Action::Nask(token) => !Self::ask_locked(&store, token),
+        }).collect()
+    }
+
+    fn import(&self, tokens: Vec<(TokenId, u32)>) {
+        let mut store = self.the_store.lock().unwrap();
+        for (token, occurrences) in tokens.into_iter().filter(|(_, occurrences)| *occurrences > 0) {
+            let count = store.entry(token).or_default();
+            *count = count.saturating_add(occurrences);
+            self.notify(token, *count);
         }
     }
 
     fn clear_store(&self) {
-        self.the_store.lock().unwrap().clear();
+        let mut store = self.the_store.lock().unwrap();
+        for (token, nbr_occurrence) in store.drain() {
+            if nbr_occurrence > 0 {
+                self.notify(token, 0);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<(Box<str>, u32)> {
+        let mut tokens: Vec<(Box<str>, u32)> = self.the_store.lock().unwrap().iter()
+            .filter(|(_, nbr_occurrence)| **nbr_occurrence > 0)
+            .map(|(token, nbr_occurrence)| ((*token).into(), *nbr_occurrence))
+            .collect();
+        tokens.sort();
+        tokens
+    }
+
+    fn size(&self) -> usize {
+        self.the_store.lock().unwrap().values().filter(|nbr_occurrence| **nbr_occurrence > 0).count()
     }
 
     fn print_store(&self) {
-        print!("=== Store ===\n");
-        for (key, value) in self.the_store.lock().unwrap().iter() {
-            println!("{}({})", key, value);
-        }
-        print!("\n");
+        let tokens: Vec<String> = self.the_store.lock().unwrap().iter()
+            .map(|(key, value)| format!("{}({})", key, value))
+            .collect();
+        log!(Level::Debug, "Store: {}", tokens.join(", "));
     }
     
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.changes.subscribe()
+    }
+
     fn clone(&self) -> Self {
         Store {
-            the_store: Arc::clone(&self.the_store)
+            the_store: Arc::clone(&self.the_store),
+            changes: self.changes.clone(),
         }
     }
 }
@@ -124,12 +294,43 @@ impl StoreTrait for Store {
 impl Store {
 
     /// Create a new store with predefined data
-    pub(crate) fn new_with_data(data: HashMap<Box<str>, u32>) -> Store {
+    pub fn new_with_data(data: HashMap<TokenId, u32>) -> Store {
         Store {
-            the_store: Arc::from(Mutex::new(data))
+            the_store: Arc::from(Mutex::new(data)),
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+        }
+    }
+
+    fn tell_locked(&self, store: &mut HashMap<TokenId, u32>, token: TokenId) -> bool {
+        let count = *store.entry(token).and_modify(|nbr_occurrence| {
+            *nbr_occurrence = Self::safe_inc(*nbr_occurrence);
+        }).or_insert(1);
+        // Notified under the lock, so that the subscribers receive the changes in order
+        self.notify(token, count);
+        true
+    }
+
+    fn ask_locked(store: &HashMap<TokenId, u32>, token: TokenId) -> bool {
+        store.get(&token).is_some_and(|nbr_occurrence| *nbr_occurrence > 0)
+    }
+
+    fn get_locked(&self, store: &mut HashMap<TokenId, u32>, token: TokenId) -> bool {
+        match store.get_mut(&token) {
+            Some(nbr_occurrence) if *nbr_occurrence > 0 => {
+                *nbr_occurrence -= 1;
+                let count = *nbr_occurrence;
+                self.notify(token, count);
+                true
+            },
+            _ => false,
         }
     }
 
+    fn notify(&self, token: TokenId, count: u32) {
+        // No subscriber is not an error
+        let _ = self.changes.send(StoreChange { token, count });
+    }
+
     /// **@summary** - It increments a number by one safely
     ///
     /// **@param** nbr: u32 - The number to increment
@@ -148,12 +349,11 @@ impl Store {
 /// ===============
 /// |    TESTS    |
 /// ===============
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn get_data(store: &Store) -> HashMap<Box<str>, u32> {
+    fn get_data(store: &Store) -> HashMap<TokenId, u32> {
         store.the_store.lock().unwrap().clone()
     }
 
@@ -174,14 +374,14 @@ mod tests {
         let store = Store::new();
         let res = store.tell("token".into());
         assert!(res);
-        assert!(get_data(&store).contains_key("token"));
+        assert!(get_data(&store).contains_key(&"token".into()));
     }
 
     #[test]
     fn the_store_should_increment_token_when_tell_if_it_exists() {
         let store = Store::new_with_data(HashMap::from([("token".into(), 1)]));
         assert!(store.tell("token".into()));
-        assert_eq!(get_data(&store).get("token").unwrap(), &2);
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &2);
     }
 
     #[test]
@@ -189,7 +389,7 @@ mod tests {
         let store = Store::new_with_data(HashMap::from([("token".into(), u32::MAX)]));
         let res = store.tell("token".into());
         assert!(res);
-        assert_eq!(get_data(&store).get("token").unwrap(), &u32::MAX);
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &u32::MAX);
     }
 
     // ask section
@@ -197,22 +397,22 @@ mod tests {
     #[test]
     fn the_store_should_be_able_to_ask_if_one_or_more_occurrence_of_token() {
         let store = Store::new_with_data(HashMap::from([("token".into(), 1)]));
-        assert!(store.ask("token"));
-        assert_eq!(get_data(&store).get("token").unwrap(), &1);
+        assert!(store.ask("token".into()));
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &1);
     }
 
     #[test]
     fn the_store_should_not_be_able_to_ask_if_zero_occurrence_of_token() {
         let store = Store::new_with_data(HashMap::from([("token".into(), 0)]));
-        assert!(!store.ask("token"));
-        assert_eq!(get_data(&store).get("token").unwrap(), &0);
+        assert!(!store.ask("token".into()));
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &0);
     }
 
     #[test]
     fn the_store_should_not_be_able_to_ask_if_no_occurrence_of_token() {
         let store = Store::new();
-        assert!(!store.ask("token"));
-        assert!(!get_data(&store).contains_key("token"));
+        assert!(!store.ask("token".into()));
+        assert!(!get_data(&store).contains_key(&"token".into()));
     }
 
     // get section
@@ -221,14 +421,14 @@ mod tests {
     fn the_store_should_be_able_to_get_one_occurrence_of_token() {
         let store = Store::new_with_data(HashMap::from([("token".into(), 1)]));
         assert!(store.get("token".into()));
-        assert_eq!(store.the_store.lock().unwrap().get("token").unwrap(), &0);
+        assert_eq!(store.the_store.lock().unwrap().get(&"token".into()).unwrap(), &0);
     }
 
     #[test]
     fn the_store_should_not_be_able_to_get_if_zero_occurrence_of_token() {
         let store = Store::new_with_data(HashMap::from([("token".into(), 0)]));
         assert!(!store.get("token".into()));
-        assert_eq!(get_data(&store).get("token").unwrap(), &0);
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &0);
     }
 
     #[test]
@@ -245,24 +445,24 @@ mod tests {
         let store = Store::new_with_data(
             HashMap::from([("token".into(), 0)])
         );
-        assert!(store.nask("token"));
-        assert_eq!(get_data(&store).get("token").unwrap(), &0);
+        assert!(store.nask("token".into()));
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &0);
     }
 
     #[test]
     fn the_store_should_be_able_to_nask_if_no_occurrence_of_token() {
         let store = Store::new();
-        let res = store.nask("token");
+        let res = store.nask("token".into());
         assert!(res);
-        assert!(!get_data(&store).contains_key("token"));
+        assert!(!get_data(&store).contains_key(&"token".into()));
     }
 
     #[test]
     fn the_store_should_not_be_able_to_nask_if_one_or_more_occurrence_of_token() {
         let store = Store::new_with_data(HashMap::from([("token".into(), 1)]));
-        let res = store.nask("token");
+        let res = store.nask("token".into());
         assert!(!res);
-        assert_eq!(get_data(&store).get("token").unwrap(), &1);
+        assert_eq!(get_data(&store).get(&"token".into()).unwrap(), &1);
     }
 
     // Clear_store section
@@ -274,6 +474,67 @@ mod tests {
         assert!(get_data(&store).is_empty());
     }
 
+    // Snapshot section
+
+    #[test]
+    fn the_store_should_list_its_present_tokens_sorted() {
+        let store = Store::new_with_data(HashMap::from([
+            ("tameImpala".into(), 5),
+            ("daftPunk".into(), u32::MAX),
+            ("gorillaz".into(), 0)
+        ]));
+        assert_eq!(store.snapshot(), vec![("daftPunk".into(), u32::MAX), ("tameImpala".into(), 5)]);
+    }
+
+    #[test]
+    fn the_store_should_notify_the_changes_of_its_tokens() {
+        let store = Store::new();
+        let mut changes = store.subscribe();
+        store.tell("a".into());
+        store.tell("a".into());
+        store.get("a".into());
+        store.get("b".into());
+        store.clear_store();
+        let notified: Vec<StoreChange> = std::iter::from_fn(|| changes.try_recv().ok()).collect();
+        let change = |token: &str, count| StoreChange { token: token.into(), count };
+        assert_eq!(notified, [change("a", 1), change("a", 2), change("a", 1), change("a", 0)], "A failed get should not be notified");
+    }
+
+    #[test]
+    fn the_store_should_count_its_present_tokens_only() {
+        let store = Store::new_with_data(HashMap::from([
+            ("tameImpala".into(), 5),
+            ("daftPunk".into(), 1),
+            ("gorillaz".into(), 0)
+        ]));
+        assert_eq!(store.size(), 2);
+        store.clear_store();
+        assert_eq!(store.size(), 0);
+    }
+
+    // Apply_batch section
+
+    #[test]
+    fn the_store_should_apply_a_batch_in_order() {
+        let store = Store::new();
+        let actions = vec![Action::Ask("a".into()), Action::Tell("a".into()), Action::Ask("a".into()), Action::Get("a".into()),
+            Action::Get("a".into()), Action::Nask("a".into())];
+        assert_eq!(store.apply_batch(actions), [false, true, true, true, false, true]);
+        assert_eq!(store.size(), 0);
+    }
+
+    // Import section
+
+    #[test]
+    fn the_store_should_import_the_occurrences_of_the_tokens_at_once() {
+        let store = Store::new_with_data(HashMap::from([("a".into(), u32::MAX - 1), ("b".into(), 1)]));
+        let mut changes = store.subscribe();
+        store.import(vec![("a".into(), 3), ("b".into(), 2), ("c".into(), 0)]);
+        assert_eq!(store.snapshot(), [("a".into(), u32::MAX), ("b".into(), 3)]);
+        assert_eq!(std::iter::from_fn(|| changes.try_recv().ok()).collect::<Vec<_>>(),
+            [StoreChange { token: "a".into(), count: u32::MAX }, StoreChange { token: "b".into(), count: 3 }]);
+    }
+
     // Print_store section
 
     #[test]
@@ -285,4 +546,18 @@ mod tests {
         ]));
         store.print_store();
     }
-}
\ No newline at end of file
+
+    // Dyn store section
+
+    #[test]
+    fn the_store_should_be_chosen_from_the_config_at_runtime() {
+        let store = from_config(&Config::new()).unwrap();
+        let shared = store.clone();
+        assert!(store.tell("token".into()));
+        assert!(shared.ask("token".into()));
+        let stores: Vec<Box<dyn StoreTrait>> = vec![Box::new(Store::new()), Box::new(shared)];
+        assert_eq!(stores.iter().map(|store| store.size()).collect::<Vec<usize>>(), [0, 1]);
+        assert_eq!(from_config(&Config::new().with("store.backend", "redis")).err().map(|e| e.to_string()),
+            Some("store.backend redis is not available in this build".to_string()));
+    }
+}
@@ -0,0 +1,160 @@
+use tokio::runtime::{Builder, Handle, Runtime};
+use crate::blackboard::{Blackboard, BlackboardTrait};
+use crate::blackboard::inline::InlineBlackboard;
+use crate::blackboard::store::Store;
+use crate::blackboard::task_queue::TaskQueue;
+use crate::blackboard::worker::Worker;
+use crate::error::StoreError;
+use crate::model::admin::{AdminCommand, AdminReply};
+#[cfg(feature = "language")]
+use crate::error::Error;
+#[cfg(feature = "language")]
+use crate::language::blackboard_interface::LocalBlackboardInterface;
+#[cfg(feature = "language")]
+use crate::language::parser::parse;
+#[cfg(feature = "language")]
+use crate::language::simulator::{Simulator, SimulatorTrait};
+
+/// @summary - A blackboard used without async: each operation blocks until the blackboard answers, the worker running
+/// on a tokio runtime owned or shared by the facade
+///
+/// @note - The operations must not be called from an async task, where blocking would stall the runtime
+pub struct SyncBlackboard<B: BlackboardTrait = Blackboard<TaskQueue, Worker, Store>> {
+    // The runtime created by new, none if the facade runs on the runtime of its user
+    runtime: Option<Runtime>,
+    handle: Handle,
+    blackboard: B,
+}
+
+impl SyncBlackboard {
+
+    /// @summary - Create a blackboard running on a runtime of its own
+    ///
+    /// @returns - An error if the runtime can't be created, e.g. the threads can't be spawned
+    pub fn new() -> std::io::Result<Self> {
+        let runtime = Builder::new_multi_thread().enable_all().build()?;
+        let handle = runtime.handle().clone();
+        // The worker of the blackboard is spawned on the runtime
+        let blackboard = handle.block_on(async { crate::blackboard::create_blackboard() });
+        Ok(Self { runtime: Some(runtime), handle, blackboard })
+    }
+}
+
+impl SyncBlackboard<InlineBlackboard> {
+
+    /// @summary - Create a blackboard running on the calling thread only: its runtime is a current-thread one, and
+    /// the events are applied by the operations themselves, see InlineBlackboard
+    ///
+    /// @returns - An error if the runtime can't be created
+    pub fn new_inline() -> std::io::Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let handle = runtime.handle().clone();
+        Ok(Self { runtime: Some(runtime), handle, blackboard: InlineBlackboard::new() })
+    }
+}
+
+impl<B: BlackboardTrait> SyncBlackboard<B> {
+
+    /// @summary - Block on an existing blackboard, sharing its store
+    ///
+    /// @param handle - The runtime of the blackboard, its worker runs on
+    pub fn new_with(blackboard: B, handle: Handle) -> Self {
+        Self { runtime: None, handle, blackboard }
+    }
+
+    /// @returns - The blackboard, e.g. to hand it to async code
+    pub fn blackboard(&self) -> &B {
+        &self.blackboard
+    }
+
+    pub fn tell(&self, coord_data: &str) -> Result<bool, StoreError> {
+        self.handle.block_on(self.blackboard.tell(coord_data.into()))
+    }
+
+    pub fn ask(&self, coord_data: &str) -> Result<bool, StoreError> {
+        self.handle.block_on(self.blackboard.ask(coord_data.into()))
+    }
+
+    pub fn get(&self, coord_data: &str) -> Result<bool, StoreError> {
+        self.handle.block_on(self.blackboard.get(coord_data.into()))
+    }
+
+    pub fn nask(&self, coord_data: &str) -> Result<bool, StoreError> {
+        self.handle.block_on(self.blackboard.nask(coord_data.into()))
+    }
+
+    pub fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        self.handle.block_on(self.blackboard.admin(command))
+    }
+}
+
+#[cfg(feature = "language")]
+impl<B: BlackboardTrait + Sync> SyncBlackboard<B> {
+
+    /// @summary - Parse an agent and run it until it terminates or gets stuck
+    ///
+    /// @returns - true if the agent terminated, false if it got stuck
+    pub fn exec(&self, agent: &str) -> Result<bool, Error> {
+        let agent = parse(agent)?;
+        let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(self.blackboard.clone()));
+        self.handle.block_on(simulator.bacht_exec_all(agent))
+    }
+
+    /// @summary - Run the agents one after the other, until one of them gets stuck
+    ///
+    /// @returns - The number of agents that terminated
+    pub fn exec_all<'a>(&self, agents: impl IntoIterator<Item = &'a str>) -> Result<usize, Error> {
+        let mut terminated = 0;
+        for agent in agents {
+            if !self.exec(agent)? {
+                break;
+            }
+            terminated += 1;
+        }
+        Ok(terminated)
+    }
+}
+
+impl<B: BlackboardTrait> Drop for SyncBlackboard<B> {
+    fn drop(&mut self) {
+        // Dropping a runtime waits for its tasks, the worker of the blackboard never ends
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_blackboard_should_block_until_the_blackboard_answers() {
+        let blackboard = SyncBlackboard::new().unwrap();
+        assert!(blackboard.tell("a").unwrap());
+        assert!(blackboard.ask("a").unwrap());
+        assert!(!blackboard.nask("a").unwrap());
+        assert!(blackboard.get("a").unwrap());
+        assert!(!blackboard.get("a").unwrap());
+        #[cfg(feature = "language")]
+        {
+            assert_eq!(blackboard.exec_all(["tell(b);tell(b)", "get(b)||ask(b)", "get(b);get(b)", "tell(c)"]).unwrap(), 2);
+            assert!(matches!(blackboard.exec("tell(b"), Err(Error::Parse(_))));
+        }
+        assert!(matches!(blackboard.admin(AdminCommand::Snapshot), Ok(AdminReply::Snapshot(tokens)) if tokens.is_empty()));
+    }
+
+    #[test]
+    fn sync_blackboard_should_run_inline_on_the_calling_thread() {
+        let blackboard = SyncBlackboard::new_inline().unwrap();
+        assert!(blackboard.tell("a").unwrap());
+        assert!(blackboard.get("a").unwrap());
+        assert!(!blackboard.ask("a").unwrap());
+        #[cfg(feature = "language")]
+        assert_eq!(blackboard.exec_all(["tell(b);tell(b)", "get(b)||ask(b)", "get(b);get(b)"]).unwrap(), 2);
+        assert_eq!(blackboard.blackboard().health().queue_depth, 0);
+    }
+}
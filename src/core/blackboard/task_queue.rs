@@ -4,7 +4,10 @@ use mockall::automock;
 use tokio::sync::Notify;
 use tokio::sync::oneshot::Receiver;
 use crate::model::event::Event;
-use crate::model::task::{Task, TaskError};
+use crate::model::task::Task;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
 
 #[automock]
 pub trait TaskQueueTrait {
@@ -26,12 +29,24 @@ pub trait TaskQueueTrait {
     /// @param event - The event to add to the queue
     ///
     /// @returns - A promise of the reception channel to get the result of the task
-    fn add_event_to_queue(&self, event: Event) -> Receiver<Result<bool, TaskError>>;
+    fn add_event_to_queue(&self, event: Event) -> Receiver<Result<bool, StoreError>>;
 
     /// @summary - Allow to get the task form the queue w.r.t. FIFO Policy
     ///
     /// @returns - The oldest task in the queue
     fn get_task(&self) -> Option<Task>;
+
+    /// @summary - Allow to get several tasks from the queue at once w.r.t. FIFO Policy
+    ///
+    /// @param max - The most tasks taken
+    ///
+    /// @returns - The oldest tasks in the queue, the oldest first, none if the queue is empty
+    fn get_tasks(&self, max: usize) -> Vec<Task>;
+
+    /// @summary - Allow to know how many tasks wait for the worker
+    ///
+    /// @returns - The number of tasks in the queue
+    fn depth(&self) -> usize;
     
     /// @summary - Notify the worker that there is a new task in the queue
     /// 
@@ -72,9 +87,12 @@ impl TaskQueueTrait for TaskQueue {
         }
     }
     
-    fn add_event_to_queue(&self, event: Event) -> Receiver<Result<bool, TaskError>> {
-        let (task, rx) = Task::new(event);
+    fn add_event_to_queue(&self, event: Event) -> Receiver<Result<bool, StoreError>> {
+        let span = event.span().clone();
         let mut queue = self.task_queue.lock().unwrap();
+        // Logged before the event moves into its task, which is counted among the pending ones
+        log!(Level::Trace, span => "Queued {:?}, {} pending", event.action, queue.len() + 1);
+        let (task, rx) = Task::new(event);
         queue.insert(0, task);
         self.notifier.notify_one();
         rx
@@ -85,6 +103,19 @@ impl TaskQueueTrait for TaskQueue {
         queue.pop()
     }
 
+    fn get_tasks(&self, max: usize) -> Vec<Task> {
+        let mut queue = self.task_queue.lock().unwrap();
+        let oldest = queue.len().saturating_sub(max);
+        // The oldest tasks are at the end of the queue
+        let mut tasks = queue.split_off(oldest);
+        tasks.reverse();
+        tasks
+    }
+
+    fn depth(&self) -> usize {
+        self.task_queue.lock().unwrap().len()
+    }
+
     async fn notify(&self) {
         self.notifier.notified().await;
     }
@@ -105,7 +136,6 @@ impl TaskQueueTrait for TaskQueue {
 /// ===============
 /// |    TESTS    |
 /// ===============
-
 #[cfg(test)]
 mod test {
     use tokio::{
@@ -118,7 +148,7 @@ mod test {
     use crate::model::event::Event;
     use crate::model::action::Action::Tell;
     use crate::model::task::Task;
-    use crate::model::task::TaskError::UnspecifiedError;
+    use crate::error::{QueueError, StoreError};
 
     // Test add event
     #[tokio::test]
@@ -134,6 +164,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
     async fn queue_should_add_even_if_queue_is_filled() {
         let queue: Arc<Mutex<Vec<Task>>> = Arc::new(Mutex::new(Vec::new()));
         let notify = Arc::new(Notify::new());
@@ -188,6 +219,7 @@ mod test {
 
     // Test get task
     #[tokio::test]
+    #[allow(clippy::assertions_on_constants)]
     async fn queue_should_allow_getting_task() {
         let (task, _) = Task::new(Event::new(Tell("token".into())));
         let queue = Arc::new(Mutex::new(vec!(task)));
@@ -199,7 +231,7 @@ mod test {
         assert!(task_from_queue.is_some(), "Task should not be None");
         match task_from_queue.unwrap().event.action {
             Tell(t) => {
-                assert_eq!(t, "token".into(), "The token should be the same as the one added");
+                assert_eq!(t, "token", "The token should be the same as the one added");
             },
             _ => {
                 assert!(false, "Should be an Tell action");
@@ -207,6 +239,29 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn queue_should_report_its_depth() {
+        let task_queue = TaskQueue::new();
+        assert_eq!(task_queue.depth(), 0);
+        task_queue.add_event_to_queue(Event::new(Tell("a".into())));
+        task_queue.add_event_to_queue(Event::new(Tell("b".into())));
+        assert_eq!(task_queue.depth(), 2);
+        task_queue.get_task();
+        assert_eq!(task_queue.depth(), 1);
+    }
+
+    #[tokio::test]
+    async fn queue_should_give_several_tasks_at_once_oldest_first() {
+        let task_queue = TaskQueue::new();
+        for token in ["a", "b", "c"] {
+            task_queue.add_event_to_queue(Event::new(Tell(token.into())));
+        }
+        let actions = |tasks: Vec<Task>| tasks.into_iter().map(|task| task.event.action).collect::<Vec<_>>();
+        assert_eq!(actions(task_queue.get_tasks(2)), [Tell("a".into()), Tell("b".into())]);
+        assert_eq!(actions(task_queue.get_tasks(2)), [Tell("c".into())]);
+        assert!(task_queue.get_tasks(2).is_empty());
+    }
+
     #[tokio::test]
     async fn queue_should_return_none_if_no_task() {
         let queue = Arc::new(Mutex::new(Vec::new()));
@@ -219,6 +274,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[allow(clippy::assertions_on_constants)]
     async fn queue_should_respect_fifo_policy() {
         let task_queue = TaskQueue::new();
         let event1 = Event::new(Tell("token1".into()));
@@ -234,7 +290,7 @@ mod test {
         assert!(task2.is_some(), "Task 2 should not be None");
         match task1.unwrap().event.action {
             Tell(t) => {
-                assert_eq!(t, "token1".into(), "Task 1 should be the first one added");
+                assert_eq!(t, "token1", "Task 1 should be the first one added");
             },
             _ => {
                 assert!(false, "Should be an Tell action");
@@ -242,7 +298,7 @@ mod test {
         }
         match task2.unwrap().event.action {
             Tell(t) => {
-                assert_eq!(t, "token2".into(), "Task 2 should be the second one added");
+                assert_eq!(t, "token2", "Task 2 should be the second one added");
             },
             _ => {
                 assert!(false, "Should be an Tell action");
@@ -286,7 +342,7 @@ mod test {
         assert_eq!(queue.len(), 1);
     }
     
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn queue_should_send_has_many_notification_as_receiving_event() {
         let task_queue = TaskQueue::new();
         let event1 = Event::new(Tell("token".into()));
@@ -314,7 +370,7 @@ mod test {
             }
         });
 
-        // Wait for the workers to be ready (not best practice, should await for the worker to be ready)
+        // Wait for the workers to be ready, at once on the paused clock
         sleep(Duration::from_secs(1)).await;
 
         task_queue.add_event_to_queue(event1);
@@ -330,7 +386,7 @@ mod test {
         assert!(result3.is_ok(), "Worker 3 task should have completed successfully");
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn queue_should_notify_only_one_element_per_append() {
         let task_queue = TaskQueue::new();
         let event = Event::new(Tell("token".into()));
@@ -349,7 +405,7 @@ mod test {
             }
         });
 
-        // Wait for the workers to be ready (not best practice, should await for the worker to be ready)
+        // Wait for the workers to be ready, at once on the paused clock
         sleep(Duration::from_secs(1)).await;
 
         task_queue.add_event_to_queue(event);
@@ -396,7 +452,7 @@ mod test {
         task::spawn(async move {
             // Simulate some processing
             let task = clone_task_queue.get_task().unwrap();
-            task.res_chanel.send(Err(UnspecifiedError))
+            task.res_chanel.send(Err(StoreError::Queue(QueueError::Unspecified)))
         });
 
         let result_chanel = rx.await;
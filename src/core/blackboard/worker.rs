@@ -1,12 +1,61 @@
 use std::future::Future;
+use std::str::FromStr;
 use std::sync::{Arc};
+use std::sync::atomic::{AtomicBool, Ordering};
 use mockall::automock;
-use tokio::task::JoinHandle;
 use tokio::sync::Mutex;
+use crate::blackboard::chaos::{Chaos, Mischief, Schedule};
+use crate::blackboard::dead_letters::{DeadLetter, DeadLetters};
 use crate::blackboard::event_handler::EventHandlerTrait;
 use crate::blackboard::store::StoreTrait;
 use crate::blackboard::task_queue::TaskQueueTrait;
+use crate::error::StoreError;
+use crate::log;
+use crate::metrics;
+use crate::log::Level;
+use crate::model::action::Action;
+use crate::model::event::Event;
+use crate::model::task::Task;
+use crate::runtime::RuntimeHandle;
+use crate::trace::ActiveSpan;
 
+/// What the worker does with the result of an event once its sender stopped waiting, e.g. a client that timed out
+#[derive(Debug, Clone, Default)]
+pub enum DroppedResultPolicy {
+    /// Log the result and forget it
+    #[default]
+    Drop,
+    /// Keep the event and its result in the dead letters given
+    DeadLetter(DeadLetters),
+    /// Undo the action applied, as if it never ran: a tell by a get, a get by a tell, an ask or a nask changing nothing
+    RollBack,
+}
+
+/// @note - `drop`, `dead_letter` and `rollback`, the dead letters being new ones
+impl FromStr for DroppedResultPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "drop" => Ok(Self::Drop),
+            "dead_letter" => Ok(Self::DeadLetter(DeadLetters::new())),
+            "rollback" => Ok(Self::RollBack),
+            _ => Err(format!("Unknown policy {}, expected drop, dead_letter or rollback", policy)),
+        }
+    }
+}
+
+/// How a worker runs, see WorkerTrait::new_with_options
+#[derive(Debug, Clone, Default)]
+pub struct WorkerOptions {
+    pub policy: DroppedResultPolicy,
+    /// The most tasks dequeued and applied on the store at once, under one lock of each; 0 or 1 applies them one by one
+    pub batch_size: usize,
+    /// The misbehaviours hitting the tasks, for the tests
+    pub chaos: Option<Chaos>,
+    /// The executor the job is spawned on, and sleeps on
+    pub runtime: RuntimeHandle,
+}
 
 #[automock]
 pub trait WorkerTrait {
@@ -19,13 +68,43 @@ pub trait WorkerTrait {
         S: StoreTrait + Sync + Send + 'static,
         T: TaskQueueTrait + Sync + Send + 'static,
         E: EventHandlerTrait + Sync + Send + 'static;
+
+    /// @summary - Start a worker applying a policy to the results nobody waits for anymore, instead of dropping them
+    fn new_with_policy<S, T, E>(
+        store: S,
+        task_queue: T,
+        event_handler: E,
+        policy: DroppedResultPolicy,
+    ) -> Self
+    where
+        S: StoreTrait + Sync + Send + 'static,
+        T: TaskQueueTrait + Sync + Send + 'static,
+        E: EventHandlerTrait + Sync + Send + 'static;
+
+    /// @summary - Start a worker tuned by the options given, e.g. applying the tasks in batches
+    fn new_with_options<S, T, E>(
+        store: S,
+        task_queue: T,
+        event_handler: E,
+        options: WorkerOptions,
+    ) -> Self
+    where
+        S: StoreTrait + Sync + Send + 'static,
+        T: TaskQueueTrait + Sync + Send + 'static,
+        E: EventHandlerTrait + Sync + Send + 'static;
     
     fn safe_stop(&self) -> impl Future<Output = ()>;
+
+    /// @summary - Allow to know if the worker still processes the tasks
+    ///
+    /// @returns - false once the job of the worker stopped or panicked
+    fn is_alive(&self) -> bool;
 }
 
 /// Worker manage the thread in which the job is executed
 pub struct Worker {
-    pub join_handler: JoinHandle<()>,
+    // Set once the job returned or panicked
+    finished: Arc<AtomicBool>,
     safe_stop_signal: Arc<Mutex<bool>>, // default: false
 }
 
@@ -38,16 +117,46 @@ impl WorkerTrait for Worker {
     where S: StoreTrait + Sync + Send + 'static,
           T: TaskQueueTrait + Sync + Send + 'static,
           E: EventHandlerTrait + Sync + Send + 'static 
+    {
+        Self::new_with_policy(store, task_queue, event_handler, DroppedResultPolicy::default())
+    }
+
+    fn new_with_policy<S, T, E>(
+        store: S,
+        task_queue: T,
+        event_handler: E,
+        policy: DroppedResultPolicy,
+    ) -> Self
+    where S: StoreTrait + Sync + Send + 'static,
+          T: TaskQueueTrait + Sync + Send + 'static,
+          E: EventHandlerTrait + Sync + Send + 'static
+    {
+        Self::new_with_options(store, task_queue, event_handler, WorkerOptions { policy, ..WorkerOptions::default() })
+    }
+
+    fn new_with_options<S, T, E>(
+        store: S,
+        task_queue: T,
+        event_handler: E,
+        options: WorkerOptions,
+    ) -> Self
+    where S: StoreTrait + Sync + Send + 'static,
+          T: TaskQueueTrait + Sync + Send + 'static,
+          E: EventHandlerTrait + Sync + Send + 'static
     {
         let safe_stop_signal = Arc::new(Mutex::new(false));
         let safe_stop_signal_clone = safe_stop_signal.clone();
+        let finished = Arc::new(AtomicBool::new(false));
+        let done = Finished(finished.clone());
 
-        let join_handler = tokio::spawn(async move {
-            job(store, task_queue, event_handler, safe_stop_signal_clone).await;
+        options.runtime.clone().spawn(async move {
+            // Dropped with the job, even when it panics
+            let _done = done;
+            job(store, task_queue, event_handler, options, safe_stop_signal_clone).await;
         });
 
         Worker {
-            join_handler,
+            finished,
             safe_stop_signal,
         }
     }
@@ -55,6 +164,19 @@ impl WorkerTrait for Worker {
     async fn safe_stop(&self) {
         *self.safe_stop_signal.lock().await = true;
     }
+
+    fn is_alive(&self) -> bool {
+        !self.finished.load(Ordering::SeqCst)
+    }
+}
+
+// Marks the job of a worker finished when dropped
+struct Finished(Arc<AtomicBool>);
+
+impl Drop for Finished {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
 }
 
 /// **@summary** - The worker's job is link to a queue, it processes the task from the queue. It is an infinite loop
@@ -65,6 +187,9 @@ impl WorkerTrait for Worker {
 /// 
 /// **@param** event_handler: impl EventHandlerTrait - The event handler to process the events
 /// 
+/// **@param** options: WorkerOptions - What to do with the results nobody waits for anymore, how many tasks to
+/// apply at once, the misbehaviours hitting them and the runtime to sleep on
+/// 
 /// **@returns** - This function live until the completion of the program
 /// 
 /// **@note** - This function aims to be used in a separate thread
@@ -72,28 +197,48 @@ async fn job(
     store: impl StoreTrait + Sync + 'static,
     task_queue: impl TaskQueueTrait + Sync,
     event_handler: impl EventHandlerTrait,
+    options: WorkerOptions,
     safe_stop_signal: Arc<Mutex<bool>>,
 ) {
+    let WorkerOptions { policy, batch_size, chaos, runtime } = options;
+    let mut chaos = chaos.map(Schedule::new);
 
     // Infinite loop to process events
     loop {
         // While there is event to process in the queue
         loop {
-            let task = task_queue.get_task();
-
-            if let Some(task) = task {
-                // Use ref (&) to avoid moving the event and keep the ownership
-                let result = event_handler.handle_event(&store, &task.event);
-                // Send the result back to the event channel
-                if task.res_chanel.send(Ok(result)).is_err() {
-                    // The receiver has been dropped
-                    // TODO: Handle channel error
-                    println!("Receiver has been dropped");
-                }
-            } else {
+            // Up to batch_size tasks at once, as many as queued during the last batch under a bursty load
+            let tasks: Vec<Task> = match batch_size {
+                0 | 1 => task_queue.get_task().into_iter().collect(),
+                size => task_queue.get_tasks(size),
+            };
+            if tasks.is_empty() {
                 // if there is no event in the queue, wait for a notification
                 break;
             }
+
+            let mut batch = Vec::with_capacity(tasks.len());
+            for task in tasks {
+                match chaos.as_mut().and_then(Schedule::roll) {
+                    Some(Mischief::Kill(restart_after)) => {
+                        // The task dies with the worker, its sender receiving no answer
+                        log!(Level::Debug, task.event.span() => "Killed by the chaos, restarting in {:?}", restart_after);
+                        drop(task);
+                        runtime.sleep(restart_after).await;
+                        log!(Level::Debug, "Restarted after the chaos");
+                    },
+                    Some(Mischief::Fail) => {
+                        log!(Level::Debug, task.event.span() => "Failed by the chaos");
+                        let _ = task.res_chanel.send(Err(StoreError::Injected));
+                    },
+                    Some(Mischief::Delay(delay)) => {
+                        runtime.sleep(delay).await;
+                        batch.push(task);
+                    },
+                    None => batch.push(task),
+                }
+            }
+            process_batch(&store, &event_handler, &policy, batch);
             if *safe_stop_signal.lock().await {
                 // If the signal is set to false, stop the worker
                 return;
@@ -108,21 +253,100 @@ async fn job(
     }
 }
 
+/// **@summary** - Apply the event of a task on the store and send its result, unless it expired
+///
+/// **@note** - The step of the job for each task, also run inline by the InlineBlackboard
+pub(crate) fn process(store: &(impl StoreTrait + 'static), event_handler: &impl EventHandlerTrait, policy: &DroppedResultPolicy, task: Task) {
+    let Some((task, handling)) = admit(task) else { return };
+    // Use ref (&) to avoid moving the event and keep the ownership, the messages of the store being in its span
+    let result = task.event.span().in_scope(|| event_handler.handle_event(store, &task.event));
+    answer(store, policy, task, handling, result);
+}
+
+/// **@summary** - Apply the events of the tasks on the store at once and send their results, unless they expired
+///
+/// **@note** - A single task is processed alone
+fn process_batch(store: &(impl StoreTrait + 'static), event_handler: &impl EventHandlerTrait, policy: &DroppedResultPolicy, tasks: Vec<Task>) {
+    if tasks.len() <= 1 {
+        tasks.into_iter().for_each(|task| process(store, event_handler, policy, task));
+        return;
+    }
+    let (tasks, spans): (Vec<Task>, Vec<Option<ActiveSpan>>) = tasks.into_iter().filter_map(admit).unzip();
+    let results = event_handler.handle_events(store, &tasks);
+    for ((task, handling), result) in tasks.into_iter().zip(spans).zip(results) {
+        answer(store, policy, task, handling, result);
+    }
+}
+
+// Answer a task whose event expired, else start the span of its handling
+fn admit(task: Task) -> Option<(Task, Option<ActiveSpan>)> {
+    log!(Level::Trace, task.event.span() => "Dequeued");
+    if task.event.is_expired() {
+        // The sender gave up on the event, which must not change the store anymore
+        log!(Level::Debug, task.event.span() => "Expired before being handled");
+        let _ = task.res_chanel.send(Err(StoreError::Expired));
+        return None;
+    }
+    if let (Some(trace), Some(queued)) = (&task.event.trace, task.queued) {
+        ActiveSpan::start_at("queue wait", Some(trace), queued).end();
+    }
+    let handling = task.event.trace.as_ref().map(|trace| ActiveSpan::start("handle", Some(trace)));
+    Some((task, handling))
+}
+
+// Send the result of the event of a task back, or apply the policy if nobody waits for it anymore
+fn answer(store: &impl StoreTrait, policy: &DroppedResultPolicy, task: Task, handling: Option<ActiveSpan>, result: bool) {
+    metrics::event_processed(&task.event.action);
+    if let Some(span) = handling {
+        span.with_attribute("bacht.action", format!("{:?}", task.event.action)).with_attribute("bacht.result", result).end();
+    }
+    // Send the result back to the event channel
+    if task.res_chanel.send(Ok(result)).is_err() {
+        dropped_result(store, policy, task.event, result);
+    }
+}
+
+// Apply the policy to the result of an event whose receiver has been dropped
+fn dropped_result(store: &impl StoreTrait, policy: &DroppedResultPolicy, event: Event, result: bool) {
+    match policy {
+        DroppedResultPolicy::Drop => log!(Level::Debug, event.span() => "Receiver has been dropped"),
+        DroppedResultPolicy::DeadLetter(dead_letters) => {
+            log!(Level::Debug, event.span() => "Receiver has been dropped, kept as a dead letter");
+            dead_letters.push(DeadLetter { event, result });
+        },
+        DroppedResultPolicy::RollBack => {
+            // Only the actions executed changed the store
+            match (&event.action, result) {
+                (Action::Tell(token), true) => { store.get(*token); },
+                (Action::Get(token), true) => { store.tell(*token); },
+                _ => {},
+            }
+            log!(Level::Debug, event.span() => "Receiver has been dropped, rolled back");
+        },
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::model::event::Event;
     use crate::model::action::Action::{Tell, Get, Ask};
-    use crate::blackboard::task_queue::MockTaskQueueTrait;
+    use crate::blackboard::task_queue::{MockTaskQueueTrait, TaskQueue, TaskQueueTrait};
     
     use std::time::Duration;
     use std::future::pending;
     use tokio::time::{sleep, timeout};
     use crate::blackboard::event_handler::{EventHandler, MockEventHandlerTrait};
-    use crate::model::task::{Task, TaskError};
-    use crate::blackboard::store::MockStoreTrait;
+    use crate::model::task::Task;
+    use crate::error::StoreError;
+    use crate::blackboard::store::{MockStoreTrait, Store};
+    use crate::runtime::tests::ThreadRuntime;
+    use std::sync::atomic::Ordering::SeqCst;
 
-    async fn check_result(rx: tokio::sync::oneshot::Receiver<Result<bool, TaskError>>, should_timeout: bool, should_channel_error: bool, should_worker_error: bool, should_positive_result: bool) {
+    async fn check_result(rx: tokio::sync::oneshot::Receiver<Result<bool, StoreError>>, should_timeout: bool, should_channel_error: bool, should_worker_error: bool, should_positive_result: bool) {
         
         match timeout(Duration::from_secs(5), rx).await {
             Ok(result_channel) => {
@@ -168,7 +392,7 @@ mod tests {
 
         check_result(rx, false, false, false, true).await;
 
-        assert!(!worker.join_handler.is_finished(), "Worker should not be finished. Error message:\n {:?}", worker.join_handler.await.unwrap_err().to_string());
+        assert!(worker.is_alive(), "Worker should not be finished");
     }
     
     #[tokio::test]
@@ -211,7 +435,7 @@ mod tests {
             }
         }
 
-        assert!(!worker.join_handler.is_finished(), "Worker should not be finished. Error message:\n {:?}", worker.join_handler.await.unwrap_err().to_string());
+        assert!(worker.is_alive(), "Worker should not be finished");
     }
     
     #[tokio::test]
@@ -233,7 +457,7 @@ mod tests {
 
         check_result(rx, false, false, false, true).await;
 
-        assert!(!worker.join_handler.is_finished(), "Worker should not be finished. Error message:\n {:?}", worker.join_handler.await.unwrap_err().to_string());
+        assert!(worker.is_alive(), "Worker should not be finished");
     }
     
     #[tokio::test]
@@ -254,10 +478,10 @@ mod tests {
         
         check_result(rx, false, false, false, false).await;
 
-        assert!(!worker.join_handler.is_finished(), "Worker should not be finished. Error message:\n {:?}", worker.join_handler.await.unwrap_err().to_string());
+        assert!(worker.is_alive(), "Worker should not be finished");
     }
     
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn worker_should_handle_empty_queue() {
         let mut mock_queue = MockTaskQueueTrait::default();
         mock_queue.expect_get_task().times(1).return_once(move || None);
@@ -273,9 +497,152 @@ mod tests {
         // await for the worker to process
         sleep(Duration::from_secs(1)).await;
         
-        assert!(!worker.join_handler.is_finished(), "Worker should not be finished. Error message:\n {:?}", worker.join_handler.await.unwrap_err().to_string());
+        assert!(worker.is_alive(), "Worker should not be finished");
     }
     
+    #[tokio::test]
+    async fn worker_should_report_its_liveness() {
+        let mut mock_queue = MockTaskQueueTrait::default();
+        mock_queue.expect_get_task().returning(|| None);
+        mock_queue.expect_notify().returning(|| Box::pin(sleep(Duration::from_millis(50))));
+        mock_queue.expect_cancel_notification().returning(|| ());
+
+        let worker = Worker::new(MockStoreTrait::default(), mock_queue, MockEventHandlerTrait::default());
+        assert!(worker.is_alive(), "Worker should be alive once started");
+
+        worker.safe_stop().await;
+        assert!(timeout(Duration::from_secs(5), async { while worker.is_alive() { sleep(Duration::from_millis(10)).await } }).await.is_ok(), "Worker should not be alive once stopped");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn worker_should_answer_the_expired_events_without_applying_them() {
+        let (task, rx) = Task::new(Event::new(Tell("token".into())).with_ttl(Duration::from_millis(10)));
+        sleep(Duration::from_millis(20)).await;
+
+        let mut mock_queue = MockTaskQueueTrait::default();
+        mock_queue.expect_get_task().times(1).return_once(move || Some(task));
+        mock_queue.expect_get_task().returning(|| None);
+        mock_queue.expect_notify().returning(|| Box::pin(pending()));
+        let mut mock_store = MockStoreTrait::default();
+        mock_store.expect_tell().times(0);
+
+        let _worker = Worker::new(mock_store, mock_queue, EventHandler::new());
+        assert_eq!(timeout(Duration::from_secs(5), rx).await.unwrap().unwrap(), Err(StoreError::Expired));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn worker_should_misbehave_on_the_tasks_hit_by_the_chaos() {
+        let store = Store::new();
+        let (killed, lost) = Task::new(Event::new(Tell("killed".into())));
+        let (delayed, late) = Task::new(Event::new(Tell("delayed".into())));
+        let mut mock_queue = MockTaskQueueTrait::default();
+        mock_queue.expect_get_task().times(1).return_once(move || Some(killed));
+        mock_queue.expect_get_task().times(1).return_once(move || Some(delayed));
+        mock_queue.expect_get_task().returning(|| None);
+        mock_queue.expect_notify().returning(|| Box::pin(pending()));
+
+        let chaos = Chaos { kill: 1.0, restart_after: Duration::from_secs(10), ..Chaos::default() };
+        let worker = Worker::new_with_options(store.clone(), mock_queue, EventHandler::new(), WorkerOptions { chaos: Some(chaos), ..WorkerOptions::default() });
+        assert!(timeout(Duration::from_secs(1), lost).await.unwrap().is_err(), "The task of a killed worker should not be answered");
+        assert!(timeout(Duration::from_secs(5), late).await.is_err(), "The worker should be down until its restart");
+        assert!(worker.is_alive());
+        assert!(!store.ask("killed".into()));
+
+        let (task, rx) = Task::new(Event::new(Tell("token".into())));
+        let mut mock_queue = MockTaskQueueTrait::default();
+        mock_queue.expect_get_task().times(1).return_once(move || Some(task));
+        mock_queue.expect_get_task().returning(|| None);
+        mock_queue.expect_notify().returning(|| Box::pin(pending()));
+        let chaos = Chaos { delay: 1.0, max_delay: Duration::from_secs(1), ..Chaos::default() };
+        let _worker = Worker::new_with_options(store.clone(), mock_queue, EventHandler::new(), WorkerOptions { chaos: Some(chaos), ..WorkerOptions::default() });
+        assert_eq!(timeout(Duration::from_secs(2), rx).await.unwrap().unwrap(), Ok(true), "A delayed task should still be applied");
+    }
+
+    #[test]
+    fn worker_should_be_spawned_and_sleep_on_the_runtime_of_its_options() {
+        let runtime = ThreadRuntime::default();
+        let (spawned, slept) = (runtime.spawned.clone(), runtime.slept.clone());
+        let chaos = Chaos { delay: 1.0, max_delay: Duration::from_millis(1), ..Chaos::default() };
+        let task_queue = TaskQueue::new();
+        // Outside of any tokio runtime
+        let options = WorkerOptions { chaos: Some(chaos), runtime: RuntimeHandle::new(runtime), ..WorkerOptions::default() };
+        let worker = Worker::new_with_options(Store::new(), task_queue.clone(), EventHandler::new(), options);
+
+        let rx = task_queue.add_event_to_queue(Event::new(Tell("token".into())));
+        let answer = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap()
+            .block_on(async { timeout(Duration::from_secs(5), rx).await });
+        assert_eq!(answer.unwrap().unwrap(), Ok(true), "The delayed task should be applied");
+        assert!(worker.is_alive());
+        assert_eq!((spawned.load(SeqCst), slept.load(SeqCst)), (1, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn worker_should_apply_the_queued_tasks_in_batches() {
+        let (store, task_queue) = (Store::new(), TaskQueue::new());
+        let expired = task_queue.add_event_to_queue(Event::new(Tell("expired".into())).with_ttl(Duration::from_millis(10)));
+        sleep(Duration::from_millis(20)).await;
+        let pending: Vec<_> = [Tell("token".into()), Ask("token".into()), Get("token".into()), Get("token".into()), Tell("other".into())]
+            .into_iter()
+            .map(|action| task_queue.add_event_to_queue(Event::new(action)))
+            .collect();
+
+        let mut mock_store = MockStoreTrait::default();
+        let shared = store.clone();
+        mock_store.expect_apply_batch().times(2).returning(move |actions| shared.apply_batch(actions));
+        let options = WorkerOptions { batch_size: 3, ..WorkerOptions::default() };
+        let _worker = Worker::new_with_options(mock_store, task_queue.clone(), EventHandler::new(), options);
+
+        assert_eq!(timeout(Duration::from_secs(5), expired).await.unwrap().unwrap(), Err(StoreError::Expired));
+        let mut results = Vec::new();
+        for result in pending {
+            results.push(timeout(Duration::from_secs(5), result).await.unwrap().unwrap().unwrap());
+        }
+        assert_eq!(results, [true, true, true, false, true]);
+        assert_eq!(store.snapshot(), [("other".into(), 1)]);
+    }
+
+    // Run a worker applying the policy to an event whose receiver is dropped, until the ask following it is answered
+    async fn drop_receiver(store: Store, policy: DroppedResultPolicy, event: Event) {
+        let (dropped, rx) = Task::new(event);
+        drop(rx);
+        let (ask, answer) = Task::new(Event::new(Ask("other".into())));
+        let mut mock_queue = MockTaskQueueTrait::default();
+        mock_queue.expect_get_task().times(1).return_once(move || Some(dropped));
+        mock_queue.expect_get_task().times(1).return_once(move || Some(ask));
+        mock_queue.expect_get_task().returning(|| None);
+        mock_queue.expect_notify().returning(|| Box::pin(pending()));
+
+        let _worker = Worker::new_with_policy(store, mock_queue, EventHandler::new(), policy);
+        check_result(answer, false, false, false, false).await;
+    }
+
+    #[tokio::test]
+    async fn worker_should_log_and_drop_the_result_of_a_dropped_receiver() {
+        let store = Store::new();
+        drop_receiver(store.clone(), "drop".parse().unwrap(), Event::new(Tell("token".into()))).await;
+        assert!(store.ask("token".into()), "The tell should stay applied");
+    }
+
+    #[tokio::test]
+    async fn worker_should_keep_the_result_of_a_dropped_receiver_as_a_dead_letter() {
+        let (store, dead_letters) = (Store::new(), DeadLetters::new());
+        drop_receiver(store.clone(), DroppedResultPolicy::DeadLetter(dead_letters.clone()), Event::new(Get("token".into()))).await;
+        assert_eq!(dead_letters.drain(), [DeadLetter { event: Event::new(Get("token".into())), result: false }]);
+        assert_eq!("lost".parse::<DroppedResultPolicy>().unwrap_err(), "Unknown policy lost, expected drop, dead_letter or rollback");
+    }
+
+    #[tokio::test]
+    async fn worker_should_roll_back_the_action_of_a_dropped_receiver() {
+        let store = Store::new();
+        drop_receiver(store.clone(), DroppedResultPolicy::RollBack, Event::new(Tell("token".into()))).await;
+        assert!(!store.ask("token".into()), "The tell should be undone");
+        store.tell("token".into());
+        drop_receiver(store.clone(), DroppedResultPolicy::RollBack, Event::new(Get("token".into()))).await;
+        assert_eq!(store.snapshot(), [("token".into(), 1)], "The get should be undone");
+        drop_receiver(store.clone(), DroppedResultPolicy::RollBack, Event::new(Get("absent".into()))).await;
+        assert_eq!(store.snapshot(), [("token".into(), 1)], "A get not executed should not be undone");
+    }
+
     // TODO: Error handling when implemented in handler
     /* #[tokio::test]
     async fn worker_should_transmit_error_of_handler() {
@@ -316,8 +683,8 @@ mod tests {
         listener1.await;
         listener2.await;
 
-        assert!(!worker1.join_handler.is_finished(), "Worker1 should not be finished. Error message:\n {:?}", worker1.join_handler.await.unwrap_err().to_string());
-        assert!(!worker2.join_handler.is_finished(), "Worker1 should not be finished. Error message:\n {:?}", worker2.join_handler.await.unwrap_err().to_string());
+        assert!(worker1.is_alive(), "Worker1 should not be finished");
+        assert!(worker2.is_alive(), "Worker1 should not be finished");
     }
     
 }
\ No newline at end of file
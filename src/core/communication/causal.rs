@@ -0,0 +1,230 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use crate::blackboard::BlackboardTrait;
+use crate::communication::socket_client::SocketClient;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::clock::{Stamp, VectorClock};
+use crate::model::event::Event;
+use crate::model::health::Health;
+use crate::model::change::StoreChange;
+use tokio::sync::broadcast;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
+use crate::model::token::TokenId;
+
+// The streams of the mutations to broadcast, one per peer
+type Peers = Arc<Mutex<Vec<UnboundedSender<(Stamp, Action)>>>>;
+
+/// @summary - The CausalBlackboard keeps copies of a store on several nodes, each node accepting the writes of its agents.
+///
+/// Every mutation applied on the local store (a tell, or a successful get) is counted in the vector clock of the node,
+/// and broadcast to the peers with the clock: its stamp. A peer applies a broadcast mutation only once it applied every
+/// mutation that happened before it, e.g. the tell of a token is applied before the get that consumed it, whatever the
+/// order they arrive in.
+///
+/// @note - Concurrent mutations are applied in any order, two nodes may consume the last occurrence of a token at the same time
+pub struct CausalBlackboard<B: BlackboardTrait> {
+    local: B,
+    node: Box<str>,
+    // The mutations applied on the local store, by node
+    delivered: Arc<watch::Sender<VectorClock>>,
+    // Serializes the mutations, so that the clock counts them in the order they were applied
+    mutations: Arc<tokio::sync::Mutex<()>>,
+    peers: Peers,
+}
+
+impl<B: BlackboardTrait> CausalBlackboard<B> {
+
+    /// @param node - The name of this node in the vector clocks, unique among the peers
+    pub fn new_with(local: B, node: &str) -> Self {
+        Self {
+            local,
+            node: node.into(),
+            delivered: Arc::new(watch::Sender::new(VectorClock::new())),
+            mutations: Arc::new(tokio::sync::Mutex::new(())),
+            peers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// @returns - The mutations applied on the local store so far, by node
+    pub fn clock(&self) -> VectorClock {
+        self.delivered.borrow().clone()
+    }
+
+    /// @summary - Broadcast the mutations applied from now on to a peer
+    ///
+    /// @param peer - The connection to the listener of the peer, presenting the key of the peers
+    ///
+    /// @returns - The handle of the broadcasting task
+    ///
+    /// @note - A peer that can't be reached anymore is detached: it missed mutations, the later ones would wait for them forever
+    pub fn add_peer(&self, peer: SocketClient) -> JoinHandle<()> {
+        let (tx, mut rx) = unbounded_channel::<(Stamp, Action)>();
+        self.peers.lock().unwrap().push(tx);
+        tokio::spawn(async move {
+            while let Some((stamp, action)) = rx.recv().await {
+                if let Err(e) = peer.causal(stamp, action).await {
+                    log!(Level::Error, "Causal peer {} detached: {:?}", peer.addr(), e);
+                    return;
+                }
+            }
+        })
+    }
+
+    /// @summary - Apply an event of this node, and broadcast it if it mutated the store
+    async fn apply_local(&self, event: Event) -> Result<bool, StoreError> {
+        if matches!(event.action, Action::Ask(_) | Action::Nask(_)) {
+            return self.local.send_event(event).await;
+        }
+        let _mutation = self.mutations.lock().await;
+        let action = event.action.clone();
+        let result = self.local.send_event(event).await;
+        if let Ok(true) = result {
+            let mut clock = VectorClock::new();
+            self.delivered.send_modify(|delivered| {
+                delivered.increment(&self.node);
+                clock = delivered.clone();
+            });
+            let stamp = Stamp { node: self.node.clone(), clock };
+            self.peers.lock().unwrap().retain(|peer| peer.send((stamp.clone(), action.clone())).is_ok());
+        }
+        result
+    }
+
+    /// @summary - Apply a mutation broadcast by a peer, once the mutations it depends on were applied
+    ///
+    /// @note - A mutation applied already is not applied again
+    async fn apply_causal(&self, event: Event, stamp: Stamp) -> Result<bool, StoreError> {
+        let mut watcher = self.delivered.subscribe();
+        loop {
+            // The sender is owned by this blackboard, the watch can't be closed
+            let _ = watcher.wait_for(|delivered| stamp.is_deliverable(delivered) != Some(false)).await;
+            let _mutation = self.mutations.lock().await;
+            let deliverable = stamp.is_deliverable(&self.delivered.borrow());
+            match deliverable {
+                None => return Ok(true),
+                Some(false) => continue,
+                Some(true) => {
+                    // Counted even if it fails, e.g. a get of a diverged store, not to hold the later mutations back
+                    let result = self.local.send_event(event).await;
+                    self.delivered.send_modify(|delivered| delivered.set(&stamp.node, stamp.clock.get(&stamp.node)));
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send> BlackboardTrait for CausalBlackboard<B> {
+
+    fn new() -> Self {
+        Self::new_with(B::new(), "local")
+    }
+
+    async fn send_event(&self, mut event: Event) -> Result<bool, StoreError> {
+        match event.stamp.take() {
+            Some(stamp) => self.apply_causal(event, stamp).await,
+            None => self.apply_local(event).await,
+        }
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        self.local.admin(command).await
+    }
+
+    fn health(&self) -> Health {
+        self.local.health()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.local.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            node: self.node.clone(),
+            delivered: self.delivered.clone(),
+            mutations: self.mutations.clone(),
+            peers: self.peers.clone(),
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::time::{sleep, timeout};
+    use super::*;
+    use crate::blackboard::create_blackboard;
+    use crate::communication::socket_client::ReconnectPolicy;
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+    use crate::communication::transport::MemoryTransport;
+
+    fn stamp(node: &str, entries: &[(&str, u64)]) -> Stamp {
+        let mut clock = VectorClock::new();
+        entries.iter().for_each(|(node, count)| clock.set(node, *count));
+        Stamp { node: node.into(), clock }
+    }
+
+    #[tokio::test]
+    async fn causal_blackboard_should_delay_a_get_until_the_tell_it_depends_on() {
+        let blackboard = CausalBlackboard::new_with(create_blackboard(), "c");
+        // b consumed the token told by a, but the tell of a is late
+        let consumer = blackboard.clone();
+        let get = tokio::spawn(async move { consumer.send_event(Event::causal(Action::Get("token".into()), stamp("b", &[("a", 1), ("b", 1)]))).await });
+        sleep(Duration::from_millis(100)).await;
+        assert!(!get.is_finished(), "The get should wait for the tell");
+
+        assert!(blackboard.send_event(Event::causal(Action::Tell("token".into()), stamp("a", &[("a", 1)]))).await.unwrap());
+        assert!(timeout(Duration::from_secs(2), get).await.unwrap().unwrap().unwrap(), "The get should be applied after the tell");
+        assert!(blackboard.nask("token".into()).await.unwrap());
+        assert_eq!(blackboard.clock(), stamp("", &[("a", 1), ("b", 1)]).clock);
+
+        assert!(blackboard.send_event(Event::causal(Action::Tell("token".into()), stamp("a", &[("a", 1)]))).await.unwrap());
+        assert!(blackboard.nask("token".into()).await.unwrap(), "A mutation should not be applied twice");
+    }
+
+    #[tokio::test]
+    async fn causal_blackboard_should_broadcast_its_mutations_with_their_stamp() {
+        let transport = Arc::new(MemoryTransport::new());
+        let remote = CausalBlackboard::new_with(create_blackboard(), "b");
+        let listener = SocketListener::new(remote.clone(), None).with_transport(transport.clone(), "b").with_peer_key("cluster");
+        tokio::spawn(async move { listener.listen().await });
+
+        let local = CausalBlackboard::new_with(create_blackboard(), "a");
+        local.add_peer(SocketClient::connect_with(transport, "b", ReconnectPolicy::default()).await.unwrap().with_peer_key("cluster"));
+        assert!(local.tell("token".into()).await.unwrap());
+        assert!(local.ask("token".into()).await.unwrap());
+        assert!(local.get("token".into()).await.unwrap());
+        assert!(!local.get("token".into()).await.unwrap(), "A failed get is not a mutation");
+        assert_eq!(local.clock(), stamp("", &[("a", 2)]).clock);
+
+        assert!(timeout(Duration::from_secs(2), async { while remote.clock() != local.clock() { sleep(Duration::from_millis(10)).await } }).await.is_ok(),
+            "The peer should apply both mutations");
+        assert!(remote.nask("token".into()).await.unwrap());
+    }
+}
@@ -0,0 +1,114 @@
+/// Frames whose payload is smaller are never compressed, the gain would not pay for the work
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+const NONE_CODE: u8 = 0x00;
+const LZ4_CODE: u8 = 0x01;
+
+/// @summary - The codec compressing the frames of a connection, negotiated by the client after its hello.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    /// The LZ4 block format, fast enough to be used on every big frame
+    Lz4,
+}
+
+impl Compression {
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Compression::None => NONE_CODE,
+            Compression::Lz4 => LZ4_CODE,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            NONE_CODE => Some(Compression::None),
+            LZ4_CODE => Some(Compression::Lz4),
+            _ => None,
+        }
+    }
+
+    /// @summary - Compress a payload if it is worth it
+    ///
+    /// @returns - The compressed payload, or None if the payload is below the threshold or does not shrink
+    pub fn compress(&self, payload: &[u8]) -> Option<Vec<u8>> {
+        if payload.len() < COMPRESSION_THRESHOLD {
+            return None;
+        }
+        let compressed = match self {
+            Compression::None => return None,
+            Compression::Lz4 => lz4_flex::block::compress(payload),
+        };
+        (compressed.len() < payload.len()).then_some(compressed)
+    }
+
+    /// @summary - Decompress a payload
+    ///
+    /// @param length - The length of the original payload
+    ///
+    /// @returns - The original payload, or an error if the compressed payload is corrupted
+    pub fn decompress(&self, compressed: &[u8], length: usize) -> Result<Vec<u8>, String> {
+        match self {
+            Compression::None => Err("The payload is not compressed".into()),
+            Compression::Lz4 => lz4_decompress(compressed, length),
+        }
+    }
+}
+
+/// @summary - Decompress an LZ4 block, refusing any block that would not yield exactly `length` bytes
+fn lz4_decompress(input: &[u8], length: usize) -> Result<Vec<u8>, String> {
+    // Never writes past length, however long the block claims to be
+    let output = lz4_flex::block::decompress(input, length).map_err(|e| e.to_string())?;
+    match output.len() == length {
+        true => Ok(output),
+        false => Err("Block shorter than announced".into()),
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(payload: &[u8]) -> Vec<u8> {
+        let compressed = lz4_flex::block::compress(payload);
+        assert_eq!(lz4_decompress(&compressed, payload.len()).unwrap(), payload);
+        compressed
+    }
+
+    #[test]
+    fn lz4_should_restore_the_original_payload() {
+        round_trip(b"");
+        round_trip(b"tell(token)");
+        round_trip(&[7; 100_000]);
+        let text: Vec<u8> = (0..5_000).flat_map(|i| format!("tell(token{});", i % 300).into_bytes()).collect();
+        let compressed = round_trip(&text);
+        assert!(compressed.len() < text.len() / 3, "Repetitive payloads should shrink");
+        let noise: Vec<u8> = (0..70_000).map(|_| rand::random::<u8>()).collect();
+        round_trip(&noise);
+    }
+
+    #[test]
+    fn lz4_should_refuse_corrupted_blocks() {
+        let payload = [b'a'; 2_000];
+        let compressed = lz4_flex::block::compress(&payload);
+        assert!(lz4_decompress(&compressed, payload.len() + 1).is_err());
+        assert!(lz4_decompress(&compressed, payload.len() - 1).is_err());
+        assert!(lz4_decompress(&compressed[..compressed.len() - 1], payload.len()).is_err());
+        // A match pointing before the beginning of the block
+        assert!(lz4_decompress(&[0x04, 0x01, 0x00], 8).is_err());
+    }
+
+    #[test]
+    fn compression_should_skip_small_or_incompressible_payloads() {
+        assert_eq!(Compression::Lz4.compress(&[0; COMPRESSION_THRESHOLD - 1]), None);
+        assert_eq!(Compression::None.compress(&[0; 2 * COMPRESSION_THRESHOLD]), None);
+        let noise: Vec<u8> = (0..2 * COMPRESSION_THRESHOLD).map(|_| rand::random::<u8>()).collect();
+        assert_eq!(Compression::Lz4.compress(&noise), None);
+        assert!(Compression::Lz4.compress(&[0; 2 * COMPRESSION_THRESHOLD]).is_some());
+    }
+}
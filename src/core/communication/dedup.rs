@@ -0,0 +1,177 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::OnceCell;
+use crate::communication::frame::Frame;
+
+const DEFAULT_WINDOW: usize = 1024;
+const DEFAULT_MAX_SESSIONS: usize = 1024;
+
+/// @summary - Who a session belongs to, from the key its connection presented: the responses cached for the session of
+/// one identity are never served to another
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Identity {
+    /// Presented no key, or a key refused
+    #[default]
+    Anonymous,
+    Admin,
+    /// Presented the key of the peers
+    Peer,
+    /// Presented the key of a tenant, by name
+    Tenant(Box<str>),
+}
+
+/// The responses of the last requests of one client session, by sequence number
+struct Session {
+    responses: HashMap<u64, Arc<OnceCell<Frame>>>,
+    order: VecDeque<u64>,
+    last_seen: Instant,
+}
+
+/// @summary - The DedupWindow makes the requests resent by the clients apply only once.
+///
+/// A request is identified by the identity of its client, its session and its sequence number (its correlation id).
+/// A session belongs to the identity that claimed it first, until it is forgotten. The response
+/// of the last `window` requests of each session is kept: a request seen again, e.g. resent after a reconnection,
+/// is answered with the response of the first one instead of being applied twice. A duplicate received while the
+/// first one is still applied waits for its response.
+///
+/// It can be cloned in order to share the same window between the connections of a listener.
+#[derive(Clone)]
+pub struct DedupWindow {
+    sessions: Arc<Mutex<HashMap<(Identity, u64), Session>>>,
+    window: usize,
+    max_sessions: usize,
+}
+
+impl Default for DedupWindow {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW, DEFAULT_MAX_SESSIONS)
+    }
+}
+
+impl DedupWindow {
+
+    /// @param window - The number of responses kept per session, it must cover the requests a client may resend
+    ///
+    /// @param max_sessions - The number of sessions tracked, the least recently seen one is forgotten first
+    pub fn new(window: usize, max_sessions: usize) -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            window,
+            max_sessions,
+        }
+    }
+
+    /// @summary - Claim a session for an identity, e.g. when a client names its session
+    ///
+    /// @returns - false if the session belongs to another identity, the client must not use it
+    pub fn claim(&self, owner: &Identity, session: u64) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.keys().any(|(other, id)| *id == session && other != owner) {
+            return false;
+        }
+        self.session(&mut sessions, (owner.clone(), session));
+        true
+    }
+
+    /// @summary - Apply a request, unless it was already applied
+    ///
+    /// @param session - The session of the client and the identity it claimed it for
+    ///
+    /// @param sequence - The sequence number of the request in the session
+    ///
+    /// @param apply - Applies the request, only polled for the first occurrence of the request
+    ///
+    /// @returns - The response to the first occurrence of the request
+    pub async fn apply<F: Future<Output = Frame>>(&self, session: &(Identity, u64), sequence: u64, apply: F) -> Frame {
+        let slot = self.slot(session, sequence);
+        slot.get_or_init(|| apply).await.clone()
+    }
+
+    fn slot(&self, session: &(Identity, u64), sequence: u64) -> Arc<OnceCell<Frame>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = self.session(&mut sessions, session.clone());
+        if let Some(slot) = session.responses.get(&sequence) {
+            return slot.clone();
+        }
+        if session.order.len() >= self.window {
+            session.order.pop_front().map(|forgotten| session.responses.remove(&forgotten));
+        }
+        let slot = Arc::new(OnceCell::new());
+        session.responses.insert(sequence, slot.clone());
+        session.order.push_back(sequence);
+        slot
+    }
+
+    // The session seen now, tracked from now on if it was not
+    fn session<'a>(&self, sessions: &'a mut HashMap<(Identity, u64), Session>, key: (Identity, u64)) -> &'a mut Session {
+        if !sessions.contains_key(&key) && sessions.len() >= self.max_sessions {
+            let oldest = sessions.iter().min_by_key(|(_, session)| session.last_seen).map(|(key, _)| key.clone());
+            oldest.map(|oldest| sessions.remove(&oldest));
+        }
+        let session = sessions.entry(key).or_insert_with(|| Session {
+            responses: HashMap::new(),
+            order: VecDeque::new(),
+            last_seen: Instant::now(),
+        });
+        session.last_seen = Instant::now();
+        session
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use super::*;
+
+    async fn count(applied: &AtomicU32) -> Frame {
+        Frame::Response(applied.fetch_add(1, Ordering::SeqCst) == 0)
+    }
+
+    #[tokio::test]
+    async fn dedup_should_apply_a_request_once_per_session() {
+        let dedup = DedupWindow::default();
+        let applied = AtomicU32::new(0);
+        assert_eq!(dedup.apply(&(Identity::Anonymous, 1), 7, count(&applied)).await, Frame::Response(true));
+        assert_eq!(dedup.apply(&(Identity::Anonymous, 1), 7, count(&applied)).await, Frame::Response(true), "The duplicate should get the first response");
+        assert_eq!(applied.load(Ordering::SeqCst), 1);
+
+        dedup.apply(&(Identity::Anonymous, 2), 7, count(&applied)).await;
+        dedup.apply(&(Identity::Anonymous, 1), 8, count(&applied)).await;
+        assert_eq!(applied.load(Ordering::SeqCst), 3, "Other sessions and sequences should be applied");
+    }
+
+    #[tokio::test]
+    async fn dedup_should_forget_requests_out_of_the_window() {
+        let dedup = DedupWindow::new(2, 1);
+        let applied = AtomicU32::new(0);
+        let (first, second) = ((Identity::Anonymous, 1), (Identity::Anonymous, 2));
+        for sequence in 0..3 {
+            dedup.apply(&first, sequence, count(&applied)).await;
+        }
+        dedup.apply(&first, 0, count(&applied)).await;
+        assert_eq!(applied.load(Ordering::SeqCst), 4, "The oldest request left the window");
+
+        dedup.apply(&second, 0, count(&applied)).await;
+        dedup.apply(&first, 2, count(&applied)).await;
+        assert_eq!(applied.load(Ordering::SeqCst), 6, "The oldest session should be forgotten");
+    }
+
+    #[tokio::test]
+    async fn dedup_should_keep_the_sessions_of_each_identity_apart() {
+        let dedup = DedupWindow::default();
+        let applied = AtomicU32::new(0);
+        let (tenant, other) = (Identity::Tenant("a".into()), Identity::Tenant("b".into()));
+        assert!(dedup.claim(&tenant, 42));
+        assert!(dedup.claim(&tenant, 42), "The owner should claim its session again, e.g. after a reconnection");
+        assert!(!dedup.claim(&other, 42) && !dedup.claim(&Identity::Anonymous, 42));
+        assert_eq!(dedup.apply(&(tenant.clone(), 42), 7, count(&applied)).await, Frame::Response(true));
+        assert_eq!(dedup.apply(&(other, 42), 7, count(&applied)).await, Frame::Response(false), "Another identity should never get the cached response");
+        assert_eq!(applied.load(Ordering::SeqCst), 2);
+    }
+}
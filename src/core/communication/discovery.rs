@@ -0,0 +1,207 @@
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+use std::time::Duration;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use crate::communication::peers::PeerTable;
+use crate::log;
+use crate::log::Level;
+use crate::error::TransportError;
+
+const DEFAULT_DISCOVERY_PORT: u16 = 2139;
+const ANNOUNCEMENT_HEADER: &str = "BACHT";
+const ANNOUNCEMENT_VERSION: &str = "1";
+
+/// @summary - Configuration of the LAN discovery.
+///
+/// Every `interval`, the node sends its announcement to `announce_to` (the broadcast address by default),
+/// and it listens on `bind` for the announcements of the other nodes.
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+    /// The name under which the other nodes will know this blackboard
+    pub name: String,
+    /// The port of this blackboard's socket listener, announced to the other nodes
+    pub listener_port: u16,
+    pub bind: SocketAddr,
+    pub announce_to: SocketAddr,
+    pub interval: Duration,
+}
+
+impl DiscoveryConfig {
+    pub fn new(name: &str, listener_port: u16) -> Self {
+        Self {
+            name: name.to_string(),
+            listener_port,
+            bind: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_DISCOVERY_PORT)),
+            announce_to: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::BROADCAST, DEFAULT_DISCOVERY_PORT)),
+            interval: Duration::from_secs(2),
+        }
+    }
+}
+
+/// The content of a discovery datagram: `BACHT 1 <name> <listener port>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    pub name: String,
+    pub listener_port: u16,
+}
+
+impl Announcement {
+
+    pub fn encode(&self) -> Vec<u8> {
+        format!("{} {} {} {}", ANNOUNCEMENT_HEADER, ANNOUNCEMENT_VERSION, self.name, self.listener_port).into_bytes()
+    }
+
+    /// @returns - The announcement, or None if the datagram is not a BachT announcement
+    pub fn decode(datagram: &[u8]) -> Option<Announcement> {
+        let text = std::str::from_utf8(datagram).ok()?;
+        match text.split_whitespace().collect::<Vec<&str>>()[..] {
+            [ANNOUNCEMENT_HEADER, ANNOUNCEMENT_VERSION, name, port] => Some(Announcement {
+                name: name.to_string(),
+                listener_port: port.parse().ok()?,
+            }),
+            _ => None
+        }
+    }
+}
+
+/// @summary - The Discovery announces this blackboard on the local network and fills the peer table with the other announced blackboards.
+pub struct Discovery {
+    config: DiscoveryConfig,
+    socket: Arc<UdpSocket>,
+    peers: PeerTable,
+}
+
+impl Discovery {
+
+    /// @summary - Bind the discovery socket
+    ///
+    /// @note - The socket is bound with SO_REUSEADDR (and SO_REUSEPORT on unix) so that several nodes of the same host can listen for announcements
+    pub async fn bind(config: DiscoveryConfig, peers: PeerTable) -> Result<Self, TransportError> {
+        let socket = Socket::new(Domain::for_address(config.bind), Type::DGRAM, Some(Protocol::UDP))
+            .map_err(|e| TransportError::BindFailed(format!("Failed to create discovery socket: {}", e)))?;
+        socket.set_reuse_address(true).map_err(|e| TransportError::BindFailed(format!("Failed to configure discovery socket: {}", e)))?;
+        #[cfg(unix)]
+        socket.set_reuse_port(true).map_err(|e| TransportError::BindFailed(format!("Failed to configure discovery socket: {}", e)))?;
+        socket.set_broadcast(true).map_err(|e| TransportError::BindFailed(format!("Failed to configure discovery socket: {}", e)))?;
+        socket.set_nonblocking(true).map_err(|e| TransportError::BindFailed(format!("Failed to configure discovery socket: {}", e)))?;
+        socket.bind(&config.bind.into()).map_err(|e| TransportError::BindFailed(format!("Failed to bind discovery socket: {}", e)))?;
+        let socket = UdpSocket::from_std(socket.into()).map_err(|e| TransportError::BindFailed(format!("Failed to bind discovery socket: {}", e)))?;
+        Ok(Self {
+            config,
+            socket: Arc::new(socket),
+            peers,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+        self.socket.local_addr().map_err(|e| TransportError::Io(e.to_string()))
+    }
+
+    /// @summary - Start announcing this node and listening for the others
+    ///
+    /// @returns - The handles of the announcing task and of the listening task
+    pub fn spawn(self) -> (JoinHandle<()>, JoinHandle<()>) {
+        let announcement = Announcement {
+            name: self.config.name.clone(),
+            listener_port: self.config.listener_port,
+        }.encode();
+        let socket = self.socket.clone();
+        let announce_to = self.config.announce_to;
+        let interval = self.config.interval;
+        let announcer = tokio::spawn(async move {
+            loop {
+                if let Err(e) = socket.send_to(&announcement, announce_to).await {
+                    log!(Level::Error, "Failed to announce on {}: {}", announce_to, e);
+                }
+                sleep(interval).await;
+            }
+        });
+        let receiver = tokio::spawn(async move {
+            let mut buffer = [0u8; 512];
+            loop {
+                match self.socket.recv_from(&mut buffer).await {
+                    Ok((n, from)) => self.on_datagram(&buffer[..n], from),
+                    Err(e) => log!(Level::Error, "Failed to receive announcement: {}", e),
+                }
+            }
+        });
+        (announcer, receiver)
+    }
+
+    fn on_datagram(&self, datagram: &[u8], from: SocketAddr) {
+        match Announcement::decode(datagram) {
+            Some(announcement) if announcement.name != self.config.name => {
+                let addr = SocketAddr::new(from.ip(), announcement.listener_port).to_string();
+                if self.peers.get(&announcement.name).is_none() {
+                    log!(Level::Info, "Discovered peer {} at {}", announcement.name, addr);
+                }
+                self.peers.insert(&announcement.name, &addr);
+                self.peers.mark_alive(&announcement.name);
+            },
+            _ => {}
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::timeout;
+
+    fn local_config(name: &str, listener_port: u16) -> DiscoveryConfig {
+        DiscoveryConfig {
+            bind: "127.0.0.1:0".parse().unwrap(),
+            interval: Duration::from_millis(20),
+            ..DiscoveryConfig::new(name, listener_port)
+        }
+    }
+
+    #[test]
+    fn announcement_should_survive_an_encode_decode_round_trip() {
+        let announcement = Announcement { name: "sensors".into(), listener_port: 2138 };
+        assert_eq!(Announcement::decode(&announcement.encode()), Some(announcement));
+    }
+
+    #[test]
+    fn announcement_should_ignore_foreign_datagrams() {
+        assert_eq!(Announcement::decode(b"HELLO 1 sensors 2138"), None);
+        assert_eq!(Announcement::decode(b"BACHT 1 sensors notaport"), None);
+        assert_eq!(Announcement::decode(&[0xff, 0xfe]), None);
+    }
+
+    #[tokio::test]
+    async fn discovery_should_fill_the_peer_table_with_announced_nodes() {
+        let receiver_peers = PeerTable::new();
+        let receiver = Discovery::bind(local_config("actuators", 3000), receiver_peers.clone()).await.unwrap();
+        let receiver_addr = receiver.local_addr().unwrap();
+        receiver.spawn();
+
+        let announcer = Discovery::bind(
+            DiscoveryConfig { announce_to: receiver_addr, ..local_config("sensors", 2138) },
+            PeerTable::new()
+        ).await.unwrap();
+        announcer.spawn();
+
+        timeout(Duration::from_secs(2), async {
+            while receiver_peers.get("sensors").is_none() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("The announced node should be discovered");
+        assert_eq!(receiver_peers.get("sensors").unwrap().addr, "127.0.0.1:2138");
+    }
+
+    #[tokio::test]
+    async fn discovery_should_ignore_its_own_announcements() {
+        let peers = PeerTable::new();
+        let discovery = Discovery::bind(local_config("sensors", 2138), peers.clone()).await.unwrap();
+        let addr = discovery.local_addr().unwrap();
+        discovery.on_datagram(&Announcement { name: "sensors".into(), listener_port: 2138 }.encode(), addr);
+        assert!(peers.is_empty());
+    }
+}
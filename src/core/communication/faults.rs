@@ -0,0 +1,223 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use tokio::time::sleep;
+use crate::communication::compression::Compression;
+use crate::communication::frame::{Frame, FrameError};
+use crate::communication::transport::{Acceptor, BoxFuture, FrameSink, Link, Transport};
+
+/// @summary - The misbehaviours of the network, as the probability of each one to hit a frame sent
+///
+/// A frame is dropped, else duplicated, else held back to be sent after the next frame of the connection, else delayed
+/// up to `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Faults {
+    pub drop: f64,
+    pub duplicate: f64,
+    pub reorder: f64,
+    pub delay: f64,
+    pub max_delay: Duration,
+}
+
+// The fault hitting a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fault {
+    Drop,
+    Duplicate,
+    Reorder,
+    Delay(Duration),
+}
+
+// The faults injected, and the seeded generator deciding which frames they hit
+struct Injector {
+    faults: Faults,
+    rng: StdRng,
+}
+
+impl Injector {
+
+    fn roll(&mut self) -> Option<Fault> {
+        let faults = self.faults;
+        if self.rng.random_bool(faults.drop.clamp(0.0, 1.0)) {
+            Some(Fault::Drop)
+        } else if self.rng.random_bool(faults.duplicate.clamp(0.0, 1.0)) {
+            Some(Fault::Duplicate)
+        } else if self.rng.random_bool(faults.reorder.clamp(0.0, 1.0)) {
+            Some(Fault::Reorder)
+        } else if self.rng.random_bool(faults.delay.clamp(0.0, 1.0)) {
+            Some(Fault::Delay(faults.max_delay.mul_f64(self.rng.random::<f64>())))
+        } else {
+            None
+        }
+    }
+}
+
+/// @summary - The FaultyTransport carries the frames of another transport through an unreliable network, e.g. to test
+/// that the protocols between several blackboards tolerate the frames lost, duplicated, reordered or late.
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use std::sync::Arc;
+/// use bacht::communication::faults::{Faults, FaultyTransport};
+/// use bacht::communication::transport::{MemoryTransport, Transport};
+///
+/// let transport = FaultyTransport::new(Arc::new(MemoryTransport::new())).with_seed(42);
+/// let _bound = transport.bind("board").await.unwrap();
+/// // Every frame sent from now on is lost, until the network heals
+/// transport.set_faults(Faults { drop: 1.0, ..Faults::default() });
+/// transport.heal();
+/// # }
+/// ```
+///
+/// The faults hit the frames sent by both ends of the connections it opens and accepts, and can be changed while they
+/// are open. The clones of a FaultyTransport share the same faults.
+///
+/// @note - A frame held back is only sent after the next frame of its connection: it is lost if none follows
+#[derive(Clone)]
+pub struct FaultyTransport {
+    inner: Arc<dyn Transport>,
+    injector: Arc<Mutex<Injector>>,
+}
+
+impl FaultyTransport {
+
+    /// @param inner - The transport actually carrying the frames, e.g. a MemoryTransport
+    pub fn new(inner: Arc<dyn Transport>) -> Self {
+        let injector = Injector { faults: Faults::default(), rng: StdRng::from_os_rng() };
+        Self { inner, injector: Arc::new(Mutex::new(injector)) }
+    }
+
+    /// @summary - Hit the same frames on each run of a test
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.injector.lock().unwrap().rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// @summary - Inject these faults in the frames sent from now on
+    pub fn set_faults(&self, faults: Faults) {
+        self.injector.lock().unwrap().faults = faults;
+    }
+
+    pub fn faults(&self) -> Faults {
+        self.injector.lock().unwrap().faults
+    }
+
+    /// @summary - Stop injecting faults, the frames held back being sent with the next frame of their connection
+    pub fn heal(&self) {
+        self.set_faults(Faults::default());
+    }
+
+    fn wrap(&self, (source, sink): Link) -> Link {
+        (source, Box::new(FaultySink { inner: sink, injector: self.injector.clone(), held: None }))
+    }
+}
+
+impl Transport for FaultyTransport {
+
+    fn connect<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Link>> {
+        Box::pin(async move { self.inner.connect(addr).await.map(|link| self.wrap(link)) })
+    }
+
+    fn bind<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Box<dyn Acceptor>>> {
+        Box::pin(async move {
+            let inner = self.inner.bind(addr).await?;
+            Ok(Box::new(FaultyAcceptor { inner, transport: self.clone() }) as Box<dyn Acceptor>)
+        })
+    }
+}
+
+struct FaultyAcceptor {
+    inner: Box<dyn Acceptor>,
+    transport: FaultyTransport,
+}
+
+impl Acceptor for FaultyAcceptor {
+    fn accept(&mut self) -> BoxFuture<'_, io::Result<(Link, String)>> {
+        Box::pin(async move {
+            let (link, name) = self.inner.accept().await?;
+            Ok((self.transport.wrap(link), name))
+        })
+    }
+}
+
+struct FaultySink {
+    inner: Box<dyn FrameSink>,
+    injector: Arc<Mutex<Injector>>,
+    // The frame held back, sent after the next one
+    held: Option<(u64, Frame)>,
+}
+
+impl FrameSink for FaultySink {
+
+    fn send<'a>(&'a mut self, id: u64, frame: &'a Frame) -> BoxFuture<'a, Result<(), FrameError>> {
+        let fault = self.injector.lock().unwrap().roll();
+        Box::pin(async move {
+            match fault {
+                Some(Fault::Drop) => return Ok(()),
+                Some(Fault::Reorder) if self.held.is_none() => {
+                    self.held = Some((id, frame.clone()));
+                    return Ok(());
+                },
+                Some(Fault::Delay(delay)) => sleep(delay).await,
+                _ => {},
+            }
+            self.inner.send(id, frame).await?;
+            if fault == Some(Fault::Duplicate) {
+                self.inner.send(id, frame).await?;
+            }
+            if let Some((id, held)) = self.held.take() {
+                self.inner.send(id, &held).await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        self.inner.set_compression(compression);
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{timeout, Instant};
+    use crate::communication::transport::MemoryTransport;
+    use crate::model::action::Action;
+
+    #[tokio::test(start_paused = true)]
+    async fn faulty_transport_should_drop_duplicate_reorder_and_delay_the_frames_on_demand() {
+        let transport = FaultyTransport::new(Arc::new(MemoryTransport::new())).with_seed(7);
+        let mut acceptor = transport.bind("board").await.unwrap();
+        let ((_, mut sink), ((mut source, _), _)) = tokio::join!(
+            async { transport.connect("board").await.unwrap() },
+            async { acceptor.accept().await.unwrap() },
+        );
+        let tell = |token: &str| Frame::Request(Action::Tell(token.into()));
+
+        transport.set_faults(Faults { drop: 1.0, ..Faults::default() });
+        sink.send(1, &tell("lost")).await.unwrap();
+        transport.set_faults(Faults { duplicate: 1.0, ..Faults::default() });
+        sink.send(2, &tell("twice")).await.unwrap();
+        transport.set_faults(Faults { reorder: 1.0, ..Faults::default() });
+        sink.send(3, &tell("late")).await.unwrap();
+        transport.heal();
+        sink.send(4, &tell("early")).await.unwrap();
+        let received: Vec<u64> = [source.recv().await, source.recv().await, source.recv().await, source.recv().await].into_iter()
+            .map(|frame| frame.unwrap().unwrap().0)
+            .collect();
+        assert_eq!(received, [2, 2, 4, 3]);
+
+        transport.set_faults(Faults { delay: 1.0, max_delay: Duration::from_secs(1), ..Faults::default() });
+        let start = Instant::now();
+        sink.send(5, &tell("slow")).await.unwrap();
+        assert_eq!(source.recv().await.unwrap().unwrap().0, 5);
+        assert!(start.elapsed() <= Duration::from_secs(1));
+        assert!(timeout(Duration::from_secs(5), source.recv()).await.is_err(), "The dropped frame should never arrive");
+    }
+}
@@ -0,0 +1,289 @@
+use std::time::Duration;
+use crate::blackboard::BlackboardTrait;
+use crate::communication::lease::DEFAULT_LEASE_DURATION;
+use crate::communication::peers::{PeerClients, PeerStatus, PeerTable};
+use crate::communication::socket_client::SocketClient;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply, PeerInfo};
+use crate::model::event::{Event, Origin};
+use crate::model::health::Health;
+use crate::model::change::StoreChange;
+use tokio::sync::broadcast;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
+use crate::error::TransportError;
+use crate::trace::{ActiveSpan, SpanContext};
+use crate::model::token::TokenId;
+
+/// @summary - The FederatedBlackboard is a blackboard whose unmet queries are forwarded to the peer blackboards.
+///
+/// When an ask or a get fails on the local store, it is forwarded to the alive peers one after the other,
+/// and succeeds as soon as one of them satisfies it (a forwarded get consumes the token on the peer's store).
+/// Tell and nask are always local.
+///
+/// A get is forwarded in two phases: an occurrence is reserved on the peer under a short lease, then the lease is
+/// confirmed. Should the confirmation be lost, the lease expires and the occurrence goes back to the peer's store,
+/// instead of being consumed for a requester that gave up on it and went on to the next peer.
+///
+/// Forwarded events are applied on the local store of the peer only, so that federations with cycles don't loop.
+pub struct FederatedBlackboard<B: BlackboardTrait> {
+    local: B,
+    clients: PeerClients,
+    forwarding: bool,
+    lease_duration: Duration,
+}
+
+impl<B: BlackboardTrait> FederatedBlackboard<B> {
+
+    /// @summary - Federate a local blackboard with the given peers
+    ///
+    /// @param local - The blackboard applying the events on the local store
+    ///
+    /// @param peers - The routing table, dead peers are skipped
+    pub fn new_with(local: B, peers: PeerTable) -> Self {
+        Self {
+            local,
+            clients: PeerClients::new(peers),
+            forwarding: true,
+            lease_duration: DEFAULT_LEASE_DURATION,
+        }
+    }
+
+    /// @summary - How long the occurrences reserved on the peers are held before going back to their store
+    ///
+    /// @note - It must cover the round trip of the confirmation, 2 seconds by default
+    pub fn with_lease_duration(mut self, duration: Duration) -> Self {
+        self.lease_duration = duration;
+        self
+    }
+
+    /// @summary - Present the key of the peers to the peers, which only hold the occurrences reserved by the other nodes
+    pub fn with_peer_key(mut self, key: &str) -> Self {
+        self.clients = self.clients.with_peer_key(key);
+        self
+    }
+
+    /// @summary - Enable or disable the forwarding of unmet queries
+    pub fn set_forwarding(&mut self, forwarding: bool) {
+        self.forwarding = forwarding;
+    }
+
+    pub fn peers(&self) -> &PeerTable {
+        self.clients.peers()
+    }
+
+    /// @summary - Forward an unmet query to the alive peers until one of them satisfies it
+    ///
+    /// @param trace - The operation of the trace that caused the query, None if it is not traced
+    ///
+    /// @returns - true if a peer satisfied the query
+    async fn forward(&self, action: Action, trace: Option<&SpanContext>) -> bool {
+        for (name, _) in self.peers().alive_peers() {
+            let Some(client) = self.clients.client(&name).await else { continue };
+            let result = match &action {
+                Action::Get(token) => self.forward_get(&client, *token, trace).await,
+                action => client.forward_traced(action.clone(), trace).await,
+            };
+            match result {
+                Ok(true) => return true,
+                Ok(false) => {},
+                Err(e) => log!(Level::Error, "Failed to forward to peer {}: {:?}", name, e),
+            }
+        }
+        false
+    }
+
+    /// @summary - Consume an occurrence of a token on a peer: reserve it, then confirm the lease
+    ///
+    /// @returns - false if the token is absent, or the lease expired before it was confirmed
+    async fn forward_get(&self, client: &SocketClient, token: TokenId, trace: Option<&SpanContext>) -> Result<bool, TransportError> {
+        let span = trace.map(|trace| ActiveSpan::start("remote forward", Some(trace)).with_attribute("bacht.peer", client.addr()));
+        let result = match client.reserve(token.into(), self.lease_duration).await {
+            Ok(Some(lease)) => client.confirm(lease).await,
+            Ok(None) => Ok(false),
+            Err(e) => Err(e),
+        };
+        if let Some(span) = span {
+            span.with_attribute("bacht.result", format!("{:?}", result)).end();
+        }
+        result
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send> BlackboardTrait for FederatedBlackboard<B> {
+
+    fn new() -> Self {
+        Self::new_with(B::new(), PeerTable::new())
+    }
+
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        let forwardable = match &event.action {
+            Action::Ask(_) | Action::Get(_) if self.forwarding && event.origin == Origin::Agent => Some(event.action.clone()),
+            _ => None,
+        };
+        let trace = event.trace.clone();
+        match (self.local.send_event(event).await, forwardable) {
+            (Ok(false), Some(action)) => Ok(self.forward(action, trace.as_ref()).await),
+            (result, _) => result,
+        }
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        match command {
+            AdminCommand::Peers => Ok(AdminReply::Peers(self.clients.peers().list().into_iter().map(|(name, peer)| PeerInfo {
+                name,
+                addr: peer.addr,
+                alive: peer.status == PeerStatus::Alive,
+            }).collect())),
+            command => self.local.admin(command).await,
+        }
+    }
+
+    fn health(&self) -> Health {
+        self.local.health()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.local.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            clients: self.clients.clone(),
+            forwarding: self.forwarding,
+            lease_duration: self.lease_duration,
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tokio::time::{sleep, timeout};
+    use super::*;
+    use crate::communication::lease::LeaseTable;
+    use crate::blackboard::{create_blackboard, MockBlackboardTrait};
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+
+    /// Start a listener serving the given blackboard to the peers presenting the key "cluster", and return its address
+    async fn serve<B: BlackboardTrait + Sync + Send + 'static>(blackboard: B) -> String {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        tokio::spawn(async move { SocketListener::new(blackboard, Some(port)).with_peer_key("cluster").listen().await });
+        format!("127.0.0.1:{}", port)
+    }
+
+    #[tokio::test]
+    async fn federation_should_satisfy_unmet_queries_on_peers() {
+        let remote = create_blackboard();
+        assert!(remote.tell("token".into()).await.unwrap());
+        let peers = PeerTable::new();
+        peers.insert("remote", &serve(remote.clone()).await);
+
+        let anonymous = FederatedBlackboard::new_with(create_blackboard(), peers.clone());
+        assert!(!anonymous.get("token".into()).await.unwrap(), "A node without the key of the peers should not reserve");
+        let federated = FederatedBlackboard::new_with(create_blackboard(), peers).with_peer_key("cluster");
+        assert!(federated.ask("token".into()).await.unwrap(), "The token should be found on the peer");
+        assert!(federated.get("token".into()).await.unwrap(), "The token should be consumed on the peer");
+        assert!(!remote.ask("token".into()).await.unwrap());
+        assert!(!federated.get("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn federation_should_reserve_then_confirm_the_forwarded_gets() {
+        let remote = create_blackboard();
+        assert!(remote.tell("token".into()).await.unwrap());
+        let leases = LeaseTable::new();
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let listener = SocketListener::new(remote.clone(), Some(port)).with_leases(leases.clone()).with_peer_key("cluster");
+        tokio::spawn(async move { listener.listen().await });
+        let peers = PeerTable::new();
+        peers.insert("remote", &format!("127.0.0.1:{}", port));
+
+        let federated = FederatedBlackboard::new_with(create_blackboard(), peers).with_lease_duration(Duration::from_millis(100)).with_peer_key("cluster");
+        assert!(federated.get("token".into()).await.unwrap());
+        assert_eq!(leases.len(), 1, "The get should have been served by a lease");
+        sleep(Duration::from_millis(300)).await;
+        assert!(leases.is_empty());
+        assert!(remote.nask("token".into()).await.unwrap(), "A confirmed occurrence should not go back to the peer's store");
+    }
+
+    #[tokio::test]
+    async fn federation_should_not_forward_satisfied_queries_nor_writes() {
+        let mut local = MockBlackboardTrait::default();
+        local.expect_send_event().times(2).returning(|_| Box::pin(async { Ok(true) }));
+        let peers = PeerTable::new();
+        // Any forwarding attempt would fail on this unreachable peer
+        peers.insert("remote", "127.0.0.1:1");
+
+        let federated = FederatedBlackboard::new_with(local, peers);
+        assert!(federated.ask("token".into()).await.unwrap());
+        assert!(federated.tell("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn federation_should_skip_dead_peers_and_respect_disabled_forwarding() {
+        let remote = create_blackboard();
+        assert!(remote.tell("token".into()).await.unwrap());
+        let peers = PeerTable::new();
+        peers.insert("remote", &serve(remote).await);
+
+        let mut federated = FederatedBlackboard::new_with(create_blackboard(), peers.clone());
+        federated.set_forwarding(false);
+        assert!(!federated.ask("token".into()).await.unwrap());
+
+        federated.set_forwarding(true);
+        peers.mark_dead("remote");
+        assert!(!federated.ask("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn federation_should_not_loop_between_federated_peers() {
+        let peers_a = PeerTable::new();
+        let peers_b = PeerTable::new();
+        let addr_a = serve(FederatedBlackboard::new_with(create_blackboard(), peers_a.clone())).await;
+        let addr_b = serve(FederatedBlackboard::new_with(create_blackboard(), peers_b.clone())).await;
+        peers_a.insert("b", &addr_b);
+        peers_b.insert("a", &addr_a);
+
+        let federated = FederatedBlackboard::new_with(create_blackboard(), peers_a);
+        let result = timeout(Duration::from_secs(5), federated.ask("missing".into())).await;
+        assert!(matches!(result, Ok(Ok(false))), "The query should fail without looping between a and b");
+    }
+
+    #[tokio::test]
+    async fn federation_should_report_its_peers_to_the_administrators() {
+        let peers = PeerTable::new();
+        peers.insert("b", "127.0.0.1:2");
+        peers.insert("a", "127.0.0.1:1");
+        peers.mark_dead("b");
+
+        let federated = FederatedBlackboard::new_with(create_blackboard(), peers);
+        assert_eq!(federated.admin(AdminCommand::Peers).await.unwrap(), AdminReply::Peers(vec![
+            PeerInfo { name: "a".into(), addr: "127.0.0.1:1".into(), alive: true },
+            PeerInfo { name: "b".into(), addr: "127.0.0.1:2".into(), alive: false },
+        ]));
+        assert_eq!(federated.admin(AdminCommand::Clear).await.unwrap(), AdminReply::Done, "The other commands should reach the local blackboard");
+    }
+}
@@ -0,0 +1,1095 @@
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::communication::compression::Compression;
+use crate::communication::gossip::TokenVersion;
+use crate::communication::raft::{AppendRequest, LogEntry, SnapshotRequest, VoteRequest};
+use crate::error::TransportError;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply, PeerInfo};
+use crate::model::clock::{Stamp, VectorClock};
+use crate::model::health::Health;
+use crate::trace::{self, SpanContext};
+
+/// Maximal size of a frame payload, bigger frames are refused to avoid unbounded allocations
+pub const MAX_FRAME_LENGTH: usize = 1024 * 1024;
+
+const CORRELATION_ID_LENGTH: usize = 8;
+
+const REQUEST_KIND: u8 = 0x01;
+const RESPONSE_KIND: u8 = 0x02;
+const ERROR_KIND: u8 = 0x03;
+const PING_KIND: u8 = 0x04;
+const PONG_KIND: u8 = 0x05;
+const FORWARD_KIND: u8 = 0x06;
+const REPLICATE_KIND: u8 = 0x07;
+const DIGEST_KIND: u8 = 0x08;
+const SYNC_KIND: u8 = 0x09;
+const HELLO_KIND: u8 = 0x0a;
+const POST_KIND: u8 = 0x0b;
+const ACK_KIND: u8 = 0x0c;
+const BATCH_KIND: u8 = 0x0d;
+const RESULTS_KIND: u8 = 0x0e;
+const COMPRESS_KIND: u8 = 0x0f;
+const AUTHENTICATE_KIND: u8 = 0x10;
+const ADMIN_KIND: u8 = 0x11;
+const ADMIN_REPLY_KIND: u8 = 0x12;
+const HEALTH_KIND: u8 = 0x13;
+const HEALTH_REPORT_KIND: u8 = 0x14;
+const PROGRAM_KIND: u8 = 0x15;
+const CAUSAL_KIND: u8 = 0x16;
+const RESERVE_KIND: u8 = 0x17;
+const LEASE_KIND: u8 = 0x18;
+const CONFIRM_KIND: u8 = 0x19;
+const RELEASE_KIND: u8 = 0x1a;
+const TRACED_KIND: u8 = 0x1b;
+const PROBE_KIND: u8 = 0x1c;
+const VOTE_KIND: u8 = 0x1d;
+const APPEND_KIND: u8 = 0x1e;
+const TERM_KIND: u8 = 0x1f;
+const INSTALL_KIND: u8 = 0x20;
+const SUBSCRIBE_KIND: u8 = 0x21;
+const CHANGE_KIND: u8 = 0x22;
+const SNAPSHOT_KIND: u8 = 0x23;
+
+// Set on the kind of a frame whose payload is compressed with the codec negotiated on the connection
+const COMPRESSED_FLAG: u8 = 0x80;
+
+const TELL_CODE: u8 = 0x01;
+const ASK_CODE: u8 = 0x02;
+const NASK_CODE: u8 = 0x03;
+const GET_CODE: u8 = 0x04;
+
+const CLEAR_CODE: u8 = 0x01;
+const STATS_CODE: u8 = 0x02;
+const SNAPSHOT_CODE: u8 = 0x03;
+const PEERS_CODE: u8 = 0x04;
+const DONE_CODE: u8 = 0x05;
+const SNAPSHOT_PART_CODE: u8 = 0x06;
+const IMPORT_CODE: u8 = 0x07;
+
+// The flags of a part of a snapshot
+const FIRST_PART_FLAG: u8 = 0x01;
+const LAST_PART_FLAG: u8 = 0x02;
+
+/// @summary - The unit of data exchanged on a blackboard connection.
+///
+/// On the wire, a frame is `[length: u32 big endian][correlation id: u64 big endian][kind: u8][payload]`, where length counts
+/// the correlation id, the kind and the payload. A response carries the correlation id of its request, so that several requests
+/// can be pending on the same connection and be answered in any order.
+///
+/// Once a compression is negotiated, the payload of big frames is compressed: the kind then carries COMPRESSED_FLAG, and the
+/// payload is `[original length: u32 big endian][compressed payload]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    /// An action to apply on the remote blackboard
+    Request(Action<Box<str>>),
+    /// The result of the last request
+    Response(bool),
+    /// The remote blackboard failed to process the last request
+    Error(String),
+    /// Heartbeat sent by a peer to check the connection is alive
+    Ping,
+    /// Answer to a Ping
+    Pong,
+    /// An action forwarded by a peer blackboard, to apply on the local store only
+    Forward(Action<Box<str>>),
+    /// A mutation applied by the primary blackboard, to apply on the replica's store
+    Replicate(Action<Box<str>>),
+    /// The digest of the gossiped store, answered by Response(true) if the stores are the same
+    Digest(u64),
+    /// The versions of the gossiped tokens, answered by the versions of the remote after it merged them
+    Sync(Vec<TokenVersion>),
+    /// First frame of a client connection, naming the session its requests belong to; answered by Response(true)
+    Hello(u64),
+    /// An action to apply on the remote blackboard whose result is not awaited, acknowledged by Ack once applied
+    Post(Action<Box<str>>),
+    /// The remote blackboard applied the posted action
+    Ack,
+    /// Actions to apply in order on the remote blackboard, answered by Results
+    Batch(Vec<Action<Box<str>>>),
+    /// The outcome of each action of a batch, in order: a Response or an Error
+    Results(Vec<Frame>),
+    /// Sent by a client after its hello to compress the big frames of the connection, answered by Response(true) if the remote
+    /// supports the codec. Both sides may then send compressed frames.
+    Compress(Compression),
+    /// Presents the admin key to the remote, answered by Response(true) if the connection was granted the admin capability
+    Authenticate(String),
+    /// A command managing the remote blackboard, refused by an Error unless the connection holds the admin capability
+    Admin(AdminCommand),
+    /// The answer to an Admin command
+    AdminReply(AdminReply),
+    /// Probes the health of the remote blackboard, answered by HealthReport; accepted from any client
+    Health,
+    /// The health of the blackboard, encoded as `[worker alive: u8][queue depth: u64][store size: u64]`
+    HealthReport(Health),
+    /// The source text of a BachT program to run on the remote blackboard, answered by Response(true) if the agent
+    /// terminated, Response(false) if it got stuck, or an Error if it does not parse
+    Program(String),
+    /// A mutation applied by a peer, broadcast with its causal context; answered by Response once applied, after the
+    /// mutations it depends on
+    Causal(Stamp, Action<Box<str>>),
+    /// Reserves an occurrence of a token on the remote blackboard for a number of milliseconds, encoded as
+    /// `[duration: u32][token]`; answered by Lease if an occurrence was reserved, Response(false) otherwise
+    Reserve(u32, Box<str>),
+    /// The identifier of the lease holding a reserved occurrence
+    Lease(u64),
+    /// Consumes the occurrence held by a lease, answered by Response(true) unless the lease expired
+    Confirm(u64),
+    /// Gives the occurrence held by a lease back to the store, answered by Response(true) unless the lease expired
+    Release(u64),
+    /// A request sent in a trace, encoded as `[traceparent length: u8][traceparent][tracestate length: u16][tracestate]
+    /// [request]`, the fields of the W3C trace context: the spans of the remote are children of the span given;
+    /// answered as the request, which must not be traced itself
+    Traced(SpanContext, Box<Frame>),
+    /// Asks the remote to ping one of its peers, by name, on behalf of a member that could not reach it, encoded as
+    /// `[timeout in milliseconds: u32][peer]`; answered by Response(true) if the peer answered in time, or an Error if
+    /// the remote does not probe its peers
+    Probe(u32, Box<str>),
+    /// Asks a member of a Raft cluster for its vote, encoded as `[term: u64][last index: u64][last term: u64][candidate]`;
+    /// answered by Term
+    Vote(VoteRequest),
+    /// Appends the entries of the leader of a Raft cluster to the log of a follower, encoded as a list: the header
+    /// `[term: u64][prev index: u64][prev term: u64][commit: u64][leader]`, then the entries as `[term: u64][action]`;
+    /// answered by Term
+    Append(AppendRequest),
+    /// The term of a member of a Raft cluster, and whether it granted the vote or appended the entries, encoded as
+    /// `[term: u64][granted: u8]`
+    Term(u64, bool),
+    /// A part of the snapshot of the leader of a Raft cluster, sent to a follower missing the entries it compacted, encoded
+    /// as a list: the header `[term: u64][last index: u64][last term: u64][flags: u8][leader]`, then the tokens as
+    /// `[occurrences: u32][token]`; answered by Term
+    Snapshot(SnapshotRequest),
+    /// The snapshot of the store of a primary, told on the store of the replica joining it before the mutations streamed
+    /// after it, encoded as a list of `[occurrences: u32][token]`; answered by Response(true) once applied
+    Install(Vec<(Box<str>, u32)>),
+    /// Subscribes to the changes of the tokens matching an SI-Term, its variables matching any term, e.g. `msg(X)`;
+    /// answered by Response(true), then by a Change carrying the same correlation id for each change, until the
+    /// connection is closed or an Error ends the subscription
+    Subscribe(Box<str>),
+    /// A change of a token matching a subscription, encoded as `[count: u32][token]`, the count being the number of
+    /// occurrences of the token after the change
+    Change(u32, Box<str>),
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// The underlying stream failed or was closed in the middle of a frame
+    Io(std::io::Error),
+    /// The bytes received do not form a valid frame
+    Malformed(String),
+    TooLarge(usize),
+}
+
+impl From<FrameError> for TransportError {
+    fn from(error: FrameError) -> Self {
+        match error {
+            FrameError::Io(e) => TransportError::Io(e.to_string()),
+            FrameError::Malformed(e) => TransportError::ProtocolError(e),
+            FrameError::TooLarge(length) => TransportError::ProtocolError(format!("Frame of {} bytes is too large", length)),
+        }
+    }
+}
+
+impl Frame {
+
+    /// @summary - Serialize the kind and the payload of the frame
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        self.encode_into(&mut body);
+        body
+    }
+
+    /// @summary - Serialize the kind and the payload of the frame at the end of a buffer, e.g. one reused from frame to
+    /// frame
+    pub fn encode_into(&self, body: &mut Vec<u8>) {
+        match self {
+            Frame::Request(action) => {
+                body.push(REQUEST_KIND);
+                encode_action(body, action);
+            },
+            Frame::Forward(action) => {
+                body.push(FORWARD_KIND);
+                encode_action(body, action);
+            },
+            Frame::Replicate(action) => {
+                body.push(REPLICATE_KIND);
+                encode_action(body, action);
+            },
+            Frame::Post(action) => {
+                body.push(POST_KIND);
+                encode_action(body, action);
+            },
+            Frame::Response(result) => {
+                body.push(RESPONSE_KIND);
+                body.push(*result as u8);
+            },
+            Frame::Error(message) => {
+                body.push(ERROR_KIND);
+                body.extend_from_slice(message.as_bytes());
+            },
+            Frame::Digest(digest) => {
+                body.push(DIGEST_KIND);
+                body.extend_from_slice(&digest.to_be_bytes());
+            },
+            Frame::Hello(session) => {
+                body.push(HELLO_KIND);
+                body.extend_from_slice(&session.to_be_bytes());
+            },
+            Frame::Sync(versions) => {
+                body.push(SYNC_KIND);
+                encode_versions(body, versions);
+            },
+            Frame::Batch(actions) => {
+                body.push(BATCH_KIND);
+                encode_list(body, actions.iter().map(|action| {
+                    let mut item = Vec::new();
+                    encode_action(&mut item, action);
+                    item
+                }));
+            },
+            Frame::Results(results) => {
+                body.push(RESULTS_KIND);
+                encode_list(body, results.iter().map(Frame::encode));
+            },
+            Frame::Compress(compression) => {
+                body.push(COMPRESS_KIND);
+                body.push(compression.code());
+            },
+            Frame::Causal(stamp, action) => {
+                body.push(CAUSAL_KIND);
+                encode_causal(body, stamp, action);
+            },
+            Frame::Reserve(duration, token) => {
+                body.push(RESERVE_KIND);
+                body.extend_from_slice(&duration.to_be_bytes());
+                body.extend_from_slice(token.as_bytes());
+            },
+            Frame::Probe(timeout, peer) => {
+                body.push(PROBE_KIND);
+                body.extend_from_slice(&timeout.to_be_bytes());
+                body.extend_from_slice(peer.as_bytes());
+            },
+            Frame::Vote(request) => {
+                body.push(VOTE_KIND);
+                for number in [request.term, request.last_index, request.last_term] {
+                    body.extend_from_slice(&number.to_be_bytes());
+                }
+                body.extend_from_slice(request.candidate.as_bytes());
+            },
+            Frame::Append(request) => {
+                body.push(APPEND_KIND);
+                encode_append(body, request);
+            },
+            Frame::Snapshot(request) => {
+                body.push(SNAPSHOT_KIND);
+                encode_snapshot(body, request);
+            },
+            Frame::Term(term, granted) => {
+                body.push(TERM_KIND);
+                body.extend_from_slice(&term.to_be_bytes());
+                body.push(*granted as u8);
+            },
+            Frame::Install(tokens) => {
+                body.push(INSTALL_KIND);
+                encode_tokens(body, tokens);
+            },
+            Frame::Subscribe(pattern) => {
+                body.push(SUBSCRIBE_KIND);
+                body.extend_from_slice(pattern.as_bytes());
+            },
+            Frame::Change(count, token) => {
+                body.push(CHANGE_KIND);
+                body.extend_from_slice(&count.to_be_bytes());
+                body.extend_from_slice(token.as_bytes());
+            },
+            Frame::Lease(lease) => {
+                body.push(LEASE_KIND);
+                body.extend_from_slice(&lease.to_be_bytes());
+            },
+            Frame::Confirm(lease) => {
+                body.push(CONFIRM_KIND);
+                body.extend_from_slice(&lease.to_be_bytes());
+            },
+            Frame::Release(lease) => {
+                body.push(RELEASE_KIND);
+                body.extend_from_slice(&lease.to_be_bytes());
+            },
+            Frame::Traced(context, request) => {
+                body.push(TRACED_KIND);
+                let (traceparent, tracestate) = trace::inject(context);
+                body.push(traceparent.len() as u8);
+                body.extend_from_slice(traceparent.as_bytes());
+                body.extend_from_slice(&(tracestate.len() as u16).to_be_bytes());
+                body.extend_from_slice(tracestate.as_bytes());
+                body.extend_from_slice(&request.encode());
+            },
+            Frame::Program(source) => {
+                body.push(PROGRAM_KIND);
+                body.extend_from_slice(source.as_bytes());
+            },
+            Frame::Authenticate(key) => {
+                body.push(AUTHENTICATE_KIND);
+                body.extend_from_slice(key.as_bytes());
+            },
+            Frame::Admin(command) => {
+                body.push(ADMIN_KIND);
+                match command {
+                    AdminCommand::Clear => body.push(CLEAR_CODE),
+                    AdminCommand::Stats => body.push(STATS_CODE),
+                    AdminCommand::Snapshot => body.push(SNAPSHOT_CODE),
+                    AdminCommand::Peers => body.push(PEERS_CODE),
+                    AdminCommand::Import(tokens) => {
+                        body.push(IMPORT_CODE);
+                        encode_tokens(body, tokens);
+                    },
+                }
+            },
+            Frame::AdminReply(reply) => {
+                body.push(ADMIN_REPLY_KIND);
+                encode_admin_reply(body, reply);
+            },
+            Frame::HealthReport(health) => {
+                body.push(HEALTH_REPORT_KIND);
+                body.push(health.worker_alive as u8);
+                body.extend_from_slice(&health.queue_depth.to_be_bytes());
+                body.extend_from_slice(&health.store_size.to_be_bytes());
+            },
+            Frame::Health => body.push(HEALTH_KIND),
+            Frame::Ping => body.push(PING_KIND),
+            Frame::Pong => body.push(PONG_KIND),
+            Frame::Ack => body.push(ACK_KIND),
+        }
+    }
+
+    /// @summary - Deserialize the kind and the payload of a frame
+    ///
+    /// @param body - The kind byte followed by the payload
+    ///
+    /// @returns - The frame, or FrameError::Malformed if the body is not a valid frame
+    pub fn decode(body: &[u8]) -> Result<Frame, FrameError> {
+        let (kind, payload) = body.split_first().ok_or(FrameError::Malformed("Empty frame".into()))?;
+        match *kind {
+            REQUEST_KIND => Ok(Frame::Request(decode_action(payload)?)),
+            FORWARD_KIND => Ok(Frame::Forward(decode_action(payload)?)),
+            REPLICATE_KIND => Ok(Frame::Replicate(decode_action(payload)?)),
+            POST_KIND => Ok(Frame::Post(decode_action(payload)?)),
+            RESPONSE_KIND => match payload {
+                [0] => Ok(Frame::Response(false)),
+                [1] => Ok(Frame::Response(true)),
+                _ => Err(FrameError::Malformed("Invalid response payload".into()))
+            },
+            ERROR_KIND => Ok(Frame::Error(decode_str(payload)?.to_string())),
+            DIGEST_KIND => match payload.try_into() {
+                Ok(digest) => Ok(Frame::Digest(u64::from_be_bytes(digest))),
+                Err(_) => Err(FrameError::Malformed("Invalid digest payload".into()))
+            },
+            SYNC_KIND => Ok(Frame::Sync(decode_versions(payload)?)),
+            HELLO_KIND => match payload.try_into() {
+                Ok(session) => Ok(Frame::Hello(u64::from_be_bytes(session))),
+                Err(_) => Err(FrameError::Malformed("Invalid hello payload".into()))
+            },
+            BATCH_KIND => Ok(Frame::Batch(decode_list(payload)?.into_iter().map(decode_action).collect::<Result<_, _>>()?)),
+            RESULTS_KIND => Ok(Frame::Results(decode_list(payload)?.into_iter().map(decode_result).collect::<Result<_, _>>()?)),
+            COMPRESS_KIND => match payload {
+                [code] => Compression::from_code(*code).map(Frame::Compress).ok_or(FrameError::Malformed(format!("Unknown compression {}", code))),
+                _ => Err(FrameError::Malformed("Invalid compress payload".into()))
+            },
+            AUTHENTICATE_KIND => Ok(Frame::Authenticate(decode_str(payload)?.to_string())),
+            CAUSAL_KIND => decode_causal(payload),
+            PROGRAM_KIND => Ok(Frame::Program(decode_str(payload)?.to_string())),
+            RESERVE_KIND => match payload.split_first_chunk::<4>() {
+                Some((duration, token)) => Ok(Frame::Reserve(u32::from_be_bytes(*duration), decode_str(token)?.into())),
+                None => Err(FrameError::Malformed("Invalid reserve payload".into()))
+            },
+            PROBE_KIND => match payload.split_first_chunk::<4>() {
+                Some((timeout, peer)) => Ok(Frame::Probe(u32::from_be_bytes(*timeout), decode_str(peer)?.into())),
+                None => Err(FrameError::Malformed("Invalid probe payload".into()))
+            },
+            VOTE_KIND => match payload.split_at_checked(24) {
+                Some((numbers, candidate)) => Ok(Frame::Vote(VoteRequest {
+                    term: u64::from_be_bytes(numbers[..8].try_into().unwrap()),
+                    last_index: u64::from_be_bytes(numbers[8..16].try_into().unwrap()),
+                    last_term: u64::from_be_bytes(numbers[16..].try_into().unwrap()),
+                    candidate: decode_str(candidate)?.into(),
+                })),
+                None => Err(FrameError::Malformed("Invalid vote payload".into()))
+            },
+            APPEND_KIND => decode_append(payload),
+            SNAPSHOT_KIND => decode_snapshot(payload),
+            INSTALL_KIND => Ok(Frame::Install(decode_tokens(payload)?)),
+            SUBSCRIBE_KIND => Ok(Frame::Subscribe(decode_str(payload)?.into())),
+            CHANGE_KIND => match payload.split_first_chunk::<4>() {
+                Some((count, token)) => Ok(Frame::Change(u32::from_be_bytes(*count), decode_str(token)?.into())),
+                None => Err(FrameError::Malformed("Invalid change payload".into()))
+            },
+            TERM_KIND => match payload.split_first_chunk::<8>() {
+                Some((term, [granted @ (0 | 1)])) => Ok(Frame::Term(u64::from_be_bytes(*term), *granted == 1)),
+                _ => Err(FrameError::Malformed("Invalid term payload".into()))
+            },
+            LEASE_KIND => Ok(Frame::Lease(decode_lease(payload)?)),
+            CONFIRM_KIND => Ok(Frame::Confirm(decode_lease(payload)?)),
+            RELEASE_KIND => Ok(Frame::Release(decode_lease(payload)?)),
+            TRACED_KIND => decode_traced(payload),
+            ADMIN_KIND => match payload {
+                [CLEAR_CODE] => Ok(Frame::Admin(AdminCommand::Clear)),
+                [STATS_CODE] => Ok(Frame::Admin(AdminCommand::Stats)),
+                [SNAPSHOT_CODE] => Ok(Frame::Admin(AdminCommand::Snapshot)),
+                [PEERS_CODE] => Ok(Frame::Admin(AdminCommand::Peers)),
+                [IMPORT_CODE, tokens @ ..] => Ok(Frame::Admin(AdminCommand::Import(decode_tokens(tokens)?))),
+                _ => Err(FrameError::Malformed("Invalid admin payload".into()))
+            },
+            ADMIN_REPLY_KIND => Ok(Frame::AdminReply(decode_admin_reply(payload)?)),
+            HEALTH_KIND => Ok(Frame::Health),
+            HEALTH_REPORT_KIND => match payload {
+                [alive @ (0 | 1), counters @ ..] if counters.len() == 16 => Ok(Frame::HealthReport(Health {
+                    worker_alive: *alive == 1,
+                    queue_depth: u64::from_be_bytes(counters[..8].try_into().unwrap()),
+                    store_size: u64::from_be_bytes(counters[8..].try_into().unwrap()),
+                })),
+                _ => Err(FrameError::Malformed("Invalid health report payload".into()))
+            },
+            PING_KIND => Ok(Frame::Ping),
+            PONG_KIND => Ok(Frame::Pong),
+            ACK_KIND => Ok(Frame::Ack),
+            _ => Err(FrameError::Malformed(format!("Unknown frame kind {}", kind)))
+        }
+    }
+}
+
+fn decode_lease(payload: &[u8]) -> Result<u64, FrameError> {
+    payload.try_into().map(u64::from_be_bytes).map_err(|_| FrameError::Malformed("Invalid lease payload".into()))
+}
+
+// A result is the reply to an action of a batch, never a frame nesting others, so that nested frames cannot exhaust
+// the stack
+fn decode_result(item: &[u8]) -> Result<Frame, FrameError> {
+    match item.first() {
+        Some(&(RESPONSE_KIND | ERROR_KIND | ADMIN_REPLY_KIND)) => Frame::decode(item),
+        _ => Err(FrameError::Malformed("Invalid result".into())),
+    }
+}
+
+fn decode_traced(payload: &[u8]) -> Result<Frame, FrameError> {
+    let truncated = || FrameError::Malformed("Truncated trace context".into());
+    let (&length, payload) = payload.split_first().ok_or_else(truncated)?;
+    let (traceparent, payload) = payload.split_at_checked(length as usize).ok_or_else(truncated)?;
+    let (length, payload) = payload.split_first_chunk::<2>().ok_or_else(truncated)?;
+    let (tracestate, request) = payload.split_at_checked(u16::from_be_bytes(*length) as usize).ok_or_else(truncated)?;
+    // A single level of tracing, so that nested frames cannot exhaust the stack
+    if request.first() == Some(&TRACED_KIND) {
+        return Err(FrameError::Malformed("Nested traced frame".into()));
+    }
+    let context = trace::extract(decode_str(traceparent)?, decode_str(tracestate)?)
+        .ok_or_else(|| FrameError::Malformed("Invalid trace context".into()))?;
+    Ok(Frame::Traced(context, Box::new(Frame::decode(request)?)))
+}
+
+fn encode_action(body: &mut Vec<u8>, action: &Action<Box<str>>) {
+    let (code, token) = match action {
+        Action::Tell(token) => (TELL_CODE, token),
+        Action::Ask(token) => (ASK_CODE, token),
+        Action::Nask(token) => (NASK_CODE, token),
+        Action::Get(token) => (GET_CODE, token),
+    };
+    body.push(code);
+    body.extend_from_slice(token.as_bytes());
+}
+
+// The token is kept as a name, interned once the request carrying it is authorized and applied
+fn decode_action(payload: &[u8]) -> Result<Action<Box<str>>, FrameError> {
+    let (code, token) = payload.split_first().ok_or(FrameError::Malformed("Missing action".into()))?;
+    let token: Box<str> = decode_str(token)?.into();
+    match *code {
+        TELL_CODE => Ok(Action::Tell(token)),
+        ASK_CODE => Ok(Action::Ask(token)),
+        NASK_CODE => Ok(Action::Nask(token)),
+        GET_CODE => Ok(Action::Get(token)),
+        _ => Err(FrameError::Malformed(format!("Unknown action code {}", code)))
+    }
+}
+
+/// Versions are encoded as `[count: u32]` followed, for each version, by `[occurrences: u32][clock: u64][token][node]`,
+/// where the token and the node are prefixed by their length as u32
+fn encode_versions(body: &mut Vec<u8>, versions: &[TokenVersion]) {
+    body.extend_from_slice(&(versions.len() as u32).to_be_bytes());
+    for version in versions {
+        body.extend_from_slice(&version.occurrences.to_be_bytes());
+        body.extend_from_slice(&version.clock.to_be_bytes());
+        for text in [&version.token, &version.node] {
+            body.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            body.extend_from_slice(text.as_bytes());
+        }
+    }
+}
+
+fn decode_versions(payload: &[u8]) -> Result<Vec<TokenVersion>, FrameError> {
+    let mut cursor = payload;
+    let mut take = |n: usize| -> Result<&[u8], FrameError> {
+        if cursor.len() < n {
+            return Err(FrameError::Malformed("Truncated versions".into()));
+        }
+        let (taken, rest) = cursor.split_at(n);
+        cursor = rest;
+        Ok(taken)
+    };
+    let count = u32::from_be_bytes(take(4)?.try_into().unwrap());
+    let mut versions = Vec::new();
+    for _ in 0..count {
+        let occurrences = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let clock = u64::from_be_bytes(take(8)?.try_into().unwrap());
+        let length = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+        let token: Box<str> = decode_str(take(length)?)?.into();
+        let length = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+        let node: Box<str> = decode_str(take(length)?)?.into();
+        versions.push(TokenVersion { token, occurrences, clock, node });
+    }
+    if !cursor.is_empty() {
+        return Err(FrameError::Malformed("Trailing bytes after versions".into()));
+    }
+    Ok(versions)
+}
+
+/// Admin replies are encoded as `[code: u8]` followed by a list of entries: `[value: u64][name]` for the stats,
+/// `[occurrences: u32][token]` for a snapshot, and `[alive: u8][name length: u32][name][addr]` for the peers. The part
+/// of a snapshot puts its flags as `[flags: u8]` before its tokens
+fn encode_admin_reply(body: &mut Vec<u8>, reply: &AdminReply) {
+    match reply {
+        AdminReply::Done => body.push(DONE_CODE),
+        AdminReply::Stats(stats) => {
+            body.push(STATS_CODE);
+            encode_list(body, stats.iter().map(|(name, value)| [&value.to_be_bytes(), name.as_bytes()].concat()));
+        },
+        AdminReply::Snapshot(tokens) => {
+            body.push(SNAPSHOT_CODE);
+            encode_tokens(body, tokens);
+        },
+        AdminReply::SnapshotPart { tokens, last } => {
+            body.push(SNAPSHOT_PART_CODE);
+            body.push(*last as u8 * LAST_PART_FLAG);
+            encode_tokens(body, tokens);
+        },
+        AdminReply::Peers(peers) => {
+            body.push(PEERS_CODE);
+            encode_list(body, peers.iter().map(|peer| {
+                [&[peer.alive as u8][..], &(peer.name.len() as u32).to_be_bytes(), peer.name.as_bytes(), peer.addr.as_bytes()].concat()
+            }));
+        },
+    }
+}
+
+fn decode_admin_reply(payload: &[u8]) -> Result<AdminReply, FrameError> {
+    let (code, entries) = payload.split_first().ok_or(FrameError::Malformed("Missing admin reply".into()))?;
+    let truncated = || FrameError::Malformed("Truncated admin reply".into());
+    match *code {
+        DONE_CODE if entries.is_empty() => Ok(AdminReply::Done),
+        STATS_CODE => decode_list(entries)?.into_iter().map(|entry| {
+            let (value, name) = entry.split_at_checked(8).ok_or_else(truncated)?;
+            Ok((decode_str(name)?.to_string(), u64::from_be_bytes(value.try_into().unwrap())))
+        }).collect::<Result<_, _>>().map(AdminReply::Stats),
+        SNAPSHOT_CODE => decode_tokens(entries).map(AdminReply::Snapshot),
+        SNAPSHOT_PART_CODE => {
+            let (flags, entries) = entries.split_first().ok_or_else(truncated)?;
+            Ok(AdminReply::SnapshotPart { tokens: decode_tokens(entries)?, last: flags & LAST_PART_FLAG != 0 })
+        },
+        PEERS_CODE => decode_list(entries)?.into_iter().map(|entry| {
+            let (alive, entry) = entry.split_first().ok_or_else(truncated)?;
+            let (length, entry) = entry.split_at_checked(4).ok_or_else(truncated)?;
+            let (name, addr) = entry.split_at_checked(u32::from_be_bytes(length.try_into().unwrap()) as usize).ok_or_else(truncated)?;
+            Ok(PeerInfo { name: decode_str(name)?.into(), addr: decode_str(addr)?.to_string(), alive: *alive != 0 })
+        }).collect::<Result<_, _>>().map(AdminReply::Peers),
+        _ => Err(FrameError::Malformed(format!("Unknown admin reply {}", code)))
+    }
+}
+
+/// The tokens of a store are encoded as a list of `[occurrences: u32][token]`
+fn encode_tokens(body: &mut Vec<u8>, tokens: &[(Box<str>, u32)]) {
+    encode_list(body, tokens.iter().map(|(token, occurrences)| [&occurrences.to_be_bytes(), token.as_bytes()].concat()));
+}
+
+fn decode_tokens(payload: &[u8]) -> Result<Vec<(Box<str>, u32)>, FrameError> {
+    decode_list(payload)?.into_iter().map(|entry| {
+        let (occurrences, token) = entry.split_at_checked(4).ok_or(FrameError::Malformed("Truncated token".into()))?;
+        Ok((decode_str(token)?.into(), u32::from_be_bytes(occurrences.try_into().unwrap())))
+    }).collect()
+}
+
+/// Causal mutations are encoded as a list: the action, the node of the stamp, then the entries of its clock as
+/// `[count: u64][node]`
+fn encode_causal(body: &mut Vec<u8>, stamp: &Stamp, action: &Action<Box<str>>) {
+    let mut encoded_action = Vec::new();
+    encode_action(&mut encoded_action, action);
+    let entries = stamp.clock.entries().map(|(node, count)| [&count.to_be_bytes(), node.as_bytes()].concat());
+    encode_list(body, [encoded_action, stamp.node.as_bytes().to_vec()].into_iter().chain(entries).collect::<Vec<_>>().into_iter());
+}
+
+fn decode_causal(payload: &[u8]) -> Result<Frame, FrameError> {
+    let items = decode_list(payload)?;
+    let [action, node, entries @ ..] = items.as_slice() else {
+        return Err(FrameError::Malformed("Truncated causal mutation".into()));
+    };
+    let mut clock = VectorClock::new();
+    for entry in entries {
+        let (count, node) = entry.split_at_checked(8).ok_or(FrameError::Malformed("Truncated clock entry".into()))?;
+        clock.set(decode_str(node)?, u64::from_be_bytes(count.try_into().unwrap()));
+    }
+    Ok(Frame::Causal(Stamp { node: decode_str(node)?.into(), clock }, decode_action(action)?))
+}
+
+/// Appends are encoded as a list: the header `[term: u64][prev index: u64][prev term: u64][commit: u64][leader]`, then
+/// the entries as `[term: u64][action]`
+fn encode_append(body: &mut Vec<u8>, request: &AppendRequest) {
+    let header = [request.term, request.prev_index, request.prev_term, request.commit].iter()
+        .flat_map(|number| number.to_be_bytes())
+        .chain(request.leader.bytes())
+        .collect();
+    let entries = request.entries.iter().map(|entry| {
+        let mut item = entry.term.to_be_bytes().to_vec();
+        encode_action(&mut item, &entry.action);
+        item
+    });
+    encode_list(body, [header].into_iter().chain(entries).collect::<Vec<_>>().into_iter());
+}
+
+fn decode_append(payload: &[u8]) -> Result<Frame, FrameError> {
+    let items = decode_list(payload)?;
+    let Some((header, entries)) = items.split_first() else {
+        return Err(FrameError::Malformed("Truncated append".into()));
+    };
+    let (numbers, leader) = header.split_at_checked(32).ok_or(FrameError::Malformed("Truncated append".into()))?;
+    let number = |n: usize| u64::from_be_bytes(numbers[n * 8..(n + 1) * 8].try_into().unwrap());
+    let entries = entries.iter().map(|entry| {
+        let (term, action) = entry.split_first_chunk::<8>().ok_or(FrameError::Malformed("Truncated log entry".into()))?;
+        Ok(LogEntry { term: u64::from_be_bytes(*term), action: decode_action(action)? })
+    }).collect::<Result<_, FrameError>>()?;
+    Ok(Frame::Append(AppendRequest {
+        term: number(0),
+        prev_index: number(1),
+        prev_term: number(2),
+        commit: number(3),
+        leader: decode_str(leader)?.into(),
+        entries,
+    }))
+}
+
+/// Parts of snapshots are encoded as a list: the header `[term: u64][last index: u64][last term: u64][flags: u8][leader]`,
+/// then the tokens as `[occurrences: u32][token]`
+fn encode_snapshot(body: &mut Vec<u8>, request: &SnapshotRequest) {
+    let flags = (request.first as u8 * FIRST_PART_FLAG) | (request.last as u8 * LAST_PART_FLAG);
+    let header = [request.term, request.last_index, request.last_term].iter()
+        .flat_map(|number| number.to_be_bytes())
+        .chain([flags])
+        .chain(request.leader.bytes())
+        .collect();
+    let tokens = request.tokens.iter().map(|(token, occurrences)| [&occurrences.to_be_bytes(), token.as_bytes()].concat());
+    encode_list(body, [header].into_iter().chain(tokens).collect::<Vec<_>>().into_iter());
+}
+
+fn decode_snapshot(payload: &[u8]) -> Result<Frame, FrameError> {
+    let items = decode_list(payload)?;
+    let Some((header, tokens)) = items.split_first() else {
+        return Err(FrameError::Malformed("Truncated snapshot".into()));
+    };
+    let (numbers, leader) = header.split_at_checked(25).ok_or(FrameError::Malformed("Truncated snapshot".into()))?;
+    let number = |n: usize| u64::from_be_bytes(numbers[n * 8..(n + 1) * 8].try_into().unwrap());
+    let tokens = tokens.iter().map(|entry| {
+        let (occurrences, token) = entry.split_at_checked(4).ok_or(FrameError::Malformed("Truncated token".into()))?;
+        Ok((decode_str(token)?.into(), u32::from_be_bytes(occurrences.try_into().unwrap())))
+    }).collect::<Result<_, FrameError>>()?;
+    Ok(Frame::Snapshot(SnapshotRequest {
+        term: number(0),
+        last_index: number(1),
+        last_term: number(2),
+        first: numbers[24] & FIRST_PART_FLAG != 0,
+        last: numbers[24] & LAST_PART_FLAG != 0,
+        leader: decode_str(leader)?.into(),
+        tokens,
+    }))
+}
+
+/// @summary - Split the tokens of a store into parts, each one encoded in at most the given number of bytes, e.g. to
+/// send a snapshot in frames smaller than MAX_FRAME_LENGTH
+///
+/// @returns - The parts in order, a single empty one if there is no token
+///
+/// @note - A token too long to fit is a part of its own, which no frame carries
+pub fn token_parts(tokens: &[(Box<str>, u32)], max_length: usize) -> Vec<&[(Box<str>, u32)]> {
+    let mut parts = Vec::new();
+    let (mut start, mut length) = (0, 0);
+    for (i, (token, _)) in tokens.iter().enumerate() {
+        // The occurrences, and the length of the item in the list
+        let encoded = token.len() + 8;
+        if i > start && length + encoded > max_length {
+            parts.push(&tokens[start..i]);
+            (start, length) = (i, 0);
+        }
+        length += encoded;
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+/// @summary - The frames answering a Snapshot admin command: the snapshot in a single frame if it fits in one, else its
+/// parts, each one smaller than MAX_FRAME_LENGTH, the last one flagged as such
+pub fn snapshot_replies(tokens: Vec<(Box<str>, u32)>) -> Vec<Frame> {
+    // The correlation id, the kind, the code, the flags and the count of the tokens
+    const OVERHEAD: usize = CORRELATION_ID_LENGTH + 3 + 4;
+    let parts = token_parts(&tokens, MAX_FRAME_LENGTH - OVERHEAD);
+    if parts.len() == 1 {
+        return vec![Frame::AdminReply(AdminReply::Snapshot(tokens))];
+    }
+    let count = parts.len();
+    parts.into_iter().enumerate()
+        .map(|(i, part)| Frame::AdminReply(AdminReply::SnapshotPart { tokens: part.to_vec(), last: i + 1 == count }))
+        .collect()
+}
+
+/// Lists are encoded as `[count: u32]` followed by the items, each one prefixed by its length as u32
+fn encode_list(body: &mut Vec<u8>, items: impl ExactSizeIterator<Item = Vec<u8>>) {
+    body.extend_from_slice(&(items.len() as u32).to_be_bytes());
+    for item in items {
+        body.extend_from_slice(&(item.len() as u32).to_be_bytes());
+        body.extend_from_slice(&item);
+    }
+}
+
+fn decode_list(payload: &[u8]) -> Result<Vec<&[u8]>, FrameError> {
+    let mut cursor = payload;
+    let mut take = |n: usize| -> Result<&[u8], FrameError> {
+        if cursor.len() < n {
+            return Err(FrameError::Malformed("Truncated list".into()));
+        }
+        let (taken, rest) = cursor.split_at(n);
+        cursor = rest;
+        Ok(taken)
+    };
+    let count = u32::from_be_bytes(take(4)?.try_into().unwrap());
+    let mut items = Vec::new();
+    for _ in 0..count {
+        let length = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+        items.push(take(length)?);
+    }
+    if !cursor.is_empty() {
+        return Err(FrameError::Malformed("Trailing bytes after list".into()));
+    }
+    Ok(items)
+}
+
+fn decode_str(bytes: &[u8]) -> Result<&str, FrameError> {
+    std::str::from_utf8(bytes).map_err(|e| FrameError::Malformed(format!("Invalid UTF-8: {}", e)))
+}
+
+/// @summary - Read the next frame from the stream
+///
+/// @returns - The correlation id and the frame, or None if the stream was closed cleanly between two frames
+///
+/// @note - A compressed frame is decompressed whatever the negotiated compression, the codec being implied by the flag
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<(u64, Frame)>, FrameError> {
+    read_frame_into(reader, &mut BytesMut::new()).await
+}
+
+/// @summary - Read the next frame from the stream, as read_frame, through a buffer reused from frame to frame
+///
+/// @param buffer - Holds the body of the frame while it is decoded, the tokens being copied once from it into the frame
+///
+/// @note - Only the buffer is pooled, the decoding is not zero-copy: the frame owns its tokens, interned into the
+/// TokenId of their events once the frame is handled
+pub async fn read_frame_into<R: AsyncRead + Unpin>(reader: &mut R, buffer: &mut BytesMut) -> Result<Option<(u64, Frame)>, FrameError> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length).await {
+        Ok(_) => {},
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(FrameError::Io(e)),
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_LENGTH {
+        return Err(FrameError::TooLarge(length));
+    }
+    if length < CORRELATION_ID_LENGTH {
+        return Err(FrameError::Malformed("Missing correlation id".into()));
+    }
+    buffer.clear();
+    buffer.resize(length, 0);
+    reader.read_exact(buffer).await.map_err(FrameError::Io)?;
+    let (id, body) = buffer.split_at(CORRELATION_ID_LENGTH);
+    let id = u64::from_be_bytes(id.try_into().unwrap());
+    match body.split_first() {
+        Some((kind, payload)) if kind & COMPRESSED_FLAG != 0 => {
+            let (original, compressed) = payload.split_at_checked(4).ok_or(FrameError::Malformed("Missing original length".into()))?;
+            let original = u32::from_be_bytes(original.try_into().unwrap()) as usize;
+            if original > MAX_FRAME_LENGTH {
+                return Err(FrameError::TooLarge(original));
+            }
+            let mut body = vec![kind & !COMPRESSED_FLAG];
+            body.extend(Compression::Lz4.decompress(compressed, original).map_err(FrameError::Malformed)?);
+            Ok(Some((id, Frame::decode(&body)?)))
+        },
+        _ => Ok(Some((id, Frame::decode(body)?))),
+    }
+}
+
+/// @summary - Write a frame on the stream
+///
+/// @param id - The correlation id, a response must carry the one of its request
+///
+/// @note - A frame longer than MAX_FRAME_LENGTH is replaced by an Error with the same correlation id, the peer refusing it
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, id: u64, frame: &Frame) -> Result<(), FrameError> {
+    write_frame_with(writer, id, frame, Compression::None).await
+}
+
+/// @summary - Write a frame on the stream, compressing its payload if it is big enough
+///
+/// @param compression - The compression negotiated on the connection
+pub async fn write_frame_with<W: AsyncWrite + Unpin>(writer: &mut W, id: u64, frame: &Frame, compression: Compression) -> Result<(), FrameError> {
+    write_frame_into(writer, id, frame, compression, &mut Vec::new()).await
+}
+
+/// @summary - Write a frame on the stream, as write_frame_with, through a buffer reused from frame to frame
+///
+/// @param buffer - Holds the frame while it is serialized, its length and correlation id included
+pub async fn write_frame_into<W: AsyncWrite + Unpin>(writer: &mut W, id: u64, frame: &Frame, compression: Compression, buffer: &mut Vec<u8>) -> Result<(), FrameError> {
+    // The length is only known once the body is serialized after it
+    const HEADER_LENGTH: usize = 4 + CORRELATION_ID_LENGTH;
+    buffer.clear();
+    buffer.extend_from_slice(&[0; 4]);
+    buffer.extend_from_slice(&id.to_be_bytes());
+    frame.encode_into(buffer);
+    // The peer refuses a frame longer than MAX_FRAME_LENGTH once decompressed, an Error is sent in its place
+    let length = buffer.len() - 4;
+    if length > MAX_FRAME_LENGTH {
+        buffer.truncate(HEADER_LENGTH);
+        Frame::Error(format!("The frame of {} bytes exceeds the maximal length of {} bytes", length, MAX_FRAME_LENGTH)).encode_into(buffer);
+    }
+    if let Some(compressed) = compression.compress(&buffer[HEADER_LENGTH + 1..]) {
+        let original = (buffer.len() - HEADER_LENGTH - 1) as u32;
+        buffer.truncate(HEADER_LENGTH + 1);
+        buffer[HEADER_LENGTH] |= COMPRESSED_FLAG;
+        buffer.extend_from_slice(&original.to_be_bytes());
+        buffer.extend(compressed);
+    }
+    let length = (buffer.len() - 4) as u32;
+    buffer[..4].copy_from_slice(&length.to_be_bytes());
+    writer.write_all(buffer).await.map_err(FrameError::Io)?;
+    writer.flush().await.map_err(FrameError::Io)
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_should_survive_an_encode_decode_round_trip() {
+        let frames = vec![
+            Frame::Request(Action::Tell("token".into())),
+            Frame::Request(Action::Ask("token".into())),
+            Frame::Request(Action::Nask("token".into())),
+            Frame::Request(Action::Get("token".into())),
+            Frame::Response(true),
+            Frame::Response(false),
+            Frame::Error("oops".into()),
+            Frame::Ping,
+            Frame::Pong,
+            Frame::Forward(Action::Get("token".into())),
+            Frame::Replicate(Action::Tell("token".into())),
+            Frame::Digest(0xdead_beef),
+            Frame::Hello(42),
+            Frame::Post(Action::Tell("token".into())),
+            Frame::Ack,
+            Frame::Batch(vec![]),
+            Frame::Batch(vec![Action::Tell("token".into()), Action::Get("".into())]),
+            Frame::Results(vec![Frame::Response(true), Frame::Error("oops".into()), Frame::Response(false)]),
+            Frame::Compress(Compression::Lz4),
+            Frame::Authenticate("secret".into()),
+            Frame::Admin(AdminCommand::Clear),
+            Frame::Admin(AdminCommand::Stats),
+            Frame::Admin(AdminCommand::Snapshot),
+            Frame::Admin(AdminCommand::Peers),
+            Frame::Admin(AdminCommand::Import(vec![("a".into(), 2), ("b".into(), 1)])),
+            Frame::AdminReply(AdminReply::Done),
+            Frame::AdminReply(AdminReply::Stats(vec![("tokens".into(), 3)])),
+            Frame::AdminReply(AdminReply::Snapshot(vec![("a".into(), 1), ("b".into(), u32::MAX)])),
+            Frame::AdminReply(AdminReply::SnapshotPart { tokens: vec![("a".into(), 1)], last: false }),
+            Frame::AdminReply(AdminReply::SnapshotPart { tokens: vec![], last: true }),
+            Frame::AdminReply(AdminReply::Peers(vec![PeerInfo { name: "sensors".into(), addr: "127.0.0.1:2138".into(), alive: false }])),
+            Frame::Program("tell(token);get(token)".into()),
+            Frame::Causal(Stamp { node: "b".into(), clock: VectorClock::new() }, Action::Tell("token".into())),
+            Frame::Causal(Stamp { node: "b".into(), clock: [("a", 3), ("b", u64::MAX)].into_iter().fold(VectorClock::new(), |mut clock, (node, count)| {
+                clock.set(node, count);
+                clock
+            }) }, Action::Get("token".into())),
+            Frame::Reserve(2000, "token".into()),
+            Frame::Probe(500, "sensors".into()),
+            Frame::Vote(VoteRequest { term: 3, candidate: "b".into(), last_index: 12, last_term: 2 }),
+            Frame::Append(AppendRequest {
+                term: 3,
+                leader: "b".into(),
+                prev_index: 12,
+                prev_term: 2,
+                entries: vec![LogEntry { term: 3, action: Action::Tell("token".into()) }, LogEntry { term: 3, action: Action::Get("token".into()) }],
+                commit: 11,
+            }),
+            Frame::Append(AppendRequest { term: 3, leader: "b".into(), prev_index: 0, prev_term: 0, entries: vec![], commit: 0 }),
+            Frame::Term(3, true),
+            Frame::Snapshot(SnapshotRequest { term: 3, leader: "b".into(), last_index: 12, last_term: 2, first: true, last: false, tokens: vec![("a".into(), 2)] }),
+            Frame::Snapshot(SnapshotRequest { term: 3, leader: "b".into(), last_index: 12, last_term: 2, first: false, last: true, tokens: vec![] }),
+            Frame::Install(vec![("a".into(), 2), ("b".into(), 1)]),
+            Frame::Subscribe("msg(X)".into()),
+            Frame::Change(2, "msg(a)".into()),
+            Frame::Lease(u64::MAX),
+            Frame::Confirm(7),
+            Frame::Release(0),
+            Frame::Health,
+            Frame::HealthReport(Health { worker_alive: true, queue_depth: 3, store_size: u64::MAX }),
+            Frame::HealthReport(Health { worker_alive: false, queue_depth: 0, store_size: 0 }),
+            Frame::Sync(vec![]),
+            Frame::Sync(vec![
+                TokenVersion { token: "token".into(), occurrences: 2, clock: 7, node: "sensors".into() },
+                TokenVersion { token: "gone".into(), occurrences: 0, clock: 9, node: "actuators".into() },
+            ]),
+            Frame::Traced(trace::extract("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01", "bacht=1").unwrap(),
+                Box::new(Frame::Forward(Action::Ask("token".into())))),
+        ];
+        for frame in frames {
+            assert_eq!(Frame::decode(&frame.encode()).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn frame_should_refuse_unknown_kind() {
+        assert!(matches!(Frame::decode(&[0x7f]), Err(FrameError::Malformed(_))));
+        assert!(matches!(Frame::decode(&[]), Err(FrameError::Malformed(_))));
+        let traced = Frame::Traced(trace::extract("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01", "").unwrap(), Box::new(Frame::Ping)).encode();
+        // The kind and the trace context of the first frame, followed by the whole second one
+        let nested = [&traced[..59], &traced[..]].concat();
+        assert!(matches!(Frame::decode(&nested), Err(FrameError::Malformed(_))));
+        // The version ff is forbidden by the W3C trace context
+        let invalid = [&traced[..2], b"ff", &traced[4..]].concat();
+        assert!(matches!(Frame::decode(&invalid), Err(FrameError::Malformed(_))));
+        // Results nested deep enough to overflow the stack if they were decoded
+        let response = Frame::Response(true).encode();
+        let mut results = Vec::new();
+        for level in (0..100_000).rev() {
+            results.push(RESULTS_KIND);
+            results.extend(1u32.to_be_bytes());
+            results.extend(((response.len() + 9 * level) as u32).to_be_bytes());
+        }
+        results.extend(response);
+        assert!(matches!(Frame::decode(&results), Err(FrameError::Malformed(_))));
+        let nested = Frame::Results(vec![Frame::Results(vec![Frame::Response(true)])]).encode();
+        assert!(matches!(Frame::decode(&nested), Err(FrameError::Malformed(_))));
+    }
+
+    #[test]
+    fn tokens_should_be_split_in_parts_of_a_bounded_length() {
+        let tokens: Vec<(Box<str>, u32)> = (0..10).map(|i| (format!("token{}", i).into(), i)).collect();
+        let parts = token_parts(&tokens, 40);
+        assert_eq!(parts.len(), 5);
+        assert_eq!(parts.concat(), tokens);
+        assert!(parts.iter().all(|part| part.iter().map(|(token, _)| token.len() + 8).sum::<usize>() <= 40));
+        assert_eq!(token_parts(&[], 40), [&[] as &[(Box<str>, u32)]]);
+        assert_eq!(token_parts(&tokens[..1], 1).len(), 1, "A token too long should be a part of its own");
+    }
+
+    #[test]
+    fn snapshot_should_be_answered_in_parts_only_if_too_big_for_a_frame() {
+        let small: Vec<(Box<str>, u32)> = vec![("a".into(), 1)];
+        assert_eq!(snapshot_replies(small.clone()), [Frame::AdminReply(AdminReply::Snapshot(small))]);
+        let big: Vec<(Box<str>, u32)> = (0..300).map(|i| (format!("{:04}{}", i, "x".repeat(4096)).into(), i)).collect();
+        let replies = snapshot_replies(big.clone());
+        assert!(replies.len() > 1);
+        assert!(replies.iter().all(|reply| reply.encode().len() + CORRELATION_ID_LENGTH <= MAX_FRAME_LENGTH));
+        let mut joined = Vec::new();
+        for (i, reply) in replies.iter().enumerate() {
+            let Frame::AdminReply(AdminReply::SnapshotPart { tokens, last }) = reply else { panic!("Unexpected reply {:?}", reply) };
+            assert_eq!(*last, i + 1 == replies.len());
+            joined.extend(tokens.iter().cloned());
+        }
+        assert_eq!(joined, big);
+    }
+
+    #[test]
+    fn frame_should_refuse_truncated_versions() {
+        let bytes = Frame::Sync(vec![TokenVersion { token: "token".into(), occurrences: 1, clock: 1, node: "a".into() }]).encode();
+        assert!(matches!(Frame::decode(&bytes[..bytes.len() - 1]), Err(FrameError::Malformed(_))));
+        assert!(matches!(Frame::decode(&[0x08, 0x01]), Err(FrameError::Malformed(_))));
+    }
+
+    #[test]
+    fn frame_should_refuse_truncated_batch() {
+        let bytes = Frame::Batch(vec![Action::Tell("token".into())]).encode();
+        assert!(matches!(Frame::decode(&bytes[..bytes.len() - 1]), Err(FrameError::Malformed(_))));
+        assert!(matches!(Frame::decode(&[bytes.as_slice(), &[0]].concat()), Err(FrameError::Malformed(_))));
+    }
+
+    #[tokio::test]
+    async fn frame_should_be_read_back_from_a_stream_with_its_correlation_id() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        write_frame(&mut client, 42, &Frame::Request(Action::Tell("token".into()))).await.unwrap();
+        write_frame(&mut client, u64::MAX, &Frame::Ping).await.unwrap();
+        drop(client);
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some((42, Frame::Request(Action::Tell("token".into())))));
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some((u64::MAX, Frame::Ping)));
+        assert!(read_frame(&mut server).await.unwrap().is_none(), "A closed stream should yield no frame");
+    }
+
+    #[tokio::test]
+    async fn frame_should_be_read_and_written_through_reused_buffers() {
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        let (mut written, mut read) = (Vec::new(), BytesMut::new());
+        let big = Frame::Batch((0..200).map(|i| Action::Tell(format!("token{}", i).into())).collect());
+        let small = Frame::Request(Action::Get("token".into()));
+        for (id, frame) in [(1, &big), (2, &small), (3, &big)] {
+            write_frame_into(&mut client, id, frame, Compression::Lz4, &mut written).await.unwrap();
+            assert_eq!(read_frame_into(&mut server, &mut read).await.unwrap(), Some((id, frame.clone())));
+        }
+        assert!(read.capacity() >= written.len() - 4, "The buffer should be kept between the frames");
+    }
+
+    #[tokio::test]
+    async fn frame_should_refuse_missing_correlation_id() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client.write_all(&[0, 0, 0, 2, 0x04, 0x04]).await.unwrap();
+        assert!(matches!(read_frame(&mut server).await, Err(FrameError::Malformed(_))));
+    }
+
+    #[tokio::test]
+    async fn frame_should_compress_big_payloads_only() {
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        let big = Frame::Batch((0..200).map(|i| Action::Tell(format!("token{}", i % 10).into())).collect());
+        let small = Frame::Request(Action::Tell("token".into()));
+        write_frame_with(&mut client, 1, &big, Compression::Lz4).await.unwrap();
+        let mut length = [0u8; 4];
+        server.read_exact(&mut length).await.unwrap();
+        let mut body = vec![0u8; u32::from_be_bytes(length) as usize];
+        server.read_exact(&mut body).await.unwrap();
+        assert_eq!(body[CORRELATION_ID_LENGTH], BATCH_KIND | COMPRESSED_FLAG);
+        assert!(body.len() < big.encode().len() / 2, "The big frame should be compressed");
+
+        write_frame_with(&mut client, 1, &big, Compression::Lz4).await.unwrap();
+        write_frame_with(&mut client, 2, &small, Compression::Lz4).await.unwrap();
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some((1, big)));
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some((2, small)));
+    }
+
+    #[tokio::test]
+    async fn frame_should_be_replaced_by_an_error_when_too_long_to_write() {
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        let oversized = Frame::Program("tell(a);".repeat(MAX_FRAME_LENGTH / 8));
+        tokio::spawn(async move {
+            write_frame_with(&mut client, 7, &oversized, Compression::Lz4).await.unwrap();
+            write_frame(&mut client, 8, &Frame::Ping).await.unwrap();
+        });
+        let Some((7, Frame::Error(message))) = read_frame(&mut server).await.unwrap() else { panic!("An error should replace the frame") };
+        assert!(message.contains("exceeds the maximal length"), "{}", message);
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some((8, Frame::Ping)), "The stream should go on");
+    }
+
+    #[tokio::test]
+    async fn frame_should_refuse_oversized_length() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client.write_all(&(MAX_FRAME_LENGTH as u32 + 1).to_be_bytes()).await.unwrap();
+        assert!(matches!(read_frame(&mut server).await, Err(FrameError::TooLarge(_))));
+    }
+}
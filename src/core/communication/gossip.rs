@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+use std::time::Duration;
+use rand::Rng;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use crate::blackboard::BlackboardTrait;
+use crate::communication::frame::{read_frame, write_frame, Frame};
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::event::Event;
+use crate::model::health::Health;
+use crate::model::change::StoreChange;
+use tokio::sync::broadcast;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
+use crate::error::TransportError;
+use crate::model::token::TokenId;
+
+const DEFAULT_GOSSIP_PORT: u16 = 2140;
+
+/// @summary - The version of a token in a gossiped store.
+///
+/// When two nodes disagree on a token, the version with the highest clock wins, ties are broken by the highest node name.
+/// A token with no occurrence left keeps its version, so that its removal is gossiped too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenVersion {
+    pub token: Box<str>,
+    pub occurrences: u32,
+    /// The Lamport clock of the node when it wrote the token
+    pub clock: u64,
+    /// The name of the node that wrote the token
+    pub node: Box<str>,
+}
+
+impl TokenVersion {
+    fn is_newer_than(&self, other: &TokenVersion) -> bool {
+        (self.clock, &self.node) > (other.clock, &other.node)
+    }
+}
+
+struct GossipState {
+    clock: u64,
+    versions: HashMap<Box<str>, TokenVersion>,
+}
+
+/// @summary - The GossipBlackboard keeps track of the version of every token written on the local store, so that it can be reconciled with peers.
+///
+/// Every tell and successful get of the agents bumps the clock of the node and becomes the new version of the token.
+/// Merging the versions of a peer applies the newer ones on the local store, so that boards which keep exchanging
+/// their versions converge without a central primary.
+pub struct GossipBlackboard<B: BlackboardTrait> {
+    local: B,
+    node: Box<str>,
+    // Held while the local store is written, so that the versions always describe the store
+    state: Arc<tokio::sync::Mutex<GossipState>>,
+}
+
+impl<B: BlackboardTrait> GossipBlackboard<B> {
+
+    /// @summary - Track the versions of a local blackboard
+    ///
+    /// @param local - The blackboard applying the events on the local store, it must start empty
+    ///
+    /// @param node - The name of this node, it must be unique among the gossiping nodes
+    pub fn new_with(local: B, node: &str) -> Self {
+        Self {
+            local,
+            node: node.into(),
+            state: Arc::new(tokio::sync::Mutex::new(GossipState {
+                clock: 0,
+                versions: HashMap::new(),
+            })),
+        }
+    }
+
+    /// @returns - The version of every token written on the store, sorted by token
+    pub async fn versions(&self) -> Vec<TokenVersion> {
+        let mut versions: Vec<TokenVersion> = self.state.lock().await.versions.values().cloned().collect();
+        versions.sort_by(|a, b| a.token.cmp(&b.token));
+        versions
+    }
+
+    /// @returns - A hash of the versions, two nodes with the same digest don't need to be reconciled
+    pub async fn digest(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for version in self.versions().await {
+            (version.token, version.occurrences, version.clock, version.node).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// @summary - Apply the versions of a peer that are newer than the local ones
+    ///
+    /// @note - The local store is reconciled by telling or getting the missing or extra occurrences
+    pub async fn merge(&self, remote: Vec<TokenVersion>) {
+        let mut state = self.state.lock().await;
+        for version in remote {
+            state.clock = state.clock.max(version.clock);
+            let current = state.versions.get(&version.token);
+            if current.is_some_and(|current| !version.is_newer_than(current)) {
+                continue;
+            }
+            let occurrences = current.map_or(0, |current| current.occurrences);
+            let token = match TokenId::try_intern(&version.token) {
+                Ok(token) => token,
+                Err(e) => {
+                    log!(Level::Error, "Failed to reconcile {}: {}", version.token, e);
+                    continue;
+                }
+            };
+            for _ in occurrences..version.occurrences {
+                if let Err(e) = self.local.tell(token).await {
+                    log!(Level::Error, "Failed to reconcile {}: {:?}", version.token, e);
+                }
+            }
+            for _ in version.occurrences..occurrences {
+                if let Err(e) = self.local.get(token).await {
+                    log!(Level::Error, "Failed to reconcile {}: {:?}", version.token, e);
+                }
+            }
+            state.versions.insert(version.token.clone(), version);
+        }
+    }
+
+    async fn apply_write(&self, event: Event, token: Box<str>) -> Result<bool, StoreError> {
+        let mut state = self.state.lock().await;
+        let told = matches!(event.action, Action::Tell(_));
+        let result = self.local.send_event(event).await;
+        if let Ok(true) = result {
+            state.clock += 1;
+            let occurrences = state.versions.get(&token).map_or(0, |version| version.occurrences);
+            let version = TokenVersion {
+                token: token.clone(),
+                occurrences: if told { occurrences.saturating_add(1) } else { occurrences.saturating_sub(1) },
+                clock: state.clock,
+                node: self.node.clone(),
+            };
+            state.versions.insert(token, version);
+        }
+        result
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send> BlackboardTrait for GossipBlackboard<B> {
+
+    fn new() -> Self {
+        Self::new_with(B::new(), &format!("node-{}", rand::random::<u32>()))
+    }
+
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        match &event.action {
+            Action::Tell(token) | Action::Get(token) => {
+                // The versions are gossiped by name
+                let token = Box::<str>::from(*token);
+                self.apply_write(event, token).await
+            },
+            _ => self.local.send_event(event).await,
+        }
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        self.local.admin(command).await
+    }
+
+    fn health(&self) -> Health {
+        self.local.health()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.local.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            node: self.node.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// @summary - Configuration of the anti-entropy protocol.
+///
+/// Every `interval`, the node picks one of `peers` at random and reconciles its store with it.
+#[derive(Debug, Clone)]
+pub struct GossipConfig {
+    pub bind: SocketAddr,
+    /// The gossip addresses of the other nodes
+    pub peers: Vec<String>,
+    pub interval: Duration,
+}
+
+impl GossipConfig {
+    pub fn new(peers: Vec<String>) -> Self {
+        Self {
+            bind: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_GOSSIP_PORT)),
+            peers,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// @summary - The Gossip periodically reconciles the store of this node with the ones of its peers.
+///
+/// A round sends the digest of the local versions; if the peer's digest differs, both nodes exchange their versions and merge them.
+pub struct Gossip<B: BlackboardTrait> {
+    config: GossipConfig,
+    listener: TcpListener,
+    blackboard: GossipBlackboard<B>,
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> Gossip<B> {
+
+    pub async fn bind(config: GossipConfig, blackboard: GossipBlackboard<B>) -> Result<Self, TransportError> {
+        let listener = TcpListener::bind(config.bind).await.map_err(|e| TransportError::BindFailed(format!("Failed to bind gossip socket: {}", e)))?;
+        Ok(Self {
+            config,
+            listener,
+            blackboard,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+        self.listener.local_addr().map_err(|e| TransportError::Io(e.to_string()))
+    }
+
+    /// @summary - Start gossiping with the peers and answering their rounds
+    ///
+    /// @returns - The handles of the gossiping task and of the answering task
+    pub fn spawn(self) -> (JoinHandle<()>, JoinHandle<()>) {
+        let blackboard = self.blackboard.clone();
+        let config = self.config.clone();
+        let gossiper = tokio::spawn(async move {
+            loop {
+                sleep(config.interval).await;
+                if config.peers.is_empty() {
+                    continue;
+                }
+                let peer = &config.peers[rand::rng().random_range(..config.peers.len())];
+                match timeout(config.interval, round(&blackboard, peer)).await {
+                    Ok(Ok(_)) => {},
+                    Ok(Err(e)) => log!(Level::Error, "Gossip with {} failed: {}", peer, e),
+                    Err(_) => log!(Level::Error, "Gossip with {} timed out", peer),
+                }
+            }
+        });
+        let responder = tokio::spawn(async move {
+            loop {
+                match self.listener.accept().await {
+                    Ok((stream, _)) => {
+                        let blackboard = self.blackboard.clone();
+                        tokio::spawn(async move {
+                            answer(stream, blackboard).await.unwrap_or_else(|e| log!(Level::Error, "Error answering gossip: {}", e));
+                        });
+                    },
+                    Err(e) => log!(Level::Error, "Failed to accept gossip connection: {}", e),
+                }
+            }
+        });
+        (gossiper, responder)
+    }
+}
+
+/// @summary - Reconcile the local store with the one of a peer
+///
+/// @param peer - The gossip address of the peer
+pub async fn round<B: BlackboardTrait>(blackboard: &GossipBlackboard<B>, peer: &str) -> Result<(), TransportError> {
+    let mut stream = TcpStream::connect(peer).await.map_err(|e| TransportError::ConnectionFailed(format!("Failed to connect: {}", e)))?;
+    // A round is a sequence of exchanges, their correlation ids are the steps of the round
+    write_frame(&mut stream, 0, &Frame::Digest(blackboard.digest().await)).await.map_err(TransportError::from)?;
+    match read_frame(&mut stream).await.map_err(TransportError::from)? {
+        Some((0, Frame::Response(true))) => return Ok(()),
+        Some((0, Frame::Response(false))) => {},
+        other => return Err(TransportError::ProtocolError(format!("Unexpected answer to digest: {:?}", other))),
+    }
+    write_frame(&mut stream, 1, &Frame::Sync(blackboard.versions().await)).await.map_err(TransportError::from)?;
+    match read_frame(&mut stream).await.map_err(TransportError::from)? {
+        Some((1, Frame::Sync(versions))) => {
+            blackboard.merge(versions).await;
+            Ok(())
+        },
+        other => Err(TransportError::ProtocolError(format!("Unexpected answer to sync: {:?}", other))),
+    }
+}
+
+/// @summary - Answer the rounds of a peer until it closes the connection
+async fn answer<B: BlackboardTrait>(mut stream: TcpStream, blackboard: GossipBlackboard<B>) -> Result<(), TransportError> {
+    while let Some((id, frame)) = read_frame(&mut stream).await.map_err(TransportError::from)? {
+        let response = match frame {
+            Frame::Digest(digest) => Frame::Response(digest == blackboard.digest().await),
+            Frame::Sync(versions) => {
+                blackboard.merge(versions).await;
+                Frame::Sync(blackboard.versions().await)
+            },
+            other => Frame::Error(format!("Unexpected frame: {:?}", other)),
+        };
+        write_frame(&mut stream, id, &response).await.map_err(TransportError::from)?;
+    }
+    Ok(())
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::create_blackboard;
+
+    fn version(token: &str, occurrences: u32, clock: u64, node: &str) -> TokenVersion {
+        TokenVersion { token: token.into(), occurrences, clock, node: node.into() }
+    }
+
+    #[tokio::test]
+    async fn gossip_should_version_the_local_writes() {
+        let board = GossipBlackboard::new_with(create_blackboard(), "a");
+        assert!(board.tell("x".into()).await.unwrap());
+        assert!(board.tell("x".into()).await.unwrap());
+        assert!(board.get("x".into()).await.unwrap());
+        assert!(!board.get("y".into()).await.unwrap());
+        assert!(board.ask("x".into()).await.unwrap());
+        assert_eq!(board.versions().await, vec![version("x", 1, 3, "a")]);
+    }
+
+    #[tokio::test]
+    async fn gossip_should_merge_only_newer_versions() {
+        let board = GossipBlackboard::new_with(create_blackboard(), "a");
+        assert!(board.tell("x".into()).await.unwrap());
+        board.merge(vec![version("x", 3, 5, "b"), version("y", 0, 2, "b")]).await;
+        assert_eq!(board.versions().await[0], version("x", 3, 5, "b"));
+        assert!(board.get("x".into()).await.unwrap());
+        assert!(board.get("x".into()).await.unwrap());
+        assert!(board.get("x".into()).await.unwrap());
+        assert!(!board.get("x".into()).await.unwrap(), "The store should hold the merged occurrences");
+
+        board.merge(vec![version("x", 4, 5, "a")]).await;
+        assert!(!board.ask("x".into()).await.unwrap(), "An older version should be ignored");
+        assert!(board.nask("y".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn gossip_should_make_peers_converge() {
+        let config = GossipConfig { bind: "127.0.0.1:0".parse().unwrap(), interval: Duration::from_millis(20), ..GossipConfig::new(vec![]) };
+        let a = GossipBlackboard::new_with(create_blackboard(), "a");
+        let b = GossipBlackboard::new_with(create_blackboard(), "b");
+        let gossip_b = Gossip::bind(config.clone(), b.clone()).await.unwrap();
+        let addr_b = gossip_b.local_addr().unwrap().to_string();
+        gossip_b.spawn();
+        Gossip::bind(GossipConfig { peers: vec![addr_b], ..config }, a.clone()).await.unwrap().spawn();
+
+        assert!(a.tell("x".into()).await.unwrap());
+        assert!(b.tell("y".into()).await.unwrap());
+        assert!(b.tell("y".into()).await.unwrap());
+        timeout(Duration::from_secs(2), async {
+            while a.digest().await != b.digest().await {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("The peers should converge");
+        assert!(b.ask("x".into()).await.unwrap());
+        assert!(a.get("y".into()).await.unwrap());
+        assert!(a.get("y".into()).await.unwrap());
+        assert!(!a.get("y".into()).await.unwrap());
+    }
+}
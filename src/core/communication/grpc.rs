@@ -0,0 +1,162 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use futures::{Stream, stream};
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Request, Response, Status};
+use crate::blackboard::BlackboardTrait;
+use crate::communication::socket_listener::matching;
+use crate::communication::tenants::same_key;
+use crate::error::TransportError;
+use crate::language::parser::parse_term;
+use crate::model::change::StoreChange;
+
+/// @summary - The messages and the service of proto/bacht.proto, with the client of the service
+pub mod proto {
+    tonic::include_proto!("bacht");
+}
+
+use proto::blackboard_server::{Blackboard, BlackboardServer};
+
+/// @summary - The GrpcServer serves the gRPC service of proto/bacht.proto on a blackboard node, for the external services
+/// reacting to its store without speaking the socket protocol.
+///
+/// `Subscribe(pattern)` streams the changes of the tokens matching an SI-Term, as the Subscribe frame of the socket
+/// protocol does. The calls present a key in their `authorization` metadata, as `Bearer <key>`: the ones without a key
+/// the server accepts are refused with UNAUTHENTICATED.
+pub struct GrpcServer<B: BlackboardTrait> {
+    blackboard: B,
+    address: IpAddr,
+    port: u16,
+    keys: Vec<String>,
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> GrpcServer<B> {
+
+    pub fn new(blackboard: B, port: u16) -> Self {
+        Self {
+            blackboard,
+            address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            keys: Vec::new(),
+        }
+    }
+
+    /// @summary - Bind another address than the IPv4 loopback, e.g. to be reachable by the other services
+    pub fn with_address(mut self, address: IpAddr) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// @summary - Accept the calls presenting this key, e.g. the admin key of the node
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.keys.push(key.to_string());
+        self
+    }
+
+    /// @summary - Serve the calls until the future is dropped
+    ///
+    /// @returns - An error if the port cannot be bound
+    pub async fn serve(&self) -> Result<(), TransportError> {
+        let service = BlackboardService { blackboard: self.blackboard.clone(), keys: self.keys.clone().into() };
+        tonic::transport::Server::builder()
+            .add_service(BlackboardServer::new(service))
+            .serve(SocketAddr::new(self.address, self.port)).await
+            .map_err(|e| TransportError::BindFailed(format!("Failed to serve gRPC: {}", e)))
+    }
+}
+
+struct BlackboardService<B: BlackboardTrait> {
+    blackboard: B,
+    keys: Arc<[String]>,
+}
+
+impl<B: BlackboardTrait> BlackboardService<B> {
+
+    // The key of the authorization metadata of a call, if the server accepts it
+    fn authorized<T>(&self, request: &Request<T>) -> bool {
+        let key = request.metadata().get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        key.is_some_and(|key| self.keys.iter().any(|expected| same_key(expected, key)))
+    }
+}
+
+#[tonic::async_trait]
+impl<B: BlackboardTrait + Sync + Send + 'static> Blackboard for BlackboardService<B> {
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<proto::StoreChange, Status>> + Send>>;
+
+    /// @note - A subscription missing changes, its client reading them too slowly, ends with DATA_LOSS
+    async fn subscribe(&self, request: Request<proto::SubscribeRequest>) -> Result<Response<Self::SubscribeStream>, Status> {
+        if !self.authorized(&request) {
+            return Err(Status::unauthenticated("A key is required to subscribe"));
+        }
+        let pattern = parse_term(&request.get_ref().pattern).map_err(|e| Status::invalid_argument(format!("Invalid pattern: {}", e)))?;
+        let changes = stream::unfold(Some((self.blackboard.subscribe(), pattern)), |subscription| async move {
+            let (mut changes, pattern) = subscription?;
+            loop {
+                match changes.recv().await {
+                    Ok(StoreChange { token, count }) if matching(&pattern, &token) => {
+                        return Some((Ok(proto::StoreChange { token: token.to_string(), count }), Some((changes, pattern))));
+                    },
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => return Some((Err(Status::data_loss(format!("The subscription missed {} changes", missed))), None)),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(changes)))
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use futures::StreamExt;
+    use tokio::net::TcpListener;
+    use tonic::Code;
+    use tonic::transport::Channel;
+    use proto::blackboard_client::BlackboardClient;
+    use crate::blackboard::create_blackboard;
+
+    fn subscription(pattern: &str, key: Option<&str>) -> Request<proto::SubscribeRequest> {
+        let mut request = Request::new(proto::SubscribeRequest { pattern: pattern.to_string() });
+        if let Some(key) = key {
+            request.metadata_mut().insert("authorization", format!("Bearer {}", key).parse().unwrap());
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn grpc_server_should_stream_the_changes_matching_the_pattern() {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let blackboard = create_blackboard();
+        let server = GrpcServer::new(blackboard.clone(), port).with_key("secret");
+        tokio::spawn(async move { server.serve().await });
+        let mut client = loop {
+            match BlackboardClient::<Channel>::connect(format!("http://127.0.0.1:{}", port)).await {
+                Ok(client) => break client,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+
+        assert_eq!(client.subscribe(subscription("X", None)).await.unwrap_err().code(), Code::Unauthenticated);
+        assert_eq!(client.subscribe(subscription("X", Some("wrong"))).await.unwrap_err().code(), Code::Unauthenticated);
+        assert_eq!(client.subscribe(subscription("msg(X", Some("secret"))).await.unwrap_err().code(), Code::InvalidArgument);
+
+        let mut changes = client.subscribe(subscription("msg(X)", Some("secret"))).await.unwrap().into_inner();
+        blackboard.tell("other".into()).await.unwrap();
+        blackboard.tell("msg(a)".into()).await.unwrap();
+        blackboard.get("msg(a)".into()).await.unwrap();
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let change = changes.next().await.unwrap().unwrap();
+            received.push((change.token, change.count));
+        }
+        assert_eq!(received, [("msg(a)".to_string(), 1), ("msg(a)".to_string(), 0)]);
+    }
+}
@@ -0,0 +1,181 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use crate::blackboard::BlackboardTrait;
+use crate::model::health::Health;
+use crate::log;
+use crate::metrics;
+use crate::log::Level;
+use crate::error::TransportError;
+
+/// Depth of the queue above which a node is reported as not ready
+pub const DEFAULT_MAX_QUEUE_DEPTH: u64 = 1024;
+
+// The probes send small requests, anything bigger is not a probe
+const MAX_REQUEST_LENGTH: usize = 4096;
+
+/// @summary - The HealthServer answers the HTTP probes of an orchestrator (e.g. Kubernetes) about a blackboard node.
+///
+/// `GET /healthz` is the liveness probe, answered by 200 while the worker runs. `GET /readyz` is the readiness probe,
+/// answered by 200 while the worker runs and the queue is not deeper than the max queue depth. Both answer 503 otherwise,
+/// with the health of the node as a JSON body. `GET /metrics` is scraped by Prometheus, see metrics::render.
+///
+/// @note - The same health is available on the blackboard protocol, see SocketClient::health
+pub struct HealthServer<B: BlackboardTrait> {
+    blackboard: B,
+    address: IpAddr,
+    port: u16,
+    max_queue_depth: u64,
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> HealthServer<B> {
+
+    pub fn new(blackboard: B, port: u16) -> Self {
+        Self {
+            blackboard,
+            address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            max_queue_depth: DEFAULT_MAX_QUEUE_DEPTH,
+        }
+    }
+
+    /// @summary - Bind another address than the IPv4 loopback, e.g. to be reachable by the orchestrator
+    pub fn with_address(mut self, address: IpAddr) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn with_max_queue_depth(mut self, max_queue_depth: u64) -> Self {
+        self.max_queue_depth = max_queue_depth;
+        self
+    }
+
+    /// @summary - Answer the probes until the future is dropped
+    ///
+    /// @returns - An error if the port cannot be bound
+    pub async fn serve(&self) -> Result<(), TransportError> {
+        let listener = TcpListener::bind(SocketAddr::new(self.address, self.port)).await
+            .map_err(|e| TransportError::BindFailed(format!("Failed to bind health port: {}", e)))?;
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    log!(Level::Error, "Error accepting health probe: {}", e);
+                    continue;
+                }
+            };
+            let blackboard = self.blackboard.clone();
+            let max_queue_depth = self.max_queue_depth;
+            tokio::spawn(async move {
+                if let Err(e) = answer_probe(stream, blackboard.health(), max_queue_depth).await {
+                    log!(Level::Error, "Error answering health probe: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn answer_probe(mut stream: TcpStream, health: Health, max_queue_depth: u64) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 512];
+    while !request.windows(4).any(|end| end == b"\r\n\r\n") && request.len() < MAX_REQUEST_LENGTH {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let request_line = String::from_utf8_lossy(&request).lines().next().unwrap_or_default().to_string();
+    let (status, content_type, body) = match request_line.split_whitespace().take(2).eq(["GET", "/metrics"]) {
+        true => ("200 OK", metrics::CONTENT_TYPE, metrics::render(&health)),
+        false => {
+            let (status, body) = probe(&request_line, health, max_queue_depth);
+            (status, "application/json", body)
+        },
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// @summary - Answer a probe
+///
+/// @param request_line - The first line of the HTTP request, e.g. `GET /readyz HTTP/1.1`
+///
+/// @returns - The status line and the body of the response
+fn probe(request_line: &str, health: Health, max_queue_depth: u64) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let healthy = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/healthz")) => health.is_live(),
+        (Some("GET"), Some("/readyz")) => health.is_ready(max_queue_depth),
+        (Some("GET"), _) => return ("404 Not Found", String::new()),
+        _ => return ("405 Method Not Allowed", String::new()),
+    };
+    let body = format!(
+        "{{\"worker_alive\":{},\"queue_depth\":{},\"store_size\":{}}}",
+        health.worker_alive, health.queue_depth, health.store_size
+    );
+    match healthy {
+        true => ("200 OK", body),
+        false => ("503 Service Unavailable", body),
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::time::sleep;
+    use super::*;
+    use crate::blackboard::create_blackboard;
+
+    fn health(worker_alive: bool, queue_depth: u64) -> Health {
+        Health { worker_alive, queue_depth, store_size: 2 }
+    }
+
+    #[test]
+    fn probe_should_report_liveness_and_readiness() {
+        assert_eq!(probe("GET /healthz HTTP/1.1", health(true, 10), 5).0, "200 OK");
+        assert_eq!(probe("GET /readyz HTTP/1.1", health(true, 5), 5).0, "200 OK");
+        assert_eq!(probe("GET /readyz HTTP/1.1", health(true, 6), 5).0, "503 Service Unavailable", "An overloaded node should not be ready");
+        assert_eq!(probe("GET /healthz HTTP/1.1", health(false, 0), 5).0, "503 Service Unavailable");
+        assert_eq!(probe("GET /readyz HTTP/1.1", health(false, 0), 5).0, "503 Service Unavailable");
+        assert_eq!(probe("GET / HTTP/1.1", health(true, 0), 5).0, "404 Not Found");
+        assert_eq!(probe("POST /healthz HTTP/1.1", health(true, 0), 5).0, "405 Method Not Allowed");
+        assert_eq!(probe("", health(true, 0), 5).0, "405 Method Not Allowed");
+        assert_eq!(probe("GET /healthz HTTP/1.1", health(true, 1), 5).1, "{\"worker_alive\":true,\"queue_depth\":1,\"store_size\":2}");
+    }
+
+    #[tokio::test]
+    async fn health_server_should_answer_http_probes() {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let blackboard = create_blackboard();
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        let server = HealthServer::new(blackboard, port);
+        tokio::spawn(async move { server.serve().await });
+
+        let mut stream = loop {
+            match TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => break stream,
+                Err(_) => sleep(Duration::from_millis(10)).await,
+            }
+        };
+        stream.write_all(b"GET /readyz HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "Unexpected response: {}", response);
+        assert!(response.ends_with("{\"worker_alive\":true,\"queue_depth\":0,\"store_size\":1}"), "Unexpected response: {}", response);
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n"), "Unexpected response: {}", response);
+        assert!(response.contains("\nbacht_store_size 1\n"), "Unexpected response: {}", response);
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+/// @summary - Configuration of the heartbeats exchanged on a connection.
+///
+/// A Ping is sent every `interval`; a peer that stays silent for `max_missed` intervals is considered dead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeartbeatConfig {
+    pub interval: Duration,
+    pub max_missed: u32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            max_missed: 3,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+
+    /// @returns - How long a peer may stay silent before being considered dead
+    pub fn timeout(&self) -> Duration {
+        self.interval.saturating_mul(self.max_missed)
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heartbeat_timeout_should_cover_all_missed_intervals() {
+        let config = HeartbeatConfig { interval: Duration::from_millis(100), max_missed: 3 };
+        assert_eq!(config.timeout(), Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+use crate::blackboard::BlackboardTrait;
+use crate::model::action::Action;
+use crate::model::event::Event;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
+use crate::model::token::TokenId;
+
+/// Default duration of the leases taken by a federated blackboard on its peers
+pub const DEFAULT_LEASE_DURATION: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LeaseState {
+    Held,
+    Confirmed,
+    Released,
+}
+
+struct Lease {
+    token: TokenId,
+    state: LeaseState,
+}
+
+/// @summary - The LeaseTable reserves occurrences of the store for the peers, the first phase of a remote get.
+///
+/// A reserved occurrence is taken out of the store, so that no other agent or peer can consume it, and held by a lease
+/// until the requester confirms it (the get succeeded) or releases it (the occurrence goes back to the store). A lease
+/// that is neither confirmed nor released before it expires is released, e.g. when the requester crashed or lost the
+/// connection in the meantime.
+///
+/// Confirming or releasing a lease twice has the same outcome as the first time, so that a requester may resend them.
+///
+/// It can be cloned in order to share the same leases between the connections of a listener.
+#[derive(Clone, Default)]
+pub struct LeaseTable {
+    leases: Arc<Mutex<HashMap<u64, Lease>>>,
+}
+
+impl LeaseTable {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// @summary - Take an occurrence of a token out of the store, and hold it by a new lease
+    ///
+    /// @param duration - How long the lease is held before it is released
+    ///
+    /// @returns - The identifier of the lease, or None if the token is absent from the store
+    pub async fn reserve<B>(&self, blackboard: &B, token: TokenId, duration: Duration) -> Result<Option<u64>, StoreError>
+    where B: BlackboardTrait + Sync + Send + 'static {
+        if !blackboard.send_event(Event::forwarded(Action::Get(token))).await? {
+            return Ok(None);
+        }
+        let id = {
+            let mut leases = self.leases.lock().unwrap();
+            loop {
+                if let Entry::Vacant(entry) = leases.entry(rand::random::<u64>()) {
+                    let id = *entry.key();
+                    entry.insert(Lease { token, state: LeaseState::Held });
+                    break id;
+                }
+            }
+        };
+        let table = self.clone();
+        let blackboard = blackboard.clone();
+        tokio::spawn(async move {
+            sleep(duration).await;
+            let expired = table.leases.lock().unwrap().remove(&id);
+            if let Some(Lease { token, state: LeaseState::Held }) = expired {
+                if let Err(e) = blackboard.send_event(Event::forwarded(Action::Tell(token))).await {
+                    log!(Level::Error, "Failed to restore the occurrence of lease {:x}: {:?}", id, e);
+                }
+            }
+        });
+        Ok(Some(id))
+    }
+
+    /// @summary - Consume the occurrence held by a lease
+    ///
+    /// @returns - false if the lease expired or was released
+    pub fn confirm(&self, id: u64) -> bool {
+        match self.leases.lock().unwrap().get_mut(&id) {
+            Some(lease) if lease.state != LeaseState::Released => {
+                lease.state = LeaseState::Confirmed;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// @summary - Put the occurrence held by a lease back into the store
+    ///
+    /// @returns - false if the lease expired or was confirmed
+    pub async fn release<B: BlackboardTrait>(&self, blackboard: &B, id: u64) -> Result<bool, StoreError> {
+        let token = match self.leases.lock().unwrap().get_mut(&id) {
+            Some(lease) if lease.state == LeaseState::Held => {
+                lease.state = LeaseState::Released;
+                lease.token
+            },
+            Some(lease) => return Ok(lease.state == LeaseState::Released),
+            None => return Ok(false),
+        };
+        blackboard.send_event(Event::forwarded(Action::Tell(token))).await?;
+        Ok(true)
+    }
+
+    /// @returns - The number of leases not expired yet, whatever their state
+    pub fn len(&self) -> usize {
+        self.leases.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::create_blackboard;
+
+    #[tokio::test]
+    async fn lease_table_should_hold_an_occurrence_until_it_is_confirmed_or_released() {
+        let blackboard = create_blackboard();
+        let leases = LeaseTable::new();
+        blackboard.tell("token".into()).await.unwrap();
+        assert_eq!(leases.reserve(&blackboard, "absent".into(), DEFAULT_LEASE_DURATION).await.unwrap(), None);
+
+        let confirmed = leases.reserve(&blackboard, "token".into(), DEFAULT_LEASE_DURATION).await.unwrap().unwrap();
+        assert!(blackboard.nask("token".into()).await.unwrap(), "A reserved occurrence should be out of the store");
+        assert!(leases.confirm(confirmed));
+        assert!(leases.confirm(confirmed), "A confirmation may be resent");
+        assert!(!leases.release(&blackboard, confirmed).await.unwrap());
+        assert!(blackboard.nask("token".into()).await.unwrap());
+
+        blackboard.tell("token".into()).await.unwrap();
+        let released = leases.reserve(&blackboard, "token".into(), DEFAULT_LEASE_DURATION).await.unwrap().unwrap();
+        assert!(leases.release(&blackboard, released).await.unwrap());
+        assert!(leases.release(&blackboard, released).await.unwrap(), "A release may be resent");
+        assert!(!leases.confirm(released));
+        assert!(blackboard.get("token".into()).await.unwrap(), "A released occurrence should be back in the store once");
+        assert!(blackboard.nask("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn lease_table_should_restore_the_occurrence_of_an_expired_lease() {
+        let blackboard = create_blackboard();
+        let leases = LeaseTable::new();
+        blackboard.tell("token".into()).await.unwrap();
+        let lease = leases.reserve(&blackboard, "token".into(), Duration::from_millis(50)).await.unwrap().unwrap();
+        sleep(Duration::from_millis(200)).await;
+        assert!(leases.is_empty());
+        assert!(!leases.confirm(lease), "An expired lease can't be confirmed");
+        assert!(blackboard.ask("token".into()).await.unwrap(), "The occurrence should be back in the store");
+    }
+}
@@ -0,0 +1,141 @@
+use tokio::task::JoinSet;
+use crate::blackboard::BlackboardTrait;
+use crate::communication::dedup::DedupWindow;
+use crate::communication::lease::LeaseTable;
+use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+use crate::error::TransportError;
+
+/// @summary - The Listeners attach several listeners to the same blackboard, e.g. TCP on two ports plus a Unix socket.
+///
+/// Each listener keeps its own configuration, but they share the deduplication of the resent requests,
+/// so that a client may reconnect through any of them, and the occurrences reserved by the peers.
+pub struct Listeners<B: BlackboardTrait> {
+    listeners: Vec<SocketListener<B>>,
+    dedup: DedupWindow,
+    leases: LeaseTable,
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> Default for Listeners<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> Listeners<B> {
+
+    pub fn new() -> Self {
+        Self {
+            listeners: Vec::new(),
+            dedup: DedupWindow::default(),
+            leases: LeaseTable::default(),
+        }
+    }
+
+    /// @param listener - A listener serving a clone of the blackboard served by the others
+    pub fn with_listener(mut self, listener: SocketListener<B>) -> Self {
+        self.listeners.push(listener.with_dedup(self.dedup.clone()).with_leases(self.leases.clone()));
+        self
+    }
+
+    /// @summary - Start all the listeners
+    ///
+    /// @returns - The handle controlling the lifecycle of the listeners
+    pub fn spawn(self) -> ListenersHandle {
+        let mut tasks = JoinSet::new();
+        for listener in self.listeners {
+            tasks.spawn(async move { listener.listen().await });
+        }
+        ListenersHandle { tasks }
+    }
+}
+
+/// @summary - The lifecycle of a group of listeners: dropping it (or shutting it down) stops all of them
+pub struct ListenersHandle {
+    tasks: JoinSet<Result<(), TransportError>>,
+}
+
+impl ListenersHandle {
+
+    /// @returns - The number of listeners still running
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// @summary - Wait until one of the listeners stops, e.g. because it failed to bind its address
+    ///
+    /// @returns - The error of the stopped listener, or Ok if no listener runs
+    pub async fn wait(&mut self) -> Result<(), TransportError> {
+        match self.tasks.join_next().await {
+            Some(Ok(result)) => result,
+            Some(Err(e)) => Err(TransportError::Io(format!("Listener crashed: {}", e))),
+            None => Ok(()),
+        }
+    }
+
+    /// @summary - Stop all the listeners and close their connections
+    pub async fn shutdown(mut self) {
+        self.tasks.shutdown().await;
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::time::{sleep, timeout};
+    use super::*;
+    use crate::blackboard::create_blackboard;
+    use crate::communication::frame::read_frame;
+    use crate::communication::socket_client::{ReconnectPolicy, SocketClient, SocketClientTrait};
+
+    async fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port()
+    }
+
+    async fn wait_for(port: u16) -> TcpStream {
+        loop {
+            match TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => return stream,
+                Err(_) => sleep(Duration::from_millis(10)).await,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn listeners_should_serve_the_same_blackboard_until_shutdown() {
+        let (first, second) = (free_port().await, free_port().await);
+        let blackboard = create_blackboard();
+        let handle = Listeners::new()
+            .with_listener(SocketListener::new(blackboard.clone(), Some(first)))
+            .with_listener(SocketListener::new(blackboard.clone(), Some(second)))
+            .spawn();
+        assert_eq!(handle.len(), 2);
+
+        let mut open = wait_for(first).await;
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", first), ReconnectPolicy::default()).await.unwrap();
+        assert!(client.tell("token".into()).await.unwrap());
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", second), ReconnectPolicy::default()).await.unwrap();
+        assert!(client.get("token".into()).await.unwrap(), "Both listeners should serve the same blackboard");
+
+        handle.shutdown().await;
+        assert!(TcpStream::connect(("127.0.0.1", second)).await.is_err(), "The listeners should be closed");
+        let closed = timeout(Duration::from_secs(1), read_frame(&mut open)).await;
+        assert!(matches!(closed, Ok(Ok(None)) | Ok(Err(_))), "The connections should be closed");
+    }
+
+    #[tokio::test]
+    async fn listeners_should_report_a_listener_failing_to_bind() {
+        let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let mut handle = Listeners::new().with_listener(SocketListener::new(create_blackboard(), Some(port))).spawn();
+        assert!(handle.wait().await.is_err());
+        assert!(handle.is_empty());
+    }
+}
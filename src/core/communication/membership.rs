@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use rand::seq::SliceRandom;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{sleep, timeout};
+use crate::communication::heartbeat::HeartbeatConfig;
+use crate::communication::peers::{PeerStatus, PeerTable};
+use crate::communication::socket_client::{PendingPolicy, ReconnectPolicy, SocketClient};
+use crate::log;
+use crate::log::Level;
+
+/// @summary - Configuration of the probing of the members of the cluster.
+///
+/// Every `probe_interval`, a member is pinged; if it stays silent for `probe_timeout`, `indirect_probes` other members
+/// are asked to ping it. A member none of them could reach is suspected, and declared dead once it stayed silent for
+/// `suspicion_timeout`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MembershipConfig {
+    pub probe_interval: Duration,
+    pub probe_timeout: Duration,
+    pub indirect_probes: usize,
+    pub suspicion_timeout: Duration,
+}
+
+impl Default for MembershipConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval: Duration::from_secs(1),
+            probe_timeout: Duration::from_millis(500),
+            indirect_probes: 3,
+            suspicion_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+/// @summary - The Membership detects the crashed members of the cluster in the manner of SWIM, marking them dead in the
+/// routing table shared by the federation and the partitioning, so that they are skipped until they answer again.
+///
+/// The members are probed one after the other in a random order, each one once per round. A member that does not answer
+/// is probed indirectly through other members, so that a congested link between two nodes does not exclude a node
+/// the rest of the cluster reaches.
+///
+/// It can be cloned in order to share the same probes between the listeners answering the indirect probes.
+///
+/// @note - The members are the peers of the routing table, declared by the configuration or the discovery: each node
+/// probes its own peers, the verdicts are not disseminated
+#[derive(Clone)]
+pub struct Membership {
+    config: MembershipConfig,
+    peers: PeerTable,
+    // The connections probing the members, unlinked from the routing table which only the verdicts change
+    clients: Arc<Mutex<HashMap<Box<str>, Arc<SocketClient>>>>,
+    // The members that failed a probe, since when
+    suspects: Arc<Mutex<HashMap<Box<str>, Instant>>>,
+    // The members left to probe in the current round
+    round: Arc<Mutex<Vec<Box<str>>>>,
+}
+
+impl Membership {
+
+    /// @param peers - The routing table, whose peers are probed
+    pub fn new(peers: PeerTable) -> Self {
+        Self {
+            config: MembershipConfig::default(),
+            peers,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            suspects: Arc::new(Mutex::new(HashMap::new())),
+            round: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_config(mut self, config: MembershipConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> MembershipConfig {
+        self.config
+    }
+
+    pub fn peers(&self) -> &PeerTable {
+        &self.peers
+    }
+
+    /// @returns - false if the member is dead, or not a member
+    pub fn is_alive(&self, name: &str) -> bool {
+        self.peers.is_alive(name)
+    }
+
+    /// @returns - true if the member failed a probe, and was not declared dead yet
+    pub fn is_suspected(&self, name: &str) -> bool {
+        self.suspects.lock().unwrap().contains_key(name)
+    }
+
+    // The connection probing a member, a new one once its address changed
+    fn client(&self, name: &str) -> Option<Arc<SocketClient>> {
+        let addr = self.peers.resolve(name)?;
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get(name).filter(|client| client.addr() == addr) {
+            return Some(client.clone());
+        }
+        let policy = ReconnectPolicy {
+            max_attempts: Some(1),
+            pending: PendingPolicy::Fail,
+            ..ReconnectPolicy::default()
+        };
+        // A ping waits one interval, an indirect probe two: the time the member takes to ping, and the answer
+        let heartbeat = HeartbeatConfig { interval: self.config.probe_timeout, max_missed: 2 };
+        let client = Arc::new(SocketClient::new_with(&addr, policy).with_heartbeat(heartbeat));
+        clients.insert(name.into(), client.clone());
+        Some(client)
+    }
+
+    /// @summary - Ping a member directly
+    ///
+    /// @param limit - How long to wait for the answer, at most the probe timeout
+    ///
+    /// @returns - false if the member did not answer in time, or is not a member
+    pub async fn ping(&self, name: &str, limit: Duration) -> bool {
+        let Some(client) = self.client(name) else { return false };
+        matches!(timeout(limit, client.ping()).await, Ok(Ok(())))
+    }
+
+    /// @summary - Ping a member, then ask other alive members to ping it if it did not answer
+    ///
+    /// @returns - true if the member answered one of the probes
+    pub async fn probe(&self, name: &str) -> bool {
+        if self.ping(name, self.config.probe_timeout).await {
+            return true;
+        }
+        let mut helpers: Vec<Box<str>> = self.peers.alive_peers().into_iter()
+            .map(|(helper, _)| helper)
+            .filter(|helper| **helper != *name)
+            .collect();
+        helpers.shuffle(&mut rand::rng());
+        let mut probes = JoinSet::new();
+        for helper in helpers.into_iter().take(self.config.indirect_probes) {
+            let Some(client) = self.client(&helper) else { continue };
+            let (name, probe_timeout) = (name.to_string(), self.config.probe_timeout);
+            probes.spawn(async move { client.probe(&name, probe_timeout).await });
+        }
+        while let Some(answered) = probes.join_next().await {
+            if let Ok(Ok(true)) = answered {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// @summary - Probe the next member of the round, and apply the verdict to the routing table
+    ///
+    /// @returns - The member probed and its status after the probe, None if there is no member
+    pub async fn probe_next(&self) -> Option<(Box<str>, PeerStatus)> {
+        self.expire_suspects();
+        let name = self.next_member()?;
+        let answered = self.probe(&name).await;
+        let was_alive = self.peers.is_alive(&name);
+        if answered {
+            self.suspects.lock().unwrap().remove(&name);
+            if !was_alive {
+                log!(Level::Info, "Member {} answers again", name);
+            }
+            self.peers.mark_alive(&name);
+        } else if was_alive {
+            // Suspected from its first failed probe
+            self.suspects.lock().unwrap().entry(name.clone()).or_insert_with(|| {
+                log!(Level::Debug, "Member {} suspected", name);
+                Instant::now()
+            });
+        }
+        self.expire_suspects();
+        let status = if self.peers.is_alive(&name) { PeerStatus::Alive } else { PeerStatus::Dead };
+        Some((name, status))
+    }
+
+    // Declare dead the members suspected for longer than the suspicion timeout
+    fn expire_suspects(&self) {
+        let suspicion_timeout = self.config.suspicion_timeout;
+        self.suspects.lock().unwrap().retain(|name, since| {
+            if since.elapsed() < suspicion_timeout {
+                return true;
+            }
+            log!(Level::Info, "Member {} declared dead, silent for {:?}", name, since.elapsed());
+            self.peers.mark_dead(name);
+            false
+        });
+    }
+
+    // The members are probed in a random order, each one once before the next round
+    fn next_member(&self) -> Option<Box<str>> {
+        let mut round = self.round.lock().unwrap();
+        // The members removed from the table since the round started are skipped
+        while let Some(name) = round.pop() {
+            if self.peers.get(&name).is_some() {
+                return Some(name);
+            }
+        }
+        *round = self.peers.list().into_iter().map(|(name, _)| name).collect();
+        round.shuffle(&mut rand::rng());
+        round.pop()
+    }
+
+    /// @summary - Start probing a member every probe interval
+    ///
+    /// @returns - The handle of the probing task
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let membership = self.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(membership.config.probe_interval).await;
+                membership.probe_next().await;
+            }
+        })
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::create_blackboard;
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn membership_should_declare_dead_the_members_nobody_reaches() {
+        let config = MembershipConfig {
+            probe_interval: Duration::from_millis(10),
+            probe_timeout: Duration::from_millis(100),
+            indirect_probes: 1,
+            suspicion_timeout: Duration::ZERO,
+        };
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let peers = PeerTable::new();
+        peers.insert("up", &format!("127.0.0.1:{}", port));
+        // Nothing listens on the port 1
+        peers.insert("down", "127.0.0.1:1");
+        let membership = Membership::new(peers.clone()).with_config(config);
+        let listener = SocketListener::new(create_blackboard(), Some(port)).with_membership(Membership::new(peers.clone()).with_config(config));
+        let listening = tokio::spawn(async move { listener.listen().await });
+        while TcpStream::connect(("127.0.0.1", port)).await.is_err() {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let mut verdicts: Vec<(Box<str>, PeerStatus)> = vec![membership.probe_next().await.unwrap(), membership.probe_next().await.unwrap()];
+        verdicts.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(verdicts, [("down".into(), PeerStatus::Dead), ("up".into(), PeerStatus::Alive)]);
+        assert_eq!(peers.alive_peers(), [("up".into(), format!("127.0.0.1:{}", port))], "The dead member should leave the routing");
+
+        // The listener of up probes down on behalf of this node
+        let up = membership.client("up").unwrap();
+        assert_eq!(up.probe("down", config.probe_timeout).await.ok(), Some(false));
+        assert_eq!(up.probe("up", config.probe_timeout).await.ok(), Some(true));
+        assert!(up.probe("unknown", config.probe_timeout).await.is_err());
+
+        peers.insert("down", &format!("127.0.0.1:{}", port));
+        while membership.probe_next().await.unwrap().0.as_ref() != "down" {}
+        assert!(membership.is_alive("down"), "A member answering again should rejoin the routing");
+        listening.abort();
+    }
+}
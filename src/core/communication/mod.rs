@@ -1 +1,27 @@
-pub mod socket_listener;
\ No newline at end of file
+pub mod causal;
+pub mod compression;
+pub mod dedup;
+pub mod discovery;
+pub mod faults;
+pub mod federation;
+pub mod frame;
+pub mod gossip;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod heartbeat;
+pub mod lease;
+pub mod listeners;
+pub mod membership;
+#[cfg(feature = "nats")]
+pub mod nats;
+pub mod otlp;
+pub mod partition;
+pub mod peers;
+pub mod raft;
+pub mod reliable;
+pub mod replication;
+pub mod socket_client;
+pub mod socket_listener;
+pub mod tenants;
+pub mod transport;
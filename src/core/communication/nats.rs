@@ -0,0 +1,249 @@
+use std::time::Duration;
+use async_nats::{Client, Request, RequestErrorKind};
+use futures::StreamExt;
+use crate::blackboard::BlackboardTrait;
+use crate::communication::frame::Frame;
+use crate::communication::socket_client::{SocketClientTrait};
+use crate::communication::socket_listener::{apply_events, intern_events, Reply};
+use crate::model::action::Action;
+use crate::model::event::Event;
+use crate::log;
+use crate::log::Level;
+use crate::error::TransportError;
+use crate::model::token::TokenId;
+
+/// How long a NatsAgent waits for the answer of the blackboard by default
+pub const DEFAULT_NATS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// @summary - The NatsBridge serves a blackboard on a NATS subject.
+///
+/// The messages published on the subject carry the frames of the blackboard protocol (without the length and the
+/// correlation id, NATS delimits the messages and correlates the answers by their reply subject). Requests, posts
+/// and batches are applied on the blackboard and answered on the reply subject, if any.
+///
+/// @note - The connection to the NATS server is the client of async-nats, e.g. `async_nats::connect("127.0.0.1:4222")`
+pub struct NatsBridge<B: BlackboardTrait> {
+    blackboard: B,
+    client: Client,
+    subject: String,
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> NatsBridge<B> {
+
+    pub fn new(blackboard: B, client: Client, subject: &str) -> Self {
+        Self {
+            blackboard,
+            client,
+            subject: subject.to_string(),
+        }
+    }
+
+    /// @summary - Serve the messages of the subject until the connection closes, or the future is dropped
+    pub async fn serve(&self) -> Result<(), TransportError> {
+        let mut messages = self.client.subscribe(self.subject.clone()).await.map_err(|e| TransportError::BindFailed(format!("Failed to subscribe: {}", e)))?;
+        while let Some(message) = messages.next().await {
+            let blackboard = self.blackboard.clone();
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let answer = answer(&blackboard, &message.payload).await;
+                if let Some(reply) = message.reply {
+                    if let Err(e) = client.publish(reply.clone(), answer.encode().into()).await {
+                        log!(Level::Error, "Failed to answer on {}: {}", reply, e);
+                    }
+                }
+            });
+        }
+        Err(TransportError::ConnectionLost)
+    }
+}
+
+async fn answer<B: BlackboardTrait>(blackboard: &B, payload: &[u8]) -> Frame {
+    let (events, reply) = match Frame::decode(payload) {
+        Ok(Frame::Request(action)) => (intern_events(vec![action], Event::new), Reply::Response),
+        Ok(Frame::Post(action)) => (intern_events(vec![action], Event::new), Reply::Ack),
+        Ok(Frame::Batch(actions)) => (intern_events(actions, Event::new), Reply::Results),
+        Ok(Frame::Ping) => return Frame::Pong,
+        Ok(Frame::Health) => return Frame::HealthReport(blackboard.health()),
+        Ok(other) => return Frame::Error(format!("Unexpected frame: {:?}", other)),
+        Err(e) => return Frame::Error(format!("Invalid frame: {:?}", e)),
+    };
+    match events {
+        Ok(events) => apply_events(blackboard, events, reply).await,
+        Err(e) => Frame::Error(e.to_string()),
+    }
+}
+
+/// @summary - The NatsAgent sends actions to a blackboard served on a NATS subject by a NatsBridge
+pub struct NatsAgent {
+    client: Client,
+    subject: String,
+    timeout: Duration,
+}
+
+impl NatsAgent {
+
+    pub fn new(client: Client, subject: &str) -> Self {
+        Self {
+            client,
+            subject: subject.to_string(),
+            timeout: DEFAULT_NATS_TIMEOUT,
+        }
+    }
+
+    /// @summary - How long to wait for the answer of the blackboard, before failing with TransportError::PeerDead
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// @summary - Publish an action without waiting for it to be applied
+    ///
+    /// @note - Nothing tells whether a blackboard received it, see SocketClientTrait::send otherwise
+    pub async fn publish(&self, action: Action) -> Result<(), TransportError> {
+        self.client.publish(self.subject.clone(), Frame::Post(action.named()).encode().into()).await
+            .map_err(|_| TransportError::ConnectionLost)
+    }
+}
+
+impl SocketClientTrait for NatsAgent {
+
+    async fn send(&self, action: Action) -> Result<bool, TransportError> {
+        let request = Request::new().payload(Frame::Request(action.named()).encode().into()).timeout(Some(self.timeout));
+        let answer = match self.client.send_request(self.subject.clone(), request).await {
+            Ok(answer) => answer,
+            Err(e) if matches!(e.kind(), RequestErrorKind::TimedOut | RequestErrorKind::NoResponders) => return Err(TransportError::PeerDead),
+            Err(_) => return Err(TransportError::ConnectionLost),
+        };
+        match Frame::decode(&answer.payload) {
+            Ok(Frame::Response(result)) => Ok(result),
+            Ok(Frame::Error(message)) => Err(TransportError::RemoteError(message)),
+            Ok(other) => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+            Err(e) => Err(TransportError::ProtocolError(format!("Invalid frame: {:?}", e))),
+        }
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Tell(coord_data)).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Ask(coord_data)).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Get(coord_data)).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Nask(coord_data)).await
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+    use tokio::time::{sleep, timeout};
+    use super::*;
+    use crate::blackboard::create_blackboard;
+
+    type Routes = Arc<Mutex<Vec<(String, u64, UnboundedSender<Vec<u8>>)>>>;
+
+    /// @returns - true if the subject matches the subscribed one, its `*` matching one token and its `>` the rest
+    fn matches(subscribed: &str, subject: &str) -> bool {
+        let mut subject = subject.split('.');
+        for token in subscribed.split('.') {
+            match (token, subject.next()) {
+                (">", Some(_)) => return true,
+                ("*", Some(_)) => {},
+                (token, Some(other)) if token == other => {},
+                _ => return false,
+            }
+        }
+        subject.next().is_none()
+    }
+
+    /// Start a NATS server routing the messages to the subscriptions of the subjects they match, and return its address
+    async fn start_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let routes: Routes = Arc::new(Mutex::new(Vec::new()));
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let (reader, mut writer) = stream.into_split();
+                let (outgoing, mut outbox) = unbounded_channel::<Vec<u8>>();
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    writer.write_all(b"INFO {\"server_id\":\"fake\",\"max_payload\":1048576}\r\n").await.unwrap();
+                    while let Some(bytes) = outbox.recv().await {
+                        if writer.write_all(&bytes).await.is_err() { break }
+                    }
+                });
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(reader);
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let words: Vec<String> = line.split_whitespace().map(String::from).collect();
+                        match words.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+                            ["SUB", subject, sid] => routes.lock().unwrap().push((subject.to_string(), sid.parse().unwrap(), outgoing.clone())),
+                            ["UNSUB", sid] => routes.lock().unwrap().retain(|(_, route, to)| !(route.to_string() == *sid && to.same_channel(&outgoing))),
+                            ["PUB", subject, rest @ ..] => {
+                                let mut payload = vec![0; rest.last().unwrap().parse::<usize>().unwrap() + 2];
+                                reader.read_exact(&mut payload).await.unwrap();
+                                let reply = if rest.len() == 2 { format!("{} ", rest[0]) } else { String::new() };
+                                for (_, sid, to) in routes.lock().unwrap().iter().filter(|(route, _, _)| matches(route, subject)) {
+                                    let header = format!("MSG {} {} {}{}\r\n", subject, sid, reply, payload.len() - 2);
+                                    let _ = to.send([header.as_bytes(), &payload].concat());
+                                }
+                            },
+                            ["PING"] => { let _ = outgoing.send(b"PONG\r\n".to_vec()); },
+                            _ => {},
+                        }
+                        line.clear();
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn fake_server_should_match_the_wildcards_of_the_subscriptions() {
+        assert!(matches("bacht.board", "bacht.board") && !matches("bacht.board", "bacht.other"));
+        assert!(matches("_INBOX.abc.*", "_INBOX.abc.1") && !matches("_INBOX.abc.*", "_INBOX.abc.1.2"));
+        assert!(matches("bacht.>", "bacht.board.a") && !matches("bacht.>", "bacht"));
+    }
+
+    #[tokio::test]
+    async fn nats_agent_should_reach_a_blackboard_through_a_bridge() {
+        let addr = start_server().await;
+        let blackboard = create_blackboard();
+        let bridge = NatsBridge::new(blackboard.clone(), async_nats::connect(&addr).await.unwrap(), "bacht.board");
+        tokio::spawn(async move { bridge.serve().await });
+        // The subscription of the bridge must reach the server before the first request
+        sleep(Duration::from_millis(100)).await;
+
+        let agent = NatsAgent::new(async_nats::connect(&addr).await.unwrap(), "bacht.board");
+        assert!(agent.tell("token".into()).await.unwrap());
+        assert!(agent.ask("token".into()).await.unwrap());
+        assert!(agent.get("token".into()).await.unwrap());
+        assert!(agent.nask("token".into()).await.unwrap());
+
+        agent.publish(Action::Tell("posted".into())).await.unwrap();
+        assert!(timeout(Duration::from_secs(2), async { while !blackboard.ask("posted".into()).await.unwrap() { sleep(Duration::from_millis(10)).await } }).await.is_ok(),
+            "The published action should be applied");
+    }
+
+    #[tokio::test]
+    async fn nats_agent_should_fail_when_no_blackboard_answers() {
+        let addr = start_server().await;
+        let agent = NatsAgent::new(async_nats::connect(&addr).await.unwrap(), "bacht.nobody").with_timeout(Duration::from_millis(100));
+        assert!(matches!(agent.tell("token".into()).await, Err(TransportError::PeerDead)));
+    }
+}
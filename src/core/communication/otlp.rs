@@ -0,0 +1,119 @@
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use crate::error::TransportError;
+use crate::trace;
+
+const DEFAULT_PATH: &str = "/v1/traces";
+
+/// @summary - The OtlpExporter sends the spans to an OpenTelemetry collector over OTLP/HTTP, e.g. to visualize in
+/// Jaeger or Tempo how a query went through the nodes of a federation.
+///
+/// The spans are sent in batches by the batch span processor of OpenTelemetry, away from the runtime, so that recording
+/// a span never waits on the network.
+pub struct OtlpExporter {
+    provider: SdkTracerProvider,
+}
+
+impl OtlpExporter {
+
+    /// @summary - Start sending the spans recorded by the provider of the exporter to a collector
+    ///
+    /// @param endpoint - `host:port` or `http[s]://host:port[/path]`, the path being /v1/traces by default,
+    /// e.g. `localhost:4318`
+    ///
+    /// @param service - The name of the service emitting the spans, e.g. the name of the node
+    ///
+    /// @returns - The exporter, to install, or an error if the endpoint is not a valid one
+    pub fn start(endpoint: &str, service: &str) -> Result<Self, TransportError> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_protocol(Protocol::HttpBinary)
+            .with_endpoint(traces_url(endpoint)?)
+            .build()
+            .map_err(|e| TransportError::ProtocolError(e.to_string()))?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder_empty().with_service_name(service.to_string()).build())
+            .build();
+        Ok(Self { provider })
+    }
+
+    /// @summary - Record the spans of the process with the provider of the exporter, see trace::set_tracer_provider
+    pub fn install(&self) {
+        trace::set_tracer_provider(Some(self.provider.clone()));
+    }
+
+    /// @summary - Send the spans still pending, then stop exporting, e.g. when the node stops
+    pub fn shutdown(&self) -> Result<(), TransportError> {
+        self.provider.shutdown().map_err(|e| TransportError::ConnectionFailed(e.to_string()))
+    }
+}
+
+// The URL the spans are posted to
+fn traces_url(endpoint: &str) -> Result<String, TransportError> {
+    let (scheme, rest) = match endpoint.split_once("://") {
+        Some((scheme @ ("http" | "https"), rest)) => (scheme, rest),
+        Some((scheme, _)) => return Err(TransportError::ProtocolError(format!("Unsupported scheme {} of {}", scheme, endpoint))),
+        None => ("http", endpoint),
+    };
+    let (address, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, DEFAULT_PATH),
+    };
+    if address.is_empty() || !address.contains(':') {
+        return Err(TransportError::ProtocolError(format!("Invalid endpoint {}, expected host:port", endpoint)));
+    }
+    Ok(format!("{}://{}{}", scheme, address, path))
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{Span, Tracer, TracerProvider};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn traces_url_should_default_to_the_traces_path() {
+        assert_eq!(traces_url("localhost:4318").unwrap(), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_url("http://tempo:4318/otlp/v1/traces").unwrap(), "http://tempo:4318/otlp/v1/traces");
+        assert_eq!(traces_url("https://tempo:4318").unwrap(), "https://tempo:4318/v1/traces");
+        assert!(traces_url("ftp://tempo:4318").is_err());
+        assert!(traces_url("tempo").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn otlp_exporter_should_post_the_spans_to_the_collector() {
+        let collector = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let exporter = OtlpExporter::start(&collector.local_addr().unwrap().to_string(), "node").unwrap();
+        let mut span = exporter.provider.tracer("bacht").start("handle");
+        span.set_attribute(opentelemetry::KeyValue::new("bacht.result", "true"));
+        span.end();
+        // Flushing blocks until the collector answered
+        let provider = exporter.provider.clone();
+        let flushed = tokio::task::spawn_blocking(move || provider.force_flush());
+
+        let (stream, _) = collector.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        let mut head = Vec::new();
+        let mut line = String::new();
+        while stream.read_line(&mut line).await.unwrap() > 2 {
+            head.push(std::mem::take(&mut line));
+        }
+        let length: usize = head.iter().find_map(|header| header.to_ascii_lowercase().strip_prefix("content-length:").map(|length| length.trim().parse().unwrap())).unwrap();
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).await.unwrap();
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/x-protobuf\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+        flushed.await.unwrap().unwrap();
+
+        assert!(head[0].starts_with("POST /v1/traces HTTP/1.1"), "Unexpected request: {:?}", head);
+        assert!(head.iter().any(|header| header.eq_ignore_ascii_case("content-type: application/x-protobuf\r\n")), "{:?}", head);
+        let contains = |text: &str| body.windows(text.len()).any(|window| window == text.as_bytes());
+        assert!(contains("node") && contains("handle") && contains("bacht.result"), "Unexpected body: {:?}", body);
+        exporter.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use crate::blackboard::BlackboardTrait;
+use crate::communication::peers::{PeerClients, PeerTable};
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::event::{Event, Origin};
+use crate::model::health::Health;
+use crate::model::change::StoreChange;
+use tokio::sync::broadcast;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
+use crate::model::token::TokenId;
+
+const DEFAULT_VIRTUAL_NODES: u32 = 64;
+
+/// @summary - A stable 64 bits hash, every process of the coordination space must agree on the owner of a token
+///
+/// @note - FNV-1a, followed by the MurmurHash3 finalizer to spread similar strings (e.g. `a#1`, `a#2`) over the whole ring
+fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3));
+    hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash = (hash ^ (hash >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// @summary - The HashRing maps tokens to nodes by consistent hashing.
+///
+/// Each node is placed `virtual_nodes` times on the ring, a token is owned by the first node found clockwise from its hash.
+/// Adding or removing a node only moves the tokens of its neighbourhood.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashRing {
+    virtual_nodes: u32,
+    ring: BTreeMap<u64, Box<str>>,
+}
+
+impl HashRing {
+
+    pub fn new(virtual_nodes: u32) -> Self {
+        Self {
+            virtual_nodes,
+            ring: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, node: &str) {
+        for i in 0..self.virtual_nodes {
+            self.ring.insert(stable_hash(format!("{}#{}", node, i).as_bytes()), node.into());
+        }
+    }
+
+    pub fn remove(&mut self, node: &str) {
+        self.ring.retain(|_, owner| owner.as_ref() != node);
+    }
+
+    /// @returns - The node owning the token, or None if the ring is empty
+    pub fn owner(&self, token: &str) -> Option<&str> {
+        let hash = stable_hash(token.as_bytes());
+        self.ring.range(hash..).next().or(self.ring.iter().next()).map(|(_, node)| node.as_ref())
+    }
+}
+
+/// @summary - The PartitionedBlackboard spreads the tokens of the coordination space over the nodes.
+///
+/// Every token is owned by one node, chosen by consistent hashing among this node and its alive peers:
+/// the primitives on a token owned by a peer are forwarded to it, the others are applied on the local store.
+///
+/// @note - The tokens of a dead node are not moved: they are unavailable until it comes back
+pub struct PartitionedBlackboard<B: BlackboardTrait> {
+    local: B,
+    node: Box<str>,
+    clients: PeerClients,
+    // The ring, and the alive peers it was built from
+    ring: Arc<Mutex<(Vec<Box<str>>, HashRing)>>,
+    partitioning: bool,
+}
+
+impl<B: BlackboardTrait> PartitionedBlackboard<B> {
+
+    /// @summary - Partition the coordination space between this node and its peers
+    ///
+    /// @param local - The blackboard holding the tokens owned by this node
+    ///
+    /// @param node - The name of this node, as known by the peers
+    ///
+    /// @param peers - The routing table, the names of the peers must be the same on every node
+    pub fn new_with(local: B, node: &str, peers: PeerTable) -> Self {
+        let mut ring = HashRing::new(DEFAULT_VIRTUAL_NODES);
+        ring.add(node);
+        Self {
+            local,
+            node: node.into(),
+            clients: PeerClients::new(peers),
+            ring: Arc::new(Mutex::new((Vec::new(), ring))),
+            partitioning: true,
+        }
+    }
+
+    /// @summary - Enable or disable the partitioning, when disabled every token is owned by this node
+    pub fn set_partitioning(&mut self, partitioning: bool) {
+        self.partitioning = partitioning;
+    }
+
+    /// @returns - The name of the node owning the token
+    pub fn owner(&self, token: &str) -> Box<str> {
+        if !self.partitioning {
+            return self.node.clone();
+        }
+        let alive: Vec<Box<str>> = self.clients.peers().alive_peers().into_iter().map(|(name, _)| name).collect();
+        let mut ring = self.ring.lock().unwrap();
+        if ring.0 != alive {
+            let mut rebuilt = HashRing::new(DEFAULT_VIRTUAL_NODES);
+            rebuilt.add(&self.node);
+            alive.iter().for_each(|peer| rebuilt.add(peer));
+            *ring = (alive, rebuilt);
+        }
+        ring.1.owner(token).unwrap_or(&self.node).into()
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send> BlackboardTrait for PartitionedBlackboard<B> {
+
+    fn new() -> Self {
+        Self::new_with(B::new(), "local", PeerTable::new())
+    }
+
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        let token = match &event.action {
+            Action::Tell(token) | Action::Ask(token) | Action::Nask(token) | Action::Get(token) => token,
+        };
+        // An event forwarded by a peer was routed by it, it is owned by this node
+        let owner = match event.origin {
+            Origin::Agent => self.owner(token),
+            _ => self.node.clone(),
+        };
+        if owner == self.node {
+            return self.local.send_event(event).await;
+        }
+        let client = self.clients.client(&owner).await.ok_or(StoreError::OwnerUnreachable)?;
+        client.forward_traced(event.action, event.trace.as_ref()).await.map_err(|e| {
+            log!(Level::Error, "Failed to route to {}: {:?}", owner, e);
+            StoreError::OwnerUnreachable
+        })
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        self.local.admin(command).await
+    }
+
+    fn health(&self) -> Health {
+        self.local.health()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.local.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            node: self.node.clone(),
+            clients: self.clients.clone(),
+            ring: self.ring.clone(),
+            partitioning: self.partitioning,
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::time::sleep;
+    use super::*;
+    use crate::blackboard::create_blackboard;
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+
+    #[test]
+    fn ring_should_spread_tokens_and_move_few_of_them_on_membership_change() {
+        let mut ring = HashRing::new(DEFAULT_VIRTUAL_NODES);
+        assert_eq!(ring.owner("token"), None);
+        ["a", "b", "c"].iter().for_each(|node| ring.add(node));
+        let tokens: Vec<String> = (0..300).map(|i| format!("token{}", i)).collect();
+        let owners: Vec<String> = tokens.iter().map(|token| ring.owner(token).unwrap().to_string()).collect();
+        for node in ["a", "b", "c"] {
+            assert!(owners.iter().filter(|owner| *owner == node).count() > 50, "Node {} should own a fair share", node);
+        }
+
+        ring.remove("c");
+        for (token, owner) in tokens.iter().zip(&owners) {
+            if owner != "c" {
+                assert_eq!(ring.owner(token), Some(owner.as_str()), "Only the tokens of the removed node should move");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn partition_should_route_primitives_to_the_owner() {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let peers_a = PeerTable::new();
+        let peers_b = PeerTable::new();
+        peers_a.insert("b", &format!("127.0.0.1:{}", port));
+        let a = PartitionedBlackboard::new_with(create_blackboard(), "a", peers_a);
+        let local_b = create_blackboard();
+        let b = PartitionedBlackboard::new_with(local_b.clone(), "b", peers_b);
+        tokio::spawn(async move { SocketListener::new(b, Some(port)).listen().await });
+        while TcpStream::connect(("127.0.0.1", port)).await.is_err() {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let token = (0..).map(|i| format!("token{}", i)).find(|token| a.owner(token).as_ref() == "b").unwrap();
+        assert!(a.tell(token.clone().into()).await.unwrap());
+        assert!(local_b.ask(token.clone().into()).await.unwrap(), "The token should be stored by its owner");
+        assert!(a.get(token.clone().into()).await.unwrap());
+        assert!(a.nask(token.into()).await.unwrap());
+
+        let token = (0..).map(|i| format!("token{}", i)).find(|token| a.owner(token).as_ref() == "a").unwrap();
+        assert!(a.tell(token.clone().into()).await.unwrap());
+        assert!(!local_b.ask(token.into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn partition_should_fail_when_the_owner_is_unreachable() {
+        let peers = PeerTable::new();
+        peers.insert("b", "127.0.0.1:1");
+        let mut a = PartitionedBlackboard::new_with(create_blackboard(), "a", peers);
+        let token = (0..).map(|i| format!("token{}", i)).find(|token| a.owner(token).as_ref() == "b").unwrap();
+        assert!(matches!(a.tell(token.clone().into()).await, Err(StoreError::OwnerUnreachable)));
+
+        a.set_partitioning(false);
+        assert!(a.tell(token.into()).await.unwrap());
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use crate::communication::socket_client::{PendingPolicy, ReconnectPolicy, SocketClient};
+use crate::communication::transport::UNIX_PREFIX;
+use crate::log;
+use crate::log::Level;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PeerStatus {
+    Alive,
+    /// The peer missed too many heartbeats, it must not be used for routing
+    Dead,
+}
+
+/// A remote blackboard known by this node
+#[derive(Debug, Clone)]
+pub struct Peer {
+    pub addr: String,
+    pub status: PeerStatus,
+    pub last_seen: Instant,
+}
+
+/// @summary - The PeerTable is the routing table of the node: it maps the name of the known remote blackboards to their address and liveness.
+///
+/// It can be cloned in order to share the same table between the components of the node.
+#[derive(Debug, Clone, Default)]
+pub struct PeerTable {
+    peers: Arc<RwLock<HashMap<Box<str>, Peer>>>,
+}
+
+impl PeerTable {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// @summary - Add a peer to the table, or update its address if already known
+    ///
+    /// @note - A newly inserted peer is considered alive
+    pub fn insert(&self, name: &str, addr: &str) {
+        let mut peers = self.peers.write().unwrap();
+        peers.entry(name.into()).and_modify(|peer| peer.addr = addr.to_string()).or_insert(Peer {
+            addr: addr.to_string(),
+            status: PeerStatus::Alive,
+            last_seen: Instant::now(),
+        });
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Peer> {
+        self.peers.write().unwrap().remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Peer> {
+        self.peers.read().unwrap().get(name).cloned()
+    }
+
+    /// @summary - Record that the peer answered, marking it alive
+    pub fn mark_alive(&self, name: &str) {
+        if let Some(peer) = self.peers.write().unwrap().get_mut(name) {
+            peer.status = PeerStatus::Alive;
+            peer.last_seen = Instant::now();
+        }
+    }
+
+    /// @summary - Mark the peer dead, so that it is skipped by routing until it answers again
+    pub fn mark_dead(&self, name: &str) {
+        if let Some(peer) = self.peers.write().unwrap().get_mut(name) {
+            peer.status = PeerStatus::Dead;
+        }
+    }
+
+    pub fn is_alive(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|peer| peer.status == PeerStatus::Alive)
+    }
+
+    /// @returns - The name and address of every alive peer, sorted by name
+    pub fn alive_peers(&self) -> Vec<(Box<str>, String)> {
+        let mut alive: Vec<(Box<str>, String)> = self.peers.read().unwrap().iter()
+            .filter(|(_, peer)| peer.status == PeerStatus::Alive)
+            .map(|(name, peer)| (name.clone(), peer.addr.clone()))
+            .collect();
+        alive.sort();
+        alive
+    }
+
+    /// @returns - Every known peer, sorted by name
+    pub fn list(&self) -> Vec<(Box<str>, Peer)> {
+        let mut peers: Vec<(Box<str>, Peer)> = self.peers.read().unwrap().iter().map(|(name, peer)| (name.clone(), peer.clone())).collect();
+        peers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        peers
+    }
+
+    /// @summary - Resolve the name of a remote blackboard (e.g. `sensors` in `tell@sensors(x)`) into its address
+    ///
+    /// @returns - The address of the peer, or None if the name is unknown
+    pub fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).map(|peer| peer.addr)
+    }
+
+    /// @summary - Declare the peers listed in a static configuration
+    ///
+    /// @param config - One `name = host:port` (or `name = unix:<path>`) declaration per line, the address may be quoted,
+    /// `#` starts a comment
+    ///
+    /// @returns - The number of declared peers, or an error pointing to the first invalid line
+    pub fn load_config(&self, config: &str) -> Result<usize, String> {
+        let mut declared = Vec::new();
+        for (i, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, addr) = line.split_once('=').ok_or(format!("Line {}: expected `name = host:port`", i + 1))?;
+            let (name, addr) = (name.trim(), addr.trim().trim_matches('"'));
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(format!("Line {}: invalid peer name `{}`", i + 1, name));
+            }
+            match addr.rsplit_once(':') {
+                _ if addr.strip_prefix(UNIX_PREFIX).is_some_and(|path| !path.is_empty()) => declared.push((name, addr)),
+                Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok_and(|port| port != 0) => declared.push((name, addr)),
+                _ => return Err(format!("Line {}: invalid address `{}`, expected host:port", i + 1, addr)),
+            }
+        }
+        for (name, addr) in &declared {
+            self.insert(name, addr);
+        }
+        Ok(declared.len())
+    }
+
+    /// @summary - Declare the peers listed in a static configuration file
+    ///
+    /// @see - load_config for the format of the file
+    pub fn load_file(&self, path: &std::path::Path) -> Result<usize, String> {
+        let config = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        self.load_config(&config).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// @summary - The PeerClients keeps one connection per peer of the routing table, opened on first use.
+///
+/// It can be cloned in order to share the same connections between the components of the node.
+#[derive(Clone)]
+pub struct PeerClients {
+    peers: PeerTable,
+    clients: Arc<Mutex<HashMap<Box<str>, Arc<SocketClient>>>>,
+    // The key of the peers presented on the connections
+    key: Option<String>,
+}
+
+impl PeerClients {
+
+    pub fn new(peers: PeerTable) -> Self {
+        Self {
+            peers,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            key: None,
+        }
+    }
+
+    /// @summary - Present the key of the peers on the connections opened from now on, so that the peers accept the
+    /// frames of the other nodes, e.g. the reservations of the forwarded gets
+    pub fn with_peer_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    pub fn peers(&self) -> &PeerTable {
+        &self.peers
+    }
+
+    /// @summary - Get the connection to a peer, connecting to it if needed
+    ///
+    /// @returns - The connection, or None if the peer is unknown or can't be reached
+    ///
+    /// @note - A peer that can't be reached must not stall the caller: a single connection attempt is made, and requests are not resent
+    pub async fn client(&self, name: &str) -> Option<Arc<SocketClient>> {
+        let addr = self.peers.resolve(name)?;
+        let known = self.clients.lock().unwrap().get(name).cloned();
+        if let Some(client) = known.filter(|client| client.addr() == addr) {
+            return Some(client);
+        }
+        let policy = ReconnectPolicy {
+            max_attempts: Some(1),
+            pending: PendingPolicy::Fail,
+            ..ReconnectPolicy::default()
+        };
+        match SocketClient::connect(&addr, policy).await {
+            Ok(client) => {
+                let client = match &self.key {
+                    Some(key) => client.with_peer_key(key),
+                    None => client,
+                };
+                let client = Arc::new(client.with_peer(self.peers.clone(), name));
+                self.clients.lock().unwrap().insert(name.into(), client.clone());
+                Some(client)
+            },
+            Err(e) => {
+                log!(Level::Error, "Failed to reach peer {}: {:?}", name, e);
+                None
+            }
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_table_should_consider_new_peers_alive() {
+        let table = PeerTable::new();
+        table.insert("sensors", "127.0.0.1:2138");
+        assert!(table.is_alive("sensors"));
+        assert_eq!(table.alive_peers(), vec![("sensors".into(), "127.0.0.1:2138".to_string())]);
+    }
+
+    #[test]
+    fn peer_table_should_skip_dead_peers_until_they_answer() {
+        let table = PeerTable::new();
+        table.insert("sensors", "127.0.0.1:2138");
+        table.insert("actuators", "127.0.0.1:2139");
+        table.mark_dead("sensors");
+        assert!(!table.is_alive("sensors"));
+        assert_eq!(table.alive_peers().len(), 1);
+        table.mark_alive("sensors");
+        assert_eq!(table.alive_peers().len(), 2);
+    }
+
+    #[test]
+    fn peer_table_should_load_static_configuration() {
+        let table = PeerTable::new();
+        let config = "# remote blackboards\nsensors = 192.168.1.10:2138\n\nactuators = \"actuators.local:2140\" # quoted\nlocal = unix:/run/bacht.sock";
+        assert_eq!(table.load_config(config), Ok(3));
+        assert_eq!(table.resolve("local"), Some("unix:/run/bacht.sock".to_string()));
+        assert_eq!(table.resolve("sensors"), Some("192.168.1.10:2138".to_string()));
+        assert_eq!(table.resolve("actuators"), Some("actuators.local:2140".to_string()));
+        assert_eq!(table.resolve("unknown"), None);
+    }
+
+    #[test]
+    fn peer_table_should_refuse_invalid_configuration_without_partial_load() {
+        let table = PeerTable::new();
+        assert!(table.load_config("sensors = 192.168.1.10:2138\nactuators 2140").unwrap_err().starts_with("Line 2"));
+        assert!(table.load_config("sensors = 192.168.1.10").is_err());
+        assert!(table.load_config("sensors = host:0").is_err());
+        assert!(table.load_config("sensors = unix:").is_err());
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn peer_table_should_be_shared_with_its_clones() {
+        let table = PeerTable::new();
+        let clone = table.clone();
+        clone.insert("sensors", "127.0.0.1:2138");
+        assert_eq!(table.len(), 1);
+        assert!(!table.is_alive("unknown"));
+    }
+}
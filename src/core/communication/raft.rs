@@ -0,0 +1,883 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rand::Rng;
+use tokio::sync::{broadcast, oneshot, watch, Notify};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{Instant, sleep_until, timeout};
+use crate::blackboard::BlackboardTrait;
+use crate::communication::frame::{MAX_FRAME_LENGTH, token_parts};
+use crate::communication::heartbeat::HeartbeatConfig;
+use crate::communication::socket_client::{PendingPolicy, ReconnectPolicy, SocketClient};
+use crate::error::{StoreError, TransportError};
+use crate::log;
+use crate::log::Level;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::change::StoreChange;
+use crate::model::event::{Event, Origin};
+use crate::model::health::Health;
+use crate::model::token::TokenId;
+
+// The entries sent at most in an append, so that a lagging member is caught up in frames of a bounded size
+const MAX_APPENDED_ENTRIES: usize = 64;
+// The entries applied since the last snapshot once a new one is taken, the log keeping the entries following it only
+const COMPACTION_THRESHOLD: u64 = 1024;
+// The bytes of tokens sent at most in a part of a snapshot, well below MAX_FRAME_LENGTH
+const MAX_SNAPSHOT_PART: usize = MAX_FRAME_LENGTH / 2;
+
+/// @summary - Configuration of the timers of a Raft member
+///
+/// A follower that heard no leader for a random time between one and two `election_timeout` stands for election, a
+/// leader sends its entries, or an empty append, every `heartbeat_interval`. An operation not committed within
+/// `commit_timeout` is answered with StoreError::NoQuorum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaftConfig {
+    pub heartbeat_interval: Duration,
+    pub election_timeout: Duration,
+    pub commit_timeout: Duration,
+}
+
+impl Default for RaftConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_millis(50),
+            election_timeout: Duration::from_millis(300),
+            commit_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RaftRole {
+    /// Applies the entries of the leader, and stands for election once the leader stays silent
+    Follower,
+    /// Asks the other members for their votes
+    Candidate,
+    /// Appends the operations of the agents to the log, and replicates it to the followers
+    Leader,
+}
+
+/// An operation of the agents, in the term of the leader that appended it to the log, on the name of its token as
+/// replicated to the other members: it is interned once applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub term: u64,
+    pub action: Action<Box<str>>,
+}
+
+/// Sent by a candidate to the other members, answered by their term and whether they voted for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteRequest {
+    pub term: u64,
+    pub candidate: Box<str>,
+    // The index and the term of the last entry of the candidate, which must be as up to date as the log of the voter
+    pub last_index: u64,
+    pub last_term: u64,
+}
+
+/// Sent by the leader to each follower, answered by their term and whether the entries follow their log
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppendRequest {
+    pub term: u64,
+    pub leader: Box<str>,
+    // The entry preceding the entries appended, which the follower must hold
+    pub prev_index: u64,
+    pub prev_term: u64,
+    pub entries: Vec<LogEntry>,
+    // The last entry committed by the leader
+    pub commit: u64,
+}
+
+/// Sent by the leader to a follower missing the entries it compacted, in parts: the tokens of the store once the entries
+/// up to the last index applied, which replace the store of the follower once its last part is received
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotRequest {
+    pub term: u64,
+    pub leader: Box<str>,
+    // The last entry applied to the store of the snapshot
+    pub last_index: u64,
+    pub last_term: u64,
+    // Whether the part starts the snapshot, and whether it ends it
+    pub first: bool,
+    pub last: bool,
+    pub tokens: Vec<(Box<str>, u32)>,
+}
+
+// The tokens of a snapshot with their occurrences, shared with the tasks sending it
+type Tokens = Arc<Vec<(Box<str>, u32)>>;
+
+// The tokens of the store once the entries up to an index applied, replacing these entries in the log
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    index: u64,
+    term: u64,
+    tokens: Tokens,
+}
+
+// The state of a member, the entry of index i being the log[i - snapshot.index - 1]
+struct RaftState {
+    term: u64,
+    voted_for: Option<Box<str>>,
+    role: RaftRole,
+    leader: Option<Box<str>>,
+    log: Vec<LogEntry>,
+    commit: u64,
+    snapshot: Snapshot,
+    // The parts of a snapshot received so far from the leader
+    receiving: Option<Snapshot>,
+    // When the follower stands for election if it hears no leader
+    election: Instant,
+    // The next entry to send to each follower, and the last one it holds, while leader
+    next: HashMap<Box<str>, u64>,
+    matched: HashMap<Box<str>, u64>,
+}
+
+impl RaftState {
+
+    fn last(&self) -> (u64, u64) {
+        (self.snapshot.index + self.log.len() as u64, self.log.last().map_or(self.snapshot.term, |entry| entry.term))
+    }
+
+    // The term of an entry, None if the log does not hold it, e.g. an entry compacted into the snapshot
+    fn term_at(&self, index: u64) -> Option<u64> {
+        match index.checked_sub(self.snapshot.index) {
+            Some(0) => Some(self.snapshot.term),
+            Some(position) => self.log.get(position as usize - 1).map(|entry| entry.term),
+            None => None,
+        }
+    }
+
+    fn step_down(&mut self, term: u64) {
+        if term > self.term {
+            self.term = term;
+            self.voted_for = None;
+        }
+        self.role = RaftRole::Follower;
+    }
+}
+
+/// @summary - The RaftNode is the consensus module of a member of a Raft cluster: it elects a leader among the members,
+/// and replicates its log of operations to the followers.
+///
+/// An entry is committed once a majority of the members hold it, committed entries are never lost while a majority of
+/// the members is alive: the blackboards applying them in order stay identical.
+///
+/// It can be cloned in order to share the same state between the listeners answering the other members.
+///
+/// The entries applied are compacted into a snapshot of the store, the followers lagging behind it being sent the
+/// snapshot instead of the entries.
+///
+/// @note - The log and the votes are kept in memory: a member that restarts must rejoin with a new name, as it forgot
+/// the votes it cast
+#[derive(Clone)]
+pub struct RaftNode {
+    name: Box<str>,
+    config: RaftConfig,
+    // The other members, by name, the connections to them being named after their address
+    members: Arc<Vec<(Box<str>, SocketClient)>>,
+    state: Arc<Mutex<RaftState>>,
+    // The last committed entry, watched by the blackboard applying them
+    commits: Arc<watch::Sender<u64>>,
+    // Wakes the leader up as soon as an entry is appended, instead of waiting for the next heartbeat
+    appended: Arc<Notify>,
+}
+
+impl RaftNode {
+
+    /// @param name - The name of this member, known by the others
+    ///
+    /// @param members - The name and the address of the other members, at least two for the cluster to survive a crash
+    pub fn new(name: &str, members: &[(&str, &str)]) -> Self {
+        Self::new_with(name, members, RaftConfig::default())
+    }
+
+    pub fn new_with(name: &str, members: &[(&str, &str)], config: RaftConfig) -> Self {
+        let members = members.iter().map(|(member, addr)| ((*member).into(), Self::client(addr, config))).collect();
+        let state = RaftState {
+            term: 0,
+            voted_for: None,
+            role: RaftRole::Follower,
+            leader: None,
+            log: Vec::new(),
+            commit: 0,
+            snapshot: Snapshot::default(),
+            receiving: None,
+            election: Instant::now(),
+            next: HashMap::new(),
+            matched: HashMap::new(),
+        };
+        let node = Self {
+            name: name.into(),
+            config,
+            members: Arc::new(members),
+            state: Arc::new(Mutex::new(state)),
+            commits: Arc::new(watch::channel(0).0),
+            appended: Arc::new(Notify::new()),
+        };
+        node.reset_election(&mut node.state.lock().unwrap());
+        node
+    }
+
+    /// @summary - Present the key of the peers to the other members, which refuse the votes and the appends otherwise
+    ///
+    /// @note - The connections to the members are opened anew, the key is given before the node is spawned
+    pub fn with_peer_key(mut self, key: &str) -> Self {
+        let members = self.members.iter().map(|(member, client)| (member.clone(), Self::client(client.addr(), self.config).with_peer_key(key))).collect();
+        self.members = Arc::new(members);
+        self
+    }
+
+    // The connection to another member
+    fn client(addr: &str, config: RaftConfig) -> SocketClient {
+        let policy = ReconnectPolicy { max_attempts: Some(1), pending: PendingPolicy::Fail, ..ReconnectPolicy::default() };
+        // A member silent for a heartbeat interval is given up until the next one
+        let heartbeat = HeartbeatConfig { interval: config.heartbeat_interval, max_missed: 1 };
+        SocketClient::new_with(addr, policy).with_heartbeat(heartbeat)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn config(&self) -> RaftConfig {
+        self.config
+    }
+
+    pub fn role(&self) -> RaftRole {
+        self.state.lock().unwrap().role
+    }
+
+    pub fn term(&self) -> u64 {
+        self.state.lock().unwrap().term
+    }
+
+    /// @returns - The name of the leader of the current term, None while it is unknown
+    pub fn leader(&self) -> Option<Box<str>> {
+        let state = self.state.lock().unwrap();
+        match state.role {
+            RaftRole::Leader => Some(self.name.clone()),
+            _ => state.leader.clone(),
+        }
+    }
+
+    fn reset_election(&self, state: &mut RaftState) {
+        let timeout = self.config.election_timeout;
+        state.election = Instant::now() + timeout + timeout.mul_f64(rand::rng().random::<f64>());
+    }
+
+    fn majority(&self) -> usize {
+        // This member and the others
+        let cluster = self.members.len() + 1;
+        cluster / 2 + 1
+    }
+
+    /// @summary - Answer the vote request of a candidate
+    ///
+    /// @returns - The term of this member, and true if it voted for the candidate
+    pub fn vote(&self, request: VoteRequest) -> (u64, bool) {
+        let mut state = self.state.lock().unwrap();
+        if request.term > state.term {
+            state.step_down(request.term);
+        }
+        let free = state.voted_for.as_ref().is_none_or(|voted_for| *voted_for == request.candidate);
+        let (last_index, last_term) = state.last();
+        let up_to_date = (request.last_term, request.last_index) >= (last_term, last_index);
+        let granted = request.term == state.term && free && up_to_date;
+        if granted {
+            state.voted_for = Some(request.candidate);
+            self.reset_election(&mut state);
+        }
+        (state.term, granted)
+    }
+
+    /// @summary - Append the entries of the leader to the log, replacing the ones of older terms conflicting with them
+    ///
+    /// @returns - The term of this member, and false if its log misses the entry preceding them, or if they conflict with
+    /// a committed entry, which is never replaced
+    pub fn append(&self, request: AppendRequest) -> (u64, bool) {
+        let mut state = self.state.lock().unwrap();
+        if request.term < state.term {
+            return (state.term, false);
+        }
+        state.step_down(request.term);
+        state.leader = Some(request.leader);
+        self.reset_election(&mut state);
+        let last = request.prev_index + request.entries.len() as u64;
+        // The entries compacted were committed, the same as the ones of the leader
+        let compacted = state.snapshot.index.saturating_sub(request.prev_index);
+        if compacted == 0 && state.term_at(request.prev_index) != Some(request.prev_term) {
+            return (state.term, false);
+        }
+        for (index, entry) in (request.prev_index + 1..).zip(request.entries).skip(compacted as usize) {
+            match state.term_at(index) {
+                Some(term) if term == entry.term => continue,
+                // No leader holds entries conflicting with the committed ones, the append is forged
+                Some(_) if index <= state.commit => {
+                    log!(Level::Error, "Entry {} committed but conflicting with the leader of term {}", index, request.term);
+                    return (state.term, false);
+                },
+                Some(_) => {
+                    log!(Level::Debug, "Entries from {} replaced by the leader of term {}", index, request.term);
+                    let position = (index - state.snapshot.index) as usize;
+                    state.log.truncate(position - 1);
+                },
+                None => {},
+            }
+            state.log.push(entry);
+        }
+        let commit = request.commit.min(last);
+        if commit > state.commit {
+            state.commit = commit;
+            self.commits.send_replace(commit);
+        }
+        (state.term, true)
+    }
+
+    /// @summary - Append an operation to the log of the leader
+    ///
+    /// @returns - The index and the term of its entry, or StoreError::NotLeader naming the leader if it is known
+    pub fn propose(&self, action: Action) -> Result<(u64, u64), StoreError> {
+        let mut state = self.state.lock().unwrap();
+        if state.role != RaftRole::Leader {
+            return Err(StoreError::NotLeader(state.leader.clone()));
+        }
+        let term = state.term;
+        state.log.push(LogEntry { term, action: action.named() });
+        self.appended.notify_one();
+        Ok((state.last().0, term))
+    }
+
+    /// @returns - The committed entries following an index, with their index, none if the entries following it were
+    /// compacted: the snapshot is then applied first
+    pub fn committed(&self, after: u64) -> Vec<(u64, LogEntry)> {
+        let state = self.state.lock().unwrap();
+        let Some(from) = after.checked_sub(state.snapshot.index) else { return Vec::new() };
+        let commit = (state.commit - state.snapshot.index) as usize;
+        let entries = state.log.get(from as usize..commit.min(state.log.len())).unwrap_or_default();
+        (after + 1..).zip(entries.iter().cloned()).collect()
+    }
+
+    /// @returns - The index of the last entry of the snapshot and its tokens, if it follows the given index
+    pub fn snapshot(&self, after: u64) -> Option<(u64, Tokens)> {
+        let state = self.state.lock().unwrap();
+        (state.snapshot.index > after).then(|| (state.snapshot.index, state.snapshot.tokens.clone()))
+    }
+
+    /// @summary - Replace the entries up to an index applied by the tokens of the store once they were applied
+    ///
+    /// @param index - The last entry applied to the store, which must be committed
+    pub fn compact(&self, index: u64, tokens: Vec<(Box<str>, u32)>) {
+        let mut state = self.state.lock().unwrap();
+        let Some(term) = state.term_at(index).filter(|_| index > state.snapshot.index && index <= state.commit) else { return };
+        let compacted = (index - state.snapshot.index) as usize;
+        state.log.drain(..compacted);
+        state.snapshot = Snapshot { index, term, tokens: Arc::new(tokens) };
+        log!(Level::Debug, "Log compacted up to entry {}", index);
+    }
+
+    /// @summary - Receive a part of the snapshot of the leader, which replaces the log up to its last entry once its last
+    /// part is received, keeping the entries following it
+    ///
+    /// @returns - The term of this member, and false if the part does not follow the previous one
+    pub fn install(&self, request: SnapshotRequest) -> (u64, bool) {
+        let mut state = self.state.lock().unwrap();
+        if request.term < state.term {
+            return (state.term, false);
+        }
+        state.step_down(request.term);
+        state.leader = Some(request.leader);
+        self.reset_election(&mut state);
+        if request.first {
+            state.receiving = Some(Snapshot { index: request.last_index, term: request.last_term, tokens: Arc::default() });
+        }
+        let term = state.term;
+        let Some(receiving) = state.receiving.as_mut().filter(|receiving| (receiving.index, receiving.term) == (request.last_index, request.last_term)) else {
+            return (term, false);
+        };
+        Arc::make_mut(&mut receiving.tokens).extend(request.tokens);
+        if !request.last {
+            return (term, true);
+        }
+        let received = state.receiving.take().unwrap();
+        if received.index <= state.snapshot.index {
+            return (term, true);
+        }
+        // The entries following the snapshot are kept if the log holds its last entry, the log is discarded otherwise
+        match state.term_at(received.index) == Some(received.term) {
+            true => {
+                let compacted = (received.index - state.snapshot.index) as usize;
+                state.log.drain(..compacted);
+            },
+            false => state.log.clear(),
+        }
+        state.snapshot = received;
+        state.commit = state.commit.max(state.snapshot.index);
+        // Wakes the blackboard up to apply the snapshot, even if the commit did not change
+        self.commits.send_replace(state.commit);
+        log!(Level::Debug, "Snapshot installed up to entry {}", state.snapshot.index);
+        (term, true)
+    }
+
+    /// @returns - The receiver of the index of the last committed entry
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.commits.subscribe()
+    }
+
+    /// @summary - Stand for election, becoming the leader of the new term with the votes of a majority
+    async fn elect(&self) {
+        let request = {
+            let mut state = self.state.lock().unwrap();
+            state.term += 1;
+            state.role = RaftRole::Candidate;
+            state.voted_for = Some(self.name.clone());
+            state.leader = None;
+            self.reset_election(&mut state);
+            let (last_index, last_term) = state.last();
+            VoteRequest { term: state.term, candidate: self.name.clone(), last_index, last_term }
+        };
+        log!(Level::Debug, "Standing for election in term {}", request.term);
+        let mut votes = JoinSet::new();
+        for index in 0..self.members.len() {
+            let (node, request) = (self.clone(), request.clone());
+            votes.spawn(async move { node.members[index].1.vote(request).await });
+        }
+        let mut granted = 1;
+        while granted < self.majority() {
+            let Some(vote) = votes.join_next().await else { return };
+            let Ok(Ok((term, vote))) = vote else { continue };
+            let mut state = self.state.lock().unwrap();
+            if term > state.term {
+                state.step_down(term);
+                return;
+            }
+            granted += vote as usize;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.term != request.term || state.role != RaftRole::Candidate {
+            return;
+        }
+        state.role = RaftRole::Leader;
+        state.leader = Some(self.name.clone());
+        let next = state.last().0 + 1;
+        state.next = self.members.iter().map(|(member, _)| (member.clone(), next)).collect();
+        state.matched = self.members.iter().map(|(member, _)| (member.clone(), 0)).collect();
+        log!(Level::Info, "Elected leader of term {}", state.term);
+    }
+
+    /// @summary - Send its missing entries to each follower, then commit the entries held by a majority
+    async fn replicate(&self) {
+        let mut appends = JoinSet::new();
+        {
+            let state = self.state.lock().unwrap();
+            for (index, (member, _)) in self.members.iter().enumerate() {
+                let next = state.next.get(member).copied().unwrap_or(1).max(1);
+                // A follower missing the entries compacted is sent the snapshot, then the entries following it
+                if next <= state.snapshot.index {
+                    let (node, snapshot, term) = (self.clone(), state.snapshot.clone(), state.term);
+                    appends.spawn(async move {
+                        let sent = node.send_snapshot(&node.members[index].1, term, &snapshot).await;
+                        (index, snapshot.index, 0, sent)
+                    });
+                    continue;
+                }
+                let prev_index = next - 1;
+                let entries: Vec<LogEntry> = state.log.iter().skip((prev_index - state.snapshot.index) as usize).take(MAX_APPENDED_ENTRIES).cloned().collect();
+                let request = AppendRequest {
+                    term: state.term,
+                    leader: self.name.clone(),
+                    prev_index,
+                    prev_term: state.term_at(prev_index).unwrap_or(0),
+                    entries,
+                    commit: state.commit,
+                };
+                let node = self.clone();
+                appends.spawn(async move {
+                    let sent = request.entries.len() as u64;
+                    (index, prev_index, sent, node.members[index].1.append(request).await)
+                });
+            }
+        }
+        let term = self.term();
+        while let Some(answer) = appends.join_next().await {
+            let Ok((index, prev_index, sent, Ok((answer_term, appended)))) = answer else { continue };
+            let member = &self.members[index].0;
+            let mut state = self.state.lock().unwrap();
+            if answer_term > state.term {
+                log!(Level::Info, "Member {} is in term {}, stepping down", member, answer_term);
+                state.step_down(answer_term);
+                return;
+            }
+            if state.term != term || state.role != RaftRole::Leader {
+                return;
+            }
+            match appended {
+                true => {
+                    state.matched.insert(member.clone(), prev_index + sent);
+                    state.next.insert(member.clone(), prev_index + sent + 1);
+                },
+                // The follower misses the preceding entry, the previous one is tried next
+                false => {
+                    state.next.insert(member.clone(), prev_index.max(1));
+                },
+            }
+        }
+        self.advance_commit();
+    }
+
+    // Send the snapshot to a follower in parts, each one in a frame of a bounded size
+    async fn send_snapshot(&self, member: &SocketClient, term: u64, snapshot: &Snapshot) -> Result<(u64, bool), TransportError> {
+        let parts = token_parts(&snapshot.tokens, MAX_SNAPSHOT_PART);
+        let mut answer = (term, true);
+        for (i, part) in parts.iter().enumerate() {
+            let request = SnapshotRequest {
+                term,
+                leader: self.name.clone(),
+                last_index: snapshot.index,
+                last_term: snapshot.term,
+                first: i == 0,
+                last: i == parts.len() - 1,
+                tokens: part.to_vec(),
+            };
+            answer = member.snapshot(request).await?;
+            if !answer.1 {
+                break;
+            }
+        }
+        Ok(answer)
+    }
+
+    // Commit the last entry of the current term held by a majority, and the entries preceding it
+    fn advance_commit(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.role != RaftRole::Leader {
+            return;
+        }
+        let mut held: Vec<u64> = state.matched.values().copied().chain([state.last().0]).collect();
+        held.sort_unstable_by(|a, b| b.cmp(a));
+        let commit = held[self.majority() - 1];
+        // Only the entries of its own term are committed by counting, the older ones being committed with them
+        if commit > state.commit && state.term_at(commit) == Some(state.term) {
+            state.commit = commit;
+            self.commits.send_replace(commit);
+        }
+    }
+
+    /// @summary - Start the timers of the member: the heartbeats while leader, the elections otherwise
+    ///
+    /// @returns - The handle of the task, aborting it stops the member from taking part in the cluster
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let node = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let (role, election) = {
+                    let state = node.state.lock().unwrap();
+                    (state.role, state.election)
+                };
+                match role {
+                    RaftRole::Leader => {
+                        node.replicate().await;
+                        let _ = timeout(node.config.heartbeat_interval, node.appended.notified()).await;
+                    },
+                    _ if Instant::now() >= election => node.elect().await,
+                    _ => sleep_until(election).await,
+                }
+            }
+        })
+    }
+}
+
+// An operation waiting for its entry to be applied, with the term it was appended in
+type Waiting = HashMap<u64, (u64, oneshot::Sender<Result<bool, StoreError>>)>;
+
+/// @summary - The RaftBlackboard applies the operations of the agents through the log of a Raft cluster, so that they are
+/// linearizable: every member applies the same operations in the same order, and an operation is answered once
+/// committed, a get never consuming a token another member consumed.
+///
+/// The reads go through the log as well, a leader cut from the majority could otherwise answer with a stale store.
+/// The followers refuse the operations of the agents with StoreError::NotLeader, naming the leader to send them to.
+///
+/// Without a node, the blackboard is standalone and applies the operations on its store directly.
+///
+/// @note - The cluster trades the availability for the consistency: while no majority of the members is reachable, the
+/// operations are refused
+pub struct RaftBlackboard<B: BlackboardTrait> {
+    local: B,
+    node: Option<RaftNode>,
+    waiting: Arc<Mutex<Waiting>>,
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> RaftBlackboard<B> {
+
+    pub fn standalone(local: B) -> Self {
+        Self { local, node: None, waiting: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// @summary - Apply the operations committed by a Raft cluster on the store
+    ///
+    /// @param node - The member of the cluster, whose timers are started apart with spawn
+    pub fn member(local: B, node: RaftNode) -> Self {
+        let blackboard = Self { local, node: Some(node.clone()), waiting: Arc::new(Mutex::new(HashMap::new())) };
+        let applier = blackboard.clone();
+        tokio::spawn(async move { applier.apply_committed(node).await });
+        blackboard
+    }
+
+    pub fn node(&self) -> Option<&RaftNode> {
+        self.node.as_ref()
+    }
+
+    // Apply the committed entries in order, answering the operations of this member waiting for them, and compact the
+    // log once enough of them were applied
+    async fn apply_committed(&self, node: RaftNode) {
+        let mut commits = node.subscribe();
+        let (mut applied, mut compacted) = (0, 0);
+        loop {
+            // Installed from the leader, the store is replaced by its tokens
+            if let Some((index, tokens)) = node.snapshot(applied) {
+                self.restore(&tokens).await;
+                (applied, compacted) = (index, index);
+                // The operations of the entries compacted are answered as without quorum
+                self.waiting.lock().unwrap().retain(|waited, _| *waited > index);
+            }
+            for (index, entry) in node.committed(applied) {
+                // Applied on every member alike, even once the table of the process is full, as the entries were
+                // appended by the leader and committed by the members presenting the key of the peers
+                let action = entry.action.map(|token| TokenId::intern(&token));
+                let result = self.local.send_event(Event::new(action)).await;
+                applied = index;
+                let Some((term, waiting)) = self.waiting.lock().unwrap().remove(&index) else { continue };
+                // Another leader replaced the entry of the operation
+                let result = if term == entry.term { result } else { Err(StoreError::NotLeader(node.leader())) };
+                let _ = waiting.send(result);
+            }
+            if applied >= compacted + COMPACTION_THRESHOLD {
+                if let Ok(AdminReply::Snapshot(tokens)) = self.local.admin(AdminCommand::Snapshot).await {
+                    node.compact(applied, tokens);
+                    compacted = applied;
+                }
+            }
+            if commits.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    // Replace the tokens of the store by the ones of a snapshot
+    async fn restore(&self, tokens: &[(Box<str>, u32)]) {
+        if let Err(e) = self.local.admin(AdminCommand::Clear).await {
+            log!(Level::Error, "Error clearing the store for the snapshot: {}", e);
+        }
+        for (token, occurrences) in tokens {
+            let token = match TokenId::try_intern(token) {
+                Ok(token) => token,
+                Err(e) => {
+                    log!(Level::Error, "Error restoring the snapshot: {}", e);
+                    return;
+                }
+            };
+            for _ in 0..*occurrences {
+                if let Err(e) = self.local.send_event(Event::new(Action::Tell(token))).await {
+                    log!(Level::Error, "Error restoring the snapshot: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn apply_through_log(&self, node: &RaftNode, action: Action) -> Result<bool, StoreError> {
+        let (applied, result) = oneshot::channel();
+        let index = {
+            // Registered before the entry can be applied
+            let mut waiting = self.waiting.lock().unwrap();
+            let (index, term) = node.propose(action)?;
+            waiting.insert(index, (term, applied));
+            index
+        };
+        match timeout(node.config().commit_timeout, result).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(StoreError::NoQuorum),
+            Err(_) => {
+                self.waiting.lock().unwrap().remove(&index);
+                Err(StoreError::NoQuorum)
+            }
+        }
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> BlackboardTrait for RaftBlackboard<B> {
+
+    fn new() -> Self {
+        Self::standalone(B::new())
+    }
+
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        match (&self.node, event.origin) {
+            (None, _) => self.local.send_event(event).await,
+            (Some(_), Origin::Primary) => Err(StoreError::NotAReplica),
+            (Some(node), _) => self.apply_through_log(node, event.action).await,
+        }
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        self.local.admin(command).await
+    }
+
+    fn health(&self) -> Health {
+        self.local.health()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.local.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            node: self.node.clone(),
+            waiting: self.waiting.clone(),
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio::time::sleep;
+    use crate::blackboard::create_blackboard;
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+
+    #[test]
+    fn raft_node_should_refuse_the_stale_candidates_and_the_conflicting_appends() {
+        let node = RaftNode::new("a", &[]);
+        let tell = |term| LogEntry { term, action: Action::Tell("token".into()) };
+        let append = |term, prev_index, prev_term, entries, commit| AppendRequest { term, leader: "b".into(), prev_index, prev_term, entries, commit };
+        assert_eq!(node.append(append(2, 0, 0, vec![tell(1), tell(2)], 1)), (2, true));
+        assert_eq!(node.committed(0), [(1, tell(1))]);
+        assert_eq!(node.leader().as_deref(), Some("b"));
+        assert_eq!(node.append(append(1, 2, 2, vec![], 2)), (2, false), "The appends of a former leader should be refused");
+        assert_eq!(node.append(append(2, 3, 2, vec![tell(2)], 2)), (2, false), "An append should follow the log");
+
+        let vote = |term, candidate: &str, last_index, last_term| VoteRequest { term, candidate: candidate.into(), last_index, last_term };
+        assert_eq!(node.vote(vote(3, "c", 1, 1)), (3, false), "A candidate missing entries should not be elected");
+        assert_eq!(node.vote(vote(3, "d", 2, 2)), (3, true));
+        assert_eq!(node.vote(vote(3, "c", 2, 2)), (3, false), "A single vote should be cast per term");
+
+        // The leader of term 4 replaces the uncommitted entry of term 2
+        assert_eq!(node.append(append(4, 1, 1, vec![tell(4)], 2)), (4, true));
+        assert_eq!(node.committed(1), [(2, tell(4))]);
+
+        assert_eq!(node.append(append(5, 0, 0, vec![tell(5)], 0)), (5, false), "A committed entry should never be replaced");
+        assert_eq!(node.committed(0), [(1, tell(1)), (2, tell(4))]);
+        assert_eq!(node.committed(3), [], "The entries after the last one should be none");
+    }
+
+    #[test]
+    fn raft_node_should_compact_its_log_and_install_the_snapshots_in_parts() {
+        let node = RaftNode::new("a", &[]);
+        let tell = |term| LogEntry { term, action: Action::Tell("token".into()) };
+        let append = |prev_index, prev_term, entries, commit| AppendRequest { term: 1, leader: "b".into(), prev_index, prev_term, entries, commit };
+        assert_eq!(node.append(append(0, 0, vec![tell(1), tell(1), tell(1)], 2)), (1, true));
+        node.compact(3, vec![("token".into(), 3)]);
+        assert_eq!(node.snapshot(0), None, "An uncommitted entry should not be compacted");
+        node.compact(2, vec![("token".into(), 2)]);
+        assert_eq!(node.snapshot(0), Some((2, Arc::new(vec![("token".into(), 2)]))));
+        assert_eq!(node.snapshot(2), None);
+        assert_eq!(node.committed(0), [], "The entries compacted should be applied through the snapshot");
+        assert_eq!(node.committed(2), []);
+        assert_eq!(node.append(append(1, 1, vec![tell(1), tell(1), tell(1)], 3)), (1, true), "The entries compacted should be skipped");
+        assert_eq!(node.committed(2), [(3, tell(1))]);
+
+        let part = |first, last, tokens: &[&str]| SnapshotRequest {
+            term: 1,
+            leader: "b".into(),
+            last_index: 5,
+            last_term: 1,
+            first,
+            last,
+            tokens: tokens.iter().map(|token| ((*token).into(), 1)).collect(),
+        };
+        assert_eq!(node.install(part(false, false, &["t"])), (1, false), "A part should follow the first one");
+        assert_eq!(node.install(part(true, false, &["t"])), (1, true));
+        assert_eq!(node.snapshot(2), None, "The snapshot should be installed once its last part is received");
+        assert_eq!(node.install(part(false, true, &["u"])), (1, true));
+        assert_eq!(node.snapshot(2), Some((5, Arc::new(vec![("t".into(), 1), ("u".into(), 1)]))));
+        assert_eq!(*node.subscribe().borrow(), 5, "The entries of the snapshot should be committed");
+        assert_eq!(node.append(append(5, 1, vec![tell(1)], 6)), (1, true));
+        assert_eq!(node.committed(5), [(6, tell(1))]);
+    }
+
+    #[tokio::test]
+    async fn raft_blackboard_should_keep_the_committed_tokens_when_the_leader_crashes() {
+        let config = RaftConfig {
+            heartbeat_interval: Duration::from_millis(20),
+            election_timeout: Duration::from_millis(100),
+            commit_timeout: Duration::from_secs(1),
+        };
+        let mut addrs = Vec::new();
+        for _ in 0..3 {
+            addrs.push(TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().to_string());
+        }
+        let names = ["a", "b", "c"];
+        let mut members = Vec::new();
+        for (name, addr) in names.iter().zip(&addrs) {
+            let others: Vec<(&str, &str)> = names.iter().zip(&addrs).filter(|(other, _)| *other != name).map(|(other, addr)| (*other, addr.as_str())).collect();
+            let node = RaftNode::new_with(name, &others, config).with_peer_key("cluster");
+            let blackboard = RaftBlackboard::member(create_blackboard(), node.clone());
+            let port = addr.rsplit(':').next().unwrap().parse().unwrap();
+            let listener = SocketListener::new(blackboard.clone(), Some(port)).with_raft(node.clone()).with_peer_key("cluster");
+            let listening = tokio::spawn(async move { listener.listen().await });
+            members.push((blackboard, node.clone(), node.spawn(), listening));
+        }
+        let leader = || async {
+            loop {
+                if let Some(leader) = members.iter().position(|(_, node, timers, _)| !timers.is_finished() && node.role() == RaftRole::Leader) {
+                    return leader;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        };
+        let first = timeout(Duration::from_secs(2), leader()).await.expect("A leader should be elected");
+        let (blackboard, node, ..) = &members[first];
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        let (follower, follower_node, ..) = &members[(first + 1) % 3];
+        while follower_node.leader().is_none() {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(follower.ask("token".into()).await, Err(StoreError::NotLeader(Some(node.name().into()))));
+
+        members[first].2.abort();
+        members[first].3.abort();
+        let second = timeout(Duration::from_secs(2), async {
+            loop {
+                let second = leader().await;
+                if second != first {
+                    return second;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("Another leader should be elected");
+        assert!(members[second].0.get("token".into()).await.unwrap(), "The committed tell should survive the leader");
+        assert!(!members[second].0.get("token".into()).await.unwrap());
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+use crate::communication::socket_client::SocketClient;
+use crate::model::action::Action;
+use crate::log;
+use crate::log::Level;
+use crate::error::TransportError;
+
+/// @summary - How the unacknowledged actions are retransmitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AckConfig {
+    /// How long to wait for the acknowledgment of an action before retransmitting it
+    pub ack_timeout: Duration,
+    /// Number of retransmissions before giving up on an action, None retransmits until it is acknowledged
+    pub max_retransmits: Option<u32>,
+}
+
+impl Default for AckConfig {
+    fn default() -> Self {
+        Self {
+            ack_timeout: Duration::from_millis(500),
+            max_retransmits: None,
+        }
+    }
+}
+
+/// @summary - The ReliableSender posts actions to a remote blackboard with an at-least-once guarantee.
+///
+/// Each posted action is kept until the remote acknowledges it, and retransmitted with its sequence number every
+/// ack timeout, across reconnections. As the remote deduplicates the sequence numbers of a session, a retransmitted
+/// action is applied only once.
+///
+/// @note - The posted actions are delivered concurrently, they may be applied in a different order than posted
+#[derive(Clone)]
+pub struct ReliableSender {
+    client: Arc<SocketClient>,
+    config: AckConfig,
+    unacked: Arc<Mutex<BTreeMap<u64, Action>>>,
+}
+
+impl ReliableSender {
+
+    pub fn new(client: Arc<SocketClient>, config: AckConfig) -> Self {
+        Self {
+            client,
+            config,
+            unacked: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// @summary - Post an action, without waiting for its acknowledgment
+    ///
+    /// @returns - The sequence number of the action
+    pub fn post(&self, action: Action) -> u64 {
+        let sequence = self.client.next_sequence();
+        self.unacked.lock().unwrap().insert(sequence, action.clone());
+        let sender = self.clone();
+        tokio::spawn(async move {
+            let mut retransmits = 0;
+            loop {
+                match sender.client.deliver(sequence, action.clone(), sender.config.ack_timeout).await {
+                    Ok(_) => break,
+                    Err(TransportError::RemoteError(message)) => {
+                        log!(Level::Error, "{} refused {:?}: {}", sender.client.addr(), action, message);
+                        break;
+                    },
+                    Err(_) if sender.config.max_retransmits.is_some_and(|max| retransmits >= max) => {
+                        log!(Level::Error, "{} did not acknowledge {:?}, giving up", sender.client.addr(), action);
+                        break;
+                    },
+                    Err(_) => {
+                        retransmits += 1;
+                        sleep(sender.config.ack_timeout).await;
+                    },
+                }
+            }
+            sender.unacked.lock().unwrap().remove(&sequence);
+        });
+        sequence
+    }
+
+    /// @returns - The actions posted and not acknowledged yet, by sequence number
+    pub fn unacked(&self) -> Vec<(u64, Action)> {
+        self.unacked.lock().unwrap().iter().map(|(sequence, action)| (*sequence, action.clone())).collect()
+    }
+
+    /// @summary - Wait until every posted action was acknowledged (or given up)
+    pub async fn flush(&self) {
+        while !self.unacked.lock().unwrap().is_empty() {
+            sleep(self.config.ack_timeout / 10).await;
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+    use super::*;
+    use crate::blackboard::{create_blackboard, BlackboardTrait};
+    use crate::communication::frame::{read_frame, write_frame, Frame};
+    use crate::communication::socket_client::{PendingPolicy, ReconnectPolicy};
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+
+    fn config() -> AckConfig {
+        AckConfig { ack_timeout: Duration::from_millis(50), max_retransmits: None }
+    }
+
+    #[tokio::test]
+    async fn sender_should_retransmit_until_acknowledged() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        // Acknowledge the session, drop the first post, then acknowledge its retransmission
+        let remote = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut posts = Vec::new();
+            while let Ok(Some((id, frame))) = read_frame(&mut stream).await {
+                match frame {
+                    Frame::Hello(_) => write_frame(&mut stream, id, &Frame::Response(true)).await.unwrap(),
+                    Frame::Post(action) if !posts.is_empty() => {
+                        posts.push((id, action));
+                        write_frame(&mut stream, id, &Frame::Ack).await.unwrap();
+                        return posts;
+                    },
+                    Frame::Post(action) => posts.push((id, action)),
+                    _ => {},
+                }
+            }
+            posts
+        });
+
+        let client = SocketClient::connect(&addr, ReconnectPolicy::default()).await.unwrap();
+        let sender = ReliableSender::new(Arc::new(client), config());
+        let sequence = sender.post(Action::Tell("token".into()));
+        assert_eq!(sender.unacked(), vec![(sequence, Action::Tell("token".into()))]);
+        timeout(Duration::from_secs(2), sender.flush()).await.expect("The action should be acknowledged");
+
+        let posts = remote.await.unwrap();
+        assert_eq!(posts.len(), 2);
+        assert!(posts.iter().all(|post| *post == (sequence, Action::Tell("token".into()))), "The same sequence number should be retransmitted");
+    }
+
+    #[tokio::test]
+    async fn sender_should_retransmit_on_a_new_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        // Close the first connection on the post, acknowledge it on the second one
+        let remote = tokio::spawn(async move {
+            let mut posts = Vec::new();
+            for connection in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                while let Ok(Some((id, frame))) = read_frame(&mut stream).await {
+                    match frame {
+                        Frame::Hello(_) => write_frame(&mut stream, id, &Frame::Response(true)).await.unwrap(),
+                        Frame::Post(_) if connection == 0 => {
+                            posts.push(id);
+                            break;
+                        },
+                        Frame::Post(_) => {
+                            posts.push(id);
+                            write_frame(&mut stream, id, &Frame::Ack).await.unwrap();
+                            return posts;
+                        },
+                        _ => {},
+                    }
+                }
+            }
+            posts
+        });
+
+        let policy = ReconnectPolicy { pending: PendingPolicy::Fail, ..ReconnectPolicy::default() };
+        let client = SocketClient::connect(&addr, policy).await.unwrap();
+        let sender = ReliableSender::new(Arc::new(client), config());
+        let sequence = sender.post(Action::Get("token".into()));
+        timeout(Duration::from_secs(2), sender.flush()).await.expect("The action should be acknowledged on the new connection");
+        assert_eq!(remote.await.unwrap(), vec![sequence, sequence]);
+    }
+
+    #[tokio::test]
+    async fn sender_should_post_to_a_listener() {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let blackboard = create_blackboard();
+        let served = blackboard.clone();
+        tokio::spawn(async move { SocketListener::new(served, Some(port)).listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        let sender = ReliableSender::new(Arc::new(client), config());
+
+        sender.post(Action::Tell("token".into()));
+        sender.post(Action::Tell("token".into()));
+        timeout(Duration::from_secs(2), sender.flush()).await.expect("The actions should be acknowledged");
+        assert!(blackboard.get("token".into()).await.unwrap());
+        assert!(blackboard.get("token".into()).await.unwrap());
+        assert!(!blackboard.get("token".into()).await.unwrap());
+    }
+}
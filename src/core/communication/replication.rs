@@ -0,0 +1,550 @@
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{Instant, sleep, timeout_at};
+use crate::blackboard::BlackboardTrait;
+use crate::communication::heartbeat::HeartbeatConfig;
+use crate::communication::membership::Membership;
+use crate::communication::socket_client::SocketClient;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::event::{Event, Origin};
+use crate::model::health::Health;
+use crate::model::change::StoreChange;
+use tokio::sync::broadcast;
+use crate::error::StoreError;
+use crate::log;
+use crate::log::Level;
+use crate::model::token::TokenId;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Applies the writes of the agents and streams them to the replicas
+    Primary,
+    /// Serves the reads of the agents and applies the mutations streamed by the primary
+    Replica,
+}
+
+// The id of the next replica attached by the process
+static NEXT_REPLICA: AtomicU64 = AtomicU64::new(1);
+
+// A mutation streamed to a replica, with the channel of its acknowledgment when the primary awaits a quorum; no action
+// is a barrier, acknowledged once the replica applied the mutations streamed before it and answered a heartbeat
+struct Mutation {
+    action: Option<Action>,
+    applied: Option<oneshot::Sender<bool>>,
+}
+
+// The mutations streamed to a replica, whether it installed the snapshot of the store, and the connection to it
+struct ReplicaQueue {
+    id: u64,
+    mutations: UnboundedSender<Mutation>,
+    joined: Arc<AtomicBool>,
+    client: Arc<SocketClient>,
+}
+
+impl ReplicaQueue {
+
+    // Detached once its streaming task ended, or once it missed its heartbeats
+    fn attached(&self) -> bool {
+        !self.mutations.is_closed() && self.client.is_alive()
+    }
+}
+
+/// @summary - The ReplicatedBlackboard keeps copies of a store on several nodes.
+///
+/// The primary applies the tell and get of the agents, and streams every mutation it applied (a tell, or a successful get)
+/// to its replicas, in the order it applied them. A replica serves ask and nask locally, and refuses the writes of the agents.
+/// When the primary fails, a replica can be promoted to take over the writes.
+///
+/// With a quorum, a mutation is only acknowledged once enough nodes applied it, and a primary attached to fewer replicas
+/// than the quorum requires is fenced: it refuses the writes. A get is only applied once a quorum of nodes answered,
+/// and the replicas that did not acknowledge a mutation in time are detached, as they may have missed it. On a network
+/// split, the primary on the minority side is thus fenced, as is a replica promoted there, so that a single side keeps
+/// consuming the tokens.
+///
+/// @note - A replica joins from an empty store, it is sent a snapshot of the store of the primary when attached
+pub struct ReplicatedBlackboard<B: BlackboardTrait> {
+    local: B,
+    role: Arc<RwLock<Role>>,
+    replicas: Arc<Mutex<Vec<ReplicaQueue>>>,
+    // Serializes the writes of the primary, so that the replicas receive the mutations in the order they were applied
+    writes: Arc<tokio::sync::Mutex<()>>,
+    // The nodes that must apply a mutation, the primary included, and how long the primary waits for their acknowledgments
+    quorum: Option<(usize, Duration)>,
+}
+
+impl<B: BlackboardTrait> ReplicatedBlackboard<B> {
+
+    pub fn primary(local: B) -> Self {
+        Self::new_with(local, Role::Primary)
+    }
+
+    pub fn replica(local: B) -> Self {
+        Self::new_with(local, Role::Replica)
+    }
+
+    fn new_with(local: B, role: Role) -> Self {
+        Self {
+            local,
+            role: Arc::new(RwLock::new(role)),
+            replicas: Arc::new(Mutex::new(Vec::new())),
+            writes: Arc::new(tokio::sync::Mutex::new(())),
+            quorum: None,
+        }
+    }
+
+    /// @summary - Acknowledge the mutations once a quorum of nodes applied them, fencing the primary while it is attached
+    /// to fewer replicas than the quorum requires
+    ///
+    /// @param nodes - The nodes that must apply a mutation, the primary included, e.g. 2 of a primary and 2 replicas
+    ///
+    /// @param ack_timeout - How long the primary waits for the acknowledgments of the replicas
+    ///
+    /// @note - The quorum must be a majority of the nodes, so that two sides of a split cannot both reach it
+    pub fn with_quorum(mut self, nodes: usize, ack_timeout: Duration) -> Self {
+        self.quorum = Some((nodes, ack_timeout));
+        self
+    }
+
+    /// @returns - The replicas the mutations are streamed to, once they installed the snapshot of the store, and while
+    /// they answer their heartbeats
+    pub fn attached_replicas(&self) -> usize {
+        let mut replicas = self.replicas.lock().unwrap();
+        replicas.retain(ReplicaQueue::attached);
+        replicas.iter().filter(|replica| replica.joined.load(Ordering::Acquire)).count()
+    }
+
+    pub fn role(&self) -> Role {
+        *self.role.read().unwrap()
+    }
+
+    /// @summary - Make this replica the primary, it starts accepting the writes of the agents
+    ///
+    /// @note - The other replicas must then be attached to the new primary with add_replica
+    pub fn promote(&self) {
+        *self.role.write().unwrap() = Role::Primary;
+    }
+
+    /// @summary - Stream a mutation, or a barrier, to every attached replica, forgetting the detached ones
+    ///
+    /// @returns - The acknowledgments of the replicas, if awaited, with their id
+    fn stream(&self, action: Option<Action>, acknowledged: bool) -> Vec<(u64, oneshot::Receiver<bool>)> {
+        let mut acks = Vec::new();
+        self.replicas.lock().unwrap().retain(|replica| {
+            if !replica.attached() {
+                return false;
+            }
+            let (applied, ack) = match acknowledged {
+                true => {
+                    let (applied, ack) = oneshot::channel();
+                    (Some(applied), Some(ack))
+                },
+                false => (None, None),
+            };
+            let attached = replica.mutations.send(Mutation { action: action.clone(), applied }).is_ok();
+            acks.extend(ack.filter(|_| attached).map(|ack| (replica.id, ack)));
+            attached
+        });
+        acks
+    }
+
+    async fn apply_write(&self, event: Event) -> Result<bool, StoreError> {
+        let write = self.writes.lock().await;
+        if let Some((nodes, ack_timeout)) = self.quorum {
+            if self.attached_replicas() + 1 < nodes {
+                log!(Level::Error, "Fenced, {} replicas attached while the quorum is {} nodes", self.attached_replicas(), nodes);
+                return Err(StoreError::Fenced);
+            }
+            // A get consumes the token, the primary cut from its replicas must leave the store unchanged
+            if matches!(event.action, Action::Get(_)) && !self.quorum_reached(self.stream(None, true), nodes - 1, ack_timeout).await {
+                log!(Level::Error, "Fenced, fewer than {} nodes answered before the get", nodes);
+                return Err(StoreError::Fenced);
+            }
+        }
+        let action = event.action.clone();
+        let result = self.local.send_event(event).await;
+        let Ok(true) = result else { return result };
+        let acks = self.stream(Some(action), self.quorum.is_some());
+        // The order of the mutations is set, the next write may be streamed while this one is acknowledged
+        drop(write);
+        match self.quorum {
+            Some((nodes, ack_timeout)) if !self.quorum_reached(acks, nodes - 1, ack_timeout).await => Err(StoreError::NoQuorum),
+            _ => result,
+        }
+    }
+
+    /// @summary - Wait until enough replicas acknowledged a mutation, detaching the replicas that did not if they are
+    /// too few, as they may have missed it: the primary is then fenced
+    ///
+    /// @returns - false if fewer replicas than required applied it before the timeout
+    async fn quorum_reached(&self, acks: Vec<(u64, oneshot::Receiver<bool>)>, required: usize, ack_timeout: Duration) -> bool {
+        let deadline = Instant::now() + ack_timeout;
+        let streamed: Vec<u64> = acks.iter().map(|(id, _)| *id).collect();
+        let mut pending = JoinSet::new();
+        for (id, ack) in acks {
+            pending.spawn(async move { (id, ack.await) });
+        }
+        let mut applied = Vec::new();
+        while applied.len() < required {
+            match timeout_at(deadline, pending.join_next()).await {
+                Ok(Some(Ok((id, Ok(true))))) => applied.push(id),
+                Ok(Some(_)) => {},
+                // Every replica answered, or the time is up
+                Ok(None) | Err(_) => {
+                    self.replicas.lock().unwrap().retain(|replica| !streamed.contains(&replica.id) || applied.contains(&replica.id));
+                    return false;
+                },
+            }
+        }
+        true
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> ReplicatedBlackboard<B> {
+
+    /// @summary - Bring a joining replica up to date, then stream the mutations applied from now on to it
+    ///
+    /// The replica is sent a snapshot of the store first, which it imports at once, the mutations applied meanwhile
+    /// being queued and streamed after it: it catches up without the history of the store. The nodes keep no event log,
+    /// so this queue is the only tail of the snapshot; a replica detached joins again from a new snapshot.
+    ///
+    /// @param replica - The connection to the listener of the replica, presenting its admin key for the snapshot to be
+    /// installed, e.g. compressing the big frames of the snapshot, and with a heartbeat, so that a replica silently cut
+    /// from the primary is detached once it missed its heartbeats or timed out a mutation
+    ///
+    /// @returns - The handle of the streaming task
+    ///
+    /// @note - The replica must join with an empty store, and counts in the quorum once it applied the snapshot. A replica
+    /// that can't be reached anymore (or that was promoted) is detached, as it missed mutations
+    pub fn add_replica(&self, replica: SocketClient) -> JoinHandle<()> {
+        let primary = self.clone();
+        let replica = Arc::new(replica);
+        tokio::spawn(async move {
+            let (tx, mut rx) = unbounded_channel::<Mutation>();
+            let joined = Arc::new(AtomicBool::new(false));
+            // Ends once the replica is detached and its streaming task ended
+            SocketClient::spawn_heartbeat(&replica);
+            let snapshot = {
+                // No mutation is applied between the snapshot and the attachment of the replica
+                let _write = primary.writes.lock().await;
+                let snapshot = match primary.local.admin(AdminCommand::Snapshot).await {
+                    Ok(AdminReply::Snapshot(tokens)) => tokens,
+                    other => {
+                        log!(Level::Error, "Replica {} not attached, the snapshot failed: {:?}", replica.addr(), other);
+                        return;
+                    }
+                };
+                let id = NEXT_REPLICA.fetch_add(1, Ordering::Relaxed);
+                primary.replicas.lock().unwrap().push(ReplicaQueue { id, mutations: tx, joined: joined.clone(), client: replica.clone() });
+                snapshot
+            };
+            let tokens = snapshot.len();
+            match replica.install(snapshot).await {
+                Ok(true) => log!(Level::Info, "Replica {} joined with {} tokens", replica.addr(), tokens),
+                other => {
+                    log!(Level::Error, "Replica {} detached, it failed to install the snapshot: {:?}", replica.addr(), other);
+                    return;
+                }
+            }
+            joined.store(true, Ordering::Release);
+            while let Some(Mutation { action, applied }) = rx.recv().await {
+                let Some(action) = action else {
+                    if let Some(applied) = applied {
+                        let _ = applied.send(replica.ping().await.is_ok());
+                    }
+                    continue;
+                };
+                let result = replica.replicate(action.clone()).await;
+                if let (Some(applied), Ok(result)) = (applied, &result) {
+                    let _ = applied.send(*result);
+                }
+                match result {
+                    Ok(true) => {},
+                    Ok(false) => log!(Level::Error, "Replica {} failed to apply {:?}, its store diverged", replica.addr(), action),
+                    Err(e) => {
+                        log!(Level::Error, "Replica {} detached: {:?}", replica.addr(), e);
+                        return;
+                    }
+                }
+            }
+        })
+    }
+
+    /// @summary - Promote this replica as soon as its primary is dead
+    ///
+    /// @param primary - The connection to the listener of the primary
+    ///
+    /// @param heartbeat - How often the primary is pinged, and how many heartbeats it may miss
+    ///
+    /// @returns - The handle of the watching task, it ends on promotion
+    pub fn watch_primary(&self, primary: SocketClient, heartbeat: HeartbeatConfig) -> JoinHandle<()> {
+        let replica = self.clone();
+        let primary = Arc::new(primary.with_heartbeat(heartbeat));
+        tokio::spawn(async move {
+            let heartbeats = SocketClient::spawn_heartbeat(&primary);
+            while primary.is_alive() {
+                sleep(heartbeat.interval).await;
+            }
+            heartbeats.abort();
+            replica.promote();
+            log!(Level::Info, "Primary {} is dead, promoted to primary", primary.addr());
+        })
+    }
+
+    /// @summary - Promote this replica as soon as the membership declares its primary dead, instead of watching it
+    /// with heartbeats of its own, so that a primary the other members still reach is not taken over
+    ///
+    /// @param primary - The name of the primary among the members
+    ///
+    /// @returns - The handle of the watching task, it ends on promotion
+    pub fn watch_member(&self, membership: &Membership, primary: &str) -> JoinHandle<()> {
+        let replica = self.clone();
+        let (membership, primary) = (membership.clone(), primary.to_string());
+        tokio::spawn(async move {
+            while membership.is_alive(&primary) {
+                sleep(membership.config().probe_interval).await;
+            }
+            replica.promote();
+            log!(Level::Info, "Member {} is dead, promoted to primary", primary);
+        })
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send> BlackboardTrait for ReplicatedBlackboard<B> {
+
+    fn new() -> Self {
+        Self::primary(B::new())
+    }
+
+    async fn send_event(&self, event: Event) -> Result<bool, StoreError> {
+        match (self.role(), event.origin, &event.action) {
+            (Role::Replica, Origin::Primary, _) => self.local.send_event(event).await,
+            (Role::Primary, Origin::Primary, _) => Err(StoreError::NotAReplica),
+            (_, _, Action::Ask(_) | Action::Nask(_)) => self.local.send_event(event).await,
+            (Role::Replica, _, _) => Err(StoreError::ReadOnlyReplica),
+            (Role::Primary, _, _) => self.apply_write(event).await,
+        }
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Tell(coord_data))).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Ask(coord_data))).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Get(coord_data))).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, StoreError> {
+        self.send_event(Event::new(Action::Nask(coord_data))).await
+    }
+
+    async fn admin(&self, command: AdminCommand) -> Result<AdminReply, StoreError> {
+        self.local.admin(command).await
+    }
+
+    fn health(&self) -> Health {
+        self.local.health()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<StoreChange> {
+        self.local.subscribe()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            role: self.role.clone(),
+            replicas: self.replicas.clone(),
+            writes: self.writes.clone(),
+            quorum: self.quorum,
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+    use super::*;
+    use crate::blackboard::create_blackboard;
+    use crate::communication::compression::Compression;
+    use crate::communication::frame::{read_frame, write_frame, Frame};
+    use crate::communication::membership::MembershipConfig;
+    use crate::communication::peers::PeerTable;
+    use crate::communication::socket_client::ReconnectPolicy;
+    use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+
+    /// Start a listener serving the given blackboard, installing the snapshots of the primary presenting the key
+    /// "secret", and return its address
+    async fn serve<B: BlackboardTrait + Sync + Send + 'static>(blackboard: B) -> String {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        tokio::spawn(async move { SocketListener::new(blackboard, Some(port)).with_admin_key("secret").listen().await });
+        format!("127.0.0.1:{}", port)
+    }
+
+    #[tokio::test]
+    async fn replication_should_stream_the_primary_mutations_to_the_replicas() {
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        let addr = serve(replica.clone()).await;
+        let primary = ReplicatedBlackboard::primary(create_blackboard());
+        primary.add_replica(SocketClient::connect(&addr, ReconnectPolicy::default()).await.unwrap().with_admin_key("secret"));
+
+        assert!(primary.tell("a".into()).await.unwrap());
+        assert!(primary.tell("b".into()).await.unwrap());
+        assert!(primary.get("a".into()).await.unwrap());
+        assert!(!primary.get("c".into()).await.unwrap());
+        timeout(Duration::from_secs(1), async {
+            while !replica.ask("b".into()).await.unwrap() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("The mutations should reach the replica");
+        timeout(Duration::from_secs(1), async {
+            while !replica.nask("a".into()).await.unwrap() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("The get should be replicated after the tell");
+    }
+
+    #[tokio::test]
+    async fn replica_should_refuse_the_writes_of_the_agents() {
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        assert!(matches!(replica.tell("a".into()).await, Err(StoreError::ReadOnlyReplica)));
+        assert!(matches!(replica.get("a".into()).await, Err(StoreError::ReadOnlyReplica)));
+        assert!(!replica.ask("a".into()).await.unwrap());
+        assert!(replica.nask("a".into()).await.unwrap());
+
+        let primary = ReplicatedBlackboard::primary(create_blackboard());
+        let replicated = primary.send_event(Event::replicated(Action::Tell("a".into()))).await;
+        assert!(matches!(replicated, Err(StoreError::NotAReplica)));
+    }
+
+    #[tokio::test]
+    async fn replica_should_be_promoted_when_the_primary_dies() {
+        // A primary answering the heartbeats of a single connection, until it is aborted
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let primary_addr = listener.local_addr().unwrap().to_string();
+        let primary = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Ok(Some((id, frame))) = read_frame(&mut stream).await {
+                let answer = if frame == Frame::Ping { Frame::Pong } else { Frame::Response(true) };
+                write_frame(&mut stream, id, &answer).await.unwrap();
+            }
+        });
+
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        let heartbeat = HeartbeatConfig { interval: Duration::from_millis(20), max_missed: 2 };
+        let watcher = replica.watch_primary(SocketClient::connect(&primary_addr, ReconnectPolicy::default()).await.unwrap(), heartbeat);
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(replica.role(), Role::Replica, "A primary answering heartbeats should not be replaced");
+
+        primary.abort();
+        timeout(Duration::from_secs(2), watcher).await.expect("The replica should be promoted").unwrap();
+        assert_eq!(replica.role(), Role::Primary);
+        assert!(replica.tell("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn replica_should_be_promoted_when_the_membership_declares_the_primary_dead() {
+        let peers = PeerTable::new();
+        peers.insert("primary", "127.0.0.1:1");
+        let membership = Membership::new(peers.clone()).with_config(MembershipConfig { probe_interval: Duration::from_millis(10), ..MembershipConfig::default() });
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        let watcher = replica.watch_member(&membership, "primary");
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(replica.role(), Role::Replica, "A primary alive among the members should not be replaced");
+
+        peers.mark_dead("primary");
+        timeout(Duration::from_secs(1), watcher).await.expect("The replica should be promoted").unwrap();
+        assert_eq!(replica.role(), Role::Primary);
+    }
+
+    #[tokio::test]
+    async fn quorum_should_fence_the_primary_cut_from_its_replicas() {
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        let addr = serve(replica.clone()).await;
+        let primary = ReplicatedBlackboard::primary(create_blackboard()).with_quorum(2, Duration::from_secs(1));
+        assert!(matches!(primary.tell("a".into()).await, Err(StoreError::Fenced)), "A primary without replicas should be fenced");
+        assert!(primary.nask("a".into()).await.unwrap(), "A fenced primary should not apply the write");
+
+        primary.add_replica(SocketClient::connect(&addr, ReconnectPolicy::default()).await.unwrap().with_admin_key("secret"));
+        while primary.attached_replicas() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(primary.tell("a".into()).await.unwrap());
+        assert!(replica.ask("a".into()).await.unwrap(), "The quorum should be reached once the replica applied the write");
+
+        // On the minority side of a split, the promoted replica has no replica of its own
+        let promoted = ReplicatedBlackboard::replica(create_blackboard()).with_quorum(2, Duration::from_secs(1));
+        promoted.promote();
+        assert!(matches!(promoted.get("a".into()).await, Err(StoreError::Fenced)));
+    }
+
+    #[tokio::test]
+    async fn quorum_should_fence_the_primary_whose_replica_is_blackholed() {
+        // A replica answering every frame until it is blackholed, then silently dropping them
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let blackholed = Arc::new(AtomicBool::new(false));
+        let silent = blackholed.clone();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Ok(Some((id, frame))) = read_frame(&mut stream).await {
+                if silent.load(Ordering::SeqCst) {
+                    continue;
+                }
+                let answer = if frame == Frame::Ping { Frame::Pong } else { Frame::Response(true) };
+                write_frame(&mut stream, id, &answer).await.unwrap();
+            }
+        });
+
+        let primary = ReplicatedBlackboard::primary(create_blackboard()).with_quorum(2, Duration::from_millis(200));
+        let heartbeat = HeartbeatConfig { interval: Duration::from_millis(50), max_missed: 2 };
+        primary.add_replica(SocketClient::connect(&addr, ReconnectPolicy::default()).await.unwrap().with_heartbeat(heartbeat));
+        while primary.attached_replicas() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(primary.tell("a".into()).await.unwrap());
+        assert!(primary.tell("a".into()).await.unwrap());
+
+        blackholed.store(true, Ordering::SeqCst);
+        assert!(matches!(primary.get("a".into()).await, Err(StoreError::Fenced)), "The quorum should be checked before the get");
+        assert!(matches!(primary.get("a".into()).await, Err(StoreError::Fenced)), "The silent replica should be detached");
+        assert_eq!(primary.attached_replicas(), 0);
+        assert_eq!(primary.admin(AdminCommand::Snapshot).await.unwrap(), AdminReply::Snapshot(vec![("a".into(), 2)]), "The fenced primary should not consume the token");
+    }
+
+    #[tokio::test]
+    async fn replica_should_join_from_a_snapshot_of_the_primary() {
+        let primary = ReplicatedBlackboard::primary(create_blackboard());
+        for token in ["a", "a", "b"] {
+            assert!(primary.tell(token.into()).await.unwrap());
+        }
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        let addr = serve(replica.clone()).await;
+        let client = SocketClient::connect(&addr, ReconnectPolicy::default()).await.unwrap().with_compression(Compression::Lz4).with_admin_key("secret");
+        primary.add_replica(client);
+        // Applied while the replica joins, streamed after the snapshot
+        assert!(primary.get("b".into()).await.unwrap());
+        assert!(primary.tell("c".into()).await.unwrap());
+
+        timeout(Duration::from_secs(1), async {
+            while !replica.ask("c".into()).await.unwrap() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("The replica should catch up");
+        assert_eq!(replica.admin(AdminCommand::Snapshot).await.unwrap(), AdminReply::Snapshot(vec![("a".into(), 2), ("c".into(), 1)]));
+        assert_eq!(primary.attached_replicas(), 1);
+    }
+}
@@ -0,0 +1,987 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use mockall::automock;
+use rand::Rng;
+use tokio::sync::{oneshot, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use crate::communication::compression::Compression;
+use crate::communication::frame::Frame;
+use crate::communication::heartbeat::HeartbeatConfig;
+use crate::communication::peers::PeerTable;
+use crate::communication::raft::{AppendRequest, SnapshotRequest, VoteRequest};
+use crate::communication::transport::{FrameSink, Link, SocketTransport, Transport};
+use crate::error::TransportError;
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::clock::Stamp;
+use crate::model::health::Health;
+use crate::trace::{ActiveSpan, SpanContext};
+use crate::model::token::TokenId;
+
+/// What to do with a request that was pending when the connection broke
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PendingPolicy {
+    /// Reconnect and send the request again
+    Resend,
+    /// Fail the request with TransportError::ConnectionLost, the next request will reconnect
+    Fail,
+}
+
+/// @summary - How the client retries to reach the remote blackboard.
+///
+/// The n-th retry waits `initial_delay * multiplier^n`, capped to `max_delay`.
+/// With jitter, a random part of the second half of this delay is dropped so that clients don't retry in lockstep.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: u32,
+    /// Number of connection attempts before giving up, None retries forever
+    pub max_attempts: Option<u32>,
+    pub jitter: bool,
+    pub pending: PendingPolicy,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(5),
+            multiplier: 2,
+            max_attempts: Some(10),
+            jitter: true,
+            pending: PendingPolicy::Resend,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+
+    /// @summary - Compute the delay to wait before the given retry
+    ///
+    /// @param attempt - The number of failed attempts so far (starting at 0)
+    ///
+    /// @returns - The delay, between half of the exponential delay and the exponential delay when jitter is enabled
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.checked_pow(attempt).unwrap_or(u32::MAX);
+        let delay = self.initial_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(rand::rng().random::<f64>())
+        } else {
+            delay
+        }
+    }
+
+    fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt < max)
+    }
+}
+
+#[automock]
+pub trait SocketClientTrait {
+
+    /// @summary - Send an action to the remote blackboard
+    ///
+    /// @param action - The action to apply remotely
+    ///
+    /// @returns - A promise of the result of the action on the remote store
+    fn send(&self, action: Action) -> impl Future<Output = Result<bool, TransportError>> + Send;
+
+    fn tell(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, TransportError>> + Send;
+
+    fn ask(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, TransportError>> + Send;
+
+    fn get(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, TransportError>> + Send;
+
+    fn nask(&self, coord_data: TokenId) -> impl Future<Output = Result<bool, TransportError>> + Send;
+}
+
+/// @summary - The SocketClient sends actions to a remote blackboard and survives its restarts.
+///
+/// The requests are multiplexed on a single connection: each one carries a correlation id, so that concurrent requests
+/// don't wait for each other and may be answered in any order.
+///
+/// When the connection breaks, the client reconnects w.r.t. its ReconnectPolicy, and the pending requests are either resent or failed.
+/// A resent request keeps its sequence number, so that the remote applies it only once (see DedupWindow).
+///
+/// With heartbeats enabled, a request whose answer does not come within the heartbeat timeout fails with TransportError::PeerDead.
+pub struct SocketClient {
+    addr: String,
+    transport: Arc<dyn Transport>,
+    policy: ReconnectPolicy,
+    heartbeat: Option<HeartbeatConfig>,
+    // The routing table entry to keep up to date with the liveness of the remote
+    peer: Option<(PeerTable, Box<str>)>,
+    alive: AtomicBool,
+    // Named to the remote on each connection, so that it recognizes the requests resent on a new one by their sequence numbers
+    session: u64,
+    // Requested to the remote on each connection
+    compression: Compression,
+    // Presented to the remote on each connection, to be granted the admin capability or the blackboard of a tenant
+    key: Option<String>,
+    next_id: AtomicU64,
+    connection: Mutex<Option<Arc<Connection>>>,
+}
+
+/// The requests waiting for their response on a connection, by correlation id
+#[derive(Default)]
+struct Pending {
+    // Set once the connection broke, no request can wait on it anymore
+    closed: bool,
+    waiting: HashMap<u64, oneshot::Sender<Frame>>,
+    // The subscriptions, by the correlation id of their Subscribe request, receiving the tokens changed and their count
+    streams: HashMap<u64, UnboundedSender<(Box<str>, u32)>>,
+    // The parts of the snapshots received so far, by the correlation id of their Snapshot command
+    parts: HashMap<u64, Vec<(Box<str>, u32)>>,
+}
+
+/// An established connection, whose responses are dispatched to the pending requests by a background task
+struct Connection {
+    writer: Mutex<Box<dyn FrameSink>>,
+    pending: Arc<std::sync::Mutex<Pending>>,
+    dispatcher: JoinHandle<()>,
+}
+
+impl Connection {
+
+    fn new((mut reader, writer): Link) -> Arc<Self> {
+        let pending = Arc::new(std::sync::Mutex::new(Pending::default()));
+        let dispatched = pending.clone();
+        let dispatcher = tokio::spawn(async move {
+            while let Ok(Some((id, frame))) = reader.recv().await {
+                let mut pending = dispatched.lock().unwrap();
+                match frame {
+                    // A change may come before the acceptance of its subscription, a subscription no longer read is
+                    // forgotten
+                    Frame::Change(count, token) => {
+                        if pending.streams.get(&id).is_some_and(|stream| stream.send((token, count)).is_err()) {
+                            pending.streams.remove(&id);
+                        }
+                    },
+                    // The parts of a snapshot are joined, the request waiting for the whole snapshot
+                    Frame::AdminReply(AdminReply::SnapshotPart { tokens, last: false }) => {
+                        pending.parts.entry(id).or_default().extend(tokens);
+                    },
+                    Frame::AdminReply(AdminReply::SnapshotPart { tokens, last: true }) => {
+                        let mut snapshot = pending.parts.remove(&id).unwrap_or_default();
+                        snapshot.extend(tokens);
+                        if let Some(waiting) = pending.waiting.remove(&id) {
+                            let _ = waiting.send(Frame::AdminReply(AdminReply::Snapshot(snapshot)));
+                        }
+                    },
+                    frame => match pending.waiting.remove(&id) {
+                        Some(waiting) => {
+                            let _ = waiting.send(frame);
+                        },
+                        // The Error ending a subscription, or a late response to a request that timed out, dropped
+                        None => {
+                            pending.streams.remove(&id);
+                        },
+                    },
+                }
+            }
+            Self::close_pending(&dispatched);
+        });
+        Arc::new(Self {
+            writer: Mutex::new(writer),
+            pending,
+            dispatcher,
+        })
+    }
+
+    /// @summary - Fail the requests pending on the connection, they see it broken
+    fn close_pending(pending: &std::sync::Mutex<Pending>) {
+        let mut pending = pending.lock().unwrap();
+        pending.closed = true;
+        pending.waiting.clear();
+        pending.streams.clear();
+        pending.parts.clear();
+    }
+
+    fn close(&self) {
+        self.dispatcher.abort();
+        Self::close_pending(&self.pending);
+    }
+
+    /// @summary - Send a request and wait for the response carrying the same correlation id
+    ///
+    /// @param limit - How long to wait for the response, None waits until the connection breaks
+    async fn exchange(&self, id: u64, request: &Frame, limit: Option<Duration>) -> Exchange {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.closed {
+                return Exchange::Broken;
+            }
+            pending.waiting.insert(id, tx);
+        }
+        if self.writer.lock().await.send(id, request).await.is_err() {
+            self.pending.lock().unwrap().waiting.remove(&id);
+            return Exchange::Broken;
+        }
+        let response = match limit {
+            Some(limit) => match timeout(limit, rx).await {
+                Ok(response) => response,
+                Err(_) => {
+                    self.pending.lock().unwrap().waiting.remove(&id);
+                    return Exchange::Silent;
+                }
+            },
+            None => rx.await,
+        };
+        match response {
+            Ok(response) => Exchange::Done(response),
+            // The connection broke before the response came
+            Err(_) => Exchange::Broken,
+        }
+    }
+}
+
+/// Outcome of one request/response exchange on an established connection
+enum Exchange {
+    Done(Frame),
+    Broken,
+    Silent,
+}
+
+impl SocketClient {
+
+    /// @summary - Connect to a remote blackboard
+    ///
+    /// @param addr - The address of the remote blackboard (host:port, or unix:<path> for a Unix domain socket)
+    ///
+    /// @param policy - The reconnection policy, also applied to this first connection
+    ///
+    /// @returns - The connected client, or TransportError::ConnectionFailed if the policy is exhausted
+    pub async fn connect(addr: &str, policy: ReconnectPolicy) -> Result<Self, TransportError> {
+        Self::connect_with(Arc::new(SocketTransport), addr, policy).await
+    }
+
+    /// @summary - Connect to a remote blackboard through another transport than the sockets
+    ///
+    /// @see - connect
+    pub async fn connect_with(transport: Arc<dyn Transport>, addr: &str, policy: ReconnectPolicy) -> Result<Self, TransportError> {
+        let client = Self::unconnected(transport, addr, policy);
+        let stream = client.reconnect().await?;
+        let connection = client.open(stream, None).await?;
+        *client.connection.lock().await = Some(connection);
+        Ok(client)
+    }
+
+    /// @summary - A client connecting to the remote blackboard on its first request, e.g. when no runtime runs yet
+    ///
+    /// @see - connect
+    pub fn new_with(addr: &str, policy: ReconnectPolicy) -> Self {
+        Self::unconnected(Arc::new(SocketTransport), addr, policy)
+    }
+
+    fn unconnected(transport: Arc<dyn Transport>, addr: &str, policy: ReconnectPolicy) -> Self {
+        Self {
+            addr: addr.to_string(),
+            transport,
+            policy,
+            heartbeat: None,
+            peer: None,
+            alive: AtomicBool::new(true),
+            session: rand::random::<u64>(),
+            compression: Compression::None,
+            key: None,
+            next_id: AtomicU64::new(0),
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// @summary - Enable dead-peer detection on this client
+    ///
+    /// @note - Periodic pings are only sent once the heartbeat task is started with spawn_heartbeat
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatConfig) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// @summary - Compress the big frames exchanged with the remote, if it supports the codec
+    ///
+    /// @note - The established connection is closed, the compression is negotiated on the next one
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        if let Some(established) = self.connection.get_mut().take() {
+            established.close();
+        }
+        self
+    }
+
+    /// @summary - Present the admin key to the remote, so that it accepts the admin commands of this client
+    ///
+    /// @note - The established connection is closed, the key is presented on the next one
+    pub fn with_admin_key(self, key: &str) -> Self {
+        self.with_key(key)
+    }
+
+    /// @summary - Present the key of the peers to the remote, so that it accepts the frames of the other nodes from this
+    /// client, e.g. the mutations streamed by a primary
+    ///
+    /// @note - The established connection is closed, the key is presented on the next one
+    pub fn with_peer_key(self, key: &str) -> Self {
+        self.with_key(key)
+    }
+
+    /// @summary - Present the key of a tenant to the remote, so that this client works on the blackboard of the tenant
+    ///
+    /// @note - The established connection is closed, the key is presented on the next one
+    pub fn with_tenant_key(self, key: &str) -> Self {
+        self.with_key(key)
+    }
+
+    fn with_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        // The session named without the key belongs to the anonymous identity on the remote, another one is named
+        self.session = rand::random::<u64>();
+        if let Some(established) = self.connection.get_mut().take() {
+            established.close();
+        }
+        self
+    }
+
+    /// @summary - Link the client to the routing table entry of the remote, updated on each liveness change
+    pub fn with_peer(mut self, peers: PeerTable, name: &str) -> Self {
+        self.peer = Some((peers, name.into()));
+        self
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// @returns - false if the remote missed too many heartbeats (or a request timed out) and did not answer since
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    fn report(&self, alive: bool) {
+        self.alive.store(alive, Ordering::SeqCst);
+        if let Some((peers, name)) = &self.peer {
+            if alive { peers.mark_alive(name) } else { peers.mark_dead(name) }
+        }
+    }
+
+    /// @summary - Check that the remote answers a heartbeat within one heartbeat interval
+    ///
+    /// @returns - Ok if the remote answered, TransportError::PeerDead otherwise
+    ///
+    /// @note - If the connection was dropped, a single connection attempt is made. A silent connection is dropped,
+    /// so that the next exchange starts on a new one.
+    pub async fn ping(&self) -> Result<(), TransportError> {
+        let interval = self.heartbeat.unwrap_or_default().interval;
+        let connection = {
+            let mut connection = self.connection.lock().await;
+            match connection.as_ref() {
+                Some(established) => established.clone(),
+                None => match timeout(interval, self.transport.connect(&self.addr)).await {
+                    Ok(Ok(stream)) => match self.open(stream, Some(interval)).await {
+                        Ok(opened) => connection.insert(opened).clone(),
+                        Err(_) => return Err(TransportError::PeerDead),
+                    },
+                    _ => return Err(TransportError::PeerDead),
+                }
+            }
+        };
+        match connection.exchange(self.next_id(), &Frame::Ping, Some(interval)).await {
+            Exchange::Done(Frame::Pong) => Ok(()),
+            _ => {
+                self.drop_connection(&connection).await;
+                Err(TransportError::PeerDead)
+            }
+        }
+    }
+
+    /// @summary - Start the background task pinging the remote every heartbeat interval
+    ///
+    /// @param client - The client to monitor, the task stops when the client is dropped
+    ///
+    /// @returns - The handle of the heartbeat task
+    ///
+    /// @note - After max_missed consecutive missed heartbeats, the remote is marked dead in the peer table
+    pub fn spawn_heartbeat(client: &Arc<SocketClient>) -> JoinHandle<()> {
+        let config = client.heartbeat.unwrap_or_default();
+        let client = Arc::downgrade(client);
+        tokio::spawn(async move {
+            let mut missed = 0;
+            loop {
+                sleep(config.interval).await;
+                let Some(client) = client.upgrade() else { return };
+                match client.ping().await {
+                    Ok(_) => {
+                        missed = 0;
+                        client.report(true);
+                    },
+                    Err(_) => {
+                        missed += 1;
+                        if missed >= config.max_missed {
+                            client.report(false);
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn reconnect(&self) -> Result<Link, TransportError> {
+        let mut attempt = 0;
+        loop {
+            match self.transport.connect(&self.addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => {
+                    attempt += 1;
+                    if !self.policy.allows(attempt) {
+                        return Err(TransportError::ConnectionFailed(format!("{} after {} attempts: {}", self.addr, attempt, e)));
+                    }
+                    sleep(self.policy.backoff_delay(attempt - 1)).await;
+                }
+            }
+        }
+    }
+
+    /// @summary - Establish a connection on the stream, by presenting the key of this client, naming its session to
+    /// the remote and negotiating the compression
+    ///
+    /// @note - The key is presented first, the remote claiming the session for the identity of the key
+    ///
+    /// @param limit - How long to wait for the remote to acknowledge the session
+    async fn open(&self, stream: Link, limit: Option<Duration>) -> Result<Arc<Connection>, TransportError> {
+        let connection = Connection::new(stream);
+        let result = match self.authenticate(&connection, limit).await {
+            Ok(_) => match connection.exchange(self.next_id(), &Frame::Hello(self.session), limit).await {
+                Exchange::Done(Frame::Response(true)) => self.negotiate(&connection, limit).await,
+                Exchange::Done(Frame::Error(e)) => Err(TransportError::RemoteError(e)),
+                Exchange::Done(other) => Err(TransportError::ProtocolError(format!("Unexpected answer to hello: {:?}", other))),
+                Exchange::Broken | Exchange::Silent => Err(TransportError::ConnectionLost),
+            },
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => Ok(connection),
+            Err(e) => {
+                connection.close();
+                Err(e)
+            }
+        }
+    }
+
+    /// @summary - Ask the remote to compress the connection, it stays uncompressed if the remote refuses
+    async fn negotiate(&self, connection: &Connection, limit: Option<Duration>) -> Result<(), TransportError> {
+        if self.compression == Compression::None {
+            return Ok(());
+        }
+        match connection.exchange(self.next_id(), &Frame::Compress(self.compression), limit).await {
+            Exchange::Done(Frame::Response(accepted)) => {
+                if accepted {
+                    connection.writer.lock().await.set_compression(self.compression);
+                }
+                Ok(())
+            },
+            Exchange::Done(other) => Err(TransportError::ProtocolError(format!("Unexpected answer to compress: {:?}", other))),
+            Exchange::Broken | Exchange::Silent => Err(TransportError::ConnectionLost),
+        }
+    }
+
+    /// @summary - Present the admin key, the key of the peers or the key of a tenant to the remote, if any
+    async fn authenticate(&self, connection: &Connection, limit: Option<Duration>) -> Result<(), TransportError> {
+        let Some(key) = &self.key else { return Ok(()) };
+        match connection.exchange(self.next_id(), &Frame::Authenticate(key.clone()), limit).await {
+            Exchange::Done(Frame::Response(true)) => Ok(()),
+            Exchange::Done(Frame::Response(false)) => Err(TransportError::RemoteError("The key was refused".into())),
+            Exchange::Done(other) => Err(TransportError::ProtocolError(format!("Unexpected answer to authenticate: {:?}", other))),
+            Exchange::Broken | Exchange::Silent => Err(TransportError::ConnectionLost),
+        }
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// @returns - The established connection, connecting w.r.t. the policy if there is none
+    async fn connection(&self) -> Result<Arc<Connection>, TransportError> {
+        let mut connection = self.connection.lock().await;
+        match connection.as_ref() {
+            Some(established) => Ok(established.clone()),
+            None => {
+                let stream = self.reconnect().await?;
+                Ok(connection.insert(self.open(stream, self.heartbeat.map(|heartbeat| heartbeat.timeout())).await?).clone())
+            },
+        }
+    }
+
+    /// @summary - Close the connection, unless it was already replaced by a new one
+    async fn drop_connection(&self, broken: &Arc<Connection>) {
+        let mut connection = self.connection.lock().await;
+        if connection.as_ref().is_some_and(|established| Arc::ptr_eq(established, broken)) {
+            *connection = None;
+        }
+        broken.close();
+    }
+
+    /// @summary - Reserve a sequence number, to deliver an action with
+    pub fn next_sequence(&self) -> u64 {
+        self.next_id()
+    }
+
+    /// @summary - Make one attempt to post an action to the remote blackboard
+    ///
+    /// @param sequence - The sequence number of the action, the same one must be used to retransmit it
+    ///
+    /// @param ack_timeout - How long to wait for the acknowledgment
+    ///
+    /// @returns - Ok once the remote acknowledged the action, an error if it must be retransmitted (or was refused, with TransportError::RemoteError)
+    ///
+    /// @note - Unlike the requests, a failed delivery is not resent: retransmission is left to the caller (see ReliableSender)
+    pub async fn deliver(&self, sequence: u64, action: Action, ack_timeout: Duration) -> Result<(), TransportError> {
+        let connection = self.connection().await?;
+        match connection.exchange(sequence, &Frame::Post(action.named()), Some(ack_timeout)).await {
+            Exchange::Done(Frame::Ack) => {
+                self.report(true);
+                Ok(())
+            },
+            Exchange::Done(Frame::Error(message)) => Err(TransportError::RemoteError(message)),
+            Exchange::Done(other) => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+            Exchange::Silent => Err(TransportError::PeerDead),
+            Exchange::Broken => {
+                self.drop_connection(&connection).await;
+                Err(TransportError::ConnectionLost)
+            },
+        }
+    }
+
+    /// @summary - Forward an action on behalf of a peer blackboard, the remote applies it on its local store only
+    pub async fn forward(&self, action: Action) -> Result<bool, TransportError> {
+        self.request(Frame::Forward(action.named())).await
+    }
+
+    /// @summary - Forward an action on behalf of a peer blackboard, recording the round trip as a `remote forward` span
+    /// of the trace given, the spans of the remote being its children
+    ///
+    /// @param trace - The operation causing the forward, e.g. the span of the request of an event; None forwards
+    /// the action without a trace
+    pub async fn forward_traced(&self, action: Action, trace: Option<&SpanContext>) -> Result<bool, TransportError> {
+        let Some(trace) = trace else { return self.forward(action).await };
+        let span = ActiveSpan::start("remote forward", Some(trace)).with_attribute("bacht.peer", &self.addr);
+        let result = self.request(Frame::Traced(span.context(), Box::new(Frame::Forward(action.named())))).await;
+        span.with_attribute("bacht.result", format!("{:?}", result)).end();
+        result
+    }
+
+    /// @summary - Reserve an occurrence of a token on the remote blackboard on behalf of a peer, see LeaseTable
+    ///
+    /// @param duration - How long the occurrence is held before it goes back to the remote store, unless confirmed
+    ///
+    /// @returns - The lease holding the occurrence, or None if the token is absent
+    pub async fn reserve(&self, token: Box<str>, duration: Duration) -> Result<Option<u64>, TransportError> {
+        let millis = duration.as_millis().min(u32::MAX as u128) as u32;
+        match self.call(Frame::Reserve(millis, token)).await? {
+            Frame::Lease(lease) => Ok(Some(lease)),
+            Frame::Response(false) => Ok(None),
+            Frame::Error(message) => Err(TransportError::RemoteError(message)),
+            other => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+        }
+    }
+
+    /// @summary - Consume the occurrence held by a lease
+    ///
+    /// @returns - false if the lease expired, the occurrence went back to the remote store
+    pub async fn confirm(&self, lease: u64) -> Result<bool, TransportError> {
+        self.request(Frame::Confirm(lease)).await
+    }
+
+    /// @summary - Give the occurrence held by a lease back to the remote store
+    ///
+    /// @returns - false if the lease expired or was confirmed
+    pub async fn release(&self, lease: u64) -> Result<bool, TransportError> {
+        self.request(Frame::Release(lease)).await
+    }
+
+    /// @summary - Stream a mutation applied by this primary blackboard to the remote replica
+    pub async fn replicate(&self, action: Action) -> Result<bool, TransportError> {
+        self.request(Frame::Replicate(action.named())).await
+    }
+
+    /// @summary - Broadcast a mutation applied by this blackboard to a causal peer, see CausalBlackboard
+    pub async fn causal(&self, stamp: Stamp, action: Action) -> Result<bool, TransportError> {
+        self.request(Frame::Causal(stamp, action.named())).await
+    }
+
+    /// @summary - Apply several actions in order on the remote blackboard, in a single round trip
+    ///
+    /// @param actions - The actions to apply, e.g. a burst of primitives emitted by an agent
+    ///
+    /// @returns - The result of each action, in order, or an error if the batch could not be exchanged
+    pub async fn batch(&self, actions: Vec<Action>) -> Result<Vec<Result<bool, TransportError>>, TransportError> {
+        let expected = actions.len();
+        match self.call(Frame::Batch(actions.iter().map(Action::named).collect())).await? {
+            Frame::Results(results) if results.len() == expected => Ok(results.into_iter().map(Self::result).collect()),
+            Frame::Error(message) => Err(TransportError::RemoteError(message)),
+            other => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+        }
+    }
+
+    /// @summary - Manage the remote blackboard
+    ///
+    /// @returns - The answer of the remote, or TransportError::RemoteError if this client does not hold the admin capability
+    pub async fn admin(&self, command: AdminCommand) -> Result<AdminReply, TransportError> {
+        match self.call(Frame::Admin(command)).await? {
+            Frame::AdminReply(reply) => Ok(reply),
+            Frame::Error(message) => Err(TransportError::RemoteError(message)),
+            other => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+        }
+    }
+
+    /// @summary - Subscribe to the changes of the tokens of the remote blackboard matching a pattern, instead of polling
+    /// its snapshot
+    ///
+    /// @param pattern - An SI-Term whose variables match any term, e.g. `msg(X)`, or a variable alone for every token
+    ///
+    /// @returns - Each token changed with its number of occurrences after the change, or TransportError::RemoteError if
+    /// the pattern does not parse or this client presented no key
+    ///
+    /// @note - The changes end with the connection, or once the client missed some by reading them too slowly: it then
+    /// subscribes again, e.g. after reading a snapshot
+    pub async fn subscribe(&self, pattern: &str) -> Result<UnboundedReceiver<(Box<str>, u32)>, TransportError> {
+        let connection = self.connection().await?;
+        let id = self.next_id();
+        let (changes, received) = unbounded_channel();
+        connection.pending.lock().unwrap().streams.insert(id, changes);
+        let result = match connection.exchange(id, &Frame::Subscribe(pattern.into()), self.heartbeat.map(|heartbeat| heartbeat.timeout())).await {
+            Exchange::Done(Frame::Response(true)) => return Ok(received),
+            Exchange::Done(Frame::Error(message)) => Err(TransportError::RemoteError(message)),
+            Exchange::Done(other) => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+            Exchange::Silent => Err(TransportError::PeerDead),
+            Exchange::Broken => {
+                self.drop_connection(&connection).await;
+                Err(TransportError::ConnectionLost)
+            },
+        };
+        connection.pending.lock().unwrap().streams.remove(&id);
+        result
+    }
+
+    /// @summary - Run a BachT program on the remote blackboard, e.g. `tell(token);get(token)`
+    ///
+    /// @returns - true if the agent terminated, false if it got stuck, or TransportError::RemoteError if it does not parse
+    /// or does not terminate within the transitions allowed by the remote
+    ///
+    /// @note - The remote only runs the programs of a client presenting the admin key or the key of a tenant
+    pub async fn run(&self, program: &str) -> Result<bool, TransportError> {
+        self.request(Frame::Program(program.to_string())).await
+    }
+
+    /// @summary - Probe the health of the remote blackboard
+    ///
+    /// @returns - The liveness of its worker, the depth of its queue and the size of its store
+    pub async fn health(&self) -> Result<Health, TransportError> {
+        match self.call(Frame::Health).await? {
+            Frame::HealthReport(health) => Ok(health),
+            Frame::Error(message) => Err(TransportError::RemoteError(message)),
+            other => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+        }
+    }
+
+    /// @summary - Ask the remote to ping one of its peers on behalf of this node, see Membership
+    ///
+    /// @param peer - The name of the peer, as known by the remote
+    ///
+    /// @param timeout - How long the remote waits for the answer of the peer
+    ///
+    /// @returns - true if the peer answered the remote in time
+    pub async fn probe(&self, peer: &str, timeout: Duration) -> Result<bool, TransportError> {
+        let millis = timeout.as_millis().min(u32::MAX as u128) as u32;
+        self.request(Frame::Probe(millis, peer.into())).await
+    }
+
+    /// @summary - Send the snapshot of the store of the primary to a joining replica
+    pub async fn install(&self, tokens: Vec<(Box<str>, u32)>) -> Result<bool, TransportError> {
+        self.request(Frame::Install(tokens)).await
+    }
+
+    /// @summary - Ask a member of a Raft cluster for its vote, see RaftNode
+    ///
+    /// @returns - The term of the member, and true if it voted for the candidate
+    pub async fn vote(&self, request: VoteRequest) -> Result<(u64, bool), TransportError> {
+        Self::term(self.call(Frame::Vote(request)).await?)
+    }
+
+    /// @summary - Send a part of the snapshot of the leader of a Raft cluster to a follower missing the entries it
+    /// compacted, see RaftNode
+    ///
+    /// @returns - The term of the follower, and false if it refused the part, e.g. one not following the previous part
+    pub async fn snapshot(&self, request: SnapshotRequest) -> Result<(u64, bool), TransportError> {
+        Self::term(self.call(Frame::Snapshot(request)).await?)
+    }
+
+    /// @summary - Append the entries of the leader of a Raft cluster to the log of a follower, see RaftNode
+    ///
+    /// @returns - The term of the follower, and false if its log misses the entry preceding them
+    pub async fn append(&self, request: AppendRequest) -> Result<(u64, bool), TransportError> {
+        Self::term(self.call(Frame::Append(request)).await?)
+    }
+
+    fn term(response: Frame) -> Result<(u64, bool), TransportError> {
+        match response {
+            Frame::Term(term, granted) => Ok((term, granted)),
+            Frame::Error(message) => Err(TransportError::RemoteError(message)),
+            other => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+        }
+    }
+
+    async fn request(&self, request: Frame) -> Result<bool, TransportError> {
+        Self::result(self.call(request).await?)
+    }
+
+    fn result(response: Frame) -> Result<bool, TransportError> {
+        match response {
+            Frame::Response(result) => Ok(result),
+            Frame::Error(message) => Err(TransportError::RemoteError(message)),
+            other => Err(TransportError::ProtocolError(format!("Unexpected frame: {:?}", other))),
+        }
+    }
+
+    /// @summary - Send a request and wait for its response, resending it on a new connection w.r.t. the policy
+    async fn call(&self, request: Frame) -> Result<Frame, TransportError> {
+        let id = self.next_id();
+        let mut resent = 0;
+        loop {
+            let connection = self.connection().await?;
+            match connection.exchange(id, &request, self.heartbeat.map(|heartbeat| heartbeat.timeout())).await {
+                Exchange::Done(response) => {
+                    self.report(true);
+                    return Ok(response);
+                },
+                Exchange::Silent => {
+                    self.drop_connection(&connection).await;
+                    self.report(false);
+                    return Err(TransportError::PeerDead);
+                },
+                Exchange::Broken => {
+                    self.drop_connection(&connection).await;
+                    resent += 1;
+                    if self.policy.pending == PendingPolicy::Fail || !self.policy.allows(resent) {
+                        return Err(TransportError::ConnectionLost);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl SocketClientTrait for SocketClient {
+
+    async fn send(&self, action: Action) -> Result<bool, TransportError> {
+        self.request(Frame::Request(action.named())).await
+    }
+
+    async fn tell(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Tell(coord_data)).await
+    }
+
+    async fn ask(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Ask(coord_data)).await
+    }
+
+    async fn get(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Get(coord_data)).await
+    }
+
+    async fn nask(&self, coord_data: TokenId) -> Result<bool, TransportError> {
+        self.send(Action::Nask(coord_data)).await
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+    use crate::communication::frame::{read_frame, write_frame};
+
+    fn policy(pending: PendingPolicy) -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(100),
+            multiplier: 2,
+            max_attempts: Some(20),
+            jitter: false,
+            pending,
+        }
+    }
+
+    /// Accept a connection and acknowledge the session of the client
+    async fn accept_session(listener: &TcpListener) -> TcpStream {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let Some((id, Frame::Hello(_))) = read_frame(&mut stream).await.unwrap() else { panic!("The client should name its session") };
+        write_frame(&mut stream, id, &Frame::Response(true)).await.unwrap();
+        stream
+    }
+
+    /// Serve `nbr_requests` requests on one connection (answering true), then close everything
+    async fn serve_once(listener: TcpListener, nbr_requests: usize) {
+        let mut stream = accept_session(&listener).await;
+        for _ in 0..nbr_requests {
+            match read_frame(&mut stream).await {
+                Ok(Some((id, Frame::Request(_)))) => write_frame(&mut stream, id, &Frame::Response(true)).await.unwrap(),
+                _ => return,
+            }
+        }
+    }
+
+    /// Answer `nbr_pings` pings on one connection, then stay silent without closing it
+    async fn answer_pings_then_hang(listener: TcpListener, nbr_pings: usize) {
+        let mut stream = accept_session(&listener).await;
+        for _ in 0..nbr_pings {
+            if let Ok(Some((id, Frame::Ping))) = read_frame(&mut stream).await {
+                write_frame(&mut stream, id, &Frame::Pong).await.unwrap();
+            }
+        }
+        // Keep the connection open but silent
+        sleep(Duration::from_secs(5)).await;
+        drop(listener);
+    }
+
+    #[test]
+    fn backoff_should_grow_exponentially_up_to_the_max_delay() {
+        let policy = policy(PendingPolicy::Resend);
+        assert_eq!(policy.backoff_delay(0), Duration::from_millis(10));
+        assert_eq!(policy.backoff_delay(1), Duration::from_millis(20));
+        assert_eq!(policy.backoff_delay(3), Duration::from_millis(80));
+        assert_eq!(policy.backoff_delay(4), Duration::from_millis(100));
+        assert_eq!(policy.backoff_delay(64), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn backoff_with_jitter_should_stay_between_half_and_full_delay() {
+        let policy = ReconnectPolicy { jitter: true, ..policy(PendingPolicy::Resend) };
+        for _ in 0..100 {
+            let delay = policy.backoff_delay(2);
+            assert!(delay >= Duration::from_millis(20) && delay <= Duration::from_millis(40), "Unexpected delay {:?}", delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_should_fail_to_connect_when_policy_is_exhausted() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let policy = ReconnectPolicy { max_attempts: Some(2), ..policy(PendingPolicy::Resend) };
+        assert!(matches!(SocketClient::connect(&addr, policy).await, Err(TransportError::ConnectionFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn client_should_resend_pending_request_after_remote_restart() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let first_server = tokio::spawn(serve_once(listener, 1));
+
+        let client = SocketClient::connect(&addr, policy(PendingPolicy::Resend)).await.unwrap();
+        assert!(client.tell("token".into()).await.unwrap());
+        first_server.await.unwrap();
+
+        // The remote restarts on the same address
+        let listener = TcpListener::bind(&addr).await.unwrap();
+        tokio::spawn(serve_once(listener, 1));
+
+        assert!(client.ask("token".into()).await.unwrap(), "The pending request should be resent on the new connection");
+    }
+
+    #[tokio::test]
+    async fn client_should_fail_pending_request_when_policy_is_fail() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let first_server = tokio::spawn(serve_once(listener, 1));
+
+        let client = SocketClient::connect(&addr, policy(PendingPolicy::Fail)).await.unwrap();
+        assert!(client.tell("token".into()).await.unwrap());
+        first_server.await.unwrap();
+
+        assert!(matches!(client.ask("token".into()).await, Err(TransportError::ConnectionLost)));
+
+        // The next request reconnects
+        let listener = TcpListener::bind(&addr).await.unwrap();
+        tokio::spawn(serve_once(listener, 1));
+        assert!(client.ask("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn client_should_multiplex_concurrent_requests_on_one_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        // Answer two requests in the reverse order, true for a tell and false otherwise
+        tokio::spawn(async move {
+            let mut stream = accept_session(&listener).await;
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                requests.push(read_frame(&mut stream).await.unwrap().unwrap());
+            }
+            for (id, request) in requests.into_iter().rev() {
+                let response = Frame::Response(matches!(request, Frame::Request(Action::Tell(_))));
+                write_frame(&mut stream, id, &response).await.unwrap();
+            }
+            sleep(Duration::from_secs(1)).await;
+        });
+
+        let client = SocketClient::connect(&addr, policy(PendingPolicy::Fail)).await.unwrap();
+        let both = async { tokio::join!(client.tell("token".into()), client.ask("token".into())) };
+        let (told, asked) = timeout(Duration::from_secs(2), both).await.expect("The requests should not wait for each other");
+        assert!(told.unwrap());
+        assert!(!asked.unwrap());
+    }
+
+    #[tokio::test]
+    async fn client_should_fail_pending_request_when_remote_stays_silent() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(answer_pings_then_hang(listener, 0));
+
+        let peers = PeerTable::new();
+        peers.insert("remote", &addr);
+        let heartbeat = HeartbeatConfig { interval: Duration::from_millis(20), max_missed: 3 };
+        let client = SocketClient::connect(&addr, policy(PendingPolicy::Resend)).await.unwrap()
+            .with_heartbeat(heartbeat)
+            .with_peer(peers.clone(), "remote");
+
+        assert!(matches!(client.tell("token".into()).await, Err(TransportError::PeerDead)));
+        assert!(!client.is_alive());
+        assert!(!peers.is_alive("remote"), "The routing table should be updated");
+    }
+
+    #[tokio::test]
+    async fn heartbeat_should_mark_peer_dead_after_max_missed_pongs() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(answer_pings_then_hang(listener, 2));
+
+        let peers = PeerTable::new();
+        peers.insert("remote", &addr);
+        let heartbeat = HeartbeatConfig { interval: Duration::from_millis(20), max_missed: 2 };
+        let client = Arc::new(SocketClient::connect(&addr, policy(PendingPolicy::Resend)).await.unwrap()
+            .with_heartbeat(heartbeat)
+            .with_peer(peers.clone(), "remote"));
+        let monitor = SocketClient::spawn_heartbeat(&client);
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(peers.is_alive("remote"), "The remote answered the first pings");
+
+        timeout(Duration::from_secs(2), async {
+            while peers.is_alive("remote") {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("The remote should be marked dead");
+        assert!(!client.is_alive());
+
+        drop(client);
+        timeout(Duration::from_secs(1), monitor).await.expect("The heartbeat task should stop with the client").unwrap();
+    }
+}
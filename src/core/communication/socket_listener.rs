@@ -1,11 +1,49 @@
-use std::ascii::escape_default;
 use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+use std::sync::{Arc, Mutex};
 use mockall::automock;
-use tokio::io::AsyncReadExt;
-use tokio::net::{TcpListener, TcpStream};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::TcpListener;
 use crate::blackboard::{BlackboardTrait};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{channel, unbounded_channel, Sender};
+use tokio::sync::{oneshot, watch};
+use crate::communication::compression::Compression;
+use crate::communication::dedup::{DedupWindow, Identity};
+use crate::communication::frame::{Frame, snapshot_replies};
+use crate::communication::heartbeat::HeartbeatConfig;
+use crate::communication::lease::LeaseTable;
+use crate::communication::membership::Membership;
+use crate::communication::raft::RaftNode;
+use crate::communication::tenants::{TenantRegistry, same_key};
+use crate::communication::transport::{Acceptor, Link, Transport};
+use crate::language::blackboard_interface::LocalBlackboardInterface;
+use crate::language::parser::{parse, parse_term};
+use crate::language::model::data::Term;
+use crate::language::model::data::Expr;
+use crate::language::simulator::{Simulator, SimulatorTrait, Transition};
+use crate::model::action::Action;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::change::StoreChange;
+use crate::model::event::Event;
+use crate::model::token::TokenId;
+use crate::runtime::RuntimeHandle;
+use crate::log;
+use crate::metrics;
+use crate::log::Level;
+use crate::error::{TableFull, TransportError};
+use crate::trace::{self, ActiveSpan};
+use tracing::Instrument;
 
-const DEFAULT_SOCKET_PORT: u16 = 2138; // BACH in alphabetical order
+pub const DEFAULT_SOCKET_PORT: u16 = 2138; // BACH in alphabetical order
+/// The transitions a program run for a client executes at most, see SocketListener::with_program_steps
+pub const DEFAULT_PROGRAM_STEPS: usize = 100_000;
+// The changes of its subscriptions a connection holds before writing them, a subscription whose client reads them more
+// slowly than the store changes ends once it missed some
+const SUBSCRIPTION_BUFFER: usize = 1024;
 
 #[automock]
 pub trait SocketListenerTrait<B: BlackboardTrait + 'static> {
@@ -19,15 +57,239 @@ pub trait SocketListenerTrait<B: BlackboardTrait + 'static> {
     ///
     /// @returns - A Result indicating success or failure
     ///
-    /// @note - It starts a thread that listens for incoming messages and parses them into events
-    fn listen(&self) -> impl Future<Output=Result<(), String>>;
+    /// @note - It starts a thread that listens for incoming messages and parses them into events.
+    /// Dropping the future stops listening and closes the connections.
+    fn listen(&self) -> impl Future<Output=Result<(), TransportError>>;
 
 }
 
 /// @summary - The SocketListener is responsible for listening to incoming message, and parse it into event.
 pub struct SocketListener<B: BlackboardTrait> {
     port: u16,
-    blackboard: B
+    address: IpAddr,
+    // Accept the IPv4 clients on an IPv6 address
+    dual_stack: bool,
+    // Listened on instead of the port when set
+    unix_socket: Option<PathBuf>,
+    // The transport and the address bound instead of the port and the Unix socket when set
+    transport: Option<(Arc<dyn Transport>, String)>,
+    blackboard: B,
+    heartbeat: Option<HeartbeatConfig>,
+    // Expires the requests of the clients still queued after it
+    request_ttl: Option<Duration>,
+    // The transitions executed by a program before it is stopped
+    program_steps: usize,
+    dedup: DedupWindow,
+    // The occurrences reserved by the peers
+    leases: LeaseTable,
+    // Pings the peers on behalf of the members that could not reach them
+    membership: Option<Membership>,
+    // Answers the votes and the appends of the other members of a Raft cluster
+    raft: Option<RaftNode>,
+    // Grants the admin capability to the connections presenting it
+    admin_key: Option<Arc<str>>,
+    // Grants the frames of the other nodes, e.g. the mutations of a primary, to the connections presenting it
+    peer_key: Option<Arc<str>>,
+    // The blackboards of the connections presenting the key of a tenant
+    tenants: TenantRegistry<B>,
+    // Runs the connections, their requests and their heartbeats
+    runtime: RuntimeHandle,
+}
+
+impl<B: BlackboardTrait> SocketListener<B> {
+
+    /// @summary - Close the connections of peers that stay silent longer than the heartbeat timeout
+    ///
+    /// @note - Peers are expected to send a Ping every heartbeat interval when they have no request to send
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatConfig) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// @summary - Discard the requests still waiting for the worker after the time-to-live, answering them with
+    /// StoreError::Expired, e.g. so that the requests resent by a slow client don't apply long after it gave up
+    pub fn with_request_ttl(mut self, ttl: Duration) -> Self {
+        self.request_ttl = Some(ttl);
+        self
+    }
+
+    /// @summary - Stop the programs of the clients after the given number of transitions, answering them with an Error,
+    /// e.g. so that `*tell(a)` does not run forever
+    pub fn with_program_steps(mut self, steps: usize) -> Self {
+        self.program_steps = steps;
+        self
+    }
+
+    /// @summary - Bind the TCP port on another address than the IPv4 loopback, e.g. an IPv6 one
+    pub fn with_address(mut self, address: IpAddr) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// @summary - Also accept the IPv4 clients (as IPv4-mapped addresses) when bound on an IPv6 address
+    ///
+    /// @note - Without it, an IPv6 listener only accepts IPv6 clients, whatever the default of the system
+    pub fn with_dual_stack(mut self, dual_stack: bool) -> Self {
+        self.dual_stack = dual_stack;
+        self
+    }
+
+    /// @summary - Listen on a Unix domain socket instead of the TCP port, for the processes of the same host
+    ///
+    /// @param path - The path of the socket file, a socket file left there by a previous listener is replaced
+    ///
+    /// @note - The clients connect to it with the `unix:<path>` address
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.unix_socket = Some(path.into());
+        self
+    }
+
+    /// @summary - Spawn the connections and their requests, and wait their heartbeats, on another executor than tokio
+    ///
+    /// @note - The sockets still need a tokio reactor, a listener bound on another transport not
+    pub fn with_runtime(mut self, runtime: RuntimeHandle) -> Self {
+        self.runtime = runtime;
+        self
+    }
+}
+
+impl<B: BlackboardTrait + Sync + Send + 'static> SocketListener<B> {
+
+    fn bind_tcp(&self) -> Result<TcpListener, TransportError> {
+        let addr = SocketAddr::new(self.address, self.port);
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+            .map_err(|e| TransportError::BindFailed(format!("Failed to create socket: {}", e)))?;
+        if addr.is_ipv6() {
+            socket.set_only_v6(!self.dual_stack).map_err(|e| TransportError::BindFailed(format!("Failed to configure socket: {}", e)))?;
+        }
+        socket.set_reuse_address(true).map_err(|e| TransportError::BindFailed(format!("Failed to configure socket: {}", e)))?;
+        socket.set_nonblocking(true).map_err(|e| TransportError::BindFailed(format!("Failed to configure socket: {}", e)))?;
+        socket.bind(&addr.into()).map_err(|e| TransportError::BindFailed(format!("Failed to bind socket: {}", e)))?;
+        socket.listen(1024).map_err(|e| TransportError::BindFailed(format!("Failed to listen on socket: {}", e)))?;
+        TcpListener::from_std(socket.into()).map_err(|e| TransportError::BindFailed(format!("Failed to bind socket: {}", e)))
+    }
+
+    /// @summary - Listen through another transport than the sockets
+    ///
+    /// @param addr - The address to bind, in the format of the transport
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>, addr: &str) -> Self {
+        self.transport = Some((transport, addr.to_string()));
+        self
+    }
+
+    /// @summary - Accept the admin commands of the clients authenticated with this key
+    ///
+    /// @note - Without a key, the admin commands are always refused
+    pub fn with_admin_key(mut self, key: &str) -> Self {
+        self.admin_key = Some(key.into());
+        self
+    }
+
+    /// @summary - Accept the frames of the other nodes of the cluster, e.g. the mutations streamed by a primary, from the
+    /// clients authenticated with this key
+    ///
+    /// @note - The clients presenting the admin key are accepted as well, without any key these frames are refused
+    pub fn with_peer_key(mut self, key: &str) -> Self {
+        self.peer_key = Some(key.into());
+        self
+    }
+
+    /// @summary - Serve the connections presenting the key of a tenant on its blackboard, isolated from the blackboard
+    /// of the listener and from the other tenants
+    pub fn with_tenants(mut self, tenants: TenantRegistry<B>) -> Self {
+        self.tenants = tenants;
+        self
+    }
+
+    /// @summary - Share the deduplication of the resent requests with other listeners of the same blackboard
+    pub fn with_dedup(mut self, dedup: DedupWindow) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// @summary - Share the occurrences reserved by the peers with other listeners of the same blackboard
+    pub fn with_leases(mut self, leases: LeaseTable) -> Self {
+        self.leases = leases;
+        self
+    }
+
+    /// @summary - Answer the indirect probes of the members of the cluster, by pinging the peers of the membership
+    ///
+    /// @note - Without a membership, the probes are refused
+    pub fn with_membership(mut self, membership: Membership) -> Self {
+        self.membership = Some(membership);
+        self
+    }
+
+    /// @summary - Answer the votes and the appends of the other members of the Raft cluster of the node
+    ///
+    /// @note - Without a node, they are refused
+    pub fn with_raft(mut self, node: RaftNode) -> Self {
+        self.raft = Some(node);
+        self
+    }
+
+    /// @summary - Serve an accepted connection in the background
+    ///
+    /// @param stopped - Dropped with the listener, stopping its connections
+    fn serve(&self, stopped: &watch::Sender<()>, link: Link, name: String) {
+        let mut stop = stopped.subscribe();
+        let cloned_bb = self.blackboard.clone();
+        let timeouts = Timeouts { heartbeat: self.heartbeat, request_ttl: self.request_ttl, program_steps: self.program_steps };
+        let shared = Shared {
+            dedup: self.dedup.clone(),
+            leases: self.leases.clone(),
+            membership: self.membership.clone(),
+            raft: self.raft.clone(),
+            runtime: self.runtime.clone(),
+        };
+        let access = Access { admin_key: self.admin_key.clone(), peer_key: self.peer_key.clone(), tenants: self.tenants.clone() };
+        // The span of the requests of the connection, from its accept on
+        let span = tracing::debug_span!("connection", connection = %name);
+        log!(Level::Debug, span => "[{}] Connection accepted", name);
+        let open = metrics::connection_opened();
+        self.runtime.spawn(async move {
+            // Closed when the connection ends, or is stopped with the listener
+            let _open = open;
+            tokio::select! {
+                result = handle_connection(link, cloned_bb, timeouts, shared, access, name).instrument(span) => result.unwrap_or_else(|e| {
+                    log!(Level::Error, "Error handling connection: {}", e);
+                }),
+                // Only changes once the listener dropped the sender
+                _ = stop.changed() => {},
+            }
+        });
+    }
+
+    async fn bind(&self) -> Result<Box<dyn Acceptor>, TransportError> {
+        if let Some((transport, addr)) = &self.transport {
+            let acceptor = transport.bind(addr).await.map_err(|e| TransportError::BindFailed(format!("Failed to bind {}: {}", addr, e)))?;
+            log!(Level::Info, "Listening on {}", addr);
+            return Ok(acceptor);
+        }
+        if let Some(path) = &self.unix_socket {
+            return self.bind_unix(path);
+        }
+        let listener = self.bind_tcp()?;
+        log!(Level::Info, "Listening on {}", SocketAddr::new(self.address, self.port));
+        Ok(Box::new(listener))
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(&self, path: &std::path::Path) -> Result<Box<dyn Acceptor>, TransportError> {
+        use std::os::unix::fs::FileTypeExt;
+        if std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            std::fs::remove_file(path).map_err(|e| TransportError::BindFailed(format!("Failed to remove stale socket: {}", e)))?;
+        }
+        let listener = tokio::net::UnixListener::bind(path).map_err(|e| TransportError::BindFailed(format!("Failed to bind socket: {}", e)))?;
+        log!(Level::Info, "Listening on {}", path.display());
+        Ok(Box::new(listener))
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(&self, _path: &std::path::Path) -> Result<Box<dyn Acceptor>, TransportError> {
+        Err(TransportError::BindFailed("Unix domain sockets are not supported on this platform".to_string()))
+    }
 }
 
 impl<B: BlackboardTrait + Sync + Send + 'static> SocketListenerTrait<B> for SocketListener<B> {
@@ -39,45 +301,979 @@ impl<B: BlackboardTrait + Sync + Send + 'static> SocketListenerTrait<B> for Sock
         }
         Self{
             port,
-            blackboard
-        }
-    }
-
-    async fn listen(&self) -> Result<(), String> {
-        let addr = format!("127.0.0.1:{}", self.port);
-        let socket = TcpListener::bind(&addr).await.map_err(|e| format!("Failed to bind socket: {}", e));
-        let mut i = 0;
-        match socket {
-            Ok(listener) => {
-                println!("Listening on {}", addr);
-                loop {
-                    let (stream, _) = listener.accept().await.map_err(|e| format!("Failed to accept connection: {}", e))?;
-                    let cloned_bb = self.blackboard.clone();
-                    let _ = tokio::spawn(async move {
-                        handle_connection(stream, cloned_bb, i.clone().to_string()).await.unwrap_or_else(|e| {
-                            eprintln!("Error handling connection: {}", e);
-                        });
-                    });
-                    i += 1;
-                };
-                Ok(())
-            },
-            Err(e) => Err(format!("Failed to bind socket: {}", e)),
+            address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dual_stack: false,
+            blackboard,
+            unix_socket: None,
+            transport: None,
+            heartbeat: None,
+            request_ttl: None,
+            program_steps: DEFAULT_PROGRAM_STEPS,
+            dedup: DedupWindow::default(),
+            leases: LeaseTable::default(),
+            membership: None,
+            raft: None,
+            admin_key: None,
+            peer_key: None,
+            tenants: TenantRegistry::new(),
+            runtime: RuntimeHandle::default(),
+        }
+    }
+
+    async fn listen(&self) -> Result<(), TransportError> {
+        let mut acceptor = self.bind().await?;
+        let (stopped, _) = watch::channel(());
+        loop {
+            let (link, name) = acceptor.accept().await.map_err(|e| TransportError::Io(format!("Failed to accept connection: {}", e)))?;
+            self.serve(&stopped, link, name);
+        }
+    }
+}
+
+// The keys granting the admin capability, the frames of the peers or the blackboard of a tenant to the connections of
+// a listener
+struct Access<B: BlackboardTrait> {
+    admin_key: Option<Arc<str>>,
+    peer_key: Option<Arc<str>>,
+    tenants: TenantRegistry<B>,
+}
+
+// The state of a listener shared by its connections
+struct Shared {
+    dedup: DedupWindow,
+    leases: LeaseTable,
+    membership: Option<Membership>,
+    raft: Option<RaftNode>,
+    runtime: RuntimeHandle,
+}
+
+// The timeouts of the connections of a listener, and the limit of their programs
+#[derive(Debug, Clone, Copy)]
+struct Timeouts {
+    heartbeat: Option<HeartbeatConfig>,
+    request_ttl: Option<Duration>,
+    program_steps: usize,
+}
+
+/// @summary - Run a BachT program on the blackboard, with the simulator, one transition after the other
+///
+/// @param steps - The transitions executed before the program is stopped and answered with an Error
+///
+/// @param request - The span of the request, its parsing being recorded as a child of it
+async fn run_program<B: BlackboardTrait + Sync>(blackboard: B, source: &str, steps: usize, request: Option<ActiveSpan>) -> Frame {
+    let parsing = request.as_ref().map(|request| ActiveSpan::start("parse", Some(&request.context())));
+    let parsed = parse(source);
+    if let Some(parsing) = parsing {
+        parsing.with_attribute("bacht.parsed", parsed.is_ok()).end();
+    }
+    let response = match parsed {
+        Ok(agent) => run_steps(&Simulator::new_with(LocalBlackboardInterface::new_with(blackboard)), agent, steps).await,
+        Err(e) => Frame::Error(format!("Invalid program: {}", e)),
+    };
+    if let Some(request) = request {
+        request.end();
+    }
+    response
+}
+
+/// @returns - Response(true) once the agent terminated, Response(false) once it is stuck, or an Error once it executed
+/// the given number of transitions
+async fn run_steps<S: SimulatorTrait>(simulator: &S, mut agent: Expr, steps: usize) -> Frame {
+    for _ in 0..steps {
+        agent = match simulator.step(agent).await {
+            Ok(Some(Transition { continuation: Expr::BachtAstEmptyAgent(), .. })) => return Frame::Response(true),
+            Ok(Some(transition)) => transition.continuation,
+            Ok(None) => return Frame::Response(false),
+            Err(e) => return Frame::Error(e.to_string()),
+        };
+    }
+    Frame::Error(format!("The program did not terminate within {} transitions", steps))
+}
+
+/// How the outcome of the events of a request is answered
+pub(crate) enum Reply {
+    /// The result of the single event, in a Response or an Error
+    Response,
+    /// Ack once the single event was applied, or an Error
+    Ack,
+    /// The outcome of each event, in order
+    Results,
+}
+
+/// @summary - Apply the events of a request one after the other, and build the answer to the request
+pub(crate) async fn apply_events<B: BlackboardTrait>(blackboard: &B, events: Vec<Event>, reply: Reply) -> Frame {
+    let mut outcomes = Vec::with_capacity(events.len());
+    for event in events {
+        outcomes.push(match blackboard.send_event(event).await {
+            Ok(result) => Frame::Response(result),
+            Err(e) => Frame::Error(e.to_string()),
+        });
+    }
+    match reply {
+        Reply::Results => Frame::Results(outcomes),
+        Reply::Ack if matches!(outcomes.first(), Some(Frame::Response(_))) => Frame::Ack,
+        _ => outcomes.pop().unwrap_or(Frame::Error("No event to apply".into())),
+    }
+}
+
+/// @summary - The events of the actions of a request, once their tokens are interned
+///
+/// @returns - The events, or TableFull if the table of the process holds too many names to intern a token
+pub(crate) fn intern_events(actions: Vec<Action<Box<str>>>, event: impl Fn(Action) -> Event) -> Result<Vec<Event>, TableFull> {
+    actions.into_iter().map(|action| action.intern().map(&event)).collect()
+}
+
+/// @summary - Import the tokens of a snapshot of the primary into the store at once, the mutations the primary applied
+/// since the snapshot following it as Replicate frames
+///
+/// @returns - Response(true) once the snapshot was imported, or an Error if the store is unchanged
+async fn install<B: BlackboardTrait>(blackboard: &B, tokens: Vec<(Box<str>, u32)>) -> Frame {
+    match blackboard.admin(AdminCommand::Import(tokens)).await {
+        Ok(_) => Frame::Response(true),
+        Err(e) => Frame::Error(e.to_string()),
+    }
+}
 
+/// @summary - Push the changes of the tokens matching the pattern of a subscription, as Change frames carrying the
+/// correlation id of the subscription, until the connection is closed
+///
+/// @note - A subscription missing changes, its client reading them too slowly, ends with an Error
+async fn push_changes(mut store_changes: broadcast::Receiver<StoreChange>, pattern: Term, id: u64, changes: Sender<(u64, Frame)>, mut closed: watch::Receiver<()>) {
+    loop {
+        let change = tokio::select! {
+            change = store_changes.recv() => change,
+            _ = closed.changed() => return,
+        };
+        let pushed = match change {
+            Ok(StoreChange { token, count }) if matching(&pattern, &token) => Frame::Change(count, token.as_str().into()),
+            Ok(_) => continue,
+            Err(RecvError::Lagged(missed)) => Frame::Error(format!("The subscription missed {} changes", missed)),
+            Err(RecvError::Closed) => return,
+        };
+        let ended = matches!(pushed, Frame::Error(_));
+        if changes.send((id, pushed)).await.is_err() || ended {
+            return;
         }
     }
 }
 
-async fn handle_connection<B: BlackboardTrait>(mut stream: TcpStream, _blackboard: B, name: String) -> Result<(), String> {
-    let mut buffer = vec![0; 1024];
+// A variable alone matches any token, even one that is not an SI-Term
+pub(crate) fn matching(pattern: &Term, token: &str) -> bool {
+    matches!(pattern, Term::Var(_)) || parse_term(token).is_ok_and(|term| pattern.unify(&term, &mut Vec::new()))
+}
+
+/// @summary - Apply a step of a remote get on behalf of a peer: reserving, confirming or releasing an occurrence
+async fn apply_lease<B>(blackboard: &B, leases: &LeaseTable, frame: Frame) -> Frame
+where B: BlackboardTrait + Sync + Send + 'static {
+    let outcome = match frame {
+        Frame::Reserve(duration, token) => match TokenId::try_intern(&token) {
+            Ok(token) => leases.reserve(blackboard, token, Duration::from_millis(duration as u64)).await
+                .map(|lease| lease.map_or(Frame::Response(false), Frame::Lease)),
+            Err(e) => return Frame::Error(e.to_string()),
+        },
+        Frame::Confirm(lease) => Ok(Frame::Response(leases.confirm(lease))),
+        Frame::Release(lease) => leases.release(blackboard, lease).await.map(Frame::Response),
+        other => return Frame::Error(format!("Unexpected frame: {:?}", other)),
+    };
+    outcome.unwrap_or_else(|e| Frame::Error(e.to_string()))
+}
+
+
+/// @summary - Serve the requests of one connection until the peer closes it
+///
+/// @note - Each request frame is turned into events sent to the blackboard (a batch being applied in order). The requests are served concurrently,
+/// each response carrying the correlation id of its request is written as soon as it is ready.
+/// Once the client named its session, its requests are applied only once, even when resent on another connection. A
+/// session belongs to the identity of the client naming it first (its tenant, the peers, the admin or no key), another
+/// identity naming it is refused.
+/// The admin commands are only applied once the client presented the admin key, the snapshots and the mutations of a
+/// primary, the mutations of a causal peer, the reservations of a federated peer and the votes and the appends of the
+/// members of a Raft cluster once it presented the admin key or the key of the peers, the programs and the
+/// subscriptions once it presented any of them or the key of a tenant.
+/// A client presenting the key of a tenant works on the blackboard of the tenant instead, until it presents another key.
+async fn handle_connection<B>((mut reader, mut writer): Link, default: B, timeouts: Timeouts, shared: Shared, access: Access<B>, name: String) -> Result<(), TransportError>
+where B: BlackboardTrait + Sync + Send + 'static {
+    let Shared { dedup, leases, membership, raft, runtime } = shared;
+    let mut blackboard = default.clone();
+    // The session named by the client, claimed for its identity
+    let mut session: Option<(Identity, u64)> = None;
+    let mut identity = Identity::Anonymous;
+    let mut admin = false;
+    // Presented the admin key or the key of the peers
+    let mut peer = false;
+    // Presented any key, that of a tenant included
+    let mut authenticated = false;
+    let (responses, mut outbox) = unbounded_channel::<(u64, Frame)>();
+    // The changes pushed to the subscriptions of the connection, until it is closed
+    let (changes, mut pushed) = channel::<(u64, Frame)>(SUBSCRIPTION_BUFFER);
+    let (closed, _) = watch::channel(());
+    // The compression requested by the client, applied to the responses
+    let compression = Arc::new(Mutex::new(Compression::None));
+    let compressed = compression.clone();
+    let (written, write_task) = oneshot::channel();
+    runtime.spawn(async move {
+        let result = async {
+            loop {
+                let (id, response) = tokio::select! {
+                    Some(response) = outbox.recv() => response,
+                    Some(change) = pushed.recv() => change,
+                    else => break,
+                };
+                writer.set_compression(*compressed.lock().unwrap());
+                writer.send(id, &response).await.map_err(TransportError::from)?;
+            }
+            Ok::<(), TransportError>(())
+        }.await;
+        let _ = written.send(result);
+    });
     loop {
-        let n = stream.read(&mut buffer).await.map_err(|e| format!("Failed to read from socket: {}", e))?;
-        if n == 0 {
-            break;
+        let frame = match timeouts.heartbeat {
+            Some(heartbeat) => match runtime.timeout(heartbeat.timeout(), reader.recv()).await {
+                Some(frame) => frame,
+                None => {
+                    log!(Level::Debug, "[{}] Peer missed {} heartbeats", name, heartbeat.max_missed);
+                    break;
+                }
+            },
+            None => reader.recv().await,
+        };
+        let Some((id, frame)) = frame.map_err(TransportError::from)? else { break };
+        let (parent, frame) = match frame {
+            Frame::Traced(parent, request) => (Some(parent), *request),
+            frame => (None, frame),
+        };
+        // The events of the request are created in its span, left before the next frame is awaited
+        let span = tracing::trace_span!("request", id);
+        let _entered = span.enter();
+        // The span of a traced request, or of a request starting a trace while the spans are recorded
+        let request = || (parent.is_some() || trace::enabled()).then(|| ActiveSpan::start("request", parent.as_ref()).with_attribute("bacht.connection", &name));
+        let (events, reply) = match frame {
+            Frame::Request(action) => (intern_events(vec![action], Event::new), Reply::Response),
+            // A posted action is acknowledged without its result
+            Frame::Post(action) => (intern_events(vec![action], Event::new), Reply::Ack),
+            Frame::Batch(actions) => (intern_events(actions, Event::new), Reply::Results),
+            Frame::Forward(action) => (intern_events(vec![action], Event::forwarded), Reply::Response),
+            // Only the primary, presenting the key of the peers, writes to a replica
+            Frame::Replicate(_) if !peer => {
+                if responses.send((id, Frame::Error("The key of the peers or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            Frame::Replicate(action) => (intern_events(vec![action], Event::replicated), Reply::Response),
+            // Only the causal peers, presenting the key of the peers, broadcast their mutations
+            Frame::Causal(..) if !peer => {
+                if responses.send((id, Frame::Error("The key of the peers or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            Frame::Causal(stamp, action) => (intern_events(vec![action], |action| Event::causal(action, stamp.clone())), Reply::Response),
+            Frame::Ping => {
+                if responses.send((id, Frame::Pong)).is_err() { break }
+                continue;
+            },
+            Frame::Hello(client_session) => {
+                let response = if dedup.claim(&identity, client_session) {
+                    session = Some((identity.clone(), client_session));
+                    Frame::Response(true)
+                } else {
+                    session = None;
+                    Frame::Error("The session belongs to another client".into())
+                };
+                if responses.send((id, response)).is_err() { break }
+                continue;
+            },
+            // Answered from the state of the blackboard, even when its queue is stuck
+            Frame::Health => {
+                if responses.send((id, Frame::HealthReport(blackboard.health()))).is_err() { break }
+                continue;
+            },
+            Frame::Authenticate(key) => {
+                admin = access.admin_key.as_deref().is_some_and(|admin_key| same_key(admin_key, &key));
+                peer = admin || access.peer_key.as_deref().is_some_and(|peer_key| same_key(peer_key, &key));
+                let tenant = if peer { None } else { access.tenants.authenticate(&key) };
+                let accepted = peer || tenant.is_some();
+                authenticated = accepted;
+                (identity, blackboard) = match tenant {
+                    Some((tenant, tenant_blackboard)) => {
+                        log!(Level::Debug, "[{}] Connection of tenant {}", name, tenant);
+                        (Identity::Tenant(tenant), tenant_blackboard)
+                    },
+                    None if admin => (Identity::Admin, default.clone()),
+                    None if peer => (Identity::Peer, default.clone()),
+                    None => (Identity::Anonymous, default.clone()),
+                };
+                // A session named under another identity is dropped, the client names it once authenticated
+                session = session.filter(|(owner, _)| *owner == identity);
+                if responses.send((id, Frame::Response(accepted))).is_err() { break }
+                continue;
+            },
+            Frame::Admin(_) if !admin => {
+                if responses.send((id, Frame::Error("The admin capability is required".into()))).is_err() { break }
+                continue;
+            },
+            Frame::Admin(command) => {
+                let blackboard = blackboard.clone();
+                let responses = responses.clone();
+                runtime.spawn(async move {
+                    // A snapshot too big for one frame is sent in parts, in order under the id of the command
+                    let replies = match blackboard.admin(command).await {
+                        Ok(AdminReply::Snapshot(tokens)) => snapshot_replies(tokens),
+                        Ok(reply) => vec![Frame::AdminReply(reply)],
+                        Err(e) => vec![Frame::Error(e.to_string())],
+                    };
+                    for reply in replies {
+                        if responses.send((id, reply)).is_err() { break }
+                    }
+                });
+                continue;
+            },
+            // Only the primary, presenting the key of the peers, installs its snapshot
+            Frame::Install(_) if !peer => {
+                if responses.send((id, Frame::Error("The key of the peers or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            // Imported as a whole, the count of a token never sizing an allocation
+            Frame::Install(tokens) => {
+                let blackboard = blackboard.clone();
+                let responses = responses.clone();
+                let dedup = dedup.clone();
+                let session = session.clone();
+                runtime.spawn(async move {
+                    let apply = install(&blackboard, tokens);
+                    let response = match &session {
+                        Some(session) => dedup.apply(session, id, apply).await,
+                        None => apply.await,
+                    };
+                    let _ = responses.send((id, response));
+                });
+                continue;
+            },
+            Frame::Program(_) if !authenticated => {
+                if responses.send((id, Frame::Error("The key of a tenant or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            Frame::Program(source) => {
+                let blackboard = blackboard.clone();
+                let responses = responses.clone();
+                let request = request();
+                let steps = timeouts.program_steps;
+                runtime.spawn(async move {
+                    let _ = responses.send((id, run_program(blackboard, &source, steps, request).await));
+                });
+                continue;
+            },
+            Frame::Subscribe(_) if !authenticated => {
+                if responses.send((id, Frame::Error("The key of a tenant or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            Frame::Subscribe(pattern) => {
+                let response = match parse_term(&pattern) {
+                    Ok(pattern) => {
+                        runtime.spawn(push_changes(blackboard.subscribe(), pattern, id, changes.clone(), closed.subscribe()));
+                        Frame::Response(true)
+                    },
+                    Err(e) => Frame::Error(format!("Invalid pattern: {}", e)),
+                };
+                if responses.send((id, response)).is_err() { break }
+                continue;
+            },
+            // Only the federated peers, presenting the key of the peers, hold the occurrences of this store
+            Frame::Reserve(..) | Frame::Confirm(_) | Frame::Release(_) if !peer => {
+                if responses.send((id, Frame::Error("The key of the peers or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            // Deduplicated as well, a resent reservation must not reserve a second occurrence
+            frame @ (Frame::Reserve(..) | Frame::Confirm(_) | Frame::Release(_)) => {
+                let blackboard = blackboard.clone();
+                let responses = responses.clone();
+                let dedup = dedup.clone();
+                let leases = leases.clone();
+                let session = session.clone();
+                runtime.spawn(async move {
+                    let apply = apply_lease(&blackboard, &leases, frame);
+                    let response = match &session {
+                        Some(session) => dedup.apply(session, id, apply).await,
+                        None => apply.await,
+                    };
+                    let _ = responses.send((id, response));
+                });
+                continue;
+            },
+            // Only the other members, presenting the key of the peers, elect a leader and append to the log
+            Frame::Vote(_) | Frame::Append(_) | Frame::Snapshot(_) if !peer => {
+                if responses.send((id, Frame::Error("The key of the peers or the admin key is required".into()))).is_err() { break }
+                continue;
+            },
+            frame @ (Frame::Vote(_) | Frame::Append(_) | Frame::Snapshot(_)) => {
+                let response = match (&raft, frame) {
+                    (Some(raft), Frame::Vote(request)) => {
+                        let (term, granted) = raft.vote(request);
+                        Frame::Term(term, granted)
+                    },
+                    (Some(raft), Frame::Append(request)) => {
+                        let (term, appended) = raft.append(request);
+                        Frame::Term(term, appended)
+                    },
+                    (Some(raft), Frame::Snapshot(request)) => {
+                        let (term, installed) = raft.install(request);
+                        Frame::Term(term, installed)
+                    },
+                    _ => Frame::Error("The node is not a member of a Raft cluster".into()),
+                };
+                if responses.send((id, response)).is_err() { break }
+                continue;
+            },
+            // Pinged from this node, within the timeout of the member asking for it
+            Frame::Probe(limit, peer) => {
+                let membership = membership.clone();
+                let responses = responses.clone();
+                runtime.spawn(async move {
+                    let response = match membership {
+                        Some(membership) if membership.peers().get(&peer).is_some() => {
+                            let limit = Duration::from_millis(limit as u64).min(membership.config().probe_timeout);
+                            Frame::Response(membership.ping(&peer, limit).await)
+                        },
+                        Some(_) => Frame::Error(format!("Unknown peer {}", peer)),
+                        None => Frame::Error("The node does not probe its peers".into()),
+                    };
+                    let _ = responses.send((id, response));
+                });
+                continue;
+            },
+            Frame::Compress(requested) => {
+                // Applied once the acceptance is written, which is too small to be compressed anyway
+                *compression.lock().unwrap() = requested;
+                if responses.send((id, Frame::Response(true))).is_err() { break }
+                continue;
+            },
+            other => {
+                if responses.send((id, Frame::Error(format!("Unexpected frame: {:?}", other)))).is_err() { break }
+                continue;
+            },
+        };
+        // The tokens are interned once the request is authorized
+        let events = match events {
+            Ok(events) => events,
+            Err(e) => {
+                if responses.send((id, Frame::Error(e.to_string()))).is_err() { break }
+                continue;
+            },
+        };
+        let request = request();
+        let events: Vec<Event> = events.into_iter().map(|event| {
+            let event = match timeouts.request_ttl {
+                Some(ttl) => event.with_ttl(ttl),
+                None => event,
+            };
+            match &request {
+                Some(request) => event.with_trace(request.context()),
+                None => event,
+            }
+        }).collect();
+        for event in &events {
+            log!(Level::Trace, event.span() => "[{}] Received {:?} as request {}", name, event.action, id);
         }
-        let message = String::from_utf8_lossy(&buffer[..n]);
-        println!("[{}] Received message: {}",name, message);
+        let blackboard = blackboard.clone();
+        let responses = responses.clone();
+        let dedup = dedup.clone();
+        let session = session.clone();
+        // The request is applied in its span as well
+        let span = span.clone();
+        runtime.spawn(async move {
+            let apply = apply_events(&blackboard, events, reply);
+            let response = match &session {
+                Some(session) => dedup.apply(session, id, apply).await,
+                None => apply.await,
+            };
+            if let Some(request) = request {
+                request.end();
+            }
+            // The connection may have been closed meanwhile, the response is then lost
+            let _ = responses.send((id, response));
+        }.instrument(span));
     }
-    println!("[{}] Connection dead",name);
+    // The pending requests still answer before the connection is closed, its subscriptions end
+    drop(responses);
+    drop(changes);
+    drop(closed);
+    write_task.await.map_err(|_| TransportError::Io("The writer of the connection stopped".to_string()))??;
+    log!(Level::Debug, "[{}] Connection dead",name);
     Ok(())
-}
\ No newline at end of file
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::create_blackboard;
+    use crate::model::action::Action;
+    use std::time::Duration;
+    use tokio::net::TcpStream;
+    use tokio::time::timeout;
+    use crate::communication::frame::{read_frame, write_frame};
+    use crate::error::TransportError;
+    use crate::communication::transport::MemoryTransport;
+    use crate::model::admin::{AdminCommand, AdminReply};
+    use crate::model::health::Health;
+    use crate::communication::socket_client::{ReconnectPolicy, SocketClient, SocketClientTrait};
+    use crate::communication::tenants::TenantRegistry;
+    use crate::communication::replication::ReplicatedBlackboard;
+    use crate::communication::raft::{AppendRequest, VoteRequest};
+    use crate::model::clock::{Stamp, VectorClock};
+    use crate::runtime::tests::ThreadRuntime;
+
+    async fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn listener_should_answer_heartbeats_and_close_silent_connections() {
+        let port = free_port().await;
+        tokio::spawn(async move {
+            let heartbeat = HeartbeatConfig { interval: Duration::from_millis(20), max_missed: 2 };
+            let listener = SocketListener::new(create_blackboard(), Some(port)).with_heartbeat(heartbeat);
+            listener.listen().await
+        });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(client.ping().await.is_ok());
+
+        let mut silent = TcpStream::connect(format!("127.0.0.1:{}", port)).await.unwrap();
+        let closed = timeout(Duration::from_secs(2), read_frame(&mut silent)).await;
+        assert!(matches!(closed, Ok(Ok(None))), "The silent connection should be closed by the listener");
+    }
+
+    #[tokio::test]
+    async fn listener_should_apply_remote_requests_on_its_blackboard() {
+        let port = free_port().await;
+        tokio::spawn(async move {
+            let listener = SocketListener::new(create_blackboard(), Some(port));
+            listener.listen().await
+        });
+
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(!client.ask("token".into()).await.unwrap());
+        assert!(client.tell("token".into()).await.unwrap());
+        assert!(client.ask("token".into()).await.unwrap());
+        assert!(client.get("token".into()).await.unwrap());
+        assert!(client.nask("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn listener_should_record_the_spans_of_a_traced_request_as_children_of_its_sender() {
+        let _exporter = crate::trace::EXPORTER_LOCK.lock().await;
+        let exporter = crate::trace::record_in_memory();
+        let port = free_port().await;
+        tokio::spawn(async move { SocketListener::new(create_blackboard(), Some(port)).listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        let root = crate::trace::ActiveSpan::start("root", None).context();
+        assert!(!client.forward_traced(Action::Ask("token".into()), Some(&root)).await.unwrap());
+        // The spans of the other tests of the process are left out
+        let spans: Vec<_> = exporter.get_finished_spans().unwrap().into_iter().filter(|span| span.span_context.trace_id() == root.trace_id()).collect();
+        crate::trace::set_tracer_provider(None);
+        let span = |name: &str| spans.iter().find(|span| span.name == name).unwrap_or_else(|| panic!("No {} span in {:?}", name, spans));
+        assert_eq!(span("remote forward").parent_span_id, root.span_id());
+        assert_eq!(span("request").parent_span_id, span("remote forward").span_context.span_id());
+        for name in ["queue wait", "handle"] {
+            assert_eq!(span(name).parent_span_id, span("request").span_context.span_id());
+        }
+        assert!(span("handle").attributes.contains(&opentelemetry::KeyValue::new("bacht.result", "false")));
+    }
+
+    #[tokio::test]
+    async fn listener_should_answer_pipelined_requests_with_their_correlation_ids() {
+        let port = free_port().await;
+        tokio::spawn(async move { SocketListener::new(create_blackboard(), Some(port)).listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(client.tell("a".into()).await.unwrap());
+
+        let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port)).await.unwrap();
+        write_frame(&mut stream, 7, &Frame::Request(Action::Ask("a".into()))).await.unwrap();
+        write_frame(&mut stream, 8, &Frame::Request(Action::Ask("b".into()))).await.unwrap();
+        write_frame(&mut stream, 9, &Frame::Ping).await.unwrap();
+        let mut responses = std::collections::HashMap::new();
+        for _ in 0..3 {
+            let (id, response) = read_frame(&mut stream).await.unwrap().unwrap();
+            responses.insert(id, response);
+        }
+        assert_eq!(responses[&7], Frame::Response(true));
+        assert_eq!(responses[&8], Frame::Response(false));
+        assert_eq!(responses[&9], Frame::Pong);
+    }
+
+    #[tokio::test]
+    async fn listener_should_not_apply_a_resent_request_twice() {
+        let port = free_port().await;
+        let blackboard = create_blackboard();
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        let served = blackboard.clone();
+        tokio::spawn(async move { SocketListener::new(served, Some(port)).listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(client.ask("token".into()).await.unwrap());
+
+        // The same get of the same session, sent on two connections as after a reconnection
+        for _ in 0..2 {
+            let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port)).await.unwrap();
+            write_frame(&mut stream, 0, &Frame::Hello(42)).await.unwrap();
+            write_frame(&mut stream, 5, &Frame::Request(Action::Get("token".into()))).await.unwrap();
+            assert_eq!(read_frame(&mut stream).await.unwrap(), Some((0, Frame::Response(true))));
+            assert_eq!(read_frame(&mut stream).await.unwrap(), Some((5, Frame::Response(true))));
+        }
+        assert!(blackboard.ask("token".into()).await.unwrap(), "The resent get should not consume a second occurrence");
+    }
+
+    #[tokio::test]
+    async fn listener_should_refuse_the_session_of_another_identity() {
+        let port = free_port().await;
+        let blackboard = create_blackboard();
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        let served = blackboard.clone();
+        tokio::spawn(async move { SocketListener::new(served, Some(port)).with_peer_key("peers").listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(client.ask("token".into()).await.unwrap());
+
+        let mut peer = TcpStream::connect(format!("127.0.0.1:{}", port)).await.unwrap();
+        write_frame(&mut peer, 0, &Frame::Authenticate("peers".into())).await.unwrap();
+        write_frame(&mut peer, 1, &Frame::Hello(42)).await.unwrap();
+        write_frame(&mut peer, 5, &Frame::Request(Action::Get("token".into()))).await.unwrap();
+        for expected in [(0, Frame::Response(true)), (1, Frame::Response(true)), (5, Frame::Response(true))] {
+            assert_eq!(read_frame(&mut peer).await.unwrap(), Some(expected));
+        }
+
+        // The same session and sequence without the key must not get the response of the peer
+        let mut other = TcpStream::connect(format!("127.0.0.1:{}", port)).await.unwrap();
+        write_frame(&mut other, 1, &Frame::Hello(42)).await.unwrap();
+        assert!(matches!(read_frame(&mut other).await.unwrap(), Some((1, Frame::Error(_)))));
+        write_frame(&mut other, 5, &Frame::Request(Action::Get("token".into()))).await.unwrap();
+        assert_eq!(read_frame(&mut other).await.unwrap(), Some((5, Frame::Response(false))), "The get should be applied on its own");
+    }
+
+    #[tokio::test]
+    async fn listener_should_apply_a_batch_in_order() {
+        let port = free_port().await;
+        tokio::spawn(async move { SocketListener::new(create_blackboard(), Some(port)).listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+
+        let results = client.batch(vec![
+            Action::Tell("token".into()),
+            Action::Get("token".into()),
+            Action::Ask("token".into()),
+            Action::Tell("other".into()),
+            Action::Nask("token".into()),
+        ]).await.unwrap();
+        let results: Vec<bool> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(results, vec![true, true, false, true, true]);
+        assert!(client.batch(vec![]).await.unwrap().is_empty());
+        assert!(client.ask("other".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn listener_should_serve_a_compressed_connection() {
+        let port = free_port().await;
+        tokio::spawn(async move { SocketListener::new(create_blackboard(), Some(port)).listen().await });
+        let client = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap()
+            .with_compression(Compression::Lz4);
+
+        let token: TokenId = "sensor/temperature;".repeat(200).into();
+        let results = client.batch(vec![Action::Tell(token); 20]).await.unwrap();
+        assert!(results.into_iter().all(|result| result.unwrap()));
+        assert!(client.get(token).await.unwrap());
+        assert!(!client.nask(token).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn listener_should_serve_ipv6_and_dual_stack_clients() {
+        let port = free_port().await;
+        tokio::spawn(async move {
+            SocketListener::new(create_blackboard(), Some(port)).with_address("::".parse().unwrap()).with_dual_stack(true).listen().await
+        });
+        let v6 = SocketClient::connect(&format!("[::1]:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(v6.tell("token".into()).await.unwrap());
+        let v4 = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap();
+        assert!(v4.get("token".into()).await.unwrap(), "The IPv4 client should reach the same blackboard");
+
+        let port = free_port().await;
+        tokio::spawn(async move { SocketListener::new(create_blackboard(), Some(port)).with_address("::1".parse().unwrap()).listen().await });
+        assert!(SocketClient::connect(&format!("[::1]:{}", port), ReconnectPolicy::default()).await.is_ok());
+        let policy = ReconnectPolicy { max_attempts: Some(1), ..ReconnectPolicy::default() };
+        assert!(SocketClient::connect(&format!("127.0.0.1:{}", port), policy).await.is_err(), "An IPv6 only listener should refuse IPv4 clients");
+    }
+
+    #[tokio::test]
+    async fn listener_should_serve_clients_of_an_in_memory_transport() {
+        let transport = Arc::new(MemoryTransport::new());
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board");
+        tokio::spawn(async move { listener.listen().await });
+        let client = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap();
+
+        assert!(client.tell("token".into()).await.unwrap());
+        assert_eq!(client.batch(vec![Action::Ask("token".into()), Action::Get("token".into())]).await.unwrap().len(), 2);
+        assert!(!client.ask("token".into()).await.unwrap());
+        assert!(client.ping().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn listener_should_spawn_its_connections_and_requests_on_its_runtime() {
+        let runtime = ThreadRuntime::default();
+        let spawned = runtime.spawned.clone();
+        let transport = Arc::new(MemoryTransport::new());
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board")
+            .with_runtime(RuntimeHandle::new(runtime));
+        tokio::spawn(async move { listener.listen().await });
+        let client = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap();
+
+        assert!(client.tell("token".into()).await.unwrap());
+        assert!(client.ask("token".into()).await.unwrap());
+        // The connection, its writer and its two requests
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn listener_should_run_the_programs_of_its_clients() {
+        let transport = Arc::new(MemoryTransport::new());
+        let blackboard = create_blackboard();
+        let listener = SocketListener::new(blackboard.clone(), None).with_transport(transport.clone(), "board").with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+        let client = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_admin_key("secret");
+
+        assert!(client.run("tell(a);tell(b);get(a)").await.unwrap());
+        assert!(blackboard.ask("b".into()).await.unwrap());
+        assert!(blackboard.nask("a".into()).await.unwrap());
+        assert!(!client.run("ask(a);tell(c)").await.unwrap(), "A stuck agent should fail");
+        assert!(blackboard.nask("c".into()).await.unwrap());
+        assert!(matches!(client.run("tell(a)??").await, Err(TransportError::RemoteError(_))), "An invalid program should be refused");
+    }
+
+    #[tokio::test]
+    async fn listener_should_run_the_programs_of_authenticated_clients_within_their_steps() {
+        let transport = Arc::new(MemoryTransport::new());
+        let tenants = TenantRegistry::new().with_tenant("a", "key-a");
+        let blackboard = create_blackboard();
+        let listener = SocketListener::new(blackboard.clone(), None).with_transport(transport.clone(), "board").with_tenants(tenants)
+            .with_program_steps(10);
+        tokio::spawn(async move { listener.listen().await });
+        let anonymous = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        assert!(matches!(anonymous.run("tell(a)").await, Err(TransportError::RemoteError(_))), "An anonymous client should not run programs");
+        assert!(blackboard.nask("a".into()).await.unwrap());
+
+        let tenant = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_tenant_key("key-a");
+        assert!(tenant.run("tell(a);tell(b)").await.unwrap());
+        let endless = timeout(Duration::from_secs(5), tenant.run("*tell(a)")).await.expect("The program should be stopped");
+        assert!(matches!(endless, Err(TransportError::RemoteError(e)) if e.contains("10 transitions")));
+        assert!(tenant.run("*(tell(a);get(a))").await.is_err(), "A program going round should be stopped as well");
+    }
+
+    #[tokio::test]
+    async fn listener_should_hold_the_occurrences_reserved_by_its_peers() {
+        let transport = Arc::new(MemoryTransport::new());
+        let blackboard = create_blackboard();
+        let listener = SocketListener::new(blackboard.clone(), None).with_transport(transport.clone(), "board").with_peer_key("cluster");
+        tokio::spawn(async move { listener.listen().await });
+        let anonymous = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        let client = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_peer_key("cluster");
+        assert!(blackboard.tell("token".into()).await.unwrap());
+        assert!(matches!(anonymous.reserve("token".into(), Duration::from_secs(2)).await, Err(TransportError::RemoteError(_))), "An anonymous client should not reserve");
+        assert!(matches!(anonymous.release(0).await, Err(TransportError::RemoteError(_))));
+
+        let lease = client.reserve("token".into(), Duration::from_secs(2)).await.unwrap().unwrap();
+        assert_eq!(client.reserve("token".into(), Duration::from_secs(2)).await.unwrap(), None, "The occurrence should be reserved already");
+        assert!(client.release(lease).await.unwrap());
+        let lease = client.reserve("token".into(), Duration::from_secs(2)).await.unwrap().unwrap();
+        assert!(client.confirm(lease).await.unwrap());
+        assert!(!client.release(lease).await.unwrap(), "A confirmed occurrence can't be released");
+        assert!(blackboard.nask("token".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn listener_should_report_its_health_to_any_client() {
+        let transport = Arc::new(MemoryTransport::new());
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board");
+        tokio::spawn(async move { listener.listen().await });
+        let client = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap();
+        assert!(client.tell("a".into()).await.unwrap());
+        assert!(client.tell("b".into()).await.unwrap());
+        assert!(client.get("b".into()).await.unwrap());
+        assert_eq!(client.health().await.unwrap(), Health { worker_alive: true, queue_depth: 0, store_size: 1 });
+    }
+
+    #[tokio::test]
+    async fn listener_should_apply_admin_commands_of_authenticated_clients_only() {
+        let transport = Arc::new(MemoryTransport::new());
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board").with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+        let agent = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        assert!(agent.tell("a".into()).await.unwrap());
+        assert!(agent.tell("a".into()).await.unwrap());
+        assert!(agent.tell("b".into()).await.unwrap());
+        assert!(matches!(agent.admin(AdminCommand::Clear).await, Err(TransportError::RemoteError(_))), "The agent is not an administrator");
+
+        let policy = ReconnectPolicy { max_attempts: Some(1), ..ReconnectPolicy::default() };
+        let intruder = SocketClient::connect_with(transport.clone(), "board", policy).await.unwrap().with_admin_key("guess");
+        assert!(matches!(intruder.admin(AdminCommand::Clear).await, Err(TransportError::RemoteError(_))), "A wrong key should be refused");
+
+        let operator = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_admin_key("secret");
+        assert_eq!(operator.admin(AdminCommand::Snapshot).await.unwrap(), AdminReply::Snapshot(vec![("a".into(), 2), ("b".into(), 1)]));
+        assert_eq!(operator.admin(AdminCommand::Stats).await.unwrap(), AdminReply::Stats(vec![("occurrences".into(), 3), ("queued".into(), 0), ("tokens".into(), 2)]));
+        assert_eq!(operator.admin(AdminCommand::Peers).await.unwrap(), AdminReply::Peers(vec![]));
+        assert_eq!(operator.admin(AdminCommand::Clear).await.unwrap(), AdminReply::Done);
+        assert!(agent.nask("a".into()).await.unwrap(), "The store should be cleared");
+    }
+
+    #[tokio::test]
+    async fn listener_should_send_a_snapshot_too_big_for_a_frame_in_parts() {
+        let blackboard = create_blackboard();
+        let tokens: Vec<(Box<str>, u32)> = (0..300).map(|i| (format!("{:04}{}", i, "x".repeat(4096)).into(), 1)).collect();
+        for (token, _) in &tokens {
+            assert!(blackboard.tell(token.as_ref().into()).await.unwrap());
+        }
+        // Over TCP, the frames are bounded by MAX_FRAME_LENGTH
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let listener = SocketListener::new(blackboard, Some(port)).with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+        let operator = SocketClient::connect(&format!("127.0.0.1:{}", port), ReconnectPolicy::default()).await.unwrap().with_admin_key("secret");
+        assert_eq!(operator.admin(AdminCommand::Snapshot).await.unwrap(), AdminReply::Snapshot(tokens), "The parts should be joined");
+        assert!(operator.ping().await.is_ok(), "The connection should go on");
+    }
+
+    #[tokio::test]
+    async fn listener_should_install_the_snapshots_of_authenticated_primaries_only() {
+        let transport = Arc::new(MemoryTransport::new());
+        let blackboard = create_blackboard();
+        let listener = SocketListener::new(blackboard.clone(), None).with_transport(transport.clone(), "board").with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+        let intruder = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        let refused = intruder.install(vec![("a".into(), u32::MAX)]).await;
+        assert!(matches!(refused, Err(TransportError::RemoteError(_))), "A client without the admin key is not a primary");
+        assert!(blackboard.nask("a".into()).await.unwrap());
+
+        let primary = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_admin_key("secret");
+        assert!(primary.install(vec![("a".into(), 2), ("b".into(), 1)]).await.unwrap());
+        assert_eq!(blackboard.admin(AdminCommand::Snapshot).await.unwrap(), AdminReply::Snapshot(vec![("a".into(), 2), ("b".into(), 1)]));
+    }
+
+    #[tokio::test]
+    async fn listener_should_apply_the_mutations_of_authenticated_primaries_only() {
+        let transport = Arc::new(MemoryTransport::new());
+        let replica = ReplicatedBlackboard::replica(create_blackboard());
+        let listener = SocketListener::new(replica.clone(), None).with_transport(transport.clone(), "board").with_peer_key("cluster");
+        tokio::spawn(async move { listener.listen().await });
+        let intruder = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        assert!(matches!(intruder.replicate(Action::Tell("a".into())).await, Err(TransportError::RemoteError(_))), "A client without the key of the peers is not a primary");
+        assert!(intruder.tell("a".into()).await.is_err(), "The replica should stay read-only");
+        assert!(intruder.nask("a".into()).await.unwrap());
+
+        let primary = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_peer_key("cluster");
+        assert!(primary.replicate(Action::Tell("a".into())).await.unwrap());
+        assert!(primary.admin(AdminCommand::Clear).await.is_err(), "The key of the peers does not grant the admin capability");
+        assert!(intruder.ask("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn listener_should_apply_the_causal_mutations_of_authenticated_peers_only() {
+        let transport = Arc::new(MemoryTransport::new());
+        let blackboard = create_blackboard();
+        let listener = SocketListener::new(blackboard.clone(), None).with_transport(transport.clone(), "board").with_peer_key("cluster");
+        tokio::spawn(async move { listener.listen().await });
+        let stamp = |count| {
+            let mut clock = VectorClock::new();
+            clock.set("b", count);
+            Stamp { node: "b".into(), clock }
+        };
+        let intruder = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        let forged = intruder.causal(stamp(1), Action::Tell("forged".into())).await;
+        assert!(matches!(forged, Err(TransportError::RemoteError(_))), "A client without the key of the peers is not a causal peer");
+        assert!(blackboard.nask("forged".into()).await.unwrap());
+
+        let peer = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_peer_key("cluster");
+        assert!(peer.causal(stamp(1), Action::Tell("a".into())).await.unwrap());
+        assert!(blackboard.ask("a".into()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn listener_should_answer_the_votes_and_the_appends_of_authenticated_members_only() {
+        let transport = Arc::new(MemoryTransport::new());
+        let node = RaftNode::new("a", &[]);
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board").with_raft(node.clone()).with_peer_key("cluster");
+        tokio::spawn(async move { listener.listen().await });
+        let vote = VoteRequest { term: 1, candidate: "b".into(), last_index: 0, last_term: 0 };
+        let append = AppendRequest { term: 1, leader: "b".into(), prev_index: 0, prev_term: 0, entries: vec![], commit: 0 };
+        let intruder = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        assert!(matches!(intruder.vote(vote.clone()).await, Err(TransportError::RemoteError(_))), "A client without the key of the peers is not a member");
+        assert!(matches!(intruder.append(append.clone()).await, Err(TransportError::RemoteError(_))));
+        assert_eq!(node.term(), 0);
+
+        let member = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_peer_key("cluster");
+        assert_eq!(member.vote(vote).await.unwrap(), (1, true));
+        assert_eq!(member.append(append).await.unwrap(), (1, true));
+        assert_eq!(node.leader().as_deref(), Some("b"));
+    }
+
+    #[tokio::test]
+    async fn listener_should_push_the_changes_matching_the_subscriptions_of_its_clients() {
+        let transport = Arc::new(MemoryTransport::new());
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board").with_admin_key("secret");
+        tokio::spawn(async move { listener.listen().await });
+        let anonymous = SocketClient::connect_with(transport.clone(), "board", ReconnectPolicy::default()).await.unwrap();
+        assert!(matches!(anonymous.subscribe("X").await, Err(TransportError::RemoteError(_))), "An anonymous client should not subscribe");
+
+        let subscriber = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap().with_admin_key("secret");
+        assert!(matches!(subscriber.subscribe("msg(X").await, Err(TransportError::RemoteError(_))), "An invalid pattern should be refused");
+        let mut messages = subscriber.subscribe("msg(X)").await.unwrap();
+        let mut everything = subscriber.subscribe("X").await.unwrap();
+        for action in [Action::Tell("other".into()), Action::Tell("msg(a)".into()), Action::Tell("msg(a)".into()), Action::Get("msg(a)".into())] {
+            assert!(anonymous.send(action).await.unwrap());
+        }
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            received.push(timeout(Duration::from_secs(1), messages.recv()).await.expect("The change should be pushed").unwrap());
+        }
+        assert_eq!(received, [("msg(a)".into(), 1), ("msg(a)".into(), 2), ("msg(a)".into(), 1)]);
+        assert_eq!(timeout(Duration::from_secs(1), everything.recv()).await.unwrap(), Some(("other".into(), 1)));
+    }
+
+    #[tokio::test]
+    async fn listener_should_isolate_the_connections_of_each_tenant() {
+        let transport = Arc::new(MemoryTransport::new());
+        let tenants = TenantRegistry::new().with_tenant("a", "key-a").with_tenant("b", "key-b");
+        let listener = SocketListener::new(create_blackboard(), None).with_transport(transport.clone(), "board").with_tenants(tenants.clone());
+        tokio::spawn(async move { listener.listen().await });
+        let connect = |key: Option<&str>| {
+            let transport = transport.clone();
+            let key = key.map(String::from);
+            async move {
+                let client = SocketClient::connect_with(transport, "board", ReconnectPolicy::default()).await.unwrap();
+                match key {
+                    Some(key) => client.with_tenant_key(&key),
+                    None => client,
+                }
+            }
+        };
+        let (a, other_a, b, anonymous) = (connect(Some("key-a")).await, connect(Some("key-a")).await, connect(Some("key-b")).await, connect(None).await);
+        assert!(a.tell("token".into()).await.unwrap());
+        assert!(other_a.ask("token".into()).await.unwrap(), "The connections of a tenant should share its blackboard");
+        assert!(!b.ask("token".into()).await.unwrap(), "The tenants should not see the tokens of the others");
+        assert!(!anonymous.ask("token".into()).await.unwrap());
+        assert!(tenants.blackboard("a").unwrap().get("token".into()).await.unwrap());
+
+        let policy = ReconnectPolicy { max_attempts: Some(1), ..ReconnectPolicy::default() };
+        let intruder = SocketClient::connect_with(transport.clone(), "board", policy).await.unwrap().with_tenant_key("key-c");
+        assert_eq!(intruder.tell("token".into()).await, Err(TransportError::RemoteError("The key was refused".into())));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn listener_should_serve_a_unix_socket() {
+        let path = std::env::temp_dir().join(format!("bacht-{}.sock", rand::random::<u64>()));
+        // A stale socket file left by a previous listener
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let served = path.clone();
+        tokio::spawn(async move { SocketListener::new(create_blackboard(), None).with_unix_socket(served).listen().await });
+
+        let addr = format!("unix:{}", path.display());
+        let client = SocketClient::connect(&addr, ReconnectPolicy::default()).await.unwrap();
+        assert!(client.tell("token".into()).await.unwrap());
+        assert!(client.get("token".into()).await.unwrap());
+        assert!(!client.ask("token".into()).await.unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+}
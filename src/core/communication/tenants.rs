@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use crate::blackboard::BlackboardTrait;
+
+// A tenant, its key and its blackboard
+struct Tenant<B> {
+    key: Box<str>,
+    blackboard: B,
+}
+
+/// @summary - The TenantRegistry holds the isolated coordination spaces of a listener, e.g. one per group of students:
+/// a connection presenting the key of a tenant works on its blackboard only, without seeing the tokens of the others.
+///
+/// It can be cloned in order to share the same tenants between the listeners of a node.
+pub struct TenantRegistry<B: BlackboardTrait> {
+    tenants: Arc<RwLock<HashMap<Box<str>, Tenant<B>>>>,
+}
+
+impl<B: BlackboardTrait> TenantRegistry<B> {
+
+    pub fn new() -> Self {
+        Self { tenants: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    /// @summary - Declare a tenant on a blackboard of its own
+    pub fn with_tenant(self, name: &str, key: &str) -> Self {
+        self.register(name, key, B::new());
+        self
+    }
+
+    /// @summary - Declare a tenant, or replace its key and its blackboard
+    ///
+    /// @param blackboard - The blackboard of the tenant, e.g. one on a store chosen from the configuration
+    pub fn register(&self, name: &str, key: &str, blackboard: B) {
+        self.tenants.write().unwrap().insert(name.into(), Tenant { key: key.into(), blackboard });
+    }
+
+    /// @summary - Declare the tenants of a configuration, one `name key` per line, the blank lines and the comments
+    /// starting with `#` being ignored
+    ///
+    /// @returns - The number of tenants declared, or the line that is not a tenant
+    pub fn load_config(&self, config: &str) -> Result<usize, String> {
+        let mut declared = 0;
+        for (number, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [name, key] => self.register(name, key, B::new()),
+                _ => return Err(format!("Invalid tenant at line {}, expected `name key`", number + 1)),
+            }
+            declared += 1;
+        }
+        Ok(declared)
+    }
+
+    /// @summary - Declare the tenants listed in a file
+    ///
+    /// @see - load_config for the format of the file
+    pub fn load_file(&self, path: &std::path::Path) -> Result<usize, String> {
+        let config = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        self.load_config(&config).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// @returns - The name and the blackboard of the tenant whose key is presented, None if no tenant has this key
+    ///
+    /// @note - The keys are compared in a time independent of their common prefix
+    pub fn authenticate(&self, key: &str) -> Option<(Box<str>, B)> {
+        self.tenants.read().unwrap().iter()
+            .find(|(_, tenant)| same_key(&tenant.key, key))
+            .map(|(name, tenant)| (name.clone(), tenant.blackboard.clone()))
+    }
+
+    /// @returns - The blackboard of a tenant, e.g. for the host to inspect it
+    pub fn blackboard(&self, name: &str) -> Option<B> {
+        self.tenants.read().unwrap().get(name).map(|tenant| tenant.blackboard.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tenants.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<B: BlackboardTrait> Default for TenantRegistry<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: BlackboardTrait> Clone for TenantRegistry<B> {
+    fn clone(&self) -> Self {
+        Self { tenants: self.tenants.clone() }
+    }
+}
+
+/// @summary - Compare a presented key to an expected one, in a time independent of their common prefix
+pub(crate) fn same_key(expected: &str, key: &str) -> bool {
+    expected.len() == key.len() && expected.bytes().zip(key.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::{Blackboard, create_blackboard};
+    use crate::blackboard::store::Store;
+    use crate::blackboard::task_queue::TaskQueue;
+    use crate::blackboard::worker::Worker;
+
+    #[tokio::test]
+    async fn tenant_registry_should_give_each_key_its_own_blackboard() {
+        let tenants: TenantRegistry<Blackboard<TaskQueue, Worker, Store>> = TenantRegistry::new().with_tenant("a", "key-a");
+        assert_eq!(tenants.load_config("# groups\nb key-b\n\n"), Ok(1));
+        assert_eq!(tenants.load_config("c"), Err("Invalid tenant at line 1, expected `name key`".to_string()));
+        tenants.register("c", "key-c", create_blackboard());
+        assert_eq!(tenants.len(), 3);
+
+        let (name, a) = tenants.authenticate("key-a").unwrap();
+        assert_eq!(&*name, "a");
+        assert!(a.tell("token".into()).await.unwrap());
+        assert!(tenants.blackboard("a").unwrap().ask("token".into()).await.unwrap());
+        assert!(!tenants.authenticate("key-b").unwrap().1.ask("token".into()).await.unwrap());
+        assert!(tenants.authenticate("key-").is_none());
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use crate::communication::compression::Compression;
+use bytes::BytesMut;
+use crate::communication::frame::{read_frame_into, write_frame_into, Frame, FrameError};
+
+/// The prefix of the addresses of Unix domain sockets, e.g. `unix:/run/bacht.sock`
+pub const UNIX_PREFIX: &str = "unix:";
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// @summary - The receiving half of a connection
+pub trait FrameSource: Send {
+
+    /// @returns - The next frame and its correlation id, or None once the remote closed the connection
+    fn recv(&mut self) -> BoxFuture<'_, Result<Option<(u64, Frame)>, FrameError>>;
+}
+
+/// @summary - The sending half of a connection
+pub trait FrameSink: Send {
+
+    fn send<'a>(&'a mut self, id: u64, frame: &'a Frame) -> BoxFuture<'a, Result<(), FrameError>>;
+
+    /// @summary - Compress the next big frames, if the transport serializes them
+    fn set_compression(&mut self, _compression: Compression) {}
+}
+
+/// An established connection, split in its two halves
+pub type Link = (Box<dyn FrameSource>, Box<dyn FrameSink>);
+
+/// @summary - Accepts the connections made to a bound address
+pub trait Acceptor: Send {
+
+    /// @returns - The accepted connection, and a name of the remote for the logs
+    fn accept(&mut self) -> BoxFuture<'_, io::Result<(Link, String)>>;
+}
+
+/// @summary - The Transport carries the frames between the blackboards.
+///
+/// The protocol logic (the listener, the client) only deals with frames, the transport decides how they travel:
+/// serialized over sockets, or passed in memory.
+pub trait Transport: Send + Sync {
+
+    /// @summary - Connect to the address the remote is bound to
+    fn connect<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Link>>;
+
+    /// @summary - Bind an address, to accept the connections made to it
+    fn bind<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Box<dyn Acceptor>>>;
+}
+
+/// Serializes the frames on a byte stream, each half reusing its buffer from frame to frame, the frames read owning
+/// a copy of their tokens
+struct StreamSource<R> {
+    reader: R,
+    buffer: BytesMut,
+}
+
+struct StreamSink<W> {
+    writer: W,
+    compression: Compression,
+    buffer: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin + Send> FrameSource for StreamSource<R> {
+    fn recv(&mut self) -> BoxFuture<'_, Result<Option<(u64, Frame)>, FrameError>> {
+        Box::pin(read_frame_into(&mut self.reader, &mut self.buffer))
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send> FrameSink for StreamSink<W> {
+    fn send<'a>(&'a mut self, id: u64, frame: &'a Frame) -> BoxFuture<'a, Result<(), FrameError>> {
+        Box::pin(write_frame_into(&mut self.writer, id, frame, self.compression, &mut self.buffer))
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+}
+
+/// @summary - Frame a byte stream, split in its two halves
+pub fn stream_link<R, W>(reader: R, writer: W) -> Link
+where R: AsyncRead + Unpin + Send + 'static, W: AsyncWrite + Unpin + Send + 'static {
+    let source = StreamSource { reader, buffer: BytesMut::new() };
+    (Box::new(source), Box::new(StreamSink { writer, compression: Compression::None, buffer: Vec::new() }))
+}
+
+impl Acceptor for TcpListener {
+    fn accept(&mut self) -> BoxFuture<'_, io::Result<(Link, String)>> {
+        Box::pin(async move {
+            let (stream, remote) = TcpListener::accept(self).await?;
+            let (reader, writer) = stream.into_split();
+            Ok((stream_link(reader, writer), remote.to_string()))
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Acceptor for tokio::net::UnixListener {
+    fn accept(&mut self) -> BoxFuture<'_, io::Result<(Link, String)>> {
+        Box::pin(async move {
+            let (stream, _) = tokio::net::UnixListener::accept(self).await?;
+            let (reader, writer) = stream.into_split();
+            Ok((stream_link(reader, writer), "unix".to_string()))
+        })
+    }
+}
+
+/// @summary - The frames travel over TCP, or over a Unix domain socket for the addresses starting with `unix:`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SocketTransport;
+
+impl Transport for SocketTransport {
+
+    fn connect<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Link>> {
+        Box::pin(async move {
+            #[cfg(unix)]
+            if let Some(path) = addr.strip_prefix(UNIX_PREFIX) {
+                let (reader, writer) = tokio::net::UnixStream::connect(path).await?.into_split();
+                return Ok(stream_link(reader, writer));
+            }
+            let (reader, writer) = TcpStream::connect(addr).await?.into_split();
+            Ok(stream_link(reader, writer))
+        })
+    }
+
+    fn bind<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Box<dyn Acceptor>>> {
+        Box::pin(async move {
+            #[cfg(unix)]
+            if let Some(path) = addr.strip_prefix(UNIX_PREFIX) {
+                return Ok(Box::new(tokio::net::UnixListener::bind(path)?) as Box<dyn Acceptor>);
+            }
+            Ok(Box::new(TcpListener::bind(addr).await?) as Box<dyn Acceptor>)
+        })
+    }
+}
+
+struct MemorySource(UnboundedReceiver<(u64, Frame)>);
+
+struct MemorySink(UnboundedSender<(u64, Frame)>);
+
+impl FrameSource for MemorySource {
+    fn recv(&mut self) -> BoxFuture<'_, Result<Option<(u64, Frame)>, FrameError>> {
+        Box::pin(async move { Ok(self.0.recv().await) })
+    }
+}
+
+impl FrameSink for MemorySink {
+    fn send<'a>(&'a mut self, id: u64, frame: &'a Frame) -> BoxFuture<'a, Result<(), FrameError>> {
+        let sent = self.0.send((id, frame.clone())).map_err(|_| FrameError::Io(io::ErrorKind::BrokenPipe.into()));
+        Box::pin(async move { sent })
+    }
+}
+
+type Endpoints = Arc<Mutex<HashMap<String, UnboundedSender<Link>>>>;
+
+struct MemoryAcceptor {
+    addr: String,
+    endpoints: Endpoints,
+    incoming: UnboundedReceiver<Link>,
+    accepted: usize,
+}
+
+impl Acceptor for MemoryAcceptor {
+    fn accept(&mut self) -> BoxFuture<'_, io::Result<(Link, String)>> {
+        Box::pin(async move {
+            // The endpoint stays registered until the acceptor is dropped, the channel never closes before
+            let link = self.incoming.recv().await.ok_or(io::Error::from(io::ErrorKind::NotConnected))?;
+            self.accepted += 1;
+            Ok((link, format!("memory-{}", self.accepted)))
+        })
+    }
+}
+
+impl Drop for MemoryAcceptor {
+    fn drop(&mut self) {
+        self.endpoints.lock().unwrap().remove(&self.addr);
+    }
+}
+
+/// @summary - The frames are passed through channels, between the blackboards of the same process.
+///
+/// Any name can be bound, the clones of a MemoryTransport share the bound names. Meant to test the protocol logic
+/// without opening sockets.
+#[derive(Clone, Default)]
+pub struct MemoryTransport {
+    endpoints: Endpoints,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Transport for MemoryTransport {
+
+    fn connect<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Link>> {
+        let connected = match self.endpoints.lock().unwrap().get(addr) {
+            Some(endpoint) => {
+                let (to_remote, from_local) = unbounded_channel();
+                let (to_local, from_remote) = unbounded_channel();
+                let remote: Link = (Box::new(MemorySource(from_local)), Box::new(MemorySink(to_local)));
+                endpoint.send(remote)
+                    .map(|_| (Box::new(MemorySource(from_remote)) as Box<dyn FrameSource>, Box::new(MemorySink(to_remote)) as Box<dyn FrameSink>))
+                    .map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))
+            },
+            None => Err(io::Error::from(io::ErrorKind::ConnectionRefused)),
+        };
+        Box::pin(async move { connected })
+    }
+
+    fn bind<'a>(&'a self, addr: &'a str) -> BoxFuture<'a, io::Result<Box<dyn Acceptor>>> {
+        let bound = {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            match endpoints.contains_key(addr) {
+                true => Err(io::Error::from(io::ErrorKind::AddrInUse)),
+                false => {
+                    let (endpoint, incoming) = unbounded_channel();
+                    endpoints.insert(addr.to_string(), endpoint);
+                    Ok(Box::new(MemoryAcceptor { addr: addr.to_string(), endpoints: self.endpoints.clone(), incoming, accepted: 0 }) as Box<dyn Acceptor>)
+                }
+            }
+        };
+        Box::pin(async move { bound })
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::action::Action;
+
+    async fn exchange_over(transport: &dyn Transport, addr: &str) {
+        let mut acceptor = transport.bind(addr).await.unwrap();
+        let ((mut client_source, mut client_sink), ((mut server_source, mut server_sink), _)) = tokio::join!(
+            async { transport.connect(addr).await.unwrap() },
+            async { acceptor.accept().await.unwrap() },
+        );
+        client_sink.send(7, &Frame::Request(Action::Tell("token".into()))).await.unwrap();
+        assert_eq!(server_source.recv().await.unwrap(), Some((7, Frame::Request(Action::Tell("token".into())))));
+        server_sink.send(7, &Frame::Response(true)).await.unwrap();
+        assert_eq!(client_source.recv().await.unwrap(), Some((7, Frame::Response(true))));
+
+        drop(client_sink);
+        assert_eq!(server_source.recv().await.unwrap(), None, "The remote should see the connection closed");
+    }
+
+    #[tokio::test]
+    async fn memory_transport_should_carry_frames_between_its_ends() {
+        let transport = MemoryTransport::new();
+        exchange_over(&transport, "board").await;
+        let _bound = transport.bind("other").await.unwrap();
+        assert_eq!(transport.bind("other").await.err().unwrap().kind(), io::ErrorKind::AddrInUse);
+        assert_eq!(transport.connect("board").await.err().unwrap().kind(), io::ErrorKind::ConnectionRefused, "The dropped acceptor should unbind its name");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn socket_transport_should_carry_frames_over_a_unix_socket() {
+        let path = std::env::temp_dir().join(format!("bacht-{}.sock", rand::random::<u64>()));
+        exchange_over(&SocketTransport, &format!("{}{}", UNIX_PREFIX, path.display())).await;
+        std::fs::remove_file(path).unwrap();
+    }
+}
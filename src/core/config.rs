@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::error::ConfigError;
+
+/// The configuration file read when no other is given, in the working directory
+pub const DEFAULT_CONFIG_FILE: &str = "bacht.toml";
+
+// Each setting of a node with the environment variable overriding it
+const ENV_OVERRIDES: [(&str, &str); 31] = [
+    ("listen.port", "BACHT_PORT"),
+    ("listen.bind", "BACHT_BIND"),
+    ("listen.socket", "BACHT_SOCKET"),
+    ("listen.health_port", "BACHT_HEALTH_PORT"),
+    ("listen.grpc_port", "BACHT_GRPC_PORT"),
+    ("listen.request_ttl", "BACHT_REQUEST_TTL"),
+    ("listen.program_steps", "BACHT_PROGRAM_STEPS"),
+    ("node.name", "BACHT_NAME"),
+    ("node.admin_key", "BACHT_ADMIN_KEY"),
+    ("node.peer_key", "BACHT_PEER_KEY"),
+    ("node.partitioned", "BACHT_PARTITIONED"),
+    ("node.pid_file", "BACHT_PID_FILE"),
+    ("node.tenants_file", "BACHT_TENANTS"),
+    ("peers.file", "BACHT_PEERS"),
+    ("peers.gossip", "BACHT_GOSSIP_PEERS"),
+    ("peers.causal", "BACHT_CAUSAL_PEERS"),
+    ("peers.nats", "BACHT_NATS"),
+    ("peers.probe_interval", "BACHT_PROBE_INTERVAL"),
+    ("replication.primary", "BACHT_PRIMARY"),
+    ("replication.replicas", "BACHT_REPLICAS"),
+    ("replication.quorum", "BACHT_QUORUM"),
+    ("raft.members", "BACHT_RAFT_MEMBERS"),
+    ("queue.max_depth", "BACHT_MAX_QUEUE_DEPTH"),
+    ("queue.dropped_results", "BACHT_DROPPED_RESULTS"),
+    ("queue.batch_size", "BACHT_BATCH_SIZE"),
+    ("store.backend", "BACHT_STORE"),
+    ("store.file", "BACHT_STORE_FILE"),
+    ("store.path", "BACHT_STORE_PATH"),
+    ("log.level", "BACHT_LOG"),
+    ("log.file", "BACHT_LOG_FILE"),
+    ("trace.otlp_endpoint", "BACHT_OTLP_ENDPOINT"),
+];
+
+// The sections of the configuration file, any other section or key being refused
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Sections {
+    listen: Listen,
+    node: Node,
+    peers: Peers,
+    replication: Replication,
+    raft: Raft,
+    queue: Queue,
+    store: Store,
+    log: Log,
+    trace: Trace,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Listen {
+    port: Option<u16>,
+    bind: Option<String>,
+    socket: Option<String>,
+    health_port: Option<u16>,
+    grpc_port: Option<u16>,
+    request_ttl: Option<u64>,
+    program_steps: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Node {
+    name: Option<String>,
+    admin_key: Option<String>,
+    peer_key: Option<String>,
+    partitioned: Option<bool>,
+    pid_file: Option<String>,
+    tenants_file: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Peers {
+    file: Option<String>,
+    gossip: Option<Vec<String>>,
+    causal: Option<Vec<String>>,
+    nats: Option<String>,
+    probe_interval: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Replication {
+    primary: Option<String>,
+    replicas: Option<Vec<String>>,
+    quorum: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Raft {
+    members: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Queue {
+    max_depth: Option<usize>,
+    dropped_results: Option<String>,
+    batch_size: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Store {
+    backend: Option<String>,
+    file: Option<String>,
+    path: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Log {
+    level: Option<String>,
+    file: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Trace {
+    otlp_endpoint: Option<String>,
+}
+
+// A setting, an array of the file keeping its items apart
+#[derive(Debug, Clone, PartialEq)]
+enum Setting {
+    Value(String),
+    List(Vec<String>),
+}
+
+impl From<toml::Value> for Setting {
+    fn from(value: toml::Value) -> Self {
+        match value {
+            toml::Value::String(value) => Setting::Value(value),
+            toml::Value::Array(items) => Setting::List(items.into_iter().map(|item| match Setting::from(item) {
+                Setting::Value(item) => item,
+                Setting::List(items) => items.join(","),
+            }).collect()),
+            value => Setting::Value(value.to_string()),
+        }
+    }
+}
+
+/// @summary - The settings of a node, read from a TOML file then overridden by the environment, e.g. `port = 2138` in
+/// the section `[listen]` is the setting `listen.port`, overridden by BACHT_PORT
+///
+/// @note - The file is read into the typed sections of the settings: an unknown section or key, e.g. a typo, and a
+/// value of the wrong type are refused with their line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    values: HashMap<String, Setting>,
+}
+
+impl Config {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// @summary - Read the settings of the file given, else of BACHT_CONFIG, else of bacht.toml if it exists, then
+    /// override them with the environment
+    ///
+    /// @returns - An error if the file can't be read or is not valid
+    pub fn find(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let from_env = std::env::var("BACHT_CONFIG").ok();
+        let config = match path.or(from_env.as_deref().map(Path::new)) {
+            Some(path) => Self::load(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::load(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Self::new(),
+        };
+        Ok(config.with_overrides(|var| std::env::var(var).ok()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Io(format!("{}: {}", path.display(), e)))?;
+        Self::parse(&text)
+    }
+
+    /// @summary - Read the settings of a TOML document
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let sections: Sections = toml::from_str(text).map_err(|e| ConfigError::Syntax {
+            line: e.span().map_or(1, |span| text[..span.start].matches('\n').count() + 1),
+            message: e.message().to_string(),
+        })?;
+        let table = toml::Table::try_from(&sections).expect("The sections serialize to a table");
+        let values = table.into_iter()
+            .flat_map(|(section, keys)| match keys {
+                toml::Value::Table(keys) => keys.into_iter().map(|(key, value)| (format!("{}.{}", section, key), Setting::from(value))).collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+        Ok(Self { values })
+    }
+
+    /// @summary - Override the settings with the environment variables set
+    ///
+    /// @param lookup - The value of an environment variable, e.g. std::env::var
+    pub fn with_overrides(mut self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        for (key, var) in ENV_OVERRIDES {
+            if let Some(value) = lookup(var) {
+                self.values.insert(key.to_string(), Setting::Value(value));
+            }
+        }
+        self
+    }
+
+    /// @summary - Set a setting, e.g. from the command line
+    pub fn with(mut self, key: &str, value: &str) -> Self {
+        self.values.insert(key.to_string(), Setting::Value(value.to_string()));
+        self
+    }
+
+    /// @returns - true if a setting is set, be it a value or an array
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// @returns - The value of a setting, e.g. `listen.port`, None if it is not set or is an array
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.values.get(key) {
+            Some(Setting::Value(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// @returns - The value of a setting parsed, or an error if it does not parse
+    pub fn parse_value<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        self.get(key).map(|value| value.parse().map_err(|_| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        })).transpose()
+    }
+
+    /// @returns - The items of a setting that is an array, or comma separated in the environment
+    pub fn get_list(&self, key: &str) -> Vec<&str> {
+        match self.values.get(key) {
+            Some(Setting::List(items)) => items.iter().map(String::as_str).collect(),
+            Some(Setting::Value(value)) => value.split(',').map(str::trim).filter(|item| !item.is_empty()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// @returns - true if a setting is set to anything but false, as the environment variables set without a value
+    pub fn is_set(&self, key: &str) -> bool {
+        self.get(key).is_some_and(|value| value != "false")
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::Level;
+
+    #[test]
+    fn config_should_read_a_toml_file_overridden_by_the_environment() {
+        let config = Config::parse("\
+            # A sensor node\n\
+            [listen]\n\
+            port = 4000\n\
+            bind = \"::\" # both IPv6 and IPv4\n\
+            [peers]\n\
+            gossip = [\"10.0.0.1:4000\", \"10.0.0.2:4000\"]\n\
+            [node]\n\
+            name = \"sensors #1\"\n\
+            partitioned = false\n\
+            [log]\n\
+            level = \"debug\"\n\
+        ").unwrap();
+        assert_eq!(config.parse_value::<u16>("listen.port").unwrap(), Some(4000));
+        assert_eq!(config.get("listen.bind"), Some("::"));
+        assert_eq!(config.get_list("peers.gossip"), vec!["10.0.0.1:4000", "10.0.0.2:4000"]);
+        assert_eq!(config.get("node.name"), Some("sensors #1"));
+        assert!(!config.is_set("node.partitioned"));
+        assert_eq!(config.parse_value::<Level>("log.level").unwrap(), Some(Level::Debug));
+
+        let config = config.with_overrides(|var| (var == "BACHT_PORT").then(|| "5000".to_string()));
+        assert_eq!(config.get("listen.port"), Some("5000"));
+        assert_eq!(config.get("listen.bind"), Some("::"));
+        assert_eq!(config.with("listen.port", "x").parse_value::<u16>("listen.port"),
+            Err(ConfigError::InvalidValue { key: "listen.port".into(), value: "x".into() }));
+
+        assert!(matches!(Config::parse("[listen]\nbind = ::1"), Err(ConfigError::Syntax { line: 2, .. })));
+        assert!(matches!(Config::parse("[node]\nname = \"a"), Err(ConfigError::Syntax { line: 2, .. })));
+    }
+
+    #[test]
+    fn config_should_refuse_the_unknown_keys_and_the_values_of_the_wrong_type() {
+        let error = Config::parse("[listen]\nbind = \"::\"\nprot = 4000").unwrap_err().to_string();
+        assert!(error.starts_with("line 3: unknown field `prot`"), "{}", error);
+        assert!(matches!(Config::parse("[lisen]\nport = 4000"), Err(ConfigError::Syntax { line: 1, .. })));
+        assert!(matches!(Config::parse("[listen]\nport = \"4000\""), Err(ConfigError::Syntax { line: 2, .. })));
+        assert!(matches!(Config::parse("name = \"a\""), Err(ConfigError::Syntax { line: 1, .. })), "A setting should be in its section");
+
+        let config = Config::parse("[peers]\ncausal = [\"a,b\", \"c\"]\ngossip = []").unwrap();
+        assert_eq!(config.get_list("peers.causal"), vec!["a,b", "c"], "The items of an array should be kept apart");
+        assert!(config.contains("peers.gossip") && config.get_list("peers.gossip").is_empty());
+    }
+}
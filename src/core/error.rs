@@ -0,0 +1,237 @@
+//! The errors of the library, one per layer, each converting into the errors of the layers above it: a QueueError
+//! of the task queue is a StoreError of the blackboard, which is an Error of the agent running on it.
+
+use thiserror::Error;
+
+/// The queue between a blackboard and its worker failed to carry a task
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum QueueError {
+    #[error("the task failed")]
+    Unspecified,
+    /// The worker dropped the task without answering it, e.g. it stopped
+    #[error("the worker dropped the task")]
+    Channel,
+}
+
+/// A blackboard failed to apply an operation on its store
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum StoreError {
+    #[error("{0}")]
+    Queue(#[from] QueueError),
+    /// A replica refuses the writes of the agents, they must be sent to the primary
+    #[error("a replica is read-only, write on the primary")]
+    ReadOnlyReplica,
+    /// A primary refuses the mutations streamed by another primary
+    #[error("a primary does not apply the mutations of another primary")]
+    NotAReplica,
+    /// The node owning the token of a partitioned blackboard can't be reached
+    #[error("the owner of the token can't be reached")]
+    OwnerUnreachable,
+    /// The deadline of the event passed before the worker applied it, the store is unchanged
+    #[error("the event expired before being applied")]
+    Expired,
+    /// The primary is cut from the quorum of its replicas, it refuses the writes and the store is unchanged
+    #[error("the primary can't reach a quorum of replicas, it refuses the writes")]
+    Fenced,
+    /// The primary applied the mutation, but the quorum of its replicas did not acknowledge it in time: it may be lost
+    /// if the primary fails
+    #[error("the mutation was not acknowledged by a quorum of replicas")]
+    NoQuorum,
+    /// A follower of a Raft cluster refuses the operations of the agents, they must be sent to the leader, if known
+    #[error("the node is not the leader, {}", leader_of(.0))]
+    NotLeader(Option<Box<str>>),
+    /// The worker failed the event on purpose, see blackboard::chaos, the store is unchanged
+    #[error("the event was failed by the chaos of the worker")]
+    Injected,
+    /// The table of the names of the tokens is full, the tokens refused are not added to the store
+    #[error("{0}")]
+    TableFull(#[from] TableFull),
+}
+
+/// Why a BachT agent does not parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The agent can't become valid, whatever follows
+    Invalid,
+    /// The agent stops before its end, e.g. on an unclosed parenthesis or after an operator: the lines following it
+    /// may complete it
+    Incomplete,
+}
+
+/// A BachT agent does not parse, only the line of its position being quoted
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{}{}", match rest.lines().next() {
+    Some(line) => format!("unexpected `{}` at {}:{}", line, self.line, column),
+    None => "unexpected end of the agent".to_string(),
+}, expected.as_ref().map_or(String::new(), |expected| format!(", expected {}", expected)))]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Where the agent stops being valid, in bytes from its start
+    pub position: usize,
+    /// The line of the position, from 1
+    pub line: usize,
+    /// The column of the position in its line, in characters from 1
+    pub column: usize,
+    /// The input left from the position
+    pub rest: String,
+    /// The construct the parser expected at the position, e.g. "`)`", if known
+    pub expected: Option<String>,
+    /// The line of the position, with a caret under its column
+    pub snippet: String,
+}
+
+/// A socket, or the remote blackboard at its end, failed
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TransportError {
+    /// The remote blackboard could not be reached within the reconnection policy
+    #[error("connection failed: {0}")]
+    ConnectionFailed(String),
+    /// The connection broke while the request was pending and the policy forbids resending it
+    #[error("connection lost")]
+    ConnectionLost,
+    /// The remote answered with something that is not a valid response
+    #[error("protocol error: {0}")]
+    ProtocolError(String),
+    /// The remote blackboard failed to process the request
+    #[error("remote error: {0}")]
+    RemoteError(String),
+    /// The remote stayed silent longer than the heartbeat timeout
+    #[error("the peer stopped answering")]
+    PeerDead,
+    /// A socket could not be opened, e.g. its port is taken
+    #[error("{0}")]
+    BindFailed(String),
+    /// An open socket failed to read, write or accept
+    #[error("{0}")]
+    Io(String),
+}
+
+/// The configuration file of a node can't be read, see config::Config
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ConfigError {
+    #[error("{0}")]
+    Io(String),
+    /// The file is not valid TOML, or has an unknown setting or a setting of the wrong type
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    /// A setting does not parse as the type expected, e.g. a port
+    #[error("invalid value {value} of {key}")]
+    InvalidValue { key: String, value: String },
+    /// A setting names a feature not built in, e.g. a store backend
+    #[error("{key} {value} is not available in this build")]
+    Unavailable { key: String, value: String },
+}
+
+/// The table of the names of the tokens is full, it refuses the names received from other processes, see
+/// model::token::TABLE_CAPACITY
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("the table of the tokens is full")]
+pub struct TableFull;
+
+/// An agent failed to run, whatever the layer
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    #[error("{0}")]
+    Store(#[from] StoreError),
+    #[error("{0}")]
+    Transport(#[from] TransportError),
+    /// A primitive other than tell, ask, get and nask
+    #[error("unknown primitive {0}")]
+    UnknownPrimitive(String),
+    /// An agent calls a name that is not defined
+    #[error("unknown agent {0}")]
+    UnknownAgent(String),
+    /// A definition calls itself before executing any primitive, e.g. `loop = loop`
+    #[error("{0} calls itself before any primitive")]
+    UnguardedRecursion(String),
+    /// A name that can't be defined, e.g. a primitive
+    #[error("Invalid name {0}")]
+    InvalidName(String),
+    /// An agent called with another number of arguments than its parameters
+    #[error("{name} expects {expected} arguments, {given} given")]
+    WrongArity { name: String, expected: usize, given: usize },
+    /// A variable written before any primitive bound it, e.g. `tell(X)`
+    #[error("the variable {0} is not bound")]
+    UnboundVariable(String),
+    /// An agent neither terminated nor got stuck within the transitions given, e.g. `*tell(a)`
+    #[error("the agent did not terminate within {0} transitions")]
+    StepLimit(usize),
+    /// An agent was stopped before it terminated, e.g. by Ctrl-C in the REPL
+    #[error("the agent was cancelled")]
+    Cancelled,
+    /// An agent built by hand that the simulator can't run, e.g. a composition by an unknown operator
+    #[error("invalid agent {0}")]
+    InvalidAgent(String),
+}
+
+impl ParseError {
+
+    /// @summary - The error of an agent, from the input left where it stops being valid
+    pub fn new(input: &str, rest: &str) -> Self {
+        let position = input.len() - rest.len();
+        let start = input[..position].rfind('\n').map_or(0, |newline| newline + 1);
+        let end = input[position..].find('\n').map_or(input.len(), |newline| position + newline);
+        let column = input[start..position].chars().count() + 1;
+        Self {
+            kind: if rest.is_empty() { ParseErrorKind::Incomplete } else { ParseErrorKind::Invalid },
+            position,
+            line: input[..position].matches('\n').count() + 1,
+            column,
+            rest: rest.to_string(),
+            expected: None,
+            snippet: format!("{}\n{}^", &input[start..end], " ".repeat(column - 1)),
+        }
+    }
+
+    /// @summary - Mark the error as the end of an incomplete agent, e.g. one whose comment is not closed
+    pub fn incomplete(self) -> Self {
+        Self { kind: ParseErrorKind::Incomplete, ..self }
+    }
+
+    /// @summary - Tell the construct expected at the position of the error
+    pub fn expecting(self, expected: &str) -> Self {
+        Self { expected: Some(expected.to_string()), ..self }
+    }
+
+    /// @returns - The error followed by its snippet, e.g. to print it under the agent typed
+    pub fn diagnostic(&self) -> String {
+        format!("{}\n{}", self, self.snippet)
+    }
+}
+
+fn leader_of(leader: &Option<Box<str>>) -> String {
+    leader.as_ref().map_or("none is elected".to_string(), |leader| format!("{} is", leader))
+}
+
+// A failure of the queue reaches the agents through the blackboard
+impl From<QueueError> for Error {
+    fn from(error: QueueError) -> Self {
+        Error::Store(error.into())
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn errors_should_convert_into_the_errors_of_the_layers_above() {
+        let error: Error = QueueError::Channel.into();
+        assert_eq!(error, Error::Store(StoreError::Queue(QueueError::Channel)));
+        assert_eq!(error.to_string(), "the worker dropped the task");
+        assert_eq!(error.source().unwrap().source().unwrap().to_string(), "the worker dropped the task");
+
+        let error: Error = ParseError::new("tell(a))", ")").into();
+        assert_eq!(error.to_string(), "unexpected `)` at 1:8");
+        let error = ParseError::new("tell(a);\n  get(", "").expecting("a term");
+        assert_eq!((error.kind, error.line, error.column), (ParseErrorKind::Incomplete, 2, 7));
+        assert_eq!(error.diagnostic(), "unexpected end of the agent, expected a term\n  get(\n      ^");
+        assert_eq!(Error::from(TransportError::PeerDead).to_string(), "the peer stopped answering");
+    }
+}
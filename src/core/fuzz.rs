@@ -0,0 +1,108 @@
+//! Entry points for the fuzzers, e.g. cargo-fuzz, each one taking arbitrary bytes. A panic is caught and returned as an
+//! error, as is a broken invariant, so that a fuzzer reports them while the inputs merely rejected are not.
+//! A stack overflow aborts the process instead, which no entry point can catch: the fuzzer reports it as a crash, the
+//! parser refusing the agents nested deeper than MAX_DEPTH so that it does not recurse that far.
+//!
+//! The seeds of the corpus are in `fuzz/corpus/<entry point>`, e.g. a cargo-fuzz target calling
+//! `bacht::fuzz::fuzz_parse(data).unwrap()` is run with `cargo fuzz run parse fuzz/corpus/parse`.
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use crate::language::parser::parse;
+#[cfg(feature = "network")]
+use crate::communication::frame::{Frame, read_frame};
+
+/// @summary - Parse the bytes as an agent
+///
+/// @returns - true if the agent parsed, false if it was rejected, or the panic or the broken invariant
+pub fn fuzz_parse(bytes: &[u8]) -> Result<bool, String> {
+    let Ok(input) = std::str::from_utf8(bytes) else { return Ok(false) };
+    catching(|| match parse(input) {
+        Ok(_) => Ok(true),
+        Err(e) if e.position > input.len() => Err(format!("Error position {} beyond the input", e.position)),
+        Err(_) => Ok(false),
+    })
+}
+
+/// @summary - Read the bytes as a frame received on a connection, length and correlation id included
+///
+/// @returns - true if a frame was read, false if the bytes were rejected, or the panic or the broken invariant, e.g. a
+/// frame read that is not read back once written
+#[cfg(feature = "network")]
+pub fn fuzz_decode_frame(bytes: &[u8]) -> Result<bool, String> {
+    catching(|| {
+        let mut reader = bytes;
+        let frame = match poll_ready(read_frame(&mut reader))? {
+            Ok(Some((_, frame))) => frame,
+            Ok(None) | Err(_) => return Ok(false),
+        };
+        match Frame::decode(&frame.encode()) {
+            Ok(decoded) if decoded == frame => Ok(true),
+            decoded => Err(format!("{:?} read back as {:?}", frame, decoded)),
+        }
+    })
+}
+
+// Run an entry point, its panic being returned as an error
+fn catching(entry_point: impl FnOnce() -> Result<bool, String>) -> Result<bool, String> {
+    catch_unwind(AssertUnwindSafe(entry_point)).unwrap_or_else(|panic| {
+        let message = panic.downcast_ref::<&str>().map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format!("Panicked: {}", message))
+    })
+}
+
+// The output of a future that never waits, e.g. reading a slice
+#[cfg(feature = "network")]
+fn poll_ready<F: std::future::Future>(future: F) -> Result<F::Output, String> {
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    match std::pin::pin!(future).poll(&mut context) {
+        std::task::Poll::Ready(output) => Ok(output),
+        std::task::Poll::Pending => Err("The future waited on a slice".to_string()),
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn corpus(entry_point: &str) -> Vec<(String, Vec<u8>)> {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus").join(entry_point);
+        std::fs::read_dir(directory).unwrap()
+            .map(|seed| seed.unwrap().path())
+            .map(|path| (path.file_name().unwrap().to_string_lossy().to_string(), std::fs::read(path).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn fuzz_entry_points_should_accept_or_reject_the_seeds_without_error() {
+        let seeds = corpus("parse");
+        assert!(!seeds.is_empty());
+        for (name, seed) in seeds {
+            assert!(fuzz_parse(&seed).is_ok(), "The seed {} should not fail", name);
+        }
+        assert_eq!(fuzz_parse(b"tell(a);get(a)"), Ok(true));
+        assert_eq!(fuzz_parse(b"tell(a"), Ok(false));
+        assert_eq!(fuzz_parse(&[0xff, 0xfe]), Ok(false));
+        for deep in ["deep_parentheses", "deep_replication", "deep_term"] {
+            let seed = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/parse").join(deep)).unwrap();
+            assert_eq!(fuzz_parse(&seed), Ok(false), "The seed {} should be rejected", deep);
+        }
+        assert_eq!(catching(|| panic!("broken")), Err("Panicked: broken".to_string()));
+
+        #[cfg(feature = "network")]
+        {
+            let seeds = corpus("decode_frame");
+            assert!(!seeds.is_empty());
+            for (name, seed) in seeds {
+                assert!(fuzz_decode_frame(&seed).is_ok(), "The seed {} should not fail", name);
+            }
+            assert_eq!(fuzz_decode_frame(b"\x00\x00\x00\x09\x00\x00\x00\x00\x00\x00\x00\x07\x04"), Ok(true));
+            assert_eq!(fuzz_decode_frame(b"\x00\x00\x00\x03abc"), Ok(false));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::future::Future;
+use mockall::automock;
+use crate::blackboard::{Blackboard, BlackboardTrait};
+use crate::blackboard::store::Store;
+use crate::blackboard::task_queue::TaskQueue;
+use crate::blackboard::worker::Worker;
+use crate::error::Error;
+use crate::model::admin::{AdminCommand, AdminReply};
+use crate::model::token::TokenId;
+#[cfg(feature = "network")]
+use crate::communication::socket_client::{ReconnectPolicy, SocketClient, SocketClientTrait};
+#[cfg(feature = "network")]
+use crate::communication::socket_listener::DEFAULT_SOCKET_PORT;
+#[cfg(feature = "network")]
+use crate::error::TransportError;
+
+#[automock]
+pub trait BlackboardInterfaceTrait {
+    
+    fn new() -> Self;
+    
+    fn tell(&self, coord_data: &str) -> impl Future<Output=Result<bool, Error>>;
+    
+    fn ask(&self, coord_data: &str) -> impl Future<Output=Result<bool, Error>>;
+    
+    fn get(&self, coord_data: &str) -> impl Future<Output=Result<bool, Error>>;
+    
+    fn nask(&self, coord_data: &str) -> impl Future<Output=Result<bool, Error>>;
+
+    /// @summary - List the tokens on the blackboard, e.g. to match the primitives on a term with variables
+    ///
+    /// @returns - The tokens with at least one occurrence, sorted by name
+    fn tokens(&self) -> impl Future<Output=Result<Vec<TokenId>, Error>>;
+}
+
+/// @summary - The interface to a blackboard of this process, e.g. the blackboard of a node running the programs of its clients
+pub struct LocalBlackboardInterface<B: BlackboardTrait = Blackboard<TaskQueue, Worker, Store>> {
+    blackboard: B,
+}
+
+impl<B: BlackboardTrait> LocalBlackboardInterface<B> {
+
+    /// @summary - Interface an existing blackboard, sharing its store
+    pub fn new_with(blackboard: B) -> Self {
+        Self { blackboard }
+    }
+
+    /// @returns - The interfaced blackboard, e.g. to manage it
+    pub fn blackboard(&self) -> &B {
+        &self.blackboard
+    }
+}
+
+impl<B: BlackboardTrait + Sync> BlackboardInterfaceTrait for LocalBlackboardInterface<B> {
+
+    fn new() -> Self {
+        Self::new_with(B::new())
+    }
+
+    async fn tell(&self, coord_data: &str) -> Result<bool, Error> {
+        self.blackboard.tell(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn ask(&self, coord_data: &str) -> Result<bool, Error> {
+        self.blackboard.ask(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn get(&self, coord_data: &str) -> Result<bool, Error> {
+        self.blackboard.get(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn nask(&self, coord_data: &str) -> Result<bool, Error> {
+        self.blackboard.nask(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn tokens(&self) -> Result<Vec<TokenId>, Error> {
+        match self.blackboard.admin(AdminCommand::Snapshot).await.map_err(Error::from)? {
+            AdminReply::Snapshot(tokens) => Ok(tokens.iter().map(|(token, _)| TokenId::from(token)).collect()),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// @summary - The interface to the blackboard of a remote node, through the wire protocol of its listener
+#[cfg(feature = "network")]
+pub struct RemoteBlackboardInterface {
+    client: SocketClient,
+}
+
+#[cfg(feature = "network")]
+impl RemoteBlackboardInterface {
+
+    /// @summary - Interface the blackboard a client is connected to
+    pub fn new_with(client: SocketClient) -> Self {
+        Self { client }
+    }
+
+    /// @returns - The client, e.g. to manage the remote blackboard
+    pub fn client(&self) -> &SocketClient {
+        &self.client
+    }
+}
+
+#[cfg(feature = "network")]
+impl BlackboardInterfaceTrait for RemoteBlackboardInterface {
+
+    /// @summary - Interface the node listening on the default port of this host, connected on the first primitive
+    fn new() -> Self {
+        Self::new_with(SocketClient::new_with(&format!("127.0.0.1:{}", DEFAULT_SOCKET_PORT), ReconnectPolicy::default()))
+    }
+
+    async fn tell(&self, coord_data: &str) -> Result<bool, Error> {
+        self.client.tell(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn ask(&self, coord_data: &str) -> Result<bool, Error> {
+        self.client.ask(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn get(&self, coord_data: &str) -> Result<bool, Error> {
+        self.client.get(coord_data.into()).await.map_err(Error::from)
+    }
+
+    async fn nask(&self, coord_data: &str) -> Result<bool, Error> {
+        self.client.nask(coord_data.into()).await.map_err(Error::from)
+    }
+
+    /// @note - The snapshot is an admin command: the client must hold the admin capability of the node
+    async fn tokens(&self) -> Result<Vec<TokenId>, Error> {
+        match self.client.admin(AdminCommand::Snapshot).await.map_err(Error::from)? {
+            AdminReply::Snapshot(tokens) => Ok(tokens.iter().map(|(token, _)| TokenId::from(token)).collect()),
+            other => Err(Error::Transport(TransportError::ProtocolError(format!("Unexpected answer to snapshot: {:?}", other)))),
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::create_blackboard;
+
+    #[tokio::test]
+    async fn local_interface_should_share_the_store_of_its_blackboard() {
+        let blackboard = create_blackboard();
+        let interface = LocalBlackboardInterface::new_with(blackboard.clone());
+        assert!(interface.tell("token").await.unwrap());
+        assert!(blackboard.ask("token".into()).await.unwrap());
+        assert!(interface.get("token").await.unwrap());
+        assert!(interface.nask("token").await.unwrap());
+        assert!(!interface.ask("token").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn local_interface_should_list_the_tokens_on_its_blackboard() {
+        let interface: LocalBlackboardInterface = LocalBlackboardInterface::new();
+        for token in ["msg(b)", "msg(a)", "msg(a)", "ack"] {
+            assert!(interface.tell(token).await.unwrap());
+        }
+        assert!(interface.get("ack").await.unwrap());
+        assert_eq!(interface.tokens().await.unwrap(), vec![TokenId::from("msg(a)"), TokenId::from("msg(b)")]);
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn remote_interface_should_run_the_simulator_on_the_node() {
+        use crate::communication::socket_listener::{SocketListener, SocketListenerTrait};
+        use crate::language::parser::parse;
+        use crate::language::simulator::{Simulator, SimulatorTrait};
+
+        let port = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let blackboard = create_blackboard();
+        let listener = SocketListener::new(blackboard.clone(), Some(port));
+        tokio::spawn(async move { listener.listen().await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let client = SocketClient::new_with(&format!("127.0.0.1:{}", port), ReconnectPolicy::default());
+        let simulator = Simulator::new_with(RemoteBlackboardInterface::new_with(client));
+        assert!(simulator.bacht_exec_all(parse("tell(a);tell(b);get(a)").unwrap()).await.unwrap());
+        assert!(blackboard.ask("b".into()).await.unwrap());
+        assert!(!blackboard.ask("a".into()).await.unwrap());
+    }
+}
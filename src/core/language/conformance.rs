@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::blackboard::{Blackboard, BlackboardTrait, create_blackboard};
+use crate::blackboard::store::Store;
+use crate::blackboard::task_queue::TaskQueue;
+use crate::blackboard::worker::Worker;
+use crate::error::Error;
+use crate::language::blackboard_interface::LocalBlackboardInterface;
+use crate::language::model::data::Expr;
+use crate::language::model::data::Expr::BachtAstEmptyAgent;
+use crate::language::parser::parse;
+use crate::language::simulator::{Simulator, SimulatorTrait};
+use crate::model::admin::{AdminCommand, AdminReply};
+
+// The seeds tried to take a transition of the reference, each one picking other branches of the agent
+const MAX_SEEDS: u64 = 256;
+
+/// One transition of a conformance trace: the primitive executed, and the store once executed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub primitive: String,
+    pub token: String,
+    /// The occurrences of each token present
+    pub store: BTreeMap<String, u32>,
+}
+
+/// How the agent of a trace ended: "terminated" once it is empty, else "stuck"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Terminated,
+    Stuck,
+}
+
+/// @summary - The transitions of an agent run from a store, in a JSON format the reference implementation of BachT in
+/// Scala can export as well, e.g. to check that this interpreter agrees with it on the programs of a course:
+///
+/// ```json
+/// {
+///   "agent": "tell(a);get(a)",
+///   "store": {},
+///   "transitions": [
+///     {"primitive": "tell", "token": "a", "store": {"a": 1}},
+///     {"primitive": "get", "token": "a", "store": {}}
+///   ],
+///   "outcome": "terminated"
+/// }
+/// ```
+///
+/// The members the format does not know are ignored, and a missing store is the empty one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConformanceTrace {
+    pub agent: String,
+    /// The store the agent is run from
+    #[serde(default)]
+    pub store: BTreeMap<String, u32>,
+    pub transitions: Vec<TraceStep>,
+    pub outcome: Outcome,
+}
+
+/// Whether this interpreter agrees with a trace
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conformance {
+    /// Every transition of the trace was taken, with the same outcome
+    Agrees,
+    /// The first transition of the trace not taken, counted from 1, the outcome being one past the last transition
+    Diverges { step: usize, reason: String },
+}
+
+impl ConformanceTrace {
+
+    /// @summary - Run an agent on this interpreter, recording its transitions
+    ///
+    /// @param store - The occurrences of the tokens before the agent runs
+    /// @param seed - Picks the branches of the agent, the same seed picking the same ones
+    pub async fn record(agent: &str, store: &[(String, u32)], seed: u64) -> Result<Self, Error> {
+        let mut current = parse(agent)?;
+        let store = present(store.iter().cloned());
+        let blackboard = filled(&store).await?;
+        let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(blackboard.clone()));
+        simulator.seed(seed);
+        let mut transitions = Vec::new();
+        let outcome = loop {
+            if current == BachtAstEmptyAgent() {
+                break Outcome::Terminated;
+            }
+            let Some(transition) = simulator.step(current).await? else { break Outcome::Stuck };
+            let (primitive, token) = transition.executed;
+            transitions.push(TraceStep { primitive, token, store: snapshot(&blackboard).await? });
+            current = transition.continuation;
+        };
+        Ok(Self { agent: agent.to_string(), store, transitions, outcome })
+    }
+
+    /// @summary - Replay the trace on this interpreter, e.g. one exported by the reference implementation
+    ///
+    /// @returns - Agrees if this interpreter can take each transition of the trace, reaching the same stores and the
+    /// same outcome, else the first transition it can't take
+    ///
+    /// @note - The branches of the parallel compositions and choices being picked at random, each transition is tried
+    /// with up to 256 seeds before being reported as not taken
+    pub async fn check(&self) -> Result<Conformance, Error> {
+        let mut current = parse(&self.agent)?;
+        let mut store = present(self.store.clone());
+        for (number, expected) in self.transitions.iter().enumerate() {
+            let Some(continuation) = take(&current, &store, expected).await? else {
+                let reason = format!("{}({}) can't be taken from {}", expected.primitive, expected.token, format_store(&store));
+                return Ok(Conformance::Diverges { step: number + 1, reason });
+            };
+            current = continuation;
+            store = present(expected.store.clone());
+        }
+        let step = self.transitions.len() + 1;
+        let outcome = match current == BachtAstEmptyAgent() {
+            true => Outcome::Terminated,
+            false => Outcome::Stuck,
+        };
+        if outcome != self.outcome {
+            let reason = match outcome {
+                Outcome::Terminated => "the agent terminated instead of being stuck".to_string(),
+                Outcome::Stuck => format!("the agent is not empty, {:?} remains", current),
+            };
+            return Ok(Conformance::Diverges { step, reason });
+        }
+        if outcome == Outcome::Stuck {
+            let blackboard = filled(&store).await?;
+            let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(blackboard));
+            if let Some(transition) = simulator.step(current).await? {
+                let (primitive, token) = transition.executed;
+                let reason = format!("the agent is not stuck, {}({}) can be taken", primitive, token);
+                return Ok(Conformance::Diverges { step, reason });
+            }
+        }
+        Ok(Conformance::Agrees)
+    }
+
+    /// @returns - The trace in the JSON format, indented
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("A trace is always serializable");
+        json.push('\n');
+        json
+    }
+
+    /// @summary - Read a trace in the JSON format, the members it does not know being ignored
+    ///
+    /// @returns - The trace, or where the JSON is invalid
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
+
+// The continuation of the agent once the expected transition is taken from the store, None if it can't be taken
+async fn take(agent: &Expr, store: &BTreeMap<String, u32>, expected: &TraceStep) -> Result<Option<Expr>, Error> {
+    for seed in 0..MAX_SEEDS {
+        let blackboard = filled(store).await?;
+        let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(blackboard.clone()));
+        simulator.seed(seed);
+        let Some(transition) = simulator.step(agent.clone()).await? else { return Ok(None) };
+        if transition.executed == (expected.primitive.clone(), expected.token.clone())
+            && snapshot(&blackboard).await? == present(expected.store.clone()) {
+            return Ok(Some(transition.continuation));
+        }
+    }
+    Ok(None)
+}
+
+async fn filled(store: &BTreeMap<String, u32>) -> Result<Blackboard<TaskQueue, Worker, Store>, Error> {
+    let blackboard = create_blackboard();
+    for (token, occurrences) in store {
+        for _ in 0..*occurrences {
+            blackboard.tell(token.as_str().into()).await?;
+        }
+    }
+    Ok(blackboard)
+}
+
+async fn snapshot<B: BlackboardTrait>(blackboard: &B) -> Result<BTreeMap<String, u32>, Error> {
+    match blackboard.admin(AdminCommand::Snapshot).await? {
+        AdminReply::Snapshot(tokens) => Ok(present(tokens.into_iter().map(|(token, occurrences)| (token.into(), occurrences)))),
+        _ => Ok(BTreeMap::new()),
+    }
+}
+
+// The tokens present, without the ones of no occurrence
+fn present(store: impl IntoIterator<Item = (String, u32)>) -> BTreeMap<String, u32> {
+    store.into_iter().filter(|(_, occurrences)| *occurrences > 0).collect()
+}
+
+fn format_store(store: &BTreeMap<String, u32>) -> String {
+    match store.is_empty() {
+        true => "the empty store".to_string(),
+        false => store.iter().map(|(token, occurrences)| format!("{}({})", token, occurrences)).collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn trace_should_be_read_back_as_written() {
+        let trace = ConformanceTrace::record("tell(a);(get(a)||tell(b))", &[("c".into(), 2)], 5).await.unwrap();
+        assert_eq!(trace.transitions.len(), 3);
+        assert_eq!(trace.outcome, Outcome::Terminated);
+        assert_eq!(trace.transitions.last().unwrap().store, BTreeMap::from([("b".to_string(), 1), ("c".to_string(), 2)]));
+        assert_eq!(ConformanceTrace::from_json(&trace.to_json()).unwrap(), trace);
+
+        let stuck = ConformanceTrace::record("get(a)", &[], 5).await.unwrap();
+        assert_eq!(stuck.to_json(), "{\n  \"agent\": \"get(a)\",\n  \"store\": {},\n  \"transitions\": [],\n  \"outcome\": \"stuck\"\n}\n");
+        let error = ConformanceTrace::from_json("{\"agent\": \"get(a)\", \"transitions\": [], \"outcome\": \"done\"}").unwrap_err();
+        assert!(error.starts_with("unknown variant `done`, expected `terminated` or `stuck`"), "{}", error);
+        let error = ConformanceTrace::from_json("{\"agent\": \"get(a)\", \"store\": {\"a\": -1}, \"transitions\": [], \"outcome\": \"stuck\"}").unwrap_err();
+        assert!(error.starts_with("invalid value: integer `-1`, expected u32"), "{}", error);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn check_should_report_the_first_transition_of_the_reference_not_taken() {
+        let reference = |transitions: &str, outcome: &str| ConformanceTrace::from_json(&format!(
+            "{{\"agent\": \"tell(a);(get(a)+tell(b))\", \"store\": {{}}, \"transitions\": [{}], \"outcome\": \"{}\"}}",
+            transitions, outcome)).unwrap();
+        let tell_a = "{\"primitive\": \"tell\", \"token\": \"a\", \"store\": {\"a\": 1}}";
+        // Either branch of the choice is taken, whatever the seed picking the branches
+        let get_a = "{\"primitive\": \"get\", \"token\": \"a\", \"store\": {}}";
+        let tell_b = "{\"primitive\": \"tell\", \"token\": \"b\", \"store\": {\"a\": 1, \"b\": 1}}";
+        assert_eq!(reference(&format!("{}, {}", tell_a, get_a), "terminated").check().await.unwrap(), Conformance::Agrees);
+        assert_eq!(reference(&format!("{}, {}", tell_a, tell_b), "terminated").check().await.unwrap(), Conformance::Agrees);
+
+        let tell_c = "{\"primitive\": \"tell\", \"token\": \"c\", \"store\": {\"a\": 1, \"c\": 1}}";
+        assert_eq!(reference(&format!("{}, {}", tell_a, tell_c), "terminated").check().await.unwrap(),
+            Conformance::Diverges { step: 2, reason: "tell(c) can't be taken from a(1)".to_string() });
+        assert!(matches!(reference(tell_a, "stuck").check().await.unwrap(), Conformance::Diverges { step: 2, .. }));
+    }
+}
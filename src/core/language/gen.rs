@@ -0,0 +1,176 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::language::model::data::Expr;
+use crate::language::model::data::Expr::BachtAstPrimitive;
+
+// The tokens of the agents generated, the first ones being picked
+const TOKENS: [&str; 26] = [
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m",
+    "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+];
+
+// In the order of the weights of the generator
+const PRIMITIVES: [&str; 4] = ["tell", "ask", "get", "nask"];
+const OPERATORS: [&str; 3] = [";", "||", "+"];
+
+/// @summary - The Generator produces random well-formed agents and stores, e.g. to check a property of the language on
+/// many agents. The same seed produces the same agents and stores.
+///
+/// ```
+/// use bacht::language::gen::Generator;
+///
+/// // Choices between tells, on 2 tokens
+/// let mut generator = Generator::new(7).with_tokens(2).with_primitives([1, 0, 0, 0]).with_operators([0, 0, 1]);
+/// let agent = generator.agent();
+/// ```
+pub struct Generator {
+    rng: StdRng,
+    // The number of distinct tokens, at most 26
+    tokens: usize,
+    // The maximum number of primitives of an agent
+    size: usize,
+    // The maximum number of occurrences of a token in a store
+    occurrences: u32,
+    // The weights of tell, ask, get and nask
+    primitives: [u32; 4],
+    // The weights of ;, || and +
+    operators: [u32; 3],
+}
+
+impl Generator {
+
+    /// @summary - Generate agents of at most 8 primitives on 4 tokens, every primitive and operator being as likely
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            tokens: 4,
+            size: 8,
+            occurrences: 2,
+            primitives: [1; 4],
+            operators: [1; 3],
+        }
+    }
+
+    /// @param tokens - The number of distinct tokens, between 1 and 26
+    pub fn with_tokens(mut self, tokens: usize) -> Self {
+        self.tokens = tokens.clamp(1, TOKENS.len());
+        self
+    }
+
+    /// @param size - The maximum number of primitives of an agent, at least 1
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.size = size.max(1);
+        self
+    }
+
+    /// @param occurrences - The maximum number of occurrences of a token in a store
+    pub fn with_occurrences(mut self, occurrences: u32) -> Self {
+        self.occurrences = occurrences;
+        self
+    }
+
+    /// @param primitives - The weights of tell, ask, get and nask, a primitive of weight 0 never being generated
+    ///
+    /// @note - Every primitive is as likely if all the weights are 0
+    pub fn with_primitives(mut self, primitives: [u32; 4]) -> Self {
+        self.primitives = primitives;
+        self
+    }
+
+    /// @param operators - The weights of the sequence, the parallel composition and the choice
+    ///
+    /// @note - Every operator is as likely if all the weights are 0
+    pub fn with_operators(mut self, operators: [u32; 3]) -> Self {
+        self.operators = operators;
+        self
+    }
+
+    /// @returns - An agent of 1 up to size primitives
+    pub fn agent(&mut self) -> Expr {
+        let size = self.rng.random_range(1..=self.size);
+        self.agent_of(size)
+    }
+
+    /// @returns - The occurrences of each token in a store, the absent tokens being left out
+    pub fn store(&mut self) -> Vec<(&'static str, u32)> {
+        TOKENS[..self.tokens].iter()
+            .map(|token| (*token, self.rng.random_range(0..=self.occurrences)))
+            .filter(|(_, occurrences)| *occurrences > 0)
+            .collect()
+    }
+
+    // An agent of exactly size primitives
+    fn agent_of(&mut self, size: usize) -> Expr {
+        if size == 1 {
+            let primitive = PRIMITIVES[pick(&mut self.rng, &self.primitives)];
+            return BachtAstPrimitive(primitive, TOKENS[self.rng.random_range(0..self.tokens)].into());
+        }
+        let operator = OPERATORS[pick(&mut self.rng, &self.operators)];
+        let left = self.rng.random_range(1..size);
+        Expr::composition(operator, vec![self.agent_of(left), self.agent_of(size - left)])
+    }
+}
+
+// The index of a weight, picked in proportion to the weights
+fn pick(rng: &mut StdRng, weights: &[u32]) -> usize {
+    let total: u32 = weights.iter().sum();
+    if total == 0 {
+        return rng.random_range(0..weights.len());
+    }
+    let mut pick = rng.random_range(0..total);
+    weights.iter().position(|weight| {
+        if pick < *weight {
+            return true;
+        }
+        pick -= weight;
+        false
+    }).unwrap_or(0)
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::{BlackboardTrait, create_blackboard};
+    use crate::language::blackboard_interface::LocalBlackboardInterface;
+    use crate::language::simulator::{Simulator, SimulatorTrait};
+    use crate::model::admin::{AdminCommand, AdminReply};
+
+    fn primitives(agent: &Expr) -> Vec<(&str, &str)> {
+        match agent {
+            BachtAstPrimitive(primitive, token) => vec![(*primitive, &**token)],
+            Expr::BachtAstAgent(_, agents) => agents.iter().flat_map(primitives).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn generator_should_follow_its_configuration_reproducibly() {
+        let mut generator = Generator::new(3).with_tokens(2).with_size(5).with_primitives([0, 1, 0, 0]).with_occurrences(4);
+        let mut replay = Generator::new(3).with_tokens(2).with_size(5).with_primitives([0, 1, 0, 0]).with_occurrences(4);
+        for _ in 0..50 {
+            let agent = generator.agent();
+            assert_eq!(agent, replay.agent());
+            let primitives = primitives(&agent);
+            assert!((1..=5).contains(&primitives.len()));
+            assert!(primitives.iter().all(|(primitive, token)| *primitive == "ask" && ["a", "b"].contains(token)));
+            let store = generator.store();
+            assert_eq!(store, replay.store());
+            assert!(store.iter().all(|(token, occurrences)| ["a", "b"].contains(token) && (1..=4).contains(occurrences)));
+        }
+    }
+
+    #[tokio::test]
+    async fn choice_should_never_execute_both_branches() {
+        let mut generator = Generator::new(11).with_size(6).with_primitives([1, 0, 0, 0]).with_operators([0, 0, 1]);
+        for _ in 0..50 {
+            let blackboard = create_blackboard();
+            let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(blackboard.clone()));
+            assert!(simulator.bacht_exec_all(generator.agent()).await.unwrap());
+            let Ok(AdminReply::Snapshot(tokens)) = blackboard.admin(AdminCommand::Snapshot).await else { panic!() };
+            assert_eq!(tokens.iter().map(|(_, occurrences)| occurrences).sum::<u32>(), 1, "Only one tell should be executed");
+        }
+    }
+}
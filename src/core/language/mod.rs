@@ -0,0 +1,6 @@
+pub mod blackboard_interface;
+pub mod conformance;
+pub mod gen;
+pub mod model;
+pub mod parser;
+pub mod simulator;
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// The BachT AST used to represent agents
+///
+/// @note - The names it holds are owned, shared by the copies of the agent: an agent does not borrow its source, it can
+/// be stored, sent to another task and run later. The primitives and the operators are among a fixed set
+#[derive(Debug, PartialEq, Clone)]
+#[allow(clippy::enum_variant_names)]
+pub enum Expr {
+    BachtAstEmptyAgent(),
+
+    // bacht_ast_primitive(primitive, token),
+    BachtAstPrimitive(&'static str, Arc<str>),
+
+    // bacht_ast_agent(operator, agents), the agents composed by the operator, at least two, flattened: none of them is
+    // a composition by the same operator, e.g. `a;b;c` holds its three agents, see Expr::composition
+    BachtAstAgent(&'static str, Vec<Expr>),
+
+    // bacht_ast_call(name, arguments), an agent defined by name, see Simulator::define, its arguments being empty if
+    // it has no parameter
+    BachtAstCall(Arc<str>, Vec<Term>),
+
+    // bacht_ast_term(primitive, term), a primitive on a structured SI-Term, a flat one being a BachtAstPrimitive
+    BachtAstTerm(&'static str, Term),
+
+    // bacht_ast_replication(agent), `*agent`: as many copies of the agent as needed in parallel, spawned one per step
+    BachtAstReplication(Box<Expr>),
+
+    // bacht_ast_guarded(guard, agent), `guard -> agent`: a branch of a choice, selectable once its primitive executes
+    BachtAstGuarded(Box<Expr>, Box<Expr>)
+}
+
+impl Expr {
+
+    /// @summary - A primitive on a term, a flat token staying a BachtAstPrimitive
+    pub fn primitive_on(primitive: &'static str, term: Term) -> Self {
+        match term {
+            Term::Atom(token) => Expr::BachtAstPrimitive(primitive, token),
+            term => Expr::BachtAstTerm(primitive, term),
+        }
+    }
+
+    /// @summary - The composition of the agents by the operator, the compositions by the same operator among them
+    /// being flattened into it, e.g. `a;(b;c)` composing a, b and c
+    ///
+    /// @returns - The agent itself if there is only one, the empty agent if there is none
+    pub fn composition(operator: &'static str, mut agents: Vec<Expr>) -> Self {
+        // The agents are moved only if one of them is to be flattened, e.g. not the rest of a sequence
+        if agents.iter().any(|agent| matches!(agent, Expr::BachtAstAgent(inner, _) if *inner == operator)) {
+            let mut flattened = Vec::with_capacity(agents.len());
+            for agent in agents {
+                match agent {
+                    Expr::BachtAstAgent(inner, inner_agents) if inner == operator => flattened.extend(inner_agents),
+                    agent => flattened.push(agent),
+                }
+            }
+            agents = flattened;
+        }
+        match agents.len() {
+            0 => Expr::BachtAstEmptyAgent(),
+            1 => agents.pop().unwrap(),
+            _ => Expr::BachtAstAgent(operator, agents),
+        }
+    }
+
+    /// @summary - Replace the parameters of a definition by the arguments of its call, or the variables bound by a
+    /// primitive by the terms it matched, in the primitives and in the arguments of the calls of the agent
+    ///
+    /// @param bindings - The parameters with their argument, or the variables with their term
+    ///
+    /// @note - A token of the agent named as a parameter is replaced as well
+    pub fn substitute(&self, bindings: &[(&str, Term)]) -> Self {
+        match self {
+            Expr::BachtAstEmptyAgent() => Expr::BachtAstEmptyAgent(),
+            Expr::BachtAstPrimitive(primitive, token) => Expr::primitive_on(primitive, Term::Atom(token.clone()).substitute(bindings)),
+            Expr::BachtAstTerm(primitive, term) => Expr::primitive_on(primitive, term.substitute(bindings)),
+            Expr::BachtAstAgent(operator, agents) => Expr::BachtAstAgent(operator, agents.iter().map(|agent| agent.substitute(bindings)).collect()),
+            Expr::BachtAstCall(name, arguments) => Expr::BachtAstCall(name.clone(), arguments.iter().map(|argument| argument.substitute(bindings)).collect()),
+            Expr::BachtAstReplication(agent) => Expr::BachtAstReplication(Box::new(agent.substitute(bindings))),
+            Expr::BachtAstGuarded(guard, agent) => Expr::BachtAstGuarded(Box::new(guard.substitute(bindings)), Box::new(agent.substitute(bindings))),
+        }
+    }
+}
+
+/// An agent defined by name, e.g. `def producer(x) = tell(x);producer(x)`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Definition {
+    pub name: Arc<str>,
+    // Replaced by the arguments of each call, see Expr::substitute
+    pub parameters: Vec<Arc<str>>,
+    pub body: Expr,
+}
+
+/// The structured coordination data of an SI-Term, e.g. `t(a, u(b, c))`
+#[derive(Debug, PartialEq, Clone)]
+pub enum Term {
+    // A flat token, e.g. `a`
+    Atom(Arc<str>),
+
+    // A functor applied to its arguments, e.g. `u(b, c)`
+    Compound(Arc<str>, Vec<Term>),
+
+    // A logic variable, starting with a capital letter, bound by the token the primitive matched, e.g. `X` in
+    // `get(msg(X));tell(ack(X))`
+    Var(Arc<str>),
+}
+
+impl Term {
+
+    /// @summary - Replace the atoms and the variables bound by their term, see Expr::substitute
+    ///
+    /// @note - The parameters are atoms and the variables start with a capital letter, they can't be mistaken
+    pub fn substitute(&self, bindings: &[(&str, Term)]) -> Self {
+        match self {
+            Term::Atom(name) | Term::Var(name) => bindings.iter().find(|(bound, _)| *bound == &**name)
+                .map_or_else(|| self.clone(), |(_, term)| term.clone()),
+            Term::Compound(functor, arguments) => Term::Compound(functor.clone(), arguments.iter().map(|argument| argument.substitute(bindings)).collect()),
+        }
+    }
+
+    /// @returns - The first variable of the term, None if it is ground
+    pub fn variable(&self) -> Option<&str> {
+        match self {
+            Term::Atom(_) => None,
+            Term::Var(name) => Some(name),
+            Term::Compound(_, arguments) => arguments.iter().find_map(Term::variable),
+        }
+    }
+
+    /// @summary - Match the term, with its variables, against a ground term, e.g. a token of the store
+    ///
+    /// @param bindings - The variables already bound, extended with the ones bound by the match
+    ///
+    /// @returns - false if the terms don't match, the bindings being then left partial
+    pub fn unify(&self, ground: &Term, bindings: &mut Vec<(Arc<str>, Term)>) -> bool {
+        match (self, ground) {
+            (Term::Var(name), ground) => match bindings.iter().find(|(bound, _)| bound == name) {
+                Some((_, term)) => term == ground,
+                None => {
+                    bindings.push((name.clone(), ground.clone()));
+                    true
+                },
+            },
+            (Term::Atom(token), Term::Atom(other)) => token == other,
+            (Term::Compound(functor, arguments), Term::Compound(other, others)) => functor == other
+                && arguments.len() == others.len()
+                && arguments.iter().zip(others).all(|(argument, other)| argument.unify(other, bindings)),
+            _ => false,
+        }
+    }
+}
+
+/// Prints an agent back in the BachT syntax, with the parentheses its structure requires, e.g. the continuation of an
+/// agent partially executed
+///
+/// @note - `;` binds tighter than `||`, which binds tighter than `+`
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn precedence(operator: &str) -> u8 {
+            match operator {
+                ";" => 3,
+                "||" => 2,
+                _ => 1,
+            }
+        }
+        match self {
+            Expr::BachtAstEmptyAgent() => f.write_str("skip"),
+            Expr::BachtAstPrimitive(primitive, token) => write!(f, "{}({})", primitive, token),
+            Expr::BachtAstCall(name, arguments) if arguments.is_empty() => f.write_str(name),
+            Expr::BachtAstCall(name, arguments) => write!(f, "{}({})", name, arguments.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")),
+            Expr::BachtAstTerm(primitive, term) => write!(f, "{}({})", primitive, term),
+            Expr::BachtAstReplication(agent) if matches!(agent.as_ref(), Expr::BachtAstAgent(..)) => write!(f, "*({})", agent),
+            Expr::BachtAstReplication(agent) => write!(f, "*{}", agent),
+            // The guarded agent extends up to the next choice
+            Expr::BachtAstGuarded(guard, agent) if matches!(agent.as_ref(), Expr::BachtAstAgent("+", _)) => write!(f, "{}->({})", guard, agent),
+            Expr::BachtAstGuarded(guard, agent) => write!(f, "{}->{}", guard, agent),
+            Expr::BachtAstAgent(operator, agents) => {
+                let outer = precedence(operator);
+                // An operand composed by the same operator is parenthesized as well, the composition not being flattened
+                let grouped = |operand: &Expr| match operand {
+                    Expr::BachtAstAgent(inner, _) if precedence(inner) <= outer => format!("({})", operand),
+                    Expr::BachtAstGuarded(..) if outer > precedence("+") => format!("({})", operand),
+                    operand => operand.to_string(),
+                };
+                f.write_str(&agents.iter().map(grouped).collect::<Vec<_>>().join(operator))
+            },
+        }
+    }
+}
+
+/// @note - The canonical form of the term, without spaces, e.g. `t(a,u(b,c))`: the token the store keeps for it
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Atom(token) | Term::Var(token) => f.write_str(token),
+            Term::Compound(functor, arguments) => {
+                write!(f, "{}(", functor)?;
+                for (i, argument) in arguments.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                f.write_str(")")
+            },
+        }
+    }
+}
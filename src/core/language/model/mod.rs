@@ -1,2 +1 @@
 pub mod data;
-pub mod error;
\ No newline at end of file
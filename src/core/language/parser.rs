@@ -0,0 +1,903 @@
+use nom::{
+    IResult, Parser, Err,
+    error::ErrorKind,
+    sequence::{delimited, preceded, terminated}, bytes::complete::tag,
+    character::complete::multispace0,
+    combinator::{opt, all_consuming, cut},
+    multi::{many0, separated_list1},
+    branch::alt
+};
+use regex::{Regex};
+use std::sync::{Arc, LazyLock};
+
+use crate::error::ParseError;
+use crate::log;
+use crate::log::Level;
+use crate::language::model::data::{Definition, Expr, Term};
+
+/// Parses a token from the input string using a regular expression.
+/// Note that the token must start with a lowercase letter and can contain any number of letters, digits, and underscores.
+/// It must be on the first position of the input string.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<&str>` - A result containing the remaining input and the parsed token,
+///   or an error if the token could not be parsed.
+///
+/// ### Errors
+///
+/// * Returns `Err::Error` if the input does not match the regular expression for a valid token.
+///
+fn token(input: &str) -> Parsed<'_, &str> {
+    TOKEN.find(input).map(
+        |m| (&input[m.end()..], m.as_str())
+    ).ok_or(Err::Error(Syntax { input, expected: "a token" }))
+}
+
+/// Where the parser stopped, and the construct it expected there, e.g. `)` after the term of a primitive
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Syntax<'a> {
+    input: &'a str,
+    expected: &'static str,
+}
+
+type Parsed<'a, O> = IResult<&'a str, O, Syntax<'a>>;
+
+// What an agent may start with
+const AGENT: &str = "an agent: a primitive, a call or a parenthesized agent";
+
+impl<'a> Syntax<'a> {
+    fn error<O>(input: &'a str, expected: &'static str) -> Parsed<'a, O> {
+        Err(Err::Error(Self { input, expected }))
+    }
+
+    // The error of the whole input, positioned where the parser stopped
+    fn within(self, input: &str) -> ParseError {
+        // The constructs are named in words, the symbols are quoted
+        let expected = match self.expected.starts_with(char::is_alphabetic) {
+            true => self.expected.to_string(),
+            false => format!("`{}`", self.expected),
+        };
+        let error = ParseError::new(input, self.input).expecting(&expected);
+        // A comment left open is skipped once closed by the lines following it
+        match self.input.starts_with("/*") && !self.input.contains("*/") {
+            true => error.incomplete(),
+            false => error,
+        }
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for Syntax<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        let expected = match kind {
+            ErrorKind::Eof => "an operator or the end of the agent",
+            _ => AGENT,
+        };
+        Self { input, expected }
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    // The alternative which went the furthest tells the most
+    fn or(self, other: Self) -> Self {
+        if other.input.len() <= self.input.len() { other } else { self }
+    }
+}
+
+// How deeply the agents and the terms may nest, e.g. within parentheses, the parser recursing at each level
+pub const MAX_DEPTH: usize = 64;
+
+// What a construct nested deeper than MAX_DEPTH is refused with
+const NESTED: &str = "an agent or a term nested less deeply";
+
+// The depth of the level entered at the input, failing past MAX_DEPTH rather than exhausting the stack
+fn deeper(depth: usize, input: &str) -> Result<usize, Err<Syntax<'_>>> {
+    match depth < MAX_DEPTH {
+        true => Ok(depth + 1),
+        false => Err(Err::Failure(Syntax { input, expected: NESTED })),
+    }
+}
+
+/// Replaces what a parser expected on error, e.g. to name the construct a regular expression stands for.
+///
+/// ### Arguments
+///
+/// * `expected` - The construct expected where the parser fails, e.g. "a term".
+/// * `parser` - The parser whose failures are renamed.
+///
+/// ### Returns
+///
+/// * `impl FnMut(&str) -> Parsed<O>` - The parser, erring with the construct expected.
+///
+fn expecting<'a, O>(expected: &'static str, mut parser: impl Parser<&'a str, Output = O, Error = Syntax<'a>>) -> impl FnMut(&'a str) -> Parsed<'a, O> {
+    move |input| parser.parse(input).map_err(|e| match e {
+        // The failures past a commitment already tell what they expected
+        Err::Error(e) => Err::Error(Syntax { expected, ..e }),
+        e => e,
+    })
+}
+
+/// Parses a fixed piece of syntax, e.g. an operator or a parenthesis, expected under its own name on failure.
+fn symbol<'a>(symbol: &'static str) -> impl FnMut(&'a str) -> Parsed<'a, &'a str> {
+    move |input| tag(symbol).parse(input).or_else(|_: Err<Syntax>| Syntax::error(input, symbol))
+}
+
+// A comment, to the end of the line or between `/*` and `*/`
+const COMMENT: &str = r"//[^\n]*|/\*(?s:.*?)\*/";
+
+// The expressions are compiled once, the parser trying them at each agent
+static TOKEN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z][a-zA-Z0-9_]*").unwrap());
+static VARIABLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z][a-zA-Z0-9_]*").unwrap());
+static BLANK: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!(r"^(?:\s|{})*", COMMENT)).unwrap());
+static COMMENTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(COMMENT).unwrap());
+
+/// Skips the whitespace and the comments from the input string, e.g. `// a note` up to the end of the line,
+/// or `/* a note */` spanning any number of lines.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<&str>` - A result containing the remaining input and what was skipped, possibly nothing.
+///
+fn blank(input: &str) -> Parsed<'_, &str> {
+    let skipped = BLANK.find(input).map_or(0, |m| m.end());
+    Ok((&input[skipped..], &input[..skipped]))
+}
+
+/// Removes the comments of an agent, e.g. to tell whether the lines typed so far form a complete agent.
+/// A block comment left open is kept, the agent being incomplete until it is closed.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the agent, with its comments.
+///
+/// ### Returns
+///
+/// * `String` - The agent without its comments.
+pub fn strip_comments(input: &str) -> String {
+    COMMENTS.replace_all(input, "").into_owned()
+}
+
+/// Parses a logic variable from the input string, bound by the token a primitive matches.
+/// Note that the variable must start with a capital letter, which tells it apart from a token.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<&str>` - A result containing the remaining input and the name of the variable,
+///   or an error if the input does not start with a capital letter.
+///
+fn variable(input: &str) -> Parsed<'_, &str> {
+    VARIABLE.find(input).map(
+        |m| (&input[m.end()..], m.as_str())
+    ).ok_or(Err::Error(Syntax { input, expected: "a variable" }))
+}
+
+/// Parses the SI-Term a primitive is applied on: a token, a variable, or a functor followed by its arguments in
+/// parentheses, separated by commas, e.g. `t(a, u(X, c))`.
+/// The arguments may be surrounded by spaces, the functor having the syntax of a token.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Term>` - A result containing the remaining input and the parsed term,
+///   or an error if the input does not start with a token, or if the arguments of a functor do not parse.
+///
+fn term(input: &str) -> Parsed<'_, Term> {
+    nested_term(0, input)
+}
+
+// The term nested in the arguments of depth functors, failing past MAX_DEPTH
+fn nested_term(depth: usize, input: &str) -> Parsed<'_, Term> {
+    if let Ok((next_input, name)) = variable(input) {
+        return Ok((next_input, Term::Var(name.into())));
+    }
+    let (next_input, functor) = expecting("a term", token)(input)?;
+    let Ok((arguments_input, _)) = symbol("(")(next_input) else {
+        return Ok((next_input, Term::Atom(functor.into())));
+    };
+    let depth = deeper(depth, arguments_input)?;
+    // A functor is committed to its arguments once its parenthesis is opened, and to a next argument after a comma
+    let argument = || delimited(multispace0, move |i| nested_term(depth, i), multispace0);
+    let arguments = (argument(), many0(preceded(symbol(","), cut(argument())))).map(|(first, others)| [vec![first], others].concat());
+    cut(terminated(arguments, symbol(")"))).parse(arguments_input).map(
+        |(next_input, arguments)| (next_input, Term::Compound(functor.into(), arguments))
+    )
+}
+
+
+/// Parses a primitive expression from the input string.
+///
+/// This function attempts to parse one of the following primitives: `tell`, `ask`, `get`, or `nask`.
+/// Each primitive is expected to be followed by a token, or an SI-Term, enclosed in parentheses.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the parsed expression,
+///   or an error if none of the primitives could be parsed, a failure if the term of the primitive does not parse.
+///
+fn primitive(input: &str) -> Parsed<'_, Expr> {
+    applied(input, &[("tell(", "tell"), ("ask(", "ask"), ("get(", "get"), ("nask(", "nask")])
+}
+
+// The first primitive the input starts with, committed to its term once its name is read
+fn applied<'a>(input: &'a str, primitives: &[(&str, &'static str)]) -> Parsed<'a, Expr> {
+    match primitives.iter().find(|(name, _)| input.starts_with(name)) {
+        Some((name, primitive)) => cut(terminated(term, symbol(")"))).parse(&input[name.len()..]).map(
+            |(next_input, term)| (next_input, Expr::primitive_on(primitive, term))
+        ),
+        None => Syntax::error(input, AGENT),
+    }
+}
+
+/// Parses a primitive of the Linda dialect from the input string.
+///
+/// `out`, `rd` and `in` are the aliases of `tell`, `ask` and `get`, while `inp` and `rdp` are kept as primitives
+/// of their own, the non-blocking variants of `get` and `ask`: they never block the agent, whether the token
+/// is on the blackboard or not.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the parsed expression,
+///   or an error if none of the Linda primitives could be parsed, a failure if the term of the primitive does not parse.
+///
+fn linda_primitive(input: &str) -> Parsed<'_, Expr> {
+    applied(input, &[("out(", "tell"), ("rd(", "ask"), ("inp(", "inp"), ("in(", "get"), ("rdp(", "rdp")])
+}
+
+/// The grammars accepted by the parser
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// The primitives `tell`, `ask`, `get` and `nask`
+    #[default]
+    BachT,
+    /// The primitives of BachT, along with the ones of Linda (`out`, `rd`, `in`, `inp` and `rdp`), easing the migration
+    /// of the examples written for Linda
+    Linda,
+}
+
+/// Parses an agent expression from the input string.
+/// It handles the following operators: `;`, `||`, `+`, the guards `->` of the choices and the replication `*`,
+/// and skips the comments between the agents.
+///
+/// ### Arguments
+///
+/// * `dialect` - The grammar of the agent, e.g. Dialect::Linda to accept the Linda primitives.
+/// * `depth` - How deeply the agent is nested, e.g. in parentheses, up to MAX_DEPTH.
+/// * `input` - A string slice that holds the agent to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the parsed agent expression,
+///   or an error if the input could not be parsed as an agent expression, a failure past an operator.
+///
+/// ### Exemples
+///
+/// `agent(tell(token1);tell(token2)||tell(token3)+tell(token4))`
+///
+///  ̀``Expr::BachtAstAgent("+", vec![
+///       Expr::BachtAstAgent("||", vec![
+///           Expr::BachtAstAgent(";", vec![
+///               Expr::BachtAstPrimitive("tell", "token1".into()),
+///               Expr::BachtAstPrimitive("tell", "token2".into())
+///           ]),
+///           Expr::BachtAstPrimitive("tell", "token3".into())
+///       ]),
+///       Expr::BachtAstPrimitive("tell", "token4".into())
+///  ])```
+fn agent(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> { composition_choice(dialect, depth, input) }
+
+// An operator is always followed by an agent, the agents it composes being collected in a loop rather than by
+// recursion, so that long chains do not exhaust the stack
+fn composition<'a>(operator: &'static str, mut operand: impl FnMut(&'a str) -> Parsed<'a, Expr>, input: &'a str) -> Parsed<'a, Expr> {
+    let (mut next_input, first) = operand(input)?;
+    let mut agents = vec![first];
+    while let Ok((rest, _)) = symbol(operator)(next_input) {
+        let (rest, agent) = cut(&mut operand).parse(rest)?;
+        agents.push(agent);
+        next_input = rest;
+    }
+    Ok((next_input, Expr::composition(operator, agents)))
+}
+
+fn composition_choice(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    composition("+", |i| alt((|i| guarded(dialect, depth, i), |i| composition_para(dialect, depth, i))).parse(i), input)
+}
+
+/// Parses a guarded branch of a choice, a primitive followed by `->` and the agent it guards, e.g.
+/// `ask(a) -> tell(b);tell(c) + ask(d) -> tell(e)`. The agent guarded extends up to the next `+`.
+///
+/// ### Arguments
+///
+/// * `dialect` - The grammar of the guard and of the agent.
+/// * `depth` - How deeply the guarded agent is nested.
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the guarded agent,
+///   or an error if the input does not start with a primitive followed by `->`, a failure if no agent follows it.
+fn guarded(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    let guard = |i| match dialect {
+        Dialect::Linda => alt((primitive, linda_primitive)).parse(i),
+        Dialect::BachT => primitive(i),
+    };
+    (delimited(blank, guard, blank), symbol("->"), cut(|i| composition_para(dialect, depth, i))).parse(input).map(
+        |(next_input, (guard, _, agent))| (next_input, Expr::BachtAstGuarded(Box::new(guard), Box::new(agent)))
+    )
+}
+
+fn composition_para(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    composition("||", |i| composition_seq(dialect, depth, i), input)
+}
+
+fn composition_seq(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    // The comments around the agents are skipped, along with the whitespace
+    composition(";", |i| delimited(blank, |i| simple_agent(dialect, depth, i), blank).parse(i), input)
+}
+
+fn simple_agent(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    let linda = |i| match dialect {
+        Dialect::Linda => linda_primitive(i),
+        Dialect::BachT => Syntax::error(i, AGENT),
+    };
+    expecting(AGENT, alt((primitive, linda, |i| parenthesized_agent(dialect, depth, i), |i| replication(dialect, depth, i), empty_agent, call)))(input)
+}
+
+/// Parses the empty agent, the keyword `skip`, e.g. `ask(x);skip + tell(y)`.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the empty agent,
+///   or an error if the input does not start with `skip`.
+fn empty_agent(input: &str) -> Parsed<'_, Expr> {
+    match token(input)? {
+        (next_input, "skip") => Ok((next_input, Expr::BachtAstEmptyAgent())),
+        _ => Syntax::error(input, AGENT),
+    }
+}
+
+/// Parses the replication of an agent, `*` followed by the agent to replicate, e.g. `*(get(job);tell(done))`.
+/// It binds tighter than the operators: `*get(job);tell(done)` replicates the get alone.
+///
+/// ### Arguments
+///
+/// * `dialect` - The grammar of the agent replicated.
+/// * `depth` - How deeply the replication is nested, the agent replicated being one level deeper.
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the replication,
+///   or an error if the input does not start with `*`, a failure if no agent follows it or if it nests too deeply.
+fn replication(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    let (next_input, _) = symbol("*")(input)?;
+    let depth = deeper(depth, next_input)?;
+    cut(preceded(blank, move |i| simple_agent(dialect, depth, i))).parse(next_input).map(
+        |(next_input, agent)| (next_input, Expr::BachtAstReplication(Box::new(agent)))
+    )
+}
+
+// The primitives of the dialects, which a call can't be mistaken for
+const RESERVED: [&str; 9] = ["tell", "ask", "get", "nask", "out", "rd", "in", "inp", "rdp"];
+
+/// Parses the call of a defined agent, its name having the syntax of a token, followed by its arguments in
+/// parentheses if it has parameters, e.g. `producer(item)`.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the input to be parsed.
+///
+/// ### Returns
+///
+/// * `Parsed<Expr>` - A result containing the remaining input and the call,
+///   or an error if the input does not start with a name.
+fn call(input: &str) -> Parsed<'_, Expr> {
+    match term(input)? {
+        // A primitive of another dialect is not an agent to call
+        (_, Term::Compound(name, _)) if RESERVED.contains(&&*name) => Syntax::error(input, AGENT),
+        (_, Term::Var(_)) => Syntax::error(input, AGENT),
+        (next_input, Term::Atom(name)) => Ok((next_input, Expr::BachtAstCall(name, Vec::new()))),
+        (next_input, Term::Compound(name, arguments)) => Ok((next_input, Expr::BachtAstCall(name, arguments))),
+    }
+}
+
+// The agent within parentheses, one level deeper than the parentheses
+fn parenthesized_agent(dialect: Dialect, depth: usize, input: &str) -> Parsed<'_, Expr> {
+    let (next_input, _) = symbol("(")(input)?;
+    let depth = deeper(depth, next_input)?;
+    cut(terminated(move |i| agent(dialect, depth, i), symbol(")"))).parse(next_input)
+}
+
+
+/// Parses an agent expression from the input string.
+///
+/// This function serves as the entry point for parsing an agent expression,
+/// delegating the actual parsing to the `agent` function and ensuring that the entire input is consumed.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the agent to be parsed.
+/// * `dialect` - The grammar of the agent.
+///
+/// ### Returns
+///
+/// * `Result<Expr, ParseError>` - A result containing the parsed agent expression,
+///   or where the input stops being an agent expression, with the construct expected there.
+///
+/// ### Errors
+///
+/// * Returns the ParseError if the input could not be parsed as an agent expression or if the entire input was not consumed.
+pub(crate) fn parse_agent(input: &str, dialect: Dialect) -> Result<Expr, ParseError> {
+    all_consuming(|i| agent(dialect, 0, i)).parse(input).map(|(_, expr)| expr).map_err(|e| match e {
+        Err::Error(e) | Err::Failure(e) => e.within(input),
+        Err::Incomplete(_) => ParseError::new(input, "").expecting(AGENT),
+    })
+}
+
+/// Parses the head of a definition: its name, followed by its parameters in parentheses if it has some, e.g.
+/// `producer(x, y)`.
+fn head(input: &str) -> Parsed<'_, (&str, Vec<&str>)> {
+    let parameters = separated_list1(symbol(","), delimited(multispace0, expecting("a parameter", token), multispace0));
+    (expecting("a name", token), opt(preceded(symbol("("), cut(terminated(parameters, symbol(")")))))).parse(input).map(
+        |(next_input, (name, parameters))| (next_input, (name, parameters.unwrap_or_default()))
+    )
+}
+
+/// Parses the head of a definition, see Simulator::define.
+///
+/// ### Returns
+///
+/// * `Result<(&str, Vec<&str>), ParseError>` - The name and the parameters, or where the head stops being valid,
+///   e.g. on a parameter given twice.
+pub(crate) fn parse_head(input: &str) -> Result<(&str, Vec<&str>), ParseError> {
+    let (name, parameters) = all_consuming(head).parse(input).map(|(_, head)| head).map_err(|e| match e {
+        Err::Error(e) | Err::Failure(e) => e.within(input),
+        Err::Incomplete(_) => ParseError::new(input, ""),
+    })?;
+    match parameters.iter().enumerate().find(|(i, parameter)| parameters[..*i].contains(parameter)) {
+        Some((_, repeated)) => Err(ParseError::new(input, rest_from(input, repeated, 0)).expecting("a parameter not given yet")),
+        None => Ok((name, parameters)),
+    }
+}
+
+// The input left from a position in a part of it
+fn rest_from<'a>(input: &'a str, part: &str, position: usize) -> &'a str {
+    &input[part.as_ptr() as usize - input.as_ptr() as usize + position..]
+}
+
+/// Parses the definition of an agent, e.g. `def producer(x) = tell(x);producer(x)`.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the definition, `def` followed by its head, an equal sign and its body.
+/// * `dialect` - The grammar of the body.
+///
+/// ### Returns
+///
+/// * `Result<Definition, ParseError>` - The definition, or where it stops being valid.
+pub fn parse_definition(input: &str, dialect: Dialect) -> Result<Definition, ParseError> {
+    let error = |part: &str, position: usize| ParseError::new(input, rest_from(input, part, position));
+    // The error of a part, positioned in the whole definition
+    let within = |part: &str, e: ParseError| ParseError { kind: e.kind, expected: e.expected, ..error(part, e.position) };
+    let definition = input.strip_prefix("def ").ok_or_else(|| error(input, 0).expecting("`def `"))?;
+    let (head, body) = definition.split_once('=').ok_or_else(|| error(input, input.len()).expecting("`=`"))?;
+    let (head, body) = (head.trim(), body.trim());
+    let (name, parameters) = parse_head(head).map_err(|e| within(head, e))?;
+    let body = parse_with(body, dialect).map_err(|e| within(body, e))?;
+    Ok(Definition { name: name.into(), parameters: parameters.into_iter().map(Arc::from).collect(), body })
+}
+
+/// Parses an SI-Term on its own, e.g. a token of the store matched by a primitive with variables.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the term, e.g. `msg(hello)`.
+///
+/// ### Returns
+///
+/// * `Result<Term, ParseError>` - The term, or where it stops being valid.
+pub fn parse_term(input: &str) -> Result<Term, ParseError> {
+    all_consuming(term).parse(input).map(|(_, term)| term).map_err(|e| match e {
+        Err::Error(e) | Err::Failure(e) => e.within(input),
+        Err::Incomplete(_) => ParseError::new(input, ""),
+    })
+}
+
+/// Parses a BachT program, e.g. a program received by a blackboard node.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the program to be parsed.
+///
+/// ### Returns
+///
+/// * `Result<Expr, ParseError>` - The agent of the program, or where the program stops being a valid agent,
+///   e.g. where it nests deeper than MAX_DEPTH.
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    parse_with(input, Dialect::BachT)
+}
+
+/// Parses a BachT program written in a dialect, e.g. a Linda example using `out` and `in`.
+///
+/// ### Arguments
+///
+/// * `input` - A string slice that holds the program to be parsed.
+/// * `dialect` - The grammar of the program.
+///
+/// ### Returns
+///
+/// * `Result<Expr, ParseError>` - The agent of the program, its Linda primitives mapped onto the BachT ones,
+///   or where the program stops being a valid agent.
+#[tracing::instrument(name = "parse", level = "trace", skip(input), fields(length = input.len()))]
+pub fn parse_with(input: &str, dialect: Dialect) -> Result<Expr, ParseError> {
+    let agent = parse_agent(input, dialect);
+    match &agent {
+        Ok(_) => log!(Level::Trace, "Parsed `{}`", input),
+        Err(e) => log!(Level::Trace, "Rejected `{}`: {}", input, e),
+    }
+    agent
+}
+
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseErrorKind;
+
+    // Primitive section
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_tell_primitive() {
+        let res = primitive("tell(token)");
+        assert_eq!(res, Ok(("", Expr::BachtAstPrimitive("tell", "token".into()))));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_an_ask_primitive() {
+        let res = primitive("ask(token)");
+        assert_eq!(res, Ok(("", Expr::BachtAstPrimitive("ask", "token".into()))));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_get_primitive() {
+        let res = primitive("get(token)");
+        assert_eq!(res, Ok(("", Expr::BachtAstPrimitive("get", "token".into()))));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_nask_primitive() {
+        let res = primitive("nask(token)");
+        assert_eq!(res, Ok(("", Expr::BachtAstPrimitive("nask", "token".into()))));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_primitive_on_an_si_term() {
+        let res = primitive("tell(t(a, u(b,c)))");
+        assert_eq!(res, Ok(("", Expr::BachtAstTerm("tell", Term::Compound("t".into(), vec![
+            Term::Atom("a".into()),
+            Term::Compound("u".into(), vec![Term::Atom("b".into()), Term::Atom("c".into())])
+        ])))));
+        assert_eq!(term("t(a, u(b, c))").unwrap().1.to_string(), "t(a,u(b,c))");
+        assert_eq!(parse_with("in(msg( hello ))", Dialect::Linda), Ok(Expr::BachtAstTerm("get", Term::Compound("msg".into(), vec![Term::Atom("hello".into())]))));
+        assert!(primitive("tell(t())").is_err());
+        assert!(primitive("tell(t(a,))").is_err());
+        assert!(primitive("tell(t(1a))").is_err());
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_the_variables_of_a_term() {
+        assert_eq!(parse("get(msg(X));tell(ack(X))"), Ok(Expr::BachtAstAgent(";", vec![
+            Expr::BachtAstTerm("get", Term::Compound("msg".into(), vec![Term::Var("X".into())])),
+            Expr::BachtAstTerm("tell", Term::Compound("ack".into(), vec![Term::Var("X".into())]))
+        ])));
+        assert_eq!(parse("ask(Item)"), Ok(Expr::BachtAstTerm("ask", Term::Var("Item".into()))));
+        assert_eq!(parse_term("msg(a, X)"), Ok(Term::Compound("msg".into(), vec![Term::Atom("a".into()), Term::Var("X".into())])));
+        assert!(parse("tell(X(a))").is_err());
+        assert!(parse_term("msg(a)@").is_err());
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_the_replication_operator() {
+        let job = || Expr::BachtAstPrimitive("get", "job".into());
+        assert_eq!(parse("*(get(job);tell(done))||tell(job)"), Ok(Expr::BachtAstAgent("||", vec![
+            Expr::BachtAstReplication(Box::new(Expr::BachtAstAgent(";", vec![job(), Expr::BachtAstPrimitive("tell", "done".into())]))),
+            Expr::BachtAstPrimitive("tell", "job".into())
+        ])));
+        assert_eq!(parse("*get(job);tell(done)"), Ok(Expr::BachtAstAgent(";", vec![
+            Expr::BachtAstReplication(Box::new(job())),
+            Expr::BachtAstPrimitive("tell", "done".into())
+        ])));
+        assert_eq!(parse("**get(job)"), Ok(Expr::BachtAstReplication(Box::new(Expr::BachtAstReplication(Box::new(job()))))));
+        assert_eq!(parse("tell(a);*").unwrap_err().expected.as_deref(), Some(AGENT));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_guarded_choice() {
+        let res = parse("ask(a) -> tell(b);tell(c) + ask(d)->tell(e)||tell(f)");
+        assert_eq!(res, Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstGuarded(Box::new(Expr::BachtAstPrimitive("ask", "a".into())), Box::new(Expr::BachtAstAgent(";", vec![
+                Expr::BachtAstPrimitive("tell", "b".into()),
+                Expr::BachtAstPrimitive("tell", "c".into())
+            ]))),
+            Expr::BachtAstGuarded(Box::new(Expr::BachtAstPrimitive("ask", "d".into())), Box::new(Expr::BachtAstAgent("||", vec![
+                Expr::BachtAstPrimitive("tell", "e".into()),
+                Expr::BachtAstPrimitive("tell", "f".into())
+            ])))
+        ])));
+        assert_eq!(res.unwrap().to_string(), "ask(a)->tell(b);tell(c)+ask(d)->tell(e)||tell(f)");
+        assert_eq!(parse("(ask(a)->tell(b));tell(c)").unwrap().to_string(), "(ask(a)->tell(b));tell(c)");
+        assert_eq!(parse_with("rd(a)->in(b)", Dialect::Linda), parse("ask(a)->get(b)"));
+        assert_eq!(parse("ask(a)->").unwrap_err().expected.as_deref(), Some(AGENT));
+        assert!(parse("tell(a);ask(b)->tell(c)").is_err());
+    }
+
+    #[test]
+    fn the_parser_should_skip_the_comments() {
+        let expected = parse("tell(a);(get(a)+ask(b))").unwrap();
+        assert_eq!(parse("// produce\ntell(a); // then consume\n(get(a) /* or only read */ + ask(b))"), Ok(expected.clone()));
+        assert_eq!(parse_agent("/* a\n * b */tell(a);(get(a)+ask(b))/**/", Dialect::BachT), Ok(expected));
+        assert_eq!(parse("tell(a) /* never closed").unwrap_err().rest, "/* never closed");
+        assert!(parse("// only a comment").is_err());
+        assert_eq!(strip_comments("tell(a); // b\n/* c */tell(d) /* e"), "tell(a); \ntell(d) /* e");
+    }
+
+    #[test]
+    fn the_parser_should_refuse_hallucinate_primitives() {
+        let res = primitive("non(token)");
+        assert!(matches!(res, Err(Err::Error(_))));
+    }
+
+    /// Token Section
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_token() {
+        let res = token("token");
+        assert_eq!(res, Ok(("", "token")));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_token_with_capital_character_and_number() {
+        let res = token("tOkEN12E");
+        assert_eq!(res, Ok(("", "tOkEN12E")));
+    }
+
+    #[test]
+    fn the_parser_should_refuse_token_with_special_character() {
+        let res = primitive("tell(tOkEN12E@)");
+        assert_eq!(res, Err(Err::Failure(Syntax { input: "@)", expected: ")" })));
+    }
+
+    #[test]
+    fn the_parser_should_refuse_token_with_first_character_as_number() {
+        let res = token("7oken");
+        assert_eq!(res, Err(Err::Error(Syntax { input: "7oken", expected: "a token" })));
+    }
+
+    #[test]
+    fn the_parser_should_refuse_token_with_first_character_as_capitals() {
+        let res = token("Token");
+        assert_eq!(res, Err(Err::Error(Syntax { input: "Token", expected: "a token" })));
+    }
+
+    // Agent section
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_an_empty_agent() {
+        let res = parse_agent("skip", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstEmptyAgent()));
+        let res = parse_agent("ask(x);skip + tell(y)", Dialect::BachT);
+        let expect_res = Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstAgent(";", vec![Expr::BachtAstPrimitive("ask", "x".into()), Expr::BachtAstEmptyAgent()]),
+            Expr::BachtAstPrimitive("tell", "y".into())
+        ]));
+        assert_eq!(res, expect_res);
+        // A name starting with the keyword is a call
+        assert_eq!(parse_agent("skipper", Dialect::BachT), Ok(Expr::BachtAstCall("skipper".into(), Vec::new())));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_simple_agent() {
+        let res = parse_agent("tell(token)", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstPrimitive("tell", "token".into())));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_simple_agent_in_brackets() {
+        let res = parse_agent("(tell(token))", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstPrimitive("tell", "token".into())));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_sequence_operator() {
+        let res = parse_agent("tell(token1);tell(token2)", Dialect::BachT);
+        let expect_res = Ok(Expr::BachtAstAgent(";", vec![
+            Expr::BachtAstPrimitive("tell", "token1".into()),
+            Expr::BachtAstPrimitive("tell", "token2".into())
+        ]));
+        assert_eq!(res, expect_res);
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_parallel_operator() {
+        let res = parse_agent("tell(token1)||tell(token2)", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstAgent("||", vec![
+            Expr::BachtAstPrimitive("tell", "token1".into()),
+            Expr::BachtAstPrimitive("tell", "token2".into())
+        ])));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_choice_operator() {
+        let res = parse_agent("tell(token1)+tell(token2)", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstPrimitive("tell", "token1".into()),
+            Expr::BachtAstPrimitive("tell", "token2".into())
+        ])));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_multiple_operators() {
+        let res = parse_agent("tell(token1)||tell(token2)||tell(token3)", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstAgent("||", vec![
+            Expr::BachtAstPrimitive("tell", "token1".into()),
+            Expr::BachtAstPrimitive("tell", "token2".into()),
+            Expr::BachtAstPrimitive("tell", "token3".into())
+        ])));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_nested_operators() {
+        let res1 = parse_agent("tell(token1);tell(token2)||tell(token3)+tell(token4)", Dialect::BachT);
+        assert_eq!(res1, Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstAgent("||", vec![
+                Expr::BachtAstAgent(";", vec![
+                    Expr::BachtAstPrimitive("tell", "token1".into()),
+                    Expr::BachtAstPrimitive("tell", "token2".into())
+                ]),
+                Expr::BachtAstPrimitive("tell", "token3".into())
+            ]),
+            Expr::BachtAstPrimitive("tell", "token4".into())
+        ])));
+
+        let res2 = parse_agent("tell(token1)+tell(token2)||tell(token3);tell(token4)", Dialect::BachT);
+        assert_eq!(res2, Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstPrimitive("tell", "token1".into()),
+            Expr::BachtAstAgent("||", vec![
+                Expr::BachtAstPrimitive("tell", "token2".into()),
+                Expr::BachtAstAgent(";", vec![
+                    Expr::BachtAstPrimitive("tell", "token3".into()),
+                    Expr::BachtAstPrimitive("tell", "token4".into())
+                ])
+            ])
+        ])));
+    }
+
+    #[test]
+    #[allow(clippy::redundant_pattern_matching)]
+    fn the_parser_should_refuse_hallucinate_operator() {
+        let res = parse_agent("tell(token1)??tell(token2)", Dialect::BachT);
+        assert!(matches!(res, Err(_)));
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_the_calls_of_defined_agents() {
+        let res = parse_agent("tell(item);producer+(consumer)", Dialect::BachT);
+        assert_eq!(res, Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstAgent(";", vec![
+                Expr::BachtAstPrimitive("tell", "item".into()),
+                Expr::BachtAstCall("producer".into(), Vec::new())
+            ]),
+            Expr::BachtAstCall("consumer".into(), Vec::new())
+        ])));
+        assert!(parse_agent("Item", Dialect::BachT).is_err());
+    }
+
+    #[test]
+    fn the_parser_should_be_able_to_parse_a_definition_with_parameters() {
+        let res = parse_definition("def producer(x, y) = tell(t(x,y));producer(x, y)", Dialect::BachT);
+        assert_eq!(res, Ok(Definition { name: "producer".into(), parameters: vec!["x".into(), "y".into()], body: Expr::BachtAstAgent(";", vec![
+            Expr::BachtAstTerm("tell", Term::Compound("t".into(), vec![Term::Atom("x".into()), Term::Atom("y".into())])),
+            Expr::BachtAstCall("producer".into(), vec![Term::Atom("x".into()), Term::Atom("y".into())])
+        ])}));
+        assert_eq!(parse_definition("def loop = loop", Dialect::BachT).unwrap().parameters, Vec::<Arc<str>>::new());
+        assert_eq!(parse_definition("def p(x, x) = tell(x)", Dialect::BachT).unwrap_err().rest, "x) = tell(x)");
+        assert_eq!(parse_definition("def p(t(x)) = tell(x)", Dialect::BachT).unwrap_err().position, 7);
+        assert_eq!(parse_definition("def p(t(x)) = tell(x)", Dialect::BachT).unwrap_err().expected.as_deref(), Some("`)`"));
+        assert_eq!(parse_definition("def p(x) = tell(x)@", Dialect::BachT).unwrap_err().rest, "@");
+        assert!(parse_definition("p(x) = tell(x)", Dialect::BachT).is_err());
+    }
+
+    #[test]
+    fn the_parser_should_map_the_linda_primitives_in_the_linda_dialect_only() {
+        assert_eq!(parse_with("out(a);(rd(a)||in(a))+inp(b);rdp(c)", Dialect::Linda), Ok(Expr::BachtAstAgent("+", vec![
+            Expr::BachtAstAgent(";", vec![
+                Expr::BachtAstPrimitive("tell", "a".into()),
+                Expr::BachtAstAgent("||", vec![
+                    Expr::BachtAstPrimitive("ask", "a".into()),
+                    Expr::BachtAstPrimitive("get", "a".into())
+                ])
+            ]),
+            Expr::BachtAstAgent(";", vec![
+                Expr::BachtAstPrimitive("inp", "b".into()),
+                Expr::BachtAstPrimitive("rdp", "c".into())
+            ])
+        ])));
+        assert_eq!(parse_with("tell(a);nask(b)", Dialect::Linda), parse("tell(a);nask(b)"));
+        assert!(parse("out(a)").is_err());
+        assert!(parse_with("inp(1a)", Dialect::Linda).is_err());
+    }
+
+    #[test]
+    fn the_parser_should_tell_where_and_what_it_expected() {
+        let expected = |input: &str| parse(input).map(|_| ()).map_err(|e| (e.line, e.column, e.expected.unwrap()));
+        assert_eq!(expected("tell(a);get("), Err((1, 13, "a term".to_string())));
+        assert_eq!(expected("tell(a);\n(get(a)+ask(b)"), Err((2, 15, "`)`".to_string())));
+        assert_eq!(expected("tell(t(a,))"), Err((1, 10, "a term".to_string())));
+        assert_eq!(expected("tell(a)||"), Err((1, 10, AGENT.to_string())));
+        assert_eq!(expected("tell(a) ?? tell(b)"), Err((1, 9, "an operator or the end of the agent".to_string())));
+        assert_eq!(parse("tell(a);\n  get(1)").unwrap_err().diagnostic(), "unexpected `1)` at 2:7, expected a term\n  get(1)\n      ^");
+    }
+
+    #[test]
+    fn the_parser_should_tell_the_agents_completed_by_more_lines_from_the_invalid_ones() {
+        for incomplete in ["tell(a);", "(tell(a)\n||get(b)", "tell(t(a,", "*", "ask(a) ->", "tell(a) /* more", "def p(x) = tell(x)+"] {
+            let kind = match incomplete.starts_with("def ") {
+                true => parse_definition(incomplete, Dialect::BachT).unwrap_err().kind,
+                false => parse(incomplete).unwrap_err().kind,
+            };
+            assert_eq!(kind, ParseErrorKind::Incomplete, "{}", incomplete);
+        }
+        for invalid in ["tell(a))", "tell(a);@", "tell(a) /* closed */ ?"] {
+            assert_eq!(parse(invalid).unwrap_err().kind, ParseErrorKind::Invalid, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn the_parser_should_refuse_the_agents_and_the_terms_nested_too_deeply() {
+        let nested = |open: &str, inner: &str, close: &str, depth: usize| format!("{}{}{}", open.repeat(depth), inner, close.repeat(depth));
+        assert!(parse(&nested("(", &nested("tell(", "a", ")", MAX_DEPTH), ")", MAX_DEPTH)).is_ok());
+        assert!(parse(&nested("*", "tell(a)", "", MAX_DEPTH)).is_ok());
+        assert!(parse(&nested("(", "tell(a)", ")", 10_000)).is_err());
+        assert!(parse(&nested("*", "tell(a)", "", 10_000)).is_err());
+        assert!(parse(&nested("t(", "a", ")", 10_000).replacen("t(", "tell(", 1)).is_err());
+        assert!(parse_term(&nested("t(", "a", ")", 10_000)).is_err());
+        assert_eq!(parse(&nested("(", "tell(a)", ")", 10_000)).unwrap_err().expected, Some(NESTED.to_string()));
+    }
+
+    #[test]
+    #[allow(clippy::redundant_pattern_matching)]
+    fn the_parser_should_refuse_hallucinate_token() {
+        let res = parse_agent("tell(token1)@", Dialect::BachT);
+        assert!(matches!(res, Err(_)));
+    }
+}
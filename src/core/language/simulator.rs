@@ -0,0 +1,889 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use crate::language::blackboard_interface::BlackboardInterfaceTrait;
+use crate::error::Error;
+use crate::language::model::data::{Expr, Term};
+use crate::language::model::data::Expr::*;
+use crate::language::parser::{Dialect, parse_head, parse_term, parse_with};
+use crate::log;
+use crate::log::Level;
+
+/// One transition of an agent: the primitive it executed, and what remains to run
+#[derive(Debug, PartialEq)]
+pub struct Transition {
+    /// The primitive and its token
+    pub executed: (String, String),
+    /// The empty agent once the agent terminated
+    pub continuation: Expr,
+}
+
+/// A primitive attempted by an agent, reported to the tracer of the simulator
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub primitive: String,
+    pub token: String,
+    /// false if the primitive could not be executed yet, e.g. a get of an absent token
+    pub result: bool,
+    pub at: Instant,
+}
+
+/// Called with each primitive attempted, see Simulator::set_tracer
+pub type Tracer = Box<dyn Fn(&TraceEntry) + Send + Sync>;
+
+// The calls unfolded before a primitive is reached, beyond which a definition is considered as unguarded
+const MAX_UNFOLDING: usize = 100;
+
+/// The transitions bacht_exec_all executes at most before giving up on an agent, see Simulator::set_max_steps
+pub const DEFAULT_MAX_STEPS: usize = 100_000;
+
+pub trait SimulatorTrait {
+    fn new() -> Self;
+    
+    fn run_one(&self, agent: Expr) -> impl Future<Output=Result<(bool, Expr), Error>>;
+
+    /// @summary - Perform exactly one transition of an agent
+    ///
+    /// @returns - The transition, or None if the agent is stuck
+    fn step(&self, agent: Expr) -> impl Future<Output=Result<Option<Transition>, Error>>;
+    
+    fn bacht_exec_all(&self, agent: Expr) -> impl Future<Output=Result<bool, Error>>;
+    
+    fn exec_primitive(&self, primitive: &str, coord_data: &str) -> impl Future<Output=Result<bool, Error>>;
+
+    fn run_one_primitive(&self, prim: &'static str, token: Arc<str>) -> impl Future<Output=Result<(bool, Expr), Error>>;
+    
+    fn run_one_sequence(&self, agents: Vec<Expr>) -> impl Future<Output=Result<(bool, Expr), Error>>;
+    
+    fn run_one_parallel(&self, agents: Vec<Expr>) -> impl Future<Output=Result<(bool, Expr), Error>>;
+    
+    fn run_one_choice(&self, agents: Vec<Expr>) -> impl Future<Output=Result<(bool, Expr), Error>>;
+}
+
+// An agent defined by name, with its head and the source of its body
+#[derive(Clone)]
+struct Defined {
+    head: String,
+    parameters: Vec<String>,
+    source: String,
+    body: Expr,
+}
+
+pub struct Simulator<B: BlackboardInterfaceTrait> {
+    blackboard: B,
+    // Picks the branch of the parallel compositions and choices run first
+    rng: Mutex<StdRng>,
+    // The last primitive executed, i.e. the one of the last transition
+    executed: Mutex<Option<(String, String)>>,
+    tracer: Mutex<Option<Tracer>>,
+    // The agents defined by name
+    definitions: Mutex<HashMap<String, Defined>>,
+    // The calls being unfolded by run_one
+    unfolding: AtomicUsize,
+    // The grammar of the definitions
+    dialect: Mutex<Dialect>,
+    // The variables bound by the last primitive executed, substituted in the rest of its sequence
+    bound: Mutex<Vec<(String, Term)>>,
+    // The transitions bacht_exec_all executes at most
+    max_steps: AtomicUsize,
+}
+
+impl<B: BlackboardInterfaceTrait> Simulator<B> {
+
+    /// @summary - Run the agents on the given blackboard interface, instead of a new one
+    pub fn new_with(blackboard: B) -> Self {
+        Simulator {
+            blackboard,
+            rng: Mutex::new(StdRng::from_os_rng()),
+            executed: Mutex::new(None),
+            tracer: Mutex::new(None),
+            definitions: Mutex::new(HashMap::new()),
+            unfolding: AtomicUsize::new(0),
+            dialect: Mutex::new(Dialect::default()),
+            bound: Mutex::new(Vec::new()),
+            max_steps: AtomicUsize::new(DEFAULT_MAX_STEPS),
+        }
+    }
+
+    /// @returns - The blackboard interface the agents are run on
+    pub fn blackboard(&self) -> &B {
+        &self.blackboard
+    }
+
+    /// @summary - Make the branches picked from now on reproducible, e.g. to replay a run
+    pub fn seed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+    }
+
+    /// @summary - Report each primitive attempted from now on, with its result, e.g. to print the interleavings of an agent
+    ///
+    /// @param tracer - The callback receiving the attempts, None to stop tracing
+    pub fn set_tracer(&self, tracer: Option<Tracer>) {
+        *self.tracer.lock().unwrap() = tracer;
+    }
+
+    /// @summary - Stop the agents run by bacht_exec_all from now on after this number of transitions, e.g. `*tell(a)`
+    /// which never terminates, with Error::StepLimit
+    pub fn set_max_steps(&self, steps: usize) {
+        self.max_steps.store(steps, Ordering::Relaxed);
+    }
+
+    /// @summary - Parse the definitions from now on in a dialect, e.g. Dialect::Linda for the ones using `out` and `in`
+    pub fn set_dialect(&self, dialect: Dialect) {
+        *self.dialect.lock().unwrap() = dialect;
+    }
+
+    fn trace(&self, primitive: &str, token: &str, result: bool) {
+        log!(Level::Trace, "{}({}) {}", primitive, token, if result { "executed" } else { "blocked" });
+        if let Some(tracer) = self.tracer.lock().unwrap().as_ref() {
+            tracer(&TraceEntry { primitive: primitive.to_string(), token: token.to_string(), result, at: Instant::now() });
+        }
+    }
+
+    /// @summary - Define an agent by name, so that the next agents can call it, e.g. `producer = tell(item);producer`,
+    /// or with parameters replaced by the arguments of each call, e.g. `producer(x) = tell(x);producer(x)`
+    ///
+    /// @param name - The name of the agent, followed by its parameters in parentheses if it has some
+    ///
+    /// @param body - The source of the agent, which may call its own name or other definitions
+    ///
+    /// @returns - Error::InvalidName if the name does not parse, Error::ParseError if the body does not
+    pub fn define(&self, name: &str, body: &str) -> Result<(), Error> {
+        let dialect = *self.dialect.lock().unwrap();
+        let head = name;
+        let (name, parameters) = parse_head(head).map_err(|_| Error::InvalidName(head.to_string()))?;
+        if ["tell", "ask", "get", "nask", "skip"].contains(&name) {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        let agent = parse_with(body, dialect).map_err(Error::from)?;
+        let parameters = parameters.into_iter().map(String::from).collect();
+        self.definitions.lock().unwrap().insert(name.to_string(), Defined { head: head.to_string(), parameters, source: body.to_string(), body: agent });
+        Ok(())
+    }
+
+    /// @returns - false if the name was not defined
+    pub fn undefine(&self, name: &str) -> bool {
+        self.definitions.lock().unwrap().remove(name).is_some()
+    }
+
+    /// @returns - The names defined, with their parameters, and the source of their agent, sorted by name
+    pub fn definitions(&self) -> Vec<(String, String)> {
+        let mut definitions: Vec<(String, String)> = self.definitions.lock().unwrap().values()
+            .map(|defined| (defined.head.clone(), defined.source.clone()))
+            .collect();
+        definitions.sort();
+        definitions
+    }
+
+    async fn run_one_call(&self, name: Arc<str>, arguments: Vec<Term>) -> Result<(bool, Expr), Error> {
+        let Some(defined) = self.definitions.lock().unwrap().get(&*name).cloned() else {
+            return Err(Error::UnknownAgent(name.to_string()));
+        };
+        if defined.parameters.len() != arguments.len() {
+            return Err(Error::WrongArity { name: name.to_string(), expected: defined.parameters.len(), given: arguments.len() });
+        }
+        let body = match arguments.is_empty() {
+            true => defined.body,
+            false => {
+                let bindings: Vec<(&str, Term)> = defined.parameters.iter().map(String::as_str).zip(arguments.iter().cloned()).collect();
+                defined.body.substitute(&bindings)
+            },
+        };
+        if self.unfolding.fetch_add(1, Ordering::SeqCst) >= MAX_UNFOLDING {
+            self.unfolding.store(0, Ordering::SeqCst);
+            return Err(Error::UnguardedRecursion(name.to_string()));
+        }
+        let result = self.run_one(body).await;
+        self.unfolding.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |unfolding| Some(unfolding.saturating_sub(1))).ok();
+        match result {
+            // A stuck call is kept folded
+            Ok((false, _)) => Ok((false, BachtAstCall(name, arguments))),
+            result => result,
+        }
+    }
+
+    // A term is executed on its canonical form, the token the store keeps for it
+    async fn run_one_term(&self, prim: &'static str, term: Term) -> Result<(bool, Expr), Error> {
+        let Some(variable) = term.variable() else {
+            return match self.run_one_primitive(prim, term.to_string().into()).await? {
+                (true, _) => Ok((true, BachtAstEmptyAgent())),
+                (false, _) => Ok((false, BachtAstTerm(prim, term))),
+            };
+        };
+        self.bound.lock().unwrap().clear();
+        if prim == "tell" {
+            return Err(Error::UnboundVariable(variable.to_string()));
+        }
+        match (prim, self.match_term(&term).await?) {
+            ("nask", Some(_)) => {
+                self.trace(prim, &term.to_string(), false);
+                Ok((false, BachtAstTerm(prim, term)))
+            },
+            (_, Some((token, bindings))) => match self.run_one_primitive(prim, token.into()).await? {
+                (true, _) => {
+                    *self.bound.lock().unwrap() = bindings.into_iter().map(|(variable, term)| (variable.to_string(), term)).collect();
+                    Ok((true, BachtAstEmptyAgent()))
+                },
+                // Taken by another agent since the tokens were listed
+                (false, _) => Ok((false, BachtAstTerm(prim, term))),
+            },
+            ("get" | "ask", None) => {
+                self.trace(prim, &term.to_string(), false);
+                Ok((false, BachtAstTerm(prim, term)))
+            },
+            // Executed without binding anything
+            ("nask" | "inp" | "rdp", None) => {
+                self.trace(prim, &term.to_string(), true);
+                *self.executed.lock().unwrap() = Some((prim.to_string(), term.to_string()));
+                Ok((true, BachtAstEmptyAgent()))
+            },
+            (_, None) => Err(Error::UnknownPrimitive(prim.to_string())),
+        }
+    }
+
+    // A copy of the agent runs one transition, in parallel with the replication, which is stuck while the copy is
+    async fn run_one_replication(&self, agent: Expr) -> Result<(bool, Expr), Error> {
+        match self.run_one(agent.clone()).await? {
+            (false, _) => Ok((false, BachtAstReplication(Box::new(agent)))),
+            // A copy terminating in a silent step, e.g. the one of *skip, leaves the replication as it was: no progress
+            (true, BachtAstEmptyAgent()) if self.executed.lock().unwrap().as_ref().is_some_and(|(primitive, _)| primitive == "skip") => {
+                Ok((false, BachtAstReplication(Box::new(agent))))
+            },
+            (true, BachtAstEmptyAgent()) => Ok((true, BachtAstReplication(Box::new(agent)))),
+            (true, copy) => Ok((true, Expr::composition("||", vec![copy, BachtAstReplication(Box::new(agent))]))),
+        }
+    }
+
+    // The guarded agent runs once its guard executed, the variables bound by the guard substituted
+    async fn run_one_guarded(&self, guard: Expr, agent: Expr) -> Result<(bool, Expr), Error> {
+        match self.run_one(guard).await? {
+            (true, _) => Ok((true, self.bind(agent))),
+            (false, guard) => Ok((false, BachtAstGuarded(Box::new(guard), Box::new(agent)))),
+        }
+    }
+
+    // The first token of the blackboard, in the order of their names, matching a term with variables
+    async fn match_term(&self, term: &Term) -> Result<Option<(&'static str, Vec<(Arc<str>, Term)>)>, Error> {
+        Ok(self.blackboard.tokens().await?.into_iter().find_map(|token| {
+            let mut bindings = Vec::new();
+            let candidate = parse_term(token.as_str()).ok()?;
+            term.unify(&candidate, &mut bindings).then_some((token.as_str(), bindings))
+        }))
+    }
+
+    // The rest of a sequence, its variables replaced by the terms the last primitive bound them to
+    fn bind(&self, agent: Expr) -> Expr {
+        let bound = self.bound.lock().unwrap();
+        match bound.is_empty() {
+            true => agent,
+            false => agent.substitute(&bound.iter().map(|(variable, term)| (variable.as_str(), term.clone())).collect::<Vec<(&str, Term)>>()),
+        }
+    }
+
+    // The order the branches of a parallel composition or of a choice are tried in, picked at random
+    fn order(&self, branches: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..branches).collect();
+        order.shuffle(&mut *self.rng.lock().unwrap());
+        order
+    }
+}
+
+impl<B: BlackboardInterfaceTrait> SimulatorTrait for Simulator<B> {
+    fn new() -> Self {
+        Self::new_with(B::new())
+    }
+
+    async fn run_one(&self, agent: Expr) -> Result<(bool, Expr), Error> {
+        // Must use Box::pin to allow recursive calls of async functions
+        match agent {
+            BachtAstPrimitive(prim, token) => Box::pin(self.run_one_primitive(prim, token)).await,
+            BachtAstAgent(";", agents) => Box::pin(self.run_one_sequence(agents)).await,
+            BachtAstAgent("||", agents) => Box::pin(self.run_one_parallel(agents)).await,
+            BachtAstAgent("+", agents) => Box::pin(self.run_one_choice(agents)).await,
+            BachtAstCall(name, arguments) => Box::pin(self.run_one_call(name, arguments)).await,
+            BachtAstTerm(prim, term) => Box::pin(self.run_one_term(prim, term)).await,
+            BachtAstReplication(ag) => Box::pin(self.run_one_replication(*ag)).await,
+            BachtAstGuarded(guard, ag) => Box::pin(self.run_one_guarded(*guard, *ag)).await,
+            // A silent transition, executing no primitive
+            BachtAstEmptyAgent() => {
+                self.bound.lock().unwrap().clear();
+                *self.executed.lock().unwrap() = Some(("skip".to_string(), String::new()));
+                Ok((true, BachtAstEmptyAgent()))
+            },
+            agent => Err(Error::InvalidAgent(agent.to_string())),
+        }
+    }
+
+    async fn step(&self, agent: Expr) -> Result<Option<Transition>, Error> {
+        match self.run_one(agent).await? {
+            (true, continuation) => {
+                let executed = self.executed.lock().unwrap().take().ok_or_else(|| Error::InvalidAgent(continuation.to_string()))?;
+                Ok(Some(Transition { executed, continuation }))
+            },
+            (false, _) => Ok(None),
+        }
+    }
+
+    /// @returns - true once the agent terminated, false once it is stuck, or Error::StepLimit if it executed the
+    /// transitions of set_max_steps without doing either
+    async fn bacht_exec_all(&self, agent: Expr) -> Result<bool, Error> {
+        if agent == BachtAstEmptyAgent() { return Ok(true); }
+        let max_steps = self.max_steps.load(Ordering::Relaxed);
+        let mut current_agent = agent;
+        for _ in 0..max_steps {
+            match self.run_one(current_agent).await {
+                Ok((false, _ag_cont)) => {
+                    return Ok(false);
+                },
+                Ok((true, BachtAstEmptyAgent())) => {
+                    return Ok(true);
+                },
+                Ok((true, ag_cont)) => {
+                    log!(Level::Trace, "Continuing with {}", ag_cont);
+                    current_agent = ag_cont;
+                },
+                Err(e) => return Err(e)
+            };
+        }
+        Err(Error::StepLimit(max_steps))
+    }
+
+    async fn exec_primitive(&self, primitive: &str, coord_data: &str) -> Result<bool, Error> {
+        match primitive {
+            "tell" => self.blackboard.tell(coord_data).await,
+            "ask" => self.blackboard.ask(coord_data).await,
+            "get" => self.blackboard.get(coord_data).await,
+            "nask" => self.blackboard.nask(coord_data).await,
+            // The non-blocking get and ask of Linda execute whether the token is there or not
+            "inp" => self.blackboard.get(coord_data).await.map(|_| true),
+            "rdp" => self.blackboard.ask(coord_data).await.map(|_| true),
+            _ => Err(Error::UnknownPrimitive(primitive.to_string()))
+        }
+    }
+
+    async fn run_one_primitive(&self, prim: &'static str, token: Arc<str>) -> Result<(bool, Expr), Error> {
+        self.bound.lock().unwrap().clear();
+        let result = self.exec_primitive(prim, &token).await;
+        if let Ok(executed) = result {
+            self.trace(prim, &token, executed);
+        }
+        match result {
+            Ok(true) => {
+                *self.executed.lock().unwrap() = Some((prim.to_string(), token.to_string()));
+                Ok((true, BachtAstEmptyAgent()))
+            },
+            Ok(false) => Ok((false, BachtAstPrimitive(prim, token))),
+            Err(e) => Err(e)
+        }
+    }
+    // Only the first agent of the sequence runs, the next ones bound to the variables it matched
+    async fn run_one_sequence(&self, agents: Vec<Expr>) -> Result<(bool, Expr), Error> {
+        let mut agents = agents.into_iter();
+        let Some(first) = agents.next() else { return Err(Error::InvalidAgent("an empty sequence".into())) };
+        match self.run_one(first).await? {
+            (false, first) => Ok((false, BachtAstAgent(";", std::iter::once(first).chain(agents).collect()))),
+            (true, BachtAstEmptyAgent()) => Ok((true, self.bind(Expr::composition(";", agents.collect())))),
+            (true, ag_cont) => Ok((true, Expr::composition(";", vec![ag_cont, self.bind(Expr::composition(";", agents.collect()))]))),
+        }
+    }
+
+    /// @summary - Run the first agent able to execute, the agents being tried in a random order
+    ///
+    /// @returns - The composition with the agent replaced by its continuation, or left out once it terminated
+    async fn run_one_parallel(&self, mut agents: Vec<Expr>) -> Result<(bool, Expr), Error> {
+        for i in self.order(agents.len()) {
+            let agent = std::mem::replace(&mut agents[i], BachtAstEmptyAgent());
+            match self.run_one(agent).await? {
+                (true, BachtAstEmptyAgent()) => {
+                    agents.remove(i);
+                    return Ok((true, Expr::composition("||", agents)));
+                },
+                (true, ag_cont) => {
+                    agents[i] = ag_cont;
+                    return Ok((true, Expr::composition("||", agents)));
+                },
+                (false, agent) => agents[i] = agent,
+            }
+        }
+        Ok((false, BachtAstAgent("||", agents)))
+    }
+
+    /// @summary - Run the first branch able to execute, the branches being tried in a random order, e.g. the first
+    /// guarded branch whose guard executes
+    ///
+    /// @returns - The choice as it was if no branch can execute: it suspends until one can
+    async fn run_one_choice(&self, agents: Vec<Expr>) -> Result<(bool, Expr), Error> {
+        let mut branches: Vec<Option<Expr>> = agents.into_iter().map(Some).collect();
+        for i in self.order(branches.len()) {
+            match self.run_one(branches[i].take().expect("Each branch is tried once")).await? {
+                (true, ag_cont) => return Ok((true, ag_cont)),
+                (false, branch) => branches[i] = Some(branch),
+            }
+        }
+        Ok((false, BachtAstAgent("+", branches.into_iter().flatten().collect())))
+    }
+}
+
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use mockall::Sequence;
+    use super::*;
+    use crate::language::blackboard_interface::MockBlackboardInterfaceTrait;
+    use crate::language::parser::parse;
+    use std::sync::Arc;
+    #[test]
+    fn the_simulator_should_pick_the_same_branches_with_the_same_seed() {
+        let simulator: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(MockBlackboardInterfaceTrait::default());
+        let replay: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(MockBlackboardInterfaceTrait::default());
+        simulator.seed(42);
+        replay.seed(42);
+        let orders: Vec<Vec<usize>> = (0..32).map(|_| simulator.order(3)).collect();
+        assert_eq!(orders, (0..32).map(|_| replay.order(3)).collect::<Vec<_>>());
+        assert!(orders.iter().any(|order| order[0] == 0) && orders.iter().any(|order| order[0] == 2));
+    }
+
+    // Primitive tests
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_tell_primitive() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.exec_primitive("tell", "token").await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_ask_primitive() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_ask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.exec_primitive("ask", "token").await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_get_primitive() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_get().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.exec_primitive("get", "token").await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_nask_primitive() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_nask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.exec_primitive("nask", "token").await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_refuse_hallucinate_primitive() {
+        let mock_bb = MockBlackboardInterfaceTrait::default();
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.exec_primitive("wrong", "token").await.is_err());
+    }
+
+    // Run-one tests
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_run_a_tell_primitive() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let agent = BachtAstPrimitive("tell", "token".into());
+        match interpreter.run_one(agent).await {
+            Ok((res, ag)) => {
+                assert!(res);
+                assert_eq!(ag, BachtAstEmptyAgent());
+            },
+            Err(_) => panic!("Error while running the agent")
+        }
+    }
+
+    // agent
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_an_empty_agent() {
+        let mock_bb = MockBlackboardInterfaceTrait::default();
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(BachtAstEmptyAgent()).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_sequence_of_agent() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        let mut seq = Sequence::new();
+        mock_bb.expect_tell().times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let agent = BachtAstAgent(";", vec![
+          BachtAstPrimitive("tell", "token".into()),
+          BachtAstPrimitive("ask", "token".into())
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_step_one_transition_at_a_time() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        let mut seq = Sequence::new();
+        mock_bb.expect_tell().times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(false)}));
+        mock_bb.expect_ask().times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let agent = BachtAstAgent(";", vec![
+          BachtAstPrimitive("tell", "token".into()),
+          BachtAstPrimitive("ask", "token".into())
+        ]);
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let transition = interpreter.step(agent).await.unwrap().unwrap();
+        assert_eq!(transition, Transition { executed: ("tell".into(), "token".into()), continuation: BachtAstPrimitive("ask", "token".into()) });
+        assert_eq!(interpreter.step(transition.continuation.clone()).await.unwrap(), None, "The ask should be stuck");
+        let transition = interpreter.step(transition.continuation).await.unwrap().unwrap();
+        assert_eq!(transition, Transition { executed: ("ask".into(), "token".into()), continuation: BachtAstEmptyAgent() });
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_run_long_sequences_without_exhausting_the_stack() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(10_000).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let source = vec!["tell(a)"; 10_000].join(";");
+        let agent = parse(&source).unwrap();
+        assert!(matches!(&agent, BachtAstAgent(";", agents) if agents.len() == 10_000));
+        assert_eq!(agent.to_string(), source);
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn the_agents_should_outlive_their_source() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().withf(|token| token == "msg(a)").times(1).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let agent = {
+            let source = String::from("tell(msg(a))");
+            parse(&source).unwrap()
+        };
+        // Sent to another task, e.g. to run it in the background
+        let agent = tokio::spawn(async move { agent }).await.unwrap();
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn the_empty_agent_should_terminate_in_a_silent_step() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_ask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let agent = parse("skip;ask(token)").unwrap();
+        let transition = interpreter.step(agent).await.unwrap().unwrap();
+        assert_eq!(transition, Transition { executed: ("skip".into(), String::new()), continuation: BachtAstPrimitive("ask", "token".into()) });
+        assert!(interpreter.bacht_exec_all(transition.continuation).await.unwrap());
+        assert!(interpreter.bacht_exec_all(parse("skip||skip").unwrap()).await.unwrap());
+        assert!(matches!(interpreter.define("skip", "tell(a)"), Err(Error::InvalidName(_))));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_unfold_the_calls_of_defined_agents() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        let mut seq = Sequence::new();
+        mock_bb.expect_get().times(2).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_get().times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(false)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.define("consumer", "get(item);consumer").is_ok());
+        assert!(interpreter.define("Consumer", "get(item)").is_err());
+        assert_eq!(interpreter.definitions(), [("consumer".to_string(), "get(item);consumer".to_string())]);
+        assert!(!interpreter.bacht_exec_all(BachtAstCall("consumer".into(), Vec::new())).await.unwrap(), "The consumer should be stuck once the items are consumed");
+        assert!(matches!(interpreter.bacht_exec_all(BachtAstCall("producer".into(), Vec::new())).await, Err(Error::UnknownAgent(_))));
+        assert!(interpreter.undefine("consumer") && !interpreter.undefine("consumer"));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_pass_the_canonical_form_of_the_si_terms_to_the_store() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().withf(|token| token == "t(a,u(b,c))").times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_get().withf(|token| token == "t(a,u(b,c))").times(1).returning(|_| Box::pin(async move {Ok(false)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let agent = parse_with("get(t(a, u(b, c)));tell(t(a,u(b,c)))", Dialect::BachT).unwrap();
+        let (executed, continuation) = interpreter.run_one(agent.clone()).await.unwrap();
+        assert!(!executed, "The get should be stuck");
+        assert_eq!(continuation, agent);
+        let transition = interpreter.step(parse_with("tell(t(a,u(b, c)))", Dialect::BachT).unwrap()).await.unwrap().unwrap();
+        assert_eq!(transition, Transition { executed: ("tell".into(), "t(a,u(b,c))".into()), continuation: BachtAstEmptyAgent() });
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_select_a_branch_whose_guard_executes_or_suspend_the_choice() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        let told = Arc::new(Mutex::new(Vec::new()));
+        mock_bb.expect_ask().returning(|token| {
+            let enabled = token == "b";
+            Box::pin(async move {Ok(enabled)})
+        });
+        let telling = told.clone();
+        mock_bb.expect_tell().returning(move |token| {
+            telling.lock().unwrap().push(token.to_string());
+            Box::pin(async move {Ok(true)})
+        });
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let choice = parse("ask(a)->tell(x) + ask(b)->tell(y);tell(z) + ask(c)->tell(w)").unwrap();
+        for seed in 0..8 {
+            interpreter.seed(seed);
+            let (executed, continuation) = interpreter.run_one(choice.clone()).await.unwrap();
+            assert!(executed);
+            assert_eq!(continuation, parse("tell(y);tell(z)").unwrap(), "Only the branch guarded by b should be selectable");
+        }
+        assert!(told.lock().unwrap().is_empty(), "The guarded agents should not run with their guard");
+        let suspended = parse("ask(a)->tell(x) + ask(c)->tell(w)").unwrap();
+        let (executed, continuation) = interpreter.run_one(suspended.clone()).await.unwrap();
+        assert!(!executed);
+        assert_eq!(continuation, suspended, "The choice should be kept as it was");
+    }
+
+    #[tokio::test]
+    async fn the_agents_built_by_hand_that_cant_run_should_be_refused() {
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(MockBlackboardInterfaceTrait::default());
+        let agent = BachtAstAgent("&", vec![BachtAstEmptyAgent(), BachtAstEmptyAgent()]);
+        assert!(matches!(interpreter.step(agent).await, Err(Error::InvalidAgent(_))));
+        assert_eq!(interpreter.bacht_exec_all(BachtAstAgent(";", Vec::new())).await, Err(Error::InvalidAgent("an empty sequence".into())));
+    }
+
+    #[tokio::test]
+    async fn the_replications_that_never_terminate_should_be_refused() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().withf(|token| token == "a").times(5).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let (executed, continuation) = interpreter.run_one(parse("*skip").unwrap()).await.unwrap();
+        assert!(!executed, "A copy taking a silent step only should make no progress");
+        assert_eq!(continuation, parse("*skip").unwrap());
+        assert!(!interpreter.bacht_exec_all(parse("*skip").unwrap()).await.unwrap());
+        interpreter.set_max_steps(5);
+        assert_eq!(interpreter.bacht_exec_all(parse("*tell(a)").unwrap()).await, Err(Error::StepLimit(5)));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_unfold_one_copy_of_a_replication_per_step() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        // Two jobs on the blackboard
+        let jobs = AtomicUsize::new(2);
+        mock_bb.expect_get().withf(|token| token == "job").returning(move |_| {
+            let taken = jobs.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |jobs| jobs.checked_sub(1)).is_ok();
+            Box::pin(async move {Ok(taken)})
+        });
+        mock_bb.expect_tell().withf(|token| token == "done").returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        interpreter.seed(7);
+        let (executed, continuation) = interpreter.run_one(parse("*(get(job);tell(done))").unwrap()).await.unwrap();
+        assert!(executed);
+        assert_eq!(continuation.to_string(), "tell(done)||*(get(job);tell(done))");
+        assert!(!interpreter.bacht_exec_all(continuation).await.unwrap(), "The replication should be stuck once the jobs are done");
+        let (executed, continuation) = interpreter.run_one(parse("*get(job)").unwrap()).await.unwrap();
+        assert!(!executed);
+        assert_eq!(continuation, parse("*get(job)").unwrap());
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_continue_with_an_agent_printable_in_bacht() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let (_, continuation) = interpreter.run_one(parse("tell(a);((get(t(a, X))+consume(b))||ask(c))").unwrap()).await.unwrap();
+        assert_eq!(continuation.to_string(), "(get(t(a,X))+consume(b))||ask(c)");
+        assert_eq!(parse(&continuation.to_string()), Ok(continuation));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_bind_the_variables_to_the_first_matching_token() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tokens().returning(|| Box::pin(async move {Ok(vec!["ack(a)".into(), "msg(a,b)".into(), "msg(hello)".into(), "msg(world)".into()])}));
+        mock_bb.expect_get().withf(|token| token == "msg(hello)").times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_tell().withf(|token| token == "ack(hello)").times(1).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let transition = interpreter.step(parse("get(msg(X));tell(ack(X));ask(msg(X))").unwrap()).await.unwrap().unwrap();
+        assert_eq!(transition, Transition { executed: ("get".into(), "msg(hello)".into()), continuation: parse("tell(ack(hello));ask(msg(hello))").unwrap() });
+        assert!(interpreter.step(parse("tell(ack(hello))").unwrap()).await.unwrap().is_some());
+        assert!(interpreter.step(parse("ask(ack(b, X))").unwrap()).await.unwrap().is_none());
+        assert!(interpreter.step(parse("nask(msg(X, Y))").unwrap()).await.unwrap().is_none());
+        assert!(interpreter.step(parse("nask(ack(X, Y))").unwrap()).await.unwrap().is_some());
+        assert!(interpreter.step(parse("get(msg(X, X))").unwrap()).await.unwrap().is_none());
+        assert!(matches!(interpreter.step(parse("tell(msg(X))").unwrap()).await, Err(Error::UnboundVariable(variable)) if variable == "X"));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_substitute_the_arguments_of_the_calls_to_their_parameters() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        let mut seq = Sequence::new();
+        mock_bb.expect_tell().withf(|token| token == "msg(a,b)").times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_get().withf(|token| token == "a").times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_get().withf(|token| token == "a").times(1).in_sequence(&mut seq).returning(|_| Box::pin(async move {Ok(false)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.define("send(x, y)", "tell(msg(x,y));consume(x)").is_ok());
+        assert!(interpreter.define("consume(item)", "get(item);consume(item)").is_ok());
+        assert!(matches!(interpreter.define("send(x, x)", "tell(x)"), Err(Error::InvalidName(_))));
+        assert_eq!(interpreter.definitions()[1], ("send(x, y)".to_string(), "tell(msg(x,y));consume(x)".to_string()));
+        let agent = parse_with("send(a, b)", Dialect::BachT).unwrap();
+        let (executed, continuation) = interpreter.run_one(agent).await.unwrap();
+        assert!(executed);
+        assert_eq!(continuation, BachtAstCall("consume".into(), vec![Term::Atom("a".into())]));
+        assert!(!interpreter.bacht_exec_all(continuation).await.unwrap(), "The consumer should be stuck once the items are consumed");
+        assert!(matches!(interpreter.bacht_exec_all(BachtAstCall("send".into(), vec![Term::Atom("a".into())])).await, Err(Error::WrongArity { expected: 2, given: 1, .. })));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_never_block_on_the_non_blocking_linda_primitives() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_get().times(1).returning(|_| Box::pin(async move {Ok(false)}));
+        mock_bb.expect_ask().times(1).returning(|_| Box::pin(async move {Ok(false)}));
+        mock_bb.expect_tell().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(matches!(interpreter.define("poll", "inp(item);rdp(item)"), Err(Error::Parse(_))));
+        interpreter.set_dialect(Dialect::Linda);
+        assert!(interpreter.define("poll", "inp(item);rdp(item);out(done)").is_ok());
+        assert!(interpreter.bacht_exec_all(BachtAstCall("poll".into(), Vec::new())).await.unwrap(), "inp and rdp should execute on an empty store");
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_trace_each_primitive_attempted() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(1).returning(|_| Box::pin(async move {Ok(false)}));
+
+        let agent = BachtAstAgent(";", vec![
+          BachtAstPrimitive("tell", "token".into()),
+          BachtAstPrimitive("ask", "other".into())
+        ]);
+
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        let traced = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let entries = traced.clone();
+        interpreter.set_tracer(Some(Box::new(move |entry: &TraceEntry| entries.lock().unwrap().push(entry.clone()))));
+        assert!(!interpreter.bacht_exec_all(agent).await.unwrap());
+        let traced: Vec<(String, String, bool)> = traced.lock().unwrap().iter().map(|entry| (entry.primitive.clone(), entry.token.clone(), entry.result)).collect();
+        assert_eq!(traced, [("tell".into(), "token".into(), true), ("ask".into(), "other".into(), false)]);
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_parallelism_of_agent() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let agent = BachtAstAgent("||", vec![
+          BachtAstPrimitive("tell", "token".into()),
+          BachtAstPrimitive("ask", "token".into())
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_be_able_to_execute_a_choice_of_agent() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(0..=1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(0..=1).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let agent = BachtAstAgent("+", vec![
+          BachtAstPrimitive("tell", "token".into()),
+          BachtAstPrimitive("ask", "token".into())
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_refuse_when_impossible_execution() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_nask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(1).returning(|_| Box::pin(async move {Ok(false)}));
+        
+        let agent = BachtAstAgent(";", vec![
+          BachtAstPrimitive("nask", "token".into()),
+          BachtAstPrimitive("ask", "token".into()),
+          BachtAstPrimitive("tell", "token".into())
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(!interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_accept_a_choice_combination_even_if_one_operator_cant_be_executed() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_nask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(0..=1).returning(|_| Box::pin(async move {Ok(false)}));
+        
+        let agent = BachtAstAgent("+", vec![
+          BachtAstPrimitive("nask", "token".into()),
+          BachtAstPrimitive("ask", "token".into())
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_refuse_a_choice_parallel_if_one_operator_cant_be_executed() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_nask().times(1).returning(|_| Box::pin(async move {Ok(true)}));
+        mock_bb.expect_ask().times(0..=2).returning(|_| Box::pin(async move {Ok(false)}));
+        
+        let agent = BachtAstAgent("||", vec![
+          BachtAstPrimitive("nask", "token".into()),
+          BachtAstPrimitive("ask", "token".into())
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(!interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+
+    #[tokio::test]
+    async fn the_simulator_should_handle_complex_correct_expression() {
+        let mut mock_bb = MockBlackboardInterfaceTrait::default();
+        mock_bb.expect_tell().times(1..=4).returning(|_| Box::pin(async move {Ok(true)}));
+        
+        let agent = BachtAstAgent("+", vec![
+          BachtAstPrimitive("tell", "token".into()),
+          BachtAstAgent("||", vec![
+            BachtAstPrimitive("tell", "token".into()),
+            BachtAstAgent(";", vec![
+               BachtAstPrimitive("tell", "token".into()),
+               BachtAstPrimitive("tell", "token".into())
+            ])
+          ])
+        ]);
+        
+        let interpreter: Simulator<MockBlackboardInterfaceTrait> = Simulator::new_with(mock_bb);
+        assert!(interpreter.bacht_exec_all(agent).await.is_ok_and(|v| v));
+    }
+}
\ No newline at end of file
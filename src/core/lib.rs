@@ -0,0 +1,79 @@
+//! The BachT coordination core: a blackboard shared by agents, the socket layer allowing remote agents
+//! (or other blackboards) to interact with it, and the BachT language the agents are written in.
+//!
+//! The binaries `bach_core` and `bach_cli` are thin wrappers around this library, which another project may use to
+//! embed a coordination space, e.g. running agents on an in-process blackboard:
+//!
+//! ```
+//! # #[cfg(feature = "language")]
+//! # #[tokio::main]
+//! # async fn main() {
+//! use bacht::{create_blackboard, parse, LocalBlackboardInterface, Simulator, SimulatorTrait};
+//!
+//! let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(create_blackboard()));
+//! let agent = parse("tell(a);(get(a)||tell(a))").unwrap();
+//! assert!(simulator.bacht_exec_all(agent).await.unwrap());
+//! # }
+//! # #[cfg(not(feature = "language"))]
+//! # fn main() {}
+//! ```
+//!
+//! The same agents run on a remote node through a SocketClient, see `bach_cli --connect`.
+//! An application without async blocks on a SyncBlackboard instead, which runs the blackboard on a runtime of its own.
+//! A host that cannot afford extra threads runs an InlineBlackboard instead, applying the events on the task sending
+//! them, e.g. on a current-thread runtime with `SyncBlackboard::new_inline`.
+//! The worker and the listener spawn their tasks and wait their timers through a `runtime::Runtime`, tokio by default,
+//! set with `WorkerOptions::runtime` and `SocketListener::with_runtime` to run them on another executor.
+//!
+//! The material written for Linda migrates with its primitives: `parser::parse_with` accepts `out`, `rd`, `in`, `inp`
+//! and `rdp` in `Dialect::Linda`, and the trait `blackboard::linda::Linda` adds them to any blackboard.
+//!
+//! The parts of the library are gated behind cargo features, all enabled by default: `language` (the parser and the
+//! simulator), `network` (the socket layer, with the language run by its listener) and `cli` (the REPL of bach_cli).
+//! The blackboard alone is left with `default-features = false`. The `testing` feature adds testing::Harness, running
+//! a blackboard on a virtual clock for the tests of the embedding applications.
+//!
+//! The messages of the library are events of `tracing`, handed to the subscriber installed by the embedding application,
+//! e.g. a `tracing_subscriber` registry with its own layers; the binaries install a `log::StreamLogger`. Each event is
+//! followed from its socket to the store in its span, `event` with the id of the event as a field, a child of the spans
+//! `request` and `connection` of the socket; the programs are parsed in the span `parse`.
+//!
+//! The spans of the requests, from their queueing to their forwarding to a peer, are recorded with OpenTelemetry, by the
+//! provider of `trace::set_tracer_provider`, e.g. the one of an OtlpExporter sending them to a collector; their context
+//! crosses the nodes as the W3C traceparent.
+
+pub mod blackboard;
+pub mod config;
+pub mod error;
+#[cfg(feature = "language")]
+pub mod fuzz;
+pub mod model;
+#[cfg(feature = "network")]
+pub mod communication;
+#[cfg(feature = "language")]
+pub mod language;
+// Needs the random tokens of the language, and loads the nodes of a networked deployment
+#[cfg(feature = "network")]
+pub mod loadgen;
+pub mod log;
+pub mod metrics;
+pub mod runtime;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod trace;
+
+// The entry points of an embedding, the rest being reached through the modules
+pub use blackboard::{Blackboard, BlackboardTrait, create_blackboard};
+pub use blackboard::sync::SyncBlackboard;
+#[cfg(feature = "network")]
+pub use communication::socket_client::{ReconnectPolicy, SocketClient, SocketClientTrait};
+#[cfg(feature = "network")]
+pub use communication::socket_listener::{SocketListener, SocketListenerTrait};
+#[cfg(feature = "language")]
+pub use language::blackboard_interface::{BlackboardInterfaceTrait, LocalBlackboardInterface};
+#[cfg(feature = "network")]
+pub use language::blackboard_interface::RemoteBlackboardInterface;
+#[cfg(feature = "language")]
+pub use language::parser::parse;
+#[cfg(feature = "language")]
+pub use language::simulator::{Simulator, SimulatorTrait};
@@ -0,0 +1,224 @@
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use tokio::task::JoinSet;
+use tokio::time::{Instant, MissedTickBehavior, interval_at, timeout_at};
+use crate::model::action::Action;
+use crate::model::token::TokenId;
+
+/// What the agents of a population do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Tells tokens
+    Producer,
+    /// Gets tokens, answered false while they are absent
+    Consumer,
+}
+
+/// @summary - Synthetic agents sharing a role, each one running a primitive at a steady rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Population {
+    pub role: Role,
+    pub agents: usize,
+    /// The primitives run per second by each agent
+    pub rate: f64,
+    /// The number of distinct tokens, picked uniformly, the producers and consumers sharing the same ones
+    pub tokens: usize,
+}
+
+/// @summary - The LoadGenerator spawns populations of producers and consumers against a blackboard for a duration,
+/// e.g. to find the throughput a node sustains before its queue builds up:
+///
+/// ```
+/// use std::time::Duration;
+/// use bacht::loadgen::LoadGenerator;
+///
+/// // 4 producers and 4 consumers of 50 primitives/s each, on 16 tokens
+/// let generator = LoadGenerator::new(Duration::from_secs(10)).with_producers(4, 50.0, 16).with_consumers(4, 50.0, 16);
+/// assert_eq!(generator.offered(), 400.0);
+/// ```
+///
+/// @note - An agent waits for the answer of a primitive before the next one, so that a saturated blackboard is offered
+/// less than the rate of its populations: the report shows it as a throughput below the one offered
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadGenerator {
+    populations: Vec<Population>,
+    duration: Duration,
+    // The same seed picks the same tokens
+    seed: u64,
+}
+
+impl LoadGenerator {
+
+    pub fn new(duration: Duration) -> Self {
+        Self { populations: Vec::new(), duration, seed: 0 }
+    }
+
+    /// @param agents - The number of producers, each one telling rate tokens per second out of tokens distinct ones
+    pub fn with_producers(self, agents: usize, rate: f64, tokens: usize) -> Self {
+        self.with_population(Population { role: Role::Producer, agents, rate, tokens })
+    }
+
+    /// @param agents - The number of consumers, each one getting rate tokens per second out of tokens distinct ones
+    pub fn with_consumers(self, agents: usize, rate: f64, tokens: usize) -> Self {
+        self.with_population(Population { role: Role::Consumer, agents, rate, tokens })
+    }
+
+    /// @note - A population of no rate runs no primitive
+    pub fn with_population(mut self, population: Population) -> Self {
+        self.populations.push(population);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// @returns - The primitives per second offered by every population
+    pub fn offered(&self) -> f64 {
+        self.populations.iter().map(offered).sum()
+    }
+
+    /// @summary - Run the populations until the duration elapsed, counting the answers of each one
+    ///
+    /// @param send - Sends a primitive to the blackboard loaded, e.g. the in-process one or a remote node
+    pub async fn run<F, Fut, E>(&self, send: F) -> LoadReport
+    where F: Fn(Action) -> Fut + Clone + Send + 'static, Fut: Future<Output = Result<bool, E>> + Send, E: Send + 'static {
+        let started = Instant::now();
+        let deadline = started + self.duration;
+        let mut agents = JoinSet::new();
+        for (index, population) in self.populations.iter().enumerate() {
+            if !population.rate.is_finite() || population.rate <= 0.0 {
+                continue;
+            }
+            let period = Duration::from_secs_f64(1.0 / population.rate);
+            for agent in 0..population.agents {
+                let (role, tokens, send) = (population.role, population.tokens.max(1), send.clone());
+                let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(((index as u64) << 32) + agent as u64));
+                agents.spawn(async move {
+                    // The agents of a population are spread over a period instead of running in lockstep
+                    let offset = period.mul_f64(rng.random::<f64>());
+                    let mut ticks = interval_at(started + offset, period);
+                    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    let mut counts = Counts::default();
+                    while timeout_at(deadline, ticks.tick()).await.is_ok() {
+                        let token = TokenId::from(format!("token{}", rng.random_range(0..tokens)));
+                        let action = match role {
+                            Role::Producer => Action::Tell(token),
+                            Role::Consumer => Action::Get(token),
+                        };
+                        match send(action).await {
+                            Ok(true) => counts.executed += 1,
+                            Ok(false) => counts.refused += 1,
+                            Err(_) => counts.errors += 1,
+                        }
+                    }
+                    (index, counts)
+                });
+            }
+        }
+        let mut counts = vec![Counts::default(); self.populations.len()];
+        while let Some(Ok((index, agent))) = agents.join_next().await {
+            counts[index].executed += agent.executed;
+            counts[index].refused += agent.refused;
+            counts[index].errors += agent.errors;
+        }
+        let populations = self.populations.iter().zip(counts)
+            .map(|(population, counts)| PopulationReport { population: population.clone(), counts })
+            .collect();
+        LoadReport { elapsed: started.elapsed(), populations }
+    }
+}
+
+fn offered(population: &Population) -> f64 {
+    population.agents as f64 * population.rate.max(0.0)
+}
+
+/// The answers to the primitives of a population
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Counts {
+    pub executed: usize,
+    /// Answered false, e.g. a get of an absent token
+    pub refused: usize,
+    /// Failed, e.g. on a lost connection
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PopulationReport {
+    pub population: Population,
+    pub counts: Counts,
+}
+
+/// @summary - The measures of a load generated
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadReport {
+    pub elapsed: Duration,
+    pub populations: Vec<PopulationReport>,
+}
+
+impl LoadReport {
+
+    /// @returns - The primitives answered per second, by every population
+    pub fn throughput(&self) -> f64 {
+        let answered: usize = self.populations.iter().map(|report| report.counts.executed + report.counts.refused).sum();
+        answered as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// @returns - The primitives per second offered by every population
+    pub fn offered(&self) -> f64 {
+        self.populations.iter().map(|report| offered(&report.population)).sum()
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in &self.populations {
+            let (population, counts) = (&report.population, &report.counts);
+            let role = match population.role {
+                Role::Producer => "producers",
+                Role::Consumer => "consumers",
+            };
+            writeln!(f, "{} {} on {} tokens offering {:.0} primitives/s: {} executed, {} answered false, {} failed",
+                population.agents, role, population.tokens, offered(population), counts.executed, counts.refused, counts.errors)?;
+        }
+        write!(f, "{:.0} primitives/s sustained of the {:.0} offered over {:.3} s", self.throughput(), self.offered(), self.elapsed.as_secs_f64())
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::blackboard::{BlackboardTrait, create_blackboard};
+    use crate::model::event::Event;
+
+    #[tokio::test(start_paused = true)]
+    async fn load_generator_should_run_each_population_at_its_rate() {
+        let blackboard = Arc::new(create_blackboard());
+        let generator = LoadGenerator::new(Duration::from_secs(2)).with_seed(5)
+            .with_producers(3, 20.0, 4)
+            .with_consumers(2, 10.0, 4)
+            .with_population(Population { role: Role::Consumer, agents: 2, rate: 0.0, tokens: 4 });
+        let report = generator.run(move |action| {
+            let blackboard = blackboard.clone();
+            async move { blackboard.send_event(Event::new(action)).await }
+        }).await;
+
+        let answered = |index: usize| report.populations[index].counts.executed + report.populations[index].counts.refused;
+        // 2 s at 20 and 10 primitives/s per agent
+        assert_eq!((answered(0), report.populations[0].counts.refused), (120, 0));
+        assert_eq!(answered(1), 40);
+        assert_eq!(answered(2), 0);
+        assert!(report.populations[1].counts.executed > 0, "The consumers should get the tokens produced");
+        assert_eq!(report.offered(), 80.0);
+        assert!((report.throughput() - 80.0).abs() < 1.0);
+        assert!(report.to_string().starts_with("3 producers on 4 tokens offering 60 primitives/s: 120 executed"));
+    }
+}
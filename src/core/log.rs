@@ -0,0 +1,222 @@
+use std::io::Write;
+use std::sync::Mutex;
+use tracing::Subscriber;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{Layer, Registry, fmt};
+use tracing_subscriber::layer::SubscriberExt;
+
+/// How much detail of the internals reaches the terminal, each level including the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// The failures, e.g. a peer that cannot be reached
+    Error = 0,
+    /// The events of the node, e.g. the address it listens on
+    Info = 1,
+    /// The connections and the activity of the worker
+    Debug = 2,
+    /// Each event handled and each primitive attempted by the agents
+    Trace = 3,
+}
+
+impl From<Level> for tracing::Level {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => tracing::Level::ERROR,
+            Level::Info => tracing::Level::INFO,
+            Level::Debug => tracing::Level::DEBUG,
+            Level::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
+impl From<Level> for LevelFilter {
+    fn from(level: Level) -> Self {
+        LevelFilter::from_level(level.into())
+    }
+}
+
+impl std::str::FromStr for Level {
+    type Err = String;
+
+    /// @summary - Read a level written in lowercase, e.g. `debug` in a configuration file
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level {
+            "error" => Ok(Level::Error),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown level {}", level)),
+        }
+    }
+}
+
+/// @summary - The subscriber of the binaries, writing each message on a stream prefixed with its spans, e.g.
+/// `event{id=42}: Queued` on the standard error or in a log file
+///
+/// @note - An embedding application installs its own subscriber of tracing instead, e.g. one of tracing_subscriber
+/// with its own layers, the messages of the library carrying their spans
+pub struct StreamLogger {
+    stream: Mutex<Box<dyn Write + Send>>,
+    timestamps: bool,
+    level: Level,
+}
+
+impl StreamLogger {
+
+    pub fn new(stream: impl Write + Send + 'static) -> Self {
+        Self { stream: Mutex::new(Box::new(stream)), timestamps: false, level: Level::Info }
+    }
+
+    /// @summary - Prefix each message with the time and its level, e.g. in a log file read later
+    pub fn with_timestamps(mut self) -> Self {
+        self.timestamps = true;
+        self
+    }
+
+    /// @summary - Set the most detailed level written, e.g. Level::Error for -q or Level::Trace for -vv
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// @returns - The subscriber writing the messages, e.g. to install for the current thread only with
+    /// tracing::subscriber::set_default
+    pub fn subscriber(self) -> impl Subscriber + Send + Sync {
+        let layer = fmt::layer().with_writer(self.stream).with_ansi(false).with_target(false);
+        let layer = match self.timestamps {
+            true => layer.boxed(),
+            false => layer.without_time().with_level(false).boxed(),
+        };
+        Registry::default().with(layer.with_filter(LevelFilter::from(self.level)))
+    }
+
+    /// @summary - Make it the subscriber of the process
+    ///
+    /// @returns - An error if the process has a subscriber already
+    pub fn install(self) -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+        tracing::subscriber::set_global_default(self.subscriber())
+    }
+}
+
+// The macros of tracing, expanded by the log! macro in the crates using the library
+#[doc(hidden)]
+pub use tracing as __tracing;
+
+/// @summary - Hand a message of a level to the subscriber of tracing, formatted as println!, e.g.
+/// `log!(Level::Debug, "Connected to {}", addr)`, in the current span, or in the span given before an arrow, e.g.
+/// `log!(Level::Trace, event.span() => "Queued")`
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $span:expr => $($arg:tt)*) => {
+        $span.in_scope(|| $crate::log!($level, $($arg)*))
+    };
+    ($level:expr, $($arg:tt)*) => {
+        match $level {
+            $crate::log::Level::Error => $crate::log::__tracing::error!($($arg)*),
+            $crate::log::Level::Info => $crate::log::__tracing::info!($($arg)*),
+            $crate::log::Level::Debug => $crate::log::__tracing::debug!($($arg)*),
+            $crate::log::Level::Trace => $crate::log::__tracing::trace!($($arg)*),
+        }
+    };
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Debug;
+    use std::sync::Arc;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::Context;
+    use tracing_subscriber::registry::LookupSpan;
+    use crate::blackboard::{BlackboardTrait, create_blackboard};
+
+    // The messages received by the layer of the tests, with the id of the event span they belong to
+    type Captured = Arc<Mutex<Vec<(tracing::Level, Option<u64>, String)>>>;
+
+    // The id of an event span, kept in the extensions of the span
+    struct EventId(u64);
+
+    #[derive(Default)]
+    struct Fields {
+        id: Option<u64>,
+        message: String,
+    }
+
+    impl Visit for Fields {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "id" {
+                self.id = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.message = format!("{:?}", value);
+            }
+        }
+    }
+
+    // A layer as an embedding application would attach to its subscriber
+    struct Capture(Captured);
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+        fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attributes.record(&mut fields);
+            if let (Some(span), Some(event), "event") = (ctx.span(id), fields.id, attributes.metadata().name()) {
+                span.extensions_mut().insert(EventId(event));
+            }
+        }
+
+        fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            let span = ctx.event_scope(event).and_then(|mut scope| scope.find_map(|span| span.extensions().get::<EventId>().map(|id| id.0)));
+            self.0.lock().unwrap().push((*event.metadata().level(), span, fields.message));
+        }
+    }
+
+    #[tokio::test]
+    async fn subscriber_should_follow_an_event_from_the_queue_to_the_store() {
+        let captured = Captured::default();
+        let _subscriber = tracing::subscriber::set_default(Registry::default().with(Capture(captured.clone())));
+        assert!(create_blackboard().tell("traced".into()).await.unwrap());
+
+        let messages = std::mem::take(&mut *captured.lock().unwrap());
+        let (level, span, _) = messages.iter().find(|(_, _, message)| message.starts_with("Queued Tell(\"traced\")")).unwrap();
+        assert_eq!((*level, span.is_some()), (tracing::Level::TRACE, true));
+        let traced: Vec<&str> = messages.iter().filter(|(_, s, _)| s == span).map(|(_, _, m)| m.as_str()).collect();
+        assert_eq!(traced.len(), 3);
+        assert_eq!(traced[1], "Dequeued");
+        assert_eq!(traced[2], "Handled Tell(\"traced\"): executed");
+    }
+
+    #[test]
+    fn stream_logger_should_prefix_the_messages_with_their_span() {
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Shared {
+            fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(bytes)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        assert!(Level::Error < Level::Info && Level::Debug < Level::Trace);
+        let stream = Shared::default();
+        let subscriber = StreamLogger::new(stream.clone()).with_level(Level::Debug).subscriber();
+        tracing::subscriber::with_default(subscriber, || {
+            crate::log!(Level::Debug, tracing::debug_span!("event", id = 42u64) => "Queued");
+            crate::log!(Level::Trace, "Hidden");
+        });
+        assert_eq!(String::from_utf8(stream.0.lock().unwrap().clone()).unwrap(), "event{id=42}: Queued\n");
+    }
+}
@@ -1,30 +1,649 @@
-use blackboard::create_blackboard;
-use communication::socket_listener::SocketListener;
-use blackboard::Blackboard;
-use blackboard::store::Store;
-use blackboard::task_queue::TaskQueue;
-use blackboard::worker::Worker;
-use communication::socket_listener::SocketListenerTrait;
-
-pub mod blackboard;
-pub mod model;
-mod communication;
+use bacht::blackboard;
+use bacht::config::Config;
+use bacht::blackboard::{Blackboard, BlackboardTrait};
+use bacht::blackboard::store::{self, DynStore};
+use bacht::blackboard::task_queue::TaskQueue;
+use bacht::blackboard::worker::{Worker, WorkerOptions};
+use bacht::communication::causal::CausalBlackboard;
+use bacht::communication::compression::Compression;
+use bacht::communication::federation::FederatedBlackboard;
+use bacht::communication::gossip::{Gossip, GossipBlackboard, GossipConfig};
+use bacht::communication::health::HealthServer;
+use bacht::communication::heartbeat::HeartbeatConfig;
+use bacht::communication::membership::{Membership, MembershipConfig};
+use bacht::communication::listeners::Listeners;
+use bacht::communication::otlp::OtlpExporter;
+use bacht::communication::partition::PartitionedBlackboard;
+use bacht::communication::peers::PeerTable;
+use bacht::communication::raft::{RaftBlackboard, RaftNode};
+use bacht::communication::replication::ReplicatedBlackboard;
+use bacht::communication::socket_client::{ReconnectPolicy, SocketClient};
+use bacht::communication::tenants::TenantRegistry;
+use bacht::communication::socket_listener::{DEFAULT_SOCKET_PORT, SocketListener, SocketListenerTrait};
+use bacht::log;
+use bacht::log::{Level, StreamLogger};
+use bacht::model::admin::{AdminCommand, AdminReply};
+use clap::{ArgAction, Parser, Subcommand};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// The static peers configuration, its path can be overridden by the setting peers.file
+const DEFAULT_PEERS_FILE: &str = "peers.conf";
+
+// How long the events queued at the shutdown are given to be applied
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The exit code of a node that failed to start, or to stop cleanly
+const EXIT_FAILURE: i32 = 1;
+
+// How long a primary with a quorum waits for its replicas to acknowledge a write
+const QUORUM_TIMEOUT: Duration = Duration::from_secs(2);
+
+// The local blackboard, agreed on by Raft, replicated, gossiped, causally broadcast, federated and partitioned with the other nodes
+type NodeBlackboard = PartitionedBlackboard<FederatedBlackboard<CausalBlackboard<GossipBlackboard<ReplicatedBlackboard<RaftBlackboard<Blackboard<TaskQueue, Worker, DynStore>>>>>>>;
+
+// The process id file of the node, removed when it exits
+static PID_FILE: OnceLock<PathBuf> = OnceLock::new();
 
 #[tokio::main]
 async fn main() {
-    // Create a blackboard
-    let blackboard = create_blackboard();
+    // Exits with the usage on --help, or on an invalid command line
+    let args = CommandLine::parse();
+    let config = match Config::find(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error reading the configuration: {}", e);
+            exit(2);
+        }
+    };
+    let settings = (|| Ok::<_, bacht::error::ConfigError>((
+        args.port.or(config.parse_value("listen.port")?),
+        args.bind.or(config.parse_value("listen.bind")?),
+        config.parse_value("listen.health_port")?,
+        config.parse_value::<u64>("listen.request_ttl")?,
+        config.parse_value::<usize>("listen.program_steps")?,
+        config.parse_value::<u64>("peers.probe_interval")?,
+        config.parse_value::<usize>("replication.quorum")?,
+        config.parse_value("queue.max_depth")?,
+        config.parse_value("queue.dropped_results")?.unwrap_or_default(),
+        config.parse_value("queue.batch_size")?.unwrap_or_default(),
+        args.level().or(config.parse_value("log.level")?).unwrap_or(Level::Info),
+    )))();
+    let (port, bind, health_port, request_ttl, program_steps, probe_interval, quorum, max_queue_depth, dropped_results, batch_size, level) = match settings {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error reading the configuration: {}", e);
+            exit(2);
+        }
+    };
+    let pid_file = args.pid_file.or(config.get("node.pid_file").map(PathBuf::from));
+    let log_file = args.log_file.or(config.get("log.file").map(PathBuf::from));
+    let store_file = args.store.or(config.get("store.file").map(PathBuf::from));
+
+    // Refuse to start a node that would fail once detached, e.g. on a port taken
+    let address = bind.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    let failures = self_checks(address, port.unwrap_or(DEFAULT_SOCKET_PORT), health_port, store_file.as_deref(), pid_file.as_deref());
+    for failure in &failures {
+        eprintln!("Check failed: {}", failure);
+    }
+    if !failures.is_empty() {
+        exit(EXIT_FAILURE);
+    }
+    if args.check {
+        eprintln!("All checks passed");
+        exit(0);
+    }
+    if args.background {
+        match detach() {
+            Ok(pid) => {
+                eprintln!("Started in the background as process {}", pid);
+                exit(0);
+            },
+            Err(e) => {
+                eprintln!("Error starting in the background: {}", e);
+                exit(EXIT_FAILURE);
+            }
+        }
+    }
+    if let Some(path) = pid_file {
+        if let Err(e) = std::fs::write(&path, format!("{}\n", std::process::id())) {
+            eprintln!("Error writing the process id in {}: {}", path.display(), e);
+            exit(EXIT_FAILURE);
+        }
+        let _ = PID_FILE.set(path);
+    }
+    let logger = match log_file {
+        Some(path) => match std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => StreamLogger::new(file).with_timestamps(),
+            Err(e) => {
+                eprintln!("Error opening the log file {}: {}", path.display(), e);
+                exit(EXIT_FAILURE);
+            }
+        },
+        None => StreamLogger::new(std::io::stderr()),
+    };
+    // The process has no other subscriber
+    let _ = logger.with_level(level).install();
+
+    // Declare the statically configured remote blackboards
+    let peers = PeerTable::new();
+    let peers_file = config.get("peers.file").unwrap_or(DEFAULT_PEERS_FILE).to_string();
+    if Path::new(&peers_file).exists() {
+        match peers.load_file(Path::new(&peers_file)) {
+            Ok(n) => log!(Level::Info, "Loaded {} peers from {}", n, peers_file),
+            Err(e) => {
+                log!(Level::Error, "Error loading peers: {}", e);
+                exit(EXIT_FAILURE);
+            }
+        }
+    }
+
+    // Probe a peer every peers.probe_interval milliseconds, the crashed ones leaving the routing until they answer again
+    let membership = probe_interval.map(|interval| {
+        let config = MembershipConfig { probe_interval: Duration::from_millis(interval), ..MembershipConfig::default() };
+        let membership = Membership::new(peers.clone()).with_config(config);
+        membership.spawn();
+        membership
+    });
+
+    // The store of store.backend, in memory by default, the results of the clients gone being handled by
+    // queue.dropped_results and the events applied queue.batch_size at once
+    let local = match store::from_config(&config) {
+        Ok(store) => Blackboard::new_with_options(store, WorkerOptions { policy: dropped_results, batch_size, ..WorkerOptions::default() }),
+        Err(e) => {
+            log!(Level::Error, "Error creating the store: {}", e);
+            exit(EXIT_FAILURE);
+        }
+    };
+
+    // The key presented to the other nodes, and accepted from them, falling back on node.admin_key
+    let peer_key = config.get("node.peer_key").or(config.get("node.admin_key"));
+
+    // Apply the operations through the log of a Raft cluster with the peers raft.members, this node being known to them
+    // as node.name
+    let name = config.get("node.name").map_or(format!("node-{}", std::process::id()), String::from);
+    let raft = match config.get_list("raft.members") {
+        members if members.is_empty() => None,
+        members => {
+            let mut addrs = Vec::new();
+            for member in members {
+                match peers.resolve(member) {
+                    Some(addr) => addrs.push((member, addr)),
+                    None => {
+                        log!(Level::Error, "Raft member {} is not a peer", member);
+                        exit(EXIT_FAILURE);
+                    }
+                }
+            }
+            let members: Vec<(&str, &str)> = addrs.iter().map(|(member, addr)| (*member, addr.as_str())).collect();
+            let node = match peer_key {
+                Some(key) => RaftNode::new(&name, &members).with_peer_key(key),
+                None => RaftNode::new(&name, &members),
+            };
+            node.spawn();
+            log!(Level::Info, "Member of a Raft cluster of {} nodes", members.len() + 1);
+            Some(node)
+        }
+    };
+    let local = match &raft {
+        Some(node) => RaftBlackboard::member(local, node.clone()),
+        None => RaftBlackboard::standalone(local),
+    };
+
+    // Replicate the store: a replica of replication.primary, or the primary of replication.replicas, acknowledging the
+    // writes applied by replication.quorum nodes and fenced while it is attached to fewer replicas
+    let with_quorum = |replicated: ReplicatedBlackboard<_>| match quorum {
+        Some(quorum) => replicated.with_quorum(quorum, QUORUM_TIMEOUT),
+        None => replicated,
+    };
+    let replicated = match config.get("replication.primary") {
+        Some(primary) => {
+            let replica = with_quorum(ReplicatedBlackboard::replica(local));
+            // A primary among the probed peers is taken over once the membership declares it dead
+            let member = peers.list().into_iter().find(|(_, peer)| peer.addr == primary).map(|(name, _)| name);
+            match (&membership, member) {
+                (Some(membership), Some(member)) => replica.watch_member(membership, &member),
+                _ => match SocketClient::connect(primary, ReconnectPolicy::default()).await {
+                    Ok(client) => replica.watch_primary(client, HeartbeatConfig::default()),
+                    Err(e) => {
+                        log!(Level::Error, "Error connecting to primary {}: {:?}", primary, e);
+                        exit(EXIT_FAILURE);
+                    }
+                },
+            };
+            log!(Level::Info, "Replica of {}", primary);
+            replica
+        },
+        None => {
+            let primary = with_quorum(ReplicatedBlackboard::primary(local));
+            for replica in config.get_list("replication.replicas") {
+                match SocketClient::connect(replica, ReconnectPolicy::default()).await {
+                    // The snapshot sent to the joining replica is compressed, and installed once the primary presented
+                    // node.peer_key, shared by the nodes of the cluster; the replica missing its heartbeats is detached
+                    Ok(client) => {
+                        let client = client.with_compression(Compression::Lz4).with_heartbeat(HeartbeatConfig::default());
+                        primary.add_replica(match peer_key {
+                            Some(key) => client.with_peer_key(key),
+                            None => client,
+                        })
+                    },
+                    Err(e) => {
+                        log!(Level::Error, "Error connecting to replica {}: {:?}", replica, e);
+                        exit(EXIT_FAILURE);
+                    }
+                };
+                log!(Level::Info, "Streaming to replica {}", replica);
+            }
+            primary
+        }
+    };
+
+    // Send the spans of the requests to the OpenTelemetry collector trace.otlp_endpoint, as the service node.name
+    let otlp = config.get("trace.otlp_endpoint").map(|endpoint| match OtlpExporter::start(endpoint, &name) {
+        Ok(exporter) => {
+            exporter.install();
+            exporter
+        },
+        Err(e) => {
+            log!(Level::Error, "Error exporting the traces: {}", e);
+            exit(EXIT_FAILURE);
+        }
+    });
+
+    // Reconcile the store with the peers.gossip, this node being known as node.name
+    let gossiped = GossipBlackboard::new_with(replicated, &name);
+    if config.contains("peers.gossip") {
+        let gossip_peers = config.get_list("peers.gossip").into_iter().map(String::from).collect();
+        match Gossip::bind(GossipConfig::new(gossip_peers), gossiped.clone()).await {
+            Ok(gossip) => {
+                gossip.spawn();
+            },
+            Err(e) => {
+                log!(Level::Error, "Error starting gossip: {}", e);
+                exit(EXIT_FAILURE);
+            }
+        }
+    }
+
+    // Broadcast the mutations to the peers.causal, applied there in causal order once this node presented node.peer_key
+    let causal = CausalBlackboard::new_with(gossiped, &name);
+    for peer in config.get_list("peers.causal") {
+        match SocketClient::connect(peer, ReconnectPolicy::default()).await {
+            Ok(client) => causal.add_peer(match peer_key {
+                Some(key) => client.with_peer_key(key),
+                None => client,
+            }),
+            Err(e) => {
+                log!(Level::Error, "Error connecting to causal peer {}: {:?}", peer, e);
+                exit(EXIT_FAILURE);
+            }
+        };
+        log!(Level::Info, "Broadcasting to causal peer {}", peer);
+    }
+
+    // Forward the unmet queries to the peers, which hold the occurrences reserved by a node presenting node.peer_key
+    let federated = FederatedBlackboard::new_with(causal, peers.clone());
+    let federated = match peer_key {
+        Some(key) => federated.with_peer_key(key),
+        None => federated,
+    };
+
+    // Route each token to its owner when node.partitioned is set, the peers must know this node as node.name
+    let mut blackboard = PartitionedBlackboard::new_with(federated, &name, peers);
+    blackboard.set_partitioning(config.is_set("node.partitioned"));
     
     // Start listening for events
-    let listener: SocketListener<Blackboard<TaskQueue, Worker, Store>> = SocketListener::new(blackboard, None);
-    let res = listener.listen().await;
-    match res {
-        Ok(_) => {
-            println!("Listening for events on port 1908...");
+    let mut listener: SocketListener<NodeBlackboard> = SocketListener::new(blackboard.clone(), port);
+    // Bind the address listen.bind instead of the IPv4 loopback, `::` accepting both IPv6 and IPv4 clients
+    if let Some(address) = bind {
+        listener = listener.with_address(address).with_dual_stack(address == IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+    }
+    // Expire the requests not applied listen.request_ttl milliseconds after their receipt
+    if let Some(ttl) = request_ttl {
+        listener = listener.with_request_ttl(Duration::from_millis(ttl));
+    }
+    // Stop the programs of the clients after listen.program_steps transitions
+    if let Some(steps) = program_steps {
+        listener = listener.with_program_steps(steps);
+    }
+    // Answer the HTTP probes of an orchestrator and the scrapes of Prometheus on the port listen.health_port, on the same
+    // address as the listener, the node being ready while its queue is not deeper than queue.max_depth
+    if let Some(port) = health_port {
+        let mut health = HealthServer::new(blackboard.clone(), port);
+        if let Some(address) = bind {
+            health = health.with_address(address);
+        }
+        if let Some(max_queue_depth) = max_queue_depth {
+            health = health.with_max_queue_depth(max_queue_depth);
+        }
+        tokio::spawn(async move {
+            if let Err(e) = health.serve().await {
+                log!(Level::Error, "Error starting health server: {}", e);
+            }
+        });
+    }
+    // Also serve the blackboard on the subject bacht.<node.name> of the NATS server peers.nats when set
+    #[cfg(feature = "nats")]
+    if let Some(nats) = config.get("peers.nats") {
+        match async_nats::connect(nats).await {
+            Ok(client) => {
+                let bridge = bacht::communication::nats::NatsBridge::new(blackboard.clone(), client, &format!("bacht.{}", name));
+                tokio::spawn(async move {
+                    if let Err(e) = bridge.serve().await {
+                        log!(Level::Error, "Error serving NATS: {}", e);
+                    }
+                });
+            },
+            Err(e) => {
+                log!(Level::Error, "Error connecting to NATS server {}: {}", nats, e);
+                exit(EXIT_FAILURE);
+            }
         }
+    }
+    // Also stream the changes of the store to the gRPC subscribers on the port listen.grpc_port when set, the calls
+    // presenting the admin key or the key of the peers
+    #[cfg(feature = "grpc")]
+    match config.parse_value::<u16>("listen.grpc_port") {
+        Ok(Some(port)) => {
+            let mut grpc = bacht::communication::grpc::GrpcServer::new(blackboard.clone(), port);
+            if let Some(address) = bind {
+                grpc = grpc.with_address(address);
+            }
+            for key in [config.get("node.admin_key"), config.get("node.peer_key")].into_iter().flatten() {
+                grpc = grpc.with_key(key);
+            }
+            tokio::spawn(async move {
+                if let Err(e) = grpc.serve().await {
+                    log!(Level::Error, "Error starting gRPC server: {}", e);
+                }
+            });
+        },
+        Ok(None) => {},
         Err(e) => {
-            eprintln!("Error starting listener: {}", e);
-            return;
+            log!(Level::Error, "Error reading the configuration: {}", e);
+            exit(EXIT_FAILURE);
+        }
+    }
+    // Accept the administration commands of the clients presenting the key node.admin_key, none without it
+    let admin_key = config.get("node.admin_key");
+    if let Some(key) = admin_key {
+        listener = listener.with_admin_key(key);
+    }
+    // Accept the frames of the other nodes presenting the key node.peer_key, e.g. the mutations of a primary
+    if let Some(key) = config.get("node.peer_key") {
+        listener = listener.with_peer_key(key);
+    }
+    // Serve the clients presenting the key of a tenant of node.tenants_file on its own blackboard
+    let tenants = TenantRegistry::new();
+    if let Some(path) = config.get("node.tenants_file") {
+        match tenants.load_file(Path::new(path)) {
+            Ok(n) => log!(Level::Info, "Loaded {} tenants from {}", n, path),
+            Err(e) => {
+                log!(Level::Error, "Error loading tenants: {}", e);
+                exit(EXIT_FAILURE);
+            }
+        }
+    }
+    listener = listener.with_tenants(tenants.clone());
+    // Answer the indirect probes of the other members
+    if let Some(membership) = membership {
+        listener = listener.with_membership(membership);
+    }
+    // Answer the votes and the appends of the other members of the Raft cluster
+    if let Some(node) = raft {
+        listener = listener.with_raft(node);
+    }
+    let mut listeners = Listeners::new().with_listener(listener);
+    // Also listen on the Unix domain socket listen.socket when set
+    if let Some(path) = config.get("listen.socket") {
+        let mut unix_listener = SocketListener::new(blackboard.clone(), None).with_unix_socket(path).with_tenants(tenants);
+        if let Some(key) = admin_key {
+            unix_listener = unix_listener.with_admin_key(key);
         }
+        if let Some(key) = config.get("node.peer_key") {
+            unix_listener = unix_listener.with_peer_key(key);
+        }
+        listeners = listeners.with_listener(unix_listener);
+    }
+    let mut handle = listeners.spawn();
+    let mut code = tokio::select! {
+        result = handle.wait() => match result {
+            Ok(()) => 0,
+            Err(e) => {
+                log!(Level::Error, "Error starting listener: {}", e);
+                EXIT_FAILURE
+            }
+        },
+        signal = shutdown_signal() => {
+            log!(Level::Info, "Received {}, shutting down", signal);
+            0
+        },
+    };
+    // Stop accepting the requests, then let the worker apply the ones already queued
+    handle.shutdown().await;
+    if !blackboard::drain(&blackboard, DRAIN_TIMEOUT).await {
+        log!(Level::Error, "Events were still queued after {:?}", DRAIN_TIMEOUT);
+        code = EXIT_FAILURE;
+    }
+    // Persist the store in --store or store.file, in the format of bach_cli --store
+    if let Some(path) = &store_file {
+        match persist_store(&blackboard, path).await {
+            Ok(count) => log!(Level::Info, "Persisted {} tokens in {}", count, path.display()),
+            Err(e) => {
+                log!(Level::Error, "Error persisting the store in {}: {}", path.display(), e);
+                code = EXIT_FAILURE;
+            }
+        }
+    }
+    // Send the spans of the last requests, the exporter blocking until the collector answered
+    if let Some(Err(e)) = otlp.map(|otlp| tokio::task::block_in_place(|| otlp.shutdown())) {
+        log!(Level::Error, "Error exporting the last traces: {}", e);
+    }
+    exit(code);
+}
+
+/// @summary - Wait for the signal stopping the node: Ctrl-C, or SIGTERM on Unix, e.g. sent by an orchestrator
+///
+/// @returns - The name of the signal received
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                log!(Level::Error, "Error handling SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl-C"
+    }
+}
+
+/// @summary - Write the tokens of the store, one `token count` per line
+///
+/// @returns - The number of distinct tokens written
+async fn persist_store<B: BlackboardTrait>(blackboard: &B, path: &Path) -> Result<usize, String> {
+    let tokens = match blackboard.admin(AdminCommand::Snapshot).await.map_err(|e| e.to_string())? {
+        AdminReply::Snapshot(tokens) => tokens,
+        reply => return Err(format!("Unexpected reply {:?}", reply)),
+    };
+    let content: String = tokens.iter().map(|(token, count)| format!("{} {}\n", token, count)).collect();
+    std::fs::write(path, content).map_err(|e| e.to_string())?;
+    Ok(tokens.len())
+}
+
+/// @summary - Remove the process id file of the node, then exit with the code
+fn exit(code: i32) -> ! {
+    if let Some(path) = PID_FILE.get() {
+        let _ = std::fs::remove_file(path);
+    }
+    std::process::exit(code);
+}
+
+/// @summary - Check that the node can start: its ports are free, and its files writable
+///
+/// @returns - The reason of each check that failed
+fn self_checks(address: IpAddr, port: u16, health_port: Option<u16>, store_file: Option<&Path>, pid_file: Option<&Path>) -> Vec<String> {
+    let mut failures = Vec::new();
+    for port in std::iter::once(port).chain(health_port) {
+        if let Err(e) = std::net::TcpListener::bind(SocketAddr::new(address, port)) {
+            failures.push(format!("port {} of {} is not available: {}", port, address, e));
+        }
+    }
+    if let Some(path) = store_file {
+        if let Err(e) = check_writable(path) {
+            failures.push(format!("store file {} is not writable: {}", path.display(), e));
+        }
+    }
+    if let Some(path) = pid_file {
+        // A process id file left by a node that crashed is replaced, not the one of a node still running
+        match std::fs::read_to_string(path).ok().and_then(|pid| pid.trim().parse::<u32>().ok()) {
+            Some(pid) if pid != std::process::id() && Path::new("/proc").join(pid.to_string()).exists() => {
+                failures.push(format!("process id file {} belongs to the running process {}", path.display(), pid));
+            },
+            _ => if let Err(e) = check_writable(path) {
+                failures.push(format!("process id file {} is not writable: {}", path.display(), e));
+            },
+        }
+    }
+    failures
+}
+
+// Open the file for writing without changing it, a file created by the check being removed
+fn check_writable(path: &Path) -> std::io::Result<()> {
+    let existed = path.exists();
+    std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    if !existed {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// @summary - Run the node again in a process of its own, out of the process group of the terminal and without
+/// --background, e.g. for an init system expecting the command to return once the node is started
+///
+/// @returns - The process id of the node
+fn detach() -> std::io::Result<u32> {
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command.args(std::env::args().skip(1).filter(|arg| arg != "--background"))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    Ok(command.spawn()?.id())
+}
+
+/// @summary - The command line of the node: the serve command, the configuration file and the settings overriding it
+#[derive(Debug, Default, PartialEq, Parser)]
+#[command(name = "bach_core", version, about = "Serve a blackboard node to the remote agents and to the other nodes",
+    after_help = "The settings of the file are overridden by the environment, e.g. BACHT_PEERS or BACHT_NAME, then by the options")]
+struct CommandLine {
+    #[command(subcommand)]
+    command: Option<NodeCommand>,
+    /// The settings of the node, BACHT_CONFIG or bacht.toml by default
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// The port listened on, 2138 by default
+    #[arg(long, global = true)]
+    port: Option<u16>,
+    /// The address listened on instead of the IPv4 loopback
+    #[arg(long, global = true, value_name = "ADDRESS")]
+    bind: Option<IpAddr>,
+    /// Persist the store in the file at the shutdown, instead of the one of store.file
+    #[arg(long, global = true, value_name = "FILE")]
+    store: Option<PathBuf>,
+    /// Write the process id of the node in the file while it runs
+    #[arg(long, global = true, value_name = "FILE")]
+    pid_file: Option<PathBuf>,
+    /// Append the messages to the file instead of the standard error
+    #[arg(long, global = true, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+    /// Detach the node from the terminal once its checks passed, in the foreground by default
+    #[arg(long, global = true)]
+    background: bool,
+    /// Only check that the node can start: its ports are free and its files writable
+    #[arg(long, global = true)]
+    check: bool,
+    /// Only print the errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also print the connections and the activity of the worker, -vv each event handled
+    #[arg(short, global = true, action = ArgAction::Count)]
+    verbose: u8,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
+enum NodeCommand {
+    /// Run the node, the default command
+    Serve,
+}
+
+impl CommandLine {
+
+    /// @returns - The level of -q or -v, None to take the one of the configuration
+    fn level(&self) -> Option<Level> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some(Level::Error),
+            (false, 0) => None,
+            (false, 1) => Some(Level::Debug),
+            (false, _) => Some(Level::Trace),
+        }
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<CommandLine, clap::error::ErrorKind> {
+        CommandLine::try_parse_from(std::iter::once("bach_core").chain(line.split_whitespace())).map_err(|e| e.kind())
+    }
+
+    #[test]
+    fn command_line_should_read_the_serve_command_and_its_options() {
+        assert_eq!(args(""), Ok(CommandLine::default()));
+        let serve = args("serve --port 2140 -v").unwrap();
+        assert_eq!((serve.level(), serve.command, serve.port), (Some(Level::Debug), Some(NodeCommand::Serve), Some(2140)));
+        assert_eq!(args("--port 2140 serve -vv").unwrap().level(), Some(Level::Trace));
+        let serve = args("serve --pid-file node.pid --log-file node.log --store store.txt --background --check").unwrap();
+        assert_eq!((serve.pid_file, serve.log_file, serve.store), (Some("node.pid".into()), Some("node.log".into()), Some("store.txt".into())));
+        assert!(serve.background && serve.check);
+        assert_eq!(args("-q").unwrap().level(), Some(Level::Error));
+        assert_eq!(args("--port serve"), Err(clap::error::ErrorKind::ValueValidation));
+        assert_eq!(args("serve serve"), Err(clap::error::ErrorKind::UnknownArgument));
+        assert_eq!(args("--help").map(|_| ()), Err(clap::error::ErrorKind::DisplayHelp));
+    }
+
+    #[test]
+    fn self_checks_should_report_the_ports_taken_and_the_files_not_writable() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let directory = std::env::temp_dir().join(format!("bacht-checks-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let store = directory.join("store.txt");
+        let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        assert!(self_checks(localhost, 0, None, Some(&store), Some(&directory.join("node.pid"))).is_empty());
+        assert!(!store.exists(), "The check should not leave the file it created");
+        let failures = self_checks(localhost, 0, Some(port), Some(&directory.join("missing/store.txt")), None);
+        assert_eq!(failures.len(), 2, "{:?}", failures);
+        assert!(failures[0].starts_with(&format!("port {} of 127.0.0.1 is not available", port)));
+        assert!(failures[1].starts_with("store file"));
+        std::fs::remove_dir_all(directory).unwrap();
     }
-}
\ No newline at end of file
+}
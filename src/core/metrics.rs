@@ -0,0 +1,155 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use crate::model::action::Action;
+use crate::model::health::Health;
+
+/// The content type of the metrics rendered, the text format of Prometheus
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+// The upper bounds of the latency buckets in seconds, the last bucket (+Inf) being the count of the latencies
+const LATENCY_BUCKETS: [f64; 6] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.1];
+
+// The label of each action, in the order of ACTION_METRICS
+const ACTIONS: [&str; 4] = ["tell", "ask", "nask", "get"];
+
+// The counters of an action, the latencies in microseconds
+struct ActionMetrics {
+    processed: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum: AtomicU64,
+}
+
+impl ActionMetrics {
+    const fn new() -> Self {
+        Self {
+            processed: AtomicU64::new(0),
+            buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS.len()],
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+        }
+    }
+}
+
+// The metrics are global to the process, as the log level
+static ACTION_METRICS: [ActionMetrics; ACTIONS.len()] = [const { ActionMetrics::new() }; ACTIONS.len()];
+static CONNECTIONS_OPEN: AtomicU64 = AtomicU64::new(0);
+static CONNECTIONS_ACCEPTED: AtomicU64 = AtomicU64::new(0);
+
+fn action_metrics(action: &Action) -> &'static ActionMetrics {
+    match action {
+        Action::Tell(_) => &ACTION_METRICS[0],
+        Action::Ask(_) => &ACTION_METRICS[1],
+        Action::Nask(_) => &ACTION_METRICS[2],
+        Action::Get(_) => &ACTION_METRICS[3],
+    }
+}
+
+/// @summary - Count an event applied by a worker, whether it succeeded or not
+pub fn event_processed(action: &Action) {
+    action_metrics(action).processed.fetch_add(1, Ordering::Relaxed);
+}
+
+/// @summary - Record the time an action took, from its queueing to its answer
+pub fn observe_latency(action: &Action, latency: Duration) {
+    let metrics = action_metrics(action);
+    let seconds = latency.as_secs_f64();
+    for (bucket, bound) in metrics.buckets.iter().zip(LATENCY_BUCKETS) {
+        if seconds <= bound {
+            bucket.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    metrics.count.fetch_add(1, Ordering::Relaxed);
+    metrics.sum.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// A connection counted as open until dropped
+pub struct OpenConnection;
+
+/// @summary - Count a connection accepted by a listener
+///
+/// @returns - The connection, to drop once it is closed
+pub fn connection_opened() -> OpenConnection {
+    CONNECTIONS_ACCEPTED.fetch_add(1, Ordering::Relaxed);
+    CONNECTIONS_OPEN.fetch_add(1, Ordering::Relaxed);
+    OpenConnection
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        CONNECTIONS_OPEN.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// @summary - Render the metrics of the process in the text format of Prometheus
+///
+/// @param health - The health of the blackboard served, giving its queue depth and its store size
+pub fn render(health: &Health) -> String {
+    let mut text = String::new();
+    // Writing in a String never fails
+    let _ = write_metrics(&mut text, health);
+    text
+}
+
+fn write_metrics(text: &mut String, health: &Health) -> std::fmt::Result {
+    writeln!(text, "# HELP bacht_queue_depth Events waiting for the worker")?;
+    writeln!(text, "# TYPE bacht_queue_depth gauge")?;
+    writeln!(text, "bacht_queue_depth {}", health.queue_depth)?;
+    writeln!(text, "# HELP bacht_store_size Distinct tokens present in the store")?;
+    writeln!(text, "# TYPE bacht_store_size gauge")?;
+    writeln!(text, "bacht_store_size {}", health.store_size)?;
+    writeln!(text, "# HELP bacht_connections_open Connections currently served by the listeners")?;
+    writeln!(text, "# TYPE bacht_connections_open gauge")?;
+    writeln!(text, "bacht_connections_open {}", CONNECTIONS_OPEN.load(Ordering::Relaxed))?;
+    writeln!(text, "# HELP bacht_connections_accepted_total Connections accepted by the listeners")?;
+    writeln!(text, "# TYPE bacht_connections_accepted_total counter")?;
+    writeln!(text, "bacht_connections_accepted_total {}", CONNECTIONS_ACCEPTED.load(Ordering::Relaxed))?;
+    writeln!(text, "# HELP bacht_events_processed_total Events applied by the workers")?;
+    writeln!(text, "# TYPE bacht_events_processed_total counter")?;
+    for (action, metrics) in ACTIONS.iter().zip(&ACTION_METRICS) {
+        writeln!(text, "bacht_events_processed_total{{action=\"{}\"}} {}", action, metrics.processed.load(Ordering::Relaxed))?;
+    }
+    writeln!(text, "# HELP bacht_action_duration_seconds Time from the queueing of an action to its answer")?;
+    writeln!(text, "# TYPE bacht_action_duration_seconds histogram")?;
+    for (action, metrics) in ACTIONS.iter().zip(&ACTION_METRICS) {
+        let count = metrics.count.load(Ordering::Relaxed);
+        for (bucket, bound) in metrics.buckets.iter().zip(LATENCY_BUCKETS) {
+            writeln!(text, "bacht_action_duration_seconds_bucket{{action=\"{}\",le=\"{}\"}} {}", action, bound, bucket.load(Ordering::Relaxed))?;
+        }
+        writeln!(text, "bacht_action_duration_seconds_bucket{{action=\"{}\",le=\"+Inf\"}} {}", action, count)?;
+        writeln!(text, "bacht_action_duration_seconds_sum{{action=\"{}\"}} {}", action, metrics.sum.load(Ordering::Relaxed) as f64 / 1e6)?;
+        writeln!(text, "bacht_action_duration_seconds_count{{action=\"{}\"}} {}", action, count)?;
+    }
+    Ok(())
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The value of a metric, the other tests of the process updating them as well
+    fn value(text: &str, metric: &str) -> f64 {
+        text.lines().find_map(|line| line.strip_prefix(metric)?.strip_prefix(' ')?.parse().ok()).unwrap()
+    }
+
+    #[test]
+    fn render_should_export_the_metrics_in_the_prometheus_format() {
+        let health = Health { worker_alive: true, queue_depth: 3, store_size: 2 };
+        let before = render(&health);
+        event_processed(&Action::Nask("a".into()));
+        observe_latency(&Action::Nask("a".into()), Duration::from_micros(700));
+        let after = render(&health);
+
+        assert_eq!(value(&after, "bacht_queue_depth"), 3.0);
+        assert_eq!(value(&after, "bacht_store_size"), 2.0);
+        for metric in ["bacht_events_processed_total{action=\"nask\"}", "bacht_action_duration_seconds_count{action=\"nask\"}",
+                       "bacht_action_duration_seconds_bucket{action=\"nask\",le=\"0.001\"}"] {
+            assert!(value(&after, metric) > value(&before, metric), "{} should have increased", metric);
+        }
+        assert!(after.contains("# TYPE bacht_action_duration_seconds histogram\n"));
+    }
+}
@@ -1,12 +1,54 @@
+use serde::{Deserialize, Serialize};
+use super::token::TokenId;
+use crate::error::TableFull;
 
-pub enum Action {
-    Tell(Box<str>),
-    Ask(Box<str>),
-    Nask(Box<str>),
-    Get(Box<str>)
+/// The primitive of an agent on a token, the token being carried by its id, or by its name in the frames until the
+/// request carrying it is authorized
+///
+/// @note - Serialized with the name of its token, e.g. `{"Tell":"token"}` in JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Action<T = TokenId> {
+    Tell(T),
+    Ask(T),
+    Nask(T),
+    Get(T)
 }
 
-impl PartialEq for Action {
+impl<T> Action<T> {
+
+    /// @returns - The same primitive on the token mapped
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Action<U> {
+        match self {
+            Action::Tell(token) => Action::Tell(f(token)),
+            Action::Ask(token) => Action::Ask(f(token)),
+            Action::Nask(token) => Action::Nask(f(token)),
+            Action::Get(token) => Action::Get(f(token)),
+        }
+    }
+}
+
+impl Action {
+
+    /// @returns - The action on the name of its token, as carried by the frames
+    pub fn named(&self) -> Action<Box<str>> {
+        self.clone().map(Box::from)
+    }
+}
+
+impl Action<Box<str>> {
+
+    /// @summary - The action on the id of its token, interned on its first use, see TokenId::try_intern
+    ///
+    /// @returns - The action, or TableFull if the table of the process holds too many names to intern it
+    pub fn intern(self) -> Result<Action, TableFull> {
+        let token = match &self {
+            Action::Tell(token) | Action::Ask(token) | Action::Nask(token) | Action::Get(token) => TokenId::try_intern(token)?,
+        };
+        Ok(self.map(|_| token))
+    }
+}
+
+impl<T: PartialEq> PartialEq for Action<T> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Action::Tell(a), Action::Tell(b)) => a == b,
@@ -16,4 +58,4 @@ impl PartialEq for Action {
             _ => false
         }
     }
-}
\ No newline at end of file
+}
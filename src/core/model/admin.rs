@@ -0,0 +1,42 @@
+/// Commands managing a blackboard, only accepted from the clients holding the admin capability
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminCommand {
+    /// Remove every token of the store of the node
+    ///
+    /// @note - Only the local store is cleared: the clear is neither replicated nor gossiped
+    Clear,
+    /// Fetch the counters of the node, e.g. the number of tokens
+    Stats,
+    /// Dump the tokens of the store with their number of occurrences
+    Snapshot,
+    /// List the peers known by the node
+    Peers,
+    /// Add the occurrences of the tokens of a snapshot to the store at once, e.g. the snapshot of its primary installed
+    /// by a replica
+    ///
+    /// @note - Nothing is added if one of the tokens can't be interned
+    Import(Vec<(Box<str>, u32)>),
+}
+
+/// A peer known by a node, as reported to the administrators
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerInfo {
+    pub name: Box<str>,
+    pub addr: String,
+    pub alive: bool,
+}
+
+/// The answer to an AdminCommand
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminReply {
+    /// The command was applied
+    Done,
+    /// Counters by name, sorted by name
+    Stats(Vec<(String, u64)>),
+    /// The tokens present in the store with their number of occurrences, sorted by token
+    Snapshot(Vec<(Box<str>, u32)>),
+    /// A part of a snapshot too big for one frame, the parts following each other under the id of the command until
+    /// the last one; the client joins them into a Snapshot
+    SnapshotPart { tokens: Vec<(Box<str>, u32)>, last: bool },
+    Peers(Vec<PeerInfo>),
+}
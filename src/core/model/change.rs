@@ -0,0 +1,9 @@
+use super::token::TokenId;
+
+/// A change of the number of occurrences of a token, notified to the subscribers of a store
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreChange {
+    pub token: TokenId,
+    /// The number of occurrences after the change, 0 once the token is absent
+    pub count: u32,
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// A vector clock: for each node, the number of its events that happened before
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VectorClock {
+    entries: BTreeMap<Box<str>, u64>,
+}
+
+impl VectorClock {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// @returns - The number of events of the node, 0 for an unknown node
+    pub fn get(&self, node: &str) -> u64 {
+        self.entries.get(node).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, node: &str, count: u64) {
+        self.entries.insert(node.into(), count);
+    }
+
+    /// @summary - Count a new event of the node
+    ///
+    /// @returns - The number of events of the node, including the new one
+    pub fn increment(&mut self, node: &str) -> u64 {
+        let count = self.entries.entry(node.into()).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// @summary - Take the events known by another clock into account, entry by entry
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (node, count) in &other.entries {
+            let entry = self.entries.entry(node.clone()).or_insert(0);
+            *entry = (*entry).max(*count);
+        }
+    }
+
+    /// @returns - true if every event known by this clock is known by the other, and the other knows more
+    pub fn happened_before(&self, other: &VectorClock) -> bool {
+        self.entries.iter().all(|(node, count)| *count <= other.get(node))
+            && other.entries.iter().any(|(node, count)| *count > self.get(node))
+    }
+
+    /// @returns - true if neither clock happened before the other
+    pub fn is_concurrent(&self, other: &VectorClock) -> bool {
+        !self.happened_before(other) && !other.happened_before(self)
+    }
+
+    /// @returns - The nodes and their number of events, sorted by node
+    pub fn entries(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.entries.iter().map(|(node, count)| (node.as_ref(), *count))
+    }
+}
+
+/// The causal context of an event sent by a node: the clock of the node once the event was counted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamp {
+    pub node: Box<str>,
+    pub clock: VectorClock,
+}
+
+impl Stamp {
+
+    /// @summary - Tell whether the event can be applied by a node, given the events it already applied
+    ///
+    /// @returns - Some(true) if it is the next event of its node and every event it depends on was applied,
+    /// Some(false) if it must wait for them, None if it was already applied
+    pub fn is_deliverable(&self, delivered: &VectorClock) -> Option<bool> {
+        let sequence = self.clock.get(&self.node);
+        if sequence <= delivered.get(&self.node) {
+            return None;
+        }
+        Some(sequence == delivered.get(&self.node) + 1
+            && self.clock.entries().all(|(node, count)| node == self.node.as_ref() || count <= delivered.get(node)))
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        let mut clock = VectorClock::new();
+        entries.iter().for_each(|(node, count)| clock.set(node, *count));
+        clock
+    }
+
+    #[test]
+    fn clock_should_order_the_events() {
+        let mut a = VectorClock::new();
+        assert_eq!(a.increment("a"), 1);
+        let mut b = a.clone();
+        b.increment("b");
+        assert!(a.happened_before(&b));
+        assert!(!b.happened_before(&a));
+        assert!(!a.happened_before(&a));
+
+        a.increment("a");
+        assert!(a.is_concurrent(&b));
+        b.merge(&a);
+        assert_eq!(b, clock(&[("a", 2), ("b", 1)]));
+        assert!(a.happened_before(&b));
+        assert!(clock(&[("a", 0)]).is_concurrent(&VectorClock::new()), "Unknown nodes count no event");
+    }
+
+    #[test]
+    fn stamp_should_be_deliverable_once_its_predecessors_are() {
+        // b got the token told by a
+        let stamp = Stamp { node: "b".into(), clock: clock(&[("a", 1), ("b", 1)]) };
+        assert_eq!(stamp.is_deliverable(&VectorClock::new()), Some(false), "The tell of a was not applied yet");
+        assert_eq!(stamp.is_deliverable(&clock(&[("a", 1)])), Some(true));
+        assert_eq!(stamp.is_deliverable(&clock(&[("a", 1), ("b", 1)])), None, "The get was already applied");
+        assert_eq!(Stamp { node: "b".into(), clock: clock(&[("b", 2)]) }.is_deliverable(&VectorClock::new()), Some(false), "The first event of b is missing");
+    }
+}
@@ -1,15 +1,161 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
 use super::action::Action;
+use super::clock::Stamp;
+use crate::trace::SpanContext;
+
+// The id of the next event created by the process
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The version of the schema of the events written by this crate, the events of the other versions being read as well
+///
+/// - 1: the action of an agent
+/// - 2: the origin and the stamp of the event, an agent without stamp when missing
+pub const EVENT_VERSION: u16 = 2;
+
+/// Where an event comes from, it decides how far the blackboard propagates it
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Origin {
+    /// An agent of the coordination infrastructure
+    #[default]
+    Agent,
+    /// A peer blackboard forwarding an unmet query, it must be applied locally only
+    Peer,
+    /// The primary blackboard streaming a mutation it applied, it must be applied on the replica's store
+    Primary,
+}
 
 /// Events represent an incoming action from another agent of the coordination infrastructure.
+///
+/// They are serialized with the version of their schema, so that the nodes running different versions of the crate
+/// exchange them: the fields a version adds are optional for the older ones, the fields of a newer one are ignored.
+/// Only the fields of the schema are serialized, a deserialized event getting a new id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
-    pub action: Action
+    /// The version of the schema the event was written in, EVENT_VERSION for the events of this crate; it is not compared
+    #[serde(default = "first_version")]
+    pub version: u16,
+    /// Unique in the process, it follows the event in the logs and is not compared
+    #[serde(skip, default = "next_id")]
+    pub id: u64,
+    pub action: Action,
+    #[serde(default)]
+    pub origin: Origin,
+    /// The causal context of an event broadcast by a peer, None for the other events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<Stamp>,
+    /// When the sender stops waiting for the event, the worker discarding it afterwards; it is not compared
+    #[serde(skip)]
+    pub deadline: Option<Instant>,
+    /// The operation of the trace that caused the event, its spans being children of it; it is not compared
+    #[serde(skip)]
+    pub trace: Option<SpanContext>,
+    // The span of the messages about the event, from its socket to the store; none for a deserialized event
+    #[serde(skip, default = "tracing::Span::none")]
+    span: tracing::Span,
+}
+
+// The events written without their version, before it was added
+fn first_version() -> u16 {
+    1
+}
+
+fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Event {
+
+    // The event of an action, in a span named event and carrying its id
+    fn with_origin(action: Action, origin: Origin, stamp: Option<Stamp>) -> Self {
+        let id = next_id();
+        let span = tracing::trace_span!("event", id, action = ?action);
+        Self { version: EVENT_VERSION, id, action, origin, stamp, deadline: None, trace: None, span }
+    }
+
     pub fn new(action: Action) -> Self {
-        Self {
-            action
-        }
-        
+        Self::with_origin(action, Origin::Agent, None)
+    }
+
+    /// @summary - Create an event forwarded by a peer blackboard
+    pub fn forwarded(action: Action) -> Self {
+        Self::with_origin(action, Origin::Peer, None)
+    }
+
+    /// @summary - Create an event replicating a mutation of the primary blackboard
+    pub fn replicated(action: Action) -> Self {
+        Self::with_origin(action, Origin::Primary, None)
+    }
+
+    /// @summary - Create an event broadcast by a peer blackboard, to apply on the local store only once its causal
+    /// predecessors were applied
+    pub fn causal(action: Action, stamp: Stamp) -> Self {
+        Self::with_origin(action, Origin::Peer, Some(stamp))
+    }
+
+    /// @summary - Expire the event at a deadline, e.g. when a remote client gives up on its request
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
     }
-}
\ No newline at end of file
+
+    /// @summary - Expire the event once it waited longer than the time-to-live from now
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.with_deadline(Instant::now() + ttl)
+    }
+
+    /// @summary - Record the spans of the event in a trace, e.g. the one of the request it comes from
+    pub fn with_trace(mut self, trace: SpanContext) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// @returns - true once the deadline of the event passed
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|deadline| deadline <= Instant::now())
+    }
+
+    /// @returns - The span of the messages about the event, a child of the span it was created in, e.g. the one of its
+    /// request; its id field is the id of the event
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.action == other.action && self.origin == other.origin && self.stamp == other.stamp
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::clock::VectorClock;
+
+    #[test]
+    fn event_should_be_read_back_from_any_version() {
+        let mut clock = VectorClock::new();
+        clock.set("a", 3);
+        clock.set("b", 1);
+        let event = Event::causal(Action::Get("token".into()), Stamp { node: "b".into(), clock });
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"version":2,"action":{"Get":"token"},"origin":"Peer","stamp":{"node":"b","clock":{"a":3,"b":1}}}"#);
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+
+        // The action of an agent written by the first version
+        let first: Event = serde_json::from_str(r#"{"action":{"Tell":"token"}}"#).unwrap();
+        assert_eq!((first.version, first), (1, Event::new(Action::Tell("token".into()))));
+        // A field added by a newer version is ignored
+        let newer: Event = serde_json::from_str(r#"{"version":3,"action":{"Ask":"token"},"origin":"Peer","priority":7}"#).unwrap();
+        assert_eq!((newer.version, newer), (3, Event::forwarded(Action::Ask("token".into()))));
+
+        assert!(serde_json::from_str::<Event>(r#"{"version":2,"origin":"Peer"}"#).is_err(), "The action is required");
+        assert!(serde_json::from_str::<Event>(r#"{"action":{"Put":"token"}}"#).is_err());
+    }
+}
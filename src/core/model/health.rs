@@ -0,0 +1,25 @@
+/// The health of a blackboard node, as reported to the probes of an orchestrator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    /// The worker applying the events to the store is still running
+    pub worker_alive: bool,
+    /// Number of events waiting for the worker
+    pub queue_depth: u64,
+    /// Number of distinct tokens present in the store
+    pub store_size: u64,
+}
+
+impl Health {
+
+    /// @summary - A live node can still make progress, it should be restarted otherwise
+    pub fn is_live(&self) -> bool {
+        self.worker_alive
+    }
+
+    /// @summary - A ready node can take more traffic
+    ///
+    /// @param max_queue_depth - The depth of the queue above which the node is considered overloaded
+    pub fn is_ready(&self, max_queue_depth: u64) -> bool {
+        self.is_live() && self.queue_depth <= max_queue_depth
+    }
+}
@@ -1,3 +1,8 @@
 pub mod action;
+pub mod admin;
+pub mod change;
+pub mod clock;
 pub mod event;
-pub mod task;
\ No newline at end of file
+pub mod health;
+pub mod task;
+pub mod token;
\ No newline at end of file
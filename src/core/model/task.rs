@@ -1,18 +1,23 @@
+use std::time::SystemTime;
 use super::event::Event;
+use crate::error::StoreError;
 use tokio::sync::oneshot::{Sender, Receiver, channel};
 
 /// Task represents a unit of work that will be processed by the event queue worker.
-pub(crate) struct Task {
+pub struct Task {
     pub(crate) event: Event,
     // Response channel, through which the event will send the result of the event
-    pub(crate) res_chanel: Sender<Result<bool, TaskError>>,
+    pub(crate) res_chanel: Sender<Result<bool, StoreError>>,
+    // When the event was queued, only read to record the wait of a traced event
+    pub(crate) queued: Option<SystemTime>,
 }
 
 impl Task {
-    pub fn new(event: Event) -> (Self, Receiver<Result<bool, TaskError>>) {
-        let (tx, rx) = channel::<Result<bool, TaskError>>();
+    pub fn new(event: Event) -> (Self, Receiver<Result<bool, StoreError>>) {
+        let (tx, rx) = channel::<Result<bool, StoreError>>();
         (
             Self {
+                queued: event.trace.is_some().then(SystemTime::now),
                 event,
                 res_chanel: tx,
             },
@@ -20,10 +25,3 @@ impl Task {
         )
     }
 }
-
-#[derive(Debug)]
-pub enum TaskError {
-    UnspecifiedError,
-    //TimeOutError,
-    ChannelError,
-}
\ No newline at end of file
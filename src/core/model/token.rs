@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{LazyLock, OnceLock, RwLock};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::error::TableFull;
+
+// The names of the tokens interned by the process, indexed by their id
+static TABLE: LazyLock<Table> = LazyLock::new(Table::default);
+
+/// The bytes of names the table of the process holds at most before refusing the names received from other processes,
+/// see TokenId::try_intern
+pub const TABLE_CAPACITY: usize = 64 << 20;
+
+// The segments of the names, the segment s holding the ids from 2^s - 1 to 2^(s + 1) - 2, so that every u32 is an id
+const SEGMENTS: usize = 33;
+
+struct Table {
+    // Allocated on the first id they hold and never moved, so that a name is resolved without taking the lock
+    names: [OnceLock<Box<[OnceLock<&'static str>]>>; SEGMENTS],
+    index: RwLock<Index>,
+}
+
+#[derive(Default)]
+struct Index {
+    ids: HashMap<&'static str, u32>,
+    // The bytes of the names interned
+    bytes: usize,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self { names: [const { OnceLock::new() }; SEGMENTS], index: RwLock::default() }
+    }
+}
+
+impl Table {
+
+    /// @returns - The id of the name, interned unless the table would hold more than capacity bytes of names
+    fn intern(&self, name: &str, capacity: usize) -> Result<u32, TableFull> {
+        if let Some(id) = self.index.read().unwrap().ids.get(name) {
+            return Ok(*id);
+        }
+        let mut index = self.index.write().unwrap();
+        // Interned by another thread since the read
+        if let Some(id) = index.ids.get(name) {
+            return Ok(*id);
+        }
+        if index.bytes + name.len() > capacity {
+            return Err(TableFull);
+        }
+        let id = u32::try_from(index.ids.len()).map_err(|_| TableFull)?;
+        let name: &'static str = Box::leak(name.into());
+        // Resolved before the id is handed out, the lock ordering the write before the reads of the other threads
+        let _ = self.slot(id).set(name);
+        index.ids.insert(name, id);
+        index.bytes += name.len();
+        Ok(id)
+    }
+
+    fn name(&self, id: u32) -> &'static str {
+        self.slot(id).get().expect("A token id is only handed out once its name is resolved")
+    }
+
+    fn slot(&self, id: u32) -> &OnceLock<&'static str> {
+        let position = id as u64 + 1;
+        let segment = position.ilog2() as usize;
+        let slots = self.names[segment].get_or_init(|| (0..1u64 << segment).map(|_| OnceLock::new()).collect());
+        &slots[(position - (1 << segment)) as usize]
+    }
+}
+
+/// @summary - The compact identifier of a token, carried by the actions, the events and the keys of the store instead
+/// of its name, which is only resolved at the edges: the frames, the logs and the snapshots.
+///
+/// ```
+/// use bacht::model::token::TokenId;
+///
+/// let token = TokenId::from("temperature");
+/// assert_eq!(token, TokenId::from(String::from("temperature")));
+/// assert_eq!(&*token, "temperature");
+/// ```
+///
+/// An id is 4 bytes copied instead of a string allocated, it derefs to the name of the token and is displayed and
+/// ordered as it.
+///
+/// @note - The ids are given by the table of the process, in the order the names are interned: they are not sent to the
+/// other nodes, the frames carrying the names. The names interned are never freed: those received are only interned
+/// once the request carrying them is authorized and applied, and are bound by TABLE_CAPACITY.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenId(u32);
+
+impl TokenId {
+
+    /// @summary - The id of a name, interned on its first use
+    ///
+    /// @note - For the names of this process, e.g. those of its agents, which are not bound by the capacity of the table.
+    /// Panics if u32::MAX names were interned
+    pub fn intern(name: &str) -> Self {
+        Self::intern_within(name, usize::MAX).expect("More than u32::MAX tokens interned")
+    }
+
+    /// @summary - The id of a name received from another process, e.g. in a frame, interned on its first use
+    ///
+    /// @returns - The id, or TableFull if interning the name would exceed TABLE_CAPACITY, as the names are never freed
+    pub fn try_intern(name: &str) -> Result<Self, TableFull> {
+        Self::intern_within(name, TABLE_CAPACITY)
+    }
+
+    fn intern_within(name: &str, capacity: usize) -> Result<Self, TableFull> {
+        TABLE.intern(name, capacity).map(Self)
+    }
+
+    pub fn id(self) -> u32 {
+        self.0
+    }
+
+    /// @returns - The name of the token, resolved without a lock
+    pub fn as_str(self) -> &'static str {
+        TABLE.name(self.0)
+    }
+}
+
+impl Deref for TokenId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for TokenId {
+    fn from(name: &str) -> Self {
+        Self::intern(name)
+    }
+}
+
+impl From<String> for TokenId {
+    fn from(name: String) -> Self {
+        Self::intern(&name)
+    }
+}
+
+impl From<Box<str>> for TokenId {
+    fn from(name: Box<str>) -> Self {
+        Self::intern(&name)
+    }
+}
+
+impl From<&Box<str>> for TokenId {
+    fn from(name: &Box<str>) -> Self {
+        Self::intern(name)
+    }
+}
+
+impl From<TokenId> for Box<str> {
+    fn from(token: TokenId) -> Self {
+        token.as_str().into()
+    }
+}
+
+impl PartialEq<str> for TokenId {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for TokenId {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// @note - By name, as the names were sorted before being interned
+impl Ord for TokenId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.0 == other.0 {
+            true => Ordering::Equal,
+            false => self.as_str().cmp(other.as_str()),
+        }
+    }
+}
+
+impl PartialOrd for TokenId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for TokenId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// @note - Serialized as its name, the ids being those of the process
+impl Serialize for TokenId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// @note - The name is interned as one received from another process, see TokenId::try_intern
+impl<'de> Deserialize<'de> for TokenId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = Box::<str>::deserialize(deserializer)?;
+        TokenId::try_intern(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Debug for TokenId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_id_should_be_interned_once_and_resolved_to_its_name() {
+        let (a, b) = (TokenId::from("token-id-a"), TokenId::from("token-id-b"));
+        assert_eq!(TokenId::from("token-id-a".to_string()).id(), a.id());
+        assert_ne!(a.id(), b.id());
+        assert_eq!((a.as_str(), b.to_string(), format!("{:?}", a)), ("token-id-a", "token-id-b".to_string(), "\"token-id-a\"".to_string()));
+        assert!(TokenId::from("token-id-0") < a.min(b) && a < b, "The ids should be ordered by name");
+        assert_eq!(Box::<str>::from(a), "token-id-a".into());
+    }
+
+    #[test]
+    fn table_should_refuse_the_names_beyond_its_capacity() {
+        let table = Table::default();
+        assert_eq!(table.intern("abc", 5), Ok(0));
+        assert_eq!(table.intern("de", 5), Ok(1));
+        assert_eq!(table.intern("f", 5), Err(TableFull));
+        assert_eq!(table.intern("abc", 5), Ok(0), "An interned name should still be resolved once the table is full");
+        assert_eq!((table.name(0), table.name(1)), ("abc", "de"));
+        assert_eq!(TokenId::try_intern("token-id-c").map(TokenId::as_str), Ok("token-id-c"));
+    }
+}
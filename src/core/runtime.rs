@@ -0,0 +1,133 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A future spawned or awaited through a Runtime
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// @summary - The executor the worker and the listener spawn their tasks and wait their timers on, e.g. to run them on
+/// async-std, smol or the runtime of an embedding application instead of a tokio multi-thread runtime
+///
+/// ```
+/// use std::time::Duration;
+/// use bacht::runtime::{BoxFuture, Runtime};
+///
+/// // Each task on a thread of its own, blocking on it with a runtime of the thread
+/// struct ThreadPerTask;
+///
+/// impl Runtime for ThreadPerTask {
+///     fn spawn(&self, future: BoxFuture) {
+///         std::thread::spawn(move || tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(future));
+///     }
+///
+///     fn sleep(&self, duration: Duration) -> BoxFuture {
+///         Box::pin(tokio::time::sleep(duration))
+///     }
+/// }
+/// ```
+///
+/// @note - Only the tasks and the timers go through it: the channels and the locks of the blackboard run on any
+/// executor, but the sockets of the listener still need a tokio reactor, a listener bound on another Transport not.
+pub trait Runtime: Send + Sync {
+
+    /// @summary - Run the future in the background until it completes
+    fn spawn(&self, future: BoxFuture);
+
+    /// @summary - A future completing after the duration
+    fn sleep(&self, duration: Duration) -> BoxFuture;
+}
+
+/// @summary - The tokio runtime the current task runs on, the default one
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioRuntime;
+
+impl Runtime for TokioRuntime {
+    fn spawn(&self, future: BoxFuture) {
+        tokio::spawn(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// @summary - A runtime shared by the parts of a node, the TokioRuntime by default
+#[derive(Clone)]
+pub struct RuntimeHandle(Arc<dyn Runtime>);
+
+impl RuntimeHandle {
+    pub fn new(runtime: impl Runtime + 'static) -> Self {
+        Self(Arc::new(runtime))
+    }
+
+    pub fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        self.0.spawn(Box::pin(future));
+    }
+
+    pub fn sleep(&self, duration: Duration) -> BoxFuture {
+        self.0.sleep(duration)
+    }
+
+    /// @summary - Run the future until it completes or the duration elapses
+    ///
+    /// @returns - None once the duration elapsed, the future being dropped
+    pub async fn timeout<T>(&self, duration: Duration, future: impl Future<Output = T>) -> Option<T> {
+        tokio::select! {
+            output = future => Some(output),
+            _ = self.sleep(duration) => None,
+        }
+    }
+}
+
+impl Default for RuntimeHandle {
+    fn default() -> Self {
+        Self::new(TokioRuntime)
+    }
+}
+
+impl fmt::Debug for RuntimeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RuntimeHandle")
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Runs each task on a thread of its own, on a current-thread runtime, and counts the tasks and the timers
+    #[derive(Default)]
+    pub(crate) struct ThreadRuntime {
+        pub(crate) spawned: Arc<AtomicUsize>,
+        pub(crate) slept: Arc<AtomicUsize>,
+    }
+
+    impl Runtime for ThreadRuntime {
+        fn spawn(&self, future: BoxFuture) {
+            self.spawned.fetch_add(1, Ordering::SeqCst);
+            std::thread::spawn(move || {
+                tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(future)
+            });
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture {
+            self.slept.fetch_add(1, Ordering::SeqCst);
+            Box::pin(tokio::time::sleep(duration))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runtime_handle_should_time_out_on_the_timers_of_its_runtime() {
+        let (spawned, slept) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let runtime = RuntimeHandle::new(ThreadRuntime { spawned: spawned.clone(), slept: slept.clone() });
+        assert_eq!(runtime.timeout(Duration::from_secs(1), async { 42 }).await, Some(42));
+        assert_eq!(runtime.timeout(Duration::from_secs(1), std::future::pending::<()>()).await, None);
+        assert_eq!((spawned.load(Ordering::SeqCst), slept.load(Ordering::SeqCst)), (0, 2));
+    }
+}
@@ -0,0 +1,141 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use crate::blackboard::{Blackboard, BlackboardTrait, create_blackboard};
+use crate::blackboard::store::Store;
+use crate::blackboard::task_queue::TaskQueue;
+use crate::blackboard::worker::Worker;
+#[cfg(feature = "language")]
+use crate::error::Error;
+#[cfg(feature = "language")]
+use crate::language::blackboard_interface::LocalBlackboardInterface;
+#[cfg(feature = "language")]
+use crate::language::parser::parse;
+#[cfg(feature = "language")]
+use crate::language::simulator::{Simulator, SimulatorTrait};
+
+/// @summary - The Harness runs a blackboard and its worker deterministically, e.g. in the integration tests of the agents
+/// of an application: its tasks run one at a time on a single thread, and its clock only moves when the harness is
+/// advanced or when every task waits for a timer, which then fires at once.
+///
+/// ```
+/// use std::time::Duration;
+/// use bacht::blackboard::BlackboardTrait;
+/// use bacht::testing::Harness;
+///
+/// let harness = Harness::new();
+/// // An hour of retries takes no time
+/// harness.run(async { tokio::time::sleep(Duration::from_secs(3600)).await });
+/// assert_eq!(harness.elapsed(), Duration::from_secs(3600));
+/// assert!(harness.run(harness.blackboard().tell("a".into())).unwrap());
+/// ```
+///
+/// @note - The async tests of the crate use the same clock with `#[tokio::test(start_paused = true)]`
+pub struct Harness {
+    runtime: Runtime,
+    blackboard: Blackboard<TaskQueue, Worker, Store>,
+    // When the harness was created, on the virtual clock
+    start: Instant,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+
+    pub fn new() -> Self {
+        let runtime = Builder::new_current_thread().enable_time().start_paused(true).build()
+            .expect("A single threaded runtime should be created");
+        // The worker of the blackboard is spawned on the runtime
+        let (blackboard, start) = runtime.block_on(async { (create_blackboard(), Instant::now()) });
+        Self { runtime, blackboard, start }
+    }
+
+    /// @returns - The blackboard, run by the harness
+    pub fn blackboard(&self) -> &Blackboard<TaskQueue, Worker, Store> {
+        &self.blackboard
+    }
+
+    /// @summary - Run a future until it completes, with the worker and the tasks it spawns
+    pub fn run<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// @summary - Start a task, e.g. an agent waiting for a token, run whenever the harness runs
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where F: Future + Send + 'static, F::Output: Send + 'static {
+        self.runtime.spawn(future)
+    }
+
+    /// @summary - Move the clock forward, firing the timers due meanwhile
+    pub fn advance(&self, duration: Duration) {
+        self.runtime.block_on(tokio::time::advance(duration));
+    }
+
+    /// @summary - Let the worker apply every event queued
+    pub fn settle(&self) {
+        self.runtime.block_on(async {
+            while self.blackboard.health().queue_depth > 0 {
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+
+    /// @returns - The time elapsed on the clock of the harness since its creation
+    pub fn elapsed(&self) -> Duration {
+        self.runtime.block_on(async { self.start.elapsed() })
+    }
+}
+
+#[cfg(feature = "language")]
+impl Harness {
+
+    /// @summary - Parse an agent and run it on the blackboard until it terminates or gets stuck
+    ///
+    /// @param seed - Picks the branches of the choices, the same seed picking the same ones
+    ///
+    /// @returns - true if the agent terminated, false if it got stuck
+    pub fn exec(&self, agent: &str, seed: u64) -> Result<bool, Error> {
+        let agent = parse(agent)?;
+        self.run(async {
+            let simulator = Simulator::new_with(LocalBlackboardInterface::new_with(self.blackboard.clone()));
+            simulator.seed(seed);
+            simulator.bacht_exec_all(agent).await
+        })
+    }
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{sleep, timeout};
+
+    #[test]
+    fn harness_should_run_the_blackboard_on_a_virtual_clock() {
+        let harness = Harness::new();
+        let blackboard = harness.blackboard().clone();
+        // A get blocked until the tell, an hour later
+        let waiting = harness.spawn(async move {
+            while !blackboard.get("a".into()).await.unwrap() {
+                sleep(Duration::from_secs(1)).await;
+            }
+            Instant::now()
+        });
+        harness.advance(Duration::from_secs(3600));
+        assert!(harness.run(harness.blackboard().tell("a".into())).unwrap());
+        let got_at = harness.run(async { timeout(Duration::from_secs(2), waiting).await }).unwrap().unwrap();
+        assert_eq!(got_at.duration_since(harness.start).as_secs(), 3601);
+        harness.settle();
+        assert_eq!(harness.blackboard().health().store_size, 0);
+        #[cfg(feature = "language")]
+        assert!(harness.exec("tell(b);(get(b)+ask(c))", 4).unwrap());
+    }
+}
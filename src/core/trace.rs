@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+use opentelemetry::{Context, KeyValue, global};
+use opentelemetry::global::BoxedSpan;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{Span, TraceContextExt, Tracer};
+use opentelemetry::trace::noop::NoopTracerProvider;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+
+pub use opentelemetry::trace::SpanContext;
+
+// The instrumentation scope of the spans of the library
+const TRACER: &str = "bacht";
+
+// The fields of the W3C trace context
+const TRACEPARENT: &str = "traceparent";
+const TRACESTATE: &str = "tracestate";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// The provider is global, the tests installing one run one at a time
+#[cfg(test)]
+pub(crate) static EXPORTER_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// @summary - Record the spans with a tracer provider, installed as the global provider of OpenTelemetry, e.g. the
+/// one of an OtlpExporter; or stop recording them with None
+///
+/// @note - The provider is global to the process, as the logger of the messages
+pub fn set_tracer_provider(provider: Option<SdkTracerProvider>) {
+    ENABLED.store(provider.is_some(), Ordering::Relaxed);
+    match provider {
+        Some(provider) => global::set_tracer_provider(provider),
+        None => global::set_tracer_provider(NoopTracerProvider::new()),
+    }
+}
+
+/// @returns - true if the spans are recorded, the requests without a trace context then starting a trace
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// @returns - The traceparent and tracestate of a context, written by the W3C trace context propagator, e.g.
+/// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01` as its traceparent
+pub fn inject(context: &SpanContext) -> (String, String) {
+    let mut fields = HashMap::new();
+    TraceContextPropagator::new().inject_context(&Context::new().with_remote_span_context(context.clone()), &mut fields);
+    (fields.remove(TRACEPARENT).unwrap_or_default(), fields.remove(TRACESTATE).unwrap_or_default())
+}
+
+/// @returns - The context of a traceparent and a tracestate, read by the W3C trace context propagator, or None if the
+/// traceparent is not a valid one
+pub fn extract(traceparent: &str, tracestate: &str) -> Option<SpanContext> {
+    let fields = HashMap::from([(TRACEPARENT.to_string(), traceparent.to_string()), (TRACESTATE.to_string(), tracestate.to_string())]);
+    let context = TraceContextPropagator::new().extract(&fields);
+    let span = context.span();
+    span.span_context().is_valid().then(|| span.span_context().clone())
+}
+
+/// @summary - An operation of a trace in progress, exported by the global tracer provider once ended
+pub struct ActiveSpan {
+    span: BoxedSpan,
+}
+
+impl ActiveSpan {
+
+    /// @summary - Start an operation caused by the one of the context given, or the root of a new trace with None
+    pub fn start(name: &'static str, parent: Option<&SpanContext>) -> Self {
+        Self::start_at(name, parent, SystemTime::now())
+    }
+
+    /// @summary - Start an operation that began earlier, e.g. the wait of an event in the queue
+    pub fn start_at(name: &'static str, parent: Option<&SpanContext>, start: SystemTime) -> Self {
+        let tracer = global::tracer(TRACER);
+        let parent = match parent {
+            Some(parent) => Context::new().with_remote_span_context(parent.clone()),
+            None => Context::new(),
+        };
+        Self { span: tracer.span_builder(name).with_start_time(start).start_with_context(&tracer, &parent) }
+    }
+
+    /// @returns - The context of the operation, the parent of the ones it causes
+    pub fn context(&self) -> SpanContext {
+        self.span.span_context().clone()
+    }
+
+    pub fn with_attribute(mut self, key: &'static str, value: impl ToString) -> Self {
+        self.span.set_attribute(KeyValue::new(key, value.to_string()));
+        self
+    }
+
+    /// @summary - End the operation now and hand it to the exporter of the provider, if any
+    pub fn end(mut self) {
+        self.span.end();
+    }
+}
+
+impl std::fmt::Debug for ActiveSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActiveSpan").field("context", self.span.span_context()).finish()
+    }
+}
+
+/// @summary - Record the spans of the process in memory, for the tests of the modules tracing their operations
+///
+/// @returns - The spans recorded, forgotten once the provider is replaced
+#[cfg(test)]
+pub(crate) fn record_in_memory() -> opentelemetry_sdk::trace::InMemorySpanExporter {
+    let exporter = opentelemetry_sdk::trace::InMemorySpanExporter::default();
+    set_tracer_provider(Some(SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build()));
+    exporter
+}
+
+/// ===============
+/// |    TESTS    |
+/// ===============
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId, TraceState};
+
+    #[test]
+    fn trace_context_should_be_written_and_read_as_a_traceparent() {
+        let context = SpanContext::new(TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(), TraceFlags::SAMPLED, true, TraceState::from_key_value([("bacht", "1")]).unwrap());
+        let (traceparent, tracestate) = inject(&context);
+        assert_eq!((traceparent.as_str(), tracestate.as_str()), ("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01", "bacht=1"));
+        assert_eq!(extract(&traceparent, &tracestate), Some(context));
+        for invalid in ["01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7", "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+                        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7"] {
+            assert_eq!(extract(invalid, ""), None, "{} should be invalid", invalid);
+        }
+    }
+
+    #[test]
+    fn spans_should_be_exported_with_their_parent_once_ended() {
+        let _provider = EXPORTER_LOCK.blocking_lock();
+        ActiveSpan::start("dropped", None).end();
+        let exporter = record_in_memory();
+        let root = ActiveSpan::start("request", None);
+        let context = root.context();
+        ActiveSpan::start("handle", Some(&context)).with_attribute("bacht.action", "Tell(\"a\")").end();
+        root.end();
+        // The spans of the other tests of the process are left out
+        let spans: Vec<_> = exporter.get_finished_spans().unwrap().into_iter()
+            .filter(|span| span.span_context.trace_id() == context.trace_id()).collect();
+        set_tracer_provider(None);
+        assert_eq!(spans.iter().map(|span| span.name.as_ref()).collect::<Vec<_>>(), ["handle", "request"]);
+        assert_eq!(spans[0].parent_span_id, context.span_id());
+        assert_eq!(spans[1].parent_span_id, SpanId::INVALID);
+        assert_eq!(spans[0].attributes, [KeyValue::new("bacht.action", "Tell(\"a\")")]);
+    }
+}